@@ -34,7 +34,7 @@ fn main() {
         ..
       } => {
         println!("Setting cursor to \"{:?}\"", CURSORS[cursor_idx]);
-        window.set_cursor_icon(CURSORS[cursor_idx]);
+        window.set_cursor_icon(CURSORS[cursor_idx].clone());
         if cursor_idx < CURSORS.len() - 1 {
           cursor_idx += 1;
         } else {
@@ -6,7 +6,7 @@ use tao::{
   event::{DeviceEvent, ElementState, Event, KeyEvent, WindowEvent},
   event_loop::{ControlFlow, EventLoop},
   keyboard::{Key, ModifiersState},
-  window::WindowBuilder,
+  window::{CursorGrabMode, WindowBuilder},
 };
 
 #[allow(clippy::single_match)]
@@ -41,7 +41,16 @@ fn main() {
           match key {
             Key::Escape => *control_flow = ControlFlow::Exit,
             Key::Character(ch) => match ch.to_lowercase().as_str() {
-              "g" => window.set_cursor_grab(!modifiers.shift_key()).unwrap(),
+              "g" | "l" => {
+                let mode = match (ch.to_lowercase().as_str(), modifiers.shift_key()) {
+                  (_, true) => CursorGrabMode::None,
+                  ("g", false) => CursorGrabMode::Confined,
+                  _ => CursorGrabMode::Locked,
+                };
+                if let Err(err) = window.set_cursor_grab(mode) {
+                  println!("error setting the cursor grab to {:?}: {}", mode, err);
+                }
+              }
               "h" => window.set_cursor_visible(modifiers.shift_key()),
               _ => (),
             },
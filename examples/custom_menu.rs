@@ -10,7 +10,7 @@ use tao::{
   event::{Event, WindowEvent},
   event_loop::{ControlFlow, EventLoop},
   keyboard::KeyCode,
-  menu::{AboutMetadata, MenuBar as Menu, MenuItem, MenuItemAttributes, MenuType},
+  menu::{AboutMetadata, MenuBar as Menu, MenuId, MenuItem, MenuItemAttributes, MenuType},
   window::WindowBuilder,
 };
 
@@ -68,6 +68,15 @@ fn main() {
   second_menu.add_native_item(MenuItem::Separator);
   // create custom item `Change menu` children of `second_menu`
   let change_menu = second_menu.add_item(MenuItemAttributes::new("Change menu"));
+  second_menu.add_native_item(MenuItem::Separator);
+  // items of the same radio group are mutually exclusive
+  let theme = MenuId::new("theme");
+  second_menu.add_item(MenuItemAttributes::new("Light theme").with_radio_group(theme));
+  second_menu.add_item(
+    MenuItemAttributes::new("Dark theme")
+      .with_radio_group(theme)
+      .with_selected(true),
+  );
 
   // add all our childs to menu_bar_menu (order is how they'll appear)
   menu_bar_menu.add_submenu("My app", true, first_menu);
@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use tao::{
-  dpi::PhysicalPosition,
+  dpi::{PhysicalPosition, PhysicalSize},
   event::{ElementState, Event, WindowEvent},
   event_loop::{ControlFlow, EventLoop},
   window::WindowBuilder,
@@ -42,7 +42,7 @@ fn main() {
           "Setting ime position to {}, {}",
           cursor_position.x, cursor_position.y
         );
-        window.set_ime_position(cursor_position);
+        window.set_ime_cursor_area(cursor_position, PhysicalSize::new(10, 10));
       }
       Event::WindowEvent {
         event: WindowEvent::CloseRequested,
@@ -48,6 +48,7 @@ use crate::keyboard::{KeyCode, ModifiersState, NativeKeyCode};
 use std::{
   borrow::Borrow,
   collections::hash_map::DefaultHasher,
+  fmt,
   hash::{Hash, Hasher},
   str::FromStr,
 };
@@ -99,6 +100,32 @@ impl Accelerator {
   }
 }
 
+/// Formats the accelerator in the same syntax accepted by [`Accelerator::from_str`],
+/// so it can be persisted and parsed back, e.g. `"Control+Shift+P"`.
+///
+/// Note that the custom id, if any, is not part of the output.
+impl fmt::Display for Accelerator {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (modifier, name) in [
+      (ModifiersState::CONTROL, "Control"),
+      (ModifiersState::ALT, "Alt"),
+      (ModifiersState::SHIFT, "Shift"),
+      (ModifiersState::SUPER, "Super"),
+    ] {
+      if self.mods.contains(modifier) {
+        write!(f, "{}+", name)?;
+      }
+    }
+
+    let key = format!("{:?}", self.key);
+    let key = key
+      .strip_prefix("Key")
+      .or_else(|| key.strip_prefix("Digit"))
+      .unwrap_or(&key);
+    f.write_str(key)
+  }
+}
+
 // Accelerator::from_str is available to be backward
 // compatible with tauri and it also open the option
 // to generate accelerator from string
@@ -337,7 +364,7 @@ fn parse_accelerator(accelerator_string: &str) -> Result<Accelerator, Accelerato
       "CONTROL" | "CTRL" => {
         mods.set(ModifiersState::CONTROL, true);
       }
-      "COMMAND" | "CMD" | "SUPER" => {
+      "COMMAND" | "CMD" | "SUPER" | "META" => {
         mods.set(ModifiersState::SUPER, true);
       }
       "SHIFT" => {
@@ -365,6 +392,13 @@ fn parse_accelerator(accelerator_string: &str) -> Result<Accelerator, Accelerato
     }
   }
 
+  if key == KeyCode::Unidentified(NativeKeyCode::Unidentified) {
+    return Err(AcceleratorParseError(format!(
+      "Missing main key in accelerator string: \"{}\"",
+      accelerator_string
+    )));
+  }
+
   Ok(Accelerator {
     // use the accelerator string as id
     id: Some(AcceleratorId(hash_string_to_u16(accelerator_string))),
@@ -390,6 +424,22 @@ fn hash_accelerator_to_u16(hotkey: Accelerator) -> u16 {
 
 #[test]
 fn test_parse_accelerator() {
+  assert_eq!(
+    parse_accelerator("PlayPause").unwrap(),
+    Accelerator {
+      id: Some(AcceleratorId::new("PlayPause")),
+      mods: ModifiersState::empty(),
+      key: KeyCode::MediaPlayPause,
+    }
+  );
+  assert_eq!(
+    parse_accelerator("ALT+BrowserBack").unwrap(),
+    Accelerator {
+      id: Some(AcceleratorId::new("ALT+BrowserBack")),
+      mods: ModifiersState::ALT,
+      key: KeyCode::BrowserBack,
+    }
+  );
   assert_eq!(
     parse_accelerator("CTRL+X").unwrap(),
     Accelerator {
@@ -480,3 +530,234 @@ fn test_parse_accelerator() {
   let acc = parse_accelerator("CTRL+");
   assert!(acc.is_err());
 }
+
+#[test]
+fn test_accelerator_to_string() {
+  for accelerator in [
+    "Control+Shift+P",
+    "Alt+Super+F5",
+    "Shift+7",
+    "Control+Space",
+    "ArrowUp",
+    "MediaPlayPause",
+    "AudioVolumeUp",
+    "Control+NumpadAdd",
+  ] {
+    assert_eq!(
+      parse_accelerator(accelerator).unwrap().to_string(),
+      accelerator
+    );
+  }
+
+  let accelerator = Accelerator::new(SysMods::CmdShift, KeyCode::KeyK);
+  let parsed = parse_accelerator(&accelerator.to_string()).unwrap();
+  assert_eq!(parsed.mods, accelerator.mods);
+  assert_eq!(parsed.key, accelerator.key);
+
+  let acc = parse_accelerator("CTRL+SHIFT");
+  assert!(acc.is_err());
+}
+
+#[test]
+fn test_accelerator_key_round_trip() {
+  for key in [
+    KeyCode::Backquote,
+    KeyCode::Backslash,
+    KeyCode::BracketLeft,
+    KeyCode::BracketRight,
+    KeyCode::Comma,
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+    KeyCode::Equal,
+    KeyCode::IntlBackslash,
+    KeyCode::IntlRo,
+    KeyCode::IntlYen,
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+    KeyCode::Minus,
+    KeyCode::Period,
+    KeyCode::Quote,
+    KeyCode::Semicolon,
+    KeyCode::Slash,
+    KeyCode::AltLeft,
+    KeyCode::AltRight,
+    KeyCode::Backspace,
+    KeyCode::CapsLock,
+    KeyCode::ContextMenu,
+    KeyCode::ControlLeft,
+    KeyCode::ControlRight,
+    KeyCode::Enter,
+    KeyCode::SuperLeft,
+    KeyCode::SuperRight,
+    KeyCode::ShiftLeft,
+    KeyCode::ShiftRight,
+    KeyCode::Space,
+    KeyCode::Tab,
+    KeyCode::Convert,
+    KeyCode::KanaMode,
+    KeyCode::Lang1,
+    KeyCode::Lang2,
+    KeyCode::Lang3,
+    KeyCode::Lang4,
+    KeyCode::Lang5,
+    KeyCode::NonConvert,
+    KeyCode::Delete,
+    KeyCode::End,
+    KeyCode::Help,
+    KeyCode::Home,
+    KeyCode::Insert,
+    KeyCode::PageDown,
+    KeyCode::PageUp,
+    KeyCode::ArrowDown,
+    KeyCode::ArrowLeft,
+    KeyCode::ArrowRight,
+    KeyCode::ArrowUp,
+    KeyCode::NumLock,
+    KeyCode::Numpad0,
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+    KeyCode::NumpadAdd,
+    KeyCode::NumpadBackspace,
+    KeyCode::NumpadClear,
+    KeyCode::NumpadClearEntry,
+    KeyCode::NumpadComma,
+    KeyCode::NumpadDecimal,
+    KeyCode::NumpadDivide,
+    KeyCode::NumpadEnter,
+    KeyCode::NumpadEqual,
+    KeyCode::NumpadHash,
+    KeyCode::NumpadMemoryAdd,
+    KeyCode::NumpadMemoryClear,
+    KeyCode::NumpadMemoryRecall,
+    KeyCode::NumpadMemoryStore,
+    KeyCode::NumpadMemorySubtract,
+    KeyCode::NumpadMultiply,
+    KeyCode::NumpadParenLeft,
+    KeyCode::NumpadParenRight,
+    KeyCode::NumpadStar,
+    KeyCode::NumpadSubtract,
+    KeyCode::Escape,
+    KeyCode::Fn,
+    KeyCode::FnLock,
+    KeyCode::PrintScreen,
+    KeyCode::ScrollLock,
+    KeyCode::Pause,
+    KeyCode::BrowserBack,
+    KeyCode::BrowserFavorites,
+    KeyCode::BrowserForward,
+    KeyCode::BrowserHome,
+    KeyCode::BrowserRefresh,
+    KeyCode::BrowserSearch,
+    KeyCode::BrowserStop,
+    KeyCode::Eject,
+    KeyCode::LaunchApp1,
+    KeyCode::LaunchApp2,
+    KeyCode::LaunchMail,
+    KeyCode::MediaPlayPause,
+    KeyCode::MediaSelect,
+    KeyCode::MediaStop,
+    KeyCode::MediaTrackNext,
+    KeyCode::MediaTrackPrevious,
+    KeyCode::Power,
+    KeyCode::Sleep,
+    KeyCode::AudioVolumeDown,
+    KeyCode::AudioVolumeMute,
+    KeyCode::AudioVolumeUp,
+    KeyCode::WakeUp,
+    KeyCode::Hyper,
+    KeyCode::Turbo,
+    KeyCode::Abort,
+    KeyCode::Resume,
+    KeyCode::Suspend,
+    KeyCode::Again,
+    KeyCode::Copy,
+    KeyCode::Cut,
+    KeyCode::Find,
+    KeyCode::Open,
+    KeyCode::Paste,
+    KeyCode::Props,
+    KeyCode::Select,
+    KeyCode::Undo,
+    KeyCode::Hiragana,
+    KeyCode::Katakana,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+    KeyCode::F25,
+    KeyCode::F26,
+    KeyCode::F27,
+    KeyCode::F28,
+    KeyCode::F29,
+    KeyCode::F30,
+    KeyCode::F31,
+    KeyCode::F32,
+    KeyCode::F33,
+    KeyCode::F34,
+    KeyCode::F35,
+  ] {
+    let accelerator = Accelerator::new(SysMods::Shift, key);
+    let parsed = parse_accelerator(&accelerator.to_string()).unwrap();
+    assert_eq!(parsed.key, key, "{} didn't round-trip", accelerator);
+    assert_eq!(parsed.mods, accelerator.mods);
+  }
+}
@@ -15,7 +15,12 @@
 //! ```
 //!
 
-use crate::platform_impl::Clipboard as ClipboardPlatform;
+use std::path::PathBuf;
+
+use crate::{
+  icon::{BadIcon, RgbaIcon},
+  platform_impl::Clipboard as ClipboardPlatform,
+};
 
 #[derive(Debug, Clone, Default)]
 /// Object that allows you to access the `Clipboard` instance.
@@ -48,6 +53,100 @@ impl Clipboard {
   pub fn read_text(&self) -> Option<String> {
     self.0.read_text()
   }
+
+  /// Writes the HTML into the clipboard, along with `alt_text` as plain text for the
+  /// applications that cannot paste HTML.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Written as `CF_HTML`, the `HTML Format` clipboard format.
+  /// - **Android / iOS:** Unsupported
+  pub fn write_html(&mut self, html: impl AsRef<str>, alt_text: impl AsRef<str>) {
+    self.0.write_html(html, alt_text);
+  }
+
+  /// The content in the clipboard as HTML.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only the copied fragment is returned, without the surrounding document.
+  /// - **Android / iOS:** Unsupported
+  pub fn read_html(&self) -> Option<String> {
+    self.0.read_html()
+  }
+
+  /// Writes the paths into the clipboard as a list of files, which the file manager can paste.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Written as `CF_HDROP`.
+  /// - **macOS:** Written as file URLs.
+  /// - **Linux:** Written as `text/uri-list`, and `x-special/gnome-copied-files` for the GNOME
+  ///   file managers.
+  /// - **Android / iOS:** Unsupported
+  pub fn write_files(&mut self, paths: Vec<PathBuf>) {
+    self.0.write_files(paths);
+  }
+
+  /// The files in the clipboard, e.g. after copying them in the file manager.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported
+  pub fn read_files(&self) -> Option<Vec<PathBuf>> {
+    self.0.read_files()
+  }
+
+  /// Writes the image into the clipboard.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Written as `CF_DIB`.
+  /// - **macOS:** Written as both PNG and TIFF.
+  /// - **Android / iOS:** Unsupported
+  pub fn write_image(&mut self, image: &RgbaImage) {
+    self.0.write_image(image);
+  }
+
+  /// The image in the clipboard, converted to RGBA.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported
+  pub fn read_image(&self) -> Option<RgbaImage> {
+    self.0.read_image()
+  }
+}
+
+/// An image read from or written into the clipboard, as 32bpp RGBA pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RgbaImage(pub(crate) RgbaIcon);
+
+impl RgbaImage {
+  /// Creates an image from 32bpp RGBA data, with rows going from top to bottom.
+  ///
+  /// The length of `rgba` must be divisible by 4, and `width * height` must equal
+  /// `rgba.len() / 4`. Otherwise, this will return a `BadIcon` error.
+  pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
+    RgbaIcon::from_rgba(rgba, width, height).map(RgbaImage)
+  }
+
+  pub fn width(&self) -> u32 {
+    self.0.width
+  }
+
+  pub fn height(&self) -> u32 {
+    self.0.height
+  }
+
+  /// The RGBA pixels of the image.
+  pub fn rgba(&self) -> &[u8] {
+    &self.0.rgba
+  }
+
+  pub fn into_rgba(self) -> Vec<u8> {
+    self.0.rgba
+  }
 }
 
 /// Identifier of a clipboard format.
@@ -75,6 +174,17 @@ impl ClipboardFormat {
     target_os = "openbsd"
   ))]
   pub const TEXT: &'static str = "UTF8_STRING";
+
+  #[cfg(target_os = "windows")]
+  pub const HTML: &'static str = "HTML Format";
+  #[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+  ))]
+  pub const HTML: &'static str = "text/html";
 }
 
 impl ClipboardFormat {
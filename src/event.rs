@@ -39,14 +39,16 @@
 //! [event_loop_run]: crate::event_loop::EventLoop::run
 use instant::Instant;
 use std::path::PathBuf;
+use url::Url;
 
 use crate::{
   accelerator::AcceleratorId,
   dpi::{PhysicalPosition, PhysicalSize},
   keyboard::{self, ModifiersState},
   menu::{MenuId, MenuType},
+  monitor::MonitorHandle,
   platform_impl,
-  window::{Theme, WindowId},
+  window::{Theme, WindowId, RGBA},
 };
 
 /// Describes a generic event.
@@ -87,6 +89,14 @@ pub enum Event<'a, T: 'static> {
     window_id: Option<WindowId>,
     menu_id: MenuId,
     origin: MenuType,
+    /// The id of the tray whose menu has been clicked, if the origin is a tray menu.
+    ///
+    /// ## Platform-specific
+    ///
+    /// - **macOS:** The tray is found through its menu, so trays sharing a
+    ///   [`ContextMenu`](crate::menu::ContextMenu) report the last tray it was set on.
+    /// - **iOS / Android:** Unsupported.
+    tray_id: Option<crate::TrayId>,
   },
 
   /// Emitted when tray has been clicked.
@@ -109,6 +119,158 @@ pub enum Event<'a, T: 'static> {
   /// - **iOS / Android:** Unsupported.
   GlobalShortcutEvent(AcceleratorId),
 
+  /// Emitted when the user session is locked, or the screen saver is activated.
+  ///
+  /// This is emitted once per transition, even if several OS notifications report it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only the session lock is reported, not the screen saver.
+  /// - **Linux:** Watches the `LockedHint` of the `org.freedesktop.login1` session, set by the
+  ///   screen locker, and the `ScreenSaver` D-Bus signals.
+  /// - **iOS / Android:** Unsupported.
+  SessionLock,
+
+  /// Emitted when the user session is unlocked, or the screen saver is dismissed.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  SessionUnlock,
+
+  /// Emitted when the system is about to sleep.
+  ///
+  /// Not to be confused with [`Event::Suspended`], which is about the application.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Listens to the `PrepareForSleep` signal of `org.freedesktop.login1`.
+  /// - **iOS / Android:** Unsupported.
+  SystemSuspend,
+
+  /// Emitted when the system woke up from sleep.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  SystemResume,
+
+  /// Emitted when the content of the clipboard changed, once enabled with
+  /// [`EventLoopWindowTarget::listen_clipboard`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`EventLoopWindowTarget::listen_clipboard`]: crate::event_loop::EventLoopWindowTarget::listen_clipboard
+  ClipboardUpdated,
+
+  /// Emitted when a monitor was connected, or enabled.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  MonitorConnected(MonitorHandle),
+
+  /// Emitted when a monitor was disconnected, or disabled.
+  ///
+  /// The handle can still be compared with the ones previously returned, but querying it may
+  /// return default values since the monitor doesn't exist anymore.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  MonitorDisconnected(MonitorHandle),
+
+  /// Emitted when the scale factor of a monitor changed.
+  ///
+  /// Windows on that monitor also receive [`WindowEvent::ScaleFactorChanged`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  MonitorScaleFactorChanged {
+    monitor: MonitorHandle,
+    scale_factor: f64,
+  },
+
+  /// Emitted when the user switched to another keyboard layout, see
+  /// [`EventLoopWindowTarget::keyboard_layout`].
+  ///
+  /// The following [`KeyEvent::logical_key`] values are translated with the new layout.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only emitted while one of the windows is focused, since the layout is set per
+  ///   application.
+  /// - **Linux:** Only the switches between the groups of the XKB keymap are reported, on X11.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`EventLoopWindowTarget::keyboard_layout`]: crate::event_loop::EventLoopWindowTarget::keyboard_layout
+  KeyboardLayoutChanged,
+
+  /// Emitted when the user chose another accent color, see
+  /// [`EventLoopWindowTarget::accent_color`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`EventLoopWindowTarget::accent_color`]: crate::event_loop::EventLoopWindowTarget::accent_color
+  AccentColorChanged(RGBA),
+
+  /// Emitted when the soft keyboard has been shown or hidden, with its height in physical pixels
+  /// to move the content above it, `0` when it is hidden.
+  ///
+  /// See [`WindowExtAndroid::show_ime`](crate::platform::android::WindowExtAndroid::show_ime).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** The activity has to report the height of the soft keyboard by calling the
+  ///   `ime(height: Int)` native method that `android_binding!` binds, from its window insets
+  ///   listener.
+  /// - **Windows / macOS / Linux / iOS:** Unsupported.
+  ImeVisibilityChanged { visible: bool, keyboard_height: u32 },
+
+  /// Emitted when the user pressed the back button, or made the back gesture.
+  ///
+  /// The activity is finished after the event, unless `prevent_default` is set to `true`, for
+  /// example to go back in the navigation of the application instead.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android:** The activity has to call the `back()` native method that `android_binding!`
+  ///   binds from `onBackPressed`, instead of the default implementation.
+  /// - **Windows / macOS / Linux / iOS:** Unsupported.
+  BackRequested { prevent_default: &'a mut bool },
+
+  /// Emitted when another instance of the application has been started, with its command-line
+  /// arguments and its current directory, after this instance became the single instance with
+  /// [`EventLoopWindowTarget::enable_single_instance`]. The other instance exits right away.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`EventLoopWindowTarget::enable_single_instance`]: crate::event_loop::EventLoopWindowTarget::enable_single_instance
+  NewInstance { args: Vec<String>, cwd: PathBuf },
+
+  /// Emitted when the system asks the application to open files or URLs, such as the URLs of a
+  /// custom scheme for deep linking.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Reported from `application:openURLs:`, at launch or while running. The URL
+  ///   schemes and document types have to be declared in the `Info.plist` of the bundle.
+  /// - **Windows / Linux:** The shell passes them as command-line arguments. Only when enabled
+  ///   with `EventLoopBuilderExtWindows::with_opened_urls_from_args` or
+  ///   `EventLoopBuilderExtUnix::with_opened_urls_from_args`, the arguments of the process that
+  ///   are URLs with a scheme or paths of existing files are reported once, after
+  ///   [`StartCause::Init`]. Later activations start another process, see
+  ///   [`Event::NewInstance`].
+  /// - **iOS / Android:** Unsupported.
+  Opened { urls: Vec<Url> },
+
   /// Emitted when the application has been suspended.
   Suspended,
 
@@ -190,10 +352,12 @@ impl<T: Clone> Clone for Event<'static, T> {
         window_id,
         menu_id,
         origin,
+        tray_id,
       } => MenuEvent {
         window_id: *window_id,
         menu_id: *menu_id,
         origin: *origin,
+        tray_id: *tray_id,
       },
       TrayEvent {
         id,
@@ -207,15 +371,48 @@ impl<T: Clone> Clone for Event<'static, T> {
         position: *position,
       },
       GlobalShortcutEvent(accelerator_id) => GlobalShortcutEvent(*accelerator_id),
+      SessionLock => SessionLock,
+      SessionUnlock => SessionUnlock,
+      SystemSuspend => SystemSuspend,
+      SystemResume => SystemResume,
+      ClipboardUpdated => ClipboardUpdated,
+      KeyboardLayoutChanged => KeyboardLayoutChanged,
+      AccentColorChanged(color) => AccentColorChanged(*color),
+      ImeVisibilityChanged {
+        visible,
+        keyboard_height,
+      } => ImeVisibilityChanged {
+        visible: *visible,
+        keyboard_height: *keyboard_height,
+      },
+      BackRequested { .. } => {
+        unreachable!("Static event can't be about the back button")
+      }
+      NewInstance { args, cwd } => NewInstance {
+        args: args.clone(),
+        cwd: cwd.clone(),
+      },
+      Opened { urls } => Opened { urls: urls.clone() },
+      MonitorConnected(monitor) => MonitorConnected(monitor.clone()),
+      MonitorDisconnected(monitor) => MonitorDisconnected(monitor.clone()),
+      MonitorScaleFactorChanged {
+        monitor,
+        scale_factor,
+      } => MonitorScaleFactorChanged {
+        monitor: monitor.clone(),
+        scale_factor: *scale_factor,
+      },
     }
   }
 }
 
 impl<'a, T> Event<'a, T> {
-  pub fn map_nonuser_event<U>(self) -> Result<Event<'a, U>, Event<'a, T>> {
+  /// Converts an event that isn't a [`Event::UserEvent`] to any user event type, or returns the
+  /// user event, boxed since events are large.
+  pub fn map_nonuser_event<U>(self) -> Result<Event<'a, U>, Box<Event<'a, T>>> {
     use self::Event::*;
     match self {
-      UserEvent(_) => Err(self),
+      UserEvent(_) => Err(Box::new(self)),
       WindowEvent { window_id, event } => Ok(WindowEvent { window_id, event }),
       DeviceEvent { device_id, event } => Ok(DeviceEvent { device_id, event }),
       NewEvents(cause) => Ok(NewEvents(cause)),
@@ -229,10 +426,12 @@ impl<'a, T> Event<'a, T> {
         window_id,
         menu_id,
         origin,
+        tray_id,
       } => Ok(MenuEvent {
         window_id,
         menu_id,
         origin,
+        tray_id,
       }),
       TrayEvent {
         id,
@@ -246,6 +445,32 @@ impl<'a, T> Event<'a, T> {
         position,
       }),
       GlobalShortcutEvent(accelerator_id) => Ok(GlobalShortcutEvent(accelerator_id)),
+      SessionLock => Ok(SessionLock),
+      SessionUnlock => Ok(SessionUnlock),
+      SystemSuspend => Ok(SystemSuspend),
+      SystemResume => Ok(SystemResume),
+      ClipboardUpdated => Ok(ClipboardUpdated),
+      KeyboardLayoutChanged => Ok(KeyboardLayoutChanged),
+      AccentColorChanged(color) => Ok(AccentColorChanged(color)),
+      ImeVisibilityChanged {
+        visible,
+        keyboard_height,
+      } => Ok(ImeVisibilityChanged {
+        visible,
+        keyboard_height,
+      }),
+      BackRequested { prevent_default } => Ok(BackRequested { prevent_default }),
+      NewInstance { args, cwd } => Ok(NewInstance { args, cwd }),
+      Opened { urls } => Ok(Opened { urls }),
+      MonitorConnected(monitor) => Ok(MonitorConnected(monitor)),
+      MonitorDisconnected(monitor) => Ok(MonitorDisconnected(monitor)),
+      MonitorScaleFactorChanged {
+        monitor,
+        scale_factor,
+      } => Ok(MonitorScaleFactorChanged {
+        monitor,
+        scale_factor,
+      }),
     }
   }
 
@@ -270,10 +495,12 @@ impl<'a, T> Event<'a, T> {
         window_id,
         menu_id,
         origin,
+        tray_id,
       } => Some(MenuEvent {
         window_id,
         menu_id,
         origin,
+        tray_id,
       }),
       TrayEvent {
         id,
@@ -287,6 +514,32 @@ impl<'a, T> Event<'a, T> {
         position,
       }),
       GlobalShortcutEvent(accelerator_id) => Some(GlobalShortcutEvent(accelerator_id)),
+      SessionLock => Some(SessionLock),
+      SessionUnlock => Some(SessionUnlock),
+      SystemSuspend => Some(SystemSuspend),
+      SystemResume => Some(SystemResume),
+      ClipboardUpdated => Some(ClipboardUpdated),
+      KeyboardLayoutChanged => Some(KeyboardLayoutChanged),
+      AccentColorChanged(color) => Some(AccentColorChanged(color)),
+      ImeVisibilityChanged {
+        visible,
+        keyboard_height,
+      } => Some(ImeVisibilityChanged {
+        visible,
+        keyboard_height,
+      }),
+      BackRequested { .. } => None,
+      NewInstance { args, cwd } => Some(NewInstance { args, cwd }),
+      Opened { urls } => Some(Opened { urls }),
+      MonitorConnected(monitor) => Some(MonitorConnected(monitor)),
+      MonitorDisconnected(monitor) => Some(MonitorDisconnected(monitor)),
+      MonitorScaleFactorChanged {
+        monitor,
+        scale_factor,
+      } => Some(MonitorScaleFactorChanged {
+        monitor,
+        scale_factor,
+      }),
     }
   }
 }
@@ -349,28 +602,86 @@ pub enum WindowEvent<'a> {
   ///
   /// When the user drops multiple files at once, this event will be emitted for each file
   /// separately.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Unsupported, see [`WindowEvent::DragDrop`].
+  /// - **iOS / Android:** Unsupported.
   DroppedFile(PathBuf),
 
   /// A file is being hovered over the window.
   ///
   /// When the user hovers multiple files at once, this event will be emitted for each file
   /// separately.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Unsupported, see [`WindowEvent::DragDrop`].
+  /// - **iOS / Android:** Unsupported.
   HoveredFile(PathBuf),
 
   /// A file was hovered, but has exited the window.
   ///
   /// There will be a single `HoveredFileCancelled` event triggered even if multiple files were
   /// hovered.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Unsupported, see [`WindowEvent::DragDrop`].
+  /// - **iOS / Android:** Unsupported.
   HoveredFileCancelled,
 
+  /// An item is dragged over the window, or dropped on it.
+  ///
+  /// Unlike [`WindowEvent::HoveredFile`] and [`WindowEvent::DroppedFile`], which are still
+  /// emitted for files on Windows and macOS, it carries the position of the cursor and the text,
+  /// URIs and HTML of the item as well.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** This is the only event reporting drags and drops, files included. Areas of the
+  ///   window covered by widgets accepting drops themselves, such as web views, don't report it,
+  ///   and neither do windows created with `with_drag_and_drop(false)`.
+  /// - **iOS / Android:** Unsupported.
+  DragDrop(DragDropEvent),
+
   /// The window received a unicode character.
+  ///
+  /// Text committed by an input method is also reported as [`Ime::Commit`].
   ReceivedImeText(String),
 
+  /// An event from the input method.
+  ///
+  /// See [`Ime`] for the order in which they are emitted, and
+  /// [`Window::set_ime_allowed`](crate::window::Window::set_ime_allowed) to turn the input
+  /// method off.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  Ime(Ime),
+
   /// The window gained or lost focus.
   ///
   /// The parameter is true if the window has gained focus, and false if it has lost focus.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Reports whether the window is the key window. See also
+  ///   [`WindowEvent::MainWindowChanged`].
   Focused(bool),
 
+  /// The window became or stopped being the main window, i.e. the document window the app's
+  /// commands apply to. A tool window or panel can have the keyboard focus while another
+  /// window stays the main window.
+  ///
+  /// The parameter is true if the window became the main window.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux / iOS / Android:** Unsupported, the focused window is the main window.
+  MainWindowChanged(bool),
+
   /// An event from the keyboard has been received.
   ///
   /// ## Platform-specific
@@ -385,10 +696,13 @@ pub enum WindowEvent<'a> {
     /// If `true`, the event was generated synthetically by tao
     /// in one of the following circumstances:
     ///
+    /// * Synthetic key release events are generated for all keys pressed
+    ///   when a window goes out of focus, before `Focused(false)`, so that
+    ///   no key stays pressed. ***Currently, this is only functional on
+    ///   Linux, macOS and Windows***
     /// * Synthetic key press events are generated for all keys pressed
-    ///   when a window gains focus. Likewise, synthetic key release events
-    ///   are generated for all keys pressed when a window goes out of focus.
-    ///   ***Currently, this is only functional on Linux and Windows***
+    ///   when a window gains focus. ***Currently, this is only functional
+    ///   on Windows***
     ///
     /// Otherwise, this value is always `false`.
     is_synthetic: bool,
@@ -405,6 +719,8 @@ pub enum WindowEvent<'a> {
     /// limited by the display area and it may have been transformed by the OS to implement effects such as cursor
     /// acceleration, it should not be used to implement non-cursor-like interactions such as 3D camera control.
     position: PhysicalPosition<f64>,
+    /// When the OS registered the motion, see [`KeyEvent::timestamp`].
+    timestamp: Instant,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
   },
@@ -416,10 +732,31 @@ pub enum WindowEvent<'a> {
   CursorLeft { device_id: DeviceId },
 
   /// A mouse wheel movement or touchpad scroll occurred.
+  ///
+  /// On Windows, macOS and Linux, a touchpad gesture is reported from a `Started` to an `Ended`
+  /// [`TouchPhase`], with the deltas of each event. On Windows and macOS, when the system keeps
+  /// scrolling with momentum after the fingers are lifted, more events follow with `inertial`
+  /// set, also ending with an `Ended` phase, so apps that implement kinetic scrolling themselves
+  /// can tell them apart or stop them.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Precision touchpads report [`MouseScrollDelta::PixelDelta`]s with phases,
+  ///   through Direct Manipulation. Wheels and other touchpads report fractional
+  ///   [`MouseScrollDelta::LineDelta`]s with a `Moved` phase.
+  /// - **Linux:** Touchpads report smooth line deltas, from a `Started` phase on the first event
+  ///   to an `Ended` phase when the fingers are lifted. Wheels report a `Moved` phase with smooth
+  ///   scrolling and an `Ended` phase otherwise. GTK doesn't generate momentum events, so
+  ///   `inertial` is always `false`.
+  /// - **iOS / Android:** Unsupported.
   MouseWheel {
     device_id: DeviceId,
     delta: MouseScrollDelta,
     phase: TouchPhase,
+    /// Whether the event is part of the momentum scrolling that follows a touchpad gesture.
+    inertial: bool,
+    /// When the OS registered the scroll, see [`KeyEvent::timestamp`].
+    timestamp: Instant,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
   },
@@ -429,10 +766,50 @@ pub enum WindowEvent<'a> {
     device_id: DeviceId,
     state: ElementState,
     button: MouseButton,
+    /// When the OS registered the press or release, see [`KeyEvent::timestamp`].
+    timestamp: Instant,
     #[deprecated = "Deprecated in favor of WindowEvent::ModifiersChanged"]
     modifiers: ModifiersState,
   },
 
+  /// Two-finger pinch gesture on a touchpad, usually used for magnification.
+  ///
+  /// `delta` is the change of the magnification, positive when zooming in. A scale of `1.0`
+  /// becomes `1.0 + delta`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only precision touchpads are supported, through Direct Manipulation. Other
+  ///   touchpads report pinches as `Ctrl` + mouse wheel.
+  /// - **iOS / Android:** Unsupported.
+  TouchpadMagnify {
+    device_id: DeviceId,
+    delta: f64,
+    phase: TouchPhase,
+  },
+
+  /// Smart magnification gesture, a two-finger double tap on a touchpad, usually used to zoom on
+  /// the content under the cursor or back out.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux / iOS / Android:** Unsupported.
+  SmartMagnify { device_id: DeviceId },
+
+  /// Two-finger rotation gesture on a touchpad.
+  ///
+  /// `delta` is the change of the rotation in degrees, positive when rotating counterclockwise.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Unsupported, Direct Manipulation doesn't report rotations.
+  /// - **iOS / Android:** Unsupported.
+  TouchpadRotate {
+    device_id: DeviceId,
+    delta: f32,
+    phase: TouchPhase,
+  },
+
   /// Touchpad pressure event.
   ///
   /// At the moment, only supported on Apple forcetouch-capable macbooks.
@@ -454,6 +831,15 @@ pub enum WindowEvent<'a> {
   /// Touch event has been received
   Touch(Touch),
 
+  /// A pen event has been received, from a pen tablet or a pen-enabled screen.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Pens are only reported with this event, and not as [`WindowEvent::Touch`].
+  /// - **macOS / Linux:** Pens also move the cursor and press the mouse buttons.
+  /// - **iOS / Android:** Unsupported, pens are reported as [`WindowEvent::Touch`].
+  PenInput(PenInput),
+
   /// The window's scale factor has changed.
   ///
   /// The following user actions can cause DPI changes:
@@ -467,6 +853,11 @@ pub enum WindowEvent<'a> {
   /// by the OS, but it can be changed to any value.
   ///
   /// For more information about DPI in general, see the [`dpi`](crate::dpi) module.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** GTK only has integer scale factors, so the text scaling factor of the desktop
+  ///   isn't included.
   ScaleFactorChanged {
     scale_factor: f64,
     new_inner_size: &'a mut PhysicalSize<u32>,
@@ -479,7 +870,8 @@ pub enum WindowEvent<'a> {
   ///
   /// ## Platform-specific
   ///
-  /// - **Linux / Android / iOS:** Unsupported
+  /// - **Linux:** Follows the `color-scheme` setting of the desktop portal, or the GTK settings.
+  /// - **Android / iOS:** Unsupported
   ThemeChanged(Theme),
 
   /// The window decorations has been clicked.
@@ -488,6 +880,92 @@ pub enum WindowEvent<'a> {
   ///
   /// - **Linux / macOS / Android / iOS:** Unsupported
   DecorationsClick,
+
+  /// The window has been hidden instead of minimized, because minimizing to the tray is enabled.
+  ///
+  /// See [`Window::set_minimize_to_tray`](crate::window::Window::set_minimize_to_tray).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  MinimizedToTray,
+
+  /// The window has been minimized, or restored from being minimized.
+  ///
+  /// A maximized window that is minimized stays maximized, so no `Maximized(false)` is sent.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  Minimized(bool),
+
+  /// The window has been maximized, or restored from being maximized.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Reported when the window is zoomed. Windows without a resizable title bar are
+  ///   considered maximized when they cover the visible frame of their screen.
+  /// - **iOS / Android:** Unsupported.
+  Maximized(bool),
+
+  /// The window has been occluded (completely hidden from view), or is visible again.
+  ///
+  /// Applications can stop drawing while the window is occluded, to save power.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only reported for minimized windows and windows cloaked by the desktop
+  ///   window manager, for example on another virtual desktop, not for windows covered by others.
+  /// - **Linux:** Windows covered by others are only reported on X11 without a compositor.
+  /// - **iOS / Android:** Unsupported.
+  Occluded(bool),
+
+  /// A button of the header bar of the window has been clicked, with the id it was given.
+  ///
+  /// See [`WindowBuilderExtUnix::with_header_bar`](crate::platform::unix::WindowBuilderExtUnix::with_header_bar).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / iOS / Android:** Unsupported.
+  HeaderBarButtonClicked(u32),
+
+  /// The safe area of the window changed, for example when the device was rotated.
+  ///
+  /// See [`WindowExtIOS::safe_area_insets`](crate::platform::ios::WindowExtIOS::safe_area_insets).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS / Linux / Android:** Unsupported.
+  SafeAreaInsetsChanged,
+
+  /// The window has been merged with other windows as tabs, or separated from them.
+  ///
+  /// See [`WindowExtMacOS::tabbed_windows`](crate::platform::macos::WindowExtMacOS::tabbed_windows).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux / iOS / Android:** Unsupported.
+  TabGroupChanged,
+
+  /// A button of the thumbnail toolbar of the window has been clicked, with the id it was given.
+  ///
+  /// See [`WindowExtWindows::set_thumbbar_buttons`](crate::platform::windows::WindowExtWindows::set_thumbbar_buttons).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux / iOS / Android:** Unsupported.
+  ThumbBarButtonClicked(u32),
+
+  /// An assistive technology, such as a screen reader, requested an action on a node of the
+  /// accessibility tree of the window.
+  ///
+  /// See [`Window::set_accessibility_source`](crate::window::Window::set_accessibility_source).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[cfg(feature = "accesskit")]
+  AccessibilityActionRequested(crate::accesskit::ActionRequest),
 }
 
 impl Clone for WindowEvent<'static> {
@@ -501,8 +979,11 @@ impl Clone for WindowEvent<'static> {
       DroppedFile(file) => DroppedFile(file.clone()),
       HoveredFile(file) => HoveredFile(file.clone()),
       HoveredFileCancelled => HoveredFileCancelled,
+      DragDrop(event) => DragDrop(event.clone()),
       ReceivedImeText(c) => ReceivedImeText(c.clone()),
+      Ime(ime) => Ime(ime.clone()),
       Focused(f) => Focused(*f),
+      MainWindowChanged(main) => MainWindowChanged(*main),
       KeyboardInput {
         device_id,
         event,
@@ -518,10 +999,12 @@ impl Clone for WindowEvent<'static> {
       CursorMoved {
         device_id,
         position,
+        timestamp,
         modifiers,
       } => CursorMoved {
         device_id: *device_id,
         position: *position,
+        timestamp: *timestamp,
         modifiers: *modifiers,
       },
       CursorEntered { device_id } => CursorEntered {
@@ -535,11 +1018,15 @@ impl Clone for WindowEvent<'static> {
         device_id,
         delta,
         phase,
+        inertial,
+        timestamp,
         modifiers,
       } => MouseWheel {
         device_id: *device_id,
         delta: *delta,
         phase: *phase,
+        inertial: *inertial,
+        timestamp: *timestamp,
         modifiers: *modifiers,
       },
       #[allow(deprecated)]
@@ -547,13 +1034,36 @@ impl Clone for WindowEvent<'static> {
         device_id,
         state,
         button,
+        timestamp,
         modifiers,
       } => MouseInput {
         device_id: *device_id,
         state: *state,
         button: *button,
+        timestamp: *timestamp,
         modifiers: *modifiers,
       },
+      TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      } => TouchpadMagnify {
+        device_id: *device_id,
+        delta: *delta,
+        phase: *phase,
+      },
+      SmartMagnify { device_id } => SmartMagnify {
+        device_id: *device_id,
+      },
+      TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      } => TouchpadRotate {
+        device_id: *device_id,
+        delta: *delta,
+        phase: *phase,
+      },
       TouchpadPressure {
         device_id,
         pressure,
@@ -573,11 +1083,22 @@ impl Clone for WindowEvent<'static> {
         value: *value,
       },
       Touch(touch) => Touch(*touch),
+      PenInput(pen) => PenInput(*pen),
       ThemeChanged(theme) => ThemeChanged(*theme),
       ScaleFactorChanged { .. } => {
         unreachable!("Static event can't be about scale factor changing")
       }
       DecorationsClick => DecorationsClick,
+      MinimizedToTray => MinimizedToTray,
+      Minimized(minimized) => Minimized(*minimized),
+      Maximized(maximized) => Maximized(*maximized),
+      Occluded(occluded) => Occluded(*occluded),
+      HeaderBarButtonClicked(id) => HeaderBarButtonClicked(*id),
+      SafeAreaInsetsChanged => SafeAreaInsetsChanged,
+      TabGroupChanged => TabGroupChanged,
+      ThumbBarButtonClicked(id) => ThumbBarButtonClicked(*id),
+      #[cfg(feature = "accesskit")]
+      AccessibilityActionRequested(request) => AccessibilityActionRequested(request.clone()),
     };
   }
 }
@@ -593,8 +1114,11 @@ impl<'a> WindowEvent<'a> {
       DroppedFile(file) => Some(DroppedFile(file)),
       HoveredFile(file) => Some(HoveredFile(file)),
       HoveredFileCancelled => Some(HoveredFileCancelled),
+      DragDrop(event) => Some(DragDrop(event)),
       ReceivedImeText(c) => Some(ReceivedImeText(c)),
+      Ime(ime) => Some(Ime(ime)),
       Focused(focused) => Some(Focused(focused)),
+      MainWindowChanged(main) => Some(MainWindowChanged(main)),
       KeyboardInput {
         device_id,
         event,
@@ -609,10 +1133,12 @@ impl<'a> WindowEvent<'a> {
       CursorMoved {
         device_id,
         position,
+        timestamp,
         modifiers,
       } => Some(CursorMoved {
         device_id,
         position,
+        timestamp,
         modifiers,
       }),
       CursorEntered { device_id } => Some(CursorEntered { device_id }),
@@ -622,11 +1148,15 @@ impl<'a> WindowEvent<'a> {
         device_id,
         delta,
         phase,
+        inertial,
+        timestamp,
         modifiers,
       } => Some(MouseWheel {
         device_id,
         delta,
         phase,
+        inertial,
+        timestamp,
         modifiers,
       }),
       #[allow(deprecated)]
@@ -634,13 +1164,34 @@ impl<'a> WindowEvent<'a> {
         device_id,
         state,
         button,
+        timestamp,
         modifiers,
       } => Some(MouseInput {
         device_id,
         state,
         button,
+        timestamp,
         modifiers,
       }),
+      TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      } => Some(TouchpadMagnify {
+        device_id,
+        delta,
+        phase,
+      }),
+      SmartMagnify { device_id } => Some(SmartMagnify { device_id }),
+      TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      } => Some(TouchpadRotate {
+        device_id,
+        delta,
+        phase,
+      }),
       TouchpadPressure {
         device_id,
         pressure,
@@ -660,9 +1211,20 @@ impl<'a> WindowEvent<'a> {
         value,
       }),
       Touch(touch) => Some(Touch(touch)),
+      PenInput(pen) => Some(PenInput(pen)),
       ThemeChanged(theme) => Some(ThemeChanged(theme)),
       ScaleFactorChanged { .. } => None,
       DecorationsClick => Some(DecorationsClick),
+      MinimizedToTray => Some(MinimizedToTray),
+      Minimized(minimized) => Some(Minimized(minimized)),
+      Maximized(maximized) => Some(Maximized(maximized)),
+      Occluded(occluded) => Some(Occluded(occluded)),
+      HeaderBarButtonClicked(id) => Some(HeaderBarButtonClicked(id)),
+      SafeAreaInsetsChanged => Some(SafeAreaInsetsChanged),
+      TabGroupChanged => Some(TabGroupChanged),
+      ThumbBarButtonClicked(id) => Some(ThumbBarButtonClicked(id)),
+      #[cfg(feature = "accesskit")]
+      AccessibilityActionRequested(request) => Some(AccessibilityActionRequested(request)),
     }
   }
 }
@@ -704,6 +1266,16 @@ pub enum DeviceEvent {
   /// Change in physical position of a pointing device.
   ///
   /// This represents raw, unfiltered physical motion. Not to be confused with `WindowEvent::CursorMoved`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The unaccelerated deltas of Raw Input, in mouse counts.
+  /// - **Linux:** The unaccelerated deltas of the XInput 2 raw events, on X11. Unsupported on
+  ///   Wayland, where the relative pointer protocol isn't exposed by GTK.
+  /// - **macOS:** The deltas of the mouse events, which the pointer acceleration of the system
+  ///   was already applied to. Reading the unaccelerated deltas of the devices through IOKit
+  ///   would require the Input Monitoring permission.
+  /// - **iOS / Android:** Unsupported.
   #[non_exhaustive]
   MouseMotion {
     /// (x, y) change in position in unspecified units.
@@ -806,8 +1378,25 @@ pub struct KeyEvent {
 
   pub location: keyboard::KeyLocation,
   pub state: ElementState,
+
+  /// Whether the press was generated by the key repeat while the key is held down, rather than
+  /// by pressing the key. Always `false` for releases.
   pub repeat: bool,
 
+  /// When the OS registered the event, before it was queued for the app. Comparing it to
+  /// [`Instant::now`] gives the input latency, and comparing the timestamps of consecutive events
+  /// gives the velocity of a gesture, regardless of when the app got to process them.
+  ///
+  /// Synthetic events, such as the releases sent when a window loses the focus, are timestamped
+  /// when they are generated.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux:** The OS timestamps events with millisecond precision.
+  /// - **Linux:** When the clock of the display server isn't the monotonic clock of the system,
+  ///   events are timestamped when they are received instead.
+  pub timestamp: Instant,
+
   pub(crate) platform_specific: platform_impl::KeyEventExtra,
 }
 
@@ -848,6 +1437,79 @@ impl KeyEvent {
   }
 }
 
+/// Describes an event from the input method.
+///
+/// A composition is reported as [`Ime::Enabled`], then [`Ime::Preedit`] every time the text being
+/// composed changes, and ends with an empty [`Ime::Preedit`] followed by [`Ime::Commit`] if the
+/// text was accepted, then [`Ime::Disabled`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Ime {
+  /// The input method started composing text.
+  Enabled,
+
+  /// The text being composed, which should be drawn in place at the text cursor, without being
+  /// inserted in the document yet.
+  ///
+  /// The range is the byte range of the text cursor, or of the selection, within the text.
+  /// `None` means the cursor should be hidden. An empty text clears the previous one.
+  Preedit(String, Option<(usize, usize)>),
+
+  /// The text to insert in the document, replacing the preedit text.
+  Commit(String),
+
+  /// The input method stopped composing text.
+  Disabled,
+}
+
+/// Describes a drag and drop over a window, see [`WindowEvent::DragDrop`].
+///
+/// The position is the one of the cursor, relative to the top-left corner of the client area of
+/// the window.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DragDropEvent {
+  /// The item entered the window.
+  Entered {
+    data: DropData,
+    position: PhysicalPosition<f64>,
+  },
+
+  /// The item moved over the window.
+  Moved { position: PhysicalPosition<f64> },
+
+  /// The item was dropped on the window.
+  Dropped {
+    data: DropData,
+    position: PhysicalPosition<f64>,
+  },
+
+  /// The item left the window without being dropped.
+  Left,
+}
+
+/// The content of an item dragged over a window, which may be offered in several formats.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DropData {
+  /// The files and directories.
+  pub paths: Vec<PathBuf>,
+  /// The URIs other than the ones of the files, e.g. links dragged from a web browser.
+  pub uris: Vec<String>,
+  pub text: Option<String>,
+  /// The HTML fragment, e.g. of a selection dragged from a web browser.
+  pub html: Option<String>,
+}
+
+impl DropData {
+  /// Returns whether the item is in none of the supported formats.
+  pub fn is_empty(&self) -> bool {
+    self.paths.is_empty() && self.uris.is_empty() && self.text.is_none() && self.html.is_none()
+  }
+}
+
 /// Describes touch-screen input state.
 #[non_exhaustive]
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
@@ -887,6 +1549,18 @@ pub enum TrayEvent {
   /// - **macOS / Linux:** Unsupported
   ///
   DoubleClick,
+  /// Fired when the mouse wheel is scrolled over the tray icon, with the number of lines
+  /// scrolled. Positive values mean scrolling right and up.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Unsupported, the system doesn't report scrolling over tray icons.
+  /// - **macOS:** The fractions of a line that trackpads scroll add up, and the event is fired
+  ///   once they make a whole line.
+  /// - **Linux:** The bounds of the event are empty, as indicators don't report where they are
+  ///   shown.
+  ///
+  Scroll { delta_x: i32, delta_y: i32 },
 }
 
 /// Describes a rectangle including position (x - y axis) and size.
@@ -896,6 +1570,16 @@ pub struct Rectangle {
   pub size: PhysicalSize<f64>,
 }
 
+impl Rectangle {
+  #[cfg_attr(any(target_os = "ios", target_os = "android"), allow(dead_code))]
+  pub(crate) fn contains(&self, point: PhysicalPosition<f64>) -> bool {
+    point.x >= self.position.x
+      && point.y >= self.position.y
+      && point.x < self.position.x + self.size.width
+      && point.y < self.position.y + self.size.height
+  }
+}
+
 /// Represents a touch event
 ///
 /// Every time the user touches the screen, a new `Start` event with an unique
@@ -926,6 +1610,36 @@ pub struct Touch {
   pub force: Option<Force>,
   /// Unique identifier of a finger.
   pub id: u64,
+  /// When the OS registered the touch, see [`KeyEvent::timestamp`].
+  pub timestamp: Instant,
+}
+
+/// Represents a pen event.
+///
+/// A stroke starts with [`TouchPhase::Started`] when the pen touches the surface, and ends with
+/// [`TouchPhase::Ended`] when it's lifted or [`TouchPhase::Cancelled`]. The pen hovering over the
+/// window is reported with [`TouchPhase::Moved`] and a `pressure` of `0.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PenInput {
+  pub device_id: DeviceId,
+  pub phase: TouchPhase,
+  pub location: PhysicalPosition<f64>,
+  /// How hard the pen is pressed, from `0.0` to `1.0`.
+  pub pressure: f64,
+  /// The tilt of the pen in degrees, from `-90.0` to `90.0`, along the x axis (positive to the
+  /// right) and the y axis (positive towards the user). `None` if the pen doesn't report it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / Linux:** Reported normalized, so the angles assume the range of Wacom pens.
+  pub tilt: Option<(f64, f64)>,
+  /// The clockwise rotation of the pen around its own axis in degrees, from `0.0` to `360.0`.
+  /// `None` if the pen doesn't report it.
+  pub twist: Option<f64>,
+  /// Whether the eraser end of the pen is used.
+  pub eraser: bool,
+  /// Whether a barrel button of the pen is pressed.
+  pub barrel_button: bool,
 }
 
 /// Describes the force of a touch event
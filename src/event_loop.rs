@@ -15,9 +15,29 @@
 //! [send_event]: crate::event_loop::EventLoopProxy::send_event
 use instant::Instant;
 use raw_window_handle::{HasRawDisplayHandle, RawDisplayHandle};
-use std::{error, fmt, ops::Deref};
+use std::{
+  cell::Cell,
+  error, fmt,
+  future::Future,
+  ops::Deref,
+  pin::Pin,
+  sync::{
+    atomic::{AtomicU8, Ordering},
+    mpsc::{self, Receiver},
+    Arc, Mutex,
+  },
+  task::{Context, Poll, Wake, Waker},
+};
 
-use crate::{event::Event, monitor::MonitorHandle, platform_impl};
+use crate::{
+  dpi::PhysicalPosition,
+  error::{ExternalError, OsError},
+  event::{DeviceEvent, DeviceId, Event, WindowEvent},
+  keyboard::{KeyCode, ModifiersState},
+  monitor::MonitorHandle,
+  platform_impl,
+  window::{Window, WindowBuilder, WindowId, RGBA},
+};
 
 /// Provides a way to retrieve events from the system and from the windows that were registered to
 /// the events loop.
@@ -34,9 +54,27 @@ use crate::{event::Event, monitor::MonitorHandle, platform_impl};
 ///
 pub struct EventLoop<T: 'static> {
   pub(crate) event_loop: platform_impl::EventLoop<T>,
+  /// Whether no other instance ran when the event loop was built, see
+  /// [`EventLoopBuilder::with_single_instance`].
+  primary_instance: bool,
   pub(crate) _marker: ::std::marker::PhantomData<*mut ()>, // Not Send nor Sync
 }
 
+/// Object that allows building the event loop.
+///
+/// This is used to make specifying options that affect the whole application
+/// easier. But note that constructing multiple event loops is not supported.
+///
+/// Platform options are set through the `EventLoopBuilderExt*` traits in the
+/// [`platform`](crate::platform) module, and can be combined freely before calling
+/// [`EventLoopBuilder::build`].
+pub struct EventLoopBuilder<T: 'static> {
+  pub(crate) platform_specific: platform_impl::PlatformSpecificEventLoopAttributes,
+  device_event_filter: Option<DeviceEventFilter>,
+  single_instance: Option<String>,
+  _p: ::std::marker::PhantomData<T>,
+}
+
 /// Target that associates windows with an `EventLoop`.
 ///
 /// This type exists to allow you to create new windows while Tao executes
@@ -47,6 +85,13 @@ pub struct EventLoop<T: 'static> {
 pub struct EventLoopWindowTarget<T: 'static> {
   pub(crate) p: platform_impl::EventLoopWindowTarget<T>,
   pub(crate) _marker: ::std::marker::PhantomData<*mut ()>, // Not Send nor Sync
+  pub(crate) coalesce_motion_events: Cell<bool>,
+}
+
+impl<T> fmt::Debug for EventLoopBuilder<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.pad("EventLoopBuilder { .. }")
+  }
 }
 
 impl<T> fmt::Debug for EventLoop<T> {
@@ -83,6 +128,17 @@ pub enum ControlFlow {
   Wait,
   /// When the current loop iteration finishes, suspend the thread until either another event
   /// arrives or the given time is reached.
+  ///
+  /// Without other events, the loop doesn't resume before the given time, but may resume a little
+  /// after it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Resumes within about half a millisecond using a high resolution timer, which
+  ///   requires Windows 10 version 1803. Older versions resume on the next system clock tick,
+  ///   usually within 16 milliseconds.
+  /// - **Linux:** Sleeps until the last whole millisecond, then keeps the loop spinning for the
+  ///   remainder, so it usually resumes within a few microseconds.
   WaitUntil(Instant),
   /// Send a `LoopDestroyed` event and stop the event loop. This variant is *sticky* - once set,
   /// `control_flow` cannot be changed from `ExitWithCode`, and any future attempts to do so will
@@ -117,21 +173,104 @@ impl Default for ControlFlow {
   }
 }
 
+impl EventLoopBuilder<()> {
+  /// Start building a new event loop.
+  #[inline]
+  pub fn new() -> Self {
+    Self::with_user_event()
+  }
+}
+
+impl Default for EventLoopBuilder<()> {
+  fn default() -> Self {
+    EventLoopBuilder::new()
+  }
+}
+
+impl<T> EventLoopBuilder<T> {
+  /// Start building a new event loop, with the given type as the user event
+  /// type.
+  #[inline]
+  pub fn with_user_event() -> Self {
+    Self {
+      platform_specific: Default::default(),
+      device_event_filter: None,
+      single_instance: None,
+      _p: ::std::marker::PhantomData,
+    }
+  }
+
+  /// Sets the [`DeviceEvent`] filter mode the event loop starts with.
+  ///
+  /// See [`EventLoopWindowTarget::set_device_event_filter`] for details.
+  ///
+  /// [`DeviceEvent`]: crate::event::DeviceEvent
+  #[inline]
+  pub fn with_device_event_filter(&mut self, filter: DeviceEventFilter) -> &mut Self {
+    self.device_event_filter = Some(filter);
+    self
+  }
+
+  /// Makes the process the single instance of the application identified by `identifier` when
+  /// the event loop is built.
+  ///
+  /// When another instance already runs, [`EventLoopBuilder::build`] forwards the arguments of
+  /// this process to it, and [`EventLoop::is_primary_instance`] returns `false`, in which case
+  /// the process should exit without running the event loop. See
+  /// [`EventLoopWindowTarget::enable_single_instance`] for details.
+  #[inline]
+  pub fn with_single_instance<S: Into<String>>(&mut self, identifier: S) -> &mut Self {
+    self.single_instance = Some(identifier.into());
+    self
+  }
+
+  /// Builds a new event loop.
+  ///
+  /// ***For cross-platform compatibility, the `EventLoop` must be created on the main thread.***
+  /// Attempting to create the event loop on a different thread will panic. This restriction isn't
+  /// strictly necessary on all platforms, but is imposed to eliminate any nasty surprises when
+  /// porting to platforms that require it. `EventLoopBuilderExt::with_any_thread` functions are
+  /// exposed in the relevant [`platform`](crate::platform) module if the target platform supports
+  /// creating an event loop on any thread.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS:** Can only be called on the main thread.
+  #[inline]
+  pub fn build(&mut self) -> EventLoop<T> {
+    let mut event_loop = EventLoop {
+      event_loop: platform_impl::EventLoop::new(&mut self.platform_specific),
+      primary_instance: true,
+      _marker: ::std::marker::PhantomData,
+    };
+    if let Some(filter) = self.device_event_filter {
+      event_loop.set_device_event_filter(filter);
+    }
+    if let Some(identifier) = &self.single_instance {
+      event_loop.primary_instance = event_loop.enable_single_instance(identifier);
+    }
+    event_loop
+  }
+}
+
 impl EventLoop<()> {
   /// Builds a new event loop with a `()` as the user event type.
   ///
+  /// Alias for [`EventLoopBuilder::new().build()`](EventLoopBuilder::build); use
+  /// [`EventLoopBuilder`] to set platform options at construction time.
+  ///
   /// ***For cross-platform compatibility, the `EventLoop` must be created on the main thread.***
   /// Attempting to create the event loop on a different thread will panic. This restriction isn't
   /// strictly necessary on all platforms, but is imposed to eliminate any nasty surprises when
-  /// porting to platforms that require it. `EventLoopExt::new_any_thread` functions are exposed
-  /// in the relevant `platform` module if the target platform supports creating an event loop on
-  /// any thread.
+  /// porting to platforms that require it. `EventLoopBuilderExt::with_any_thread` functions are
+  /// exposed in the relevant `platform` module if the target platform supports creating an event
+  /// loop on any thread.
   ///
   /// ## Platform-specific
   ///
   /// - **iOS:** Can only be called on the main thread.
   pub fn new() -> EventLoop<()> {
-    EventLoop::<()>::with_user_event()
+    EventLoopBuilder::new().build()
   }
 }
 
@@ -150,10 +289,16 @@ impl<T> EventLoop<T> {
   ///
   /// - **iOS:** Can only be called on the main thread.
   pub fn with_user_event() -> EventLoop<T> {
-    EventLoop {
-      event_loop: platform_impl::EventLoop::new(),
-      _marker: ::std::marker::PhantomData,
-    }
+    EventLoopBuilder::<T>::with_user_event().build()
+  }
+
+  /// Returns `false` when another instance of the application already ran when the event loop
+  /// was built with [`EventLoopBuilder::with_single_instance`], in which case the arguments of
+  /// this process were forwarded to it and this process should exit without running the event
+  /// loop. Returns `true` otherwise.
+  #[inline]
+  pub fn is_primary_instance(&self) -> bool {
+    self.primary_instance
   }
 
   /// Hijacks the calling thread and initializes the tao event loop with the provided
@@ -165,6 +310,11 @@ impl<T> EventLoop<T> {
   ///
   /// Any values not passed to this function will *not* be dropped.
   ///
+  /// Once the callback sets [`ControlFlow::ExitWithCode`], the process exits with the given code
+  /// after `LoopDestroyed` has been delivered. Use
+  /// [`EventLoopExtRunReturn::run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return)
+  /// instead if you need to observe that code before the process terminates.
+  ///
   /// ## Platform-specific
   ///
   /// - **Unix**: The program terminates with exit code 1 if the display server
@@ -172,11 +322,16 @@ impl<T> EventLoop<T> {
   ///
   /// [`ControlFlow`]: crate::event_loop::ControlFlow
   #[inline]
-  pub fn run<F>(self, event_handler: F) -> !
+  pub fn run<F>(self, mut event_handler: F) -> !
   where
     F: 'static + FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
   {
-    self.event_loop.run(event_handler)
+    let mut coalescer = MotionCoalescer::default();
+    self
+      .event_loop
+      .run(move |event, window_target, control_flow| {
+        coalescer.dispatch(event, window_target, control_flow, &mut event_handler)
+      })
   }
 
   /// Creates an `EventLoopProxy` that can be used to dispatch user events to the main event loop.
@@ -213,19 +368,194 @@ impl<T> EventLoopWindowTarget<T> {
     self.p.primary_monitor()
   }
 
+  /// Returns the current position of the cursor, in the same desktop coordinates as
+  /// [`Window::outer_position`](crate::window::Window::outer_position), whether or not it is
+  /// over one of the windows of the app.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux(Wayland) / iOS / Android:** Always returns an [`ExternalError::NotSupported`],
+  ///   since there is no global coordinate system. Use the position of
+  ///   [`WindowEvent::CursorMoved`](crate::event::WindowEvent::CursorMoved) instead.
+  #[inline]
+  pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, ExternalError> {
+    self.p.cursor_position()
+  }
+
+  /// Returns the id of the window that currently has the OS focus, or `None` if none of this
+  /// event loop's windows has it, e.g. because another app is in the foreground.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Always returns `None`.
+  #[inline]
+  pub fn focused_window(&self) -> Option<WindowId> {
+    self.p.focused_window()
+  }
+
+  /// Returns the modifiers that are currently held down, whether or not one of the windows of the
+  /// app has the keyboard focus.
+  ///
+  /// Unlike [`WindowEvent::ModifiersChanged`](crate::event::WindowEvent::ModifiersChanged),
+  /// this reflects the state of the keyboard at the time of the call, not at the time of the
+  /// event being processed.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Always returns an empty [`ModifiersState`].
+  #[inline]
+  pub fn modifiers_state(&self) -> ModifiersState {
+    self.p.modifiers_state()
+  }
+
+  /// Returns whether the physical `key` is currently held down, whether or not one of the windows
+  /// of the app has the keyboard focus.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Keys pressed while another app is active may only be reported once the user
+  ///   granted the app the input monitoring permission.
+  /// - **Linux(Wayland) / iOS / Android:** Always returns `false`.
+  #[inline]
+  pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+    self.p.is_key_pressed(key)
+  }
+
+  /// Brings the application to the foreground with all of its windows, and focuses the window
+  /// that was focused last. This is what a second instance of a single-instance app should
+  /// trigger, for example.
+  ///
+  /// With `ignore_other_apps` set to `false`, the OS may only ask the user's attention instead
+  /// when another app is in use.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Minimized windows are restored. `ignore_other_apps` works around the
+  ///   restrictions on setting the foreground window.
+  /// - **Linux:** `ignore_other_apps` is ignored, the window manager decides whether to
+  ///   steal the focus.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn activate_app(&self, ignore_other_apps: bool) {
+    self.p.activate_app(ignore_other_apps)
+  }
+
+  /// Returns whether a compositor is running, which is required for transparent windows to
+  /// actually be transparent. Apps can use it to fall back to an opaque look.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Always returns `true` since Windows 8.
+  /// - **Linux:** Returns `false` on X11 without a compositing window manager.
+  /// - **macOS / iOS / Android:** Always returns `true`.
+  #[inline]
+  pub fn is_compositor_active(&self) -> bool {
+    self.p.is_compositor_active()
+  }
+
+  /// Starts or stops emitting [`Event::ClipboardUpdated`] whenever the content of the clipboard
+  /// changes, including when this application writes to it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** The pasteboard has no change notification, so it is polled twice a second
+  ///   while listening.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`Event::ClipboardUpdated`]: crate::event::Event::ClipboardUpdated
+  #[inline]
+  pub fn listen_clipboard(&self, listen: bool) {
+    self.p.listen_clipboard(listen)
+  }
+
+  /// Makes this process the single instance of the application identified by `identifier`, so
+  /// that the instances started later forward their command-line arguments and current directory
+  /// to it as [`Event::NewInstance`], such as to open a file in an existing window.
+  ///
+  /// Returns `false` when another instance already runs, in which case the arguments of this
+  /// process were forwarded to it and this process should exit without running the event loop.
+  /// Returns `true` otherwise.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `identifier` names a mutex of the session, and the arguments are sent with
+  ///   `WM_COPYDATA`.
+  /// - **macOS:** `identifier` names a lock file in the temporary directory, and the arguments
+  ///   are sent with a distributed notification, which sandboxed applications can't receive.
+  /// - **Linux:** `identifier` is a well-known name owned on the D-Bus session bus, so it must be
+  ///   a valid bus name such as `com.example.App`. Always returns `true` without a session bus.
+  /// - **iOS / Android:** Unsupported, always returns `true`.
+  ///
+  /// [`Event::NewInstance`]: crate::event::Event::NewInstance
+  #[inline]
+  pub fn enable_single_instance(&self, identifier: &str) -> bool {
+    self.p.enable_single_instance(identifier)
+  }
+
+  /// Returns an identifier of the active keyboard layout, which changes whenever
+  /// [`Event::KeyboardLayoutChanged`] is emitted.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The keyboard layout identifier, e.g. `00000409` for US English.
+  /// - **macOS:** The input source identifier, e.g. `com.apple.keylayout.US`.
+  /// - **Linux:** The name of the XKB group, e.g. `English (US)`. Returns `None` on Wayland.
+  /// - **iOS / Android:** Always returns `None`.
+  ///
+  /// [`Event::KeyboardLayoutChanged`]: crate::event::Event::KeyboardLayoutChanged
+  #[inline]
+  pub fn keyboard_layout(&self) -> Option<String> {
+    self.p.keyboard_layout()
+  }
+
+  /// Returns the accent color chosen by the user, reported again with
+  /// [`Event::AccentColorChanged`] when it changes.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The DWM colorization color, which follows the accent color.
+  /// - **Linux:** The `accent-color` setting of the desktop portal, which GNOME and KDE Plasma
+  ///   implement. Returns `None` when no portal provides it, and until the portal answered
+  ///   once the event loop runs, when [`Event::AccentColorChanged`] reports it.
+  /// - **macOS:** Returns `None` before macOS 10.14.
+  /// - **iOS / Android:** Always returns `None`.
+  ///
+  /// [`Event::AccentColorChanged`]: crate::event::Event::AccentColorChanged
+  #[inline]
+  pub fn accent_color(&self) -> Option<RGBA> {
+    self.p.accent_color()
+  }
+
+  /// Merges consecutive [`WindowEvent::CursorMoved`] events of a window into a single one with
+  /// the latest position, and consecutive [`DeviceEvent::MouseMotion`] events of a device into a
+  /// single one with the accumulated delta. Merged events are delivered before the next event of
+  /// another kind, so at the latest before [`Event::MainEventsCleared`].
+  ///
+  /// This keeps high polling rate mice from flooding the event loop and delaying redraws. It is
+  /// disabled by default, so every motion event is delivered as is.
+  pub fn set_coalesce_motion_events(&self, coalesce: bool) {
+    self.coalesce_motion_events.set(coalesce)
+  }
+
   /// Change [`DeviceEvent`] filter mode.
   ///
-  /// Since the [`DeviceEvent`] capture can lead to high CPU usage for unfocused windows, winit
-  /// will ignore them by default for unfocused windows on Linux/BSD. This method allows changing
-  /// this filter at runtime to explicitly capture them again.
+  /// Since the [`DeviceEvent`] capture can lead to high CPU usage for unfocused windows, tao
+  /// ignores them by default while none of the windows of the app is focused. This method allows
+  /// changing this filter at runtime to explicitly capture them again. A window can override it
+  /// while focused with [`Window::set_device_event_filter`].
   ///
   /// ## Platform-specific
   ///
-  /// - ** Linux / macOS / iOS / Android / Web**: Unsupported.
+  /// - **macOS:** Only mouse device events are reported. While the app is inactive, they are
+  ///   read from a listen-only event tap, which is only installed with
+  ///   [`DeviceEventFilter::Never`].
+  /// - **Linux:** Only mouse motion is reported, on X11.
+  /// - **iOS / Android:** Unsupported.
   ///
   /// [`DeviceEvent`]: crate::event::DeviceEvent
+  /// [`Window::set_device_event_filter`]: crate::window::Window::set_device_event_filter
   pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {
-    #[cfg(target_os = "windows")]
+    #[cfg(not(any(target_os = "ios", target_os = "android")))]
     self.p.set_device_event_filter(_filter);
   }
 }
@@ -237,6 +567,16 @@ unsafe impl<T> HasRawDisplayHandle for EventLoopWindowTarget<T> {
   }
 }
 
+#[cfg(feature = "rwh_06")]
+impl<T> rwh_06::HasDisplayHandle for EventLoopWindowTarget<T> {
+  /// Returns a [`rwh_06::DisplayHandle`] for the event loop.
+  fn display_handle(&self) -> Result<rwh_06::DisplayHandle<'_>, rwh_06::HandleError> {
+    let raw = crate::window::raw_display_handle_06(self.p.raw_display_handle())?;
+    // Safety: the display outlives the borrow of the event loop.
+    Ok(unsafe { rwh_06::DisplayHandle::borrow_raw(raw) })
+  }
+}
+
 /// Used to send custom events to `EventLoop`.
 pub struct EventLoopProxy<T: 'static> {
   event_loop_proxy: platform_impl::EventLoopProxy<T>,
@@ -259,6 +599,282 @@ impl<T: 'static> EventLoopProxy<T> {
   pub fn send_event(&self, event: T) -> Result<(), EventLoopClosed<T>> {
     self.event_loop_proxy.send_event(event)
   }
+
+  /// Creates a window on the thread running the `EventLoop`, so that threads without access to
+  /// an [`EventLoopWindowTarget`], like plugin or scripting threads, can open windows.
+  ///
+  /// `builder` is called on the thread of the `EventLoop` the next time it processes events, since
+  /// a [`WindowBuilder`] can hold values, like menus, that must stay on that thread. The window it
+  /// returns is built right away, and the result is sent to the returned receiver. The receiver
+  /// is disconnected instead if the `EventLoop` no longer exists.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported, the receiver is always disconnected.
+  pub fn create_window<F>(&self, builder: F) -> Receiver<Result<Window, OsError>>
+  where
+    F: FnOnce(&EventLoopWindowTarget<T>) -> WindowBuilder + Send + 'static,
+  {
+    let (tx, rx) = mpsc::channel();
+    self
+      .event_loop_proxy
+      .send_task(Box::new(move |window_target| {
+        let _ = tx.send(builder(window_target).build(window_target));
+      }));
+    rx
+  }
+}
+
+/// Holds back motion events while [`EventLoopWindowTarget::set_coalesce_motion_events`] is
+/// enabled, merging them with the following ones.
+#[derive(Default)]
+pub(crate) struct MotionCoalescer {
+  cursor_moved: Option<(
+    WindowId,
+    DeviceId,
+    PhysicalPosition<f64>,
+    Instant,
+    ModifiersState,
+  )>,
+  mouse_motion: Option<(DeviceId, (f64, f64))>,
+  /// Whether the pending `MouseMotion` came before the pending `CursorMoved`.
+  mouse_motion_first: bool,
+}
+
+impl MotionCoalescer {
+  pub(crate) fn dispatch<T, F>(
+    &mut self,
+    event: Event<'_, T>,
+    window_target: &EventLoopWindowTarget<T>,
+    control_flow: &mut ControlFlow,
+    event_handler: &mut F,
+  ) where
+    F: FnMut(Event<'_, T>, &EventLoopWindowTarget<T>, &mut ControlFlow),
+  {
+    self.push(
+      event,
+      window_target.coalesce_motion_events.get(),
+      &mut |event| event_handler(event, window_target, control_flow),
+    );
+  }
+
+  /// Holds back `event` if it can be merged with the following ones, and emits the events that
+  /// can't be merged anymore.
+  fn push<'a, T>(
+    &mut self,
+    event: Event<'a, T>,
+    coalesce: bool,
+    emit: &mut dyn FnMut(Event<'a, T>),
+  ) {
+    if !coalesce {
+      self.flush(emit);
+      return emit(event);
+    }
+
+    // Only a motion event that can't be merged with the pending one of the same kind flushes
+    // them, so that interleaved `CursorMoved` and `MouseMotion` events are merged too.
+    #[allow(deprecated)]
+    match event {
+      Event::WindowEvent {
+        window_id,
+        event:
+          WindowEvent::CursorMoved {
+            device_id,
+            position,
+            timestamp,
+            modifiers,
+          },
+      } => match &mut self.cursor_moved {
+        Some((
+          pending_window_id,
+          pending_device_id,
+          pending_position,
+          pending_timestamp,
+          pending_modifiers,
+        )) if *pending_window_id == window_id && *pending_device_id == device_id => {
+          *pending_position = position;
+          *pending_timestamp = timestamp;
+          *pending_modifiers = modifiers;
+        }
+        pending => {
+          if pending.is_some() {
+            self.flush(emit);
+          }
+          self.mouse_motion_first = self.mouse_motion.is_some();
+          self.cursor_moved = Some((window_id, device_id, position, timestamp, modifiers));
+        }
+      },
+      Event::DeviceEvent {
+        device_id,
+        event: DeviceEvent::MouseMotion { delta },
+      } => match &mut self.mouse_motion {
+        Some((pending_device_id, pending_delta)) if *pending_device_id == device_id => {
+          pending_delta.0 += delta.0;
+          pending_delta.1 += delta.1;
+        }
+        pending => {
+          if pending.is_some() {
+            self.flush(emit);
+          }
+          self.mouse_motion_first = self.cursor_moved.is_none();
+          self.mouse_motion = Some((device_id, delta));
+        }
+      },
+      event => {
+        self.flush(emit);
+        emit(event);
+      }
+    }
+  }
+
+  fn flush<'a, T>(&mut self, emit: &mut dyn FnMut(Event<'a, T>)) {
+    let mouse_motion = self
+      .mouse_motion
+      .take()
+      .map(|(device_id, delta)| Event::DeviceEvent {
+        device_id,
+        event: DeviceEvent::MouseMotion { delta },
+      });
+    #[allow(deprecated)]
+    let cursor_moved =
+      self
+        .cursor_moved
+        .take()
+        .map(
+          |(window_id, device_id, position, timestamp, modifiers)| Event::WindowEvent {
+            window_id,
+            event: WindowEvent::CursorMoved {
+              device_id,
+              position,
+              timestamp,
+              modifiers,
+            },
+          },
+        );
+    let events = if self.mouse_motion_first {
+      [mouse_motion, cursor_moved]
+    } else {
+      [cursor_moved, mouse_motion]
+    };
+    for event in events.into_iter().flatten() {
+      emit(event);
+    }
+  }
+}
+
+/// A closure run on the thread of the `EventLoop`, sent by an `EventLoopProxy`.
+pub(crate) type EventLoopTask<T> = Box<dyn FnOnce(&EventLoopWindowTarget<T>) + Send>;
+
+impl<T: Send + 'static> EventLoopProxy<T> {
+  /// Drives `future` to completion, then sends its output to the `EventLoop` as a
+  /// `UserEvent(output)` event.
+  ///
+  /// No executor is needed: the future is polled right away on the calling thread, then on
+  /// whichever thread wakes it, e.g. the thread completing a channel or a timer. Futures that must
+  /// be polled from within a specific runtime, like most tokio I/O, should be spawned on that
+  /// runtime instead, with their output sent back through [`EventLoopProxy::send_event`].
+  ///
+  /// The output is dropped if the `EventLoop` no longer exists once the future completes.
+  ///
+  /// ```no_run
+  /// use tao::event_loop::EventLoop;
+  ///
+  /// async fn answer() -> u32 {
+  ///   42
+  /// }
+  ///
+  /// let event_loop = EventLoop::<u32>::with_user_event();
+  /// event_loop.create_proxy().spawn(answer());
+  /// ```
+  pub fn spawn<F>(&self, future: F)
+  where
+    F: Future<Output = T> + Send + 'static,
+  {
+    let task = Arc::new(ProxyTask {
+      future: Mutex::new(Some(Box::pin(future))),
+      state: AtomicU8::new(ProxyTask::<T>::IDLE),
+      proxy: Mutex::new(self.clone()),
+    });
+    task.wake();
+  }
+}
+
+type BoxedFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// A future spawned with [`EventLoopProxy::spawn`], which is polled by its own waker.
+struct ProxyTask<T: 'static> {
+  future: Mutex<Option<BoxedFuture<T>>>,
+  state: AtomicU8,
+  proxy: Mutex<EventLoopProxy<T>>,
+}
+
+impl<T: Send + 'static> ProxyTask<T> {
+  const IDLE: u8 = 0;
+  const POLLING: u8 = 1;
+  /// Woken while being polled, so it must be polled again.
+  const NOTIFIED: u8 = 2;
+
+  fn poll(self: &Arc<Self>) {
+    let waker = Waker::from(self.clone());
+    let mut cx = Context::from_waker(&waker);
+    loop {
+      let mut future = self.future.lock().unwrap();
+      let output = match future.as_mut().map(|future| future.as_mut().poll(&mut cx)) {
+        Some(Poll::Ready(output)) => {
+          *future = None;
+          output
+        }
+        Some(Poll::Pending) => {
+          drop(future);
+          match self.state.compare_exchange(
+            Self::POLLING,
+            Self::IDLE,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+          ) {
+            Ok(_) => return,
+            Err(_) => {
+              self.state.store(Self::POLLING, Ordering::Release);
+              continue;
+            }
+          }
+        }
+        None => return,
+      };
+      drop(future);
+      let _ = self.proxy.lock().unwrap().send_event(output);
+      return;
+    }
+  }
+}
+
+impl<T: Send + 'static> Wake for ProxyTask<T> {
+  fn wake(self: Arc<Self>) {
+    self.wake_by_ref()
+  }
+
+  fn wake_by_ref(self: &Arc<Self>) {
+    let mut state = self.state.load(Ordering::Acquire);
+    loop {
+      let (new_state, poll) = match state {
+        Self::IDLE => (Self::POLLING, true),
+        Self::POLLING => (Self::NOTIFIED, false),
+        _ => return,
+      };
+      match self
+        .state
+        .compare_exchange(state, new_state, Ordering::AcqRel, Ordering::Acquire)
+      {
+        Ok(_) => {
+          if poll {
+            self.poll();
+          }
+          return;
+        }
+        Err(actual) => state = actual,
+      }
+    }
+  }
 }
 
 impl<T: 'static> fmt::Debug for EventLoopProxy<T> {
@@ -296,3 +912,58 @@ impl Default for DeviceEventFilter {
     Self::Unfocused
   }
 }
+
+#[test]
+fn motion_coalescer_merges_interleaved_events() {
+  let window_id = unsafe { WindowId::dummy() };
+  let device_id = unsafe { DeviceId::dummy() };
+  let timestamp = Instant::now();
+  #[allow(deprecated)]
+  let cursor_moved = |x: f64| -> Event<'static, ()> {
+    Event::WindowEvent {
+      window_id,
+      event: WindowEvent::CursorMoved {
+        device_id,
+        position: PhysicalPosition::new(x, 0.0),
+        timestamp,
+        modifiers: ModifiersState::empty(),
+      },
+    }
+  };
+  let mouse_motion = |dx: f64| -> Event<'static, ()> {
+    Event::DeviceEvent {
+      device_id,
+      event: DeviceEvent::MouseMotion { delta: (dx, 0.0) },
+    }
+  };
+
+  let mut coalescer = MotionCoalescer::default();
+  let mut emitted = Vec::new();
+  for event in [
+    mouse_motion(1.0),
+    cursor_moved(10.0),
+    mouse_motion(2.0),
+    cursor_moved(20.0),
+    mouse_motion(3.0),
+    Event::MainEventsCleared,
+  ] {
+    coalescer.push(event, true, &mut |event| emitted.push(event));
+  }
+
+  assert_eq!(emitted.len(), 3);
+  assert!(matches!(
+    emitted[0],
+    Event::DeviceEvent {
+      event: DeviceEvent::MouseMotion { delta: (dx, _) },
+      ..
+    } if dx == 6.0
+  ));
+  assert!(matches!(
+    emitted[1],
+    Event::WindowEvent {
+      event: WindowEvent::CursorMoved { position, .. },
+      ..
+    } if position.x == 20.0
+  ));
+  assert!(matches!(emitted[2], Event::MainEventsCleared));
+}
@@ -16,7 +16,12 @@
 //!
 //! ## Platform-specific
 //!
-//! - **Linux**: Only works on x11. See [#331](https://github.com/tauri-apps/tao/issues/331) for more information.
+//! - **Linux**: On Wayland, shortcuts are registered through the `GlobalShortcuts` desktop portal,
+//!   which may ask the user to confirm or change them. Without the portal, X11 key grabs are used
+//!   through XWayland, and only trigger while an X11 window has focus. Use
+//!   [`ShortcutManager::is_supported`] to know whether shortcuts can be registered at all.
+//! - **macOS**: Shortcuts using the volume and track keys rely on an event tap, which requires the
+//!   application to be trusted for accessibility.
 //!
 //! ```rust,ignore
 //! let mut hotkey_manager = ShortcutManager::new(&event_loop);
@@ -26,6 +31,7 @@
 
 use crate::{
   accelerator::Accelerator,
+  error::OsError,
   event_loop::EventLoopWindowTarget,
   platform_impl::{
     GlobalShortcut as GlobalShortcutPlatform, ShortcutManager as ShortcutManagerPlatform,
@@ -53,6 +59,17 @@ impl ShortcutManager {
     }
   }
 
+  /// Whether global shortcuts can be registered in the current session.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / macOS:** Always `true`.
+  /// - **Linux:** `false` on Wayland when neither the `GlobalShortcuts` desktop portal nor
+  ///   XWayland is available.
+  pub fn is_supported(&self) -> bool {
+    self.p.is_supported()
+  }
+
   /// Whether the application has registered this `Accelerator`.
   pub fn is_registered(&self, accelerator: &Accelerator) -> bool {
     self.registered_hotkeys.contains(&Box::new(accelerator))
@@ -97,6 +114,12 @@ pub enum ShortcutManagerError {
   AcceleratorAlreadyRegistered(Accelerator),
   AcceleratorNotRegistered(Accelerator),
   InvalidAccelerator(String),
+  /// Global shortcuts are not available in the current session, see
+  /// [`ShortcutManager::is_supported`].
+  Unsupported,
+  /// The OS failed to set up the shortcut, e.g. because the app isn't allowed to monitor the
+  /// media keys on macOS.
+  Os(OsError),
 }
 
 impl error::Error for ShortcutManagerError {}
@@ -110,6 +133,10 @@ impl fmt::Display for ShortcutManagerError {
         f.pad(&format!("hotkey not registered: {:?}", e))
       }
       ShortcutManagerError::InvalidAccelerator(e) => e.fmt(f),
+      ShortcutManagerError::Unsupported => {
+        f.pad("global shortcuts are not supported in this session")
+      }
+      ShortcutManagerError::Os(e) => e.fmt(f),
     }
   }
 }
@@ -18,7 +18,7 @@ pub(crate) const PIXEL_SIZE: usize = mem::size_of::<Pixel>();
 
 #[non_exhaustive]
 #[derive(Debug)]
-/// An error produced when using `Icon::from_rgba` with invalid arguments.
+/// An error produced when using `Icon::from_rgba` or `Icon::from_bytes` with invalid arguments.
 pub enum BadIcon {
   /// Produced when the length of the `rgba` argument isn't divisible by 4, thus `rgba` can't be
   /// safely interpreted as 32bpp RGBA pixels.
@@ -33,8 +33,20 @@ pub enum BadIcon {
     width_x_height: usize,
     pixel_count: usize,
   },
+  /// Produced when the hotspot passed to `CustomCursor::from_rgba` is outside of the image.
+  #[non_exhaustive]
+  HotspotOutOfBounds {
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+  },
   /// Produced when underlying OS functionality failed to create the icon
   OsError(io::Error),
+  /// Produced when the data passed to `Icon::from_bytes` is neither a PNG nor an ICO image.
+  UnsupportedFormat,
+  /// Produced when the PNG or ICO data passed to `Icon::from_bytes` couldn't be decoded.
+  DecodingError(String),
 }
 
 impl fmt::Display for BadIcon {
@@ -53,7 +65,18 @@ impl fmt::Display for BadIcon {
                 "The specified dimensions ({:?}x{:?}) don't match the number of pixels supplied by the `rgba` argument ({:?}). For those dimensions, the expected pixel count is {:?}.",
                 width, height, pixel_count, width_x_height,
             ),
+            BadIcon::HotspotOutOfBounds {
+                width,
+                height,
+                hotspot_x,
+                hotspot_y,
+            } => write!(f,
+                "The hotspot ({:?}, {:?}) is outside of the {:?}x{:?} cursor image.",
+                hotspot_x, hotspot_y, width, height,
+            ),
             BadIcon::OsError(e) => write!(f, "OS error when instantiating the icon: {:?}", e),
+            BadIcon::UnsupportedFormat => write!(f, "The icon data is neither a PNG nor an ICO image."),
+            BadIcon::DecodingError(e) => write!(f, "Failed to decode the icon data: {}", e),
         }
   }
 }
@@ -64,7 +87,7 @@ impl Error for BadIcon {
   }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct RgbaIcon {
   pub(crate) rgba: Vec<u8>,
   pub(crate) width: u32,
@@ -91,11 +114,13 @@ mod constructors {
         });
       }
       let pixel_count = rgba.len() / PIXEL_SIZE;
-      if pixel_count != (width * height) as usize {
+      // Don't let huge dimensions overflow into a matching pixel count.
+      let width_x_height = (width as usize).saturating_mul(height as usize);
+      if pixel_count != width_x_height {
         Err(BadIcon::DimensionsVsPixelCount {
           width,
           height,
-          width_x_height: (width * height) as usize,
+          width_x_height,
           pixel_count,
         })
       } else {
@@ -130,13 +155,327 @@ impl fmt::Debug for Icon {
 }
 
 impl Icon {
-  /// Creates an `Icon` from 32bpp RGBA data.
+  /// Creates an `Icon` from 32bpp RGBA data with straight, i.e. not premultiplied, alpha.
   ///
   /// The length of `rgba` must be divisible by 4, and `width * height` must equal
   /// `rgba.len() / 4`. Otherwise, this will return a `BadIcon` error.
+  ///
+  /// All platforms expect straight alpha. Passing premultiplied data here makes the
+  /// semi-transparent edges of the icon look too dark; use
+  /// [`Icon::from_rgba_premultiplied`] for such data instead.
   pub fn from_rgba(rgba: Vec<u8>, width: u32, height: u32) -> Result<Self, BadIcon> {
     Ok(Icon {
       inner: PlatformIcon::from_rgba(rgba, width, height)?,
     })
   }
+
+  /// Creates an `Icon` from 32bpp RGBA data with premultiplied alpha, as produced by most
+  /// renderers and compositors.
+  ///
+  /// The pixels are converted to straight alpha, which is what every platform expects.
+  /// The same validation as [`Icon::from_rgba`] applies.
+  pub fn from_rgba_premultiplied(
+    mut rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+  ) -> Result<Self, BadIcon> {
+    // Converting leaves the length as is, so this doesn't get in the way of validation.
+    unpremultiply_alpha(&mut rgba);
+    Self::from_rgba(rgba, width, height)
+  }
+
+  /// Creates an `Icon` from the contents of a PNG or ICO file, e.g. one embedded with
+  /// `include_bytes!`.
+  ///
+  /// For an ICO file holding several images, the largest one is used.
+  ///
+  /// Returns [`BadIcon::UnsupportedFormat`] if `bytes` is neither a PNG nor an ICO image, and
+  /// [`BadIcon::DecodingError`] if it is malformed.
+  pub fn from_bytes(bytes: &[u8]) -> Result<Self, BadIcon> {
+    let (rgba, width, height) = decode_image(bytes)?;
+    Self::from_rgba(rgba, width, height)
+  }
+}
+
+/// Converts premultiplied RGBA pixels to straight alpha in place.
+fn unpremultiply_alpha(rgba: &mut [u8]) {
+  for pixel in rgba.chunks_exact_mut(PIXEL_SIZE) {
+    let alpha = pixel[3] as u32;
+    // Fully transparent pixels carry no color to recover.
+    if alpha == 0 || alpha == 255 {
+      continue;
+    }
+    for channel in &mut pixel[..3] {
+      *channel = ((*channel as u32 * 255 + alpha / 2) / alpha).min(255) as u8;
+    }
+  }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const ICO_SIGNATURE: &[u8] = &[0, 0, 1, 0];
+
+/// Decodes a PNG or ICO image into 32bpp RGBA pixels, returning them along with the width and
+/// height.
+fn decode_image(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+  if bytes.starts_with(PNG_SIGNATURE) {
+    decode_png(bytes)
+  } else if bytes.starts_with(ICO_SIGNATURE) {
+    decode_ico(bytes)
+  } else {
+    Err(BadIcon::UnsupportedFormat)
+  }
+}
+
+fn decoding_error(e: impl fmt::Display) -> BadIcon {
+  BadIcon::DecodingError(e.to_string())
+}
+
+fn decode_png(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+  let mut decoder = png::Decoder::new(bytes);
+  // Expands palettes and low bit depths, and strips 16-bit channels down to 8 bits.
+  decoder.set_transformations(png::Transformations::normalize_to_color8());
+  let mut reader = decoder.read_info().map_err(decoding_error)?;
+  let mut buf = vec![0; reader.output_buffer_size()];
+  let info = reader.next_frame(&mut buf).map_err(decoding_error)?;
+  buf.truncate(info.buffer_size());
+
+  let rgba = match info.color_type {
+    png::ColorType::Rgba => buf,
+    png::ColorType::Rgb => buf
+      .chunks_exact(3)
+      .flat_map(|p| [p[0], p[1], p[2], 255])
+      .collect(),
+    png::ColorType::GrayscaleAlpha => buf
+      .chunks_exact(2)
+      .flat_map(|p| [p[0], p[0], p[0], p[1]])
+      .collect(),
+    png::ColorType::Grayscale => buf.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+    png::ColorType::Indexed => return Err(decoding_error("unexpanded PNG palette")),
+  };
+  Ok((rgba, info.width, info.height))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16, BadIcon> {
+  bytes
+    .get(offset..offset + 2)
+    .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    .ok_or_else(|| decoding_error("unexpected end of data"))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, BadIcon> {
+  bytes
+    .get(offset..offset + 4)
+    .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    .ok_or_else(|| decoding_error("unexpected end of data"))
+}
+
+fn decode_ico(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+  const HEADER_SIZE: usize = 6;
+  const ENTRY_SIZE: usize = 16;
+
+  let count = read_u16(bytes, 4)? as usize;
+  // Picks the largest image, preferring higher color depths among those of the same size.
+  let mut best = None;
+  for i in 0..count {
+    let entry = HEADER_SIZE + i * ENTRY_SIZE;
+    let entry_bytes = bytes
+      .get(entry..entry + ENTRY_SIZE)
+      .ok_or_else(|| decoding_error("unexpected end of data"))?;
+    // A dimension of 0 stands for 256 pixels.
+    let width = if entry_bytes[0] == 0 {
+      256
+    } else {
+      entry_bytes[0] as u32
+    };
+    let height = if entry_bytes[1] == 0 {
+      256
+    } else {
+      entry_bytes[1] as u32
+    };
+    let bit_count = read_u16(entry_bytes, 6)?;
+    let size = read_u32(entry_bytes, 8)? as usize;
+    let offset = read_u32(entry_bytes, 12)? as usize;
+    let key = (width * height, bit_count);
+    if best.map_or(true, |(best_key, _, _)| key > best_key) {
+      best = Some((key, offset, size));
+    }
+  }
+
+  let (_, offset, size) = best.ok_or_else(|| decoding_error("ICO file has no images"))?;
+  let image = offset
+    .checked_add(size)
+    .and_then(|end| bytes.get(offset..end))
+    .ok_or_else(|| decoding_error("ICO image data out of bounds"))?;
+  if image.starts_with(PNG_SIGNATURE) {
+    decode_png(image)
+  } else {
+    decode_dib(image)
+  }
+}
+
+/// Decodes the BMP image of an ICO file: a `BITMAPINFOHEADER` followed by an optional color
+/// table, the color bitmap and the 1bpp transparency mask, both stored bottom-up.
+fn decode_dib(image: &[u8]) -> Result<(Vec<u8>, u32, u32), BadIcon> {
+  let header_size = read_u32(image, 0)? as usize;
+  let width = read_u32(image, 4)? as i32;
+  // The height covers both the color bitmap and the mask.
+  let height = read_u32(image, 8)? as i32 / 2;
+  let bit_count = read_u16(image, 14)? as usize;
+  let compression = read_u32(image, 16)?;
+  let colors_used = read_u32(image, 32)? as usize;
+  if width <= 0 || height <= 0 || width > 256 || height > 256 {
+    return Err(decoding_error("invalid BMP dimensions"));
+  }
+  if compression != 0 {
+    return Err(decoding_error("compressed BMP images are not supported"));
+  }
+  if !matches!(bit_count, 1 | 4 | 8 | 24 | 32) {
+    return Err(decoding_error(format!(
+      "{}bpp BMP images are not supported",
+      bit_count
+    )));
+  }
+  let (width, height) = (width as usize, height as usize);
+
+  let palette_len = match bit_count {
+    1 | 4 | 8 if colors_used == 0 => 1 << bit_count,
+    1 | 4 | 8 => colors_used,
+    _ => 0,
+  };
+  let palette_start = header_size;
+  // Rows are padded to a multiple of 4 bytes.
+  let stride = (width * bit_count + 31) / 32 * 4;
+  let mask_stride = (width + 31) / 32 * 4;
+  // The header size and palette length come from the data, and may be made to overflow.
+  let (pixels_start, mask_start) = palette_len
+    .checked_mul(4)
+    .and_then(|palette_size| palette_start.checked_add(palette_size))
+    .and_then(|pixels_start| Some((pixels_start, pixels_start.checked_add(stride * height)?)))
+    .ok_or_else(|| decoding_error("invalid BMP header"))?;
+  let palette = image
+    .get(palette_start..pixels_start)
+    .ok_or_else(|| decoding_error("unexpected end of data"))?;
+  let pixels = image
+    .get(pixels_start..mask_start)
+    .ok_or_else(|| decoding_error("unexpected end of data"))?;
+  // Some 32bpp images omit the mask, as they carry their own alpha channel.
+  let mask = mask_start
+    .checked_add(mask_stride * height)
+    .and_then(|mask_end| image.get(mask_start..mask_end));
+
+  let mut rgba = vec![0; width * height * PIXEL_SIZE];
+  for y in 0..height {
+    let row = &pixels[(height - 1 - y) * stride..][..stride];
+    for x in 0..width {
+      let (b, g, r, a) = match bit_count {
+        32 => (row[x * 4], row[x * 4 + 1], row[x * 4 + 2], row[x * 4 + 3]),
+        24 => (row[x * 3], row[x * 3 + 1], row[x * 3 + 2], 255),
+        _ => {
+          let bit = x * bit_count;
+          let index = (row[bit / 8] >> (8 - bit_count - bit % 8)) as usize & ((1 << bit_count) - 1);
+          let color = palette
+            .get(index * 4..index * 4 + 3)
+            .ok_or_else(|| decoding_error("BMP color index out of bounds"))?;
+          (color[0], color[1], color[2], 255)
+        }
+      };
+      let pixel = &mut rgba[(y * width + x) * PIXEL_SIZE..][..PIXEL_SIZE];
+      pixel.copy_from_slice(&[r, g, b, a]);
+    }
+  }
+
+  // Without an alpha channel, or with an unused one, transparency comes from the mask.
+  let has_alpha = bit_count == 32 && rgba.chunks_exact(PIXEL_SIZE).any(|p| p[3] != 0);
+  if !has_alpha {
+    if let Some(mask) = mask {
+      for y in 0..height {
+        let row = &mask[(height - 1 - y) * mask_stride..][..mask_stride];
+        for x in 0..width {
+          let transparent = row[x / 8] & (0x80 >> (x % 8)) != 0;
+          rgba[(y * width + x) * PIXEL_SIZE + 3] = if transparent { 0 } else { 255 };
+        }
+      }
+    } else if bit_count == 32 {
+      rgba.chunks_exact_mut(PIXEL_SIZE).for_each(|p| p[3] = 255);
+    }
+  }
+
+  Ok((rgba, width as u32, height as u32))
+}
+
+#[test]
+fn test_icon_from_rgba() {
+  assert!(matches!(
+    Icon::from_rgba(vec![0; 7], 1, 2),
+    Err(BadIcon::ByteCountNotDivisibleBy4 { byte_count: 7 })
+  ));
+  assert!(matches!(
+    Icon::from_rgba(vec![0; 8], u32::MAX, u32::MAX),
+    Err(BadIcon::DimensionsVsPixelCount { pixel_count: 2, .. })
+  ));
+
+  let mut rgba = vec![64, 32, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0];
+  unpremultiply_alpha(&mut rgba);
+  assert_eq!(rgba, vec![128, 64, 0, 128, 10, 20, 30, 255, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_icon_from_bytes() {
+  assert!(matches!(
+    Icon::from_bytes(b"GIF89a"),
+    Err(BadIcon::UnsupportedFormat)
+  ));
+
+  // A 2x1 RGB PNG.
+  let mut png_bytes = Vec::new();
+  {
+    let mut encoder = png::Encoder::new(&mut png_bytes, 2, 1);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[255, 0, 0, 0, 0, 255]).unwrap();
+  }
+  let (rgba, width, height) = decode_image(&png_bytes).unwrap();
+  assert_eq!((width, height), (2, 1));
+  assert_eq!(rgba, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+
+  // A 1x2 24bpp BMP whose bottom pixel is masked out.
+  let mut dib = vec![0; 40];
+  dib[0] = 40;
+  dib[4] = 1;
+  dib[8] = 4;
+  dib[12] = 1;
+  dib[14] = 24;
+  // Bottom-up rows, padded to 4 bytes: blue, then green.
+  dib.extend_from_slice(&[255, 0, 0, 0, 0, 255, 0, 0]);
+  // Mask rows: the bottom one is transparent.
+  dib.extend_from_slice(&[0x80, 0, 0, 0, 0, 0, 0, 0]);
+
+  // An ICO holding both images, with the larger PNG listed last.
+  let mut ico = vec![0, 0, 1, 0, 2, 0];
+  let dib_offset = 6 + 16 * 2;
+  let png_offset = dib_offset + dib.len();
+  for (width, height, bit_count, size, offset) in [
+    (1u8, 2u8, 24u16, dib.len(), dib_offset),
+    (2, 1, 32, png_bytes.len(), png_offset),
+  ] {
+    ico.extend_from_slice(&[width, height, 0, 0, 1, 0]);
+    ico.extend_from_slice(&bit_count.to_le_bytes());
+    ico.extend_from_slice(&(size as u32).to_le_bytes());
+    ico.extend_from_slice(&(offset as u32).to_le_bytes());
+  }
+  ico.extend_from_slice(&dib);
+  ico.extend_from_slice(&png_bytes);
+  let (rgba, width, height) = decode_image(&ico).unwrap();
+  assert_eq!((width, height), (2, 1));
+  assert_eq!(rgba, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+
+  let (rgba, width, height) = decode_dib(&dib).unwrap();
+  assert_eq!((width, height), (1, 2));
+  assert_eq!(rgba, vec![0, 255, 0, 255, 0, 0, 255, 0]);
+
+  assert!(matches!(
+    decode_image(&ico[..ico.len() - 1]),
+    Err(BadIcon::DecodingError(_))
+  ));
 }
@@ -696,11 +696,13 @@ impl FromStr for KeyCode {
       "NUM7" | "NUMPAD7" => KeyCode::Numpad7,
       "NUM8" | "NUMPAD8" => KeyCode::Numpad8,
       "NUM9" | "NUMPAD9" => KeyCode::Numpad9,
-      "=" => KeyCode::Equal,
-      "-" => KeyCode::Minus,
+      "=" | "EQUAL" => KeyCode::Equal,
+      "-" | "MINUS" => KeyCode::Minus,
       "." | "PERIOD" => KeyCode::Period,
       "'" | "QUOTE" => KeyCode::Quote,
-      "\\" => KeyCode::IntlBackslash,
+      "\\" | "INTLBACKSLASH" => KeyCode::IntlBackslash,
+      "INTLRO" => KeyCode::IntlRo,
+      "INTLYEN" => KeyCode::IntlYen,
       "A" => KeyCode::KeyA,
       "B" => KeyCode::KeyB,
       "C" => KeyCode::KeyC,
@@ -733,22 +735,40 @@ impl FromStr for KeyCode {
       "BACKSPACE" => KeyCode::Backspace,
       "CAPSLOCK" => KeyCode::CapsLock,
       "CONTEXTMENU" => KeyCode::ContextMenu,
-      "ENTER" => KeyCode::Enter,
+      "ENTER" | "RETURN" => KeyCode::Enter,
       "SPACE" => KeyCode::Space,
       "TAB" => KeyCode::Tab,
       "CONVERT" => KeyCode::Convert,
+      "NONCONVERT" => KeyCode::NonConvert,
+      "KANAMODE" => KeyCode::KanaMode,
+      "HIRAGANA" => KeyCode::Hiragana,
+      "KATAKANA" => KeyCode::Katakana,
+      "LANG1" => KeyCode::Lang1,
+      "LANG2" => KeyCode::Lang2,
+      "LANG3" => KeyCode::Lang3,
+      "LANG4" => KeyCode::Lang4,
+      "LANG5" => KeyCode::Lang5,
+      "ALTLEFT" => KeyCode::AltLeft,
+      "ALTRIGHT" => KeyCode::AltRight,
+      "CONTROLLEFT" => KeyCode::ControlLeft,
+      "CONTROLRIGHT" => KeyCode::ControlRight,
+      "SHIFTLEFT" => KeyCode::ShiftLeft,
+      "SHIFTRIGHT" => KeyCode::ShiftRight,
+      "SUPERLEFT" => KeyCode::SuperLeft,
+      "SUPERRIGHT" => KeyCode::SuperRight,
 
-      "DELETE" => KeyCode::Delete,
+      "DELETE" | "DEL" => KeyCode::Delete,
       "END" => KeyCode::End,
       "HELP" => KeyCode::Help,
       "HOME" => KeyCode::Home,
+      "INSERT" | "INS" => KeyCode::Insert,
       "PAGEDOWN" => KeyCode::PageDown,
       "PAGEUP" => KeyCode::PageUp,
 
-      "DOWN" => KeyCode::ArrowDown,
-      "UP" => KeyCode::ArrowUp,
-      "LEFT" => KeyCode::ArrowLeft,
-      "RIGHT" => KeyCode::ArrowRight,
+      "DOWN" | "ARROWDOWN" => KeyCode::ArrowDown,
+      "UP" | "ARROWUP" => KeyCode::ArrowUp,
+      "LEFT" | "ARROWLEFT" => KeyCode::ArrowLeft,
+      "RIGHT" | "ARROWRIGHT" => KeyCode::ArrowRight,
 
       "NUMLOCK" => KeyCode::NumLock,
       "NUMADD" | "NUMPADADD" => KeyCode::NumpadAdd,
@@ -756,26 +776,70 @@ impl FromStr for KeyCode {
       "NUMCLEAR" | "NUMPADCLEAR" => KeyCode::NumpadClear,
       "NUMCOMMA" | "NUMPADCOMMA" => KeyCode::NumpadComma,
       "NUMDIVIDE" | "NUMPADDIVIDE" => KeyCode::NumpadDivide,
-      "NUMSUBSTRACT" | "NUMPADSUBSTRACT" => KeyCode::NumpadSubtract,
+      "NUMDEC" | "NUMDECIMAL" | "NUMPADDECIMAL" => KeyCode::NumpadDecimal,
+      "NUMEQUAL" | "NUMPADEQUAL" => KeyCode::NumpadEqual,
+      "NUMMULT" | "NUMMULTIPLY" | "NUMPADMULTIPLY" => KeyCode::NumpadMultiply,
+      "NUMSUB" | "NUMSUBSTRACT" | "NUMSUBTRACT" | "NUMPADSUBSTRACT" | "NUMPADSUBTRACT" => {
+        KeyCode::NumpadSubtract
+      }
       "NUMENTER" | "NUMPADENTER" => KeyCode::NumpadEnter,
+      "NUMPADCLEARENTRY" => KeyCode::NumpadClearEntry,
+      "NUMPADHASH" => KeyCode::NumpadHash,
+      "NUMPADMEMORYADD" => KeyCode::NumpadMemoryAdd,
+      "NUMPADMEMORYCLEAR" => KeyCode::NumpadMemoryClear,
+      "NUMPADMEMORYRECALL" => KeyCode::NumpadMemoryRecall,
+      "NUMPADMEMORYSTORE" => KeyCode::NumpadMemoryStore,
+      "NUMPADMEMORYSUBTRACT" => KeyCode::NumpadMemorySubtract,
+      "NUMPADPARENLEFT" => KeyCode::NumpadParenLeft,
+      "NUMPADPARENRIGHT" => KeyCode::NumpadParenRight,
+      "NUMPADSTAR" => KeyCode::NumpadStar,
 
       "ESC" | "ESCAPE" => KeyCode::Escape,
       "FN" => KeyCode::Fn,
       "FNLOCK" => KeyCode::FnLock,
+      "HYPER" => KeyCode::Hyper,
+      "TURBO" => KeyCode::Turbo,
       "PRINTSCREEN" => KeyCode::PrintScreen,
       "SCROLLLOCK" => KeyCode::ScrollLock,
 
       "PAUSE" => KeyCode::Pause,
+      "ABORT" => KeyCode::Abort,
+      "RESUME" => KeyCode::Resume,
+      "AGAIN" => KeyCode::Again,
+      "COPY" => KeyCode::Copy,
+      "CUT" => KeyCode::Cut,
+      "FIND" => KeyCode::Find,
+      "OPEN" => KeyCode::Open,
+      "PASTE" => KeyCode::Paste,
+      "PROPS" => KeyCode::Props,
+      "SELECT" => KeyCode::Select,
+      "UNDO" => KeyCode::Undo,
 
-      "VOLUMEMUTE" => KeyCode::AudioVolumeMute,
-      "VOLUMEDOWN" => KeyCode::AudioVolumeDown,
-      "VOLUMEUP" => KeyCode::AudioVolumeUp,
-      "MEDIANEXTTRACK" => KeyCode::MediaTrackNext,
-      "MEDIAPREVIOUSTRACK" => KeyCode::MediaTrackPrevious,
-      "MEDIAPLAYPAUSE" => KeyCode::MediaPlayPause,
+      "VOLUMEMUTE" | "AUDIOVOLUMEMUTE" => KeyCode::AudioVolumeMute,
+      "VOLUMEDOWN" | "AUDIOVOLUMEDOWN" => KeyCode::AudioVolumeDown,
+      "VOLUMEUP" | "AUDIOVOLUMEUP" => KeyCode::AudioVolumeUp,
+      "NEXTTRACK" | "MEDIANEXTTRACK" | "MEDIATRACKNEXT" => KeyCode::MediaTrackNext,
+      "PREVIOUSTRACK" | "MEDIAPREVIOUSTRACK" | "MEDIATRACKPREVIOUS" => KeyCode::MediaTrackPrevious,
+      "PLAYPAUSE" | "MEDIAPLAYPAUSE" => KeyCode::MediaPlayPause,
+      "MEDIASTOP" => KeyCode::MediaStop,
+      "MEDIASELECT" => KeyCode::MediaSelect,
       "LAUNCHMAIL" => KeyCode::LaunchMail,
+      "LAUNCHAPP1" => KeyCode::LaunchApp1,
+      "LAUNCHAPP2" => KeyCode::LaunchApp2,
+
+      "BROWSERBACK" => KeyCode::BrowserBack,
+      "BROWSERFORWARD" => KeyCode::BrowserForward,
+      "BROWSERREFRESH" => KeyCode::BrowserRefresh,
+      "BROWSERSTOP" => KeyCode::BrowserStop,
+      "BROWSERSEARCH" => KeyCode::BrowserSearch,
+      "BROWSERFAVORITES" => KeyCode::BrowserFavorites,
+      "BROWSERHOME" => KeyCode::BrowserHome,
 
       "SUSPEND" => KeyCode::Suspend,
+      "EJECT" => KeyCode::Eject,
+      "POWER" => KeyCode::Power,
+      "SLEEP" => KeyCode::Sleep,
+      "WAKEUP" => KeyCode::WakeUp,
       "F1" => KeyCode::F1,
       "F2" => KeyCode::F2,
       "F3" => KeyCode::F3,
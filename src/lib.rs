@@ -228,3 +228,10 @@ pub mod system_tray;
 pub mod window;
 
 pub mod platform;
+
+#[cfg(feature = "accesskit")]
+pub use accesskit_core as accesskit;
+pub use raw_window_handle as rwh_05;
+#[cfg(feature = "rwh_06")]
+pub use rwh_06;
+pub use url;
@@ -32,24 +32,35 @@ use crate::{
 
 /// Object that allows you to create a `ContextMenu`.
 ///
+/// A clone of a `ContextMenu` refers to the same menu, so you can keep one to modify the menu
+/// after giving it to a system tray.
+///
 /// ## Platform-specific
 ///
+#[derive(Clone)]
 pub struct ContextMenu(pub(crate) Menu);
 /// Object that allows you to create a `MenuBar`, menu.
 ///
+/// A `MenuBar` can be cloned and attached to several windows, changes made through the
+/// [`CustomMenuItem`]s it returned are then applied to every window at once.
+///
 /// ## Platform-specific
 ///
 /// **macOs:** The menu will show in the **Menu Bar**.
 /// **Linux / Windows:** The menu will be show at the top of the window.
+#[derive(Clone)]
 pub struct MenuBar(pub(crate) Menu);
 
 /// A custom menu item.
 pub struct MenuItemAttributes<'a> {
-  id: MenuId,
-  title: &'a str,
-  keyboard_accelerator: Option<Accelerator>,
-  enabled: bool,
-  selected: bool,
+  pub(crate) id: MenuId,
+  #[cfg_attr(any(target_os = "ios", target_os = "android"), allow(dead_code))]
+  pub(crate) title: &'a str,
+  pub(crate) keyboard_accelerator: Option<Accelerator>,
+  pub(crate) accelerator_display_only: bool,
+  pub(crate) enabled: bool,
+  pub(crate) selected: bool,
+  pub(crate) radio_group: Option<MenuId>,
 }
 
 impl<'a> MenuItemAttributes<'a> {
@@ -57,14 +68,17 @@ impl<'a> MenuItemAttributes<'a> {
   ///
   /// ## Platform-specific
   ///
-  /// - **Linux:** `selected` render a regular item
+  /// - **Linux:** An item that is neither selected nor part of a radio group when added renders a
+  ///   regular item, see [`CustomMenuItem::set_selected`].
   pub fn new(title: &'a str) -> Self {
     Self {
       id: MenuId::new(title),
       title,
       keyboard_accelerator: None,
+      accelerator_display_only: false,
       enabled: true,
       selected: false,
+      radio_group: None,
     }
   }
 
@@ -82,6 +96,23 @@ impl<'a> MenuItemAttributes<'a> {
   ///
   pub fn with_accelerators(mut self, keyboard_accelerators: &Accelerator) -> Self {
     self.keyboard_accelerator = Some(keyboard_accelerators.to_owned());
+    self.accelerator_display_only = false;
+    self
+  }
+
+  /// Shows the keyboard shortcut next to the menu item without handling it, for apps that
+  /// handle their shortcuts themselves through keyboard events.
+  ///
+  /// Pressing the shortcut is reported as a regular key press instead of a
+  /// [`Event::MenuEvent`](crate::event::Event::MenuEvent).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Android / iOS:** Unsupported
+  ///
+  pub fn with_accelerator_display_only(mut self, keyboard_accelerator: &Accelerator) -> Self {
+    self.keyboard_accelerator = Some(keyboard_accelerator.to_owned());
+    self.accelerator_display_only = true;
     self
   }
 
@@ -96,6 +127,22 @@ impl<'a> MenuItemAttributes<'a> {
     self.selected = selected;
     self
   }
+
+  /// Makes the item part of a radio group, identified by any `MenuId` shared by the items of
+  /// the group, e.g. `MenuId::new("theme")`.
+  ///
+  /// Items of the same menu (submenus excluded) that share a group are mutually exclusive:
+  /// selecting one of them, either by clicking it or through [`CustomMenuItem::set_selected`],
+  /// deselects the others.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** One item of the group is always selected, the first one by default.
+  /// - **Android / iOS:** Unsupported
+  pub fn with_radio_group(mut self, group: MenuId) -> Self {
+    self.radio_group = Some(group);
+    self
+  }
 }
 
 /// Base `Menu` functions.
@@ -126,14 +173,33 @@ impl ContextMenu {
 
   /// Add new item to this menu.
   pub fn add_item(&mut self, item: MenuItemAttributes<'_>) -> CustomMenuItem {
-    self.0.menu_platform.add_item(
-      item.id,
-      item.title,
-      item.keyboard_accelerator,
-      item.enabled,
-      item.selected,
-      MenuType::ContextMenu,
-    )
+    self.0.menu_platform.add_item(item, MenuType::ContextMenu)
+  }
+
+  /// Insert a new item at `index`, or at the end if `index` is past the last item.
+  ///
+  /// Separators and submenus count as items. This can be used after the menu is shown.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The keyboard accelerator of an item inserted after the menu is shown isn't
+  ///   registered.
+  pub fn insert_item(&mut self, index: usize, item: MenuItemAttributes<'_>) -> CustomMenuItem {
+    self
+      .0
+      .menu_platform
+      .insert_item(index, item, MenuType::ContextMenu)
+  }
+
+  /// Remove an item returned by [`ContextMenu::add_item`] or [`ContextMenu::insert_item`] from
+  /// this menu. This can be used after the menu is shown.
+  pub fn remove_item(&mut self, item: &CustomMenuItem) {
+    self.0.menu_platform.remove_item(&item.0)
+  }
+
+  /// Remove all the items of this menu. This can be used after the menu is shown.
+  pub fn clear(&mut self) {
+    self.0.menu_platform.clear()
   }
 
   /// Add new item to this menu.
@@ -167,14 +233,34 @@ impl MenuBar {
 
   /// Add new item to this menu.
   pub fn add_item(&mut self, item: MenuItemAttributes<'_>) -> CustomMenuItem {
-    self.0.menu_platform.add_item(
-      item.id,
-      item.title,
-      item.keyboard_accelerator,
-      item.enabled,
-      item.selected,
-      MenuType::MenuBar,
-    )
+    self.0.menu_platform.add_item(item, MenuType::MenuBar)
+  }
+
+  /// Insert a new item at `index`, or at the end if `index` is past the last item.
+  ///
+  /// Separators and submenus count as items. This can be used after the menu is attached to a
+  /// window, keep a clone of the menu (or submenu) to do so.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The keyboard accelerator of an item inserted after the menu is attached isn't
+  ///   registered.
+  pub fn insert_item(&mut self, index: usize, item: MenuItemAttributes<'_>) -> CustomMenuItem {
+    self
+      .0
+      .menu_platform
+      .insert_item(index, item, MenuType::MenuBar)
+  }
+
+  /// Remove an item returned by [`MenuBar::add_item`] or [`MenuBar::insert_item`] from this
+  /// menu. This can be used after the menu is attached to a window.
+  pub fn remove_item(&mut self, item: &CustomMenuItem) {
+    self.0.menu_platform.remove_item(&item.0)
+  }
+
+  /// Remove all the items of this menu. This can be used after the menu is attached to a window.
+  pub fn clear(&mut self) {
+    self.0.menu_platform.clear()
   }
 
   /// Add new item to this menu.
@@ -236,6 +322,7 @@ pub enum MenuItem {
   /// ## Platform-specific
   ///
   /// - **Android / iOS:** Unsupported
+  /// - **Linux:** Does nothing in a system tray menu.
   ///
   Hide,
 
@@ -268,6 +355,7 @@ pub enum MenuItem {
   /// ## Platform-specific
   ///
   /// - **Android / iOS:** Unsupported
+  /// - **Linux:** Does nothing in a system tray menu.
   ///
   CloseWindow,
 
@@ -364,7 +452,8 @@ pub enum MenuItem {
 
 /// Custom menu item, when clicked an event is emitted in the EventLoop.
 ///
-/// You can modify the item after it's creation.
+/// You can modify the item after it's creation. If the menu it belongs to is attached to
+/// multiple windows, the modification is applied to all of them.
 #[derive(Debug, Clone)]
 pub struct CustomMenuItem(pub CustomMenuItemPlatform);
 
@@ -392,9 +481,12 @@ impl CustomMenuItem {
 
   /// Modifies the selected state of the menu item.
   ///
+  /// Selecting an item that is part of a radio group deselects the other items of the group.
+  ///
   /// ## Platform-specific
   ///
-  /// - **Linux:** Unsupported, render a regular item
+  /// - **Linux:** Only items built with [`MenuItemAttributes::with_selected`] or
+  ///   [`MenuItemAttributes::with_radio_group`] can be selected, the others render a regular item.
   pub fn set_selected(&mut self, is_selected: bool) {
     self.0.set_selected(is_selected)
   }
@@ -403,7 +495,8 @@ impl CustomMenuItem {
   ///
   /// ## Platform-specific
   ///
-  /// - **Windows / Linux:** Not implemented
+  /// - **Windows:** The icon is scaled to the small icon size.
+  /// - **Linux:** The icon is scaled to 16x16. The icons of check items are not shown in system tray menus.
   pub fn set_icon(&mut self, icon: Icon) {
     self.0.set_icon(icon)
   }
@@ -13,6 +13,8 @@
 //! [monitor_handle]: crate::monitor::MonitorHandle
 //! [loop_get]: crate::event_loop::EventLoopWindowTarget::available_monitors
 //! [window_get]: crate::window::Window::available_monitors
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use crate::event::Event;
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize},
   platform_impl,
@@ -152,12 +154,98 @@ impl MonitorHandle {
     self.inner.scale_factor()
   }
 
+  /// Returns the refresh rate of the current video mode of the monitor in millihertz, or `None`
+  /// if it isn't known.
+  ///
+  /// Unlike [`VideoMode::refresh_rate`], this isn't rounded, e.g. 59.94 Hz is `59940`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The display settings only report whole hertz.
+  /// - **iOS:** Returns the maximum frames per second of the screen.
+  /// - **Android:** Always returns `None`.
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    self.inner.refresh_rate_millihertz()
+  }
+
+  /// Returns an identifier shared by all monitors that mirror the same output, or `None` if the
+  /// monitor isn't part of a mirror set.
+  ///
+  /// Monitors with the same id show the same content, and should be treated as a single monitor,
+  /// e.g. when picking a monitor to go fullscreen on.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** The id is the display id of the mirror source, which is the monitor whose
+  ///   video modes are authoritative.
+  /// - **Linux:** Mirrored monitors are detected by their identical geometry.
+  /// - **Windows:** Always returns `None`, mirrored displays are reported as a single monitor.
+  /// - **iOS / Android:** Always returns `None`.
+  #[inline]
+  pub fn mirror_group_id(&self) -> Option<u32> {
+    self.inner.mirror_group_id()
+  }
+
   /// Returns all fullscreen video modes supported by this monitor.
   ///
   /// ## Platform-specific
-  /// - **Linux:** Unsupported. This will always return empty iterator.
+  /// - **Linux:** Lists the modes of the output through XRandR. On Wayland, only the current mode
+  ///   is returned, and switching to it doesn't change anything.
   #[inline]
   pub fn video_modes(&self) -> impl Iterator<Item = VideoMode> {
     self.inner.video_modes()
   }
 }
+
+/// The monitors and scale factors last reported, used to turn the "displays changed"
+/// notifications of the OS into monitor events.
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+#[derive(Debug, Default)]
+pub(crate) struct MonitorTracker {
+  monitors: Vec<(MonitorHandle, f64)>,
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+impl MonitorTracker {
+  pub(crate) fn new(monitors: impl IntoIterator<Item = platform_impl::MonitorHandle>) -> Self {
+    let mut tracker = MonitorTracker::default();
+    tracker.update::<()>(monitors);
+    tracker
+  }
+
+  /// Replaces the known monitors, returning the events describing what changed.
+  pub(crate) fn update<T: 'static>(
+    &mut self,
+    monitors: impl IntoIterator<Item = platform_impl::MonitorHandle>,
+  ) -> Vec<Event<'static, T>> {
+    let monitors: Vec<_> = monitors
+      .into_iter()
+      .map(|inner| {
+        let scale_factor = inner.scale_factor();
+        (MonitorHandle { inner }, scale_factor)
+      })
+      .collect();
+
+    let mut events = Vec::new();
+    for (monitor, _) in &self.monitors {
+      if !monitors.iter().any(|(m, _)| m == monitor) {
+        events.push(Event::MonitorDisconnected(monitor.clone()));
+      }
+    }
+    for (monitor, scale_factor) in &monitors {
+      match self.monitors.iter().find(|(m, _)| m == monitor) {
+        None => events.push(Event::MonitorConnected(monitor.clone())),
+        Some((_, old_scale_factor)) if old_scale_factor != scale_factor => {
+          events.push(Event::MonitorScaleFactorChanged {
+            monitor: monitor.clone(),
+            scale_factor: *scale_factor,
+          })
+        }
+        Some(_) => (),
+      }
+    }
+    self.monitors = monitors;
+    events
+  }
+}
@@ -19,13 +19,69 @@ pub trait EventLoopExtAndroid {}
 impl<T> EventLoopExtAndroid for EventLoop<T> {}
 
 /// Additional methods on `EventLoopWindowTarget` that are specific to Android.
-pub trait EventLoopWindowTargetExtAndroid {}
+pub trait EventLoopWindowTargetExtAndroid {
+  /// Returns the state of the activity in its lifecycle.
+  fn activity_state(&self) -> ActivityState;
+
+  /// Returns the state the activity saved before the process was killed, as set with
+  /// [`EventLoopWindowTargetExtAndroid::set_saved_state`], if it was recreated.
+  ///
+  /// The activity has to call the `restore(state: ByteArray)` native method that
+  /// `android_binding!` binds from `onCreate`, with the state saved in the bundle.
+  fn saved_state(&self) -> Option<Vec<u8>>;
+
+  /// Sets the state the activity saves when it may be killed, to restore it once recreated with
+  /// [`EventLoopWindowTargetExtAndroid::saved_state`].
+  ///
+  /// The state has to be kept up to date, since it is read from `onSaveInstanceState` on the UI
+  /// thread. The activity has to call the `persist(): ByteArray` native method that
+  /// `android_binding!` binds from there, and put the state in the bundle.
+  fn set_saved_state(&self, state: Option<Vec<u8>>);
+
+  /// Whether the activity is shown next to other activities, such as in split-screen.
+  fn is_in_multi_window_mode(&self) -> bool;
+
+  /// Whether the activity is shown in picture-in-picture mode.
+  fn is_in_picture_in_picture_mode(&self) -> bool;
+}
+
+/// The state of the activity in its lifecycle, see
+/// [`EventLoopWindowTargetExtAndroid::activity_state`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActivityState {
+  /// The activity has been created, but is not visible yet.
+  Created,
+  /// The activity is visible.
+  Started,
+  /// The activity is visible and in the foreground, after [`Event::Resumed`].
+  ///
+  /// [`Event::Resumed`]: crate::event::Event::Resumed
+  Resumed,
+  /// The activity is visible, but another one is in the foreground, after [`Event::Suspended`].
+  ///
+  /// [`Event::Suspended`]: crate::event::Event::Suspended
+  Paused,
+  /// The activity is no longer visible.
+  Stopped,
+  /// The activity is being destroyed.
+  Destroyed,
+}
 
 /// Additional methods on `Window` that are specific to Android.
 pub trait WindowExtAndroid {
   fn content_rect(&self) -> Rect;
 
   fn config(&self) -> Configuration;
+
+  /// Shows the soft keyboard.
+  ///
+  /// [`Event::ImeVisibilityChanged`](crate::event::Event::ImeVisibilityChanged) is emitted once
+  /// it is shown, with its height.
+  fn show_ime(&self);
+
+  /// Hides the soft keyboard.
+  fn hide_ime(&self);
 }
 
 impl WindowExtAndroid for Window {
@@ -36,9 +92,37 @@ impl WindowExtAndroid for Window {
   fn config(&self) -> Configuration {
     self.window.config()
   }
+
+  fn show_ime(&self) {
+    self.window.set_ime_visible(true)
+  }
+
+  fn hide_ime(&self) {
+    self.window.set_ime_visible(false)
+  }
 }
 
-impl<T> EventLoopWindowTargetExtAndroid for EventLoopWindowTarget<T> {}
+impl<T> EventLoopWindowTargetExtAndroid for EventLoopWindowTarget<T> {
+  fn activity_state(&self) -> ActivityState {
+    self.p.activity_state()
+  }
+
+  fn saved_state(&self) -> Option<Vec<u8>> {
+    self.p.saved_state()
+  }
+
+  fn set_saved_state(&self, state: Option<Vec<u8>>) {
+    self.p.set_saved_state(state)
+  }
+
+  fn is_in_multi_window_mode(&self) -> bool {
+    self.p.is_in_multi_window_mode()
+  }
+
+  fn is_in_picture_in_picture_mode(&self) -> bool {
+    self.p.is_in_picture_in_picture_mode()
+  }
+}
 
 /// Additional methods on `WindowBuilder` that are specific to Android.
 pub trait WindowBuilderExtAndroid {}
@@ -98,6 +98,27 @@ pub trait WindowExtIOS {
   /// and then calls
   /// [`-[UIViewController setNeedsStatusBarAppearanceUpdate]`](https://developer.apple.com/documentation/uikit/uiviewcontroller/1621354-setneedsstatusbarappearanceupdat?language=objc).
   fn set_prefers_status_bar_hidden(&self, hidden: bool);
+
+  /// Sets the style of the status bar, for the contrast with the content behind it.
+  ///
+  /// The default is [`StatusBarStyle::Default`].
+  ///
+  /// This changes the value returned by
+  /// [`-[UIViewController preferredStatusBarStyle]`](https://developer.apple.com/documentation/uikit/uiviewcontroller/1621416-preferredstatusbarstyle?language=objc),
+  /// and then calls
+  /// [`-[UIViewController setNeedsStatusBarAppearanceUpdate]`](https://developer.apple.com/documentation/uikit/uiviewcontroller/1621354-setneedsstatusbarappearanceupdat?language=objc).
+  fn set_status_bar_style(&self, style: StatusBarStyle);
+
+  /// Returns the insets of the [safe area] of the [`Window`] in physical pixels, which the status
+  /// bar, the notch and the home indicator don't cover.
+  ///
+  /// [`WindowEvent::SafeAreaInsetsChanged`](crate::event::WindowEvent::SafeAreaInsetsChanged)
+  /// is emitted when they change.
+  ///
+  /// The insets are all `0` before iOS 11.
+  ///
+  /// [safe area]: https://developer.apple.com/documentation/uikit/uiview/2891103-safeareainsets?language=objc
+  fn safe_area_insets(&self) -> SafeAreaInsets;
 }
 
 impl WindowExtIOS for Window {
@@ -142,6 +163,16 @@ impl WindowExtIOS for Window {
   fn set_prefers_status_bar_hidden(&self, hidden: bool) {
     self.window.set_prefers_status_bar_hidden(hidden)
   }
+
+  #[inline]
+  fn set_status_bar_style(&self, style: StatusBarStyle) {
+    self.window.set_status_bar_style(style)
+  }
+
+  #[inline]
+  fn safe_area_insets(&self) -> SafeAreaInsets {
+    self.window.safe_area_insets()
+  }
 }
 
 /// Additional methods on [`WindowBuilder`] that are specific to iOS.
@@ -199,6 +230,14 @@ pub trait WindowBuilderExtIOS {
   /// This sets the initial value returned by
   /// [`-[UIViewController prefersStatusBarHidden]`](https://developer.apple.com/documentation/uikit/uiviewcontroller/1621440-prefersstatusbarhidden?language=objc).
   fn with_prefers_status_bar_hidden(self, hidden: bool) -> WindowBuilder;
+
+  /// Sets the style of the status bar.
+  ///
+  /// The default is [`StatusBarStyle::Default`].
+  ///
+  /// This sets the initial value returned by
+  /// [`-[UIViewController preferredStatusBarStyle]`](https://developer.apple.com/documentation/uikit/uiviewcontroller/1621416-preferredstatusbarstyle?language=objc).
+  fn with_status_bar_style(self, style: StatusBarStyle) -> WindowBuilder;
 }
 
 impl WindowBuilderExtIOS for WindowBuilder {
@@ -242,6 +281,12 @@ impl WindowBuilderExtIOS for WindowBuilder {
     self.platform_specific.prefers_status_bar_hidden = hidden;
     self
   }
+
+  #[inline]
+  fn with_status_bar_style(mut self, style: StatusBarStyle) -> WindowBuilder {
+    self.platform_specific.status_bar_style = style;
+    self
+  }
 }
 
 /// Additional methods on [`MonitorHandle`] that are specific to iOS.
@@ -289,6 +334,39 @@ impl Default for ValidOrientations {
   }
 }
 
+/// The [style] of the status bar.
+///
+/// [style]: https://developer.apple.com/documentation/uikit/uistatusbarstyle?language=objc
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatusBarStyle {
+  /// Dark or light content depending on the interface style.
+  Default,
+
+  /// Light content, for dark backgrounds.
+  LightContent,
+
+  /// Dark content, for light backgrounds. Only on iOS 13 and later.
+  DarkContent,
+}
+
+impl Default for StatusBarStyle {
+  #[inline]
+  fn default() -> StatusBarStyle {
+    StatusBarStyle::Default
+  }
+}
+
+/// The insets of the safe area of a [`Window`] in physical pixels, see
+/// [`WindowExtIOS::safe_area_insets`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SafeAreaInsets {
+  pub top: u32,
+  pub left: u32,
+  pub bottom: u32,
+  pub right: u32,
+}
+
 /// The device [idiom].
 ///
 /// [idiom]: https://developer.apple.com/documentation/uikit/uidevice/1620037-userinterfaceidiom?language=objc
@@ -8,11 +8,11 @@ use std::os::raw::c_void;
 
 use crate::{
   dpi::LogicalSize,
-  event_loop::{EventLoop, EventLoopWindowTarget},
-  menu::CustomMenuItem,
+  event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget},
+  menu::{ContextMenu, CustomMenuItem},
   monitor::MonitorHandle,
-  platform_impl::{get_aux_state_mut, Parent},
-  window::{Window, WindowBuilder},
+  platform_impl::{get_aux_state_mut, set_dock_menu, Parent},
+  window::{Window, WindowBuilder, WindowId},
 };
 
 #[cfg(feature = "tray")]
@@ -55,6 +55,29 @@ pub trait WindowExtMacOS {
 
   /// Sets whether or not the window has shadow.
   fn set_has_shadow(&self, has_shadow: bool);
+
+  /// Returns the identifier of the windows this window can be merged with as tabs, see
+  /// [`WindowBuilderExtMacOS::with_tabbing_identifier`].
+  fn tabbing_identifier(&self) -> String;
+
+  /// Sets the identifier of the windows this window can be merged with as tabs.
+  fn set_tabbing_identifier(&self, identifier: &str);
+
+  /// Selects the tab after the tab of this window, if it is in a tab group.
+  fn select_next_tab(&self);
+
+  /// Selects the tab before the tab of this window, if it is in a tab group.
+  fn select_previous_tab(&self);
+
+  /// Selects the tab at `index` in the tab group of this window, if there is one.
+  fn select_tab_at_index(&self, index: usize);
+
+  /// Returns the windows of the tab group of this window in the order of their tabs, including
+  /// this window, or `None` if it isn't tabbed.
+  ///
+  /// [`WindowEvent::TabGroupChanged`](crate::event::WindowEvent::TabGroupChanged) is emitted
+  /// when the user merges or separates tabs.
+  fn tabbed_windows(&self) -> Option<Vec<WindowId>>;
 }
 
 impl WindowExtMacOS for Window {
@@ -87,6 +110,36 @@ impl WindowExtMacOS for Window {
   fn set_has_shadow(&self, has_shadow: bool) {
     self.window.set_has_shadow(has_shadow)
   }
+
+  #[inline]
+  fn tabbing_identifier(&self) -> String {
+    self.window.tabbing_identifier()
+  }
+
+  #[inline]
+  fn set_tabbing_identifier(&self, identifier: &str) {
+    self.window.set_tabbing_identifier(identifier)
+  }
+
+  #[inline]
+  fn select_next_tab(&self) {
+    self.window.select_next_tab()
+  }
+
+  #[inline]
+  fn select_previous_tab(&self) {
+    self.window.select_previous_tab()
+  }
+
+  #[inline]
+  fn select_tab_at_index(&self, index: usize) {
+    self.window.select_tab_at_index(index)
+  }
+
+  #[inline]
+  fn tabbed_windows(&self) -> Option<Vec<WindowId>> {
+    self.window.tabbed_windows()
+  }
 }
 
 /// Corresponds to `NSApplicationActivationPolicy`.
@@ -339,6 +392,16 @@ pub trait WindowBuilderExtMacOS {
   fn with_resize_increments(self, increments: LogicalSize<f64>) -> WindowBuilder;
   fn with_disallow_hidpi(self, disallow_hidpi: bool) -> WindowBuilder;
   fn with_has_shadow(self, has_shadow: bool) -> WindowBuilder;
+  /// Sets the identifier of the windows the window can be merged with as tabs, such as the
+  /// windows of the same kind of document. Windows without an identifier are merged with the
+  /// other windows of the application.
+  fn with_tabbing_identifier(self, identifier: &str) -> WindowBuilder;
+  /// Backs the window with a non-activating panel, which can accept keyboard input without
+  /// activating the application, so the frontmost application keeps its activation, such as
+  /// for launcher windows.
+  ///
+  /// Ignored for tool windows, which are already non-activating panels.
+  fn with_panel(self, panel: bool) -> WindowBuilder;
 }
 
 impl WindowBuilderExtMacOS for WindowBuilder {
@@ -404,6 +467,21 @@ impl WindowBuilderExtMacOS for WindowBuilder {
     self.platform_specific.has_shadow = has_shadow;
     self
   }
+
+  #[inline]
+  fn with_tabbing_identifier(mut self, identifier: &str) -> WindowBuilder {
+    self
+      .platform_specific
+      .tabbing_identifier
+      .replace(identifier.into());
+    self
+  }
+
+  #[inline]
+  fn with_panel(mut self, panel: bool) -> WindowBuilder {
+    self.platform_specific.panel = panel;
+    self
+  }
 }
 
 pub trait EventLoopExtMacOS {
@@ -425,6 +503,16 @@ pub trait EventLoopExtMacOS {
   /// [`run`](crate::event_loop::EventLoop::run) or
   /// [`run_return`](crate::platform::run_return::EventLoopExtRunReturn::run_return)
   fn enable_default_menu_creation(&mut self, enable: bool);
+
+  /// Sets a hook that is called with every `NSEvent` sent to the application, before it is
+  /// dispatched.
+  ///
+  /// Returning `true` marks the event as handled, and it won't be dispatched any further.
+  /// This is meant for integrations that need raw access to the events; prefer the regular
+  /// events whenever they cover your use case.
+  fn set_event_hook<F>(&mut self, hook: F)
+  where
+    F: FnMut(*mut c_void) -> bool + 'static;
 }
 impl<T> EventLoopExtMacOS for EventLoop<T> {
   #[inline]
@@ -440,6 +528,40 @@ impl<T> EventLoopExtMacOS for EventLoop<T> {
       get_aux_state_mut(&**self.event_loop.delegate).create_default_menu = enable;
     }
   }
+
+  #[inline]
+  fn set_event_hook<F>(&mut self, hook: F)
+  where
+    F: FnMut(*mut c_void) -> bool + 'static,
+  {
+    self.event_loop.set_event_hook(hook)
+  }
+}
+
+/// Additional methods on `EventLoopBuilder` that are specific to MacOS.
+pub trait EventLoopBuilderExtMacOS {
+  /// Sets the activation policy for the application. It is set to
+  /// `NSApplicationActivationPolicyRegular` by default.
+  fn with_activation_policy(&mut self, activation_policy: ActivationPolicy) -> &mut Self;
+
+  /// Used to control whether a default menubar menu is created.
+  ///
+  /// The default menu creation is enabled by default.
+  fn with_default_menu(&mut self, enable: bool) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtMacOS for EventLoopBuilder<T> {
+  #[inline]
+  fn with_activation_policy(&mut self, activation_policy: ActivationPolicy) -> &mut Self {
+    self.platform_specific.activation_policy = activation_policy;
+    self
+  }
+
+  #[inline]
+  fn with_default_menu(&mut self, enable: bool) -> &mut Self {
+    self.platform_specific.default_menu = enable;
+    self
+  }
 }
 
 /// Additional methods on `MonitorHandle` that are specific to MacOS.
@@ -475,6 +597,12 @@ pub trait EventLoopWindowTargetExtMacOS {
   /// To set the activation policy before the app starts running, see
   /// [`EventLoopExtMacOS::set_activation_policy`](crate::platform::macos::EventLoopExtMacOS::set_activation_policy).
   fn set_activation_policy_at_runtime(&self, activation_policy: ActivationPolicy);
+  /// Sets the menu shown when right clicking the application icon in the Dock, above the
+  /// items macOS adds itself. `None` removes it.
+  ///
+  /// Clicking one of its items emits an [`Event::MenuEvent`](crate::event::Event::MenuEvent)
+  /// with a [`MenuType::ContextMenu`](crate::menu::MenuType::ContextMenu) origin.
+  fn set_dock_menu(&self, menu: Option<ContextMenu>);
 }
 
 impl<T> EventLoopWindowTargetExtMacOS for EventLoopWindowTarget<T> {
@@ -502,6 +630,10 @@ impl<T> EventLoopWindowTargetExtMacOS for EventLoopWindowTarget<T> {
     let ns_activation_policy: NSApplicationActivationPolicy = activation_policy.into();
     unsafe { msg_send![app, setActivationPolicy: ns_activation_policy] }
   }
+
+  fn set_dock_menu(&self, menu: Option<ContextMenu>) {
+    set_dock_menu(menu.map(|menu| menu.0.menu_platform.menu))
+  }
 }
 
 #[cfg(feature = "tray")]
@@ -6,7 +6,7 @@
 
 use crate::{
   event::Event,
-  event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+  event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget, MotionCoalescer},
 };
 
 /// Additional methods on `EventLoop` to return control flow to the caller.
@@ -19,6 +19,31 @@ pub trait EventLoopExtRunReturn {
   /// Unlike `run`, this function accepts non-`'static` (i.e. non-`move`) closures and returns
   /// control flow to the caller when `control_flow` is set to `ControlFlow::Exit`.
   ///
+  /// The return value is the exit code the callback set through `ControlFlow::ExitWithCode`
+  /// (`0` for `ControlFlow::Exit`), so a wrapping `main` can forward it to
+  /// [`std::process::exit`]:
+  ///
+  /// ```no_run
+  /// use tao::{
+  ///     event::{Event, WindowEvent},
+  ///     event_loop::{ControlFlow, EventLoop},
+  ///     platform::run_return::EventLoopExtRunReturn,
+  /// };
+  ///
+  /// let mut event_loop = EventLoop::new();
+  /// let exit_code = event_loop.run_return(|event, _, control_flow| {
+  ///     *control_flow = ControlFlow::Wait;
+  ///     if let Event::WindowEvent {
+  ///         event: WindowEvent::CloseRequested,
+  ///         ..
+  ///     } = event
+  ///     {
+  ///         *control_flow = ControlFlow::ExitWithCode(3);
+  ///     }
+  /// });
+  /// std::process::exit(exit_code);
+  /// ```
+  ///
   /// # Caveats
   /// Despite its appearance at first glance, this is *not* a perfect replacement for
   /// `poll_events`. For example, this function will not return on Windows or macOS while a
@@ -41,10 +66,15 @@ pub trait EventLoopExtRunReturn {
 impl<T> EventLoopExtRunReturn for EventLoop<T> {
   type UserEvent = T;
 
-  fn run_return<F>(&mut self, event_handler: F) -> i32
+  fn run_return<F>(&mut self, mut event_handler: F) -> i32
   where
     F: FnMut(Event<'_, Self::UserEvent>, &EventLoopWindowTarget<Self::UserEvent>, &mut ControlFlow),
   {
-    self.event_loop.run_return(event_handler)
+    let mut coalescer = MotionCoalescer::default();
+    self
+      .event_loop
+      .run_return(|event, window_target, control_flow| {
+        coalescer.dispatch(event, window_target, control_flow, &mut event_handler)
+      })
   }
 }
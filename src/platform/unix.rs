@@ -18,11 +18,15 @@ pub use crate::platform_impl::x11;
 
 pub use crate::platform_impl::{hit_test, EventLoop as UnixEventLoop};
 use crate::{
-  event_loop::{EventLoop, EventLoopWindowTarget},
+  error::ExternalError,
+  event_loop::{EventLoop, EventLoopBuilder, EventLoopWindowTarget},
   platform_impl::{x11::xdisplay::XError, Parent},
   window::{Window, WindowBuilder},
 };
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use self::x11::xdisplay::XConnection;
 
 /// Additional methods on `Window` that are specific to Unix.
@@ -32,6 +36,26 @@ pub trait WindowExtUnix {
 
   /// Whether to show the window icon in the taskbar or not.
   fn set_skip_taskbar(&self, skip: bool);
+
+  /// Sets the instance and class names of the window, see
+  /// [`WindowBuilderExtUnix::with_class`].
+  ///
+  /// Window managers usually read them only once, when the window is mapped.
+  fn set_class(&self, instance: &str, class: &str);
+
+  /// Sets the role of the window, see [`WindowBuilderExtUnix::with_role`].
+  fn set_role(&self, role: &str);
+
+  /// Requests a new activation token from the desktop, to launch another app that should get the
+  /// focus when it opens a window, in spite of the focus stealing prevention of the window
+  /// manager.
+  ///
+  /// Pass it to the launched process in the `XDG_ACTIVATION_TOKEN` environment variable on
+  /// Wayland, and `DESKTOP_STARTUP_ID` on X11. Apps made with tao consume both, so that their
+  /// first visible window gets the focus.
+  ///
+  /// On Wayland, compositors only honor tokens requested while the window has the focus.
+  fn request_activation_token(&self) -> Result<String, ExternalError>;
 }
 
 impl WindowExtUnix for Window {
@@ -42,6 +66,18 @@ impl WindowExtUnix for Window {
   fn set_skip_taskbar(&self, skip: bool) {
     self.window.set_skip_taskbar(skip);
   }
+
+  fn set_class(&self, instance: &str, class: &str) {
+    self.window.set_class(instance.into(), class.into());
+  }
+
+  fn set_role(&self, role: &str) {
+    self.window.set_role(role.into());
+  }
+
+  fn request_activation_token(&self) -> Result<String, ExternalError> {
+    self.window.request_activation_token()
+  }
 }
 
 pub trait WindowBuilderExtUnix {
@@ -57,6 +93,58 @@ pub trait WindowBuilderExtUnix {
   /// For anyone who wants to draw the background themselves, set this to `false`.
   /// Default is `true`.
   fn with_transparent_draw(self, draw: bool) -> WindowBuilder;
+
+  /// Creates the window as a [layer-shell] surface, for panels, docks, wallpapers and overlays.
+  ///
+  /// This requires Wayland, a compositor implementing the protocol and the
+  /// [`gtk-layer-shell`](https://github.com/wmww/gtk-layer-shell) library, which is loaded at
+  /// runtime. Window creation fails with an [`OsError`](crate::error::OsError) otherwise.
+  ///
+  /// The compositor places the surface, so the position of the window is ignored.
+  ///
+  /// [layer-shell]: https://wayland.app/protocols/wlr-layer-shell-unstable-v1
+  fn with_layer_shell(self, attributes: LayerShellAttributes) -> WindowBuilder;
+
+  /// Sets the instance and class names of the `WM_CLASS` hint of the window, which window
+  /// managers and taskbars match their rules against, and group windows with.
+  ///
+  /// The default is the program name for both, as set by GTK.
+  ///
+  /// This only applies to X11. Wayland compositors use the application id of the
+  /// [`gtk::Application`] instead.
+  fn with_class(self, instance: String, class: String) -> WindowBuilder;
+
+  /// Sets the `WM_WINDOW_ROLE` hint of the window, which tells apart the windows of an
+  /// application that have the same class, such as a main window and a preferences window.
+  ///
+  /// This only applies to X11.
+  fn with_role(self, role: String) -> WindowBuilder;
+
+  /// Sets the type of the window, which tells the window manager how to decorate, place and
+  /// stack it, such as splash screens or docks.
+  ///
+  /// The default is [`WindowType::Normal`], or [`WindowType::Utility`] for tool windows.
+  ///
+  /// On X11, this is the `_NET_WM_WINDOW_TYPE` hint. Wayland has no window types, and GTK
+  /// only uses them there to pick the surface of menus and tooltips.
+  fn with_window_type(self, window_type: WindowType) -> WindowBuilder;
+
+  /// Uses a GTK header bar as the titlebar of the window, drawn by the application instead of the
+  /// window manager, with its own buttons next to the window controls.
+  ///
+  /// Clicking a button of the header bar sends a
+  /// [`WindowEvent::HeaderBarButtonClicked`](crate::event::WindowEvent::HeaderBarButtonClicked)
+  /// with its id.
+  ///
+  /// The header bar is hidden with the other decorations when they are disabled.
+  fn with_header_bar(self, attributes: HeaderBarAttributes) -> WindowBuilder;
+
+  /// Enables or disables drag and drop support (enabled by default).
+  ///
+  /// When disabled, the window doesn't accept drops and doesn't report
+  /// [`WindowEvent::DragDrop`](crate::event::WindowEvent::DragDrop), leaving them to the widgets
+  /// added to it.
+  fn with_drag_and_drop(self, flag: bool) -> WindowBuilder;
 }
 
 impl WindowBuilderExtUnix for WindowBuilder {
@@ -74,6 +162,212 @@ impl WindowBuilderExtUnix for WindowBuilder {
     self.platform_specific.auto_transparent = draw;
     self
   }
+
+  fn with_layer_shell(mut self, attributes: LayerShellAttributes) -> WindowBuilder {
+    self.platform_specific.layer_shell = Some(attributes);
+    self
+  }
+
+  fn with_class(mut self, instance: String, class: String) -> WindowBuilder {
+    self.platform_specific.class = Some((instance, class));
+    self
+  }
+
+  fn with_role(mut self, role: String) -> WindowBuilder {
+    self.platform_specific.role = Some(role);
+    self
+  }
+
+  fn with_window_type(mut self, window_type: WindowType) -> WindowBuilder {
+    self.platform_specific.window_type = Some(window_type);
+    self
+  }
+
+  fn with_header_bar(mut self, attributes: HeaderBarAttributes) -> WindowBuilder {
+    self.platform_specific.header_bar = Some(attributes);
+    self
+  }
+
+  fn with_drag_and_drop(mut self, flag: bool) -> WindowBuilder {
+    self.platform_specific.drag_and_drop = flag;
+    self
+  }
+}
+
+/// The attributes of a header bar, see [`WindowBuilderExtUnix::with_header_bar`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HeaderBarAttributes {
+  /// The title shown in the header bar.
+  ///
+  /// The default is the title of the window. [`Window::set_title`] replaces it either way.
+  pub title: Option<String>,
+
+  /// The subtitle shown below the title.
+  ///
+  /// The default is no subtitle.
+  pub subtitle: Option<String>,
+
+  /// The buttons packed in the header bar, in order from the packed side.
+  ///
+  /// The default is no buttons.
+  pub buttons: Vec<HeaderBarButton>,
+}
+
+/// A button of a header bar.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HeaderBarButton {
+  /// The id sent with [`WindowEvent::HeaderBarButtonClicked`](crate::event::WindowEvent::HeaderBarButtonClicked)
+  /// when the button is clicked.
+  pub id: u32,
+
+  /// The label of the button, shown next to the icon if there is one.
+  pub label: Option<String>,
+
+  /// The name of the icon of the button in the icon theme, such as `"open-menu-symbolic"`.
+  pub icon_name: Option<String>,
+
+  /// The tooltip of the button.
+  pub tooltip: Option<String>,
+
+  /// The side of the header bar the button is packed on.
+  pub pack: HeaderBarPack,
+}
+
+/// The side of a header bar a button is packed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HeaderBarPack {
+  Start,
+  End,
+}
+
+impl Default for HeaderBarPack {
+  fn default() -> Self {
+    HeaderBarPack::Start
+  }
+}
+
+/// The type of a window, see [`WindowBuilderExtUnix::with_window_type`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WindowType {
+  /// A normal top-level window.
+  Normal,
+  /// A dialog, usually transient for another window.
+  Dialog,
+  /// A small persistent window, such as a palette or toolbox.
+  Utility,
+  /// A toolbar torn off from the main window.
+  Toolbar,
+  /// A menu torn off from the main window.
+  Menu,
+  /// A menu opened from a menu bar.
+  DropdownMenu,
+  /// A context menu.
+  PopupMenu,
+  /// The popup of a combo box.
+  Combo,
+  /// A tooltip.
+  Tooltip,
+  /// A notification bubble.
+  Notification,
+  /// A splash screen shown while the application starts.
+  Splash,
+  /// The icon dragged during drag and drop.
+  Dnd,
+  /// A dock or panel, kept on top of the other windows.
+  Dock,
+  /// The desktop background, kept below all the other windows.
+  Desktop,
+}
+
+impl Default for WindowType {
+  fn default() -> Self {
+    WindowType::Normal
+  }
+}
+
+/// The layer a layer-shell surface is stacked in, from the bottom to the top.
+///
+/// Normal windows are drawn between [`Layer::Bottom`] and [`Layer::Top`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Layer {
+  Background,
+  Bottom,
+  Top,
+  Overlay,
+}
+
+impl Default for Layer {
+  fn default() -> Self {
+    Layer::Top
+  }
+}
+
+bitflags! {
+  /// The edges of the output a layer-shell surface is anchored to.
+  ///
+  /// A surface anchored to two opposite edges is stretched between them.
+  #[derive(Default)]
+  #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+  pub struct Anchor: u8 {
+    const TOP = 1 << 0;
+    const BOTTOM = 1 << 1;
+    const LEFT = 1 << 2;
+    const RIGHT = 1 << 3;
+  }
+}
+
+/// How a layer-shell surface receives keyboard input.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyboardInteractivity {
+  /// The surface never gets the keyboard focus.
+  None,
+  /// The surface grabs the keyboard focus while it is mapped.
+  Exclusive,
+  /// The surface gets the keyboard focus like a normal window, when it is clicked.
+  ///
+  /// Versions of `gtk-layer-shell` older than 0.6 fall back to [`KeyboardInteractivity::Exclusive`].
+  OnDemand,
+}
+
+impl Default for KeyboardInteractivity {
+  fn default() -> Self {
+    KeyboardInteractivity::None
+  }
+}
+
+/// The attributes of a layer-shell surface, see [`WindowBuilderExtUnix::with_layer_shell`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LayerShellAttributes {
+  /// The layer the surface is stacked in.
+  ///
+  /// The default is [`Layer::Top`].
+  pub layer: Layer,
+
+  /// The edges the surface is anchored to. It is centered on the axes it isn't anchored on.
+  ///
+  /// The default is no edge.
+  pub anchor: Anchor,
+
+  /// The space in logical pixels, from the anchored edge, that other surfaces should not cover,
+  /// such as the height of a panel. `0` lets other surfaces move out of the way of exclusive
+  /// zones without reserving any, and `-1` ignores them.
+  ///
+  /// The default is `0`.
+  pub exclusive_zone: i32,
+
+  /// How the surface receives keyboard input.
+  ///
+  /// The default is [`KeyboardInteractivity::None`].
+  pub keyboard_interactivity: KeyboardInteractivity,
 }
 
 /// Additional methods on `EventLoop` that are specific to Unix.
@@ -87,17 +381,79 @@ pub trait EventLoopExtUnix {
     Self: Sized;
 }
 
-fn wrap_ev<T>(event_loop: UnixEventLoop<T>) -> EventLoop<T> {
-  EventLoop {
-    event_loop,
-    _marker: std::marker::PhantomData,
+impl<T> EventLoopExtUnix for EventLoop<T> {
+  #[inline]
+  fn new_any_thread() -> Self {
+    EventLoopBuilder::with_user_event()
+      .with_any_thread(true)
+      .build()
   }
 }
 
-impl<T> EventLoopExtUnix for EventLoop<T> {
+/// Additional methods on `EventLoopBuilder` that are specific to Unix.
+pub trait EventLoopBuilderExtUnix {
+  /// Whether to allow the event loop to be created off of the main thread.
+  ///
+  /// By default, the event loop can only be created on the main thread.
+  fn with_any_thread(&mut self, any_thread: bool) -> &mut Self;
+
+  /// Sets the application id of the underlying `GtkApplication`, which is used by the desktop
+  /// to match windows with their `.desktop` file.
+  ///
+  /// The application is registered with `G_APPLICATION_NON_UNIQUE`, so the id isn't owned on
+  /// the session bus and later instances start normally. Use
+  /// [`EventLoopBuilderExtUnix::with_dbus_name`] to own it.
+  ///
+  /// It must be a valid application identifier, such as `com.example.App`, see
+  /// `g_application_id_is_valid`.
+  fn with_app_id<S: Into<String>>(&mut self, app_id: S) -> &mut Self;
+
+  /// Sets the application id of the underlying `GtkApplication`, like
+  /// [`EventLoopBuilderExtUnix::with_app_id`], and owns it as a well-known name on the session
+  /// bus. It takes precedence over the application id.
+  ///
+  /// When another process already owns the name, the application doesn't own it either, as with
+  /// [`EventLoopBuilderExtUnix::with_app_id`], rather than forwarding its activation to that
+  /// process. Use [`EventLoopBuilder::with_single_instance`] to forward the arguments to the
+  /// running instance instead.
+  ///
+  /// [`EventLoopBuilder::with_single_instance`]: crate::event_loop::EventLoopBuilder::with_single_instance
+  fn with_dbus_name<S: Into<String>>(&mut self, name: S) -> &mut Self;
+
+  /// Whether to report the command-line arguments that are URLs with a scheme or paths of
+  /// existing files as [`Event::Opened`], once after [`StartCause::Init`].
+  ///
+  /// This is how the desktop passes the files and the URLs of the schemes registered in the
+  /// `.desktop` file. Disabled by default, since the arguments may mean something else.
+  ///
+  /// [`Event::Opened`]: crate::event::Event::Opened
+  /// [`StartCause::Init`]: crate::event::StartCause::Init
+  fn with_opened_urls_from_args(&mut self, enabled: bool) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtUnix for EventLoopBuilder<T> {
   #[inline]
-  fn new_any_thread() -> Self {
-    wrap_ev(UnixEventLoop::new_any_thread())
+  fn with_any_thread(&mut self, any_thread: bool) -> &mut Self {
+    self.platform_specific.any_thread = any_thread;
+    self
+  }
+
+  #[inline]
+  fn with_app_id<S: Into<String>>(&mut self, app_id: S) -> &mut Self {
+    self.platform_specific.app_id = Some(app_id.into());
+    self
+  }
+
+  #[inline]
+  fn with_dbus_name<S: Into<String>>(&mut self, name: S) -> &mut Self {
+    self.platform_specific.dbus_name = Some(name.into());
+    self
+  }
+
+  #[inline]
+  fn with_opened_urls_from_args(&mut self, enabled: bool) -> &mut Self {
+    self.platform_specific.opened_urls_from_args = enabled;
+    self
   }
 }
 
@@ -4,20 +4,24 @@
 
 #![cfg(target_os = "windows")]
 
-use std::path::Path;
+use std::{
+  path::{Path, PathBuf},
+  time::Duration,
+};
 
 pub use crate::platform_impl::hit_test;
 use crate::{
   dpi::PhysicalSize,
+  error::ExternalError,
   event::DeviceId,
-  event_loop::EventLoop,
+  event_loop::{EventLoop, EventLoopBuilder},
   monitor::MonitorHandle,
-  platform_impl::{EventLoop as WindowsEventLoop, Parent, WinIcon},
+  platform_impl::{Parent, WinIcon},
   window::{BadIcon, Icon, Theme, Window, WindowBuilder},
 };
 use libc;
 use windows::Win32::{
-  Foundation::HWND,
+  Foundation::{HWND, LPARAM, LRESULT, WPARAM},
   UI::{Input::KeyboardAndMouse::*, WindowsAndMessaging::*},
 };
 
@@ -46,31 +50,136 @@ pub trait EventLoopExtWindows {
   fn new_dpi_unaware_any_thread() -> Self
   where
     Self: Sized;
+
+  /// Sets a hook that is called with every message retrieved by the event loop, before it is
+  /// translated and dispatched.
+  ///
+  /// Returning `true` marks the message as handled, and it won't be dispatched to any window.
+  /// This is meant for integrations that need raw access to the message pump; prefer the
+  /// regular events whenever they cover your use case.
+  fn set_msg_hook<F>(&mut self, hook: F)
+  where
+    F: FnMut(*const MSG) -> bool + 'static;
+
+  /// Keeps delivering [`Event::UserEvent`]s while the user moves or resizes a window, by
+  /// checking for pending ones every `interval`. `None`, the default, turns this off.
+  ///
+  /// Windows runs a modal loop for the whole drag, during which events sent through an
+  /// [`EventLoopProxy`] may otherwise be held back until the mouse button is released.
+  ///
+  /// The timer only fires once no other messages are pending, so user events are always
+  /// delivered after the [`WindowEvent::Moved`] and [`WindowEvent::Resized`] events of the drag
+  /// that are already queued, and are followed by the usual `MainEventsCleared` and
+  /// `RedrawEventsCleared` events.
+  ///
+  /// [`Event::UserEvent`]: crate::event::Event::UserEvent
+  /// [`EventLoopProxy`]: crate::event_loop::EventLoopProxy
+  /// [`WindowEvent::Moved`]: crate::event::WindowEvent::Moved
+  /// [`WindowEvent::Resized`]: crate::event::WindowEvent::Resized
+  fn set_size_move_user_event_interval(&mut self, interval: Option<Duration>);
 }
 
 impl<T> EventLoopExtWindows for EventLoop<T> {
   #[inline]
   fn new_any_thread() -> Self {
-    EventLoop {
-      event_loop: WindowsEventLoop::new_any_thread(),
-      _marker: ::std::marker::PhantomData,
-    }
+    EventLoopBuilder::with_user_event()
+      .with_any_thread(true)
+      .build()
   }
 
   #[inline]
   fn new_dpi_unaware() -> Self {
-    EventLoop {
-      event_loop: WindowsEventLoop::new_dpi_unaware(),
-      _marker: ::std::marker::PhantomData,
-    }
+    EventLoopBuilder::with_user_event()
+      .with_dpi_aware(false)
+      .build()
   }
 
   #[inline]
   fn new_dpi_unaware_any_thread() -> Self {
-    EventLoop {
-      event_loop: WindowsEventLoop::new_dpi_unaware_any_thread(),
-      _marker: ::std::marker::PhantomData,
-    }
+    EventLoopBuilder::with_user_event()
+      .with_any_thread(true)
+      .with_dpi_aware(false)
+      .build()
+  }
+
+  #[inline]
+  fn set_msg_hook<F>(&mut self, hook: F)
+  where
+    F: FnMut(*const MSG) -> bool + 'static,
+  {
+    self.event_loop.set_msg_hook(hook)
+  }
+
+  #[inline]
+  fn set_size_move_user_event_interval(&mut self, interval: Option<Duration>) {
+    self.event_loop.set_size_move_user_event_interval(interval)
+  }
+}
+
+/// Additional methods on `EventLoopBuilder` that are specific to Windows.
+pub trait EventLoopBuilderExtWindows {
+  /// Whether to allow the event loop to be created off of the main thread.
+  ///
+  /// By default, the event loop can only be created on the main thread.
+  ///
+  /// # `Window` caveats
+  ///
+  /// Note that any `Window` created on the new thread will be destroyed when the thread
+  /// terminates. Attempting to use a `Window` after its parent thread terminates has
+  /// unspecified, although explicitly not undefined, behavior.
+  fn with_any_thread(&mut self, any_thread: bool) -> &mut Self;
+
+  /// Whether to enable process-wide DPI awareness.
+  ///
+  /// By default, tao will attempt to enable process-wide DPI awareness. If
+  /// that's undesirable, you can disable it with this function.
+  fn with_dpi_aware(&mut self, dpi_aware: bool) -> &mut Self;
+
+  /// Sets a hook that is called with every message retrieved by the event loop, before it is
+  /// translated and dispatched.
+  ///
+  /// See [`EventLoopExtWindows::set_msg_hook`] for details.
+  fn with_msg_hook<F>(&mut self, hook: F) -> &mut Self
+  where
+    F: FnMut(*const MSG) -> bool + 'static;
+
+  /// Whether to report the command-line arguments that are URLs with a scheme or paths of
+  /// existing files as [`Event::Opened`], once after [`StartCause::Init`].
+  ///
+  /// This is how the shell passes the files and the URLs of the schemes registered for the
+  /// application. Disabled by default, since the arguments may mean something else.
+  ///
+  /// [`Event::Opened`]: crate::event::Event::Opened
+  /// [`StartCause::Init`]: crate::event::StartCause::Init
+  fn with_opened_urls_from_args(&mut self, enabled: bool) -> &mut Self;
+}
+
+impl<T> EventLoopBuilderExtWindows for EventLoopBuilder<T> {
+  #[inline]
+  fn with_any_thread(&mut self, any_thread: bool) -> &mut Self {
+    self.platform_specific.any_thread = any_thread;
+    self
+  }
+
+  #[inline]
+  fn with_dpi_aware(&mut self, dpi_aware: bool) -> &mut Self {
+    self.platform_specific.dpi_aware = dpi_aware;
+    self
+  }
+
+  #[inline]
+  fn with_msg_hook<F>(&mut self, hook: F) -> &mut Self
+  where
+    F: FnMut(*const MSG) -> bool + 'static,
+  {
+    self.platform_specific.msg_hook = Some(Box::new(hook));
+    self
+  }
+
+  #[inline]
+  fn with_opened_urls_from_args(&mut self, enabled: bool) -> &mut Self {
+    self.platform_specific.opened_urls_from_args = enabled;
+    self
   }
 }
 
@@ -116,6 +225,29 @@ pub trait WindowExtWindows {
 
   /// Whether to show the window icon in the taskbar or not.
   fn set_skip_taskbar(&self, skip: bool);
+
+  /// Sets a hook called with the Win32 messages of the window before tao handles them, such as
+  /// `WM_COPYDATA` or custom messages tao doesn't surface. Replaces the previous hook, if any.
+  ///
+  /// When the hook returns `Some`, the message is considered handled and the value is returned
+  /// to the system without tao seeing the message, so the hook should return `None` for the
+  /// messages tao relies on. `WM_NCDESTROY` is always handled by tao.
+  ///
+  /// The hook is called on the thread of the event loop. Messages sent while it runs, such as
+  /// with `SendMessageW` from the hook, don't reach it.
+  fn set_subclass_hook<F>(&self, hook: F)
+  where
+    F: FnMut(HWND, u32, WPARAM, LPARAM) -> Option<LRESULT> + Send + 'static;
+
+  /// Removes the hook set with [`WindowExtWindows::set_subclass_hook`].
+  fn remove_subclass_hook(&self);
+
+  /// Sets the buttons of the thumbnail toolbar shown in the taskbar preview of the window,
+  /// replacing the previous ones. At most 7 buttons are shown, the others are ignored.
+  ///
+  /// Clicks are reported with
+  /// [`WindowEvent::ThumbBarButtonClicked`](crate::event::WindowEvent::ThumbBarButtonClicked).
+  fn set_thumbbar_buttons(&self, buttons: Vec<ThumbButton>);
 }
 
 impl WindowExtWindows for Window {
@@ -160,6 +292,24 @@ impl WindowExtWindows for Window {
   fn set_skip_taskbar(&self, skip: bool) {
     self.window.set_skip_taskbar(skip);
   }
+
+  #[inline]
+  fn set_subclass_hook<F>(&self, hook: F)
+  where
+    F: FnMut(HWND, u32, WPARAM, LPARAM) -> Option<LRESULT> + Send + 'static,
+  {
+    self.window.set_subclass_hook(Some(Box::new(hook)));
+  }
+
+  #[inline]
+  fn remove_subclass_hook(&self) {
+    self.window.set_subclass_hook(None);
+  }
+
+  #[inline]
+  fn set_thumbbar_buttons(&self, buttons: Vec<ThumbButton>) {
+    self.window.set_thumbbar_buttons(buttons);
+  }
 }
 
 /// Additional methods on `WindowBuilder` that are specific to Windows.
@@ -324,3 +474,151 @@ impl IconExtWindows for Icon {
     Ok(Icon { inner: win_icon })
   }
 }
+
+/// A button of the thumbnail toolbar of a window, see
+/// [`WindowExtWindows::set_thumbbar_buttons`].
+#[derive(Debug, Clone)]
+pub struct ThumbButton {
+  /// The id sent with [`WindowEvent::ThumbBarButtonClicked`](crate::event::WindowEvent::ThumbBarButtonClicked)
+  /// when the button is clicked.
+  pub id: u32,
+  /// The icon of the button, typically 16x16.
+  pub icon: Icon,
+  /// The tooltip of the button.
+  pub tooltip: String,
+  /// Whether the button can be clicked. Defaults to `true`.
+  pub enabled: bool,
+  /// Whether the taskbar preview is closed when the button is clicked. Defaults to `false`.
+  pub dismiss_on_click: bool,
+}
+
+impl ThumbButton {
+  /// Creates an enabled button.
+  pub fn new<T: Into<String>>(id: u32, icon: Icon, tooltip: T) -> Self {
+    Self {
+      id,
+      icon,
+      tooltip: tooltip.into(),
+      enabled: true,
+      dismiss_on_click: false,
+    }
+  }
+
+  /// Sets whether the button can be clicked.
+  pub fn with_enabled(mut self, enabled: bool) -> Self {
+    self.enabled = enabled;
+    self
+  }
+
+  /// Sets whether the taskbar preview is closed when the button is clicked.
+  pub fn with_dismiss_on_click(mut self, dismiss_on_click: bool) -> Self {
+    self.dismiss_on_click = dismiss_on_click;
+    self
+  }
+}
+
+/// A task of a [`JumpList`], which starts a new instance of the application with arguments.
+///
+/// The task isn't delivered to an instance that is already running. To handle it there, call
+/// [`EventLoopWindowTarget::enable_single_instance`] at startup: the new instance then forwards
+/// its arguments to the running one as [`Event::NewInstance`], and should exit.
+///
+/// [`EventLoopWindowTarget::enable_single_instance`]: crate::event_loop::EventLoopWindowTarget::enable_single_instance
+/// [`Event::NewInstance`]: crate::event::Event::NewInstance
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JumpListTask {
+  /// The title of the task.
+  pub title: String,
+  /// The command-line arguments the application is started with.
+  pub arguments: String,
+  /// The tooltip of the task.
+  pub description: String,
+  /// The path of the file containing the icon of the task, and the index of the icon in it.
+  pub icon: Option<(PathBuf, i32)>,
+}
+
+impl JumpListTask {
+  /// Creates a task with a title, starting the application with `arguments`.
+  pub fn new<T: Into<String>, A: Into<String>>(title: T, arguments: A) -> Self {
+    Self {
+      title: title.into(),
+      arguments: arguments.into(),
+      ..Default::default()
+    }
+  }
+
+  /// Sets the tooltip of the task.
+  pub fn with_description<D: Into<String>>(mut self, description: D) -> Self {
+    self.description = description.into();
+    self
+  }
+
+  /// Sets the icon of the task, at `index` in the file at `path`, such as an executable.
+  pub fn with_icon<P: Into<PathBuf>>(mut self, path: P, index: i32) -> Self {
+    self.icon = Some((path.into(), index));
+    self
+  }
+}
+
+/// A category of a [`JumpList`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq)]
+pub enum JumpListCategory {
+  /// The files recently opened by the application, which requires the application to be
+  /// registered as a handler of their type.
+  Recent,
+  /// The files frequently opened by the application, which requires the application to be
+  /// registered as a handler of their type.
+  Frequent,
+  /// A category with a title and tasks.
+  Custom(String, Vec<JumpListTask>),
+}
+
+/// The jump list of the application, shown when right-clicking its taskbar button.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JumpList {
+  /// The application user model ID of the jump list, see
+  /// <https://docs.microsoft.com/en-us/windows/win32/shell/appids>. The ID of the process is used
+  /// when `None`.
+  pub app_id: Option<String>,
+  /// The tasks of the `Tasks` category, below the other categories.
+  pub tasks: Vec<JumpListTask>,
+  /// The categories of the jump list, in order.
+  pub categories: Vec<JumpListCategory>,
+}
+
+impl JumpList {
+  /// Creates an empty jump list.
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Sets the application user model ID of the jump list.
+  pub fn with_app_id<I: Into<String>>(mut self, app_id: I) -> Self {
+    self.app_id = Some(app_id.into());
+    self
+  }
+
+  /// Adds a task to the `Tasks` category.
+  pub fn with_task(mut self, task: JumpListTask) -> Self {
+    self.tasks.push(task);
+    self
+  }
+
+  /// Adds a category.
+  pub fn with_category(mut self, category: JumpListCategory) -> Self {
+    self.categories.push(category);
+    self
+  }
+
+  /// Replaces the jump list of the application with this one.
+  pub fn apply(&self) -> Result<(), ExternalError> {
+    crate::platform_impl::apply_jump_list(self)
+  }
+
+  /// Removes the jump list of the application with the given application user model ID, or of
+  /// the process when `None`.
+  pub fn clear(app_id: Option<&str>) -> Result<(), ExternalError> {
+    crate::platform_impl::clear_jump_list(app_id)
+  }
+}
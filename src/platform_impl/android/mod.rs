@@ -4,13 +4,14 @@
 
 #![cfg(target_os = "android")]
 use crate::{
-  accelerator::Accelerator,
   dpi::{PhysicalPosition, PhysicalSize, Position, Size},
   error, event,
   event_loop::{self, ControlFlow},
   icon::Icon,
-  keyboard::{Key, KeyCode, KeyLocation, NativeKeyCode},
-  menu::{CustomMenuItem, MenuId, MenuItem, MenuType},
+  keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NativeKeyCode},
+  menu::{
+    CustomMenuItem, MenuId, MenuItem, MenuItemAttributes as RootMenuItemAttributes, MenuType,
+  },
   monitor,
   window::{self, Theme},
 };
@@ -33,6 +34,7 @@ use std::{
 mod clipboard;
 pub use clipboard::Clipboard;
 pub mod ndk_glue;
+use crate::platform::android::ActivityState;
 use ndk_glue::{Event, Rect};
 
 lazy_static! {
@@ -58,6 +60,39 @@ fn poll(poll: Poll) -> Option<EventSource> {
   }
 }
 
+/// Calls a method of the activity that returns a boolean, `false` if it fails.
+fn activity_flag(method: &str) -> bool {
+  let ctx = ndk_context::android_context();
+  let vm = match unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) } {
+    Ok(vm) => vm,
+    Err(_) => return false,
+  };
+  let result = vm.attach_current_thread().and_then(|env| {
+    let activity = jni::objects::JObject::from(ctx.context() as jni::sys::jobject);
+    env.call_method(activity, method, "()Z", &[])?.z()
+  });
+  result.unwrap_or_else(|e| {
+    log::warn!("Failed to call {} on the activity: {}", method, e);
+    false
+  })
+}
+
+/// Finishes the activity, which is what it does by default when the back button is pressed.
+fn finish_activity() {
+  let ctx = ndk_context::android_context();
+  let vm = match unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) } {
+    Ok(vm) => vm,
+    Err(_) => return,
+  };
+  let result = vm.attach_current_thread().and_then(|env| {
+    let activity = jni::objects::JObject::from(ctx.context() as jni::sys::jobject);
+    env.call_method(activity, "finish", "()V", &[]).map(|_| ())
+  });
+  if let Err(e) = result {
+    log::warn!("Failed to finish the activity: {}", e);
+  }
+}
+
 // todo: implement android menubar
 #[derive(Debug, Clone)]
 pub struct MenuItemAttributes;
@@ -83,15 +118,21 @@ impl Menu {
   }
   pub fn add_item(
     &mut self,
-    _menu_id: MenuId,
-    _title: &str,
-    _accelerator: Option<Accelerator>,
-    _enabled: bool,
-    _selected: bool,
+    _item: RootMenuItemAttributes<'_>,
     _menu_type: MenuType,
   ) -> CustomMenuItem {
     CustomMenuItem(MenuItemAttributes {})
   }
+  pub fn insert_item(
+    &mut self,
+    _index: usize,
+    _item: RootMenuItemAttributes<'_>,
+    _menu_type: MenuType,
+  ) -> CustomMenuItem {
+    CustomMenuItem(MenuItemAttributes {})
+  }
+  pub fn remove_item(&mut self, _item: &MenuItemAttributes) {}
+  pub fn clear(&mut self) {}
   pub fn add_submenu(&mut self, _title: &str, _enabled: bool, _submenu: Menu) {}
   pub fn add_native_item(
     &mut self,
@@ -134,14 +175,18 @@ macro_rules! call_event_handler {
   }};
 }
 
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlatformSpecificEventLoopAttributes {}
+
 impl<T: 'static> EventLoop<T> {
-  pub fn new() -> Self {
+  pub(crate) fn new(_: &mut PlatformSpecificEventLoopAttributes) -> Self {
     Self {
       window_target: event_loop::EventLoopWindowTarget {
         p: EventLoopWindowTarget {
           _marker: std::marker::PhantomData,
         },
         _marker: std::marker::PhantomData,
+        coalesce_motion_events: Default::default(),
       },
       user_queue: Default::default(),
       first_event: None,
@@ -218,6 +263,32 @@ impl<T: 'static> EventLoop<T> {
             //   call_event_handler!(event_handler, self.window_target(), control_flow, event);
             // }
           }
+          Event::ImeVisibilityChanged => {
+            let keyboard_height = ndk_glue::ime_height();
+            call_event_handler!(
+              event_handler,
+              self.window_target(),
+              control_flow,
+              event::Event::ImeVisibilityChanged {
+                visible: keyboard_height > 0,
+                keyboard_height,
+              }
+            );
+          }
+          Event::BackPressed => {
+            let mut prevent_default = false;
+            call_event_handler!(
+              event_handler,
+              self.window_target(),
+              control_flow,
+              event::Event::BackRequested {
+                prevent_default: &mut prevent_default
+              }
+            );
+            if !prevent_default {
+              finish_activity();
+            }
+          }
           Event::WindowHasFocus => {
             call_event_handler!(
               event_handler,
@@ -289,6 +360,7 @@ impl<T: 'static> EventLoop<T> {
                             location,
                             id: pointer.pointer_id() as u64,
                             force: None,
+                            timestamp: event_timestamp(motion_event.event_time()),
                           }),
                         };
                         call_event_handler!(
@@ -331,6 +403,7 @@ impl<T: 'static> EventLoop<T> {
                           location: keycode_to_location(keycode),
                           repeat: key.repeat_count() > 0,
                           text: None,
+                          timestamp: event_timestamp(key.event_time()),
                           platform_specific: KeyEventExtra {},
                         },
                         is_synthetic: false,
@@ -463,6 +536,8 @@ impl<T> EventLoopProxy<T> {
     self.looper.wake();
     Ok(())
   }
+
+  pub fn send_task(&self, _task: event_loop::EventLoopTask<T>) {}
 }
 
 impl<T> Clone for EventLoopProxy<T> {
@@ -480,12 +555,70 @@ pub struct EventLoopWindowTarget<T: 'static> {
 }
 
 impl<T: 'static> EventLoopWindowTarget<T> {
+  pub fn activity_state(&self) -> ActivityState {
+    ndk_glue::activity_state()
+  }
+
+  pub fn saved_state(&self) -> Option<Vec<u8>> {
+    ndk_glue::saved_state()
+  }
+
+  pub fn set_saved_state(&self, state: Option<Vec<u8>>) {
+    ndk_glue::set_saved_state(state)
+  }
+
+  pub fn is_in_multi_window_mode(&self) -> bool {
+    activity_flag("isInMultiWindowMode")
+  }
+
+  pub fn is_in_picture_in_picture_mode(&self) -> bool {
+    activity_flag("isInPictureInPictureMode")
+  }
+
   pub fn primary_monitor(&self) -> Option<monitor::MonitorHandle> {
     Some(monitor::MonitorHandle {
       inner: MonitorHandle,
     })
   }
 
+  pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn modifiers_state(&self) -> ModifiersState {
+    ModifiersState::empty()
+  }
+
+  pub fn is_key_pressed(&self, _key: KeyCode) -> bool {
+    false
+  }
+
+  pub fn focused_window(&self) -> Option<window::WindowId> {
+    None
+  }
+
+  pub fn activate_app(&self, _ignore_other_apps: bool) {}
+
+  pub fn listen_clipboard(&self, _listen: bool) {}
+
+  pub fn enable_single_instance(&self, _identifier: &str) -> bool {
+    true
+  }
+
+  pub fn is_compositor_active(&self) -> bool {
+    true
+  }
+
+  pub fn keyboard_layout(&self) -> Option<String> {
+    None
+  }
+
+  pub fn accent_color(&self) -> Option<window::RGBA> {
+    None
+  }
+
   pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
     let mut v = VecDeque::with_capacity(1);
     v.push_back(MonitorHandle);
@@ -560,6 +693,8 @@ impl Window {
     // TODO
   }
 
+  pub fn set_vsync_redraws(&self, _vsync_redraws: bool) {}
+
   pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, error::NotSupportedError> {
     Err(error::NotSupportedError::new())
   }
@@ -580,12 +715,19 @@ impl Window {
     warn!("Cannot set window size on Android");
   }
 
+  pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    self.set_inner_size(size);
+    Some(self.inner_size())
+  }
+
   pub fn outer_size(&self) -> PhysicalSize<u32> {
     MonitorHandle.size()
   }
 
   pub fn set_min_inner_size(&self, _: Option<Size>) {}
 
+  pub fn set_resize_increments(&self, _: Option<Size>) {}
+
   pub fn set_max_inner_size(&self, _: Option<Size>) {}
 
   pub fn set_title(&self, _title: &str) {}
@@ -606,8 +748,16 @@ impl Window {
 
   pub fn set_resizable(&self, _resizeable: bool) {}
 
+  pub fn set_minimizable(&self, _minimizable: bool) {}
+
+  pub fn set_maximizable(&self, _maximizable: bool) {}
+
+  pub fn set_closable(&self, _closable: bool) {}
+
   pub fn set_minimized(&self, _minimized: bool) {}
 
+  pub fn set_minimize_to_tray(&self, _minimize_to_tray: bool) {}
+
   pub fn set_maximized(&self, _maximized: bool) {}
 
   pub fn is_maximized(&self) -> bool {
@@ -618,13 +768,32 @@ impl Window {
     false
   }
 
+  pub fn restored_geometry(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    (self.outer_position().unwrap_or_default(), self.inner_size())
+  }
+
   pub fn is_visible(&self) -> bool {
     log::warn!("`Window::is_visible` is ignored on android");
     false
   }
 
   pub fn is_resizable(&self) -> bool {
-    warn!("`Window::is_resizable` is ignored on android");
+    warn!("`Window::is_resizable` is ignored on Android");
+    false
+  }
+
+  pub fn is_minimizable(&self) -> bool {
+    warn!("`Window::is_minimizable` is ignored on Android");
+    false
+  }
+
+  pub fn is_maximizable(&self) -> bool {
+    warn!("`Window::is_maximizable` is ignored on Android");
+    false
+  }
+
+  pub fn is_closable(&self) -> bool {
+    warn!("`Window::is_closable` is ignored on Android");
     false
   }
 
@@ -641,18 +810,67 @@ impl Window {
     None
   }
 
+  pub fn current_fullscreen(&self) -> Option<window::Fullscreen> {
+    None
+  }
+
   pub fn set_decorations(&self, _decorations: bool) {}
 
-  pub fn set_always_on_bottom(&self, _always_on_bottom: bool) {}
+  pub fn set_corner_radius(&self, _radius: Option<f64>) {}
+
+  pub fn set_drag_regions(
+    &self,
+    _regions: Vec<event::Rectangle>,
+    _exclusions: Vec<event::Rectangle>,
+  ) {
+  }
+
+  pub fn set_drop_highlight(&self, _highlight: bool) {}
+
+  pub fn set_background_color(&self, _color: Option<window::RGBA>) {}
+
+  pub fn set_keyboard_grab(&self, _grab: bool) {}
 
-  pub fn set_always_on_top(&self, _always_on_top: bool) {}
+  pub fn set_opacity(&self, _opacity: f64) {}
+
+  pub fn set_content_protection(&self, _protected: bool) {}
+
+  pub fn set_window_level(&self, _window_level: window::WindowLevel) {}
+
+  pub fn set_visible_on_all_workspaces(&self, _visible: bool) {}
 
   pub fn set_window_icon(&self, _window_icon: Option<crate::icon::Icon>) {}
 
-  pub fn set_ime_position(&self, _position: Position) {}
+  pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {}
+
+  pub fn set_ime_allowed(&self, _allowed: bool) {}
+
+  pub fn reset_dead_keys(&self) {}
 
   pub fn request_user_attention(&self, _request_type: Option<window::UserAttentionType>) {}
 
+  pub fn set_progress_bar(&self, _progress: window::ProgressBarState) {}
+
+  pub fn set_badge_count(&self, _count: Option<u64>) {}
+
+  #[cfg(feature = "accesskit")]
+  pub fn set_accessibility_source(
+    &self,
+    _source: Box<dyn FnMut() -> crate::accesskit::TreeUpdate + Send>,
+  ) {
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn update_accessibility<F>(&self, _update: F)
+  where
+    F: FnOnce() -> crate::accesskit::TreeUpdate,
+  {
+  }
+
+  pub fn set_blur(&self, _effect: Option<window::BlurEffect>) {}
+
+  pub fn set_shadow(&self, _shadow: bool) {}
+
   pub fn hide_menu(&self) {}
 
   pub fn show_menu(&self) {}
@@ -670,7 +888,7 @@ impl Window {
     ))
   }
 
-  pub fn set_cursor_grab(&self, _: bool) -> Result<(), error::ExternalError> {
+  pub fn set_cursor_grab(&self, _: window::CursorGrabMode) -> Result<(), error::ExternalError> {
     Err(error::ExternalError::NotSupported(
       error::NotSupportedError::new(),
     ))
@@ -684,6 +902,30 @@ impl Window {
     ))
   }
 
+  pub fn drag_resize_window(
+    &self,
+    _direction: window::ResizeDirection,
+  ) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn start_drag(&self, _data: window::DragData) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
+  pub fn set_cursor_hittest_region(
+    &self,
+    _regions: Option<&[event::Rectangle]>,
+  ) -> Result<(), error::ExternalError> {
+    Err(error::ExternalError::NotSupported(
+      error::NotSupportedError::new(),
+    ))
+  }
+
   pub fn set_ignore_cursor_events(&self, _ignore: bool) -> Result<(), error::ExternalError> {
     Err(error::ExternalError::NotSupported(
       error::NotSupportedError::new(),
@@ -713,9 +955,15 @@ impl Window {
     ndk_glue::content_rect()
   }
 
+  pub fn set_ime_visible(&self, visible: bool) {
+    ndk_glue::set_ime_visible(visible);
+  }
+
   pub fn theme(&self) -> Theme {
     Theme::Light
   }
+
+  pub fn set_theme(&self, _theme: Option<Theme>) {}
 }
 
 #[derive(Default, Clone, Debug)]
@@ -788,6 +1036,14 @@ impl MonitorHandle {
       .unwrap_or(1.0)
   }
 
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    None
+  }
+
+  pub fn mirror_group_id(&self) -> Option<u32> {
+    None
+  }
+
   pub fn video_modes(&self) -> impl Iterator<Item = monitor::VideoMode> {
     let size = self.size().into();
     let mut v = Vec::new();
@@ -833,6 +1089,21 @@ impl VideoMode {
   }
 }
 
+/// Converts the time of an input event, in nanoseconds of the monotonic clock, to an `Instant`.
+fn event_timestamp(event_time: i64) -> Instant {
+  let now = Instant::now();
+  let mut time = libc::timespec {
+    tv_sec: 0,
+    tv_nsec: 0,
+  };
+  if unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut time) } != 0 {
+    return now;
+  }
+  let clock = Duration::new(time.tv_sec as u64, time.tv_nsec as u32);
+  let age = clock.saturating_sub(Duration::from_nanos(event_time as u64));
+  now.checked_sub(age).unwrap_or(now)
+}
+
 fn keycode_to_logical(keycode: ndk::event::Keycode, native: NativeKeyCode) -> Key<'static> {
   use ndk::event::Keycode::*;
 
@@ -2,10 +2,11 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::platform::android::ActivityState;
 pub use jni;
 pub use jni::{
   objects::{GlobalRef, JClass, JMap, JObject, JString},
-  sys::jobject,
+  sys::{jbyteArray, jobject},
   JNIEnv,
 };
 use log::Level;
@@ -48,6 +49,10 @@ macro_rules! android_binding {
         android_fn!($domain, $package, TauriActivity, destroy);
         android_fn!($domain, $package, TauriActivity, memory);
         android_fn!($domain, $package, TauriActivity, focus, i32);
+        android_fn!($domain, $package, TauriActivity, ime, i32);
+        android_fn!($domain, $package, TauriActivity, back);
+        android_fn!($domain, $package, TauriActivity, persist, JObject, jbyteArray);
+        android_fn!($domain, $package, TauriActivity, restore, jbyteArray);
     }
   };
 }
@@ -102,9 +107,13 @@ pub fn android_log(level: Level, tag: &CStr, msg: &CStr) {
 }
 
 static WINDOW_MANGER: OnceCell<GlobalRef> = OnceCell::new();
-static INPUT_QUEUE: Lazy<RwLock<Option<InputQueue>>> = Lazy::new(|| Default::default());
-static CONTENT_RECT: Lazy<RwLock<Rect>> = Lazy::new(|| Default::default());
-static LOOPER: Lazy<Mutex<Option<ForeignLooper>>> = Lazy::new(|| Default::default());
+static INPUT_QUEUE: Lazy<RwLock<Option<InputQueue>>> = Lazy::new(Default::default);
+static CONTENT_RECT: Lazy<RwLock<Rect>> = Lazy::new(Default::default);
+static IME_HEIGHT: Lazy<RwLock<u32>> = Lazy::new(Default::default);
+static SAVED_STATE: Lazy<RwLock<Option<Vec<u8>>>> = Lazy::new(Default::default);
+static ACTIVITY_STATE: Lazy<RwLock<ActivityState>> =
+  Lazy::new(|| RwLock::new(ActivityState::Created));
+static LOOPER: Lazy<Mutex<Option<ForeignLooper>>> = Lazy::new(Default::default);
 
 pub fn window_manager() -> Option<&'static GlobalRef> {
   WINDOW_MANGER.get()
@@ -118,12 +127,107 @@ pub fn content_rect() -> Rect {
   CONTENT_RECT.read().unwrap().clone()
 }
 
+pub fn ime_height() -> u32 {
+  *IME_HEIGHT.read().unwrap()
+}
+
+pub fn saved_state() -> Option<Vec<u8>> {
+  SAVED_STATE.read().unwrap().clone()
+}
+
+pub fn set_saved_state(state: Option<Vec<u8>>) {
+  *SAVED_STATE.write().unwrap() = state;
+}
+
+pub fn activity_state() -> ActivityState {
+  *ACTIVITY_STATE.read().unwrap()
+}
+
+fn set_activity_state(state: ActivityState) {
+  *ACTIVITY_STATE.write().unwrap() = state;
+}
+
 static PIPE: Lazy<[RawFd; 2]> = Lazy::new(|| {
   let mut pipe: [RawFd; 2] = Default::default();
   unsafe { libc::pipe(pipe.as_mut_ptr()) };
   pipe
 });
 
+/// Carries the requests to show (`1`) or hide (`0`) the soft keyboard to the UI thread.
+static IME_PIPE: Lazy<[RawFd; 2]> = Lazy::new(|| {
+  let mut pipe: [RawFd; 2] = Default::default();
+  unsafe { libc::pipe(pipe.as_mut_ptr()) };
+  pipe
+});
+
+/// Shows or hides the soft keyboard. The input method manager has to be called on the UI thread,
+/// where the request is handled.
+pub fn set_ime_visible(visible: bool) {
+  let request = visible as u8;
+  unsafe { libc::write(IME_PIPE[1], &request as *const _ as *const _, 1) };
+}
+
+/// Shows the soft keyboard for the focused view, or hides it. Must be called on the UI thread.
+fn apply_ime_visible(visible: bool) {
+  let ctx = ndk_context::android_context();
+  let vm = match unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) } {
+    Ok(vm) => vm,
+    Err(_) => return,
+  };
+  let env = match vm.get_env() {
+    Ok(env) => env,
+    Err(_) => return,
+  };
+  let activity = JObject::from(ctx.context() as jobject);
+  let result = (|| -> jni::errors::Result<()> {
+    let service = env.new_string("input_method")?;
+    let input_method_manager = env
+      .call_method(
+        activity,
+        "getSystemService",
+        "(Ljava/lang/String;)Ljava/lang/Object;",
+        &[service.into()],
+      )?
+      .l()?;
+    let window = env
+      .call_method(activity, "getWindow", "()Landroid/view/Window;", &[])?
+      .l()?;
+    let decor_view = env
+      .call_method(window, "getDecorView", "()Landroid/view/View;", &[])?
+      .l()?;
+    if visible {
+      let focused_view = env
+        .call_method(activity, "getCurrentFocus", "()Landroid/view/View;", &[])?
+        .l()?;
+      let view = if focused_view.is_null() {
+        decor_view
+      } else {
+        focused_view
+      };
+      env.call_method(
+        input_method_manager,
+        "showSoftInput",
+        "(Landroid/view/View;I)Z",
+        &[view.into(), 0.into()],
+      )?;
+    } else {
+      let window_token = env
+        .call_method(decor_view, "getWindowToken", "()Landroid/os/IBinder;", &[])?
+        .l()?;
+      env.call_method(
+        input_method_manager,
+        "hideSoftInputFromWindow",
+        "(Landroid/os/IBinder;I)Z",
+        &[window_token.into(), 0.into()],
+      )?;
+    }
+    Ok(())
+  })();
+  if let Err(e) = result {
+    log::warn!("Failed to set the visibility of the soft keyboard: {}", e);
+  }
+}
+
 pub fn poll_events() -> Option<Event> {
   unsafe {
     let size = std::mem::size_of::<Event>();
@@ -171,6 +275,8 @@ pub enum Event {
   InputQueueCreated,
   InputQueueDestroyed,
   ContentRectChanged,
+  ImeVisibilityChanged,
+  BackPressed,
 }
 
 pub unsafe fn create(
@@ -181,6 +287,8 @@ pub unsafe fn create(
   main: fn(),
 ) {
   //-> jobjectArray {
+  // The activity is recreated, e.g. after a configuration change, in the same process.
+  set_activity_state(ActivityState::Created);
   // Initialize global context
   let window_manager = env
     .call_method(
@@ -205,6 +313,20 @@ pub unsafe fn create(
   let looper = ThreadLooper::for_thread().unwrap().into_foreign();
   setup(env, &looper, activity);
 
+  // This is the UI thread, keep it handling the soft keyboard requests.
+  if looper
+    .add_fd_with_callback(IME_PIPE[0], FdEvent::INPUT, |fd| {
+      let mut request = 0u8;
+      if libc::read(fd, &mut request as *mut _ as *mut _, 1) == 1 {
+        apply_ime_visible(request != 0);
+      }
+      true
+    })
+    .is_err()
+  {
+    log::warn!("Failed to watch the soft keyboard requests on the UI thread");
+  }
+
   let mut logpipe: [RawFd; 2] = Default::default();
   libc::pipe(logpipe.as_mut_ptr());
   libc::dup2(logpipe[1], libc::STDOUT_FILENO);
@@ -261,10 +383,12 @@ pub unsafe fn create(
 }
 
 pub unsafe fn resume(_: JNIEnv, _: JClass, _: JObject) {
+  set_activity_state(ActivityState::Resumed);
   wake(Event::Resume);
 }
 
 pub unsafe fn pause(_: JNIEnv, _: JClass, _: JObject) {
+  set_activity_state(ActivityState::Paused);
   wake(Event::Pause);
 }
 
@@ -277,14 +401,66 @@ pub unsafe fn focus(_: JNIEnv, _: JClass, has_focus: libc::c_int) {
   wake(event);
 }
 
+/// Called by the activity with the bottom inset of the soft keyboard, `0` when it is hidden.
+///
+/// # Safety
+///
+/// Only called by the activity, through the JNI bindings of `android_binding!`.
+pub unsafe fn ime(_: JNIEnv, _: JClass, height: i32) {
+  let height = height.max(0) as u32;
+  let changed = std::mem::replace(&mut *IME_HEIGHT.write().unwrap(), height) != height;
+  if changed {
+    wake(Event::ImeVisibilityChanged);
+  }
+}
+
+/// Called by the activity instead of finishing when the back button is pressed.
+///
+/// # Safety
+///
+/// Only called by the activity, through the JNI bindings of `android_binding!`.
+pub unsafe fn back(_: JNIEnv, _: JClass, _: JObject) {
+  wake(Event::BackPressed);
+}
+
 pub unsafe fn start(_: JNIEnv, _: JClass, _: JObject) {
+  set_activity_state(ActivityState::Started);
   wake(Event::Start);
 }
 
 pub unsafe fn stop(_: JNIEnv, _: JClass, _: JObject) {
+  set_activity_state(ActivityState::Stopped);
   wake(Event::Stop);
 }
 
+/// Called by the activity from `onSaveInstanceState`, to put the returned state in the bundle.
+///
+/// # Safety
+///
+/// Only called by the activity, through the JNI bindings of `android_binding!`.
+pub unsafe fn persist(env: JNIEnv, _: JClass, _: JObject) -> jbyteArray {
+  match SAVED_STATE.read().unwrap().as_deref() {
+    Some(state) => env
+      .byte_array_from_slice(state)
+      .unwrap_or(std::ptr::null_mut()),
+    None => std::ptr::null_mut(),
+  }
+}
+
+/// Called by the activity from `onCreate` with the state it got back from the bundle.
+///
+/// # Safety
+///
+/// Only called by the activity, through the JNI bindings of `android_binding!`.
+pub unsafe fn restore(env: JNIEnv, _: JClass, state: jbyteArray) {
+  if state.is_null() {
+    return;
+  }
+  if let Ok(state) = env.convert_byte_array(state) {
+    set_saved_state(Some(state));
+  }
+}
+
 ///////////////////////////////////////////////
 // Events below are not used by event loop yet.
 ///////////////////////////////////////////////
@@ -294,6 +470,7 @@ pub unsafe fn save(_: JNIEnv, _: JClass, _: JObject) {
 }
 
 pub unsafe fn destroy(_: JNIEnv, _: JClass, _: JObject) {
+  set_activity_state(ActivityState::Destroyed);
   wake(Event::Destroy);
 }
 
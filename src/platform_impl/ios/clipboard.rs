@@ -2,6 +2,10 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::PathBuf;
+
+use crate::clipboard::RgbaImage;
+
 #[derive(Debug, Clone, Default)]
 pub struct Clipboard;
 impl Clipboard {
@@ -9,4 +13,16 @@ impl Clipboard {
   pub(crate) fn read_text(&self) -> Option<String> {
     None
   }
+  pub(crate) fn write_html(&mut self, _html: impl AsRef<str>, _alt_text: impl AsRef<str>) {}
+  pub(crate) fn read_html(&self) -> Option<String> {
+    None
+  }
+  pub(crate) fn write_files(&mut self, _paths: Vec<PathBuf>) {}
+  pub(crate) fn read_files(&self) -> Option<Vec<PathBuf>> {
+    None
+  }
+  pub(crate) fn write_image(&mut self, _image: &RgbaImage) {}
+  pub(crate) fn read_image(&self) -> Option<RgbaImage> {
+    None
+  }
 }
@@ -15,11 +15,16 @@ use crossbeam_channel::{self as channel, Receiver, Sender};
 use raw_window_handle::{RawDisplayHandle, UiKitDisplayHandle};
 
 use crate::{
-  dpi::LogicalSize,
+  dpi::{LogicalSize, PhysicalPosition},
+  error::{ExternalError, NotSupportedError},
   event::Event,
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootEventLoopWindowTarget},
+  event_loop::{
+    ControlFlow, EventLoopClosed, EventLoopTask, EventLoopWindowTarget as RootEventLoopWindowTarget,
+  },
+  keyboard::{KeyCode, ModifiersState},
   monitor::MonitorHandle as RootMonitorHandle,
   platform::ios::Idiom,
+  window::{WindowId as RootWindowId, RGBA},
 };
 
 use crate::platform_impl::platform::{
@@ -71,6 +76,47 @@ impl<T: 'static> EventLoopWindowTarget<T> {
     Some(RootMonitorHandle { inner: monitor })
   }
 
+  pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn modifiers_state(&self) -> ModifiersState {
+    ModifiersState::empty()
+  }
+
+  pub fn is_key_pressed(&self, _key: KeyCode) -> bool {
+    false
+  }
+
+  pub fn focused_window(&self) -> Option<RootWindowId> {
+    None
+  }
+
+  pub fn activate_app(&self, _ignore_other_apps: bool) {
+    warn!("`EventLoopWindowTarget::activate_app` is ignored on iOS")
+  }
+
+  pub fn listen_clipboard(&self, _listen: bool) {
+    warn!("`EventLoopWindowTarget::listen_clipboard` is ignored on iOS")
+  }
+
+  pub fn enable_single_instance(&self, _identifier: &str) -> bool {
+    warn!("`EventLoopWindowTarget::enable_single_instance` is ignored on iOS");
+    true
+  }
+
+  pub fn is_compositor_active(&self) -> bool {
+    true
+  }
+
+  pub fn keyboard_layout(&self) -> Option<String> {
+    None
+  }
+
+  pub fn accent_color(&self) -> Option<RGBA> {
+    None
+  }
+
   pub fn raw_display_handle(&self) -> RawDisplayHandle {
     RawDisplayHandle::UiKit(UiKitDisplayHandle::empty())
   }
@@ -80,8 +126,11 @@ pub struct EventLoop<T: 'static> {
   window_target: RootEventLoopWindowTarget<T>,
 }
 
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlatformSpecificEventLoopAttributes {}
+
 impl<T: 'static> EventLoop<T> {
-  pub fn new() -> EventLoop<T> {
+  pub(crate) fn new(_: &mut PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
     static mut SINGLETON_INIT: bool = false;
     unsafe {
       assert_main_thread!("`EventLoop` can only be created on the main thread on iOS");
@@ -106,6 +155,7 @@ impl<T: 'static> EventLoop<T> {
           sender_to_clone,
         },
         _marker: PhantomData,
+        coalesce_motion_events: Default::default(),
       },
     }
   }
@@ -210,6 +260,10 @@ impl<T> EventLoopProxy<T> {
     }
     Ok(())
   }
+
+  pub fn send_task(&self, _task: EventLoopTask<T>) {
+    warn!("`EventLoopProxy::create_window` is ignored on iOS")
+  }
 }
 
 fn setup_control_flow_observers() {
@@ -10,7 +10,7 @@ use objc::{runtime::Object, Encode, Encoding};
 
 use crate::{
   dpi::LogicalSize,
-  platform::ios::{Idiom, ScreenEdge, ValidOrientations},
+  platform::ios::{Idiom, ScreenEdge, StatusBarStyle, ValidOrientations},
 };
 
 pub type id = *mut Object;
@@ -212,6 +212,26 @@ impl UIInterfaceOrientationMask {
   }
 }
 
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UIStatusBarStyle(NSInteger);
+
+unsafe impl Encode for UIStatusBarStyle {
+  fn encode() -> Encoding {
+    NSInteger::encode()
+  }
+}
+
+impl From<StatusBarStyle> for UIStatusBarStyle {
+  fn from(style: StatusBarStyle) -> UIStatusBarStyle {
+    match style {
+      StatusBarStyle::Default => UIStatusBarStyle(0),
+      StatusBarStyle::LightContent => UIStatusBarStyle(1),
+      StatusBarStyle::DarkContent => UIStatusBarStyle(3),
+    }
+  }
+}
+
 #[repr(transparent)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct UIRectEdge(NSUInteger);
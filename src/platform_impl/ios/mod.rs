@@ -82,15 +82,16 @@ mod monitor;
 mod view;
 mod window;
 
-use crate::{
-  accelerator::Accelerator,
-  menu::{CustomMenuItem, MenuId, MenuItem, MenuType},
+use crate::menu::{
+  CustomMenuItem, MenuId, MenuItem, MenuItemAttributes as RootMenuItemAttributes, MenuType,
 };
 use std::fmt;
 
 pub use self::{
   clipboard::Clipboard,
-  event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
+  event_loop::{
+    EventLoop, EventLoopProxy, EventLoopWindowTarget, PlatformSpecificEventLoopAttributes,
+  },
   keycode::{keycode_from_scancode, keycode_to_scancode},
   monitor::{MonitorHandle, VideoMode},
   window::{PlatformSpecificWindowBuilderAttributes, Window, WindowId},
@@ -123,15 +124,21 @@ impl Menu {
   }
   pub fn add_item(
     &mut self,
-    _menu_id: MenuId,
-    _title: &str,
-    _accelerator: Option<Accelerator>,
-    _enabled: bool,
-    _selected: bool,
+    _item: RootMenuItemAttributes<'_>,
     _menu_type: MenuType,
   ) -> CustomMenuItem {
     CustomMenuItem(MenuItemAttributes {})
   }
+  pub fn insert_item(
+    &mut self,
+    _index: usize,
+    _item: RootMenuItemAttributes<'_>,
+    _menu_type: MenuType,
+  ) -> CustomMenuItem {
+    CustomMenuItem(MenuItemAttributes {})
+  }
+  pub fn remove_item(&mut self, _item: &MenuItemAttributes) {}
+  pub fn clear(&mut self) {}
   pub fn add_submenu(&mut self, _title: &str, _enabled: bool, _submenu: Menu) {}
   pub fn add_native_item(
     &mut self,
@@ -237,6 +237,18 @@ impl Inner {
     }
   }
 
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    if !app_state::os_capabilities().maximum_frames_per_second {
+      return None;
+    }
+    let refresh_rate: NSInteger = unsafe { msg_send![self.ui_screen(), maximumFramesPerSecond] };
+    Some(refresh_rate as u32 * 1000)
+  }
+
+  pub fn mirror_group_id(&self) -> Option<u32> {
+    None
+  }
+
   pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
     let mut modes = BTreeSet::new();
     unsafe {
@@ -2,7 +2,10 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::HashMap;
+use std::{
+  collections::HashMap,
+  time::{Duration, Instant},
+};
 
 use objc::{
   declare::ClassDecl,
@@ -18,7 +21,7 @@ use crate::{
     event_loop::{self, EventProxy, EventWrapper},
     ffi::{
       id, nil, CGFloat, CGPoint, CGRect, UIForceTouchCapability, UIInterfaceOrientationMask,
-      UIRectEdge, UITouchPhase, UITouchType,
+      UIRectEdge, UIStatusBarStyle, UITouchPhase, UITouchType,
     },
     window::PlatformSpecificWindowBuilderAttributes,
     DeviceId,
@@ -154,6 +157,22 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
       }
     }
 
+    extern "C" fn safe_area_insets_did_change(object: &Object, _: Sel) {
+      unsafe {
+        let superclass: &'static Class = msg_send![object, superclass];
+        let () = msg_send![super(object, superclass), safeAreaInsetsDidChange];
+
+        let window: id = msg_send![object, window];
+        if window.is_null() {
+          return;
+        }
+        app_state::handle_nonuser_event(EventWrapper::StaticEvent(Event::WindowEvent {
+          window_id: RootWindowId(window.into()),
+          event: WindowEvent::SafeAreaInsetsChanged,
+        }));
+      }
+    }
+
     extern "C" fn set_content_scale_factor(
       object: &mut Object,
       _: Sel,
@@ -276,6 +295,7 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
               location: physical_location,
               force,
               phase,
+              timestamp: touch_timestamp(touch),
             }),
           }));
         }
@@ -294,6 +314,10 @@ unsafe fn get_view_class(root_view_class: &'static Class) -> &'static Class {
       sel!(layoutSubviews),
       layout_subviews as extern "C" fn(&Object, Sel),
     );
+    decl.add_method(
+      sel!(safeAreaInsetsDidChange),
+      safe_area_insets_did_change as extern "C" fn(&Object, Sel),
+    );
     decl.add_method(
       sel!(setContentScaleFactor:),
       set_content_scale_factor as extern "C" fn(&mut Object, Sel, CGFloat),
@@ -348,6 +372,16 @@ unsafe fn get_view_controller_class() -> &'static Class {
         },
         prefersStatusBarHidden,
     }
+    add_property! {
+        decl,
+        status_bar_style: UIStatusBarStyle,
+        setPreferredStatusBarStyle: |object| {
+            unsafe {
+                let () = msg_send![object, setNeedsStatusBarAppearanceUpdate];
+            }
+        },
+        preferredStatusBarStyle,
+    }
     add_property! {
         decl,
         prefers_home_indicator_auto_hidden: BOOL,
@@ -503,6 +537,11 @@ pub unsafe fn create_view_controller(
     view_controller,
     setPreferredScreenEdgesDeferringSystemGestures: edges
   ];
+  let status_bar_style: UIStatusBarStyle = platform_attributes.status_bar_style.into();
+  let () = msg_send![
+    view_controller,
+    setPreferredStatusBarStyle: status_bar_style
+  ];
   let () = msg_send![view_controller, setView: view];
   view_controller
 }
@@ -625,3 +664,16 @@ pub fn create_delegate_class() {
     decl.register();
   }
 }
+
+/// Converts the timestamp of a touch, in seconds since the system started, to an `Instant`.
+unsafe fn touch_timestamp(touch: id) -> Instant {
+  let now = Instant::now();
+  let timestamp: f64 = msg_send![touch, timestamp];
+  let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+  let uptime: f64 = msg_send![process_info, systemUptime];
+  let age = uptime - timestamp;
+  if !age.is_finite() || age <= 0.0 {
+    return now;
+  }
+  now.checked_sub(Duration::from_secs_f64(age)).unwrap_or(now)
+}
@@ -13,21 +13,25 @@ use objc::runtime::{Class, Object, BOOL, NO, YES};
 use crate::{
   dpi::{self, LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
-  event::{Event, WindowEvent},
+  event::{Event, Rectangle, WindowEvent},
   icon::Icon,
   monitor::MonitorHandle as RootMonitorHandle,
-  platform::ios::{MonitorHandleExtIOS, ScreenEdge, ValidOrientations},
+  platform::ios::{
+    MonitorHandleExtIOS, SafeAreaInsets, ScreenEdge, StatusBarStyle, ValidOrientations,
+  },
   platform_impl::platform::{
     app_state,
     event_loop::{self, EventProxy, EventWrapper},
     ffi::{
       id, CGFloat, CGPoint, CGRect, CGSize, UIEdgeInsets, UIInterfaceOrientationMask, UIRectEdge,
-      UIScreenOverscanCompensation,
+      UIScreenOverscanCompensation, UIStatusBarStyle,
     },
     monitor, view, EventLoopWindowTarget, Menu, MonitorHandle,
   },
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    BlurEffect, CursorGrabMode, CursorIcon, DragData, Fullscreen, ProgressBarState,
+    ResizeDirection, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    WindowLevel, RGBA,
   },
 };
 
@@ -165,6 +169,15 @@ impl Inner {
     warn!("not clear what `Window::set_inner_size` means on iOS");
   }
 
+  pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    self.set_inner_size(size);
+    Some(self.inner_size())
+  }
+
+  pub fn set_resize_increments(&self, _increments: Option<Size>) {
+    warn!("`Window::set_resize_increments` is ignored on iOS")
+  }
+
   pub fn set_min_inner_size(&self, _dimensions: Option<Size>) {
     warn!("`Window::set_min_inner_size` is ignored on iOS")
   }
@@ -177,6 +190,18 @@ impl Inner {
     warn!("`Window::set_resizable` is ignored on iOS")
   }
 
+  pub fn set_minimizable(&self, _minimizable: bool) {
+    warn!("`Window::set_minimizable` is ignored on iOS")
+  }
+
+  pub fn set_maximizable(&self, _maximizable: bool) {
+    warn!("`Window::set_maximizable` is ignored on iOS")
+  }
+
+  pub fn set_closable(&self, _closable: bool) {
+    warn!("`Window::set_closable` is ignored on iOS")
+  }
+
   pub fn scale_factor(&self) -> f64 {
     unsafe {
       let hidpi: CGFloat = msg_send![self.view, contentScaleFactor];
@@ -192,7 +217,7 @@ impl Inner {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
-  pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
+  pub fn set_cursor_grab(&self, _mode: CursorGrabMode) -> Result<(), ExternalError> {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
@@ -204,14 +229,33 @@ impl Inner {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
+  pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  pub fn start_drag(&self, _data: DragData) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   pub fn set_ignore_cursor_events(&self, _ignore: bool) -> Result<(), ExternalError> {
     Err(ExternalError::NotSupported(NotSupportedError::new()))
   }
 
+  pub fn set_cursor_hittest_region(
+    &self,
+    _regions: Option<&[Rectangle]>,
+  ) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   pub fn set_minimized(&self, _minimized: bool) {
     warn!("`Window::set_minimized` is ignored on iOS")
   }
 
+  pub fn set_minimize_to_tray(&self, _minimize_to_tray: bool) {
+    warn!("`Window::set_minimize_to_tray` is ignored on iOS")
+  }
+
   pub fn set_maximized(&self, _maximized: bool) {
     warn!("`Window::set_maximized` is ignored on iOS")
   }
@@ -226,6 +270,10 @@ impl Inner {
     false
   }
 
+  pub fn restored_geometry(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    (self.outer_position().unwrap_or_default(), self.inner_size())
+  }
+
   pub fn is_visible(&self) -> bool {
     log::warn!("`Window::is_visible` is ignored on iOS");
     false
@@ -236,6 +284,21 @@ impl Inner {
     false
   }
 
+  pub fn is_minimizable(&self) -> bool {
+    warn!("`Window::is_minimizable` is ignored on iOS");
+    false
+  }
+
+  pub fn is_maximizable(&self) -> bool {
+    warn!("`Window::is_maximizable` is ignored on iOS");
+    false
+  }
+
+  pub fn is_closable(&self) -> bool {
+    warn!("`Window::is_closable` is ignored on iOS");
+    false
+  }
+
   pub fn is_decorated(&self) -> bool {
     warn!("`Window::is_decorated` is ignored on iOS");
     false
@@ -297,30 +360,105 @@ impl Inner {
     }
   }
 
+  pub fn current_fullscreen(&self) -> Option<Fullscreen> {
+    self.fullscreen()
+  }
+
   pub fn set_decorations(&self, _decorations: bool) {
     warn!("`Window::set_decorations` is ignored on iOS")
   }
 
-  pub fn set_always_on_bottom(&self, _always_on_bottom: bool) {
-    warn!("`Window::set_always_on_bottom` is ignored on iOS")
+  pub fn set_corner_radius(&self, _radius: Option<f64>) {
+    warn!("`Window::set_corner_radius` is ignored on iOS")
+  }
+
+  pub fn set_drag_regions(&self, _regions: Vec<Rectangle>, _exclusions: Vec<Rectangle>) {
+    warn!("`Window::set_drag_regions` is ignored on iOS")
+  }
+
+  pub fn set_drop_highlight(&self, _highlight: bool) {
+    warn!("`Window::set_drop_highlight` is ignored on iOS")
+  }
+
+  pub fn set_vsync_redraws(&self, _vsync_redraws: bool) {
+    warn!("`Window::set_vsync_redraws` is ignored on iOS")
+  }
+
+  pub fn set_background_color(&self, _color: Option<RGBA>) {
+    warn!("`Window::set_background_color` is ignored on iOS")
+  }
+
+  pub fn set_keyboard_grab(&self, _grab: bool) {
+    warn!("`Window::set_keyboard_grab` is ignored on iOS")
+  }
+
+  pub fn set_opacity(&self, _opacity: f64) {
+    warn!("`Window::set_opacity` is ignored on iOS")
+  }
+
+  pub fn set_content_protection(&self, _protected: bool) {
+    warn!("`Window::set_content_protection` is ignored on iOS")
+  }
+
+  pub fn set_window_level(&self, _window_level: WindowLevel) {
+    warn!("`Window::set_window_level` is ignored on iOS")
   }
 
-  pub fn set_always_on_top(&self, _always_on_top: bool) {
-    warn!("`Window::set_always_on_top` is ignored on iOS")
+  pub fn set_visible_on_all_workspaces(&self, _visible: bool) {
+    warn!("`Window::set_visible_on_all_workspaces` is ignored on iOS")
   }
 
   pub fn set_window_icon(&self, _icon: Option<Icon>) {
     warn!("`Window::set_window_icon` is ignored on iOS")
   }
 
-  pub fn set_ime_position(&self, _position: Position) {
-    warn!("`Window::set_ime_position` is ignored on iOS")
+  pub fn set_ime_cursor_area(&self, _position: Position, _size: Size) {
+    warn!("`Window::set_ime_cursor_area` is ignored on iOS")
+  }
+
+  pub fn set_ime_allowed(&self, _allowed: bool) {
+    warn!("`Window::set_ime_allowed` is ignored on iOS")
+  }
+
+  pub fn reset_dead_keys(&self) {
+    warn!("`Window::reset_dead_keys` is ignored on iOS")
   }
 
   pub fn request_user_attention(&self, _request_type: Option<UserAttentionType>) {
     warn!("`Window::request_user_attention` is ignored on iOS")
   }
 
+  pub fn set_progress_bar(&self, _progress: ProgressBarState) {
+    warn!("`Window::set_progress_bar` is ignored on iOS")
+  }
+
+  pub fn set_badge_count(&self, _count: Option<u64>) {
+    warn!("`Window::set_badge_count` is ignored on iOS")
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn set_accessibility_source(
+    &self,
+    _source: Box<dyn FnMut() -> crate::accesskit::TreeUpdate + Send>,
+  ) {
+    warn!("`Window::set_accessibility_source` is ignored on iOS")
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn update_accessibility<F>(&self, _update: F)
+  where
+    F: FnOnce() -> crate::accesskit::TreeUpdate,
+  {
+  }
+
+  pub fn set_blur(&self, _effect: Option<BlurEffect>) {
+    warn!("`Window::set_blur` is ignored on iOS")
+  }
+
+  pub fn set_shadow(&self, _shadow: bool) {
+    warn!("`Window::set_shadow` is ignored on iOS")
+  }
+
   pub fn hide_menu(&self) {
     warn!("`Window::hide_menu` is ignored on iOS")
   }
@@ -376,6 +514,10 @@ impl Inner {
   pub fn theme(&self) -> Theme {
     Theme::Light
   }
+
+  pub fn set_theme(&self, _theme: Option<Theme>) {
+    warn!("`Window::set_theme` is ignored on iOS")
+  }
 }
 
 pub struct Window {
@@ -425,8 +567,8 @@ impl Window {
     if let Some(_) = window_attributes.max_inner_size {
       warn!("`WindowAttributes::max_inner_size` is ignored on iOS");
     }
-    if window_attributes.always_on_top {
-      warn!("`WindowAttributes::always_on_top` is unsupported on iOS");
+    if window_attributes.window_level != WindowLevel::Normal {
+      warn!("`WindowAttributes::window_level` is unsupported on iOS");
     }
     // TODO: transparency, visible
 
@@ -583,6 +725,30 @@ impl Inner {
       ];
     }
   }
+
+  pub fn set_status_bar_style(&self, style: StatusBarStyle) {
+    let style: UIStatusBarStyle = style.into();
+    unsafe {
+      let () = msg_send![self.view_controller, setPreferredStatusBarStyle: style];
+    }
+  }
+
+  pub fn safe_area_insets(&self) -> SafeAreaInsets {
+    unsafe {
+      if !app_state::os_capabilities().safe_area {
+        return SafeAreaInsets::default();
+      }
+      let insets: UIEdgeInsets = msg_send![self.window, safeAreaInsets];
+      let scale_factor = self.scale_factor();
+      let to_physical = |inset: CGFloat| (inset * scale_factor).round() as u32;
+      SafeAreaInsets {
+        top: to_physical(insets.top),
+        left: to_physical(insets.left),
+        bottom: to_physical(insets.bottom),
+        right: to_physical(insets.right),
+      }
+    }
+  }
 }
 
 impl Inner {
@@ -706,6 +872,7 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub valid_orientations: ValidOrientations,
   pub prefers_home_indicator_hidden: bool,
   pub prefers_status_bar_hidden: bool,
+  pub status_bar_style: StatusBarStyle,
   pub preferred_screen_edges_deferring_system_gestures: ScreenEdge,
 }
 
@@ -717,6 +884,7 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       valid_orientations: Default::default(),
       prefers_home_indicator_hidden: false,
       prefers_status_bar_hidden: false,
+      status_bar_style: Default::default(),
       preferred_screen_edges_deferring_system_gestures: Default::default(),
     }
   }
@@ -0,0 +1,118 @@
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The accessibility tree of a window, exposed through AT-SPI by AccessKit, which calls its
+//! handlers on a thread of its own.
+
+use std::{cell::RefCell, rc::Rc};
+
+use accesskit_unix::Adapter;
+use gtk::prelude::*;
+
+use crate::accesskit::{
+  ActionHandler, ActionRequest, ActivationHandler, DeactivationHandler, Rect, TreeUpdate,
+};
+
+use super::window::{WindowId, WindowRequest};
+
+pub(crate) type AccessibilitySource = Box<dyn FnMut() -> TreeUpdate + Send>;
+
+/// The adapter of a window, if it was given a source, shared with the signal handlers of the
+/// window.
+pub(crate) type Accessibility = Rc<RefCell<Option<Adapter>>>;
+
+struct Source(AccessibilitySource);
+
+impl ActivationHandler for Source {
+  fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+    Some((self.0)())
+  }
+}
+
+/// Sends the requested actions to the event loop, which reports them.
+struct ActionForwarder {
+  window_id: WindowId,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+}
+
+impl ActionHandler for ActionForwarder {
+  fn do_action(&mut self, request: ActionRequest) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::AccessibilityAction(request)))
+    {
+      log::warn!("Fail to send accessibility action request: {}", e);
+    }
+  }
+}
+
+/// Nothing is kept for the assistive technologies, the tree being requested again from the
+/// source when they come back.
+struct NoDeactivation;
+
+impl DeactivationHandler for NoDeactivation {
+  fn deactivate_accessibility(&mut self) {}
+}
+
+pub(crate) fn set_source(
+  accessibility: &Accessibility,
+  window: &gtk::ApplicationWindow,
+  window_id: WindowId,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  source: AccessibilitySource,
+) {
+  let mut adapter = Adapter::new(
+    Source(source),
+    ActionForwarder {
+      window_id,
+      window_requests_tx,
+    },
+    NoDeactivation,
+  );
+  adapter.update_window_focus_state(window.is_active());
+  set_bounds(
+    &mut adapter,
+    window.position(),
+    window.size(),
+    window.scale_factor(),
+  );
+  *accessibility.borrow_mut() = Some(adapter);
+}
+
+pub(crate) fn update(accessibility: &Accessibility, update: impl FnOnce() -> TreeUpdate) {
+  if let Some(adapter) = &mut *accessibility.borrow_mut() {
+    adapter.update_if_active(update);
+  }
+}
+
+pub(crate) fn update_focus(accessibility: &Accessibility, is_focused: bool) {
+  if let Some(adapter) = &mut *accessibility.borrow_mut() {
+    adapter.update_window_focus_state(is_focused);
+  }
+}
+
+/// Updates the bounds of the window from its logical position and size, which AT-SPI expects in
+/// physical pixels.
+pub(crate) fn update_bounds(
+  accessibility: &Accessibility,
+  position: (i32, i32),
+  size: (i32, i32),
+  scale_factor: i32,
+) {
+  if let Some(adapter) = &mut *accessibility.borrow_mut() {
+    set_bounds(adapter, position, size, scale_factor);
+  }
+}
+
+fn set_bounds(adapter: &mut Adapter, (x, y): (i32, i32), (w, h): (i32, i32), scale_factor: i32) {
+  let scale_factor = scale_factor as f64;
+  let (x, y) = (x as f64 * scale_factor, y as f64 * scale_factor);
+  let bounds = Rect::new(
+    x,
+    y,
+    x + w as f64 * scale_factor,
+    y + h as f64 * scale_factor,
+  );
+  // The frame isn't known, as is the case of `Window::outer_position` and `Window::outer_size`.
+  adapter.set_root_window_bounds(bounds, bounds);
+}
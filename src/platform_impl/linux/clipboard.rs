@@ -2,9 +2,14 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use std::path::PathBuf;
+
 use gdk::Atom;
+use gdk_pixbuf::{Colorspace, Pixbuf};
 use gtk::{TargetEntry, TargetFlags};
 
+use crate::clipboard::{ClipboardFormat, RgbaImage};
+
 #[derive(Debug, Clone, Default)]
 pub struct Clipboard;
 
@@ -47,4 +52,139 @@ impl Clipboard {
 
     None
   }
+
+  pub(crate) fn write_html(&mut self, html: impl AsRef<str>, alt_text: impl AsRef<str>) {
+    let html = html.as_ref().to_string();
+    let alt_text = alt_text.as_ref().to_string();
+
+    let display = gdk::Display::default().unwrap();
+    let clipboard = gtk::Clipboard::default(&display).unwrap();
+
+    // The HTML target comes first, so its info is 0 and the plain text ones follow.
+    let targets: Vec<TargetEntry> = std::iter::once(&ClipboardFormat::HTML)
+      .chain(CLIPBOARD_TARGETS.iter())
+      .enumerate()
+      .map(|(i, target)| TargetEntry::new(target, TargetFlags::all(), i as u32))
+      .collect();
+
+    clipboard.set_with_data(&targets, move |_, selection, info| {
+      let data = if info == 0 { &html } else { &alt_text };
+      selection.set(&selection.target(), 8i32, data.as_bytes());
+    });
+  }
+
+  pub(crate) fn read_html(&self) -> Option<String> {
+    let display = gdk::Display::default().unwrap();
+    let clipboard = gtk::Clipboard::default(&display).unwrap();
+
+    let selection = clipboard.wait_for_contents(&Atom::intern(ClipboardFormat::HTML))?;
+    let data = selection.data();
+    // Some browsers provide the HTML as UTF-16 with a byte order mark.
+    match data.as_slice() {
+      [0xff, 0xfe, rest @ ..] => {
+        let utf16: Vec<u16> = rest
+          .chunks_exact(2)
+          .map(|c| u16::from_le_bytes([c[0], c[1]]))
+          .collect();
+        String::from_utf16(&utf16).ok()
+      }
+      _ => String::from_utf8(data).ok(),
+    }
+  }
+
+  pub(crate) fn write_files(&mut self, paths: Vec<PathBuf>) {
+    let uris: Vec<String> = paths
+      .iter()
+      .filter_map(|path| glib::filename_to_uri(path, None).ok())
+      .map(|uri| uri.to_string())
+      .collect();
+    let uri_list = uris
+      .iter()
+      .map(|uri| format!("{}\r\n", uri))
+      .collect::<String>();
+    // Nautilus and its derivatives only paste files from their own target.
+    let gnome_copied_files = std::iter::once("copy")
+      .chain(uris.iter().map(String::as_str))
+      .collect::<Vec<_>>()
+      .join("\n");
+
+    let display = gdk::Display::default().unwrap();
+    let clipboard = gtk::Clipboard::default(&display).unwrap();
+
+    let targets = [
+      TargetEntry::new("text/uri-list", TargetFlags::all(), 0),
+      TargetEntry::new("x-special/gnome-copied-files", TargetFlags::all(), 1),
+    ];
+    clipboard.set_with_data(&targets, move |_, selection, info| {
+      let data = if info == 0 {
+        &uri_list
+      } else {
+        &gnome_copied_files
+      };
+      selection.set(&selection.target(), 8i32, data.as_bytes());
+    });
+  }
+
+  pub(crate) fn read_files(&self) -> Option<Vec<PathBuf>> {
+    let display = gdk::Display::default().unwrap();
+    let clipboard = gtk::Clipboard::default(&display).unwrap();
+
+    let uris = clipboard.wait_for_uris();
+    if uris.is_empty() {
+      return None;
+    }
+    Some(
+      uris
+        .iter()
+        .filter_map(|uri| glib::filename_from_uri(uri).ok())
+        .map(|(path, _)| path)
+        .collect(),
+    )
+  }
+
+  pub(crate) fn write_image(&mut self, image: &RgbaImage) {
+    let display = gdk::Display::default().unwrap();
+    let clipboard = gtk::Clipboard::default(&display).unwrap();
+
+    if image.width() == 0 || image.height() == 0 {
+      return;
+    }
+
+    let width = image.width() as i32;
+    let pixbuf = Pixbuf::from_bytes(
+      &glib::Bytes::from(image.rgba()),
+      Colorspace::Rgb,
+      true,
+      8,
+      width,
+      image.height() as i32,
+      width * 4,
+    );
+    clipboard.set_image(&pixbuf);
+  }
+
+  pub(crate) fn read_image(&self) -> Option<RgbaImage> {
+    let display = gdk::Display::default().unwrap();
+    let clipboard = gtk::Clipboard::default(&display).unwrap();
+
+    let pixbuf = clipboard.wait_for_image()?;
+    if pixbuf.bits_per_sample() != 8 {
+      return None;
+    }
+    let width = pixbuf.width() as usize;
+    let height = pixbuf.height() as usize;
+    let channels = pixbuf.n_channels() as usize;
+    let rowstride = pixbuf.rowstride() as usize;
+    let pixels = pixbuf.read_pixel_bytes()?;
+
+    // Rows may be padded, and images without alpha only have 3 channels.
+    let mut rgba = Vec::with_capacity(width * height * 4);
+    for row in pixels.chunks(rowstride).take(height) {
+      for pixel in row[..width * channels].chunks_exact(channels) {
+        rgba.extend_from_slice(&pixel[..3]);
+        rgba.push(if channels == 4 { pixel[3] } else { u8::MAX });
+      }
+    }
+    RgbaImage::from_rgba(rgba, width as u32, height as u32).ok()
+  }
 }
@@ -0,0 +1,135 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Device events on X11. GDK only reports the pointer motion of its own windows, after pointer
+//! acceleration, so the raw XInput 2 events are read from a separate X connection instead, which
+//! receives them for the root window regardless of focus.
+
+use std::{
+  mem::MaybeUninit,
+  os::raw::c_int,
+  ptr, slice,
+  sync::{Mutex, Weak},
+};
+
+use crate::{event::DeviceEvent, event_loop::DeviceEventFilter};
+
+use super::{
+  window::{WindowId, WindowRequest},
+  x11::ffi,
+};
+
+/// How long to wait for an X event before checking whether the event loop is still alive.
+const POLL_TIMEOUT_MS: c_int = 500;
+
+/// Reads the raw motion of the pointing devices on a new thread, and sends it to the event loop
+/// as [`WindowRequest::DeviceEvent`]s unless `filter` is [`DeviceEventFilter::Always`]. The
+/// thread closes its X connection and exits once the event loop drops `filter`. Does nothing
+/// without the XInput 2 extension.
+pub(crate) fn spawn_raw_input_thread(
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  filter: Weak<Mutex<DeviceEventFilter>>,
+) {
+  std::thread::spawn(move || unsafe {
+    let (xlib, xinput2) = match (ffi::Xlib::open(), ffi::XInput2::open()) {
+      (Ok(xlib), Ok(xinput2)) => (xlib, xinput2),
+      _ => return,
+    };
+    let display = (xlib.XOpenDisplay)(ptr::null());
+    if display.is_null() {
+      return;
+    }
+
+    let (mut opcode, mut first_event, mut first_error) = (0, 0, 0);
+    let (mut major, mut minor) = (2, 0);
+    if (xlib.XQueryExtension)(
+      display,
+      b"XInputExtension\0".as_ptr() as *const _,
+      &mut opcode,
+      &mut first_event,
+      &mut first_error,
+    ) == ffi::False
+      || (xinput2.XIQueryVersion)(display, &mut major, &mut minor) != ffi::Success as c_int
+    {
+      log::warn!("XInput 2 isn't available, device events won't be reported");
+      (xlib.XCloseDisplay)(display);
+      return;
+    }
+
+    let mut mask = [0u8; (ffi::XI_LASTEVENT as usize + 7) / 8];
+    ffi::XISetMask(&mut mask, ffi::XI_RawMotion);
+    let mut event_mask = ffi::XIEventMask {
+      deviceid: ffi::XIAllMasterDevices,
+      mask_len: mask.len() as c_int,
+      mask: mask.as_mut_ptr(),
+    };
+    let root = (xlib.XDefaultRootWindow)(display);
+    (xinput2.XISelectEvents)(display, root, &mut event_mask, 1);
+    (xlib.XFlush)(display);
+
+    let mut pollfd = libc::pollfd {
+      fd: (xlib.XConnectionNumber)(display),
+      events: libc::POLLIN,
+      revents: 0,
+    };
+    let mut event = MaybeUninit::<ffi::XEvent>::zeroed().assume_init();
+    // Stops once the event loop is gone.
+    while let Some(filter) = filter.upgrade() {
+      let filter = *filter.lock().unwrap();
+      if (xlib.XPending)(display) == 0 {
+        libc::poll(&mut pollfd, 1, POLL_TIMEOUT_MS);
+        continue;
+      }
+
+      (xlib.XNextEvent)(display, &mut event);
+      let cookie = &mut event.generic_event_cookie;
+      if cookie.type_ != ffi::GenericEvent
+        || cookie.extension != opcode
+        || (xlib.XGetEventData)(display, cookie) == ffi::False
+      {
+        continue;
+      }
+
+      let delta = match cookie.evtype {
+        ffi::XI_RawMotion if filter != DeviceEventFilter::Always => {
+          raw_motion_delta(&*(cookie.data as *const ffi::XIRawEvent))
+        }
+        _ => None,
+      };
+      (xlib.XFreeEventData)(display, cookie);
+
+      if let Some(delta) = delta {
+        let event = DeviceEvent::MouseMotion { delta };
+        if window_requests_tx
+          .send((WindowId::dummy(), WindowRequest::DeviceEvent(event)))
+          .is_err()
+        {
+          // The event loop is gone.
+          break;
+        }
+      }
+    }
+    (xlib.XCloseDisplay)(display);
+  });
+}
+
+/// Returns the unaccelerated motion along the x and y valuators of a raw event, if any.
+unsafe fn raw_motion_delta(event: &ffi::XIRawEvent) -> Option<(f64, f64)> {
+  let mask = slice::from_raw_parts(event.valuators.mask, event.valuators.mask_len as usize);
+  // `raw_values` only holds the valuators set in the mask, in order.
+  let mut value = event.raw_values;
+  let mut delta = (0.0, 0.0);
+  for valuator in 0..(mask.len() * 8) as c_int {
+    if !ffi::XIMaskIsSet(mask, valuator) {
+      continue;
+    }
+    match valuator {
+      0 => delta.0 = *value,
+      1 => delta.1 = *value,
+      _ => {}
+    }
+    value = value.offset(1);
+  }
+  (delta != (0.0, 0.0)).then(|| delta)
+}
@@ -3,44 +3,65 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-  cell::RefCell,
-  collections::{HashSet, VecDeque},
+  cell::{Cell, RefCell},
+  collections::{HashMap, HashSet, VecDeque},
+  env,
   error::Error,
   process,
   rc::Rc,
-  sync::mpsc::SendError,
+  sync::{atomic::Ordering, mpsc::SendError, Arc, Mutex},
   time::Instant,
 };
 
-use cairo::{RectangleInt, Region};
-use gdk::{Cursor, CursorType, EventKey, EventMask, ScrollDirection, WindowEdge, WindowState};
+use gdk::{
+  Cursor, CursorType, EventKey, EventMask, GrabStatus, ScrollDirection, SeatCapabilities,
+  WindowEdge, WindowState,
+};
 use gio::{prelude::*, Cancellable};
 use glib::{source::Priority, Continue, MainContext};
-use gtk::{builders::AboutDialogBuilder, prelude::*, Inhibit};
+use gtk::{builders::AboutDialogBuilder, prelude::*, Inhibit, Settings};
 
-use raw_window_handle::{RawDisplayHandle, XlibDisplayHandle};
+use raw_window_handle::RawDisplayHandle;
 
 use crate::{
   accelerator::AcceleratorId,
-  dpi::{LogicalPosition, LogicalSize},
+  dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+  error::{ExternalError, NotSupportedError},
   event::{
-    ElementState, Event, MouseButton, MouseScrollDelta, StartCause, TouchPhase, WindowEvent,
+    DeviceEvent, DragDropEvent, DropData, ElementState, Event, Ime, MouseButton, MouseScrollDelta,
+    PenInput, Rectangle, StartCause, TouchPhase, WindowEvent,
+  },
+  event_loop::{
+    ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopTask,
+    EventLoopWindowTarget as RootELW,
   },
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootELW},
-  keyboard::ModifiersState,
-  menu::{MenuItem, MenuType},
+  keyboard::{KeyCode, ModifiersState},
+  menu::{AboutMetadata, MenuItem, MenuType},
   monitor::MonitorHandle as RootMonitorHandle,
-  platform_impl::platform::{window::hit_test, DEVICE_ID},
-  window::{CursorIcon, Fullscreen, WindowId as RootWindowId},
+  platform_impl::{
+    opened_urls,
+    platform::{
+      window::{hit_test, HEADER_BAR_BUTTON_ID},
+      DEVICE_ID,
+    },
+  },
+  window::{
+    CursorIcon, DragData, Fullscreen, ResizeDirection, UserAttentionType, WindowId as RootWindowId,
+    RGBA,
+  },
 };
 
 use super::{
-  keyboard,
-  monitor::MonitorHandle,
-  window::{WindowId, WindowRequest},
+  device, event_timestamp, keyboard, menu,
+  monitor::{self, MonitorHandle},
+  session, single_instance, taskbar, theme, wayland,
+  window::{
+    apply_blur, apply_buttons, apply_class, apply_geometry_hints, apply_shadow, apply_window_level,
+    apply_window_shape, WindowId, WindowRequest,
+  },
+  x11, xrandr, OsError,
 };
 
-#[derive(Clone)]
 pub struct EventLoopWindowTarget<T> {
   /// Gdk display
   pub(crate) display: gdk::Display,
@@ -50,9 +71,28 @@ pub struct EventLoopWindowTarget<T> {
   pub(crate) windows: Rc<RefCell<HashSet<WindowId>>>,
   /// Window requests sender
   pub(crate) window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  /// Device event filter of the application
+  pub(crate) device_event_filter: Arc<Mutex<DeviceEventFilter>>,
+  /// Activation token the app was launched with, until its first window is shown
+  pub(crate) startup_token: Rc<Cell<Option<String>>>,
   _marker: std::marker::PhantomData<T>,
 }
 
+// Not derived, since the user event type doesn't have to be `Clone`.
+impl<T> Clone for EventLoopWindowTarget<T> {
+  fn clone(&self) -> Self {
+    Self {
+      display: self.display.clone(),
+      app: self.app.clone(),
+      windows: self.windows.clone(),
+      window_requests_tx: self.window_requests_tx.clone(),
+      device_event_filter: self.device_event_filter.clone(),
+      startup_token: self.startup_token.clone(),
+      _marker: std::marker::PhantomData,
+    }
+  }
+}
+
 impl<T> EventLoopWindowTarget<T> {
   #[inline]
   pub fn available_monitors(&self) -> VecDeque<MonitorHandle> {
@@ -77,16 +117,101 @@ impl<T> EventLoopWindowTarget<T> {
     Some(RootMonitorHandle { inner: handle })
   }
 
+  pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, ExternalError> {
+    if self.is_wayland() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    let pointer = self
+      .display
+      .default_seat()
+      .and_then(|seat| seat.pointer())
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))?;
+    let (_, x, y) = pointer.position();
+    let scale_factor = self
+      .display
+      .monitor_at_point(x, y)
+      .map_or(1, |monitor| monitor.scale_factor());
+    Ok(LogicalPosition::new(x, y).to_physical(scale_factor as f64))
+  }
+
+  pub fn focused_window(&self) -> Option<RootWindowId> {
+    let windows = self.windows.borrow();
+    self
+      .app
+      .windows()
+      .into_iter()
+      .filter(|window| window.is_active())
+      .filter_map(|window| window.downcast::<gtk::ApplicationWindow>().ok())
+      .map(|window| WindowId(window.id()))
+      .find(|id| windows.contains(id))
+      .map(RootWindowId)
+  }
+
+  pub fn activate_app(&self, _ignore_other_apps: bool) {
+    let windows = self.windows.borrow();
+    let active_window = self.app.active_window();
+    let mut app_windows: Vec<gtk::Window> = self
+      .app
+      .windows()
+      .into_iter()
+      .filter(|window| window.is_visible())
+      .filter(|window| {
+        window
+          .downcast_ref::<gtk::ApplicationWindow>()
+          .map_or(false, |window| windows.contains(&WindowId(window.id())))
+      })
+      .collect();
+    // Present the last active window last, so that it ends up on top with the focus.
+    app_windows.sort_by_key(|window| Some(window) == active_window.as_ref());
+    for window in app_windows {
+      window.present_with_time(gdk_sys::GDK_CURRENT_TIME as _);
+    }
+  }
+
+  pub fn is_compositor_active(&self) -> bool {
+    self.display.default_screen().is_composited()
+  }
+
+  pub fn keyboard_layout(&self) -> Option<String> {
+    keyboard::keyboard_layout(&self.display)
+  }
+
+  pub fn modifiers_state(&self) -> ModifiersState {
+    keyboard::modifiers_state(&self.display)
+  }
+
+  pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+    keyboard::is_key_pressed(&self.display, key)
+  }
+
+  pub fn accent_color(&self) -> Option<RGBA> {
+    theme::accent_color()
+  }
+
   pub fn raw_display_handle(&self) -> RawDisplayHandle {
-    let mut display_handle = XlibDisplayHandle::empty();
-    unsafe {
-      if let Ok(xlib) = x11_dl::xlib::Xlib::open() {
-        let display = (xlib.XOpenDisplay)(std::ptr::null());
-        display_handle.display = display as _;
-        display_handle.screen = (xlib.XDefaultScreen)(display) as _;
-      }
+    if self.is_wayland() {
+      RawDisplayHandle::Wayland(wayland::display_handle(&self.display))
+    } else {
+      RawDisplayHandle::Xlib(x11::display_handle(&self.display))
+    }
+  }
+
+  pub fn listen_clipboard(&self, listen: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((WindowId::dummy(), WindowRequest::ListenClipboard(listen)))
+    {
+      log::warn!("Fail to send listen clipboard request: {}", e);
     }
-    RawDisplayHandle::Xlib(display_handle)
+  }
+
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    *self.device_event_filter.lock().unwrap() = filter;
+  }
+
+  pub fn enable_single_instance(&self, identifier: &str) -> bool {
+    single_instance::enable(identifier, self.window_requests_tx.clone())
   }
 
   pub fn is_wayland(&self) -> bool {
@@ -99,40 +224,109 @@ pub struct EventLoop<T: 'static> {
   window_target: RootELW<T>,
   /// User event sender for EventLoopProxy
   user_event_tx: glib::Sender<T>,
+  /// Task sender for EventLoopProxy
+  task_tx: glib::Sender<EventLoopTask<T>>,
   /// Event queue of EventLoop
   events: crossbeam_channel::Receiver<Event<'static, T>>,
   /// Draw queue of EventLoop
   draws: crossbeam_channel::Receiver<WindowId>,
+  /// Scale factor changes of the windows, which can't go through the event queue since
+  /// `WindowEvent::ScaleFactorChanged` borrows the suggested size
+  scale_factors: crossbeam_channel::Receiver<(WindowId, i32)>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PlatformSpecificEventLoopAttributes {
+  pub(crate) any_thread: bool,
+  pub(crate) app_id: Option<String>,
+  pub(crate) dbus_name: Option<String>,
+  pub(crate) opened_urls_from_args: bool,
 }
 
 impl<T: 'static> EventLoop<T> {
-  pub fn new() -> EventLoop<T> {
-    assert_is_main_thread("new_any_thread");
-    EventLoop::new_any_thread()
-  }
+  pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
+    if !attributes.any_thread {
+      assert_is_main_thread("with_any_thread");
+    }
 
-  pub fn new_any_thread() -> EventLoop<T> {
     let context = MainContext::default();
     context
-      .with_thread_default(|| EventLoop::new_gtk().expect("Failed to initialize gtk backend!"))
+      .with_thread_default(|| {
+        EventLoop::new_gtk(attributes).expect("Failed to initialize gtk backend!")
+      })
       .expect("Failed to initialize gtk backend!")
   }
 
-  fn new_gtk() -> Result<EventLoop<T>, Box<dyn Error>> {
+  fn new_gtk(
+    attributes: &PlatformSpecificEventLoopAttributes,
+  ) -> Result<EventLoop<T>, Box<dyn Error>> {
     let context = MainContext::default();
-    let app = gtk::Application::new(None, gio::ApplicationFlags::empty());
-    let app_ = app.clone();
+    // GDK only consumes `DESKTOP_STARTUP_ID`, when it's initialized. Neither token may be
+    // inherited by the processes the app launches.
+    let startup_token = env::var("XDG_ACTIVATION_TOKEN")
+      .or_else(|_| env::var("DESKTOP_STARTUP_ID"))
+      .ok()
+      .filter(|token| !token.is_empty());
+    env::remove_var("XDG_ACTIVATION_TOKEN");
+    env::remove_var("DESKTOP_STARTUP_ID");
+
     let cancellable: Option<&Cancellable> = None;
-    app.register(cancellable)?;
+    // A unique application owns its id on the session bus. A remote one would only forward its
+    // activation to the process owning it, so it's registered again as a non-unique one.
+    let app = match &attributes.dbus_name {
+      Some(name) => {
+        let app = gtk::Application::new(Some(name), gio::ApplicationFlags::empty());
+        app.register(cancellable)?;
+        if app.is_remote() {
+          log::warn!(
+            "The D-Bus name {} is already owned by another process",
+            name
+          );
+          None
+        } else {
+          Some(app)
+        }
+      }
+      None => None,
+    };
+    let app = match app {
+      Some(app) => app,
+      None => {
+        let app_id = attributes
+          .dbus_name
+          .as_deref()
+          .or(attributes.app_id.as_deref());
+        let flags = if app_id.is_some() {
+          gio::ApplicationFlags::NON_UNIQUE
+        } else {
+          gio::ApplicationFlags::empty()
+        };
+        let app = gtk::Application::new(app_id, flags);
+        app.register(cancellable)?;
+        app
+      }
+    };
+    let app_ = app.clone();
 
     // Send StartCause::Init event
     let (event_tx, event_rx) = crossbeam_channel::unbounded();
     let (draw_tx, draw_rx) = crossbeam_channel::unbounded();
+    let (scale_factor_tx, scale_factor_rx) = crossbeam_channel::unbounded();
     let event_tx_ = event_tx.clone();
+    let report_opened_urls = Cell::new(attributes.opened_urls_from_args);
     app.connect_activate(move |_| {
       if let Err(e) = event_tx_.send(Event::NewEvents(StartCause::Init)) {
         log::warn!("Failed to send init event to event channel: {}", e);
       }
+      if !report_opened_urls.replace(false) {
+        return;
+      }
+      let urls = opened_urls::from_args();
+      if !urls.is_empty() {
+        if let Err(e) = event_tx_.send(Event::Opened { urls }) {
+          log::warn!("Failed to send opened event to event channel: {}", e);
+        }
+      }
     });
 
     // Create event loop window target.
@@ -145,9 +339,20 @@ impl<T: 'static> EventLoop<T> {
       app,
       windows: Rc::new(RefCell::new(HashSet::new())),
       window_requests_tx,
+      device_event_filter: Default::default(),
+      startup_token: Rc::new(Cell::new(startup_token)),
       _marker: std::marker::PhantomData,
     };
 
+    // Raw mouse motion
+    if window_target.display.backend().is_x11() {
+      device::spawn_raw_input_thread(
+        window_target.window_requests_tx.clone(),
+        Arc::downgrade(&window_target.device_event_filter),
+      );
+    }
+    let device_event_filter = window_target.device_event_filter.clone();
+
     // Create user event channel
     let (user_event_tx, user_event_rx) = glib::MainContext::channel(Priority::default());
     let event_tx_ = event_tx.clone();
@@ -158,6 +363,42 @@ impl<T: 'static> EventLoop<T> {
       Continue(true)
     });
 
+    // Create task channel
+    let (task_tx, task_rx) = glib::MainContext::channel::<EventLoopTask<T>>(Priority::default());
+    let task_window_target = RootELW {
+      p: window_target.clone(),
+      _marker: std::marker::PhantomData,
+      coalesce_motion_events: Default::default(),
+    };
+    task_rx.attach(Some(&context), move |task| {
+      task(&task_window_target);
+      Continue(true)
+    });
+
+    // Session lock and screen saver notifications
+    session::connect_session_signals(event_tx.clone());
+
+    // System sleep and wake up notifications
+    session::connect_power_signals(event_tx.clone());
+
+    // Monitor hot-plug and scale factor changes
+    monitor::connect_monitor_signals(&window_target.display, event_tx.clone());
+
+    // Keyboard layout switches
+    keyboard::connect_keyboard_layout_signals(&window_target.display, event_tx.clone());
+
+    // Dark and light color scheme switches, and accent color changes
+    theme::connect_theme_signals(event_tx.clone());
+
+    // Handler of the clipboard `owner-change` signal, while listening to it
+    let mut clipboard_owner_change = None;
+
+    // The window holding the keyboard grab of the seat, which is the only one that releases it
+    let mut keyboard_grab_window = None;
+
+    // Windows waiting for the next tick of their frame clock to be redrawn
+    let frame_redraws = Rc::new(RefCell::new(HashSet::new()));
+
     // Window Request
     window_requests_rx.attach(Some(&context), move |(id, request)| {
       if let Some(window) = app_.window_by_id(id.0) {
@@ -165,46 +406,7 @@ impl<T: 'static> EventLoop<T> {
           WindowRequest::Title(title) => window.set_title(&title),
           WindowRequest::Position((x, y)) => window.move_(x, y),
           WindowRequest::Size((w, h)) => window.resize(w, h),
-          WindowRequest::MinSize((min_width, min_height)) => {
-            let picky_none: Option<&gtk::Window> = None;
-            window.set_geometry_hints(
-              picky_none,
-              Some(&gdk::Geometry::new(
-                min_width,
-                min_height,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0,
-                0f64,
-                0f64,
-                gdk::Gravity::Center,
-              )),
-              gdk::WindowHints::MIN_SIZE,
-            )
-          }
-          WindowRequest::MaxSize((max_width, max_height)) => {
-            let picky_none: Option<&gtk::Window> = None;
-            window.set_geometry_hints(
-              picky_none,
-              Some(&gdk::Geometry::new(
-                0,
-                0,
-                max_width,
-                max_height,
-                0,
-                0,
-                0,
-                0,
-                0f64,
-                0f64,
-                gdk::Gravity::Center,
-              )),
-              gdk::WindowHints::MAX_SIZE,
-            )
-          }
+          WindowRequest::GeometryHints(hints) => apply_geometry_hints(&window, hints),
           WindowRequest::Visible(visible) => {
             if visible {
               window.show_all();
@@ -244,41 +446,181 @@ impl<T: 'static> EventLoop<T> {
               window.begin_move_drag(1, x, y, 0);
             }
           }
-          WindowRequest::Fullscreen(fullscreen) => match fullscreen {
-            Some(f) => {
-              if let Fullscreen::Borderless(m) = f {
-                if let Some(monitor) = m {
-                  let number = monitor.inner.number;
-                  let screen = window.display().default_screen();
-                  window.fullscreen_on_monitor(&screen, number);
+          WindowRequest::DragResizeWindow(direction) => {
+            if let Some(cursor) = window
+              .display()
+              .default_seat()
+              .and_then(|seat| seat.pointer())
+            {
+              let (_, x, y) = cursor.position();
+              let edge = match direction {
+                ResizeDirection::East => WindowEdge::East,
+                ResizeDirection::North => WindowEdge::North,
+                ResizeDirection::NorthEast => WindowEdge::NorthEast,
+                ResizeDirection::NorthWest => WindowEdge::NorthWest,
+                ResizeDirection::South => WindowEdge::South,
+                ResizeDirection::SouthEast => WindowEdge::SouthEast,
+                ResizeDirection::SouthWest => WindowEdge::SouthWest,
+                ResizeDirection::West => WindowEdge::West,
+              };
+              window.begin_resize_drag(edge, 1, x, y, 0);
+            }
+          }
+          WindowRequest::StartDrag(data) => {
+            let targets = gtk::TargetList::new(&[]);
+            match &data {
+              DragData::Files(_) => targets.add_uri_targets(0),
+              DragData::Text(_) => targets.add_text_targets(0),
+            }
+
+            // The handlers only live as long as the drag.
+            let handlers = Rc::new(RefCell::new(Vec::new()));
+            let data_get = window.connect_drag_data_get(move |_, _, selection, _, _| match &data {
+              DragData::Files(paths) => {
+                let uris = paths
+                  .iter()
+                  .filter_map(|path| glib::filename_to_uri(path, None).ok())
+                  .collect::<Vec<_>>();
+                selection.set_uris(&uris.iter().map(|uri| uri.as_str()).collect::<Vec<_>>());
+              }
+              DragData::Text(text) => {
+                selection.set_text(text);
+              }
+            });
+            let handlers_ = handlers.clone();
+            let drag_end = window.connect_drag_end(move |window, _| {
+              for handler in handlers_.take() {
+                window.disconnect(handler);
+              }
+            });
+            handlers.borrow_mut().extend([data_get, drag_end]);
+
+            let (x, y) = window
+              .display()
+              .default_seat()
+              .and_then(|seat| seat.pointer())
+              .and_then(|cursor| {
+                window
+                  .window()
+                  .map(|window| window.device_position(&cursor))
+              })
+              .map_or((0, 0), |(_, x, y, _)| (x, y));
+            if window
+              .drag_begin_with_coordinates(&targets, gdk::DragAction::COPY, 1, None, x, y)
+              .is_none()
+            {
+              for handler in handlers.take() {
+                window.disconnect(handler);
+              }
+            }
+          }
+          WindowRequest::Fullscreen(fullscreen) => {
+            xrandr::restore_window_mode(id);
+            match fullscreen {
+              Some(Fullscreen::Exclusive(video_mode)) => {
+                // Without XRandR, e.g. on Wayland, this falls back to borderless fullscreen.
+                let video_mode = video_mode.video_mode;
+                video_mode.monitor.set_video_mode(id, &video_mode);
+                let screen = window.display().default_screen();
+                window.fullscreen_on_monitor(&screen, video_mode.monitor.number);
+              }
+              Some(Fullscreen::Borderless(Some(monitor))) => {
+                let screen = window.display().default_screen();
+                window.fullscreen_on_monitor(&screen, monitor.inner.number);
+              }
+              Some(Fullscreen::Borderless(None)) => window.fullscreen(),
+              None => window.unfullscreen(),
+            }
+          }
+          WindowRequest::Decorations(decorations) => window.set_decorated(decorations),
+          WindowRequest::Buttons(buttons) => apply_buttons(&window, buttons),
+          WindowRequest::WindowShape(shape) => apply_window_shape(&window, &shape),
+          WindowRequest::Blur(effect) => apply_blur(&window, effect),
+          WindowRequest::Shadow(shadow) => apply_shadow(&window, shadow),
+          WindowRequest::DropHighlight(highlight) => {
+            if highlight {
+              window.drag_highlight();
+            } else {
+              window.drag_unhighlight();
+            }
+          }
+          WindowRequest::KeyboardGrab(grab) => {
+            if let Some(seat) = window.display().default_seat() {
+              if !grab {
+                // The grab of a seat isn't tied to a window, so releasing it for another window
+                // would end the grab of the one holding it.
+                if keyboard_grab_window == Some(id) {
+                  keyboard_grab_window = None;
+                  seat.ungrab();
+                }
+              } else if let Some(gdk_window) = window.window() {
+                // On Wayland, GDK also asks the compositor to inhibit its shortcuts.
+                let status = seat.grab(
+                  &gdk_window,
+                  SeatCapabilities::KEYBOARD,
+                  false,
+                  None,
+                  None,
+                  None,
+                );
+                if status == GrabStatus::Success {
+                  keyboard_grab_window = Some(id);
                 } else {
-                  window.fullscreen();
+                  log::warn!("Fail to grab the keyboard: {:?}", status);
                 }
               }
             }
-            None => window.unfullscreen(),
-          },
-          WindowRequest::Decorations(decorations) => window.set_decorated(decorations),
-          WindowRequest::AlwaysOnBottom(always_on_bottom) => {
-            window.set_keep_below(always_on_bottom)
           }
-          WindowRequest::AlwaysOnTop(always_on_top) => window.set_keep_above(always_on_top),
-          WindowRequest::WindowIcon(window_icon) => {
-            if let Some(icon) = window_icon {
-              window.set_icon(Some(&icon.inner.into()));
+          WindowRequest::WindowLevel(window_level) => apply_window_level(&window, window_level),
+          WindowRequest::VisibleOnAllWorkspaces(visible) => {
+            if visible {
+              window.stick();
+            } else {
+              window.unstick();
             }
           }
+          WindowRequest::WindowIcon(window_icon) => {
+            // Without an icon of its own, the window falls back to the default icon list.
+            let pixbuf: Option<gdk_pixbuf::Pixbuf> = window_icon.map(|icon| icon.inner.into());
+            window.set_icon(pixbuf.as_ref());
+          }
           WindowRequest::UserAttention(request_type) => {
-            window.set_urgency_hint(request_type.is_some())
+            window.set_urgency_hint(request_type.is_some());
+            if request_type == Some(UserAttentionType::Critical) {
+              if let Some(gdk_window) = window.window() {
+                gdk_window.beep();
+              }
+            }
           }
           WindowRequest::SetSkipTaskbar(skip) => {
             window.set_skip_taskbar_hint(skip);
             window.set_skip_pager_hint(skip)
           }
+          WindowRequest::Class(instance, class) => apply_class(&window, &instance, &class),
+          WindowRequest::Role(role) => window.set_role(&role),
           WindowRequest::CursorIcon(cursor) => {
             if let Some(gdk_window) = window.window() {
               let display = window.display();
               match cursor {
+                Some(CursorIcon::Custom(cursor)) => {
+                  let image = &cursor.image;
+                  let (width, height) = (image.rgba.width as i32, image.rgba.height as i32);
+                  let pixbuf = gdk_pixbuf::Pixbuf::from_mut_slice(
+                    image.rgba.rgba.clone(),
+                    gdk_pixbuf::Colorspace::Rgb,
+                    true,
+                    8,
+                    width,
+                    height,
+                    width * 4,
+                  );
+                  gdk_window.set_cursor(Some(&Cursor::from_pixbuf(
+                    &display,
+                    &pixbuf,
+                    image.hotspot_x as i32,
+                    image.hotspot_y as i32,
+                  )));
+                }
                 Some(cr) => gdk_window.set_cursor(
                   Cursor::from_name(
                     &display,
@@ -317,7 +659,7 @@ impl<T: 'static> EventLoop<T> {
                       CursorIcon::NwseResize => "nwse-resize",
                       CursorIcon::ColResize => "col-resize",
                       CursorIcon::RowResize => "row-resize",
-                      CursorIcon::Default => "default",
+                      CursorIcon::Default | CursorIcon::Custom(_) => "default",
                     },
                   )
                   .as_ref(),
@@ -328,33 +670,29 @@ impl<T: 'static> EventLoop<T> {
             };
           }
           WindowRequest::CursorPosition((x, y)) => {
-            if let Some(cursor) = window
-              .display()
-              .default_seat()
-              .and_then(|seat| seat.pointer())
-            {
-              if let Some(screen) = window.screen() {
-                cursor.warp(&screen, x, y);
-              }
-            }
-          }
-          WindowRequest::CursorIgnoreEvents(ignore) => {
-            if ignore {
-              let empty_region = Region::create_rectangle(&RectangleInt {
-                x: 0,
-                y: 0,
-                width: 1,
-                height: 1,
-              });
+            // The origin of the client area is only known here, after pending moves.
+            if let (Some(cursor), Some(gdk_window)) = (
               window
-                .window()
-                .unwrap()
-                .input_shape_combine_region(&empty_region, 0, 0);
-            } else {
-              window.input_shape_combine_region(None)
-            };
+                .display()
+                .default_seat()
+                .and_then(|seat| seat.pointer()),
+              window.window(),
+            ) {
+              let (_, origin_x, origin_y) = gdk_window.origin();
+              cursor.warp(&gdk_window.screen(), origin_x + x, origin_y + y);
+            }
           }
-          WindowRequest::WireUpEvents { transparent } => {
+          WindowRequest::WireUpEvents {
+            transparent,
+            ime_context: ime,
+            ime_allowed,
+            minimize_to_tray,
+            background_color,
+            drag_regions,
+            preferred_theme,
+            resize_borders,
+            drag_and_drop,
+          } => {
             window.add_events(
               EventMask::POINTER_MOTION_MASK
                 | EventMask::BUTTON1_MOTION_MASK
@@ -362,12 +700,17 @@ impl<T: 'static> EventLoop<T> {
                 | EventMask::TOUCH_MASK
                 | EventMask::STRUCTURE_MASK
                 | EventMask::FOCUS_CHANGE_MASK
-                | EventMask::SCROLL_MASK,
+                | EventMask::SCROLL_MASK
+                | EventMask::TOUCHPAD_GESTURE_MASK
+                | EventMask::VISIBILITY_NOTIFY_MASK,
             );
 
             // Allow resizing unmaximized borderless window
-            window.connect_motion_notify_event(|window, event| {
-              if !window.is_decorated() && window.is_resizable() && !window.is_maximized() {
+            let has_resize_borders = move |window: &gtk::Window| {
+              resize_borders && !window.is_decorated() && window.is_resizable()
+            };
+            window.connect_motion_notify_event(move |window, event| {
+              if has_resize_borders(window) && !window.is_maximized() {
                 if let Some(window) = window.window() {
                   let (cx, cy) = event.root();
                   let edge = hit_test(&window, cx, cy);
@@ -392,8 +735,12 @@ impl<T: 'static> EventLoop<T> {
               }
               Inhibit(false)
             });
-            window.connect_button_press_event(|window, event| {
-              if !window.is_decorated() && window.is_resizable() && event.button() == 1 {
+            window.connect_button_press_event(move |window, event| {
+              if event.button() != 1 {
+                return Inhibit(false);
+              }
+
+              if has_resize_borders(window) {
                 if let Some(window) = window.window() {
                   let (cx, cy) = event.root();
                   let result = hit_test(&window, cx, cy);
@@ -403,16 +750,34 @@ impl<T: 'static> EventLoop<T> {
                     WindowEdge::__Unknown(_) => (),
                     _ => {
                       // FIXME: calling `window.begin_resize_drag` uses the default cursor, it should show a resizing cursor instead
-                      window.begin_resize_drag(result, 1, cx as i32, cy as i32, event.time())
+                      window.begin_resize_drag(result, 1, cx as i32, cy as i32, event.time());
+                      return Inhibit(false);
                     }
                   }
                 }
               }
 
+              let (x, y) = event.position();
+              let position = LogicalPosition::new(x, y).to_physical(window.scale_factor() as f64);
+              if drag_regions.borrow().contains(position) {
+                // Behave like a titlebar, and keep the click from reaching the app.
+                if event.event_type() == gdk::EventType::DoubleButtonPress {
+                  if window.is_maximized() {
+                    window.unmaximize();
+                  } else {
+                    window.maximize();
+                  }
+                } else if event.event_type() == gdk::EventType::ButtonPress {
+                  let (cx, cy) = event.root();
+                  window.begin_move_drag(1, cx as i32, cy as i32, event.time());
+                }
+                return Inhibit(true);
+              }
+
               Inhibit(false)
             });
-            window.connect_touch_event(|window, event| {
-              if !window.is_decorated() && window.is_resizable() {
+            window.connect_touch_event(move |window, event| {
+              if has_resize_borders(window) {
                 if let Some(window) = window.window() {
                   if let Some((cx, cy)) = event.root_coords() {
                     if let Some(device) = event.device() {
@@ -492,8 +857,31 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            // The keys held down, to flag the presses repeated by the key repeat, which GDK
+            // doesn't, and to release them when the window loses focus.
+            let pressed_keys: Rc<RefCell<HashMap<u16, EventKey>>> = Default::default();
+
             let tx_clone = event_tx.clone();
+            let pressed_keys_ = pressed_keys.clone();
             window.connect_focus_out_event(move |_, _| {
+              // The window won't receive the releases anymore.
+              for (_, event_key) in pressed_keys_.borrow_mut().drain() {
+                let event =
+                  keyboard::make_key_event(&event_key, false, None, ElementState::Released);
+                if let Some(mut event) = event {
+                  event.timestamp = Instant::now();
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::KeyboardInput {
+                      device_id: DEVICE_ID,
+                      event,
+                      is_synthetic: true,
+                    },
+                  }) {
+                    log::warn!("Failed to send keyboard event to event channel: {}", e);
+                  }
+                }
+              }
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Focused(false),
@@ -506,8 +894,48 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            // The GTK settings are shared by every window, and outlive them.
+            let theme_handlers = match Settings::default() {
+              Some(settings) => {
+                let current_theme = Cell::new(theme::system_theme());
+                let tx_clone = event_tx.clone();
+                let check_theme: Rc<dyn Fn()> = Rc::new(move || {
+                  // The theme forced with `set_theme` doesn't follow the system.
+                  if preferred_theme.get().is_some() {
+                    return;
+                  }
+                  let theme = theme::system_theme();
+                  if current_theme.replace(theme) != theme {
+                    if let Err(e) = tx_clone.send(Event::WindowEvent {
+                      window_id: RootWindowId(id),
+                      event: WindowEvent::ThemeChanged(theme),
+                    }) {
+                      log::warn!("Failed to send theme changed event to event channel: {}", e);
+                    }
+                  }
+                });
+                let handlers: Vec<_> = ["gtk-theme-name", "gtk-application-prefer-dark-theme"]
+                  .into_iter()
+                  .map(|property| {
+                    let check_theme = check_theme.clone();
+                    settings.connect_notify_local(Some(property), move |_, _| check_theme())
+                  })
+                  .collect();
+                Some((settings, handlers))
+              }
+              None => None,
+            };
+            let theme_handlers = RefCell::new(theme_handlers);
+
             let tx_clone = event_tx.clone();
             window.connect_destroy(move |_| {
+              if let Some((settings, handlers)) = theme_handlers.take() {
+                for handler in handlers {
+                  settings.disconnect(handler);
+                }
+              }
+              xrandr::restore_window_mode(id);
+              menu::forget_menu_accelerators(id);
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::Destroyed,
@@ -519,24 +947,61 @@ impl<T: 'static> EventLoop<T> {
               }
             });
 
+            // Crossing events aren't reliable while a button is held (e.g. Wayland keeps the
+            // pointer focus during implicit grabs), so enter and leave are also derived from
+            // the motion events, and only sent when the cursor actually crosses the window.
+            let cursor_in_window = Rc::new(Cell::new(false));
             let tx_clone = event_tx.clone();
-            window.connect_enter_notify_event(move |_, _| {
+            let set_cursor_in_window = Rc::new(move |in_window: bool| {
+              if cursor_in_window.replace(in_window) == in_window {
+                return;
+              }
+              let event = if in_window {
+                WindowEvent::CursorEntered {
+                  device_id: DEVICE_ID,
+                }
+              } else {
+                WindowEvent::CursorLeft {
+                  device_id: DEVICE_ID,
+                }
+              };
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
-                event: WindowEvent::CursorEntered {
-                  device_id: DEVICE_ID,
-                },
+                event,
               }) {
                 log::warn!(
-                  "Failed to send cursor entered event to event channel: {}",
+                  "Failed to send cursor crossing event to event channel: {}",
                   e
                 );
               }
+            });
+
+            let set_cursor_in_window_ = set_cursor_in_window.clone();
+            window.connect_enter_notify_event(move |_, _| {
+              set_cursor_in_window_(true);
               Inhibit(false)
             });
 
             let tx_clone = event_tx.clone();
+            let set_cursor_in_window_ = set_cursor_in_window.clone();
             window.connect_motion_notify_event(move |window, motion| {
+              if let Some(pen) = pen_input(motion, TouchPhase::Moved, window.scale_factor()) {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::PenInput(pen),
+                }) {
+                  log::warn!("Failed to send pen event to event channel: {}", e);
+                }
+              }
+
+              let (mx, my) = motion.position();
+              set_cursor_in_window_(
+                mx >= 0.
+                  && my >= 0.
+                  && mx < window.allocated_width() as f64
+                  && my < window.allocated_height() as f64,
+              );
+
               if let Some(cursor) = motion.device() {
                 let scale_factor = window.scale_factor();
                 let (_, x, y) = cursor.window_at_position();
@@ -545,6 +1010,7 @@ impl<T: 'static> EventLoop<T> {
                   event: WindowEvent::CursorMoved {
                     position: LogicalPosition::new(x, y).to_physical(scale_factor as f64),
                     device_id: DEVICE_ID,
+                    timestamp: event_timestamp(motion.time()),
                     // this field is depracted so it is fine to pass empty state
                     modifiers: ModifiersState::empty(),
                   },
@@ -555,22 +1021,25 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
-            let tx_clone = event_tx.clone();
             window.connect_leave_notify_event(move |_, _| {
-              if let Err(e) = tx_clone.send(Event::WindowEvent {
-                window_id: RootWindowId(id),
-                event: WindowEvent::CursorLeft {
-                  device_id: DEVICE_ID,
-                },
-              }) {
-                log::warn!("Failed to send cursor left event to event channel: {}", e);
-              }
+              set_cursor_in_window(false);
               Inhibit(false)
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_button_press_event(move |_, event| {
+            window.connect_button_press_event(move |window, event| {
               let button = event.button();
+              // The other buttons are the barrel buttons.
+              if button == 1 {
+                if let Some(pen) = pen_input(event, TouchPhase::Started, window.scale_factor()) {
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::PenInput(pen),
+                  }) {
+                    log::warn!("Failed to send pen event to event channel: {}", e);
+                  }
+                }
+              }
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseInput {
@@ -582,6 +1051,7 @@ impl<T: 'static> EventLoop<T> {
                   },
                   state: ElementState::Pressed,
                   device_id: DEVICE_ID,
+                  timestamp: event_timestamp(event.time()),
                   // this field is depracted so it is fine to pass empty state
                   modifiers: ModifiersState::empty(),
                 },
@@ -595,8 +1065,18 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
-            window.connect_button_release_event(move |_, event| {
+            window.connect_button_release_event(move |window, event| {
               let button = event.button();
+              if button == 1 {
+                if let Some(pen) = pen_input(event, TouchPhase::Ended, window.scale_factor()) {
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::PenInput(pen),
+                  }) {
+                    log::warn!("Failed to send pen event to event channel: {}", e);
+                  }
+                }
+              }
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseInput {
@@ -608,6 +1088,7 @@ impl<T: 'static> EventLoop<T> {
                   },
                   state: ElementState::Released,
                   device_id: DEVICE_ID,
+                  timestamp: event_timestamp(event.time()),
                   // this field is depracted so it is fine to pass empty state
                   modifiers: ModifiersState::empty(),
                 },
@@ -621,17 +1102,38 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let tx_clone = event_tx.clone();
+            // Whether a touchpad gesture is in progress, to report its first event as `Started`.
+            let touchpad_scrolling = Cell::new(false);
             window.connect_scroll_event(move |_, event| {
-              let (x, y) = event.delta();
+              let is_touchpad = event.source_device().map_or(false, |device| {
+                device.source() == gdk::InputSource::Touchpad
+              });
+              // Wheels without smooth scrolling only report a direction, of one line. The deltas
+              // are in GTK's coordinates, where y points down, and flipped to the
+              // `MouseScrollDelta` convention, where positive scrolls away from the user.
+              let ((x, y), phase) = match event.direction() {
+                ScrollDirection::Smooth if event.is_stop() => {
+                  touchpad_scrolling.set(false);
+                  ((0.0, 0.0), TouchPhase::Ended)
+                }
+                ScrollDirection::Smooth if is_touchpad && !touchpad_scrolling.replace(true) => {
+                  (event.delta(), TouchPhase::Started)
+                }
+                ScrollDirection::Smooth => (event.delta(), TouchPhase::Moved),
+                ScrollDirection::Up => ((0.0, -1.0), TouchPhase::Ended),
+                ScrollDirection::Down => ((0.0, 1.0), TouchPhase::Ended),
+                ScrollDirection::Left => ((-1.0, 0.0), TouchPhase::Ended),
+                ScrollDirection::Right => ((1.0, 0.0), TouchPhase::Ended),
+                _ => (event.delta(), TouchPhase::Ended),
+              };
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::MouseWheel {
                   device_id: DEVICE_ID,
-                  delta: MouseScrollDelta::LineDelta(x as f32, y as f32),
-                  phase: match event.direction() {
-                    ScrollDirection::Smooth => TouchPhase::Moved,
-                    _ => TouchPhase::Ended,
-                  },
+                  delta: MouseScrollDelta::LineDelta(x as f32, -y as f32),
+                  phase,
+                  inertial: false,
+                  timestamp: event_timestamp(event.time()),
                   modifiers: ModifiersState::empty(),
                 },
               }) {
@@ -640,6 +1142,114 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            // Touchpad pinch and rotation. GTK reports the scale and angle since the gesture
+            // began, the events the change since the last one.
+            let send_gesture = {
+              let tx_clone = event_tx.clone();
+              Rc::new(move |event| {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event,
+                }) {
+                  log::warn!("Failed to send gesture event to event channel: {}", e);
+                }
+              })
+            };
+            let zoom = gtk::GestureZoom::new(&window);
+            let last_scale = Rc::new(Cell::new(1.0));
+            {
+              let send_gesture = send_gesture.clone();
+              let last_scale = last_scale.clone();
+              zoom.connect_begin(move |_, _| {
+                last_scale.set(1.0);
+                send_gesture(WindowEvent::TouchpadMagnify {
+                  device_id: DEVICE_ID,
+                  delta: 0.0,
+                  phase: TouchPhase::Started,
+                });
+              });
+            }
+            {
+              let send_gesture = send_gesture.clone();
+              zoom.connect_scale_changed(move |_, scale| {
+                send_gesture(WindowEvent::TouchpadMagnify {
+                  device_id: DEVICE_ID,
+                  delta: scale - last_scale.replace(scale),
+                  phase: TouchPhase::Moved,
+                });
+              });
+            }
+            {
+              let send_gesture = send_gesture.clone();
+              zoom.connect_end(move |_, _| {
+                send_gesture(WindowEvent::TouchpadMagnify {
+                  device_id: DEVICE_ID,
+                  delta: 0.0,
+                  phase: TouchPhase::Ended,
+                });
+              });
+            }
+            let rotate = gtk::GestureRotate::new(&window);
+            let last_angle = Rc::new(Cell::new(0.0));
+            {
+              let send_gesture = send_gesture.clone();
+              let last_angle = last_angle.clone();
+              rotate.connect_begin(move |_, _| {
+                last_angle.set(0.0);
+                send_gesture(WindowEvent::TouchpadRotate {
+                  device_id: DEVICE_ID,
+                  delta: 0.0,
+                  phase: TouchPhase::Started,
+                });
+              });
+            }
+            {
+              let send_gesture = send_gesture.clone();
+              rotate.connect_angle_changed(move |_, _, angle_delta| {
+                // GTK angles grow clockwise.
+                let delta = last_angle.replace(angle_delta) - angle_delta;
+                send_gesture(WindowEvent::TouchpadRotate {
+                  device_id: DEVICE_ID,
+                  delta: delta.to_degrees() as f32,
+                  phase: TouchPhase::Moved,
+                });
+              });
+            }
+            rotate.connect_end(move |_, _| {
+              send_gesture(WindowEvent::TouchpadRotate {
+                device_id: DEVICE_ID,
+                delta: 0.0,
+                phase: TouchPhase::Ended,
+              });
+            });
+            // Widgets don't hold on to their gestures in GTK 3.
+            unsafe { window.set_data("tao-gestures", (zoom, rotate)) };
+
+            if let Some(header_bar) = window
+              .titlebar()
+              .and_then(|titlebar| titlebar.downcast::<gtk::HeaderBar>().ok())
+            {
+              for child in header_bar.children() {
+                let button_id = unsafe { child.data::<u32>(HEADER_BAR_BUTTON_ID) };
+                if let (Some(button_id), Ok(button)) = (button_id, child.downcast::<gtk::Button>())
+                {
+                  let button_id = unsafe { *button_id.as_ref() };
+                  let tx_clone = event_tx.clone();
+                  button.connect_clicked(move |_| {
+                    if let Err(e) = tx_clone.send(Event::WindowEvent {
+                      window_id: RootWindowId(id),
+                      event: WindowEvent::HeaderBarButtonClicked(button_id),
+                    }) {
+                      log::warn!(
+                        "Failed to send header bar button event to event channel: {}",
+                        e
+                      );
+                    }
+                  });
+                }
+              }
+            }
+
             let tx_clone = event_tx.clone();
             let keyboard_handler = Rc::new(move |event_key: EventKey, element_state| {
               // if we have a modifier lets send it
@@ -665,8 +1275,19 @@ impl<T: 'static> EventLoop<T> {
                 }
               }
 
-              // todo: implement repeat?
-              let event = keyboard::make_key_event(&event_key, false, None, element_state);
+              let is_repeat = match element_state {
+                ElementState::Pressed => pressed_keys
+                  .borrow_mut()
+                  .insert(event_key.hardware_keycode(), event_key.clone())
+                  .is_some(),
+                ElementState::Released => {
+                  pressed_keys
+                    .borrow_mut()
+                    .remove(&event_key.hardware_keycode());
+                  false
+                }
+              };
+              let event = keyboard::make_key_event(&event_key, is_repeat, None, element_state);
 
               if let Some(event) = event {
                 if let Err(e) = tx_clone.send(Event::WindowEvent {
@@ -683,12 +1304,56 @@ impl<T: 'static> EventLoop<T> {
               Continue(true)
             });
 
-            let tx_clone = event_tx.clone();
-            // TODO Add actual IME from system
-            let ime = gtk::IMContextSimple::default();
             ime.set_client_window(window.window().as_ref());
             ime.focus_in();
+            let tx_clone = event_tx.clone();
+            let send_ime = Rc::new(move |ime| {
+              if let Err(e) = tx_clone.send(Event::WindowEvent {
+                window_id: RootWindowId(id),
+                event: WindowEvent::Ime(ime),
+              }) {
+                log::warn!("Failed to send IME event to event channel: {}", e);
+              }
+            });
+            // Whether a composition is shown, the text committed outside of one being only
+            // reported as received text.
+            let preediting = Rc::new(Cell::new(false));
+            {
+              let send_ime = send_ime.clone();
+              let preediting = preediting.clone();
+              ime.connect_preedit_start(move |_| {
+                preediting.set(true);
+                send_ime(Ime::Enabled);
+              });
+            }
+            {
+              let send_ime = send_ime.clone();
+              ime.connect_preedit_changed(move |ime| {
+                let (text, _, cursor) = ime.preedit_string();
+                // The cursor is a character index.
+                let cursor = text
+                  .char_indices()
+                  .nth(cursor.max(0) as usize)
+                  .map_or(text.len(), |(index, _)| index);
+                send_ime(Ime::Preedit(text.to_string(), Some((cursor, cursor))));
+              });
+            }
+            {
+              let send_ime = send_ime.clone();
+              let preediting = preediting.clone();
+              ime.connect_preedit_end(move |_| {
+                if preediting.replace(false) {
+                  send_ime(Ime::Preedit(String::new(), None));
+                  send_ime(Ime::Disabled);
+                }
+              });
+            }
+            let tx_clone = event_tx.clone();
             ime.connect_commit(move |_, s| {
+              if preediting.get() {
+                send_ime(Ime::Preedit(String::new(), None));
+                send_ime(Ime::Commit(s.to_string()));
+              }
               if let Err(e) = tx_clone.send(Event::WindowEvent {
                 window_id: RootWindowId(id),
                 event: WindowEvent::ReceivedImeText(s.to_string()),
@@ -701,9 +1366,16 @@ impl<T: 'static> EventLoop<T> {
             });
 
             let handler = keyboard_handler.clone();
+            let ime_ = ime.clone();
+            let ime_allowed_ = ime_allowed.clone();
             window.connect_key_press_event(move |_, event_key| {
+              if menu::activate_menu_accelerator(id, event_key) {
+                return Inhibit(true);
+              }
               handler(event_key.to_owned(), ElementState::Pressed);
-              ime.filter_keypress(event_key);
+              if ime_allowed_.load(Ordering::Acquire) {
+                ime_.filter_keypress(event_key);
+              }
 
               Inhibit(false)
             });
@@ -711,12 +1383,226 @@ impl<T: 'static> EventLoop<T> {
             let handler = keyboard_handler.clone();
             window.connect_key_release_event(move |_, event_key| {
               handler(event_key.to_owned(), ElementState::Released);
+              // Input methods may act on the release of the keys as well.
+              if ime_allowed.load(Ordering::Acquire) {
+                ime.filter_keypress(event_key);
+              }
+              Inhibit(false)
+            });
+
+            if drag_and_drop {
+              // Accepts drops of files, links, text and HTML, whose data is received
+              // asynchronously, one format at a time.
+              let targets = gtk::TargetList::new(&[]);
+              targets.add_uri_targets(0);
+              targets.add_text_targets(0);
+              targets.add(&gdk::Atom::intern("text/html"), 0, 0);
+              window.drag_dest_set(gtk::DestDefaults::empty(), &[], gdk::DragAction::COPY);
+              window.drag_dest_set_target_list(Some(&targets));
+
+              let tx_clone = event_tx.clone();
+              let send_drag_drop = Rc::new(move |event| {
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::DragDrop(event),
+                }) {
+                  log::warn!("Failed to send drag and drop event to event channel: {}", e);
+                }
+              });
+              let drop_state: Rc<RefCell<Option<DropState>>> = Rc::new(RefCell::new(None));
+              {
+                let send_drag_drop = send_drag_drop.clone();
+                let drop_state = drop_state.clone();
+                window.connect_drag_motion(move |window, context, x, y, time| {
+                  let position =
+                    LogicalPosition::new(x, y).to_physical(window.scale_factor() as f64);
+                  if let Some(state) = drop_state.borrow_mut().as_mut() {
+                    state.position = position;
+                    if state.entered {
+                      send_drag_drop(DragDropEvent::Moved { position });
+                    }
+                    context.drag_status(gdk::DragAction::COPY, time);
+                    return true;
+                  }
+
+                  let requested = drop_targets(&context.list_targets());
+                  if requested.is_empty() {
+                    return false;
+                  }
+                  drop_state.replace(Some(DropState {
+                    position,
+                    pending: requested.len(),
+                    ..Default::default()
+                  }));
+                  for target in requested {
+                    window.drag_get_data(context, &target, time);
+                  }
+                  context.drag_status(gdk::DragAction::COPY, time);
+                  true
+                });
+              }
+              {
+                let send_drag_drop = send_drag_drop.clone();
+                let drop_state = drop_state.clone();
+                window.connect_drag_data_received(move |_, context, _, _, selection, _, time| {
+                  let mut drop_state = drop_state.borrow_mut();
+                  let state = match drop_state.as_mut() {
+                    Some(state) => state,
+                    None => return,
+                  };
+                  match selection.target().name().as_str() {
+                    "text/uri-list" => {
+                      for uri in selection.uris() {
+                        match glib::filename_from_uri(&uri) {
+                          Ok((path, _)) => state.data.paths.push(path),
+                          Err(_) => state.data.uris.push(uri.to_string()),
+                        }
+                      }
+                    }
+                    "text/html" => state.data.html = Some(decode_html(&selection.data())),
+                    _ => state.data.text = selection.text().map(|text| text.to_string()),
+                  }
+
+                  state.pending = state.pending.saturating_sub(1);
+                  if state.pending > 0 {
+                    return;
+                  }
+                  if !state.entered {
+                    state.entered = true;
+                    send_drag_drop(DragDropEvent::Entered {
+                      data: state.data.clone(),
+                      position: state.position,
+                    });
+                  }
+                  if state.dropped {
+                    send_drag_drop(DragDropEvent::Dropped {
+                      data: state.data.clone(),
+                      position: state.position,
+                    });
+                    context.drag_finish(true, false, time);
+                    *drop_state = None;
+                  }
+                });
+              }
+              {
+                let send_drag_drop = send_drag_drop.clone();
+                let drop_state = drop_state.clone();
+                window.connect_drag_drop(move |window, context, x, y, time| {
+                  let mut drop_state = drop_state.borrow_mut();
+                  let state = match drop_state.as_mut() {
+                    Some(state) => state,
+                    None => return false,
+                  };
+                  state.position =
+                    LogicalPosition::new(x, y).to_physical(window.scale_factor() as f64);
+                  state.dropped = true;
+                  // Otherwise the drop is reported once the rest of the data is received.
+                  if state.pending == 0 {
+                    send_drag_drop(DragDropEvent::Dropped {
+                      data: state.data.clone(),
+                      position: state.position,
+                    });
+                    context.drag_finish(true, false, time);
+                    *drop_state = None;
+                  }
+                  true
+                });
+              }
+              // `drag-leave` is also emitted right before `drag-drop`, so whether the item left
+              // is only known once the latter was handled.
+              window.connect_drag_leave(move |window, _, _| {
+                window.drag_unhighlight();
+                let send_drag_drop = send_drag_drop.clone();
+                let drop_state = drop_state.clone();
+                glib::idle_add_local_once(move || {
+                  let mut drop_state = drop_state.borrow_mut();
+                  if let Some(state) = drop_state.as_ref().filter(|state| !state.dropped) {
+                    if state.entered {
+                      send_drag_drop(DragDropEvent::Left);
+                    }
+                    *drop_state = None;
+                  }
+                });
+              });
+            }
+
+            // Whether the window is fully obscured by others, and whether it is iconified.
+            let occlusion = Rc::new(Cell::new((false, false)));
+            let update_occlusion = {
+              let tx_clone = event_tx.clone();
+              move |occlusion: &Cell<(bool, bool)>, obscured, iconified| {
+                let (was_obscured, was_iconified) = occlusion.replace((obscured, iconified));
+                let occluded = obscured || iconified;
+                if occluded != (was_obscured || was_iconified) {
+                  if let Err(e) = tx_clone.send(Event::WindowEvent {
+                    window_id: RootWindowId(id),
+                    event: WindowEvent::Occluded(occluded),
+                  }) {
+                    log::warn!("Failed to send occluded event to event channel: {}", e);
+                  }
+                }
+              }
+            };
+
+            let occlusion_clone = occlusion.clone();
+            let update_occlusion_clone = update_occlusion.clone();
+            // The `visibility-notify-event` signal isn't bound, as GTK deprecated it.
+            window.connect_event(move |_, event| {
+              if let Some(event) = event.downcast_ref::<gdk::EventVisibility>() {
+                let obscured = event.state() == gdk::VisibilityState::FullyObscured;
+                let (_, iconified) = occlusion_clone.get();
+                update_occlusion_clone(&occlusion_clone, obscured, iconified);
+              }
               Inhibit(false)
             });
 
             let tx_clone = event_tx.clone();
             window.connect_window_state_event(move |window, event| {
               let state = event.changed_mask();
+              if state.contains(WindowState::ICONIFIED)
+                && event.new_window_state().contains(WindowState::ICONIFIED)
+                && minimize_to_tray.load(Ordering::Acquire)
+              {
+                // Hiding the window also removes it from the taskbar.
+                window.deiconify();
+                window.hide();
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::MinimizedToTray,
+                }) {
+                  log::warn!(
+                    "Failed to send window minimized to tray event to event channel: {}",
+                    e
+                  );
+                }
+                return Inhibit(false);
+              }
+              if state.contains(WindowState::ICONIFIED) {
+                let iconified = event.new_window_state().contains(WindowState::ICONIFIED);
+                let (obscured, _) = occlusion.get();
+                update_occlusion(&occlusion, obscured, iconified);
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Minimized(iconified),
+                }) {
+                  log::warn!(
+                    "Failed to send window minimized event to event channel: {}",
+                    e
+                  );
+                }
+              }
+              if state.contains(WindowState::MAXIMIZED) {
+                let maximized = event.new_window_state().contains(WindowState::MAXIMIZED);
+                if let Err(e) = tx_clone.send(Event::WindowEvent {
+                  window_id: RootWindowId(id),
+                  event: WindowEvent::Maximized(maximized),
+                }) {
+                  log::warn!(
+                    "Failed to send window maximized event to event channel: {}",
+                    e
+                  );
+                }
+              }
               if state.contains(WindowState::ICONIFIED) || state.contains(WindowState::MAXIMIZED) {
                 let scale_factor = window.scale_factor();
 
@@ -746,6 +1632,15 @@ impl<T: 'static> EventLoop<T> {
               Inhibit(false)
             });
 
+            // The window moved to a monitor with another scale factor, or its scale factor
+            // changed.
+            let scale_factor_clone = scale_factor_tx.clone();
+            window.connect_scale_factor_notify(move |window| {
+              if let Err(e) = scale_factor_clone.send((id, window.scale_factor())) {
+                log::warn!("Failed to send scale factor event to event channel: {}", e);
+              }
+            });
+
             // Receive draw events of the window.
             let draw_clone = draw_tx.clone();
             window.connect_draw(move |_, cr| {
@@ -753,7 +1648,14 @@ impl<T: 'static> EventLoop<T> {
                 log::warn!("Failed to send redraw event to event channel: {}", e);
               }
 
-              if transparent {
+              if let Some((r, g, b, a)) = background_color.get() {
+                // Without an RGBA visual, the alpha channel is simply dropped.
+                let alpha = if transparent { a as f64 / 255. } else { 1. };
+                cr.set_source_rgba(r as f64 / 255., g as f64 / 255., b as f64 / 255., alpha);
+                cr.set_operator(cairo::Operator::Source);
+                let _ = cr.paint();
+                cr.set_operator(cairo::Operator::Over);
+              } else if transparent {
                 cr.set_source_rgba(0., 0., 0., 0.);
                 cr.set_operator(cairo::Operator::Source);
                 let _ = cr.paint();
@@ -770,48 +1672,31 @@ impl<T: 'static> EventLoop<T> {
 
             window.queue_draw();
           }
+          WindowRequest::FrameRedraw => {
+            if frame_redraws.borrow_mut().insert(id) {
+              let draw_tx = draw_tx.clone();
+              let frame_redraws = frame_redraws.clone();
+              window.add_tick_callback(move |_, _| {
+                frame_redraws.borrow_mut().remove(&id);
+                if let Err(e) = draw_tx.send(id) {
+                  log::warn!("Failed to send redraw event to event channel: {}", e);
+                }
+                Continue(false)
+              });
+            }
+          }
           WindowRequest::Menu(m) => match m {
             (None, Some(menu_id)) => {
               if let Err(e) = event_tx.send(Event::MenuEvent {
                 window_id: Some(RootWindowId(id)),
                 menu_id,
                 origin: MenuType::MenuBar,
+                tray_id: None,
               }) {
                 log::warn!("Failed to send menu event to event channel: {}", e);
               }
             }
-            (Some(MenuItem::About(name, app)), None) => {
-              let mut builder = AboutDialogBuilder::new()
-                .program_name(&name)
-                .modal(true)
-                .resizable(false);
-              if let Some(version) = &app.version {
-                builder = builder.version(version);
-              }
-              if let Some(authors) = app.authors {
-                builder = builder.authors(authors);
-              }
-              if let Some(comments) = &app.comments {
-                builder = builder.comments(comments);
-              }
-              if let Some(copyright) = &app.copyright {
-                builder = builder.copyright(copyright);
-              }
-              if let Some(license) = &app.license {
-                builder = builder.license(license);
-              }
-              if let Some(website) = &app.website {
-                builder = builder.website(website);
-              }
-              if let Some(website_label) = &app.website_label {
-                builder = builder.website_label(website_label);
-              }
-              let about = builder.build();
-              about.run();
-              unsafe {
-                about.destroy();
-              }
-            }
+            (Some(MenuItem::About(name, app)), None) => show_about_dialog(&name, app),
             (Some(MenuItem::Hide), None) => window.hide(),
             (Some(MenuItem::CloseWindow), None) => window.close(),
             (Some(MenuItem::Quit), None) => {
@@ -841,12 +1726,31 @@ impl<T: 'static> EventLoop<T> {
                 menubar.remove(&i);
               }
               // create all new elements
-              window_menu.generate_menu(&mut menubar, &window_requests_tx_, &accel_group, id);
+              window_menu.generate_menu(&mut menubar, &window_requests_tx_, &accel_group, id, None);
               // make sure all newly added elements are visible
               menubar.show_all();
             }
           }
-          WindowRequest::GlobalHotKey(_hotkey_id) => {}
+          #[cfg(feature = "accesskit")]
+          WindowRequest::AccessibilityAction(request) => {
+            if let Err(e) = event_tx.send(Event::WindowEvent {
+              window_id: RootWindowId(id),
+              event: WindowEvent::AccessibilityActionRequested(request),
+            }) {
+              log::warn!(
+                "Failed to send accessibility action event to event channel: {}",
+                e
+              );
+            }
+          }
+          WindowRequest::GlobalHotKey(_)
+          | WindowRequest::ListenClipboard(_)
+          | WindowRequest::ProgressBar(_)
+          | WindowRequest::BadgeCount(_)
+          | WindowRequest::NewInstance(..)
+          | WindowRequest::DeviceEvent(_)
+          | WindowRequest::TrayMenu(_)
+          | WindowRequest::TrayEvent(..) => {}
         }
       } else if id == WindowId::dummy() {
         match request {
@@ -855,15 +1759,116 @@ impl<T: 'static> EventLoop<T> {
               log::warn!("Failed to send global hotkey event to event channel: {}", e);
             }
           }
+          WindowRequest::ProgressBar(progress) => taskbar::set_progress_bar(progress),
+          WindowRequest::NewInstance(args, cwd) => {
+            if let Err(e) = event_tx.send(Event::NewInstance { args, cwd }) {
+              log::warn!("Failed to send new instance event to event channel: {}", e);
+            }
+          }
+          WindowRequest::BadgeCount(count) => taskbar::set_badge_count(count),
+          WindowRequest::DeviceEvent(event) => {
+            let focused = app_
+              .active_window()
+              .map_or(false, |window| window.is_active());
+            let dispatch = match *device_event_filter.lock().unwrap() {
+              DeviceEventFilter::Always => false,
+              DeviceEventFilter::Unfocused => focused,
+              DeviceEventFilter::Never => true,
+            };
+            if dispatch {
+              let mut events = Vec::with_capacity(3);
+              if let DeviceEvent::MouseMotion { delta: (x, y) } = event {
+                if x != 0.0 {
+                  events.push(DeviceEvent::Motion { axis: 0, value: x });
+                }
+                if y != 0.0 {
+                  events.push(DeviceEvent::Motion { axis: 1, value: y });
+                }
+              }
+              events.push(event);
+              for event in events {
+                if let Err(e) = event_tx.send(Event::DeviceEvent {
+                  device_id: DEVICE_ID,
+                  event,
+                }) {
+                  log::warn!("Failed to send device event to event channel: {}", e);
+                }
+              }
+            }
+          }
+          WindowRequest::ListenClipboard(listen) => {
+            let clipboard = gtk::Clipboard::default(&gdk::Display::default().unwrap()).unwrap();
+            match (listen, clipboard_owner_change.take()) {
+              (true, Some(handler)) => clipboard_owner_change = Some(handler),
+              (true, None) => {
+                let event_tx = event_tx.clone();
+                // gtk-rs has no binding for this signal.
+                clipboard_owner_change =
+                  Some(clipboard.connect_local("owner-change", false, move |_| {
+                    if let Err(e) = event_tx.send(Event::ClipboardUpdated) {
+                      log::warn!("Failed to send clipboard event to event channel: {}", e);
+                    }
+                    None
+                  }));
+              }
+              (false, Some(handler)) => clipboard.disconnect(handler),
+              (false, None) => (),
+            }
+          }
           WindowRequest::Menu((None, Some(menu_id))) => {
             if let Err(e) = event_tx.send(Event::MenuEvent {
               window_id: None,
               menu_id,
               origin: MenuType::ContextMenu,
+              tray_id: None,
             }) {
               log::warn!("Failed to send status bar event to event channel: {}", e);
             }
           }
+          WindowRequest::TrayMenu((tray_id, menu_id)) => {
+            if let Err(e) = event_tx.send(Event::MenuEvent {
+              window_id: None,
+              menu_id,
+              origin: MenuType::ContextMenu,
+              tray_id: Some(tray_id),
+            }) {
+              log::warn!("Failed to send status bar event to event channel: {}", e);
+            }
+          }
+          WindowRequest::TrayEvent(tray_id, event) => {
+            // Indicators don't report where they are shown, only the cursor position is known.
+            let position = gdk::Display::default()
+              .and_then(|display| {
+                let (_, x, y) = display.default_seat()?.pointer()?.position();
+                let scale_factor = display.monitor_at_point(x, y)?.scale_factor();
+                Some(LogicalPosition::new(x, y).to_physical(scale_factor as f64))
+              })
+              .unwrap_or_default();
+            if let Err(e) = event_tx.send(Event::TrayEvent {
+              id: tray_id,
+              bounds: Rectangle {
+                position: PhysicalPosition::default(),
+                size: PhysicalSize::default(),
+              },
+              event,
+              position,
+            }) {
+              log::warn!("Failed to send tray event to event channel: {}", e);
+            }
+          }
+          // Tray menus have no window, so only the native items that don't act on one are
+          // handled.
+          WindowRequest::Menu((Some(MenuItem::About(name, app)), None)) => {
+            show_about_dialog(&name, app)
+          }
+          WindowRequest::Menu((Some(MenuItem::Quit), None)) => {
+            if let Err(e) = event_tx.send(Event::LoopDestroyed) {
+              log::warn!(
+                "Failed to send loop destroyed event to event channel: {}",
+                e
+              );
+            }
+          }
           _ => {}
         }
       }
@@ -875,10 +1880,13 @@ impl<T: 'static> EventLoop<T> {
       window_target: RootELW {
         p: window_target,
         _marker: std::marker::PhantomData,
+        coalesce_motion_events: Default::default(),
       },
       user_event_tx,
+      task_tx,
       events: event_rx,
       draws: draw_rx,
+      scale_factors: scale_factor_rx,
     };
 
     Ok(event_loop)
@@ -939,10 +1947,15 @@ impl<T: 'static> EventLoop<T> {
         let window_target = &self.window_target;
         let events = &self.events;
         let draws = &self.draws;
+        let scale_factors = &self.scale_factors;
 
         window_target.p.app.activate();
 
         let mut state = EventState::NewStart;
+        // The number of events left in the current batch. Events sent while a batch is handled,
+        // e.g. by a flood of mouse motion, wait for the next one, so that `MainEventsCleared` and
+        // the redraws aren't held back indefinitely.
+        let mut batch_events = None;
         let exit_code = loop {
           let mut blocking = false;
           match state {
@@ -952,7 +1965,7 @@ impl<T: 'static> EventLoop<T> {
                 break code;
               }
               ControlFlow::Wait => {
-                if !events.is_empty() || !draws.is_empty() {
+                if !events.is_empty() || !draws.is_empty() || !scale_factors.is_empty() {
                   callback(
                     Event::NewEvents(StartCause::WaitCancelled {
                       start: Instant::now(),
@@ -978,7 +1991,7 @@ impl<T: 'static> EventLoop<T> {
                     &mut control_flow,
                   );
                   state = EventState::EventQueue;
-                } else if !events.is_empty() {
+                } else if !events.is_empty() || !scale_factors.is_empty() {
                   callback(
                     Event::NewEvents(StartCause::WaitCancelled {
                       start,
@@ -1006,17 +2019,52 @@ impl<T: 'static> EventLoop<T> {
                 callback(Event::LoopDestroyed, window_target, &mut control_flow);
                 break (code);
               }
-              _ => match events.try_recv() {
-                Ok(event) => match event {
-                  Event::LoopDestroyed => control_flow = ControlFlow::ExitWithCode(1),
-                  _ => callback(event, window_target, &mut control_flow),
-                },
+              _ => match scale_factors.try_recv() {
+                Ok((id, scale_factor)) => {
+                  if let Some(window) = window_target.p.app.window_by_id(id.0) {
+                    // GTK keeps the logical size, which is suggested at the new scale factor.
+                    let scale_factor = scale_factor as f64;
+                    let (w, h) = window.size();
+                    let size = LogicalSize::new(w, h).to_physical(scale_factor);
+                    let mut new_inner_size = size;
+                    callback(
+                      Event::WindowEvent {
+                        window_id: RootWindowId(id),
+                        event: WindowEvent::ScaleFactorChanged {
+                          scale_factor,
+                          new_inner_size: &mut new_inner_size,
+                        },
+                      },
+                      window_target,
+                      &mut control_flow,
+                    );
+                    if new_inner_size != size {
+                      let (w, h): (i32, i32) =
+                        new_inner_size.to_logical::<i32>(scale_factor).into();
+                      window.resize(w, h);
+                    }
+                  }
+                }
                 Err(_) => {
-                  callback(Event::MainEventsCleared, window_target, &mut control_flow);
-                  if draws.is_empty() {
-                    state = EventState::NewStart;
+                  let remaining = batch_events.get_or_insert_with(|| events.len());
+                  let event = if *remaining > 0 {
+                    *remaining -= 1;
+                    events.try_recv().ok()
                   } else {
-                    state = EventState::DrawQueue;
+                    None
+                  };
+                  match event {
+                    Some(Event::LoopDestroyed) => control_flow = ControlFlow::ExitWithCode(1),
+                    Some(event) => callback(event, window_target, &mut control_flow),
+                    None => {
+                      batch_events = None;
+                      callback(Event::MainEventsCleared, window_target, &mut control_flow);
+                      if draws.is_empty() {
+                        state = EventState::NewStart;
+                      } else {
+                        state = EventState::DrawQueue;
+                      }
+                    }
                   }
                 }
               },
@@ -1039,7 +2087,27 @@ impl<T: 'static> EventLoop<T> {
               }
             },
           }
+
+          // Nothing else wakes the loop up once `WaitUntil` is reached. GLib timeouts have a
+          // millisecond resolution, so the timeout is rounded down and the loop keeps iterating
+          // without blocking for the remainder.
+          let timeout = match (blocking, control_flow) {
+            (true, ControlFlow::WaitUntil(requested_resume)) => {
+              let timeout = glib::timeout_source_new(
+                requested_resume.saturating_duration_since(Instant::now()),
+                None,
+                Priority::default(),
+                || Continue(false),
+              );
+              timeout.attach(Some(&context));
+              Some(timeout)
+            }
+            _ => None,
+          };
           gtk::main_iteration_do(blocking);
+          if let Some(timeout) = timeout {
+            timeout.destroy();
+          }
         };
         exit_code
       })
@@ -1055,6 +2123,7 @@ impl<T: 'static> EventLoop<T> {
   pub fn create_proxy(&self) -> EventLoopProxy<T> {
     EventLoopProxy {
       user_event_tx: self.user_event_tx.clone(),
+      task_tx: self.task_tx.clone(),
     }
   }
 }
@@ -1063,12 +2132,14 @@ impl<T: 'static> EventLoop<T> {
 #[derive(Debug)]
 pub struct EventLoopProxy<T: 'static> {
   user_event_tx: glib::Sender<T>,
+  task_tx: glib::Sender<EventLoopTask<T>>,
 }
 
 impl<T: 'static> Clone for EventLoopProxy<T> {
   fn clone(&self) -> Self {
     Self {
       user_event_tx: self.user_event_tx.clone(),
+      task_tx: self.task_tx.clone(),
     }
   }
 }
@@ -1085,6 +2156,119 @@ impl<T: 'static> EventLoopProxy<T> {
       .send(event)
       .map_err(|SendError(error)| EventLoopClosed(error))
   }
+
+  pub fn send_task(&self, task: EventLoopTask<T>) {
+    if let Err(e) = self.task_tx.send(task) {
+      log::warn!("Failed to send task to the event loop: {}", e);
+    }
+  }
+}
+
+/// A drag and drop over a window.
+#[derive(Default)]
+struct DropState {
+  data: DropData,
+  position: PhysicalPosition<f64>,
+  /// The number of formats of the data not received yet.
+  pending: usize,
+  entered: bool,
+  dropped: bool,
+}
+
+/// Returns the targets to request the data of a dragged item in, one per format.
+fn drop_targets(targets: &[gdk::Atom]) -> Vec<gdk::Atom> {
+  let mut requested = Vec::new();
+  for name in ["text/uri-list", "text/html"] {
+    if let Some(target) = targets.iter().find(|target| target.name() == name) {
+      requested.push(*target);
+    }
+  }
+  if let Some(target) = targets
+    .iter()
+    .find(|&target| gtk::targets_include_text(std::slice::from_ref(target)))
+  {
+    requested.push(*target);
+  }
+  requested
+}
+
+/// Decodes dragged HTML, which some applications send as UTF-16.
+fn decode_html(data: &[u8]) -> String {
+  if let Some(data) = data.strip_prefix(&[0xff, 0xfe]) {
+    let wide = data
+      .chunks_exact(2)
+      .map(|c| u16::from_le_bytes([c[0], c[1]]))
+      .collect::<Vec<_>>();
+    String::from_utf16_lossy(&wide)
+  } else {
+    String::from_utf8_lossy(data).into_owned()
+  }
+}
+
+/// Returns the pen event of `event`, or `None` if it wasn't sent by a pen.
+fn pen_input(event: &gdk::Event, phase: TouchPhase, scale_factor: i32) -> Option<PenInput> {
+  // The tilt is normalized, assume it's the one of a Wacom pen, which goes up to 64°.
+  const MAX_TILT: f64 = 64.0;
+
+  let eraser = match event.source_device()?.source() {
+    gdk::InputSource::Pen => false,
+    gdk::InputSource::Eraser => true,
+    _ => return None,
+  };
+  let (x, y) = event.coords()?;
+  let tilt = event
+    .axis(gdk::AxisUse::Xtilt)
+    .zip(event.axis(gdk::AxisUse::Ytilt))
+    .map(|(x, y)| (x * MAX_TILT, y * MAX_TILT));
+  let barrel_button = event.state().map_or(false, |state| {
+    state.intersects(gdk::ModifierType::BUTTON2_MASK | gdk::ModifierType::BUTTON3_MASK)
+  });
+  Some(PenInput {
+    device_id: DEVICE_ID,
+    phase,
+    location: LogicalPosition::new(x, y).to_physical(scale_factor as f64),
+    pressure: event.axis(gdk::AxisUse::Pressure).unwrap_or(0.0),
+    tilt,
+    // Normalized as well.
+    twist: event
+      .axis(gdk::AxisUse::Rotation)
+      .map(|rotation| rotation * 360.0),
+    eraser,
+    barrel_button,
+  })
+}
+
+fn show_about_dialog(name: &str, app: AboutMetadata) {
+  let mut builder = AboutDialogBuilder::new()
+    .program_name(name)
+    .modal(true)
+    .resizable(false);
+  if let Some(version) = &app.version {
+    builder = builder.version(version);
+  }
+  if let Some(authors) = app.authors {
+    builder = builder.authors(authors);
+  }
+  if let Some(comments) = &app.comments {
+    builder = builder.comments(comments);
+  }
+  if let Some(copyright) = &app.copyright {
+    builder = builder.copyright(copyright);
+  }
+  if let Some(license) = &app.license {
+    builder = builder.license(license);
+  }
+  if let Some(website) = &app.website {
+    builder = builder.website(website);
+  }
+  if let Some(website_label) = &app.website_label {
+    builder = builder.website_label(website_label);
+  }
+  let about = builder.build();
+  about.run();
+  unsafe {
+    about.destroy();
+  }
 }
 
 fn assert_is_main_thread(suggested_method: &str) {
@@ -1092,7 +2276,7 @@ fn assert_is_main_thread(suggested_method: &str) {
     is_main_thread(),
     "Initializing the event loop outside of the main thread is a significant \
              cross-platform compatibility hazard. If you really, absolutely need to create an \
-             EventLoop on a different thread, please use the `EventLoopExtUnix::{}` function.",
+             EventLoop on a different thread, please use the `EventLoopBuilderExtUnix::{}` method.",
     suggested_method
   );
 }
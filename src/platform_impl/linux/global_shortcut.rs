@@ -2,7 +2,10 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use super::window::{WindowId, WindowRequest};
+use super::{
+  portal::GlobalShortcutsPortal,
+  window::{WindowId, WindowRequest},
+};
 use crate::{
   accelerator::{Accelerator, AcceleratorId},
   event_loop::EventLoopWindowTarget,
@@ -31,6 +34,10 @@ pub struct ShortcutManager {
   shortcuts: ListenerMap,
   method_sender: Sender<HotkeyMessage>,
   method_receiver: Receiver<HotkeyMessage>,
+  /// Used instead of X11 key grabs on Wayland, when the desktop portal supports it.
+  portal: Option<GlobalShortcutsPortal>,
+  /// Whether the X11 key grabbing thread was started.
+  x11: bool,
 }
 
 impl ShortcutManager {
@@ -44,12 +51,25 @@ impl ShortcutManager {
     let (method_sender, thread_receiver) = channel::unbounded();
     let (thread_sender, method_receiver) = channel::unbounded();
 
-    if !_window_target.p.is_wayland() {
+    let is_wayland = _window_target.p.is_wayland();
+    let portal = if is_wayland {
+      GlobalShortcutsPortal::new(event_loop_channel.clone())
+    } else {
+      None
+    };
+    // Without the portal, fall back to grabbing keys through XWayland when it is running.
+    let x11 = portal.is_none() && (!is_wayland || std::env::var_os("DISPLAY").is_some());
+
+    if x11 {
       std::thread::spawn(move || {
         let event_loop_channel = event_loop_channel.clone();
         let xlib = xlib::Xlib::open().unwrap();
         unsafe {
           let display = (xlib.XOpenDisplay)(ptr::null());
+          if display.is_null() {
+            // Dropping the channels makes any pending request fail.
+            return;
+          }
           let root = (xlib.XDefaultRootWindow)(display);
 
           // Only trigger key release at end of repeated keys
@@ -173,15 +193,37 @@ impl ShortcutManager {
       shortcuts: hotkeys,
       method_sender,
       method_receiver,
+      portal,
+      x11,
     }
   }
 
+  pub(crate) fn is_supported(&self) -> bool {
+    self.portal.is_some() || self.x11
+  }
+
   pub(crate) fn register(
     &mut self,
     accelerator: Accelerator,
   ) -> Result<RootGlobalShortcut, ShortcutManagerError> {
     let keycode = get_x11_scancode_from_hotkey(accelerator.key);
 
+    if let Some(portal) = &self.portal {
+      let trigger = keycode.and_then(|keycode| portal_trigger(&accelerator, keycode));
+      return match trigger {
+        Some(trigger) => {
+          portal.register(accelerator.clone().id().0, accelerator.to_string(), trigger);
+          Ok(RootGlobalShortcut(GlobalShortcut { accelerator }))
+        }
+        None => Err(ShortcutManagerError::InvalidAccelerator(
+          "Invalid accelerators".into(),
+        )),
+      };
+    }
+    if !self.x11 {
+      return Err(ShortcutManagerError::Unsupported);
+    }
+
     if let Some(keycode) = keycode {
       let mut converted_modifiers: u32 = 0;
       if accelerator.mods.shift_key() {
@@ -232,6 +274,10 @@ impl ShortcutManager {
   }
 
   pub(crate) fn unregister_all(&mut self) -> Result<(), ShortcutManagerError> {
+    if let Some(portal) = &self.portal {
+      portal.unregister_all();
+      return Ok(());
+    }
     for (found_id, _) in self.shortcuts.lock().unwrap().iter() {
       self
         .method_sender
@@ -246,6 +292,16 @@ impl ShortcutManager {
     &self,
     shortcut: RootGlobalShortcut,
   ) -> Result<(), ShortcutManagerError> {
+    if let Some(portal) = &self.portal {
+      return if portal.unregister(shortcut.0.id().0) {
+        Ok(())
+      } else {
+        Err(ShortcutManagerError::AcceleratorNotRegistered(
+          shortcut.0.accelerator,
+        ))
+      };
+    }
+
     let mut found_id = (-1, 0);
     for (id, shortcut_id) in self.shortcuts.lock().unwrap().iter() {
       if *shortcut_id == shortcut.0.id().0 as u32 {
@@ -308,6 +364,25 @@ unsafe impl Sync for WindowId {}
 unsafe impl Send for WindowRequest {}
 unsafe impl Sync for WindowRequest {}
 
+/// Formats an accelerator as a portal trigger, e.g. `CTRL+SHIFT+a`.
+fn portal_trigger(accelerator: &Accelerator, keysym: u32) -> Option<String> {
+  let key = gdk::keys::Key::from(keysym).to_lower().name()?;
+  let mut trigger = String::new();
+  for (pressed, name) in [
+    (accelerator.mods.control_key(), "CTRL"),
+    (accelerator.mods.alt_key(), "ALT"),
+    (accelerator.mods.shift_key(), "SHIFT"),
+    (accelerator.mods.super_key(), "LOGO"),
+  ] {
+    if pressed {
+      trigger.push_str(name);
+      trigger.push('+');
+    }
+  }
+  trigger.push_str(&key);
+  Some(trigger)
+}
+
 fn get_x11_scancode_from_hotkey(key: KeyCode) -> Option<u32> {
   Some(match key {
     KeyCode::KeyA => 'A' as u32,
@@ -390,13 +465,25 @@ fn get_x11_scancode_from_hotkey(key: KeyCode) -> Option<u32> {
     KeyCode::Escape => keysym::XK_Escape,
     KeyCode::PrintScreen => keysym::XK_Print,
     KeyCode::ScrollLock => keysym::XK_Scroll_Lock,
-    KeyCode::Pause => keysym::XF86XK_AudioPlay,
+    KeyCode::Pause => keysym::XK_Pause,
+    KeyCode::MediaPlayPause => keysym::XF86XK_AudioPlay,
+    KeyCode::MediaSelect => keysym::XF86XK_AudioMedia,
     KeyCode::MediaStop => keysym::XF86XK_AudioStop,
     KeyCode::MediaTrackNext => keysym::XF86XK_AudioNext,
     KeyCode::MediaTrackPrevious => keysym::XF86XK_AudioPrev,
     KeyCode::AudioVolumeDown => keysym::XF86XK_AudioLowerVolume,
     KeyCode::AudioVolumeMute => keysym::XF86XK_AudioMute,
     KeyCode::AudioVolumeUp => keysym::XF86XK_AudioRaiseVolume,
+    KeyCode::BrowserBack => keysym::XF86XK_Back,
+    KeyCode::BrowserForward => keysym::XF86XK_Forward,
+    KeyCode::BrowserRefresh => keysym::XF86XK_Reload,
+    KeyCode::BrowserStop => keysym::XF86XK_Stop,
+    KeyCode::BrowserSearch => keysym::XF86XK_Search,
+    KeyCode::BrowserFavorites => keysym::XF86XK_Favorites,
+    KeyCode::BrowserHome => keysym::XF86XK_HomePage,
+    KeyCode::LaunchMail => keysym::XF86XK_Mail,
+    KeyCode::LaunchApp1 => keysym::XF86XK_MyComputer,
+    KeyCode::LaunchApp2 => keysym::XF86XK_Calculator,
     KeyCode::F1 => keysym::XK_F1,
     KeyCode::F2 => keysym::XK_F2,
     KeyCode::F3 => keysym::XK_F3,
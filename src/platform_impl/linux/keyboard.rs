@@ -2,17 +2,26 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use super::KeyEventExtra;
+use super::{
+  event_timestamp,
+  x11::{ffi, XLIB},
+  KeyEventExtra,
+};
 use crate::{
-  event::{ElementState, KeyEvent},
+  event::{ElementState, Event, KeyEvent},
   keyboard::{Key, KeyCode, KeyLocation, ModifiersState, NativeKeyCode},
 };
 use gdk::{keys::constants::*, EventKey};
+use gtk::prelude::*;
 use std::{
+  cell::RefCell,
   collections::HashSet,
-  ffi::c_void,
+  ffi::{c_void, CStr},
+  mem,
   os::raw::{c_int, c_uint},
-  ptr, slice,
+  ptr,
+  rc::Rc,
+  slice,
   sync::Mutex,
 };
 
@@ -22,6 +31,10 @@ lazy_static! {
   static ref KEY_STRINGS: Mutex<HashSet<&'static str>> = Mutex::new(HashSet::new());
 }
 
+// From `X11/extensions/XKB.h`, which x11-dl doesn't define.
+const XKB_USE_CORE_KBD: c_uint = 0x0100;
+const XKB_GROUP_NAMES_MASK: c_uint = 1 << 12;
+
 fn insert_or_get_key_str(string: String) -> &'static str {
   let mut string_set = KEY_STRINGS.lock().unwrap();
   if let Some(contained) = string_set.get(string.as_str()) {
@@ -172,8 +185,8 @@ pub(crate) fn make_key_event(
   let scancode = key.hardware_keycode();
   // a keyval (keysym in X) is a "logical" key name, such as GDK_Enter, GDK_a, GDK_space, etc.
   let keyval_without_modifiers = key.keyval();
-  let keyval_with_modifiers =
-    hardware_keycode_to_keyval(scancode).unwrap_or_else(|| keyval_without_modifiers.clone());
+  let keyval_with_modifiers = hardware_keycode_to_keyval(scancode, key.group())
+    .unwrap_or_else(|| keyval_without_modifiers.clone());
   // get unicode value, with and without modifiers
   let text_without_modifiers = keyval_with_modifiers.to_unicode();
   let text_with_modifiers = keyval_without_modifiers.to_unicode();
@@ -218,6 +231,7 @@ pub(crate) fn make_key_event(
       repeat: is_repeat,
       state,
       text: text_with_all_modifiers,
+      timestamp: event_timestamp(key.time()),
       platform_specific: KeyEventExtra {
         text_with_all_modifiers,
         key_without_modifiers,
@@ -231,8 +245,9 @@ pub(crate) fn make_key_event(
 }
 
 /// Map a hardware keycode to a keyval by performing a lookup in the keymap and finding the
-/// keyval with the lowest group and level
-fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
+/// keyval with the lowest level in `group`, the layout active when the key was pressed. Falls back
+/// to the first group for the keys it doesn't define.
+fn hardware_keycode_to_keyval(keycode: u16, group: u8) -> Option<RawKey> {
   use glib::translate::FromGlib;
   unsafe {
     let keymap = gdk_sys::gdk_keymap_get_default();
@@ -254,13 +269,16 @@ fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
       let keyvals_slice = slice::from_raw_parts(keyvals, nkeys as usize);
       let keys_slice = slice::from_raw_parts(keys, nkeys as usize);
 
-      let resolved_keyval = keys_slice.iter().enumerate().find_map(|(id, gdk_keymap)| {
-        if gdk_keymap.group == 0 && gdk_keymap.level == 0 {
-          Some(RawKey::from_glib(keyvals_slice[id]))
-        } else {
-          None
-        }
-      });
+      let find_keyval = |group: c_int| {
+        keys_slice.iter().enumerate().find_map(|(id, gdk_keymap)| {
+          if gdk_keymap.group == group && gdk_keymap.level == 0 {
+            Some(RawKey::from_glib(keyvals_slice[id]))
+          } else {
+            None
+          }
+        })
+      };
+      let resolved_keyval = find_keyval(group as c_int).or_else(|| find_keyval(0));
 
       // notify glib to free the allocated arrays
       glib_sys::g_free(keyvals as *mut c_void);
@@ -272,6 +290,121 @@ fn hardware_keycode_to_keyval(keycode: u16) -> Option<RawKey> {
   None
 }
 
+/// Returns the name of the active XKB group of `display`, or `None` if it isn't an X11 display.
+pub(crate) fn keyboard_layout(display: &gdk::Display) -> Option<String> {
+  if !display.backend().is_x11() {
+    return None;
+  }
+  let xlib = XLIB.as_ref()?;
+  unsafe {
+    let xdisplay =
+      gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _) as *mut ffi::Display;
+    let mut state: ffi::XkbStateRec = mem::zeroed();
+    if (xlib.XkbGetState)(xdisplay, XKB_USE_CORE_KBD, &mut state) != ffi::Success as c_int {
+      return None;
+    }
+    let desc = (xlib.XkbGetKeyboard)(xdisplay, XKB_GROUP_NAMES_MASK, XKB_USE_CORE_KBD);
+    if desc.is_null() {
+      return None;
+    }
+    let names = (*desc).names;
+    let atom = if names.is_null() {
+      0
+    } else {
+      (*names)
+        .groups
+        .get(state.group as usize)
+        .copied()
+        .unwrap_or(0)
+    };
+    let mut layout = None;
+    if atom != 0 {
+      let name = (xlib.XGetAtomName)(xdisplay, atom);
+      if !name.is_null() {
+        layout = Some(CStr::from_ptr(name).to_string_lossy().into_owned());
+        (xlib.XFree)(name as *mut _);
+      }
+    }
+    (xlib.XkbFreeKeyboard)(desc, 0, ffi::True);
+    layout
+  }
+}
+
+/// Returns the modifiers currently held down, as GDK tracks them for `display`.
+pub(crate) fn modifiers_state(display: &gdk::Display) -> ModifiersState {
+  let state = gdk::Keymap::for_display(display).map_or_else(gdk::ModifierType::empty, |keymap| {
+    gdk::ModifierType::from_bits_truncate(keymap.modifier_state())
+  });
+  let mut mods = ModifiersState::empty();
+  mods.set(
+    ModifiersState::SHIFT,
+    state.contains(gdk::ModifierType::SHIFT_MASK),
+  );
+  mods.set(
+    ModifiersState::CONTROL,
+    state.contains(gdk::ModifierType::CONTROL_MASK),
+  );
+  mods.set(
+    ModifiersState::ALT,
+    state.contains(gdk::ModifierType::MOD1_MASK),
+  );
+  mods.set(
+    ModifiersState::SUPER,
+    state.intersects(gdk::ModifierType::SUPER_MASK | gdk::ModifierType::META_MASK),
+  );
+  mods
+}
+
+/// Returns whether `key` is currently held down, or `false` if it isn't an X11 display.
+pub(crate) fn is_key_pressed(display: &gdk::Display, key: KeyCode) -> bool {
+  if !display.backend().is_x11() {
+    return false;
+  }
+  let (xlib, keycode) = match (XLIB.as_ref(), key.to_scancode()) {
+    (Some(xlib), Some(keycode)) if keycode < 256 => (xlib, keycode as usize),
+    _ => return false,
+  };
+  let mut keys = [0u8; 32];
+  unsafe {
+    let xdisplay =
+      gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _) as *mut ffi::Display;
+    (xlib.XQueryKeymap)(xdisplay, keys.as_mut_ptr() as *mut _);
+  }
+  keys[keycode / 8] & (1 << (keycode % 8)) != 0
+}
+
+/// Sends `Event::KeyboardLayoutChanged` when the active layout of `display` changes.
+///
+/// GDK doesn't have a signal for it, but the keymap reports the state changes that come with it.
+pub(crate) fn connect_keyboard_layout_signals<T: 'static>(
+  display: &gdk::Display,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let keymap = match gdk::Keymap::for_display(display) {
+    Some(keymap) => keymap,
+    None => return,
+  };
+  let layout = RefCell::new(keyboard_layout(display));
+  let display = display.clone();
+  let check_layout = Rc::new(move || {
+    let current = keyboard_layout(&display);
+    if *layout.borrow() != current {
+      layout.replace(current);
+      if let Err(err) = event_tx.send(Event::KeyboardLayoutChanged) {
+        log::warn!(
+          "Failed to send keyboard layout event to event channel: {}",
+          err
+        );
+      }
+    }
+  });
+  let check = check_layout.clone();
+  keymap.connect_state_changed(move |_| check());
+  let check = check_layout.clone();
+  keymap.connect_keys_changed(move |_| check());
+  keymap.connect_direction_changed(move |_| check_layout());
+}
+
 #[allow(non_upper_case_globals)]
 pub fn key_to_raw_key(src: &KeyCode) -> Option<RawKey> {
   Some(match src {
@@ -0,0 +1,115 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Layer-shell surfaces through `gtk-layer-shell`. Few systems ship the library, so it is loaded
+//! at runtime the first time a layer-shell window is created instead of being linked.
+
+use std::os::raw::c_int;
+
+use glib::translate::ToGlibPtr;
+use libloading::Library;
+
+use crate::platform::unix::{Anchor, KeyboardInteractivity, Layer, LayerShellAttributes};
+
+type GtkWindowPtr = *mut gtk::ffi::GtkWindow;
+
+struct GtkLayerShell {
+  is_supported: Option<unsafe extern "C" fn() -> glib::ffi::gboolean>,
+  init_for_window: unsafe extern "C" fn(GtkWindowPtr),
+  set_layer: unsafe extern "C" fn(GtkWindowPtr, c_int),
+  set_anchor: unsafe extern "C" fn(GtkWindowPtr, c_int, glib::ffi::gboolean),
+  set_exclusive_zone: unsafe extern "C" fn(GtkWindowPtr, c_int),
+  // `set_keyboard_mode` replaced `set_keyboard_interactivity` in gtk-layer-shell 0.6.
+  set_keyboard_mode: Option<unsafe extern "C" fn(GtkWindowPtr, c_int)>,
+  set_keyboard_interactivity: Option<unsafe extern "C" fn(GtkWindowPtr, glib::ffi::gboolean)>,
+  _library: Library,
+}
+
+impl GtkLayerShell {
+  fn open() -> Option<Self> {
+    unsafe {
+      let library = Library::new("libgtk-layer-shell.so.0").ok()?;
+      Some(Self {
+        is_supported: library.get(b"gtk_layer_is_supported\0").ok().map(|f| *f),
+        init_for_window: *library.get(b"gtk_layer_init_for_window\0").ok()?,
+        set_layer: *library.get(b"gtk_layer_set_layer\0").ok()?,
+        set_anchor: *library.get(b"gtk_layer_set_anchor\0").ok()?,
+        set_exclusive_zone: *library.get(b"gtk_layer_set_exclusive_zone\0").ok()?,
+        set_keyboard_mode: library
+          .get(b"gtk_layer_set_keyboard_mode\0")
+          .ok()
+          .map(|f| *f),
+        set_keyboard_interactivity: library
+          .get(b"gtk_layer_set_keyboard_interactivity\0")
+          .ok()
+          .map(|f| *f),
+        _library: library,
+      })
+    }
+  }
+}
+
+lazy_static! {
+  static ref GTK_LAYER_SHELL: Option<GtkLayerShell> = GtkLayerShell::open();
+}
+
+/// Turns `window` into a layer-shell surface. It must not have been realized yet.
+///
+/// Returns `false` if `gtk-layer-shell` isn't installed or the compositor doesn't support the
+/// protocol, in which case the window is left untouched.
+pub(crate) fn init(window: &gtk::Window, attributes: &LayerShellAttributes) -> bool {
+  let layer_shell = match &*GTK_LAYER_SHELL {
+    Some(layer_shell) => layer_shell,
+    None => {
+      log::warn!("gtk-layer-shell isn't available");
+      return false;
+    }
+  };
+
+  let window: GtkWindowPtr = window.to_glib_none().0;
+  unsafe {
+    if let Some(is_supported) = layer_shell.is_supported {
+      if is_supported() == glib::ffi::GFALSE {
+        log::warn!("The compositor doesn't support the layer shell protocol");
+        return false;
+      }
+    }
+
+    (layer_shell.init_for_window)(window);
+
+    let layer = match attributes.layer {
+      Layer::Background => 0,
+      Layer::Bottom => 1,
+      Layer::Top => 2,
+      Layer::Overlay => 3,
+    };
+    (layer_shell.set_layer)(window, layer);
+
+    for (edge, anchor) in [
+      (0, Anchor::LEFT),
+      (1, Anchor::RIGHT),
+      (2, Anchor::TOP),
+      (3, Anchor::BOTTOM),
+    ] {
+      (layer_shell.set_anchor)(window, edge, attributes.anchor.contains(anchor) as _);
+    }
+
+    (layer_shell.set_exclusive_zone)(window, attributes.exclusive_zone);
+
+    if let Some(set_keyboard_mode) = layer_shell.set_keyboard_mode {
+      let mode = match attributes.keyboard_interactivity {
+        KeyboardInteractivity::None => 0,
+        KeyboardInteractivity::Exclusive => 1,
+        KeyboardInteractivity::OnDemand => 2,
+      };
+      set_keyboard_mode(window, mode);
+    } else if let Some(set_keyboard_interactivity) = layer_shell.set_keyboard_interactivity {
+      // Older versions only know the exclusive mode.
+      let interactive = attributes.keyboard_interactivity != KeyboardInteractivity::None;
+      set_keyboard_interactivity(window, interactive as _);
+    }
+  }
+
+  true
+}
@@ -2,10 +2,21 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use glib::{Cast, Sender};
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  sync::{Arc, Mutex, MutexGuard},
+};
+
+use gdk::EventKey;
+use gdk_pixbuf::{InterpType, Pixbuf};
+use glib::{
+  translate::{from_glib, from_glib_none, ToGlibPtr},
+  Cast, Sender, WeakRef,
+};
 use gtk::{
-  prelude::*, AccelFlags, AccelGroup, CheckMenuItem, Menu as GtkMenu, MenuItem as GtkMenuItem,
-  SeparatorMenuItem,
+  prelude::*, AccelFlags, AccelGroup, AccelLabel, CheckMenuItem, Image, Menu as GtkMenu,
+  MenuItem as GtkMenuItem, Orientation, RadioMenuItem, SeparatorMenuItem,
 };
 
 use super::{
@@ -16,7 +27,10 @@ use crate::{
   accelerator::Accelerator,
   icon::Icon,
   keyboard::{KeyCode, ModifiersState},
-  menu::{CustomMenuItem, MenuId, MenuItem, MenuType},
+  menu::{
+    CustomMenuItem, MenuId, MenuItem, MenuItemAttributes as RootMenuItemAttributes, MenuType,
+  },
+  TrayId,
 };
 
 macro_rules! menuitem {
@@ -57,23 +71,59 @@ struct SubmenuDetail {
   enabled: bool,
 }
 
+thread_local! {
+  /// The menu items of each window with a keyboard accelerator, see `activate_menu_accelerator`.
+  static MENU_ACCELERATORS: RefCell<HashMap<WindowId, Vec<(Accelerator, WeakRef<GtkMenuItem>)>>> =
+    RefCell::new(HashMap::new());
+}
+
+/// Key of the data holding the `Menu::owner_id` of the menu a GTK menu was built from.
+const GTK_MENU_OWNER: &str = "tao-menu-owner";
+
+/// A menu description, turned into GTK menus as it gets attached to windows and trays.
+///
+/// Clones share the same description, and changes made to it are applied to the GTK menus
+/// built from it.
 #[derive(Debug, Clone)]
 pub struct Menu {
-  gtk_items: Vec<GtkMenuInfo>,
+  gtk_items: Arc<Mutex<Vec<GtkMenuInfo>>>,
+  /// The first item of each radio group, that the following ones join.
+  radio_groups: Arc<Mutex<HashMap<MenuId, RadioMenuItem>>>,
+  gtk_menus: Arc<Mutex<Vec<GtkMenuInstance>>>,
 }
 
+// The GTK objects of a menu are only used on the event loop thread, the menu being sent to it
+// through `WindowRequest`s.
 unsafe impl Send for Menu {}
 unsafe impl Sync for Menu {}
 
+/// A GTK menu built from a `Menu`.
+#[derive(Debug)]
+struct GtkMenuInstance {
+  shell: glib::WeakRef<gtk::MenuShell>,
+  tx: Sender<(WindowId, WindowRequest)>,
+  accel_group: AccelGroup,
+  window_id: WindowId,
+  /// The tray showing the menu, if any.
+  tray_id: Option<TrayId>,
+  /// Copies of radio items form groups of their own.
+  radio_groups: HashMap<MenuId, RadioMenuItem>,
+}
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct MenuItemAttributes {
   id: MenuId,
   key: Option<Accelerator>,
+  /// Whether `key` is only shown next to the label, instead of being registered.
+  key_display_only: bool,
   selected: bool,
+  radio_group: Option<MenuId>,
   enabled: bool,
   menu_type: MenuType,
   gtk_item: GtkMenuItem,
+  /// Every instance of this item, one per window the menu is attached to, `gtk_item` included.
+  gtk_items: Arc<Mutex<Vec<GtkMenuItem>>>,
 }
 
 impl MenuItemAttributes {
@@ -81,27 +131,41 @@ impl MenuItemAttributes {
     self.id
   }
   pub fn title(&self) -> String {
-    self
-      .gtk_item
-      .label()
-      .map(|gstr| gstr.as_str().to_owned())
+    menu_item_label(&self.gtk_item)
+      .map(|label| label.label().as_str().to_owned())
       .unwrap_or("".to_owned())
   }
   pub fn set_enabled(&mut self, is_enabled: bool) {
-    self.gtk_item.set_sensitive(is_enabled);
+    for item in self.gtk_items.lock().unwrap().iter() {
+      item.set_sensitive(is_enabled);
+    }
   }
   pub fn set_title(&mut self, title: &str) {
-    self.gtk_item.set_label(title);
+    for item in self.gtk_items.lock().unwrap().iter() {
+      match menu_item_label(item) {
+        Some(label) => label.set_label(title),
+        None => item.set_label(title),
+      }
+    }
   }
 
   pub fn set_selected(&mut self, is_selected: bool) {
-    if let Some(item) = self.gtk_item.downcast_ref::<CheckMenuItem>() {
-      item.set_active(is_selected);
+    for item in self.gtk_items.lock().unwrap().iter() {
+      if let Some(item) = item.downcast_ref::<CheckMenuItem>() {
+        item.set_active(is_selected);
+      }
     }
   }
 
-  // TODO
-  pub fn set_icon(&mut self, _icon: Icon) {}
+  pub fn set_icon(&mut self, icon: Icon) {
+    let pixbuf =
+      Pixbuf::from(icon.inner).scale_simple(MENU_ICON_SIZE, MENU_ICON_SIZE, InterpType::Bilinear);
+    if let Some(pixbuf) = pixbuf {
+      for item in self.gtk_items.lock().unwrap().iter() {
+        set_menu_item_image(item, &pixbuf);
+      }
+    }
+  }
 }
 
 impl Default for Menu {
@@ -113,7 +177,9 @@ impl Default for Menu {
 impl Menu {
   pub fn new() -> Self {
     Menu {
-      gtk_items: Vec::new(),
+      gtk_items: Default::default(),
+      radio_groups: Default::default(),
+      gtk_menus: Default::default(),
     }
   }
   pub fn new_popup_menu() -> Self {
@@ -122,63 +188,129 @@ impl Menu {
 
   pub fn add_item(
     &mut self,
-    menu_id: MenuId,
-    title: &str,
-    accelerators: Option<Accelerator>,
-    enabled: bool,
-    selected: bool,
+    item: RootMenuItemAttributes<'_>,
+    menu_type: MenuType,
+  ) -> CustomMenuItem {
+    self.insert_item(usize::MAX, item, menu_type)
+  }
+
+  pub fn insert_item(
+    &mut self,
+    index: usize,
+    item: RootMenuItemAttributes<'_>,
     menu_type: MenuType,
   ) -> CustomMenuItem {
-    let gtk_item = if selected {
+    let RootMenuItemAttributes {
+      id: menu_id,
+      title,
+      keyboard_accelerator: accelerators,
+      accelerator_display_only,
+      enabled,
+      selected,
+      radio_group,
+    } = item;
+    let gtk_item = if let Some(radio_group) = radio_group {
+      let item = RadioMenuItem::builder().label(title).build();
+      let mut radio_groups = self.radio_groups.lock().unwrap();
+      match radio_groups.get(&radio_group) {
+        Some(first_item) => item.join_group(Some(first_item)),
+        None => {
+          radio_groups.insert(radio_group, item.clone());
+        }
+      }
+      if selected {
+        item.set_active(true);
+      }
+      item.upcast::<GtkMenuItem>()
+    } else if selected {
       let item = CheckMenuItem::with_label(title);
       item.set_active(true);
       item.upcast::<GtkMenuItem>()
     } else {
-      GtkMenuItem::with_label(title)
+      image_menu_item(title)
     };
+    // Shared with the `Menu` holding the item, which is `Send`.
+    #[allow(clippy::arc_with_non_send_sync)]
     let custom_menu = MenuItemAttributes {
       id: menu_id,
       key: accelerators,
+      key_display_only: accelerator_display_only,
       enabled,
       selected,
+      radio_group,
       menu_type,
+      gtk_items: Arc::new(Mutex::new(vec![gtk_item.clone()])),
       gtk_item,
     };
 
-    self.gtk_items.push(GtkMenuInfo {
-      menu_type: GtkMenuType::Custom,
-      menu_item: None,
-      sub_menu: None,
-      custom_menu_item: Some(custom_menu.clone()),
-    });
+    self.insert_gtk_info(
+      index,
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Custom,
+        menu_item: None,
+        sub_menu: None,
+        custom_menu_item: Some(custom_menu.clone()),
+      },
+    );
     CustomMenuItem(custom_menu)
   }
 
+  pub fn remove_item(&mut self, item: &MenuItemAttributes) {
+    let position = self.gtk_items.lock().unwrap().iter().position(|info| {
+      matches!(&info.custom_menu_item, Some(custom_item) if Arc::ptr_eq(&custom_item.gtk_items, &item.gtk_items))
+    });
+    if let Some(position) = position {
+      self.gtk_items.lock().unwrap().remove(position);
+      for gtk_item in item.gtk_items.lock().unwrap().iter() {
+        self.detach_menu_item(gtk_item);
+      }
+    }
+  }
+
+  pub fn clear(&mut self) {
+    self.gtk_items.lock().unwrap().clear();
+    self.radio_groups.lock().unwrap().clear();
+    for gtk_menu in self.gtk_menus().iter_mut() {
+      if let Some(shell) = gtk_menu.shell.upgrade() {
+        for child in shell.children() {
+          shell.remove(&child);
+        }
+      }
+      gtk_menu.radio_groups.clear();
+    }
+  }
+
   pub fn add_native_item(
     &mut self,
     item: MenuItem,
     _menu_type: MenuType,
   ) -> Option<CustomMenuItem> {
-    self.gtk_items.push(GtkMenuInfo {
-      menu_type: GtkMenuType::Native,
-      menu_item: Some(item),
-      sub_menu: None,
-      custom_menu_item: None,
-    });
+    self.insert_gtk_info(
+      usize::MAX,
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Native,
+        menu_item: Some(item),
+        sub_menu: None,
+        custom_menu_item: None,
+      },
+    );
     None
   }
 
   pub fn add_submenu(&mut self, title: &str, enabled: bool, submenu: Menu) {
-    self.gtk_items.push(GtkMenuInfo {
-      menu_type: GtkMenuType::Submenu,
-      menu_item: None,
-      sub_menu: Some(SubmenuDetail {
-        menu: submenu,
-        title: title.to_string(),
-        enabled,
-      }),
-      custom_menu_item: None,
-    });
+    self.insert_gtk_info(
+      usize::MAX,
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Submenu,
+        menu_item: None,
+        sub_menu: Some(SubmenuDetail {
+          menu: submenu,
+          title: title.to_string(),
+          enabled,
+        }),
+        custom_menu_item: None,
+      },
+    );
   }
 
   pub fn into_gtkmenu(
@@ -186,10 +318,11 @@ impl Menu {
     tx: &Sender<(WindowId, WindowRequest)>,
     accel_group: &AccelGroup,
     window_id: WindowId,
+    tray_id: Option<TrayId>,
   ) -> GtkMenu {
     let mut menu = GtkMenu::new();
     menu.set_accel_group(Some(accel_group));
-    self.generate_menu(&mut menu, tx, accel_group, window_id);
+    self.generate_menu(&mut menu, tx, accel_group, window_id, tray_id);
     menu
   }
 
@@ -199,132 +332,512 @@ impl Menu {
     tx: &Sender<(WindowId, WindowRequest)>,
     accel_group: &AccelGroup,
     window_id: WindowId,
+    tray_id: Option<TrayId>,
   ) {
-    for menu_item in self.gtk_items {
-      let new_item = match menu_item.clone() {
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Submenu,
-          sub_menu:
-            Some(SubmenuDetail {
-              menu,
-              title,
-              enabled,
-              ..
-            }),
-          ..
-        } => {
-          let item = GtkMenuItem::with_label(&title);
-          item.set_sensitive(enabled);
-          item.set_submenu(Some(&menu.into_gtkmenu(tx, accel_group, window_id)));
-          Some(item)
-        }
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Custom,
-          custom_menu_item:
-            Some(MenuItemAttributes {
-              enabled,
-              gtk_item,
-              id,
-              key,
-              ..
-            }),
-          ..
-        } => {
-          if let Some(key) = key {
-            register_accelerator(&gtk_item, accel_group, key);
+    let shell = menu.upcast_ref::<gtk::MenuShell>();
+    // `SetMenu` reuses the menu bar of the window, so we keep track of which menu it shows.
+    unsafe { shell.set_data(GTK_MENU_OWNER, self.owner_id()) };
+    let mut gtk_menu = GtkMenuInstance {
+      shell: shell.downgrade(),
+      tx: tx.clone(),
+      accel_group: accel_group.clone(),
+      window_id,
+      tray_id,
+      radio_groups: HashMap::new(),
+    };
+
+    for menu_item in self.gtk_items.lock().unwrap().iter() {
+      if let Some(new_item) = menu_item.to_gtk_item(&mut gtk_menu) {
+        menu.append(&new_item);
+      }
+    }
+    self.gtk_menus.lock().unwrap().push(gtk_menu);
+  }
+
+  fn owner_id(&self) -> usize {
+    Arc::as_ptr(&self.gtk_items) as usize
+  }
+
+  /// Returns the GTK menus built from this menu that are still shown.
+  fn gtk_menus(&self) -> MutexGuard<'_, Vec<GtkMenuInstance>> {
+    let owner_id = self.owner_id();
+    let mut gtk_menus = self.gtk_menus.lock().unwrap();
+    gtk_menus.retain(|gtk_menu| {
+      gtk_menu.shell.upgrade().map_or(false, |shell| {
+        unsafe { shell.data::<usize>(GTK_MENU_OWNER) }
+          .map_or(false, |owner| unsafe { *owner.as_ref() } == owner_id)
+      })
+    });
+    gtk_menus
+  }
+
+  fn insert_gtk_info(&mut self, index: usize, info: GtkMenuInfo) {
+    let mut gtk_items = self.gtk_items.lock().unwrap();
+    let index = index.min(gtk_items.len());
+    // Some native items aren't shown, so they don't count in the position of the GTK item.
+    let position = gtk_items[..index]
+      .iter()
+      .filter(|info| info.has_gtk_item())
+      .count();
+    gtk_items.insert(index, info.clone());
+    drop(gtk_items);
+
+    for gtk_menu in self.gtk_menus().iter_mut() {
+      if let (Some(shell), Some(item)) = (gtk_menu.shell.upgrade(), info.to_gtk_item(gtk_menu)) {
+        shell.insert(&item, position as i32);
+        item.show_all();
+      }
+    }
+  }
+
+  /// Removes an instance of a custom item from the GTK menu it's in.
+  fn detach_menu_item(&self, item: &GtkMenuItem) {
+    if let Some(radio_item) = item.downcast_ref::<RadioMenuItem>() {
+      // Items added later to the group join it through another item.
+      let next_item = radio_item
+        .group()
+        .into_iter()
+        .find(|other| other != radio_item);
+      let mut radio_groups = self.radio_groups.lock().unwrap();
+      let mut gtk_menus = self.gtk_menus.lock().unwrap();
+      for radio_groups in std::iter::once(&mut *radio_groups).chain(
+        gtk_menus
+          .iter_mut()
+          .map(|gtk_menu| &mut gtk_menu.radio_groups),
+      ) {
+        radio_groups.retain(|_, first_item| {
+          if first_item != radio_item {
+            return true;
           }
+          match &next_item {
+            Some(next_item) => {
+              *first_item = next_item.clone();
+              true
+            }
+            None => false,
+          }
+        });
+      }
+      radio_item.join_group(None::<&RadioMenuItem>);
+    }
 
-          gtk_item.set_sensitive(enabled);
+    if let Some(parent) = item
+      .parent()
+      .and_then(|parent| parent.downcast::<gtk::Container>().ok())
+    {
+      parent.remove(item);
+    }
+  }
+}
 
-          let tx_ = tx.clone();
-          gtk_item.connect_activate(move |_| {
-            if let Err(e) = tx_.send((window_id, WindowRequest::Menu((None, Some(id))))) {
-              log::warn!("Fail to send menu request: {}", e);
+impl GtkMenuInfo {
+  /// Whether the item is shown in GTK menus.
+  fn has_gtk_item(&self) -> bool {
+    match self.menu_type {
+      GtkMenuType::Custom | GtkMenuType::Submenu => true,
+      GtkMenuType::Native => matches!(
+        self.menu_item,
+        Some(
+          MenuItem::Separator
+            | MenuItem::About(..)
+            | MenuItem::Hide
+            | MenuItem::CloseWindow
+            | MenuItem::Quit
+        )
+      ),
+    }
+  }
+
+  fn to_gtk_item(&self, gtk_menu: &mut GtkMenuInstance) -> Option<GtkMenuItem> {
+    let tx = &gtk_menu.tx;
+    let accel_group = &gtk_menu.accel_group;
+    let window_id = gtk_menu.window_id;
+    let tray_id = gtk_menu.tray_id;
+
+    match self.clone() {
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Submenu,
+        sub_menu:
+          Some(SubmenuDetail {
+            menu,
+            title,
+            enabled,
+            ..
+          }),
+        ..
+      } => {
+        let item = GtkMenuItem::with_label(&title);
+        item.set_sensitive(enabled);
+        item.set_submenu(Some(&menu.into_gtkmenu(
+          tx,
+          accel_group,
+          window_id,
+          tray_id,
+        )));
+        Some(item)
+      }
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Custom,
+        custom_menu_item:
+          Some(MenuItemAttributes {
+            enabled,
+            gtk_item,
+            gtk_items,
+            id,
+            key,
+            key_display_only,
+            radio_group,
+            ..
+          }),
+        ..
+      } => {
+        let gtk_item = if gtk_item.parent().is_none() {
+          gtk_item.set_sensitive(enabled);
+          gtk_item
+        } else {
+          // The item is already attached to another window, so we create a copy of it
+          // that will receive the same updates.
+          let item = duplicate_menu_item(&gtk_item);
+          if let (Some(radio_group), Some(radio_item)) =
+            (radio_group, item.downcast_ref::<RadioMenuItem>())
+          {
+            match gtk_menu.radio_groups.get(&radio_group) {
+              Some(first_item) => radio_item.join_group(Some(first_item)),
+              None => {
+                gtk_menu
+                  .radio_groups
+                  .insert(radio_group, radio_item.clone());
+              }
             }
-          });
+            // Joining a group deselects the item.
+            if is_menu_item_selected(&gtk_item) {
+              radio_item.set_active(true);
+            }
+          }
+          gtk_items.lock().unwrap().push(item.clone());
+          item
+        };
 
-          Some(gtk_item)
-        }
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Native,
-          menu_item: Some(MenuItem::Separator),
-          ..
-        } => {
-          menu.append(&SeparatorMenuItem::new());
-          None
-        }
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Native,
-          menu_item: Some(MenuItem::About(name, app)),
-          ..
-        } => {
-          let tx_clone = tx.clone();
-          menuitem!(
-            &format!("About {}", name),
-            "",
-            accel_group,
-            window_id,
-            Some(MenuItem::About(name.clone(), app.clone())),
-            tx_clone
-          )
-        }
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Native,
-          menu_item: Some(MenuItem::Hide),
-          ..
-        } => {
-          let tx_clone = tx.clone();
-          menuitem!(
-            "Hide",
-            "<Ctrl>H",
-            accel_group,
-            window_id,
-            Some(MenuItem::Hide),
-            tx_clone
-          )
-        }
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Native,
-          menu_item: Some(MenuItem::CloseWindow),
-          ..
-        } => {
-          let tx_clone = tx.clone();
-          menuitem!(
-            "Close Window",
-            "<Ctrl>W",
-            accel_group,
-            window_id,
-            Some(MenuItem::CloseWindow),
-            tx_clone
-          )
-        }
-        GtkMenuInfo {
-          menu_type: GtkMenuType::Native,
-          menu_item: Some(MenuItem::Quit),
-          ..
-        } => {
-          let tx_clone = tx.clone();
-          menuitem!(
-            "Quit",
-            "<Ctrl>Q",
-            accel_group,
-            window_id,
-            Some(MenuItem::Quit),
-            tx_clone
-          )
+        if let Some((accel_key, accel_mods)) = key.as_ref().and_then(accelerator_to_gdk) {
+          if key_display_only {
+            if let Some(label) = menu_item_label(&gtk_item) {
+              label.set_accel(accel_key, accel_mods);
+            }
+          } else {
+            gtk_item.add_accelerator(
+              "activate",
+              accel_group,
+              accel_key,
+              accel_mods,
+              AccelFlags::VISIBLE,
+            );
+            if window_id != WindowId::dummy() {
+              if let Some(key) = &key {
+                MENU_ACCELERATORS.with(|accelerators| {
+                  let mut accelerators = accelerators.borrow_mut();
+                  let items = accelerators.entry(window_id).or_default();
+                  // Forget the items of menus that were dropped.
+                  items.retain(|(_, item)| item.upgrade().is_some());
+                  items.push((key.clone(), gtk_item.downgrade()));
+                });
+              }
+            }
+          }
         }
-        // TODO add others
-        _ => None,
-      };
 
-      if let Some(new_item) = new_item {
-        menu.append(&new_item);
+        let tx_ = tx.clone();
+        gtk_item.connect_activate(move |item| {
+          // Radio items are also activated when they get deselected.
+          if let Some(radio_item) = item.downcast_ref::<RadioMenuItem>() {
+            if !radio_item.is_active() {
+              return;
+            }
+          }
+          let request = match tray_id {
+            Some(tray_id) => WindowRequest::TrayMenu((tray_id, id)),
+            None => WindowRequest::Menu((None, Some(id))),
+          };
+          if let Err(e) = tx_.send((window_id, request)) {
+            log::warn!("Fail to send menu request: {}", e);
+          }
+        });
+
+        Some(gtk_item)
+      }
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Native,
+        menu_item: Some(MenuItem::Separator),
+        ..
+      } => Some(SeparatorMenuItem::new().upcast::<GtkMenuItem>()),
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Native,
+        menu_item: Some(MenuItem::About(name, app)),
+        ..
+      } => {
+        let tx_clone = tx.clone();
+        menuitem!(
+          &format!("About {}", name),
+          "",
+          accel_group,
+          window_id,
+          Some(MenuItem::About(name.clone(), app.clone())),
+          tx_clone
+        )
+      }
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Native,
+        menu_item: Some(MenuItem::Hide),
+        ..
+      } => {
+        let tx_clone = tx.clone();
+        menuitem!(
+          "Hide",
+          "<Ctrl>H",
+          accel_group,
+          window_id,
+          Some(MenuItem::Hide),
+          tx_clone
+        )
       }
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Native,
+        menu_item: Some(MenuItem::CloseWindow),
+        ..
+      } => {
+        let tx_clone = tx.clone();
+        menuitem!(
+          "Close Window",
+          "<Ctrl>W",
+          accel_group,
+          window_id,
+          Some(MenuItem::CloseWindow),
+          tx_clone
+        )
+      }
+      GtkMenuInfo {
+        menu_type: GtkMenuType::Native,
+        menu_item: Some(MenuItem::Quit),
+        ..
+      } => {
+        let tx_clone = tx.clone();
+        menuitem!(
+          "Quit",
+          "<Ctrl>Q",
+          accel_group,
+          window_id,
+          Some(MenuItem::Quit),
+          tx_clone
+        )
+      }
+      // TODO add others
+      _ => None,
+    }
+  }
+}
+
+/// Activates the menu item of the window matching a key press, if any.
+///
+/// GTK only triggers the accelerators of a menu bar while it's shown, and after the focused
+/// widget had a chance to handle the key press, so we handle them ourselves before the key press
+/// reaches any widget, like Windows does.
+pub(crate) fn activate_menu_accelerator(window_id: WindowId, event_key: &EventKey) -> bool {
+  let key = KeyCode::from_scancode(event_key.hardware_keycode() as u32);
+  let state = event_key.state();
+  let mut mods = ModifiersState::empty();
+  mods.set(
+    ModifiersState::SHIFT,
+    state.contains(gdk::ModifierType::SHIFT_MASK),
+  );
+  mods.set(
+    ModifiersState::CONTROL,
+    state.contains(gdk::ModifierType::CONTROL_MASK),
+  );
+  mods.set(
+    ModifiersState::ALT,
+    state.contains(gdk::ModifierType::MOD1_MASK),
+  );
+  mods.set(
+    ModifiersState::SUPER,
+    state.intersects(gdk::ModifierType::SUPER_MASK | gdk::ModifierType::META_MASK),
+  );
+
+  let item = MENU_ACCELERATORS.with(|accelerators| {
+    let mut accelerators = accelerators.borrow_mut();
+    let items = accelerators.get_mut(&window_id)?;
+    // Forget the items of menus that were replaced or removed.
+    items.retain(|(_, item)| item.upgrade().map_or(false, |item| is_in_menu_bar(&item)));
+    // The key press of a disabled item goes on to the focused widget, as if it had none.
+    items
+      .iter()
+      .filter(|(accelerator, _)| accelerator.matches(mods, key))
+      .filter_map(|(_, item)| item.upgrade())
+      .find(is_menu_item_sensitive)
+  });
+
+  match item {
+    Some(item) => {
+      item.activate();
+      true
+    }
+    None => false,
+  }
+}
+
+/// Forgets the menu accelerators of a destroyed window.
+pub(crate) fn forget_menu_accelerators(window_id: WindowId) {
+  MENU_ACCELERATORS.with(|accelerators| accelerators.borrow_mut().remove(&window_id));
+}
+
+/// Whether the item is (in a submenu of) a menu bar.
+fn is_in_menu_bar(item: &GtkMenuItem) -> bool {
+  let mut item = item.clone().upcast::<gtk::Widget>();
+  loop {
+    match item.parent() {
+      Some(parent) if parent.is::<gtk::MenuBar>() => return true,
+      Some(parent) => match parent.downcast::<GtkMenu>() {
+        Ok(menu) => match menu.attach_widget() {
+          Some(attach_widget) => item = attach_widget,
+          None => return false,
+        },
+        Err(_) => return false,
+      },
+      None => return false,
     }
   }
 }
 
-fn register_accelerator(item: &GtkMenuItem, accel_group: &AccelGroup, menu_key: Accelerator) {
+/// Whether the item and the submenus it is in are enabled.
+fn is_menu_item_sensitive(item: &GtkMenuItem) -> bool {
+  let mut item = item.clone().upcast::<gtk::Widget>();
+  loop {
+    if !item.is_sensitive() {
+      return false;
+    }
+    match item
+      .parent()
+      .and_then(|parent| parent.downcast::<GtkMenu>().ok())
+      .and_then(|menu| menu.attach_widget())
+    {
+      Some(attach_widget) => item = attach_widget,
+      None => return true,
+    }
+  }
+}
+
+fn duplicate_menu_item(item: &GtkMenuItem) -> GtkMenuItem {
+  let label = menu_item_label(item)
+    .map(|label| label.label().as_str().to_owned())
+    .unwrap_or_default();
+  let new_item = match item.downcast_ref::<CheckMenuItem>() {
+    Some(check_item) => {
+      let new_item = if item.is::<RadioMenuItem>() {
+        RadioMenuItem::builder()
+          .label(&label)
+          .build()
+          .upcast::<CheckMenuItem>()
+      } else {
+        CheckMenuItem::with_label(&label)
+      };
+      new_item.set_active(check_item.is_active());
+      new_item.upcast::<GtkMenuItem>()
+    }
+    None if is_image_menu_item(item) => image_menu_item(&label),
+    None => GtkMenuItem::with_label(&label),
+  };
+  new_item.set_sensitive(item.is_sensitive());
+  if let Some(pixbuf) = menu_item_image(item).and_then(|image| image.pixbuf()) {
+    set_menu_item_image(&new_item, &pixbuf);
+  }
+  new_item
+}
+
+fn is_menu_item_selected(item: &GtkMenuItem) -> bool {
+  item
+    .downcast_ref::<CheckMenuItem>()
+    .map_or(false, |item| item.is_active())
+}
+
+/// The size of menu item icons, matching `GTK_ICON_SIZE_MENU`.
+const MENU_ICON_SIZE: i32 = 16;
+
+/// Creates a `GtkImageMenuItem`, which gtk-rs doesn't bind since it's deprecated. Its image is
+/// the only one the D-Bus menus of system trays export, unlike an image packed next to the label.
+fn image_menu_item(label: &str) -> GtkMenuItem {
+  unsafe {
+    let item: gtk::Widget = from_glib_none(gtk::ffi::gtk_image_menu_item_new_with_label(
+      label.to_glib_none().0,
+    ));
+    item.unsafe_cast()
+  }
+}
+
+fn is_image_menu_item(item: &GtkMenuItem) -> bool {
+  let image_menu_item_type: glib::Type =
+    unsafe { from_glib(gtk::ffi::gtk_image_menu_item_get_type()) };
+  item.type_().is_a(image_menu_item_type)
+}
+
+/// Returns the label of a menu item. Once a check or radio item has an icon, the label is packed
+/// in a box next to it.
+fn menu_item_label(item: &GtkMenuItem) -> Option<AccelLabel> {
+  let child = item.child()?;
+  match child.downcast::<gtk::Box>() {
+    Ok(container) => container
+      .children()
+      .into_iter()
+      .find_map(|child| child.downcast::<AccelLabel>().ok()),
+    Err(child) => child.downcast::<AccelLabel>().ok(),
+  }
+}
+
+fn menu_item_image(item: &GtkMenuItem) -> Option<Image> {
+  if is_image_menu_item(item) {
+    let image: Option<gtk::Widget> = unsafe {
+      from_glib_none(gtk::ffi::gtk_image_menu_item_get_image(
+        item.as_ptr() as *mut gtk::ffi::GtkImageMenuItem
+      ))
+    };
+    return image?.downcast::<Image>().ok();
+  }
+  item
+    .child()?
+    .downcast::<gtk::Box>()
+    .ok()?
+    .children()
+    .into_iter()
+    .find_map(|child| child.downcast::<Image>().ok())
+}
+
+fn set_menu_item_image(item: &GtkMenuItem, pixbuf: &Pixbuf) {
+  if let Some(image) = menu_item_image(item) {
+    image.set_from_pixbuf(Some(pixbuf));
+    return;
+  }
+
+  if is_image_menu_item(item) {
+    let image = Image::from_pixbuf(Some(pixbuf));
+    unsafe {
+      let item = item.as_ptr() as *mut gtk::ffi::GtkImageMenuItem;
+      gtk::ffi::gtk_image_menu_item_set_image(
+        item,
+        image.upcast_ref::<gtk::Widget>().to_glib_none().0,
+      );
+      gtk::ffi::gtk_image_menu_item_set_always_show_image(item, glib::ffi::GTRUE);
+    }
+    return;
+  }
+
+  if let Some(label) = menu_item_label(item) {
+    let container = gtk::Box::new(Orientation::Horizontal, 6);
+    item.remove(&label);
+    container.pack_start(&Image::from_pixbuf(Some(pixbuf)), false, false, 0);
+    container.pack_start(&label, true, true, 0);
+    item.add(&container);
+    container.show_all();
+  }
+}
+
+fn accelerator_to_gdk(menu_key: &Accelerator) -> Option<(u32, gdk::ModifierType)> {
   let accel_key = match &menu_key.key {
     KeyCode::KeyA => 'A' as u32,
     KeyCode::KeyB => 'B' as u32,
@@ -379,18 +892,12 @@ fn register_accelerator(item: &GtkMenuItem, accel_group: &AccelGroup, menu_key:
         *gdk_key
       } else {
         dbg!("Cannot map key {:?}", k);
-        return;
+        return None;
       }
     }
   };
 
-  item.add_accelerator(
-    "activate",
-    accel_group,
-    accel_key,
-    modifiers_to_gdk_modifier_type(menu_key.mods),
-    gtk::AccelFlags::VISIBLE,
-  );
+  Some((accel_key, modifiers_to_gdk_modifier_type(menu_key.mods)))
 }
 
 fn modifiers_to_gdk_modifier_type(modifiers: ModifiersState) -> gdk::ModifierType {
@@ -10,18 +10,29 @@
   target_os = "openbsd"
 ))]
 
+#[cfg(feature = "accesskit")]
+mod accessibility;
 mod clipboard;
+mod device;
 mod event_loop;
 mod global_shortcut;
 mod icon;
 mod keyboard;
 mod keycode;
+mod layer_shell;
 mod menu;
 mod monitor;
+mod portal;
+mod session;
+mod single_instance;
 #[cfg(feature = "tray")]
 mod system_tray;
+mod taskbar;
+mod theme;
+mod wayland;
 mod window;
 pub mod x11;
+mod xrandr;
 
 #[cfg(feature = "tray")]
 pub use self::system_tray::{SystemTray, SystemTrayBuilder};
@@ -31,12 +42,20 @@ pub use self::{
   keycode::{keycode_from_scancode, keycode_to_scancode},
   menu::{Menu, MenuItemAttributes},
 };
-pub use event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget};
+pub use event_loop::{
+  EventLoop, EventLoopProxy, EventLoopWindowTarget, PlatformSpecificEventLoopAttributes,
+};
 pub use icon::PlatformIcon;
 pub use monitor::{MonitorHandle, VideoMode};
 pub use window::{hit_test, Window, WindowId};
 
-use crate::{event::DeviceId as RootDeviceId, keyboard::Key};
+use std::time::{Duration, Instant};
+
+use crate::{
+  event::DeviceId as RootDeviceId,
+  keyboard::Key,
+  platform::unix::{HeaderBarAttributes, LayerShellAttributes, WindowType},
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct KeyEventExtra {
@@ -62,6 +81,12 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub parent: Parent,
   pub skip_taskbar: bool,
   pub auto_transparent: bool,
+  pub layer_shell: Option<LayerShellAttributes>,
+  pub class: Option<(String, String)>,
+  pub role: Option<String>,
+  pub window_type: Option<WindowType>,
+  pub header_bar: Option<HeaderBarAttributes>,
+  pub drag_and_drop: bool,
 }
 
 impl Default for PlatformSpecificWindowBuilderAttributes {
@@ -70,6 +95,12 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       parent: Default::default(),
       skip_taskbar: Default::default(),
       auto_transparent: true,
+      layer_shell: None,
+      class: None,
+      role: None,
+      window_type: None,
+      header_bar: None,
+      drag_and_drop: true,
     }
   }
 }
@@ -97,3 +128,20 @@ impl DeviceId {
 
 // FIXME: currently we use a dummy device id, find if we can get device id from gtk
 pub(crate) const DEVICE_ID: RootDeviceId = RootDeviceId(DeviceId(0));
+
+/// Converts the time of a GDK event, in milliseconds, to an `Instant`.
+///
+/// X servers and Wayland compositors usually timestamp events with the monotonic clock of the
+/// system, like `Instant`, truncated to 32 bits. Events are timestamped when they are received
+/// otherwise.
+pub(crate) fn event_timestamp(time: u32) -> Instant {
+  let now = Instant::now();
+  let age = ((glib::monotonic_time() / 1000) as u32).wrapping_sub(time);
+  // Events that seem older than a minute come from another clock.
+  if time == 0 || age > 60_000 {
+    return now;
+  }
+  now
+    .checked_sub(Duration::from_millis(u64::from(age)))
+    .unwrap_or(now)
+}
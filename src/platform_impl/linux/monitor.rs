@@ -2,12 +2,17 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{cell::RefCell, rc::Rc};
+
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize},
+  event::Event,
   monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
 };
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+use super::{window::WindowId, xrandr};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct MonitorHandle {
   monitor: gdk::Monitor,
   // We have to store the monitor number in GdkScreen despite
@@ -47,41 +52,184 @@ impl MonitorHandle {
     .to_physical(self.scale_factor())
   }
 
+  /// Returns the part of the monitor not covered by panels, in logical pixels.
+  pub(crate) fn work_area(&self) -> gdk::Rectangle {
+    self.monitor.workarea()
+  }
+
   #[inline]
   pub fn scale_factor(&self) -> f64 {
     self.monitor.scale_factor() as f64
   }
 
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    // GDK reports 0 when it isn't known.
+    let refresh_rate = self.monitor.refresh_rate();
+    (refresh_rate > 0).then(|| refresh_rate as u32)
+  }
+
+  pub fn mirror_group_id(&self) -> Option<u32> {
+    let display = self.monitor.display()?;
+    let geometry = self.monitor.geometry();
+    let mut mirrors = (0..display.n_monitors()).filter(|number| {
+      display
+        .monitor(*number)
+        .map_or(false, |monitor| monitor.geometry() == geometry)
+    });
+    // The group is named after its first member, and only exists if there's a second one.
+    match (mirrors.next(), mirrors.next()) {
+      (Some(first), Some(_)) => Some(first as u32),
+      _ => None,
+    }
+  }
+
   #[inline]
   pub fn video_modes(&self) -> Box<dyn Iterator<Item = RootVideoMode>> {
-    Box::new(Vec::new().into_iter())
+    let bit_depth = self
+      .monitor
+      .display()
+      .and_then(|display| display.default_screen().system_visual())
+      .map_or(24, |visual| visual.depth() as u16);
+    let video_mode = |size, refresh_rate, native_mode| RootVideoMode {
+      video_mode: VideoMode {
+        size,
+        bit_depth,
+        refresh_rate,
+        monitor: self.clone(),
+        native_mode,
+      },
+    };
+
+    let video_modes: Vec<_> = match xrandr::output_modes(&self.monitor) {
+      Some(modes) => modes
+        .into_iter()
+        .map(|mode| video_mode(mode.size, mode.refresh_rate, Some(mode.id)))
+        .collect(),
+      // Only the current mode is known without XRandR, e.g. on Wayland.
+      None => {
+        let size = self.size();
+        let refresh_rate = (self.monitor.refresh_rate() as f64 / 1000.0).round() as u16;
+        vec![video_mode((size.width, size.height), refresh_rate, None)]
+      }
+    };
+    Box::new(video_modes.into_iter())
   }
+
+  /// Switches the monitor to `video_mode` while `window_id` is fullscreen.
+  pub(crate) fn set_video_mode(&self, window_id: WindowId, video_mode: &VideoMode) {
+    if let Some(mode) = video_mode.native_mode {
+      xrandr::set_window_mode(window_id, &self.monitor, mode);
+    }
+  }
+}
+
+/// Forwards the monitors added to and removed from `display`, and their scale factor changes, as
+/// monitor events.
+pub(crate) fn connect_monitor_signals<T: 'static>(
+  display: &gdk::Display,
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let send = Rc::new(move |event| {
+    if let Err(e) = event_tx.send(event) {
+      log::warn!("Failed to send monitor event to event channel: {}", e);
+    }
+  });
+
+  // The monitor numbers shift when one is removed, so the known monitors are kept in order to
+  // report a removed one with the number it had.
+  let monitors: Rc<RefCell<Vec<gdk::Monitor>>> = Rc::new(RefCell::new(
+    (0..display.n_monitors())
+      .filter_map(|number| display.monitor(number))
+      .collect(),
+  ));
+  let connect_scale_factor = {
+    let send = send.clone();
+    let monitors = monitors.clone();
+    move |monitor: &gdk::Monitor| {
+      let send = send.clone();
+      let monitors = monitors.clone();
+      monitor.connect_scale_factor_notify(move |monitor| {
+        if let Some(number) = monitors.borrow().iter().position(|m| m == monitor) {
+          send(Event::MonitorScaleFactorChanged {
+            monitor: RootMonitorHandle {
+              inner: MonitorHandle {
+                monitor: monitor.clone(),
+                number: number as i32,
+              },
+            },
+            scale_factor: monitor.scale_factor() as f64,
+          });
+        }
+      });
+    }
+  };
+  for monitor in monitors.borrow().iter() {
+    connect_scale_factor(monitor);
+  }
+
+  {
+    let send = send.clone();
+    let monitors = monitors.clone();
+    display.connect_monitor_added(move |display, monitor| {
+      connect_scale_factor(monitor);
+      *monitors.borrow_mut() = (0..display.n_monitors())
+        .filter_map(|number| display.monitor(number))
+        .collect();
+      if let Some(number) = monitors.borrow().iter().position(|m| m == monitor) {
+        send(Event::MonitorConnected(RootMonitorHandle {
+          inner: MonitorHandle::new(display, number as i32),
+        }));
+      }
+    });
+  }
+  display.connect_monitor_removed(move |_, monitor| {
+    let mut monitors = monitors.borrow_mut();
+    if let Some(number) = monitors.iter().position(|m| m == monitor) {
+      monitors.remove(number);
+      send(Event::MonitorDisconnected(RootMonitorHandle {
+        inner: MonitorHandle {
+          monitor: monitor.clone(),
+          number: number as i32,
+        },
+      }));
+    }
+  });
 }
 
 unsafe impl Send for MonitorHandle {}
 unsafe impl Sync for MonitorHandle {}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct VideoMode;
+pub struct VideoMode {
+  pub(crate) size: (u32, u32),
+  pub(crate) bit_depth: u16,
+  pub(crate) refresh_rate: u16,
+  pub(crate) monitor: MonitorHandle,
+  /// The XRandR mode, `None` if it can't be switched to.
+  pub(crate) native_mode: Option<u64>,
+}
 
 impl VideoMode {
   #[inline]
   pub fn size(&self) -> PhysicalSize<u32> {
-    panic!("VideoMode is unsupported on Linux.")
+    self.size.into()
   }
 
   #[inline]
   pub fn bit_depth(&self) -> u16 {
-    panic!("VideoMode is unsupported on Linux.")
+    self.bit_depth
   }
 
   #[inline]
   pub fn refresh_rate(&self) -> u16 {
-    panic!("VideoMode is unsupported on Linux.")
+    self.refresh_rate
   }
 
   #[inline]
   pub fn monitor(&self) -> RootMonitorHandle {
-    panic!("VideoMode is unsupported on Linux.")
+    RootMonitorHandle {
+      inner: self.monitor.clone(),
+    }
   }
 }
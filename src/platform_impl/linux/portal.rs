@@ -0,0 +1,369 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Global shortcuts through the `org.freedesktop.portal.GlobalShortcuts` desktop portal, used on
+//! Wayland where applications cannot grab keys themselves.
+
+use std::{
+  cell::RefCell,
+  collections::{BTreeMap, HashMap},
+  rc::Rc,
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, Weak,
+  },
+};
+
+use gio::{
+  BusType, Cancellable, DBusCallFlags, DBusConnection, DBusSignalFlags, SignalSubscriptionId,
+};
+use glib::{ToVariant, Variant, VariantTy};
+
+use super::window::{WindowId, WindowRequest};
+
+const PORTAL_BUS: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const GLOBAL_SHORTCUTS_INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+
+/// Used to build unique request and session handle tokens.
+static NEXT_TOKEN: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+struct Shortcut {
+  description: String,
+  trigger: String,
+}
+
+#[derive(Debug)]
+struct PortalState {
+  connection: DBusConnection,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  shortcuts: BTreeMap<u16, Shortcut>,
+  /// Handle of the portal session the shortcuts are currently bound to.
+  session: Option<String>,
+  /// Bumped every time the session is recreated, so replies for an older session are dropped.
+  generation: usize,
+  rebind_pending: bool,
+}
+
+/// A `GlobalShortcuts` portal session holding every registered shortcut.
+///
+/// The portal only lets a session bind its shortcuts once, so the session is recreated with the
+/// full list whenever a shortcut is added or removed.
+#[derive(Debug)]
+pub(crate) struct GlobalShortcutsPortal {
+  state: Arc<Mutex<PortalState>>,
+  activated_subscription: Option<SignalSubscriptionId>,
+}
+
+impl GlobalShortcutsPortal {
+  /// Connects to the session bus, returning `None` if the desktop portal does not implement the
+  /// `GlobalShortcuts` interface.
+  pub(crate) fn new(
+    window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+  ) -> Option<GlobalShortcutsPortal> {
+    let cancellable: Option<&Cancellable> = None;
+    let connection = gio::bus_get_sync(BusType::Session, cancellable)
+      .map_err(|e| log::warn!("Failed to connect to the session bus: {}", e))
+      .ok()?;
+
+    let version = connection
+      .call_sync(
+        Some(PORTAL_BUS),
+        PORTAL_PATH,
+        "org.freedesktop.DBus.Properties",
+        "Get",
+        Some(&(GLOBAL_SHORTCUTS_INTERFACE, "version").to_variant()),
+        VariantTy::new("(v)").ok(),
+        DBusCallFlags::NONE,
+        1000,
+        cancellable,
+      )
+      .ok()?
+      .child_value(0)
+      .as_variant()
+      .and_then(|version| version.get::<u32>())?;
+    log::debug!("Using the GlobalShortcuts portal, version {}", version);
+
+    let state = Arc::new(Mutex::new(PortalState {
+      connection: connection.clone(),
+      window_requests_tx,
+      shortcuts: BTreeMap::new(),
+      session: None,
+      generation: 0,
+      rebind_pending: false,
+    }));
+
+    let weak_state = Arc::downgrade(&state);
+    let activated_subscription = connection.signal_subscribe(
+      Some(PORTAL_BUS),
+      Some(GLOBAL_SHORTCUTS_INTERFACE),
+      Some("Activated"),
+      Some(PORTAL_PATH),
+      None,
+      DBusSignalFlags::NONE,
+      move |_, _, _, _, _, params| {
+        let state = match weak_state.upgrade() {
+          Some(state) => state,
+          None => return,
+        };
+        let state = state.lock().unwrap();
+
+        // (session_handle, shortcut_id, timestamp, options)
+        let session = params.try_child_value(0);
+        let id = params.try_child_value(1);
+        if session.as_ref().and_then(|s| s.str()) != state.session.as_deref() {
+          return;
+        }
+        if let Some(id) = id.as_ref().and_then(|id| id.str()?.parse::<u16>().ok()) {
+          if let Err(e) = state
+            .window_requests_tx
+            .send((WindowId::dummy(), WindowRequest::GlobalHotKey(id)))
+          {
+            log::warn!("Failed to send global shortcut to the event loop: {}", e);
+          }
+        }
+      },
+    );
+
+    Some(GlobalShortcutsPortal {
+      state,
+      activated_subscription: Some(activated_subscription),
+    })
+  }
+
+  /// Adds a shortcut, `trigger` being its preferred trigger in the
+  /// [shortcuts specification](https://specifications.freedesktop.org/shortcuts-spec/latest/)
+  /// format.
+  pub(crate) fn register(&self, id: u16, description: String, trigger: String) {
+    self.state.lock().unwrap().shortcuts.insert(
+      id,
+      Shortcut {
+        description,
+        trigger,
+      },
+    );
+    schedule_rebind(&self.state);
+  }
+
+  /// Removes a shortcut, returning whether it was registered.
+  pub(crate) fn unregister(&self, id: u16) -> bool {
+    let removed = self.state.lock().unwrap().shortcuts.remove(&id).is_some();
+    if removed {
+      schedule_rebind(&self.state);
+    }
+    removed
+  }
+
+  pub(crate) fn unregister_all(&self) {
+    self.state.lock().unwrap().shortcuts.clear();
+    schedule_rebind(&self.state);
+  }
+}
+
+impl Drop for GlobalShortcutsPortal {
+  fn drop(&mut self) {
+    let mut state = self.state.lock().unwrap();
+    if let Some(subscription) = self.activated_subscription.take() {
+      state.connection.signal_unsubscribe(subscription);
+    }
+    state.close_session();
+  }
+}
+
+impl PortalState {
+  fn close_session(&mut self) {
+    if let Some(session) = self.session.take() {
+      let cancellable: Option<&Cancellable> = None;
+      self.connection.call(
+        Some(PORTAL_BUS),
+        &session,
+        "org.freedesktop.portal.Session",
+        "Close",
+        None,
+        None,
+        DBusCallFlags::NONE,
+        -1,
+        cancellable,
+        |result| {
+          if let Err(e) = result {
+            log::warn!("Failed to close the global shortcuts session: {}", e);
+          }
+        },
+      );
+    }
+  }
+}
+
+/// Recreates the session once the current main loop iteration is done, so that registering
+/// several shortcuts in a row only binds them once.
+fn schedule_rebind(state: &Arc<Mutex<PortalState>>) {
+  let mut locked_state = state.lock().unwrap();
+  if locked_state.rebind_pending {
+    return;
+  }
+  locked_state.rebind_pending = true;
+
+  let weak_state = Arc::downgrade(state);
+  glib::idle_add(move || {
+    if let Some(state) = weak_state.upgrade() {
+      rebind(&state);
+    }
+    glib::Continue(false)
+  });
+}
+
+fn rebind(state: &Arc<Mutex<PortalState>>) {
+  let mut locked_state = state.lock().unwrap();
+  locked_state.rebind_pending = false;
+  locked_state.close_session();
+  locked_state.generation += 1;
+  if locked_state.shortcuts.is_empty() {
+    return;
+  }
+
+  let generation = locked_state.generation;
+  let weak_state = Arc::downgrade(state);
+  let mut options = HashMap::new();
+  options.insert(
+    "session_handle_token".to_string(),
+    next_token().to_variant(),
+  );
+  request(
+    &locked_state.connection,
+    "CreateSession",
+    Vec::new(),
+    options,
+    move |results| {
+      let session = match results.get("session_handle").and_then(|s| s.str()) {
+        Some(session) => session.to_string(),
+        None => return,
+      };
+      if let Some(state) = upgrade_current(&weak_state, generation) {
+        let mut state = state.lock().unwrap();
+        state.session = Some(session.clone());
+        bind_shortcuts(&state, &session);
+      }
+    },
+  );
+}
+
+fn bind_shortcuts(state: &PortalState, session: &str) {
+  let shortcuts: Vec<(String, HashMap<String, Variant>)> = state
+    .shortcuts
+    .iter()
+    .map(|(id, shortcut)| {
+      let mut properties = HashMap::new();
+      properties.insert("description".to_string(), shortcut.description.to_variant());
+      properties.insert(
+        "preferred_trigger".to_string(),
+        shortcut.trigger.to_variant(),
+      );
+      (id.to_string(), properties)
+    })
+    .collect();
+
+  let session = match Variant::parse(Some(VariantTy::OBJECT_PATH), &format!("'{}'", session)) {
+    Ok(session) => session,
+    Err(e) => {
+      log::warn!("Invalid global shortcuts session handle: {}", e);
+      return;
+    }
+  };
+  request(
+    &state.connection,
+    "BindShortcuts",
+    vec![session, shortcuts.to_variant(), "".to_variant()],
+    HashMap::new(),
+    |_| {},
+  );
+}
+
+/// Calls a portal method which answers through a `org.freedesktop.portal.Request` object, then
+/// calls `callback` with the results if the user or the portal accepted it.
+fn request<F: FnOnce(HashMap<String, Variant>) + 'static>(
+  connection: &DBusConnection,
+  method: &'static str,
+  args: Vec<Variant>,
+  mut options: HashMap<String, Variant>,
+  callback: F,
+) {
+  let token = next_token();
+  options.insert("handle_token".to_string(), token.to_variant());
+
+  // Subscribe before calling the method so the response cannot be missed. The request path is
+  // predictable from our unique bus name and the handle token.
+  let sender = connection
+    .unique_name()
+    .map(|name| name.trim_start_matches(':').replace('.', "_"))
+    .unwrap_or_default();
+  let request_path = format!("{}/request/{}/{}", PORTAL_PATH, sender, token);
+
+  let callback = RefCell::new(Some(callback));
+  let subscription: Rc<RefCell<Option<SignalSubscriptionId>>> = Default::default();
+  let subscription_ = subscription.clone();
+  let id = connection.signal_subscribe(
+    Some(PORTAL_BUS),
+    Some("org.freedesktop.portal.Request"),
+    Some("Response"),
+    Some(&request_path),
+    None,
+    DBusSignalFlags::NONE,
+    move |connection, _, _, _, _, params| {
+      if let Some(subscription) = subscription_.borrow_mut().take() {
+        connection.signal_unsubscribe(subscription);
+      }
+
+      // (response, results), a non-zero response meaning it was cancelled or failed.
+      match params.get::<(u32, HashMap<String, Variant>)>() {
+        Some((0, results)) => {
+          if let Some(callback) = callback.borrow_mut().take() {
+            callback(results);
+          }
+        }
+        Some((response, _)) => {
+          log::warn!("Global shortcuts portal {} failed ({})", method, response)
+        }
+        None => {}
+      }
+    },
+  );
+  subscription.replace(Some(id));
+
+  let params = Variant::tuple_from_iter(args.into_iter().chain(Some(options.to_variant())));
+  let cancellable: Option<&Cancellable> = None;
+  connection.call(
+    Some(PORTAL_BUS),
+    PORTAL_PATH,
+    GLOBAL_SHORTCUTS_INTERFACE,
+    method,
+    Some(&params),
+    None,
+    DBusCallFlags::NONE,
+    -1,
+    cancellable,
+    move |result| {
+      if let Err(e) = result {
+        log::warn!("Global shortcuts portal {} failed: {}", method, e);
+      }
+    },
+  );
+}
+
+fn upgrade_current(
+  state: &Weak<Mutex<PortalState>>,
+  generation: usize,
+) -> Option<Arc<Mutex<PortalState>>> {
+  let state = state.upgrade()?;
+  let current = state.lock().unwrap().generation == generation;
+  current.then(|| state)
+}
+
+fn next_token() -> String {
+  format!(
+    "tao{}_{}",
+    std::process::id(),
+    NEXT_TOKEN.fetch_add(1, Ordering::Relaxed)
+  )
+}
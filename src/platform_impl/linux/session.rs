@@ -0,0 +1,151 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::Cell, collections::HashMap, env, rc::Rc};
+
+use gio::{BusType, Cancellable, DBusConnection, DBusSignalFlags};
+
+use crate::event::Event;
+
+const SCREENSAVER_INTERFACES: [&str; 2] = ["org.freedesktop.ScreenSaver", "org.gnome.ScreenSaver"];
+
+/// Subscribes to the logind session and screen saver D-Bus signals, and forwards them as
+/// `Event::SessionLock` / `Event::SessionUnlock`.
+pub(crate) fn connect_session_signals<T: 'static>(
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let locked = Rc::new(Cell::new(false));
+  let notify: Rc<dyn Fn(bool)> = Rc::new(move |lock| {
+    // Both logind and the screen saver may report the same transition.
+    if locked.replace(lock) == lock {
+      return;
+    }
+
+    let event = if lock {
+      Event::SessionLock
+    } else {
+      Event::SessionUnlock
+    };
+    if let Err(e) = event_tx.send(event) {
+      log::warn!("Failed to send session event to event channel: {}", e);
+    }
+  });
+
+  let cancellable: Option<&Cancellable> = None;
+  match gio::bus_get_sync(BusType::Session, cancellable) {
+    Ok(connection) => {
+      for interface in SCREENSAVER_INTERFACES {
+        let notify = notify.clone();
+        subscribe(
+          &connection,
+          interface,
+          Some("ActiveChanged"),
+          None,
+          move |params| {
+            if let Some((active,)) = params.get::<(bool,)>() {
+              notify(active);
+            }
+          },
+        );
+      }
+    }
+    Err(e) => log::warn!("Failed to connect to the session bus: {}", e),
+  }
+
+  // logind's `Lock` and `Unlock` signals are requests to the screen locker, which sets the
+  // `LockedHint` of the session once it actually locked or unlocked the screen.
+  let session_path = match env::var("XDG_SESSION_ID") {
+    Ok(id) => format!(
+      "/org/freedesktop/login1/session/{}",
+      escape_object_path(&id)
+    ),
+    Err(_) => return,
+  };
+  match gio::bus_get_sync(BusType::System, cancellable) {
+    Ok(connection) => {
+      subscribe(
+        &connection,
+        "org.freedesktop.DBus.Properties",
+        Some("PropertiesChanged"),
+        Some(&session_path),
+        move |params| {
+          let changed = match params.get::<(String, HashMap<String, glib::Variant>, Vec<String>)>()
+          {
+            Some((interface, changed, _)) if interface == "org.freedesktop.login1.Session" => {
+              changed
+            }
+            _ => return,
+          };
+          if let Some(locked) = changed.get("LockedHint").and_then(|locked| locked.get()) {
+            notify(locked);
+          }
+        },
+      );
+    }
+    Err(e) => log::warn!("Failed to connect to the system bus: {}", e),
+  }
+}
+
+/// Subscribes to the `PrepareForSleep` signal of logind, and forwards it as
+/// `Event::SystemSuspend` / `Event::SystemResume`.
+pub(crate) fn connect_power_signals<T: 'static>(
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let cancellable: Option<&Cancellable> = None;
+  match gio::bus_get_sync(BusType::System, cancellable) {
+    Ok(connection) => subscribe(
+      &connection,
+      "org.freedesktop.login1.Manager",
+      Some("PrepareForSleep"),
+      Some("/org/freedesktop/login1"),
+      move |params| {
+        let event = match params.get::<(bool,)>() {
+          Some((true,)) => Event::SystemSuspend,
+          Some((false,)) => Event::SystemResume,
+          None => return,
+        };
+        if let Err(e) = event_tx.send(event) {
+          log::warn!("Failed to send power event to event channel: {}", e);
+        }
+      },
+    ),
+    Err(e) => log::warn!("Failed to connect to the system bus: {}", e),
+  }
+}
+
+pub(crate) fn subscribe<F: Fn(&glib::Variant) + 'static>(
+  connection: &DBusConnection,
+  interface: &str,
+  member: Option<&str>,
+  object_path: Option<&str>,
+  callback: F,
+) {
+  // The bus connection is a shared singleton; keep it alive for as long as the subscription is.
+  let connection_ = connection.clone();
+  connection.signal_subscribe(
+    None,
+    Some(interface),
+    member,
+    object_path,
+    None,
+    DBusSignalFlags::NONE,
+    move |_, _, _, _, _, params| {
+      let _ = &connection_;
+      callback(params)
+    },
+  );
+}
+
+/// Escapes a logind session id the same way `sd_bus_path_encode` does.
+fn escape_object_path(id: &str) -> String {
+  let mut escaped = String::with_capacity(id.len());
+  for (i, b) in id.bytes().enumerate() {
+    if b.is_ascii_alphabetic() || (i > 0 && b.is_ascii_digit()) {
+      escaped.push(b as char);
+    } else {
+      escaped.push_str(&format!("_{:02x}", b));
+    }
+  }
+  escaped
+}
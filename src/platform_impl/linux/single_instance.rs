@@ -0,0 +1,100 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{env, path::PathBuf};
+
+use gio::{BusType, Cancellable, DBusCallFlags};
+use glib::{ToVariant, VariantTy};
+
+use super::{
+  session::subscribe,
+  window::{WindowId, WindowRequest},
+};
+
+const OBJECT_PATH: &str = "/app/tao/SingleInstance";
+const INTERFACE: &str = "app.tao.SingleInstance";
+const SIGNAL: &str = "NewInstance";
+
+/// `DBUS_NAME_FLAG_DO_NOT_QUEUE`, so that `RequestName` fails when the name is owned.
+const DO_NOT_QUEUE: u32 = 4;
+/// `DBUS_REQUEST_NAME_REPLY_PRIMARY_OWNER` and `DBUS_REQUEST_NAME_REPLY_ALREADY_OWNER`.
+const OWNER_REPLIES: [u32; 2] = [1, 4];
+
+/// Owns `identifier` on the session bus, or forwards the arguments of the process to its owner
+/// with a signal sent to it when it is already owned.
+pub fn enable(
+  identifier: &str,
+  window_requests_tx: glib::Sender<(WindowId, WindowRequest)>,
+) -> bool {
+  let cancellable: Option<&Cancellable> = None;
+  let connection = match gio::bus_get_sync(BusType::Session, cancellable) {
+    Ok(connection) => connection,
+    Err(e) => {
+      log::warn!("Failed to connect to the session bus: {}", e);
+      return true;
+    }
+  };
+
+  let reply = connection.call_sync(
+    Some("org.freedesktop.DBus"),
+    "/org/freedesktop/DBus",
+    "org.freedesktop.DBus",
+    "RequestName",
+    Some(&(identifier, DO_NOT_QUEUE).to_variant()),
+    VariantTy::new("(u)").ok(),
+    DBusCallFlags::NONE,
+    -1,
+    cancellable,
+  );
+  let reply = match reply {
+    Ok(reply) => reply.get::<(u32,)>().map(|(reply,)| reply),
+    Err(e) => {
+      log::warn!("Failed to request the single instance name: {}", e);
+      return true;
+    }
+  };
+
+  if reply.map_or(false, |reply| OWNER_REPLIES.contains(&reply)) {
+    subscribe(
+      &connection,
+      INTERFACE,
+      Some(SIGNAL),
+      Some(OBJECT_PATH),
+      move |params| {
+        if let Some((args, cwd)) = params.get::<(Vec<String>, String)>() {
+          let request = WindowRequest::NewInstance(args, PathBuf::from(cwd));
+          if let Err(e) = window_requests_tx.send((WindowId::dummy(), request)) {
+            log::warn!("Fail to send new instance request: {}", e);
+          }
+        }
+      },
+    );
+    return true;
+  }
+
+  let args: Vec<String> = env::args_os()
+    .map(|arg| arg.to_string_lossy().into_owned())
+    .collect();
+  let cwd = env::current_dir()
+    .unwrap_or_default()
+    .to_string_lossy()
+    .into_owned();
+  let result = connection
+    .emit_signal(
+      Some(identifier),
+      OBJECT_PATH,
+      INTERFACE,
+      SIGNAL,
+      Some(&(args, cwd).to_variant()),
+    )
+    // The process exits right after, so the signal is sent before returning.
+    .and_then(|_| connection.flush_sync(cancellable));
+  if let Err(e) = result {
+    log::warn!(
+      "Failed to forward the arguments to the single instance: {}",
+      e
+    );
+  }
+  false
+}
@@ -4,16 +4,25 @@
 
 use crate::{
   error::OsError,
+  event::TrayEvent,
   event_loop::EventLoopWindowTarget,
-  system_tray::{Icon, SystemTray as RootSystemTray},
+  system_tray::{Icon, SystemTray as RootSystemTray, TrayStatus},
   TrayId,
 };
 
-use glib::Sender;
-use std::path::PathBuf;
+use glib::{translate::ToGlibPtr, Sender};
+use std::{
+  os::raw::c_int,
+  path::PathBuf,
+  sync::atomic::{AtomicUsize, Ordering},
+};
 
 use gtk::{prelude::WidgetExt, AccelGroup};
-use libappindicator::{AppIndicator, AppIndicatorStatus};
+use libappindicator::{
+  _AppIndicator, app_indicator_new, app_indicator_set_icon_full, app_indicator_set_icon_theme_path,
+  app_indicator_set_label, app_indicator_set_menu, app_indicator_set_status,
+  app_indicator_set_title, AppIndicatorCategory, AppIndicatorStatus,
+};
 
 use super::{menu::Menu, window::WindowRequest, WindowId};
 
@@ -37,10 +46,19 @@ impl SystemTrayBuilder {
   pub fn build<T: 'static>(
     self,
     window_target: &EventLoopWindowTarget<T>,
-    _id: TrayId,
-    _tooltip: Option<String>,
+    id: TrayId,
+    tooltip: Option<String>,
+    title: Option<String>,
   ) -> Result<RootSystemTray, OsError> {
-    let mut app_indicator = AppIndicator::new("tao application", "");
+    // The indicator id names its D-Bus object, so every tray of the application needs its own.
+    // Numbering them in creation order keeps the ids stable across runs, which lets the desktop
+    // remember the settings of each tray.
+    static INDICATOR_COUNT: AtomicUsize = AtomicUsize::new(0);
+    let indicator_id = match INDICATOR_COUNT.fetch_add(1, Ordering::Relaxed) {
+      0 => "tao application".to_string(),
+      n => format!("tao application {}", n + 1),
+    };
+    let app_indicator = AppIndicator::new(&indicator_id);
 
     let (parent_path, icon_path) =
       temp_icon_path(self.temp_icon_dir.as_ref()).expect("Failed to create a temp folder for icon");
@@ -50,18 +68,27 @@ impl SystemTrayBuilder {
     app_indicator.set_icon_theme_path(&parent_path.to_string_lossy());
     app_indicator.set_icon_full(&icon_path.to_string_lossy(), "icon");
 
+    if let Some(tooltip) = tooltip {
+      app_indicator.set_title(&tooltip);
+    }
+    if let Some(title) = title {
+      app_indicator.set_label(&title, "");
+    }
+
     let sender = window_target.p.window_requests_tx.clone();
+    app_indicator.connect_scroll(sender.clone(), id);
 
     if let Some(tray_menu) = self.tray_menu.clone() {
-      let menu = &mut tray_menu.into_gtkmenu(&sender, &AccelGroup::new(), WindowId::dummy());
+      let menu = tray_menu.into_gtkmenu(&sender, &AccelGroup::new(), WindowId::dummy(), Some(id));
 
-      app_indicator.set_menu(menu);
+      app_indicator.set_menu(&menu);
       menu.show_all();
     }
 
     app_indicator.set_status(AppIndicatorStatus::Active);
 
     Ok(RootSystemTray(SystemTray {
+      id,
       temp_icon_dir: self.temp_icon_dir,
       app_indicator,
       sender,
@@ -71,6 +98,7 @@ impl SystemTrayBuilder {
 }
 
 pub struct SystemTray {
+  id: TrayId,
   temp_icon_dir: Option<PathBuf>,
   app_indicator: AppIndicator,
   sender: Sender<(WindowId, WindowRequest)>,
@@ -92,26 +120,135 @@ impl SystemTray {
     self.path = icon_path;
   }
 
-  pub fn set_tooltip(&self, _tooltip: &str) {}
+  /// libappindicator has no tooltip of its own, but the StatusNotifierItem hosts show the
+  /// indicator title when hovering it.
+  pub fn set_tooltip(&mut self, tooltip: &str) {
+    self.app_indicator.set_title(tooltip);
+  }
+
+  pub fn set_title(&mut self, title: &str) {
+    self.app_indicator.set_label(title, "");
+  }
+
+  pub fn set_status(&mut self, status: TrayStatus) {
+    self.app_indicator.set_status(match status {
+      TrayStatus::Active => AppIndicatorStatus::Active,
+      TrayStatus::Passive => AppIndicatorStatus::Passive,
+      TrayStatus::NeedsAttention => AppIndicatorStatus::Attention,
+    });
+  }
 
   pub fn set_menu(&mut self, tray_menu: &Menu) {
-    let mut menu =
-      tray_menu
-        .clone()
-        .into_gtkmenu(&self.sender, &AccelGroup::new(), WindowId::dummy());
+    let menu = tray_menu.clone().into_gtkmenu(
+      &self.sender,
+      &AccelGroup::new(),
+      WindowId::dummy(),
+      Some(self.id),
+    );
 
-    self.app_indicator.set_menu(&mut menu);
+    self.app_indicator.set_menu(&menu);
     menu.show_all();
   }
 }
 
 impl Drop for SystemTray {
   fn drop(&mut self) {
-    self.app_indicator.set_status(AppIndicatorStatus::Passive);
     let _ = std::fs::remove_file(self.path.clone());
   }
 }
 
+/// An `AppIndicator` that is released when dropped.
+///
+/// `libappindicator::AppIndicator` never releases its GObject, which would keep the tray
+/// registered with the desktop, so we call the C functions ourselves.
+struct AppIndicator(*mut _AppIndicator);
+
+impl AppIndicator {
+  fn new(id: &str) -> Self {
+    Self(unsafe {
+      app_indicator_new(
+        id.to_glib_none().0,
+        "".to_glib_none().0,
+        AppIndicatorCategory::ApplicationStatus as u32,
+      )
+    })
+  }
+
+  fn set_status(&self, status: AppIndicatorStatus) {
+    unsafe { app_indicator_set_status(self.0, status as u32) }
+  }
+
+  fn set_menu(&self, menu: &gtk::Menu) {
+    unsafe { app_indicator_set_menu(self.0, menu.to_glib_none().0) }
+  }
+
+  fn set_label(&self, label: &str, guide: &str) {
+    unsafe { app_indicator_set_label(self.0, label.to_glib_none().0, guide.to_glib_none().0) }
+  }
+
+  fn set_title(&self, title: &str) {
+    unsafe { app_indicator_set_title(self.0, title.to_glib_none().0) }
+  }
+
+  fn set_icon_theme_path(&self, path: &str) {
+    unsafe { app_indicator_set_icon_theme_path(self.0, path.to_glib_none().0) }
+  }
+
+  fn set_icon_full(&self, name: &str, desc: &str) {
+    unsafe { app_indicator_set_icon_full(self.0, name.to_glib_none().0, desc.to_glib_none().0) }
+  }
+
+  /// Sends a [`TrayEvent::Scroll`] for `id` when scrolling over the indicator.
+  fn connect_scroll(&self, sender: Sender<(WindowId, WindowRequest)>, id: TrayId) {
+    unsafe extern "C" fn scroll_trampoline(
+      _: *mut _AppIndicator,
+      delta: c_int,
+      direction: gdk_sys::GdkScrollDirection,
+      data: glib::ffi::gpointer,
+    ) {
+      let (sender, id) = &*(data as *const (Sender<(WindowId, WindowRequest)>, TrayId));
+      // Positive values mean scrolling right and up.
+      let (delta_x, delta_y) = match direction {
+        gdk_sys::GDK_SCROLL_UP => (0, delta),
+        gdk_sys::GDK_SCROLL_DOWN => (0, -delta),
+        gdk_sys::GDK_SCROLL_LEFT => (-delta, 0),
+        gdk_sys::GDK_SCROLL_RIGHT => (delta, 0),
+        _ => return,
+      };
+      if let Err(e) = sender.send((
+        WindowId::dummy(),
+        WindowRequest::TrayEvent(*id, TrayEvent::Scroll { delta_x, delta_y }),
+      )) {
+        log::warn!("Fail to send tray scroll request: {}", e);
+      }
+    }
+
+    unsafe {
+      glib::signal::connect_raw(
+        self.0 as *mut glib::gobject_ffi::GObject,
+        b"scroll-event\0".as_ptr() as *const _,
+        Some(std::mem::transmute::<
+          unsafe extern "C" fn(
+            *mut _AppIndicator,
+            c_int,
+            gdk_sys::GdkScrollDirection,
+            glib::ffi::gpointer,
+          ),
+          unsafe extern "C" fn(),
+        >(scroll_trampoline)),
+        Box::into_raw(Box::new((sender, id))),
+      );
+    }
+  }
+}
+
+impl Drop for AppIndicator {
+  fn drop(&mut self) {
+    // Disposing the indicator hides it and unregisters it from D-Bus.
+    unsafe { glib::gobject_ffi::g_object_unref(self.0 as *mut _) }
+  }
+}
+
 /// Generates an icon path in one of the following dirs:
 /// 1. If `temp_icon_dir` is `Some` use that.
 /// 2. `$XDG_RUNTIME_DIR/tao`
@@ -0,0 +1,122 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Progress bars and badge counts through the Unity `LauncherEntry` D-Bus API, which docks and
+//! desktops show on the launcher of the application whose `.desktop` file matches.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use gio::{BusType, Cancellable, DBusConnection};
+use glib::{ToVariant, Variant};
+
+use crate::window::{ProgressBarState, ProgressState};
+
+const LAUNCHER_ENTRY_PATH: &str = "/com/canonical/unity/launcherentry/tao";
+const LAUNCHER_ENTRY_INTERFACE: &str = "com.canonical.Unity.LauncherEntry";
+
+struct LauncherEntry {
+  connection: Option<DBusConnection>,
+  desktop_filename: Option<String>,
+  state: ProgressState,
+  progress: u64,
+  count: Option<u64>,
+}
+
+thread_local! {
+  // Only touched on the main thread, where the window requests are handled.
+  static LAUNCHER_ENTRY: RefCell<LauncherEntry> = RefCell::new(LauncherEntry {
+    connection: None,
+    desktop_filename: None,
+    state: ProgressState::None,
+    progress: 0,
+    count: None,
+  });
+}
+
+/// Applies the changes in `progress` and broadcasts the resulting launcher entry.
+pub(crate) fn set_progress_bar(progress: ProgressBarState) {
+  LAUNCHER_ENTRY.with(|cell| {
+    let mut entry = cell.borrow_mut();
+    if let Some(desktop_filename) = progress.desktop_filename {
+      entry.desktop_filename = Some(desktop_filename);
+    }
+    if let Some(state) = progress.state {
+      entry.state = state;
+    }
+    if let Some(value) = progress.progress {
+      entry.progress = value.min(100);
+    }
+    entry.update();
+  });
+}
+
+pub(crate) fn set_badge_count(count: Option<u64>) {
+  LAUNCHER_ENTRY.with(|cell| {
+    let mut entry = cell.borrow_mut();
+    entry.count = count;
+    entry.update();
+  });
+}
+
+impl LauncherEntry {
+  fn update(&mut self) {
+    if self.connection.is_none() {
+      let cancellable: Option<&Cancellable> = None;
+      match gio::bus_get_sync(BusType::Session, cancellable) {
+        Ok(connection) => self.connection = Some(connection),
+        Err(e) => {
+          log::warn!("Failed to connect to the session bus: {}", e);
+          return;
+        }
+      }
+    }
+
+    let app_uri = format!(
+      "application://{}",
+      self
+        .desktop_filename
+        .clone()
+        .unwrap_or_else(default_desktop_filename)
+    );
+    let mut properties: HashMap<String, Variant> = HashMap::new();
+    properties.insert(
+      "progress".to_string(),
+      (self.progress as f64 / 100.0).to_variant(),
+    );
+    properties.insert(
+      "progress-visible".to_string(),
+      (self.state != ProgressState::None).to_variant(),
+    );
+    properties.insert(
+      "urgent".to_string(),
+      (self.state == ProgressState::Error).to_variant(),
+    );
+    properties.insert(
+      "count".to_string(),
+      (self.count.unwrap_or(0).min(i64::MAX as u64) as i64).to_variant(),
+    );
+    properties.insert(
+      "count-visible".to_string(),
+      self.count.is_some().to_variant(),
+    );
+
+    if let Err(e) = self.connection.as_ref().unwrap().emit_signal(
+      None,
+      LAUNCHER_ENTRY_PATH,
+      LAUNCHER_ENTRY_INTERFACE,
+      "Update",
+      Some(&(app_uri, properties).to_variant()),
+    ) {
+      log::warn!("Failed to update the launcher entry: {}", e);
+    }
+  }
+}
+
+fn default_desktop_filename() -> String {
+  let name = std::env::current_exe()
+    .ok()
+    .and_then(|exe| exe.file_stem()?.to_str().map(ToString::to_string))
+    .unwrap_or_default();
+  format!("{}.desktop", name)
+}
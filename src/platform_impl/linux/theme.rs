@@ -0,0 +1,243 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The system theme and accent color, from the `color-scheme` and `accent-color` settings of the
+//! `org.freedesktop.portal.Settings` desktop portal, the theme falling back to the GTK settings
+//! when no portal is running.
+
+use std::sync::{
+  atomic::{AtomicBool, AtomicU32, Ordering},
+  Mutex,
+};
+
+use gio::{BusType, Cancellable, DBusCallFlags, DBusConnection, DBusSignalFlags};
+use glib::{ToVariant, Variant, VariantTy};
+use gtk::{prelude::*, traits::SettingsExt, Settings};
+
+use crate::{
+  event::Event,
+  window::{Theme, RGBA},
+};
+
+const PORTAL_BUS: &str = "org.freedesktop.portal.Desktop";
+const PORTAL_PATH: &str = "/org/freedesktop/portal/desktop";
+const SETTINGS_INTERFACE: &str = "org.freedesktop.portal.Settings";
+const APPEARANCE_NAMESPACE: &str = "org.freedesktop.appearance";
+const COLOR_SCHEME_KEY: &str = "color-scheme";
+const ACCENT_COLOR_KEY: &str = "accent-color";
+
+const NO_PREFERENCE: u32 = 0;
+const PREFER_DARK: u32 = 1;
+const PREFER_LIGHT: u32 = 2;
+
+// Currently GTK doesn't provide feature for detect theme, so we need to check theme manually.
+// ref: https://github.com/WebKit/WebKit/blob/e44ffaa0d999a9807f76f1805943eea204cfdfbc/Source/WebKit/UIProcess/API/gtk/PageClientImpl.cpp#L587
+const GTK_THEME_SUFFIX_LIST: [&str; 3] = ["-dark", "-Dark", "-Darker"];
+
+/// The last `color-scheme` read from the portal.
+static COLOR_SCHEME: AtomicU32 = AtomicU32::new(NO_PREFERENCE);
+lazy_static! {
+  /// The last `accent-color` read from the portal.
+  static ref ACCENT_COLOR: Mutex<Option<RGBA>> = Mutex::new(None);
+}
+/// Whether a window forced the theme of the GTK settings, which are shared by every window.
+static FORCED_THEME: AtomicBool = AtomicBool::new(false);
+
+/// Returns the theme preferred by the user.
+pub(crate) fn system_theme() -> Theme {
+  match COLOR_SCHEME.load(Ordering::Relaxed) {
+    PREFER_DARK => return Theme::Dark,
+    PREFER_LIGHT => return Theme::Light,
+    _ => {}
+  }
+  if let Some(settings) = Settings::default() {
+    if settings.is_gtk_application_prefer_dark_theme() {
+      return Theme::Dark;
+    }
+    let theme_name = settings.gtk_theme_name().map(|s| s.as_str().to_owned());
+    if let Some(theme) = theme_name {
+      if GTK_THEME_SUFFIX_LIST.iter().any(|t| theme.ends_with(t)) {
+        return Theme::Dark;
+      }
+    }
+  }
+  Theme::Light
+}
+
+/// Makes GTK draw with the dark or light variant of its theme.
+pub(crate) fn force_theme(theme: Theme) {
+  let settings = match Settings::default() {
+    Some(settings) => settings,
+    None => return,
+  };
+  FORCED_THEME.store(true, Ordering::Relaxed);
+  match theme {
+    Theme::Dark => settings.set_gtk_application_prefer_dark_theme(true),
+    Theme::Light => {
+      settings.set_gtk_application_prefer_dark_theme(false);
+      let theme_name = settings.gtk_theme_name().map(|t| t.as_str().to_owned());
+      if let Some(theme) = theme_name {
+        // Remove dark variant.
+        if let Some(theme) = GTK_THEME_SUFFIX_LIST
+          .iter()
+          .find(|t| theme.ends_with(*t))
+          .map(|v| theme.strip_suffix(v))
+        {
+          settings.set_gtk_theme_name(theme);
+        }
+      }
+    }
+  }
+}
+
+/// Reverts the GTK settings changed by [`force_theme`].
+pub(crate) fn follow_system_theme() {
+  let settings = match Settings::default() {
+    Some(settings) => settings,
+    None => return,
+  };
+  if !FORCED_THEME.swap(false, Ordering::Relaxed) {
+    return;
+  }
+  settings.reset_property("gtk-theme-name");
+  settings.reset_property("gtk-application-prefer-dark-theme");
+  let color_scheme = COLOR_SCHEME.load(Ordering::Relaxed);
+  if color_scheme != NO_PREFERENCE {
+    settings.set_gtk_application_prefer_dark_theme(color_scheme == PREFER_DARK);
+  }
+}
+
+/// Returns the accent color last read from the portal.
+pub(crate) fn accent_color() -> Option<RGBA> {
+  *ACCENT_COLOR.lock().unwrap()
+}
+
+/// Reads the `color-scheme` and `accent-color` of the portal and follows their changes, applying
+/// the dark variant of the GTK theme when the user prefers it and sending
+/// `Event::AccentColorChanged`.
+///
+/// Windows are told about the color scheme changes through the `notify` signal of the
+/// `gtk-application-prefer-dark-theme` GTK setting.
+pub(crate) fn connect_theme_signals<T: 'static>(
+  event_tx: crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let cancellable: Option<&Cancellable> = None;
+  let connection = match gio::bus_get_sync(BusType::Session, cancellable) {
+    Ok(connection) => connection,
+    Err(e) => {
+      log::warn!("Failed to connect to the session bus: {}", e);
+      return;
+    }
+  };
+
+  // Answered once the event loop runs, which notifies the windows if the theme changed.
+  read_setting(&connection, COLOR_SCHEME_KEY, |value| {
+    if let Some(color_scheme) = value.get::<u32>() {
+      set_color_scheme(color_scheme);
+    }
+  });
+  let event_tx_ = event_tx.clone();
+  read_setting(&connection, ACCENT_COLOR_KEY, move |value| {
+    set_accent_color(to_rgba(&value), &event_tx_)
+  });
+
+  // The bus connection is a shared singleton; keep it alive for as long as the subscription is.
+  let connection_ = connection.clone();
+  connection.signal_subscribe(
+    Some(PORTAL_BUS),
+    Some(SETTINGS_INTERFACE),
+    Some("SettingChanged"),
+    Some(PORTAL_PATH),
+    None,
+    DBusSignalFlags::NONE,
+    move |_, _, _, _, _, params| {
+      let _ = &connection_;
+      // (namespace, key, value)
+      let namespace = params.try_child_value(0);
+      if namespace.as_ref().and_then(|n| n.str()) != Some(APPEARANCE_NAMESPACE) {
+        return;
+      }
+      let key = params.try_child_value(1);
+      let value = match params.try_child_value(2) {
+        Some(value) => unwrap_setting(value),
+        None => return,
+      };
+      match key.as_ref().and_then(|k| k.str()) {
+        Some(COLOR_SCHEME_KEY) => {
+          if let Some(color_scheme) = value.get::<u32>() {
+            set_color_scheme(color_scheme);
+          }
+        }
+        Some(ACCENT_COLOR_KEY) => set_accent_color(to_rgba(&value), &event_tx),
+        _ => {}
+      }
+    },
+  );
+}
+
+/// Reads a setting of the appearance namespace of the portal, without blocking the event loop
+/// while the portal starts.
+fn read_setting<F: FnOnce(Variant) + 'static>(
+  connection: &DBusConnection,
+  key: &'static str,
+  callback: F,
+) {
+  let cancellable: Option<&Cancellable> = None;
+  connection.call(
+    Some(PORTAL_BUS),
+    PORTAL_PATH,
+    SETTINGS_INTERFACE,
+    "Read",
+    Some(&(APPEARANCE_NAMESPACE, key).to_variant()),
+    VariantTy::new("(v)").ok(),
+    DBusCallFlags::NONE,
+    1000,
+    cancellable,
+    move |reply| match reply {
+      Ok(reply) => callback(unwrap_setting(reply.child_value(0))),
+      Err(e) => log::debug!("Failed to read the `{}` setting: {}", key, e),
+    },
+  );
+}
+
+fn set_color_scheme(color_scheme: u32) {
+  let previous = COLOR_SCHEME.swap(color_scheme, Ordering::Relaxed);
+  if let Some(settings) = Settings::default() {
+    // Without a preference, the dark variant configured in the GTK settings is kept.
+    let follow = color_scheme != NO_PREFERENCE || previous == PREFER_DARK;
+    if follow && !FORCED_THEME.load(Ordering::Relaxed) {
+      settings.set_gtk_application_prefer_dark_theme(color_scheme == PREFER_DARK);
+    }
+    // Setting the same value doesn't always notify, and a forced theme doesn't set it at all.
+    settings.notify("gtk-application-prefer-dark-theme");
+  }
+}
+
+/// Sends `Event::AccentColorChanged` if the accent color changed, including when it is first read.
+fn set_accent_color<T>(
+  accent_color: Option<RGBA>,
+  event_tx: &crossbeam_channel::Sender<Event<'static, T>>,
+) {
+  let previous = std::mem::replace(&mut *ACCENT_COLOR.lock().unwrap(), accent_color);
+  if let (true, Some(accent_color)) = (previous != accent_color, accent_color) {
+    if let Err(e) = event_tx.send(Event::AccentColorChanged(accent_color)) {
+      log::warn!("Failed to send accent color event to event channel: {}", e);
+    }
+  }
+}
+
+/// Unwraps the value of a setting, which `Read` nests in several variants.
+fn unwrap_setting(value: Variant) -> Variant {
+  match value.as_variant() {
+    Some(inner) => unwrap_setting(inner),
+    None => value,
+  }
+}
+
+/// Converts an `accent-color`, whose channels are out of the `[0, 1]` range when it isn't set.
+fn to_rgba(value: &Variant) -> Option<RGBA> {
+  let (r, g, b) = value.get::<(f64, f64, f64)>()?;
+  let channel = |c: f64| (0.0..=1.0).contains(&c).then(|| (c * 255.).round() as u8);
+  Some((channel(r)?, channel(g)?, channel(b)?, 255))
+}
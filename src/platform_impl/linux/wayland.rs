@@ -0,0 +1,77 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Wayland objects behind the GDK display and windows, for the raw handles. The GDK Wayland
+//! backend isn't bound by gtk-rs, and GDK may be built without it, so its functions are looked
+//! up at runtime in the `gdk-3` library instead of being linked.
+
+use std::{
+  ffi::CString,
+  os::raw::{c_char, c_void},
+};
+
+use gtk::prelude::*;
+use libloading::Library;
+use raw_window_handle::{WaylandDisplayHandle, WaylandWindowHandle};
+
+type GdkDisplayPtr = *mut gdk::ffi::GdkDisplay;
+
+struct GdkWayland {
+  get_wl_display: unsafe extern "C" fn(GdkDisplayPtr) -> *mut c_void,
+  get_wl_surface: unsafe extern "C" fn(*mut gdk::ffi::GdkWindow) -> *mut c_void,
+  // Only in recent versions of GTK 3, which support the activation protocol.
+  set_startup_notification_id: Option<unsafe extern "C" fn(GdkDisplayPtr, *const c_char)>,
+  _library: Library,
+}
+
+impl GdkWayland {
+  fn open() -> Option<Self> {
+    unsafe {
+      let library = Library::new("libgdk-3.so.0").ok()?;
+      Some(Self {
+        get_wl_display: *library.get(b"gdk_wayland_display_get_wl_display\0").ok()?,
+        get_wl_surface: *library.get(b"gdk_wayland_window_get_wl_surface\0").ok()?,
+        set_startup_notification_id: library
+          .get(b"gdk_wayland_display_set_startup_notification_id\0")
+          .ok()
+          .map(|f| *f),
+        _library: library,
+      })
+    }
+  }
+}
+
+lazy_static! {
+  static ref GDK_WAYLAND: Option<GdkWayland> = GdkWayland::open();
+}
+
+/// The `wl_display` of a display of the Wayland backend.
+pub(crate) fn display_handle(display: &gdk::Display) -> WaylandDisplayHandle {
+  let mut display_handle = WaylandDisplayHandle::empty();
+  if let Some(gdk_wayland) = &*GDK_WAYLAND {
+    display_handle.display = unsafe { (gdk_wayland.get_wl_display)(display.as_ptr()) };
+  }
+  display_handle
+}
+
+/// The `wl_surface` of a window of the Wayland backend, null until the window is realized.
+pub(crate) fn window_handle(window: Option<&gdk::Window>) -> WaylandWindowHandle {
+  let mut window_handle = WaylandWindowHandle::empty();
+  if let (Some(window), Some(gdk_wayland)) = (window, &*GDK_WAYLAND) {
+    window_handle.surface = unsafe { (gdk_wayland.get_wl_surface)(window.as_ptr()) };
+  }
+  window_handle
+}
+
+/// Sets the activation token GDK hands to the compositor when the next window is presented.
+pub(crate) fn set_startup_notification_id(display: &gdk::Display, token: &str) {
+  let set_startup_notification_id = GDK_WAYLAND
+    .as_ref()
+    .and_then(|gdk_wayland| gdk_wayland.set_startup_notification_id);
+  if let (Some(set_startup_notification_id), Ok(token)) =
+    (set_startup_notification_id, CString::new(token))
+  {
+    unsafe { set_startup_notification_id(display.as_ptr(), token.as_ptr()) };
+  }
+}
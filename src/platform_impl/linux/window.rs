@@ -3,31 +3,39 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
-  cell::RefCell,
+  cell::{Cell, RefCell},
   collections::VecDeque,
+  path::PathBuf,
   rc::Rc,
   sync::atomic::{AtomicBool, AtomicI32, Ordering},
 };
 
-use gdk::{WindowEdge, WindowState};
+use gdk::{WMFunction, WindowEdge, WindowState};
 use glib::translate::ToGlibPtr;
-use gtk::{prelude::*, traits::SettingsExt, AccelGroup, Orientation, Settings};
-use raw_window_handle::{RawDisplayHandle, RawWindowHandle, XlibDisplayHandle, XlibWindowHandle};
+use gtk::{prelude::*, AccelGroup, Orientation};
+use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
 
 use crate::{
   dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
+  event::{DeviceEvent, Rectangle, TrayEvent},
   icon::Icon,
   menu::{MenuId, MenuItem},
   monitor::MonitorHandle as RootMonitorHandle,
+  platform::unix::{HeaderBarAttributes, HeaderBarPack, WindowType},
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, BORDERLESS_RESIZE_INSET,
+    BlurEffect, CursorGrabMode, CursorIcon, DragData, Fullscreen, ProgressBarState,
+    ResizeDirection, Theme, UserAttentionType, WindowAttributes, WindowButtons, WindowLevel,
+    BORDERLESS_RESIZE_INSET, RGBA,
   },
+  TrayId,
 };
 
+#[cfg(feature = "accesskit")]
+use super::accessibility;
 use super::{
-  event_loop::EventLoopWindowTarget, menu, monitor::MonitorHandle, Parent,
-  PlatformSpecificWindowBuilderAttributes,
+  event_loop::EventLoopWindowTarget, layer_shell, menu, monitor::MonitorHandle, theme, wayland,
+  x11, OsError, Parent, PlatformSpecificWindowBuilderAttributes,
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -39,10 +47,6 @@ impl WindowId {
   }
 }
 
-// Currently GTK doesn't provide feature for detect theme, so we need to check theme manually.
-// ref: https://github.com/WebKit/WebKit/blob/e44ffaa0d999a9807f76f1805943eea204cfdfbc/Source/WebKit/UIProcess/API/gtk/PageClientImpl.cpp#L587
-const GTK_THEME_SUFFIX_LIST: [&'static str; 3] = ["-dark", "-Dark", "-Darker"];
-
 pub struct Window {
   /// Window id.
   pub(crate) window_id: WindowId,
@@ -57,9 +61,27 @@ pub struct Window {
   scale_factor: Rc<AtomicI32>,
   position: Rc<(AtomicI32, AtomicI32)>,
   size: Rc<(AtomicI32, AtomicI32)>,
+  /// The position and size from the last configure event while neither maximized nor fullscreen.
+  restored_position: Rc<(AtomicI32, AtomicI32)>,
+  restored_size: Rc<(AtomicI32, AtomicI32)>,
   maximized: Rc<AtomicBool>,
   minimized: Rc<AtomicBool>,
   fullscreen: RefCell<Option<Fullscreen>>,
+  /// Input method context, also responsible for dead keys and compose sequences.
+  ime_context: gtk::IMMulticontext,
+  ime_allowed: Rc<AtomicBool>,
+  /// Re-applied on every size allocation.
+  window_shape: Rc<RefCell<WindowShape>>,
+  minimize_to_tray: Rc<AtomicBool>,
+  background_color: Rc<Cell<Option<RGBA>>>,
+  drag_regions: Rc<RefCell<DragRegions>>,
+  vsync_redraws: Cell<bool>,
+  buttons: Cell<WindowButtons>,
+  geometry_hints: Cell<GeometryHints>,
+  /// Set by `WindowBuilder::with_theme`, the system theme being followed otherwise.
+  preferred_theme: Rc<Cell<Option<Theme>>>,
+  #[cfg(feature = "accesskit")]
+  accessibility: accessibility::Accessibility,
 }
 
 impl Window {
@@ -71,6 +93,17 @@ impl Window {
     let app = &event_loop_window_target.app;
     let window_requests_tx = event_loop_window_target.window_requests_tx.clone();
     let window = gtk::ApplicationWindow::new(app);
+
+    // The surface role is picked when the window is realized, so this has to come first.
+    if let Some(layer_shell) = &pl_attribs.layer_shell {
+      if !event_loop_window_target.is_wayland()
+        || !layer_shell::init(window.upcast_ref(), layer_shell)
+      {
+        unsafe { window.destroy() };
+        return Err(os_error!(OsError));
+      }
+    }
+
     let window_id = WindowId(window.id());
     event_loop_window_target
       .windows
@@ -81,7 +114,10 @@ impl Window {
     window.add_accel_group(&accel_group);
 
     // Set Width/Height & Resizable
-    let win_scale_factor = window.scale_factor();
+    let win_scale_factor = match attributes.position_on_monitor {
+      Some((ref monitor, _)) => monitor.scale_factor() as i32,
+      None => window.scale_factor(),
+    };
     let (width, height) = attributes
       .inner_size
       .map(|size| size.to_logical::<f64>(win_scale_factor as f64).into())
@@ -102,45 +138,24 @@ impl Window {
       }
     }
 
-    // Set Min/Max Size
-    let geom_mask = (if attributes.min_inner_size.is_some() {
-      gdk::WindowHints::MIN_SIZE
-    } else {
-      gdk::WindowHints::empty()
-    }) | (if attributes.max_inner_size.is_some() {
-      gdk::WindowHints::MAX_SIZE
-    } else {
-      gdk::WindowHints::empty()
-    });
-    let (min_width, min_height) = attributes
-      .min_inner_size
-      .map(|size| size.to_logical::<f64>(win_scale_factor as f64).into())
-      .unwrap_or_default();
-    let (max_width, max_height) = attributes
-      .max_inner_size
-      .map(|size| size.to_logical::<f64>(win_scale_factor as f64).into())
-      .unwrap_or_default();
-    let picky_none: Option<&gtk::Window> = None;
-    window.set_geometry_hints(
-      picky_none,
-      Some(&gdk::Geometry::new(
-        min_width,
-        min_height,
-        max_width,
-        max_height,
-        0,
-        0,
-        0,
-        0,
-        0f64,
-        0f64,
-        gdk::Gravity::Center,
-      )),
-      geom_mask,
-    );
+    // Set Min/Max Size and Resize Increments
+    let to_logical = |size: Size| size.to_logical::<f64>(win_scale_factor as f64).into();
+    let geometry_hints = GeometryHints {
+      min_size: attributes.min_inner_size.map(to_logical),
+      max_size: attributes.max_inner_size.map(to_logical),
+      resize_increments: attributes.resize_increments.map(to_logical),
+    };
+    apply_geometry_hints(window.upcast_ref(), geometry_hints);
 
     // Set Position
-    if let Some(position) = attributes.position {
+    if let Some((ref monitor, placement)) = attributes.position_on_monitor {
+      let work_area = monitor.inner.work_area();
+      let (x, y) = placement.offset(
+        (work_area.width() as f64, work_area.height() as f64),
+        (width as f64, height as f64),
+      );
+      window.move_(work_area.x() + x as i32, work_area.y() + y as i32);
+    } else if let Some(position) = attributes.position {
       let (x, y): (i32, i32) = position.to_logical::<i32>(win_scale_factor as f64).into();
       window.move_(x, y);
     }
@@ -184,11 +199,21 @@ impl Window {
     }
     window_box.pack_start(&menu_bar, false, false, 0);
     if let Some(window_menu) = attributes.window_menu {
-      window_menu.generate_menu(&mut menu_bar, &window_requests_tx, &accel_group, window_id);
+      window_menu.generate_menu(
+        &mut menu_bar,
+        &window_requests_tx,
+        &accel_group,
+        window_id,
+        None,
+      );
     }
 
     // Rest attributes
     window.set_title(&attributes.title);
+    if let Some(header_bar) = &pl_attribs.header_bar {
+      let header_bar = build_header_bar(header_bar, &attributes.title);
+      window.set_titlebar(Some(&header_bar));
+    }
     if let Some(Fullscreen::Borderless(m)) = &attributes.fullscreen {
       if let Some(monitor) = m {
         let number = monitor.inner.number;
@@ -213,46 +238,82 @@ impl Window {
       }
       window.maximize();
     }
+    // The activation token the app was launched with is meant for its first window.
+    let startup_token = attributes
+      .visible
+      .then(|| event_loop_window_target.startup_token.take())
+      .flatten();
+    if let Some(token) = &startup_token {
+      if event_loop_window_target.is_wayland() {
+        wayland::set_startup_notification_id(&event_loop_window_target.display, token);
+      } else {
+        window.set_startup_id(token);
+      }
+    }
     window.set_visible(attributes.visible);
     window.set_decorated(attributes.decorations);
+    window.set_deletable(attributes.closable);
+    let mut buttons = WindowButtons::empty();
+    buttons.set(WindowButtons::MINIMIZE, attributes.minimizable);
+    buttons.set(WindowButtons::MAXIMIZE, attributes.maximizable);
+    buttons.set(WindowButtons::CLOSE, attributes.closable);
+    if buttons != WindowButtons::all() {
+      if let Err(e) = window_requests_tx.send((window_id, WindowRequest::Buttons(buttons))) {
+        log::warn!("Fail to send buttons request: {}", e);
+      }
+    }
 
-    if attributes.always_on_bottom {
-      window.set_keep_below(attributes.always_on_bottom);
+    if attributes.window_level != WindowLevel::Normal {
+      apply_window_level(window.upcast_ref(), attributes.window_level);
     }
 
-    if attributes.always_on_top {
-      window.set_keep_above(attributes.always_on_top);
+    if attributes.opacity < 1.0 {
+      window.set_opacity(attributes.opacity.clamp(0.0, 1.0));
     }
 
     if let Some(icon) = attributes.window_icon {
       window.set_icon(Some(&icon.inner.into()));
     }
 
-    let settings = Settings::default();
-
-    if let Some(settings) = settings {
-      if let Some(preferred_theme) = attributes.preferred_theme {
-        match preferred_theme {
-          Theme::Dark => settings.set_gtk_application_prefer_dark_theme(true),
-          Theme::Light => {
-            let theme_name = settings.gtk_theme_name().map(|t| t.as_str().to_owned());
-            if let Some(theme) = theme_name {
-              // Remove dark variant.
-              if let Some(theme) = GTK_THEME_SUFFIX_LIST
-                .iter()
-                .find(|t| theme.ends_with(*t))
-                .map(|v| theme.strip_suffix(v))
-              {
-                settings.set_gtk_theme_name(theme);
-              }
-            }
-          }
-        }
-      }
+    if let Some(preferred_theme) = attributes.preferred_theme {
+      theme::force_theme(preferred_theme);
+    }
+
+    // The type hint has to be set before the window is mapped.
+    let tool_window_parent = attributes
+      .tool_window
+      .and_then(|parent| app.window_by_id(parent.0 .0));
+    if let Some(parent) = &tool_window_parent {
+      window.set_type_hint(gdk::WindowTypeHint::Utility);
+      window.set_transient_for(Some(parent));
+      window.set_accept_focus(false);
+    } else if let Some(owner) = attributes
+      .parent
+      .or(attributes.owner)
+      .and_then(|owner| app.window_by_id(owner.0 .0))
+    {
+      window.set_transient_for(Some(&owner));
+      window.set_destroy_with_parent(true);
+      window.set_modal(attributes.modal);
+    }
+    if let Some(window_type) = pl_attribs.window_type {
+      window.set_type_hint(window_type_hint(window_type));
+    }
+
+    // The window manager matches its rules against these when the window is mapped.
+    if let Some((instance, class)) = &pl_attribs.class {
+      apply_class(window.upcast_ref(), instance, class);
+    }
+    if let Some(role) = &pl_attribs.role {
+      window.set_role(role);
     }
 
     if attributes.visible {
       window.show_all();
+      // GDK activates Wayland windows with the token when they are presented.
+      if startup_token.is_some() && event_loop_window_target.is_wayland() {
+        window.present();
+      }
     } else {
       window.hide();
     }
@@ -269,7 +330,17 @@ impl Window {
     let size: Rc<(AtomicI32, AtomicI32)> = Rc::new((w_size.0.into(), w_size.1.into()));
     let size_clone = size.clone();
 
-    window.connect_configure_event(move |_, event| {
+    let restored_position: Rc<(AtomicI32, AtomicI32)> = Rc::new((w_pos.0.into(), w_pos.1.into()));
+    let restored_position_clone = restored_position.clone();
+    let restored_size: Rc<(AtomicI32, AtomicI32)> = Rc::new((w_size.0.into(), w_size.1.into()));
+    let restored_size_clone = restored_size.clone();
+
+    #[cfg(feature = "accesskit")]
+    let accessibility = accessibility::Accessibility::default();
+    #[cfg(feature = "accesskit")]
+    let accessibility_clone = accessibility.clone();
+
+    window.connect_configure_event(move |window, event| {
       let (x, y) = event.position();
       position_clone.0.store(x, Ordering::Release);
       position_clone.1.store(y, Ordering::Release);
@@ -278,9 +349,45 @@ impl Window {
       size_clone.0.store(w as i32, Ordering::Release);
       size_clone.1.store(h as i32, Ordering::Release);
 
+      // Keep the geometry the window is restored to when it leaves the maximized or
+      // fullscreen state.
+      let is_restored = window.window().map_or(true, |gdk_window| {
+        !gdk_window
+          .state()
+          .intersects(WindowState::MAXIMIZED | WindowState::FULLSCREEN)
+      });
+      if is_restored {
+        restored_position_clone.0.store(x, Ordering::Release);
+        restored_position_clone.1.store(y, Ordering::Release);
+        restored_size_clone.0.store(w as i32, Ordering::Release);
+        restored_size_clone.1.store(h as i32, Ordering::Release);
+      }
+
+      #[cfg(feature = "accesskit")]
+      accessibility::update_bounds(
+        &accessibility_clone,
+        (x, y),
+        (w as i32, h as i32),
+        window.scale_factor(),
+      );
+
       false
     });
 
+    #[cfg(feature = "accesskit")]
+    {
+      let accessibility_ = accessibility.clone();
+      window.connect_focus_in_event(move |_, _| {
+        accessibility::update_focus(&accessibility_, true);
+        Inhibit(false)
+      });
+      let accessibility_ = accessibility.clone();
+      window.connect_focus_out_event(move |_, _| {
+        accessibility::update_focus(&accessibility_, false);
+        Inhibit(false)
+      });
+    }
+
     let w_max = window.is_maximized();
     let maximized: Rc<AtomicBool> = Rc::new(w_max.into());
     let max_clone = maximized.clone();
@@ -300,14 +407,41 @@ impl Window {
       scale_factor_clone.store(window.scale_factor(), Ordering::Release);
     });
 
+    let window_shape = Rc::new(RefCell::new(WindowShape::default()));
+    let window_shape_clone = window_shape.clone();
+    window.connect_size_allocate(move |window, _| {
+      let shape = window_shape_clone.borrow();
+      if shape.corner_radius.is_some() || shape.cursor_hittest_regions.is_some() {
+        apply_window_shape(window.upcast_ref(), &shape);
+      }
+    });
+
     // Check if we should paint the transparent background ourselves.
     let mut transparent = false;
     if attributes.transparent && pl_attribs.auto_transparent {
       transparent = true;
     }
-    if let Err(e) =
-      window_requests_tx.send((window_id, WindowRequest::WireUpEvents { transparent }))
-    {
+    // Uses the input method of the desktop, falling back to the simple one built into GTK.
+    let ime_context = gtk::IMMulticontext::new();
+    let ime_allowed = Rc::new(AtomicBool::new(true));
+    let minimize_to_tray = Rc::new(AtomicBool::new(false));
+    let background_color = Rc::new(Cell::new(attributes.background_color));
+    let drag_regions = Rc::new(RefCell::new(DragRegions::default()));
+    let preferred_theme = Rc::new(Cell::new(attributes.preferred_theme));
+    if let Err(e) = window_requests_tx.send((
+      window_id,
+      WindowRequest::WireUpEvents {
+        transparent,
+        ime_context: ime_context.clone(),
+        ime_allowed: ime_allowed.clone(),
+        minimize_to_tray: minimize_to_tray.clone(),
+        background_color: background_color.clone(),
+        drag_regions: drag_regions.clone(),
+        preferred_theme: preferred_theme.clone(),
+        resize_borders: attributes.undecorated_resize_borders,
+        drag_and_drop: pl_attribs.drag_and_drop,
+      },
+    )) {
       log::warn!("Fail to send wire up events request: {}", e);
     }
 
@@ -315,6 +449,28 @@ impl Window {
       log::warn!("Fail to send redraw request: {}", e);
     }
 
+    if !attributes.shadow {
+      if let Err(e) = window_requests_tx.send((window_id, WindowRequest::Shadow(false))) {
+        log::warn!("Fail to send shadow request: {}", e);
+      }
+    }
+
+    if let Some(effect) = attributes.blur {
+      if let Err(e) = window_requests_tx.send((window_id, WindowRequest::Blur(Some(effect)))) {
+        log::warn!("Fail to send blur request: {}", e);
+      }
+    }
+
+    // The video mode is restored when the window is destroyed, which needs its events wired up.
+    if let Some(Fullscreen::Exclusive(_)) = &attributes.fullscreen {
+      if let Err(e) = window_requests_tx.send((
+        window_id,
+        WindowRequest::Fullscreen(attributes.fullscreen.clone()),
+      )) {
+        log::warn!("Fail to send fullscreen request: {}", e);
+      }
+    }
+
     let win = Self {
       window_id,
       window,
@@ -324,12 +480,26 @@ impl Window {
       scale_factor,
       position,
       size,
+      restored_position,
+      restored_size,
       maximized,
       minimized,
       fullscreen: RefCell::new(attributes.fullscreen),
+      buttons: Cell::new(buttons),
+      geometry_hints: Cell::new(geometry_hints),
+      ime_context,
+      ime_allowed,
+      window_shape,
+      minimize_to_tray,
+      background_color,
+      drag_regions,
+      vsync_redraws: Cell::new(attributes.vsync_redraws),
+      preferred_theme,
+      #[cfg(feature = "accesskit")]
+      accessibility,
     };
 
-    win.set_skip_taskbar(pl_attribs.skip_taskbar);
+    win.set_skip_taskbar(pl_attribs.skip_taskbar || tool_window_parent.is_some());
 
     Ok(win)
   }
@@ -343,15 +513,24 @@ impl Window {
   }
 
   pub fn request_redraw(&self) {
-    if let Err(e) = self
-      .window_requests_tx
-      .send((self.window_id, WindowRequest::Redraw))
-    {
+    let request = if self.vsync_redraws.get() {
+      WindowRequest::FrameRedraw
+    } else {
+      WindowRequest::Redraw
+    };
+    if let Err(e) = self.window_requests_tx.send((self.window_id, request)) {
       log::warn!("Fail to send redraw request: {}", e);
     }
   }
 
+  pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+    self.vsync_redraws.set(vsync_redraws);
+  }
+
   pub fn inner_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+    if self.is_wayland() {
+      return Err(NotSupportedError::new());
+    }
     let (x, y) = &*self.position;
     Ok(
       LogicalPosition::new(x.load(Ordering::Acquire), y.load(Ordering::Acquire))
@@ -360,6 +539,9 @@ impl Window {
   }
 
   pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
+    if self.is_wayland() {
+      return Err(NotSupportedError::new());
+    }
     let (x, y) = &*self.position;
     Ok(
       LogicalPosition::new(x.load(Ordering::Acquire), y.load(Ordering::Acquire))
@@ -402,6 +584,12 @@ impl Window {
     }
   }
 
+  pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    // The request is handled by the event loop, and GTK resizes the window asynchronously.
+    self.set_inner_size(size);
+    None
+  }
+
   pub fn outer_size(&self) -> PhysicalSize<u32> {
     let (width, height) = &*self.size;
 
@@ -413,27 +601,33 @@ impl Window {
   }
 
   pub fn set_min_inner_size<S: Into<Size>>(&self, min_size: Option<S>) {
-    if let Some(size) = min_size {
-      let (min_width, min_height) = size.into().to_logical::<i32>(self.scale_factor()).into();
-
-      if let Err(e) = self.window_requests_tx.send((
-        self.window_id,
-        WindowRequest::MinSize((min_width, min_height)),
-      )) {
-        log::warn!("Fail to send min size request: {}", e);
-      }
-    }
+    let min_size = min_size.map(|size| self.to_logical_hint(size.into()));
+    self.set_geometry_hints(|hints| hints.min_size = min_size);
   }
+
   pub fn set_max_inner_size<S: Into<Size>>(&self, max_size: Option<S>) {
-    if let Some(size) = max_size {
-      let (max_width, max_height) = size.into().to_logical::<i32>(self.scale_factor()).into();
+    let max_size = max_size.map(|size| self.to_logical_hint(size.into()));
+    self.set_geometry_hints(|hints| hints.max_size = max_size);
+  }
 
-      if let Err(e) = self.window_requests_tx.send((
-        self.window_id,
-        WindowRequest::MaxSize((max_width, max_height)),
-      )) {
-        log::warn!("Fail to send max size request: {}", e);
-      }
+  pub fn set_resize_increments(&self, increments: Option<Size>) {
+    let increments = increments.map(|size| self.to_logical_hint(size));
+    self.set_geometry_hints(|hints| hints.resize_increments = increments);
+  }
+
+  fn to_logical_hint(&self, size: Size) -> (i32, i32) {
+    size.to_logical::<i32>(self.scale_factor()).into()
+  }
+
+  fn set_geometry_hints<F: FnOnce(&mut GeometryHints)>(&self, f: F) {
+    let mut hints = self.geometry_hints.get();
+    f(&mut hints);
+    self.geometry_hints.set(hints);
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::GeometryHints(hints)))
+    {
+      log::warn!("Fail to send geometry hints request: {}", e);
     }
   }
 
@@ -488,6 +682,30 @@ impl Window {
     }
   }
 
+  pub fn set_minimizable(&self, minimizable: bool) {
+    self.set_button(WindowButtons::MINIMIZE, minimizable);
+  }
+
+  pub fn set_maximizable(&self, maximizable: bool) {
+    self.set_button(WindowButtons::MAXIMIZE, maximizable);
+  }
+
+  pub fn set_closable(&self, closable: bool) {
+    self.set_button(WindowButtons::CLOSE, closable);
+  }
+
+  fn set_button(&self, button: WindowButtons, enabled: bool) {
+    let mut buttons = self.buttons.get();
+    buttons.set(button, enabled);
+    self.buttons.set(buttons);
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Buttons(buttons)))
+    {
+      log::warn!("Fail to send buttons request: {}", e);
+    }
+  }
+
   pub fn set_minimized(&self, minimized: bool) {
     if let Err(e) = self
       .window_requests_tx
@@ -497,6 +715,12 @@ impl Window {
     }
   }
 
+  pub fn set_minimize_to_tray(&self, minimize_to_tray: bool) {
+    self
+      .minimize_to_tray
+      .store(minimize_to_tray, Ordering::Release);
+  }
+
   pub fn set_maximized(&self, maximized: bool) {
     if let Err(e) = self
       .window_requests_tx
@@ -514,10 +738,42 @@ impl Window {
     self.minimized.load(Ordering::Acquire)
   }
 
+  pub fn restored_geometry(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let scale_factor = self.scale_factor.load(Ordering::Acquire) as f64;
+    let (x, y) = &*self.restored_position;
+    let (width, height) = &*self.restored_size;
+    let position = if self.is_wayland() {
+      PhysicalPosition::default()
+    } else {
+      LogicalPosition::new(x.load(Ordering::Acquire), y.load(Ordering::Acquire))
+        .to_physical(scale_factor)
+    };
+    (
+      position,
+      LogicalSize::new(
+        width.load(Ordering::Acquire) as u32,
+        height.load(Ordering::Acquire) as u32,
+      )
+      .to_physical(scale_factor),
+    )
+  }
+
   pub fn is_resizable(&self) -> bool {
     self.window.is_resizable()
   }
 
+  pub fn is_minimizable(&self) -> bool {
+    self.buttons.get().contains(WindowButtons::MINIMIZE)
+  }
+
+  pub fn is_maximizable(&self) -> bool {
+    self.buttons.get().contains(WindowButtons::MAXIMIZE)
+  }
+
+  pub fn is_closable(&self) -> bool {
+    self.buttons.get().contains(WindowButtons::CLOSE)
+  }
+
   pub fn is_decorated(&self) -> bool {
     self.window.is_decorated()
   }
@@ -527,6 +783,30 @@ impl Window {
     self.window.is_visible()
   }
 
+  pub fn request_activation_token(&self) -> Result<String, ExternalError> {
+    let context = self
+      .window
+      .display()
+      .app_launch_context()
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))?;
+    // On X11, the launch feedback uses the name of the app info.
+    let app_info = std::env::current_exe()
+      .ok()
+      .and_then(|exe| {
+        gio::AppInfo::create_from_commandline(
+          exe,
+          None,
+          gio::AppInfoCreateFlags::SUPPORTS_STARTUP_NOTIFICATION,
+        )
+        .ok()
+      })
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))?;
+    context
+      .startup_notify_id(&app_info, &[])
+      .map(Into::into)
+      .ok_or_else(|| ExternalError::Os(os_error!(OsError)))
+  }
+
   pub fn drag_window(&self) -> Result<(), ExternalError> {
     if let Err(e) = self
       .window_requests_tx
@@ -537,6 +817,26 @@ impl Window {
     Ok(())
   }
 
+  pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::DragResizeWindow(direction)))
+    {
+      log::warn!("Fail to send drag resize window request: {}", e);
+    }
+    Ok(())
+  }
+
+  pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::StartDrag(data)))
+    {
+      log::warn!("Fail to send start drag request: {}", e);
+    }
+    Ok(())
+  }
+
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
     self.fullscreen.replace(fullscreen.clone());
     if let Err(e) = self
@@ -551,6 +851,11 @@ impl Window {
     self.fullscreen.borrow().clone()
   }
 
+  pub fn current_fullscreen(&self) -> Option<Fullscreen> {
+    // The requested video mode is reported, XRandR isn't asked which one is in use.
+    self.fullscreen()
+  }
+
   pub fn set_decorations(&self, decorations: bool) {
     if let Err(e) = self
       .window_requests_tx
@@ -560,21 +865,110 @@ impl Window {
     }
   }
 
-  pub fn set_always_on_bottom(&self, always_on_bottom: bool) {
-    if let Err(e) = self.window_requests_tx.send((
-      self.window_id,
-      WindowRequest::AlwaysOnBottom(always_on_bottom),
-    )) {
-      log::warn!("Fail to send always on bottom request: {}", e);
+  pub fn set_corner_radius(&self, radius: Option<f64>) {
+    let radius = radius.filter(|r| *r > 0.0);
+    self.window_shape.borrow_mut().corner_radius = radius;
+    self.send_window_shape();
+  }
+
+  pub fn set_cursor_hittest_region(
+    &self,
+    regions: Option<&[Rectangle]>,
+  ) -> Result<(), ExternalError> {
+    self.window_shape.borrow_mut().cursor_hittest_regions = regions.map(<[Rectangle]>::to_vec);
+    self.send_window_shape();
+
+    Ok(())
+  }
+
+  fn send_window_shape(&self) {
+    let shape = self.window_shape.borrow().clone();
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::WindowShape(shape)))
+    {
+      log::warn!("Fail to send window shape request: {}", e);
+    }
+  }
+
+  pub fn set_drag_regions(&self, regions: Vec<Rectangle>, exclusions: Vec<Rectangle>) {
+    *self.drag_regions.borrow_mut() = DragRegions {
+      regions,
+      exclusions,
+    };
+  }
+
+  pub fn set_drop_highlight(&self, highlight: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::DropHighlight(highlight)))
+    {
+      log::warn!("Fail to send drop highlight request: {}", e);
+    }
+  }
+
+  pub fn set_background_color(&self, color: Option<RGBA>) {
+    self.background_color.set(color);
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Redraw))
+    {
+      log::warn!("Fail to send redraw request: {}", e);
+    }
+  }
+
+  pub fn set_keyboard_grab(&self, grab: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::KeyboardGrab(grab)))
+    {
+      log::warn!("Fail to send keyboard grab request: {}", e);
+    }
+  }
+
+  pub fn set_opacity(&self, opacity: f64) {
+    self.window.set_opacity(opacity.clamp(0.0, 1.0));
+  }
+
+  pub fn set_content_protection(&self, protected: bool) {
+    if protected {
+      log::warn!("`Window::set_content_protection` is unsupported on Linux");
+    }
+  }
+
+  pub fn set_shadow(&self, shadow: bool) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Shadow(shadow)))
+    {
+      log::warn!("Fail to send shadow request: {}", e);
     }
   }
 
-  pub fn set_always_on_top(&self, always_on_top: bool) {
+  pub fn set_blur(&self, effect: Option<BlurEffect>) {
     if let Err(e) = self
       .window_requests_tx
-      .send((self.window_id, WindowRequest::AlwaysOnTop(always_on_top)))
+      .send((self.window_id, WindowRequest::Blur(effect)))
     {
-      log::warn!("Fail to send always on top request: {}", e);
+      log::warn!("Fail to send blur request: {}", e);
+    }
+  }
+
+  pub fn set_window_level(&self, window_level: WindowLevel) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::WindowLevel(window_level)))
+    {
+      log::warn!("Fail to send window level request: {}", e);
+    }
+  }
+
+  pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+    if let Err(e) = self.window_requests_tx.send((
+      self.window_id,
+      WindowRequest::VisibleOnAllWorkspaces(visible),
+    )) {
+      log::warn!("Fail to send visible on all workspaces request: {}", e);
     }
   }
 
@@ -587,8 +981,29 @@ impl Window {
     }
   }
 
-  pub fn set_ime_position<P: Into<Position>>(&self, _position: P) {
-    //TODO
+  pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+    let scale_factor = self.scale_factor();
+    let (x, y): (i32, i32) = position.to_logical::<i32>(scale_factor).into();
+    let (width, height): (i32, i32) = size.to_logical::<i32>(scale_factor).into();
+    self
+      .ime_context
+      .set_cursor_location(&gdk::Rectangle::new(x, y, width, height));
+  }
+
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    if self.ime_allowed.swap(allowed, Ordering::AcqRel) == allowed {
+      return;
+    }
+    if allowed {
+      self.ime_context.focus_in();
+    } else {
+      self.ime_context.reset();
+      self.ime_context.focus_out();
+    }
+  }
+
+  pub fn reset_dead_keys(&self) {
+    self.ime_context.reset();
   }
 
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
@@ -600,6 +1015,44 @@ impl Window {
     }
   }
 
+  pub fn set_progress_bar(&self, progress: ProgressBarState) {
+    // The launcher entry belongs to the application, not to this window.
+    if let Err(e) = self
+      .window_requests_tx
+      .send((WindowId::dummy(), WindowRequest::ProgressBar(progress)))
+    {
+      log::warn!("Fail to send update progress bar request: {}", e);
+    }
+  }
+
+  pub fn set_badge_count(&self, count: Option<u64>) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((WindowId::dummy(), WindowRequest::BadgeCount(count)))
+    {
+      log::warn!("Fail to send update badge count request: {}", e);
+    }
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn set_accessibility_source(&self, source: accessibility::AccessibilitySource) {
+    accessibility::set_source(
+      &self.accessibility,
+      &self.window,
+      self.window_id,
+      self.window_requests_tx.clone(),
+      source,
+    );
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn update_accessibility<F>(&self, update: F)
+  where
+    F: FnOnce() -> crate::accesskit::TreeUpdate,
+  {
+    accessibility::update(&self.accessibility, update);
+  }
+
   pub fn hide_menu(&self) {
     self.menu_bar.hide();
   }
@@ -622,33 +1075,48 @@ impl Window {
   }
 
   pub fn set_cursor_position<P: Into<Position>>(&self, position: P) -> Result<(), ExternalError> {
-    let inner_pos = self.inner_position().unwrap_or_default();
+    if self.is_wayland() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
     let (x, y): (i32, i32) = position
       .into()
       .to_logical::<i32>(self.scale_factor())
       .into();
 
-    if let Err(e) = self.window_requests_tx.send((
-      self.window_id,
-      WindowRequest::CursorPosition((x + inner_pos.x, y + inner_pos.y)),
-    )) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::CursorPosition((x, y))))
+    {
       log::warn!("Fail to send cursor position request: {}", e);
     }
 
     Ok(())
   }
 
-  pub fn set_cursor_grab(&self, _grab: bool) -> Result<(), ExternalError> {
-    Ok(())
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    let confine = match mode {
+      CursorGrabMode::None => false,
+      CursorGrabMode::Confined => true,
+      // Neither the pointer constraints of Wayland nor relative motion are available with GTK.
+      CursorGrabMode::Locked => return Err(ExternalError::NotSupported(NotSupportedError::new())),
+    };
+    let gdk_window = match self.window.window() {
+      Some(gdk_window) => gdk_window,
+      None => return Err(ExternalError::Os(os_error!(OsError))),
+    };
+    if !gdk_window.display().backend().is_x11() {
+      return Err(ExternalError::NotSupported(NotSupportedError::new()));
+    }
+    if x11::confine_pointer(&gdk_window, confine) {
+      Ok(())
+    } else {
+      Err(ExternalError::Os(os_error!(OsError)))
+    }
   }
 
   pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<(), ExternalError> {
-    if let Err(e) = self
-      .window_requests_tx
-      .send((self.window_id, WindowRequest::CursorIgnoreEvents(ignore)))
-    {
-      log::warn!("Fail to send cursor position request: {}", e);
-    }
+    self.window_shape.borrow_mut().ignore_cursor_events = ignore;
+    self.send_window_shape();
 
     Ok(())
   }
@@ -709,27 +1177,26 @@ impl Window {
   }
 
   pub fn raw_window_handle(&self) -> RawWindowHandle {
-    // TODO: add wayland support
-    let mut window_handle = XlibWindowHandle::empty();
-    unsafe {
-      if let Some(window) = self.window.window() {
-        window_handle.window = gdk_x11_sys::gdk_x11_window_get_xid(window.as_ptr() as *mut _);
-      }
+    let window = self.window.window();
+    if self.is_wayland() {
+      RawWindowHandle::Wayland(wayland::window_handle(window.as_ref()))
+    } else {
+      RawWindowHandle::Xlib(x11::window_handle(window.as_ref()))
     }
-    RawWindowHandle::Xlib(window_handle)
   }
 
   pub fn raw_display_handle(&self) -> RawDisplayHandle {
-    let mut display_handle = XlibDisplayHandle::empty();
-    unsafe {
-      if let Ok(xlib) = x11_dl::xlib::Xlib::open() {
-        let display = (xlib.XOpenDisplay)(std::ptr::null());
-        display_handle.display = display as _;
-        display_handle.screen = (xlib.XDefaultScreen)(display) as _;
-      }
+    let display = self.window.display();
+    if self.is_wayland() {
+      RawDisplayHandle::Wayland(wayland::display_handle(&display))
+    } else {
+      RawDisplayHandle::Xlib(x11::display_handle(&display))
     }
+  }
 
-    RawDisplayHandle::Xlib(display_handle)
+  /// Wayland has no global coordinates, and doesn't let clients move the cursor.
+  fn is_wayland(&self) -> bool {
+    self.window.display().backend().is_wayland()
   }
 
   pub(crate) fn set_skip_taskbar(&self, skip: bool) {
@@ -741,16 +1208,37 @@ impl Window {
     }
   }
 
+  pub(crate) fn set_class(&self, instance: String, class: String) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Class(instance, class)))
+    {
+      log::warn!("Fail to send class request: {}", e);
+    }
+  }
+
+  pub(crate) fn set_role(&self, role: String) {
+    if let Err(e) = self
+      .window_requests_tx
+      .send((self.window_id, WindowRequest::Role(role)))
+    {
+      log::warn!("Fail to send role request: {}", e);
+    }
+  }
+
   pub fn theme(&self) -> Theme {
-    if let Some(settings) = Settings::default() {
-      let theme_name = settings.gtk_theme_name().map(|s| s.as_str().to_owned());
-      if let Some(theme) = theme_name {
-        if GTK_THEME_SUFFIX_LIST.iter().any(|t| theme.ends_with(t)) {
-          return Theme::Dark;
-        }
-      }
+    self
+      .preferred_theme
+      .get()
+      .unwrap_or_else(theme::system_theme)
+  }
+
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    self.preferred_theme.set(theme);
+    match theme {
+      Some(theme) => theme::force_theme(theme),
+      None => theme::follow_system_theme(),
     }
-    return Theme::Light;
   }
 }
 
@@ -759,34 +1247,351 @@ impl Window {
 unsafe impl Send for Window {}
 unsafe impl Sync for Window {}
 
+/// Client areas that move the window when dragged, in physical pixels.
+#[derive(Default)]
+pub struct DragRegions {
+  pub regions: Vec<Rectangle>,
+  pub exclusions: Vec<Rectangle>,
+}
+
+impl DragRegions {
+  pub fn contains(&self, position: PhysicalPosition<f64>) -> bool {
+    self.regions.iter().any(|r| r.contains(position))
+      && !self.exclusions.iter().any(|r| r.contains(position))
+  }
+}
+
 #[non_exhaustive]
 pub enum WindowRequest {
   Title(String),
   Position((i32, i32)),
   Size((i32, i32)),
-  MinSize((i32, i32)),
-  MaxSize((i32, i32)),
+  GeometryHints(GeometryHints),
   Visible(bool),
   Focus,
   Resizable(bool),
   Minimized(bool),
   Maximized(bool),
   DragWindow,
+  DragResizeWindow(ResizeDirection),
+  StartDrag(DragData),
   Fullscreen(Option<Fullscreen>),
   Decorations(bool),
-  AlwaysOnBottom(bool),
-  AlwaysOnTop(bool),
+  Buttons(WindowButtons),
+  WindowShape(WindowShape),
+  Blur(Option<BlurEffect>),
+  Shadow(bool),
+  KeyboardGrab(bool),
+  DropHighlight(bool),
+  WindowLevel(WindowLevel),
+  VisibleOnAllWorkspaces(bool),
   WindowIcon(Option<Icon>),
   UserAttention(Option<UserAttentionType>),
   SetSkipTaskbar(bool),
+  Class(String, String),
+  Role(String),
   CursorIcon(Option<CursorIcon>),
   CursorPosition((i32, i32)),
-  CursorIgnoreEvents(bool),
-  WireUpEvents { transparent: bool },
+  WireUpEvents {
+    transparent: bool,
+    ime_context: gtk::IMMulticontext,
+    ime_allowed: Rc<AtomicBool>,
+    minimize_to_tray: Rc<AtomicBool>,
+    background_color: Rc<Cell<Option<RGBA>>>,
+    drag_regions: Rc<RefCell<DragRegions>>,
+    preferred_theme: Rc<Cell<Option<Theme>>>,
+    resize_borders: bool,
+    drag_and_drop: bool,
+  },
   Redraw,
+  /// A redraw on the next tick of the frame clock of the window.
+  FrameRedraw,
   Menu((Option<MenuItem>, Option<MenuId>)),
+  TrayMenu((TrayId, MenuId)),
+  TrayEvent(TrayId, TrayEvent),
   SetMenu((Option<menu::Menu>, AccelGroup, gtk::MenuBar)),
   GlobalHotKey(u16),
+  ListenClipboard(bool),
+  ProgressBar(ProgressBarState),
+  BadgeCount(Option<u64>),
+  NewInstance(Vec<String>, PathBuf),
+  DeviceEvent(DeviceEvent),
+  #[cfg(feature = "accesskit")]
+  AccessibilityAction(crate::accesskit::ActionRequest),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WindowShape {
+  /// In logical pixels.
+  pub corner_radius: Option<f64>,
+  pub ignore_cursor_events: bool,
+  /// In physical pixels, relative to the window.
+  pub cursor_hittest_regions: Option<Vec<Rectangle>>,
+}
+
+/// Applies the rounded-rectangle shape of the window, or resets it when there is no corner
+/// radius. The input region follows the shape, limited to the hit-test regions if any, or is
+/// emptied so cursor events pass through when they are ignored.
+pub fn apply_window_shape(window: &gtk::Window, shape: &WindowShape) {
+  let region = match shape.corner_radius {
+    Some(radius) => {
+      let (width, height) = (window.allocated_width(), window.allocated_height());
+      if width <= 0 || height <= 0 {
+        return;
+      }
+      Some(rounded_region(width, height, radius))
+    }
+    None => None,
+  };
+
+  // Compositors on Wayland ignore the shape region, but still honor the input region.
+  window.shape_combine_region(region.as_ref());
+  if shape.ignore_cursor_events {
+    window.input_shape_combine_region(Some(&cairo::Region::create()));
+  } else if let Some(regions) = &shape.cursor_hittest_regions {
+    let scale_factor = window.scale_factor() as f64;
+    let input_region = cairo::Region::create();
+    for rect in regions {
+      let position = rect.position.to_logical::<f64>(scale_factor);
+      let size = rect.size.to_logical::<f64>(scale_factor);
+      // Round outwards so regions never lose their edge pixels.
+      let (x, y) = (position.x.floor() as i32, position.y.floor() as i32);
+      let _ = input_region.union_rectangle(&cairo::RectangleInt {
+        x,
+        y,
+        width: (position.x + size.width).ceil() as i32 - x,
+        height: (position.y + size.height).ceil() as i32 - y,
+      });
+    }
+    if let Some(region) = &region {
+      let _ = input_region.intersect(region);
+    }
+    window.input_shape_combine_region(Some(&input_region));
+  } else {
+    window.input_shape_combine_region(region.as_ref());
+  }
+}
+
+/// Removes or restores the shadow GTK draws around client-side decorations.
+pub fn apply_shadow(window: &gtk::Window, shadow: bool) {
+  const NO_SHADOW_CLASS: &str = "tao-no-shadow";
+  thread_local! {
+    static NO_SHADOW_CSS: gtk::CssProvider = {
+      let css_provider = gtk::CssProvider::new();
+      let _ = css_provider.load_from_data(
+        format!(
+          ".{} decoration {{ box-shadow: none; margin: 0; }}",
+          NO_SHADOW_CLASS
+        )
+        .as_bytes(),
+      );
+      css_provider
+    };
+  }
+
+  let style_context = window.style_context();
+  if shadow {
+    style_context.remove_class(NO_SHADOW_CLASS);
+  } else {
+    NO_SHADOW_CSS.with(|css_provider| {
+      style_context.add_provider(css_provider, gtk::STYLE_PROVIDER_PRIORITY_APPLICATION)
+    });
+    style_context.add_class(NO_SHADOW_CLASS);
+  }
+}
+
+/// The size hints of a window in logical pixels, which GTK only lets set all at once.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct GeometryHints {
+  pub min_size: Option<(i32, i32)>,
+  pub max_size: Option<(i32, i32)>,
+  pub resize_increments: Option<(i32, i32)>,
+}
+
+pub fn apply_geometry_hints(window: &gtk::Window, hints: GeometryHints) {
+  let mut mask = gdk::WindowHints::empty();
+  let (min_width, min_height) = hints.min_size.unwrap_or_default();
+  mask.set(gdk::WindowHints::MIN_SIZE, hints.min_size.is_some());
+  let (max_width, max_height) = hints.max_size.unwrap_or_default();
+  mask.set(gdk::WindowHints::MAX_SIZE, hints.max_size.is_some());
+  let (width_inc, height_inc) = hints.resize_increments.unwrap_or_default();
+  mask.set(
+    gdk::WindowHints::RESIZE_INC,
+    hints.resize_increments.is_some(),
+  );
+  let picky_none: Option<&gtk::Window> = None;
+  window.set_geometry_hints(
+    picky_none,
+    Some(&gdk::Geometry::new(
+      min_width,
+      min_height,
+      max_width,
+      max_height,
+      0,
+      0,
+      width_inc.max(1),
+      height_inc.max(1),
+      0f64,
+      0f64,
+      gdk::Gravity::Center,
+    )),
+    mask,
+  );
+}
+
+/// Keeps the window above or below the other windows for its level.
+pub fn apply_window_level(window: &gtk::Window, window_level: WindowLevel) {
+  let above = !matches!(window_level, WindowLevel::Desktop | WindowLevel::Normal);
+  window.set_keep_above(above);
+  window.set_keep_below(window_level == WindowLevel::Desktop);
+}
+
+/// Builds a header bar whose buttons send their id back to the event loop.
+/// The key of the id of a header bar button, which the event loop reports its clicks with.
+pub(crate) const HEADER_BAR_BUTTON_ID: &str = "tao-header-bar-button-id";
+
+fn build_header_bar(attributes: &HeaderBarAttributes, title: &str) -> gtk::HeaderBar {
+  let header_bar = gtk::HeaderBar::new();
+  header_bar.set_show_close_button(true);
+  header_bar.set_title(Some(attributes.title.as_deref().unwrap_or(title)));
+  if let Some(subtitle) = &attributes.subtitle {
+    header_bar.set_subtitle(Some(subtitle));
+  }
+  for button in &attributes.buttons {
+    let gtk_button = match (&button.icon_name, &button.label) {
+      (Some(icon_name), _) => gtk::Button::from_icon_name(Some(icon_name), gtk::IconSize::Button),
+      (None, Some(label)) => gtk::Button::with_label(label),
+      (None, None) => gtk::Button::new(),
+    };
+    if button.icon_name.is_some() {
+      if let Some(label) = &button.label {
+        gtk_button.set_label(label);
+        gtk_button.set_always_show_image(true);
+      }
+    }
+    gtk_button.set_tooltip_text(button.tooltip.as_deref());
+    unsafe { gtk_button.set_data(HEADER_BAR_BUTTON_ID, button.id) };
+    match button.pack {
+      HeaderBarPack::Start => header_bar.pack_start(&gtk_button),
+      HeaderBarPack::End => header_bar.pack_end(&gtk_button),
+    }
+  }
+  header_bar.show_all();
+  header_bar
+}
+
+fn window_type_hint(window_type: WindowType) -> gdk::WindowTypeHint {
+  match window_type {
+    WindowType::Normal => gdk::WindowTypeHint::Normal,
+    WindowType::Dialog => gdk::WindowTypeHint::Dialog,
+    WindowType::Utility => gdk::WindowTypeHint::Utility,
+    WindowType::Toolbar => gdk::WindowTypeHint::Toolbar,
+    WindowType::Menu => gdk::WindowTypeHint::Menu,
+    WindowType::DropdownMenu => gdk::WindowTypeHint::DropdownMenu,
+    WindowType::PopupMenu => gdk::WindowTypeHint::PopupMenu,
+    WindowType::Combo => gdk::WindowTypeHint::Combo,
+    WindowType::Tooltip => gdk::WindowTypeHint::Tooltip,
+    WindowType::Notification => gdk::WindowTypeHint::Notification,
+    WindowType::Splash => gdk::WindowTypeHint::Splashscreen,
+    WindowType::Dnd => gdk::WindowTypeHint::Dnd,
+    WindowType::Dock => gdk::WindowTypeHint::Dock,
+    WindowType::Desktop => gdk::WindowTypeHint::Desktop,
+  }
+}
+
+/// Sets the instance and class names of the `WM_CLASS` hint of X11, which the other backends
+/// don't have.
+pub fn apply_class(window: &gtk::Window, instance: &str, class: &str) {
+  if !window.display().backend().is_x11() {
+    return;
+  }
+  // The hint is a property of the X11 window, which only exists once realized.
+  window.realize();
+  if let Some(gdk_window) = window.window() {
+    x11::set_class_hint(&gdk_window, instance, class);
+  }
+}
+
+/// Enables the close button with GTK, and the minimize and maximize buttons with the window
+/// manager hints of X11, which the other backends don't have.
+pub fn apply_buttons(window: &gtk::Window, buttons: WindowButtons) {
+  window.set_deletable(buttons.contains(WindowButtons::CLOSE));
+  if !window.display().backend().is_x11() {
+    return;
+  }
+  // The hints are properties of the X11 window, which only exists once realized.
+  window.realize();
+  let gdk_window = match window.window() {
+    Some(gdk_window) => gdk_window,
+    None => return,
+  };
+  let mut disabled = WMFunction::empty();
+  disabled.set(
+    WMFunction::MINIMIZE,
+    !buttons.contains(WindowButtons::MINIMIZE),
+  );
+  disabled.set(
+    WMFunction::MAXIMIZE,
+    !buttons.contains(WindowButtons::MAXIMIZE),
+  );
+  disabled.set(WMFunction::CLOSE, !buttons.contains(WindowButtons::CLOSE));
+  // Along with other functions, `ALL` means all of them but those.
+  gdk_window.set_functions(WMFunction::ALL | disabled);
+}
+
+/// Sets or removes the blur hint of KWin, which other compositors ignore.
+pub fn apply_blur(window: &gtk::Window, effect: Option<BlurEffect>) {
+  if !window.display().backend().is_x11() {
+    return;
+  }
+  // The hint is a property of the X11 window, which only exists once realized.
+  window.realize();
+  let gdk_window = match window.window() {
+    Some(gdk_window) => gdk_window,
+    None => return,
+  };
+
+  let property = gdk::Atom::intern("_KDE_NET_WM_BLUR_BEHIND_REGION");
+  match effect {
+    // An empty region blurs behind the whole window.
+    Some(_) => gdk::property_change(
+      &gdk_window,
+      &property,
+      &gdk::Atom::intern("CARDINAL"),
+      32,
+      gdk::PropMode::Replace,
+      gdk::ChangeData::ULongs(&[]),
+    ),
+    None => gdk::property_delete(&gdk_window, &property),
+  }
+}
+
+fn rounded_region(width: i32, height: i32, radius: f64) -> cairo::Region {
+  let r = radius.min(width as f64 / 2.).min(height as f64 / 2.);
+  let ri = r.ceil() as i32;
+  let region = cairo::Region::create_rectangle(&cairo::RectangleInt {
+    x: 0,
+    y: ri,
+    width,
+    height: height - 2 * ri,
+  });
+
+  // Build the corners out of one-pixel high strips, inset by the circle's horizontal offset.
+  for row in 0..ri {
+    let dy = r - row as f64 - 0.5;
+    let inset = (r - (r * r - dy * dy).max(0.).sqrt()).round() as i32;
+    let strip_width = width - 2 * inset;
+    for y in [row, height - row - 1] {
+      let _ = region.union_rectangle(&cairo::RectangleInt {
+        x: inset,
+        y,
+        width: strip_width,
+        height: 1,
+      });
+    }
+  }
+
+  region
 }
 
 pub fn hit_test(window: &gdk::Window, cx: f64, cy: f64) -> WindowEdge {
@@ -6,3 +6,97 @@ pub mod ffi;
 pub mod xdisplay;
 
 pub use xdisplay::XConnection;
+
+use gtk::prelude::*;
+use raw_window_handle::{XlibDisplayHandle, XlibWindowHandle};
+use std::{
+  ffi::CString,
+  os::raw::{c_int, c_uint},
+};
+
+lazy_static! {
+  pub(crate) static ref XLIB: Option<ffi::Xlib> = ffi::Xlib::open().ok();
+}
+
+/// The Xlib display of a display of the X11 backend, which GDK owns.
+pub(crate) fn display_handle(display: &gdk::Display) -> XlibDisplayHandle {
+  let mut display_handle = XlibDisplayHandle::empty();
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _);
+    display_handle.display = xdisplay as _;
+    if let Some(xlib) = XLIB.as_ref() {
+      display_handle.screen = (xlib.XDefaultScreen)(xdisplay as *mut ffi::Display);
+    }
+  }
+  display_handle
+}
+
+/// The XID of a window of the X11 backend, `0` until the window is realized.
+pub(crate) fn window_handle(window: Option<&gdk::Window>) -> XlibWindowHandle {
+  let mut window_handle = XlibWindowHandle::empty();
+  if let Some(window) = window {
+    window_handle.window =
+      unsafe { gdk_x11_sys::gdk_x11_window_get_xid(window.as_ptr() as *mut _) };
+  }
+  window_handle
+}
+
+/// Sets the `WM_CLASS` property of `window`, which GTK only lets set before realizing it.
+pub(crate) fn set_class_hint(window: &gdk::Window, instance: &str, class: &str) {
+  let xlib = match XLIB.as_ref() {
+    Some(xlib) => xlib,
+    None => return,
+  };
+  let (instance, class) = match (CString::new(instance), CString::new(class)) {
+    (Ok(instance), Ok(class)) => (instance, class),
+    _ => return,
+  };
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(window.display().as_ptr() as *mut _)
+      as *mut ffi::Display;
+    let xid = gdk_x11_sys::gdk_x11_window_get_xid(window.as_ptr() as *mut _);
+    let mut class_hint = ffi::XClassHint {
+      res_name: instance.as_ptr() as *mut _,
+      res_class: class.as_ptr() as *mut _,
+    };
+    (xlib.XSetClassHint)(xdisplay, xid, &mut class_hint);
+    (xlib.XFlush)(xdisplay);
+  }
+}
+
+/// Grabs the pointer and confines it to `window`, or releases it when `confine` is `false`.
+/// Returns `false` if the pointer couldn't be grabbed.
+pub(crate) fn confine_pointer(window: &gdk::Window, confine: bool) -> bool {
+  let xlib = match XLIB.as_ref() {
+    Some(xlib) => xlib,
+    None => return false,
+  };
+  unsafe {
+    let xdisplay = gdk_x11_sys::gdk_x11_display_get_xdisplay(window.display().as_ptr() as *mut _)
+      as *mut ffi::Display;
+    if !confine {
+      (xlib.XUngrabPointer)(xdisplay, ffi::CurrentTime);
+      (xlib.XFlush)(xdisplay);
+      return true;
+    }
+    let xid = gdk_x11_sys::gdk_x11_window_get_xid(window.as_ptr() as *mut _);
+    let event_mask = ffi::ButtonPressMask
+      | ffi::ButtonReleaseMask
+      | ffi::PointerMotionMask
+      | ffi::EnterWindowMask
+      | ffi::LeaveWindowMask;
+    let result = (xlib.XGrabPointer)(
+      xdisplay,
+      xid,
+      ffi::True,
+      event_mask as c_uint,
+      ffi::GrabModeAsync,
+      ffi::GrabModeAsync,
+      xid,
+      0,
+      ffi::CurrentTime,
+    );
+    (xlib.XFlush)(xdisplay);
+    result == ffi::GrabSuccess as c_int
+  }
+}
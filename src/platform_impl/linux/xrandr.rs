@@ -0,0 +1,214 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Video modes and exclusive fullscreen through XRandR. Wayland doesn't let clients change the
+//! video mode, so none of this is available there.
+
+use std::{cell::RefCell, collections::HashMap, os::raw::c_int, slice};
+
+use gtk::prelude::*;
+
+use super::{window::WindowId, x11::ffi};
+
+lazy_static! {
+  static ref XRANDR: Option<ffi::Xrandr> = ffi::Xrandr::open().ok();
+}
+
+thread_local! {
+  // Only touched on the main thread, where the window requests are handled.
+  static SAVED_CRTCS: RefCell<HashMap<WindowId, SavedCrtc>> = RefCell::new(HashMap::new());
+}
+
+/// A mode supported by the output of a monitor.
+pub(crate) struct OutputMode {
+  pub(crate) id: u64,
+  pub(crate) size: (u32, u32),
+  pub(crate) refresh_rate: u16,
+}
+
+/// The configuration of a CRTC before a window changed its mode.
+#[derive(Clone)]
+struct SavedCrtc {
+  crtc: ffi::RRCrtc,
+  mode: ffi::RRMode,
+  x: c_int,
+  y: c_int,
+  rotation: ffi::Rotation,
+  outputs: Vec<ffi::RROutput>,
+}
+
+/// The screen resources of the X display of GDK.
+struct ScreenResources {
+  xrandr: &'static ffi::Xrandr,
+  display: *mut ffi::Display,
+  resources: *mut ffi::XRRScreenResources,
+}
+
+impl ScreenResources {
+  fn get(display: &gdk::Display) -> Option<ScreenResources> {
+    if !display.backend().is_x11() {
+      return None;
+    }
+    let xrandr = XRANDR.as_ref()?;
+    unsafe {
+      let display = gdk_x11_sys::gdk_x11_display_get_xdisplay(display.as_ptr() as *mut _) as _;
+      let root = gdk_x11_sys::gdk_x11_get_default_root_xwindow();
+      let resources = (xrandr.XRRGetScreenResourcesCurrent)(display, root);
+      if resources.is_null() {
+        return None;
+      }
+      Some(ScreenResources {
+        xrandr,
+        display,
+        resources,
+      })
+    }
+  }
+
+  fn modes(&self) -> &[ffi::XRRModeInfo] {
+    unsafe {
+      let resources = &*self.resources;
+      if resources.modes.is_null() {
+        return &[];
+      }
+      slice::from_raw_parts(resources.modes, resources.nmode as usize)
+    }
+  }
+
+  /// Calls `f` with the info of the output of `monitor`.
+  fn with_output<R>(
+    &self,
+    monitor: &gdk::Monitor,
+    f: impl FnOnce(&ffi::XRROutputInfo) -> R,
+  ) -> Option<R> {
+    unsafe {
+      let output = gdk_x11_sys::gdk_x11_monitor_get_output(monitor.as_ptr());
+      let info = (self.xrandr.XRRGetOutputInfo)(self.display, self.resources, output);
+      if info.is_null() {
+        return None;
+      }
+      let result = f(&*info);
+      (self.xrandr.XRRFreeOutputInfo)(info);
+      Some(result)
+    }
+  }
+
+  fn set_crtc(&self, crtc: &SavedCrtc) -> bool {
+    let mut outputs = crtc.outputs.clone();
+    unsafe {
+      (self.xrandr.XRRSetCrtcConfig)(
+        self.display,
+        self.resources,
+        crtc.crtc,
+        (*self.resources).timestamp,
+        crtc.x,
+        crtc.y,
+        crtc.mode,
+        crtc.rotation,
+        outputs.as_mut_ptr(),
+        outputs.len() as c_int,
+      ) == 0
+    }
+  }
+}
+
+impl Drop for ScreenResources {
+  fn drop(&mut self) {
+    unsafe { (self.xrandr.XRRFreeScreenResources)(self.resources) };
+  }
+}
+
+/// Returns the modes of the output of `monitor`, or `None` if XRandR is unavailable.
+pub(crate) fn output_modes(monitor: &gdk::Monitor) -> Option<Vec<OutputMode>> {
+  let resources = ScreenResources::get(&monitor.display()?)?;
+  resources.with_output(monitor, |output| {
+    let ids = unsafe { slice::from_raw_parts(output.modes, output.nmode as usize) };
+    resources
+      .modes()
+      .iter()
+      .filter(|mode| ids.contains(&mode.id))
+      .map(|mode| OutputMode {
+        id: mode.id,
+        size: (mode.width, mode.height),
+        refresh_rate: refresh_rate(mode),
+      })
+      .collect()
+  })
+}
+
+fn refresh_rate(mode: &ffi::XRRModeInfo) -> u16 {
+  let mut lines = mode.vTotal as f64;
+  if mode.modeFlags & ffi::RR_DoubleScan as u64 != 0 {
+    lines *= 2.0;
+  }
+  if mode.modeFlags & ffi::RR_Interlace as u64 != 0 {
+    lines /= 2.0;
+  }
+  if mode.hTotal == 0 || lines == 0.0 {
+    return 0;
+  }
+  (mode.dotClock as f64 / (mode.hTotal as f64 * lines)).round() as u16
+}
+
+/// Switches the output of `monitor` to `mode` while `window_id` is fullscreen, restoring the mode
+/// the window may have set before.
+pub(crate) fn set_window_mode(window_id: WindowId, monitor: &gdk::Monitor, mode: u64) {
+  restore_window_mode(window_id);
+  let display = match monitor.display() {
+    Some(display) => display,
+    None => return,
+  };
+  let resources = match ScreenResources::get(&display) {
+    Some(resources) => resources,
+    None => return,
+  };
+
+  let saved = resources.with_output(monitor, |output| unsafe {
+    let info =
+      (resources.xrandr.XRRGetCrtcInfo)(resources.display, resources.resources, output.crtc);
+    if info.is_null() {
+      return None;
+    }
+    let crtc = &*info;
+    let saved = SavedCrtc {
+      crtc: output.crtc,
+      mode: crtc.mode,
+      x: crtc.x,
+      y: crtc.y,
+      rotation: crtc.rotation,
+      outputs: slice::from_raw_parts(crtc.outputs, crtc.noutput as usize).to_vec(),
+    };
+    (resources.xrandr.XRRFreeCrtcInfo)(info);
+    Some(saved)
+  });
+  let saved = match saved.flatten() {
+    Some(saved) if saved.mode != mode => saved,
+    _ => return,
+  };
+
+  // Modes bigger than the screen are refused, since the screen isn't resized.
+  if !resources.set_crtc(&SavedCrtc {
+    mode,
+    ..saved.clone()
+  }) {
+    log::warn!("Failed to switch the monitor to the video mode");
+    return;
+  }
+  SAVED_CRTCS.with(|saved_crtcs| saved_crtcs.borrow_mut().insert(window_id, saved));
+}
+
+/// Restores the mode that was replaced by the one of `window_id`, if any.
+pub(crate) fn restore_window_mode(window_id: WindowId) {
+  let saved = match SAVED_CRTCS.with(|saved_crtcs| saved_crtcs.borrow_mut().remove(&window_id)) {
+    Some(saved) => saved,
+    None => return,
+  };
+  let resources = match gdk::Display::default().and_then(|display| ScreenResources::get(&display)) {
+    Some(resources) => resources,
+    None => return,
+  };
+  if !resources.set_crtc(&saved) {
+    log::warn!("Failed to restore the video mode of the monitor");
+  }
+}
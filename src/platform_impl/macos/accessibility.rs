@@ -0,0 +1,93 @@
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The accessibility tree of a window, exposed by AccessKit, which subclasses the content view of
+//! the window to answer its accessibility requests.
+//!
+//! The adapter may only be used on the main thread.
+
+use std::sync::Mutex;
+
+use accesskit_macos::SubclassingAdapter;
+use cocoa::base::id;
+
+use crate::{
+  accesskit::{ActionHandler, ActionRequest, ActivationHandler, TreeUpdate},
+  event::{Event, WindowEvent},
+  platform_impl::platform::{app_state::AppState, event::EventWrapper, window::Id},
+  window::WindowId,
+};
+
+pub(crate) type AccessibilitySource = Box<dyn FnMut() -> TreeUpdate + Send>;
+
+/// The adapter of a window, if it was given a source.
+pub(crate) type Accessibility = Mutex<Option<SubclassingAdapter>>;
+
+struct Source(AccessibilitySource);
+
+impl ActivationHandler for Source {
+  fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+    Some((self.0)())
+  }
+}
+
+/// Reports the requested actions, which AccessKit handles on the main thread.
+struct ActionForwarder {
+  window_id: Id,
+}
+
+impl ActionHandler for ActionForwarder {
+  fn do_action(&mut self, request: ActionRequest) {
+    AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+      window_id: WindowId(self.window_id),
+      event: WindowEvent::AccessibilityActionRequested(request),
+    }));
+  }
+}
+
+/// # Safety
+///
+/// `ns_view` must be the content view of the window of `window_id`, and this must be called on
+/// the main thread.
+pub(crate) unsafe fn set_source(
+  accessibility: &Accessibility,
+  ns_view: id,
+  window_id: Id,
+  is_focused: bool,
+  source: AccessibilitySource,
+) {
+  let mut accessibility = accessibility.lock().unwrap();
+  // A view can only be subclassed by one adapter at a time.
+  *accessibility = None;
+  let mut adapter =
+    SubclassingAdapter::new(ns_view as _, Source(source), ActionForwarder { window_id });
+  let events = adapter.update_view_focus_state(is_focused);
+  *accessibility = Some(adapter);
+  drop(accessibility);
+  if let Some(events) = events {
+    events.raise();
+  }
+}
+
+/// Must be called on the main thread.
+pub(crate) fn update(accessibility: &Accessibility, update: impl FnOnce() -> TreeUpdate) {
+  let events = match &mut *accessibility.lock().unwrap() {
+    Some(adapter) => adapter.update_if_active(update),
+    None => None,
+  };
+  // The events are raised without the lock, as the notifications may query the tree right away.
+  if let Some(events) = events {
+    events.raise();
+  }
+}
+
+/// Must be called on the main thread.
+pub(crate) fn update_focus(accessibility: &Accessibility, is_focused: bool) {
+  let events = match &mut *accessibility.lock().unwrap() {
+    Some(adapter) => adapter.update_view_focus_state(is_focused),
+    None => None,
+  };
+  if let Some(events) = events {
+    events.raise();
+  }
+}
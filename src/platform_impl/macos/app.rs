@@ -2,7 +2,7 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::collections::VecDeque;
+use std::{cell::RefCell, collections::VecDeque, os::raw::c_void};
 
 use cocoa::{
   appkit::{self, NSEvent},
@@ -13,13 +13,38 @@ use objc::{
   runtime::{Class, Object, Sel},
 };
 
-use super::{app_state::AppState, event::EventWrapper, util, DEVICE_ID};
+use super::{app_state::AppState, device_events, event::EventWrapper, util, DEVICE_ID};
 use crate::event::{DeviceEvent, ElementState, Event};
 
 pub struct AppClass(pub *const Class);
 unsafe impl Send for AppClass {}
 unsafe impl Sync for AppClass {}
 
+thread_local! {
+  /// User hook called with every `NSEvent` before it is dispatched.
+  static EVENT_HOOK: RefCell<Option<Box<dyn FnMut(*mut c_void) -> bool>>> = RefCell::new(None);
+}
+
+pub fn set_event_hook(hook: Box<dyn FnMut(*mut c_void) -> bool>) {
+  EVENT_HOOK.with(|event_hook| *event_hook.borrow_mut() = Some(hook));
+}
+
+/// Calls the user hook, if any. Returns `true` if the event was consumed by it.
+fn call_event_hook(event: id) -> bool {
+  // Take the hook out while calling it, so that it can safely replace itself.
+  let hook = EVENT_HOOK.with(|event_hook| event_hook.borrow_mut().take());
+  match hook {
+    Some(mut hook) => {
+      let handled = hook(event as *mut c_void);
+      EVENT_HOOK.with(|event_hook| {
+        event_hook.borrow_mut().get_or_insert(hook);
+      });
+      handled
+    }
+    None => false,
+  }
+}
+
 lazy_static! {
   pub static ref APP_CLASS: AppClass = unsafe {
     let superclass = class!(NSApplication);
@@ -38,6 +63,10 @@ lazy_static! {
 // Overriding `sendEvent:` like this fixes that. (https://stackoverflow.com/a/15294196)
 // Fun fact: Firefox still has this bug! (https://bugzilla.mozilla.org/show_bug.cgi?id=1299553)
 extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
+  if call_event_hook(event) {
+    return;
+  }
+
   unsafe {
     // For posterity, there are some undocumented event types
     // (https://github.com/servo/cocoa-rs/issues/155)
@@ -53,14 +82,16 @@ extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
       let key_window: id = msg_send![this, keyWindow];
       let _: () = msg_send![key_window, sendEvent: event];
     } else {
-      maybe_dispatch_device_event(event);
+      if device_events::should_dispatch_app_event() {
+        maybe_dispatch_device_event(event);
+      }
       let superclass = util::superclass(this);
       let _: () = msg_send![super(this, superclass), sendEvent: event];
     }
   }
 }
 
-unsafe fn maybe_dispatch_device_event(event: id) {
+pub unsafe fn maybe_dispatch_device_event(event: id) {
   let event_type = event.eventType();
   match event_type {
     appkit::NSMouseMoved
@@ -2,20 +2,57 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{platform::macos::ActivationPolicy, platform_impl::platform::app_state::AppState};
+use crate::{
+  event::Event,
+  monitor::MonitorTracker,
+  platform::macos::ActivationPolicy,
+  platform_impl::platform::{
+    app_state::AppState,
+    event::{self, EventWrapper},
+    monitor, single_instance, util,
+  },
+  window::RGBA,
+};
 
-use cocoa::base::id;
+use cocoa::{
+  appkit::NSApp,
+  base::{id, nil},
+  foundation::NSArray,
+};
 use objc::{
   declare::ClassDecl,
-  runtime::{Class, Object, Sel},
+  runtime::{Class, Object, Sel, BOOL, NO},
 };
 use std::{
   cell::{RefCell, RefMut},
   os::raw::c_void,
 };
+use url::Url;
 
 static AUX_DELEGATE_STATE_NAME: &str = "auxState";
 
+/// Distributed notifications posted when the session is locked / unlocked, and when the
+/// screen saver starts / stops.
+const SESSION_NOTIFICATIONS: [(&str, bool); 4] = [
+  ("com.apple.screenIsLocked", true),
+  ("com.apple.screenIsUnlocked", false),
+  ("com.apple.screensaver.didstart", true),
+  ("com.apple.screensaver.didstop", false),
+];
+
+/// Distributed notification posted when the user selects another keyboard input source.
+const KEYBOARD_LAYOUT_NOTIFICATION: &str =
+  "com.apple.Carbon.TISNotifySelectedKeyboardInputSourceChanged";
+
+/// Notifications of the workspace posted when the system goes to sleep / wakes up.
+const POWER_NOTIFICATIONS: [(&str, bool); 2] = [
+  ("NSWorkspaceWillSleepNotification", true),
+  ("NSWorkspaceDidWakeNotification", false),
+];
+
+/// Notification posted when the system colors, including the accent color, change.
+const SYSTEM_COLORS_NOTIFICATION: &str = "NSSystemColorsDidChangeNotification";
+
 pub struct AuxDelegateState {
   /// We store this value in order to be able to defer setting the activation policy until
   /// after the app has finished launching. If the activation policy is set earlier, the
@@ -23,6 +60,23 @@ pub struct AuxDelegateState {
   pub activation_policy: ActivationPolicy,
 
   pub create_default_menu: bool,
+
+  /// Whether `Event::SessionLock` was the last session event sent, so that the screen saver
+  /// and the lock screen don't report the same transition twice.
+  pub session_locked: bool,
+
+  /// The retained menu returned by `applicationDockMenu:`, `nil` if there is none.
+  pub dock_menu: id,
+
+  /// The monitors last reported, to tell what changed when the screen parameters change.
+  pub monitors: MonitorTracker,
+
+  /// The keyboard layout last reported, since selecting an input method that keeps the same
+  /// layout also posts the notification.
+  pub keyboard_layout: Option<String>,
+
+  /// The accent color last reported, since the notification is posted for every system color.
+  pub accent_color: Option<RGBA>,
 }
 
 pub struct AppDelegateClass(pub *const Class);
@@ -45,6 +99,46 @@ lazy_static! {
       sel!(applicationWillTerminate:),
       application_will_terminate as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(applicationDockMenu:),
+      application_dock_menu as extern "C" fn(&Object, Sel, id) -> id,
+    );
+    decl.add_method(
+      sel!(applicationDidChangeScreenParameters:),
+      did_change_screen_parameters as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(sessionDidLock:),
+      session_did_lock as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(sessionDidUnlock:),
+      session_did_unlock as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(application:openURLs:),
+      application_open_urls as extern "C" fn(&Object, Sel, id, id),
+    );
+    decl.add_method(
+      sel!(newInstance:),
+      new_instance as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(systemWillSleep:),
+      system_will_sleep as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(systemDidWake:),
+      system_did_wake as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(keyboardLayoutDidChange:),
+      keyboard_layout_did_change as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(systemColorsDidChange:),
+      system_colors_did_change as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_ivar::<*mut c_void>(AUX_DELEGATE_STATE_NAME);
 
     AppDelegateClass(decl.register())
@@ -67,6 +161,11 @@ extern "C" fn new(class: &Class, _: Sel) -> id {
       Box::into_raw(Box::new(RefCell::new(AuxDelegateState {
         activation_policy: ActivationPolicy::Regular,
         create_default_menu: true,
+        session_locked: false,
+        dock_menu: nil,
+        monitors: MonitorTracker::new(monitor::available_monitors()),
+        keyboard_layout: event::keyboard_layout(),
+        accent_color: util::accent_color(),
       }))) as *mut c_void,
     );
     this
@@ -75,6 +174,16 @@ extern "C" fn new(class: &Class, _: Sel) -> id {
 
 extern "C" fn dealloc(this: &Object, _: Sel) {
   unsafe {
+    let notification_center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+    let _: () = msg_send![notification_center, removeObserver: this];
+    let notification_center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+    let _: () = msg_send![notification_center, removeObserver: this];
+
+    let dock_menu = get_aux_state_mut(this).dock_menu;
+    if dock_menu != nil {
+      let _: () = msg_send![dock_menu, release];
+    }
+
     let state_ptr: *mut c_void = *(this.get_ivar(AUX_DELEGATE_STATE_NAME));
     // As soon as the box is constructed it is immediately dropped, releasing the underlying
     // memory
@@ -85,6 +194,60 @@ extern "C" fn dealloc(this: &Object, _: Sel) {
 extern "C" fn did_finish_launching(this: &Object, _: Sel, _: id) {
   trace!("Triggered `applicationDidFinishLaunching`");
   AppState::launched(this);
+  unsafe {
+    let notification_center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+    for (name, lock) in SESSION_NOTIFICATIONS {
+      let name = util::ns_string_id_ref(name);
+      let selector = if lock {
+        sel!(sessionDidLock:)
+      } else {
+        sel!(sessionDidUnlock:)
+      };
+      let _: () = msg_send![
+        notification_center,
+        addObserver: this
+        selector: selector
+        name: *name
+        object: nil
+      ];
+    }
+    let name = util::ns_string_id_ref(KEYBOARD_LAYOUT_NOTIFICATION);
+    let _: () = msg_send![
+      notification_center,
+      addObserver: this
+      selector: sel!(keyboardLayoutDidChange:)
+      name: *name
+      object: nil
+    ];
+
+    let notification_center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+    let name = util::ns_string_id_ref(SYSTEM_COLORS_NOTIFICATION);
+    let _: () = msg_send![
+      notification_center,
+      addObserver: this
+      selector: sel!(systemColorsDidChange:)
+      name: *name
+      object: nil
+    ];
+
+    let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+    let notification_center: id = msg_send![workspace, notificationCenter];
+    for (name, sleep) in POWER_NOTIFICATIONS {
+      let name = util::ns_string_id_ref(name);
+      let selector = if sleep {
+        sel!(systemWillSleep:)
+      } else {
+        sel!(systemDidWake:)
+      };
+      let _: () = msg_send![
+        notification_center,
+        addObserver: this
+        selector: selector
+        name: *name
+        object: nil
+      ];
+    }
+  }
   trace!("Completed `applicationDidFinishLaunching`");
 }
 
@@ -93,3 +256,163 @@ extern "C" fn application_will_terminate(_: &Object, _: Sel, _: id) {
   AppState::exit();
   trace!("Completed `applicationWillTerminate`");
 }
+
+extern "C" fn application_open_urls(_: &Object, _: Sel, _: id, urls: id) {
+  trace!("Triggered `application:openURLs:`");
+  let urls = unsafe {
+    (0..NSArray::count(urls))
+      .filter_map(|i| {
+        let url: id = msg_send![NSArray::objectAtIndex(urls, i), absoluteString];
+        Url::parse(&util::ns_string_to_rust(url)).ok()
+      })
+      .collect::<Vec<_>>()
+  };
+  if !urls.is_empty() {
+    AppState::queue_event(EventWrapper::StaticEvent(Event::Opened { urls }));
+  }
+  trace!("Completed `application:openURLs:`");
+}
+
+extern "C" fn application_dock_menu(this: &Object, _: Sel, _: id) -> id {
+  unsafe { get_aux_state_mut(this).dock_menu }
+}
+
+/// Sets the menu shown in the Dock, if the application delegate is ours.
+pub fn set_dock_menu(menu: Option<id>) {
+  unsafe {
+    let delegate: id = msg_send![NSApp(), delegate];
+    if delegate == nil {
+      return;
+    }
+    let is_app_delegate: BOOL = msg_send![delegate, isKindOfClass: APP_DELEGATE_CLASS.0];
+    if is_app_delegate == NO {
+      return;
+    }
+
+    let menu = match menu {
+      Some(menu) => msg_send![menu, retain],
+      None => nil,
+    };
+    let old_menu = std::mem::replace(&mut get_aux_state_mut(&*delegate).dock_menu, menu);
+    if old_menu != nil {
+      let _: () = msg_send![old_menu, release];
+    }
+  }
+}
+
+/// Observes the notifications posted by the other instances of the application with their
+/// arguments, if the application delegate is ours.
+pub fn observe_new_instances(notification_name: &str) {
+  unsafe {
+    let delegate: id = msg_send![NSApp(), delegate];
+    if delegate == nil {
+      return;
+    }
+    let is_app_delegate: BOOL = msg_send![delegate, isKindOfClass: APP_DELEGATE_CLASS.0];
+    if is_app_delegate == NO {
+      return;
+    }
+
+    let notification_center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+    let name = util::ns_string_id_ref(notification_name);
+    let _: () = msg_send![
+      notification_center,
+      addObserver: delegate
+      selector: sel!(newInstance:)
+      name: *name
+      object: nil
+    ];
+  }
+}
+
+extern "C" fn new_instance(_: &Object, _: Sel, notification: id) {
+  trace!("Triggered `newInstance:`");
+  if let Some((args, cwd)) = unsafe { single_instance::decode_new_instance(notification) } {
+    AppState::queue_event(EventWrapper::StaticEvent(Event::NewInstance { args, cwd }));
+  }
+  trace!("Completed `newInstance:`");
+}
+
+extern "C" fn did_change_screen_parameters(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `applicationDidChangeScreenParameters`");
+  let events = unsafe { get_aux_state_mut(this) }
+    .monitors
+    .update(monitor::available_monitors());
+  for event in events {
+    AppState::queue_event(EventWrapper::StaticEvent(event));
+  }
+  trace!("Completed `applicationDidChangeScreenParameters`");
+}
+
+extern "C" fn keyboard_layout_did_change(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `keyboardLayoutDidChange`");
+  let layout = event::keyboard_layout();
+  let changed = {
+    let mut aux_state = unsafe { get_aux_state_mut(this) };
+    let changed = aux_state.keyboard_layout != layout;
+    aux_state.keyboard_layout = layout;
+    changed
+  };
+  if changed {
+    AppState::queue_event(EventWrapper::StaticEvent(Event::KeyboardLayoutChanged));
+  }
+  trace!("Completed `keyboardLayoutDidChange`");
+}
+
+extern "C" fn system_colors_did_change(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `systemColorsDidChange`");
+  let accent_color = util::accent_color();
+  let changed = {
+    let mut aux_state = unsafe { get_aux_state_mut(this) };
+    let changed = aux_state.accent_color != accent_color;
+    aux_state.accent_color = accent_color;
+    changed
+  };
+  if let (true, Some(accent_color)) = (changed, accent_color) {
+    AppState::queue_event(EventWrapper::StaticEvent(Event::AccentColorChanged(
+      accent_color,
+    )));
+  }
+  trace!("Completed `systemColorsDidChange`");
+}
+
+extern "C" fn session_did_lock(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `sessionDidLock`");
+  set_session_locked(this, true);
+  trace!("Completed `sessionDidLock`");
+}
+
+extern "C" fn session_did_unlock(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `sessionDidUnlock`");
+  set_session_locked(this, false);
+  trace!("Completed `sessionDidUnlock`");
+}
+
+extern "C" fn system_will_sleep(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `systemWillSleep`");
+  AppState::queue_event(EventWrapper::StaticEvent(Event::SystemSuspend));
+  trace!("Completed `systemWillSleep`");
+}
+
+extern "C" fn system_did_wake(_: &Object, _: Sel, _: id) {
+  trace!("Triggered `systemDidWake`");
+  AppState::queue_event(EventWrapper::StaticEvent(Event::SystemResume));
+  trace!("Completed `systemDidWake`");
+}
+
+fn set_session_locked(this: &Object, locked: bool) {
+  {
+    let mut aux_state = unsafe { get_aux_state_mut(this) };
+    if aux_state.session_locked == locked {
+      return;
+    }
+    aux_state.session_locked = locked;
+  }
+
+  let event = if locked {
+    Event::SessionLock
+  } else {
+    Event::SessionUnlock
+  };
+  AppState::queue_event(EventWrapper::StaticEvent(event));
+}
@@ -2,13 +2,41 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
+use std::{
+  cell::RefCell,
+  ffi::{c_void, CStr},
+  os::unix::ffi::OsStrExt,
+  path::{Path, PathBuf},
+  ptr,
+};
+
 use cocoa::{
-  appkit::NSPasteboardTypeString,
-  base::{id, nil, BOOL, YES},
-  foundation::{NSInteger, NSString},
+  appkit::{
+    NSPasteboardTypeHTML, NSPasteboardTypePNG, NSPasteboardTypeString, NSPasteboardTypeTIFF,
+  },
+  base::{id, nil, BOOL, NO, YES},
+  foundation::{NSArray, NSInteger, NSPoint, NSRect, NSSize, NSString, NSUInteger},
+};
+use core_foundation::{
+  base::TCFType,
+  date::CFDate,
+  runloop::{
+    kCFRunLoopCommonModes, CFRunLoop, CFRunLoopTimer, CFRunLoopTimerInvalidate, CFRunLoopTimerRef,
+  },
 };
 use objc::{class, msg_send, sel, sel_impl};
 
+use super::{app_state::AppState, event::EventWrapper};
+use crate::{clipboard::RgbaImage, event::Event};
+
+/// The pasteboard has no change notification, so it is polled at this interval, in seconds.
+const CLIPBOARD_POLL_INTERVAL: f64 = 0.5;
+
+/// `NSBitmapImageFileTypePNG`
+const PNG_FILE_TYPE: NSInteger = 4;
+/// `NSBitmapFormatAlphaNonpremultiplied`
+pub(crate) const ALPHA_NONPREMULTIPLIED_FORMAT: NSInteger = 1 << 1;
+
 #[derive(Debug, Clone, Default)]
 pub struct Clipboard;
 
@@ -28,16 +56,268 @@ impl Clipboard {
   }
 
   pub(crate) fn read_text(&self) -> Option<String> {
+    unsafe { read_string(NSPasteboardTypeString) }
+  }
+
+  pub(crate) fn write_html(&mut self, html: impl AsRef<str>, alt_text: impl AsRef<str>) {
+    unsafe {
+      let html = NSString::alloc(nil).init_str(html.as_ref());
+      let alt_text = NSString::alloc(nil).init_str(alt_text.as_ref());
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let _: NSInteger = msg_send![pasteboard, clearContents];
+      let html_result: BOOL = msg_send![pasteboard, setString: html forType: NSPasteboardTypeHTML];
+      let text_result: BOOL =
+        msg_send![pasteboard, setString: alt_text forType: NSPasteboardTypeString];
+      let _: () = msg_send![html, release];
+      let _: () = msg_send![alt_text, release];
+      if html_result != YES || text_result != YES {
+        #[cfg(debug_assertions)]
+        println!("failed to set clipboard");
+      }
+    }
+  }
+
+  pub(crate) fn read_html(&self) -> Option<String> {
+    unsafe { read_string(NSPasteboardTypeHTML) }
+  }
+
+  pub(crate) fn write_files(&mut self, paths: Vec<PathBuf>) {
     unsafe {
+      let urls: Vec<id> = paths
+        .iter()
+        .map(|path| {
+          let path = NSString::alloc(nil).init_str(&path.to_string_lossy());
+          let url: id = msg_send![class!(NSURL), fileURLWithPath: path];
+          let _: () = msg_send![path, release];
+          url
+        })
+        .collect();
+      let urls = NSArray::arrayWithObjects(nil, &urls);
+
       let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
-      let contents: id = msg_send![pasteboard, stringForType: NSPasteboardTypeString];
-      if contents.is_null() {
+      let _: NSInteger = msg_send![pasteboard, clearContents];
+      let result: BOOL = msg_send![pasteboard, writeObjects: urls];
+      if result != YES {
+        #[cfg(debug_assertions)]
+        println!("failed to set clipboard");
+      }
+    }
+  }
+
+  pub(crate) fn read_files(&self) -> Option<Vec<PathBuf>> {
+    unsafe {
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let classes = NSArray::arrayWithObject(nil, class!(NSURL) as *const _ as id);
+      let urls: id = msg_send![pasteboard, readObjectsForClasses: classes options: nil];
+      if urls == nil {
+        return None;
+      }
+
+      let mut paths = Vec::new();
+      for i in 0..urls.count() {
+        let url = urls.objectAtIndex(i as NSUInteger);
+        let is_file_url: BOOL = msg_send![url, isFileURL];
+        if is_file_url != YES {
+          continue;
+        }
+        let path: *const std::os::raw::c_char = msg_send![url, fileSystemRepresentation];
+        if !path.is_null() {
+          let path = Path::new(std::ffi::OsStr::from_bytes(CStr::from_ptr(path).to_bytes()));
+          paths.push(path.to_path_buf());
+        }
+      }
+      if paths.is_empty() {
         None
       } else {
-        let slice = std::slice::from_raw_parts(contents.UTF8String() as *const _, contents.len());
-        let result = std::str::from_utf8_unchecked(slice);
-        Some(result.to_string())
+        Some(paths)
       }
     }
   }
+
+  pub(crate) fn write_image(&mut self, image: &RgbaImage) {
+    if image.width() == 0 || image.height() == 0 {
+      return;
+    }
+    unsafe {
+      let rep = new_rgba_image_rep(
+        image.width() as NSInteger,
+        image.height() as NSInteger,
+        ALPHA_NONPREMULTIPLIED_FORMAT,
+      );
+      let bitmap_data: *mut u8 = msg_send![rep, bitmapData];
+      ptr::copy_nonoverlapping(image.rgba().as_ptr(), bitmap_data, image.rgba().len());
+
+      let properties: id = msg_send![class!(NSDictionary), dictionary];
+      let png: id = msg_send![rep, representationUsingType: PNG_FILE_TYPE properties: properties];
+      let tiff: id = msg_send![rep, TIFFRepresentation];
+      let _: () = msg_send![rep, release];
+
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let _: NSInteger = msg_send![pasteboard, clearContents];
+      let png_result: BOOL = msg_send![pasteboard, setData: png forType: NSPasteboardTypePNG];
+      let tiff_result: BOOL = msg_send![pasteboard, setData: tiff forType: NSPasteboardTypeTIFF];
+      if png_result != YES || tiff_result != YES {
+        #[cfg(debug_assertions)]
+        println!("failed to set clipboard");
+      }
+    }
+  }
+
+  pub(crate) fn read_image(&self) -> Option<RgbaImage> {
+    unsafe {
+      let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+      let mut data: id = msg_send![pasteboard, dataForType: NSPasteboardTypePNG];
+      if data == nil {
+        data = msg_send![pasteboard, dataForType: NSPasteboardTypeTIFF];
+      }
+      if data == nil {
+        return None;
+      }
+      let source: id = msg_send![class!(NSBitmapImageRep), imageRepWithData: data];
+      if source == nil {
+        return None;
+      }
+      let width: NSInteger = msg_send![source, pixelsWide];
+      let height: NSInteger = msg_send![source, pixelsHigh];
+      if width <= 0 || height <= 0 {
+        return None;
+      }
+
+      // Draw the image into a bitmap of known layout, whatever its own format is. Graphics
+      // contexts can only draw into bitmaps with premultiplied alpha.
+      let target = new_rgba_image_rep(width, height, 0);
+      let context: id =
+        msg_send![class!(NSGraphicsContext), graphicsContextWithBitmapImageRep: target];
+      let _: () = msg_send![class!(NSGraphicsContext), saveGraphicsState];
+      let _: () = msg_send![class!(NSGraphicsContext), setCurrentContext: context];
+      let rect = NSRect::new(
+        NSPoint::new(0., 0.),
+        NSSize::new(width as f64, height as f64),
+      );
+      let _: BOOL = msg_send![source, drawInRect: rect];
+      let _: () = msg_send![class!(NSGraphicsContext), restoreGraphicsState];
+
+      let bitmap_data: *const u8 = msg_send![target, bitmapData];
+      let bytes_per_row: NSInteger = msg_send![target, bytesPerRow];
+      let (width, height) = (width as usize, height as usize);
+      let mut rgba = Vec::with_capacity(width * height * 4);
+      for y in 0..height {
+        let row =
+          std::slice::from_raw_parts(bitmap_data.add(y * bytes_per_row as usize), width * 4);
+        for pixel in row.chunks_exact(4) {
+          let alpha = pixel[3];
+          let unpremultiply = |c: u8| match alpha {
+            0 => 0,
+            _ => ((c as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8,
+          };
+          rgba.extend_from_slice(&[
+            unpremultiply(pixel[0]),
+            unpremultiply(pixel[1]),
+            unpremultiply(pixel[2]),
+            alpha,
+          ]);
+        }
+      }
+      let _: () = msg_send![target, release];
+
+      RgbaImage::from_rgba(rgba, width as u32, height as u32).ok()
+    }
+  }
+}
+
+struct ClipboardListener {
+  timer: CFRunLoopTimer,
+  change_count: NSInteger,
+}
+
+thread_local! {
+  // Only touched on the main thread, which is also where the timer fires.
+  static CLIPBOARD_LISTENER: RefCell<Option<ClipboardListener>> = RefCell::new(None);
+}
+
+/// Starts or stops polling the pasteboard for changes.
+///
+/// Must be called on the main thread.
+pub(crate) fn listen_clipboard(listen: bool) {
+  CLIPBOARD_LISTENER.with(|cell| {
+    let mut listener = cell.borrow_mut();
+    match (listen, listener.take()) {
+      (true, Some(current)) => *listener = Some(current),
+      (true, None) => {
+        let timer = CFRunLoopTimer::new(
+          CFDate::now().abs_time() + CLIPBOARD_POLL_INTERVAL,
+          CLIPBOARD_POLL_INTERVAL,
+          0,
+          0,
+          poll_clipboard,
+          ptr::null_mut(),
+        );
+        unsafe { CFRunLoop::get_main().add_timer(&timer, kCFRunLoopCommonModes) };
+        *listener = Some(ClipboardListener {
+          timer,
+          change_count: unsafe { pasteboard_change_count() },
+        });
+      }
+      (false, Some(current)) => unsafe {
+        CFRunLoopTimerInvalidate(current.timer.as_concrete_TypeRef())
+      },
+      (false, None) => (),
+    }
+  });
+}
+
+extern "C" fn poll_clipboard(_timer: CFRunLoopTimerRef, _info: *mut c_void) {
+  let change_count = unsafe { pasteboard_change_count() };
+  let changed = CLIPBOARD_LISTENER.with(|cell| match cell.borrow_mut().as_mut() {
+    Some(listener) if listener.change_count != change_count => {
+      listener.change_count = change_count;
+      true
+    }
+    _ => false,
+  });
+  if changed {
+    AppState::queue_event(EventWrapper::StaticEvent(Event::ClipboardUpdated));
+  }
+}
+
+unsafe fn pasteboard_change_count() -> NSInteger {
+  let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+  msg_send![pasteboard, changeCount]
+}
+
+unsafe fn read_string(pasteboard_type: id) -> Option<String> {
+  let pasteboard: id = msg_send![class!(NSPasteboard), generalPasteboard];
+  let contents: id = msg_send![pasteboard, stringForType: pasteboard_type];
+  if contents.is_null() {
+    None
+  } else {
+    let slice = std::slice::from_raw_parts(contents.UTF8String() as *const _, contents.len());
+    let result = std::str::from_utf8_unchecked(slice);
+    Some(result.to_string())
+  }
+}
+
+/// Allocates an 8 bits per sample RGBA bitmap owning its pixels.
+pub(crate) unsafe fn new_rgba_image_rep(
+  width: NSInteger,
+  height: NSInteger,
+  bitmap_format: NSInteger,
+) -> id {
+  let color_space = NSString::alloc(nil).init_str("NSDeviceRGBColorSpace");
+  let rep: id = msg_send![class!(NSBitmapImageRep), alloc];
+  let rep: id = msg_send![rep,
+    initWithBitmapDataPlanes: ptr::null_mut::<*mut u8>()
+    pixelsWide: width
+    pixelsHigh: height
+    bitsPerSample: 8 as NSInteger
+    samplesPerPixel: 4 as NSInteger
+    hasAlpha: YES
+    isPlanar: NO
+    colorSpaceName: color_space
+    bitmapFormat: bitmap_format
+    bytesPerRow: width * 4
+    bitsPerPixel: 32 as NSInteger
+  ];
+  let _: () = msg_send![color_space, release];
+  rep
 }
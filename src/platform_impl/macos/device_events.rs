@@ -0,0 +1,178 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The device event filter. Device events are read from the mouse events sent to the app while it
+//! is active, and from a listen-only event tap while it isn't and the filter lets them through.
+
+use std::{cell::RefCell, collections::HashMap, ffi::c_void, ptr, sync::Mutex};
+
+use cocoa::{
+  appkit::NSApp,
+  base::{id, nil, BOOL, NO},
+};
+use core_foundation::{
+  base::TCFType,
+  mach_port::{CFMachPort, CFMachPortInvalidate},
+  runloop::{kCFRunLoopCommonModes, CFRunLoop, CFRunLoopSource},
+};
+use core_graphics::{
+  event::{CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType},
+  sys::CGEventRef,
+};
+
+use super::{
+  app, ffi,
+  window::{get_window_id, Id},
+};
+use crate::event_loop::DeviceEventFilter;
+
+#[derive(Default)]
+struct Filters {
+  filter: DeviceEventFilter,
+  /// Overrides of `filter` while a window is focused.
+  window_filters: HashMap<Id, DeviceEventFilter>,
+}
+
+lazy_static! {
+  static ref FILTERS: Mutex<Filters> = Default::default();
+}
+
+thread_local! {
+  // Only touched on the main thread, which is also where the tap callback runs.
+  static TAP: RefCell<Option<(CFMachPort, CFRunLoopSource)>> = RefCell::new(None);
+}
+
+/// Sets the filter of the app, installing the event tap while it is `Never`.
+///
+/// Must be called on the main thread.
+pub unsafe fn set_filter(filter: DeviceEventFilter) {
+  FILTERS.lock().unwrap().filter = filter;
+  TAP.with(|tap| {
+    let mut tap = tap.borrow_mut();
+    match (filter, tap.is_some()) {
+      (DeviceEventFilter::Never, false) => *tap = install(),
+      (DeviceEventFilter::Never, true) | (_, false) => (),
+      (_, true) => {
+        if let Some((tap, source)) = tap.take() {
+          ffi::CGEventTapEnable(tap.as_concrete_TypeRef(), false);
+          CFRunLoop::get_main().remove_source(&source, kCFRunLoopCommonModes);
+          CFMachPortInvalidate(tap.as_concrete_TypeRef());
+        }
+      }
+    }
+  });
+}
+
+/// Sets or removes the override of the app filter while `window_id` is focused.
+pub fn set_window_filter(window_id: Id, filter: Option<DeviceEventFilter>) {
+  let mut filters = FILTERS.lock().unwrap();
+  match filter {
+    Some(filter) => filters.window_filters.insert(window_id, filter),
+    None => filters.window_filters.remove(&window_id),
+  };
+}
+
+/// Whether the device events of an event sent to the app should be dispatched.
+///
+/// Must be called on the main thread.
+pub unsafe fn should_dispatch_app_event() -> bool {
+  let app = NSApp();
+  let is_active: BOOL = msg_send![app, isActive];
+  if is_active == NO {
+    // The event tap reports them, if the filter allows it.
+    return false;
+  }
+  let key_window: id = msg_send![app, keyWindow];
+  let filters = FILTERS.lock().unwrap();
+  let filter = match key_window {
+    key_window if key_window == nil => None,
+    key_window => filters
+      .window_filters
+      .get(&get_window_id(key_window))
+      .copied(),
+  }
+  .unwrap_or(filters.filter);
+  match filter {
+    DeviceEventFilter::Always => false,
+    DeviceEventFilter::Unfocused => key_window != nil,
+    DeviceEventFilter::Never => true,
+  }
+}
+
+unsafe fn install() -> Option<(CFMachPort, CFRunLoopSource)> {
+  let events_of_interest = [
+    CGEventType::LeftMouseDown,
+    CGEventType::LeftMouseUp,
+    CGEventType::RightMouseDown,
+    CGEventType::RightMouseUp,
+    CGEventType::MouseMoved,
+    CGEventType::LeftMouseDragged,
+    CGEventType::RightMouseDragged,
+    CGEventType::OtherMouseDown,
+    CGEventType::OtherMouseUp,
+    CGEventType::OtherMouseDragged,
+  ]
+  .iter()
+  .fold(0u64, |mask, event_type| mask | 1 << *event_type as u64);
+
+  // Listening to mouse events doesn't need any permission, unlike keyboard events.
+  let tap_ref = ffi::CGEventTapCreate(
+    CGEventTapLocation::Session,
+    CGEventTapPlacement::TailAppendEventTap,
+    CGEventTapOptions::ListenOnly,
+    events_of_interest,
+    tap_callback,
+    ptr::null_mut(),
+  );
+  if tap_ref.is_null() {
+    warn!("Failed to create the device event tap");
+    return None;
+  }
+
+  let tap = CFMachPort::wrap_under_create_rule(tap_ref);
+  let source = match tap.create_runloop_source(0) {
+    Ok(source) => source,
+    Err(_) => {
+      warn!("Failed to create a run loop source for the device event tap");
+      CFMachPortInvalidate(tap.as_concrete_TypeRef());
+      return None;
+    }
+  };
+  CFRunLoop::get_main().add_source(&source, kCFRunLoopCommonModes);
+  ffi::CGEventTapEnable(tap.as_concrete_TypeRef(), true);
+
+  Some((tap, source))
+}
+
+extern "C" fn tap_callback(
+  _proxy: *const c_void,
+  event_type: u32,
+  event: CGEventRef,
+  _user_info: *mut c_void,
+) -> CGEventRef {
+  // The system disables taps that take too long, turn ours back on.
+  if event_type == CGEventType::TapDisabledByTimeout as u32
+    || event_type == CGEventType::TapDisabledByUserInput as u32
+  {
+    TAP.with(|tap| {
+      if let Some((tap, _)) = &*tap.borrow() {
+        unsafe { ffi::CGEventTapEnable(tap.as_concrete_TypeRef(), true) };
+      }
+    });
+    return event;
+  }
+
+  unsafe {
+    // While the app is active, the same events are sent to it.
+    let is_active: BOOL = msg_send![NSApp(), isActive];
+    if is_active != NO {
+      return event;
+    }
+    let ns_event: id = msg_send![class!(NSEvent), eventWithCGEvent: event];
+    if ns_event != nil {
+      app::maybe_dispatch_device_event(ns_event);
+    }
+  }
+  event
+}
@@ -0,0 +1,94 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Redraws paced by a `CVDisplayLink`, which fires once per refresh of the active displays. The
+//! display link only runs while redraws are pending.
+
+use std::{ffi::c_void, ptr, sync::Mutex};
+
+use dispatch::Queue;
+
+use super::{app_state::AppState, ffi};
+use crate::window::WindowId;
+
+struct DisplayLink {
+  link: ffi::CVDisplayLinkRef,
+  /// Whether the display link is running, or about to be started.
+  active: bool,
+  pending: Vec<WindowId>,
+}
+
+unsafe impl Send for DisplayLink {}
+
+lazy_static! {
+  static ref DISPLAY_LINK: Mutex<DisplayLink> = Mutex::new(DisplayLink {
+    link: ptr::null_mut(),
+    active: false,
+    pending: Vec::new(),
+  });
+}
+
+/// Redraws `window_id` on the next refresh of the displays.
+pub fn request_redraw(window_id: WindowId) {
+  let mut display_link = DISPLAY_LINK.lock().unwrap();
+  if !display_link.pending.contains(&window_id) {
+    display_link.pending.push(window_id);
+  }
+  if !display_link.active {
+    display_link.active = true;
+    // Starting and stopping both happen on the main thread, so that they can't interleave.
+    Queue::main().exec_async(start);
+  }
+}
+
+fn start() {
+  let mut display_link = DISPLAY_LINK.lock().unwrap();
+  unsafe {
+    if display_link.link.is_null() {
+      let mut link = ptr::null_mut();
+      if ffi::CVDisplayLinkCreateWithActiveCGDisplays(&mut link) != ffi::kCVReturnSuccess {
+        warn!("Failed to create a display link, redrawing right away");
+        display_link.active = false;
+        for window_id in display_link.pending.drain(..) {
+          AppState::queue_redraw(window_id);
+        }
+        return;
+      }
+      ffi::CVDisplayLinkSetOutputCallback(link, output_callback, ptr::null_mut());
+      display_link.link = link;
+    }
+    ffi::CVDisplayLinkStart(display_link.link);
+  }
+}
+
+fn stop() {
+  let link = {
+    let mut display_link = DISPLAY_LINK.lock().unwrap();
+    if !display_link.active || !display_link.pending.is_empty() {
+      return;
+    }
+    display_link.active = false;
+    display_link.link
+  };
+  // Stopping waits for the callback to return, which needs the lock.
+  unsafe { ffi::CVDisplayLinkStop(link) };
+}
+
+extern "C" fn output_callback(
+  _display_link: ffi::CVDisplayLinkRef,
+  _now: *const c_void,
+  _output_time: *const c_void,
+  _flags_in: ffi::CVOptionFlags,
+  _flags_out: *mut ffi::CVOptionFlags,
+  _context: *mut c_void,
+) -> ffi::CVReturn {
+  let mut display_link = DISPLAY_LINK.lock().unwrap();
+  if display_link.pending.is_empty() {
+    Queue::main().exec_async(stop);
+  }
+  for window_id in display_link.pending.drain(..) {
+    AppState::queue_redraw(window_id);
+  }
+  ffi::kCVReturnSuccess
+}
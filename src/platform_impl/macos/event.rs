@@ -2,14 +2,25 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::HashSet, ffi::c_void, os::raw::c_ushort, sync::Mutex};
+use std::{
+  collections::HashSet,
+  ffi::c_void,
+  os::raw::c_ushort,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
 
 use cocoa::{
   appkit::{NSEvent, NSEventModifierFlags},
   base::id,
+  foundation::NSTimeInterval,
 };
 
-use core_foundation::{base::CFRelease, data::CFDataGetBytePtr};
+use core_foundation::{
+  base::{CFRelease, TCFType},
+  data::CFDataGetBytePtr,
+  string::{CFString, CFStringRef},
+};
 
 use crate::{
   dpi::LogicalSize,
@@ -115,6 +126,21 @@ pub fn get_modifierless_char(scancode: u16) -> Key<'static> {
   Key::Character(insert_or_get_key_str(chars))
 }
 
+/// Returns the identifier of the current keyboard layout, e.g. `com.apple.keylayout.US`.
+pub fn keyboard_layout() -> Option<String> {
+  unsafe {
+    let input_source = ffi::TISCopyCurrentKeyboardLayoutInputSource();
+    if input_source.is_null() {
+      return None;
+    }
+    let id =
+      ffi::TISGetInputSourceProperty(input_source, ffi::kTISPropertyInputSourceID) as CFStringRef;
+    let id = (!id.is_null()).then(|| CFString::wrap_under_get_rule(id).to_string());
+    CFRelease(input_source as *mut c_void);
+    id
+  }
+}
+
 fn get_logical_key_char(ns_event: id, modifierless_chars: &str) -> Key<'static> {
   let characters: id = unsafe { msg_send![ns_event, charactersIgnoringModifiers] };
   let string = unsafe { ns_string_to_rust(characters) };
@@ -192,6 +218,7 @@ pub fn create_key_event(
     repeat: is_repeat,
     state,
     text,
+    timestamp: event_timestamp(ns_event),
     platform_specific: KeyEventExtra {
       text_with_all_modifiers,
       key_without_modifiers,
@@ -330,6 +357,21 @@ pub fn event_mods(event: id) -> ModifiersState {
   m
 }
 
+/// Converts the timestamp of an event, in seconds since the system started, to an `Instant`.
+pub fn event_timestamp(event: id) -> Instant {
+  let now = Instant::now();
+  let age = unsafe {
+    let timestamp: NSTimeInterval = msg_send![event, timestamp];
+    let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+    let uptime: NSTimeInterval = msg_send![process_info, systemUptime];
+    uptime - timestamp
+  };
+  if !age.is_finite() || age <= 0.0 {
+    return now;
+  }
+  now.checked_sub(Duration::from_secs_f64(age)).unwrap_or(now)
+}
+
 pub fn get_scancode(event: cocoa::base::id) -> c_ushort {
   // In AppKit, `keyCode` refers to the position (scancode) of a key rather than its character,
   // and there is no easy way to navtively retrieve the layout-dependent character.
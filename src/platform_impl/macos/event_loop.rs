@@ -19,22 +19,39 @@ use cocoa::{
   base::{id, nil, BOOL, NO, YES},
   foundation::{NSAutoreleasePool, NSInteger, NSPoint, NSTimeInterval},
 };
+use core_graphics::{
+  event::{CGEventFlags, CGKeyCode},
+  event_source::CGEventSourceStateID,
+};
 use crossbeam_channel::{self as channel, Receiver, Sender};
+use dispatch::Queue;
+use objc::runtime::Class;
 use raw_window_handle::{AppKitDisplayHandle, RawDisplayHandle};
 use scopeguard::defer;
 
 use crate::{
+  dpi::{LogicalPosition, PhysicalPosition},
+  error::ExternalError,
   event::Event,
-  event_loop::{ControlFlow, EventLoopClosed, EventLoopWindowTarget as RootWindowTarget},
+  event_loop::{
+    ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopTask,
+    EventLoopWindowTarget as RootWindowTarget,
+  },
+  keyboard::{KeyCode, ModifiersState},
   monitor::MonitorHandle as RootMonitorHandle,
+  platform::macos::ActivationPolicy,
   platform_impl::platform::{
-    app::APP_CLASS,
-    app_delegate::APP_DELEGATE_CLASS,
+    app::{self, APP_CLASS},
+    app_delegate::{get_aux_state_mut, APP_DELEGATE_CLASS},
     app_state::AppState,
+    clipboard, device_events, event, ffi,
     monitor::{self, MonitorHandle},
     observer::*,
-    util::IdRef,
+    single_instance,
+    util::{self, IdRef},
+    window::get_window_id,
   },
+  window::{WindowId as RootWindowId, RGBA},
 };
 
 #[derive(Default)]
@@ -90,6 +107,108 @@ impl<T: 'static> EventLoopWindowTarget<T> {
     Some(RootMonitorHandle { inner: monitor })
   }
 
+  #[inline]
+  pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, ExternalError> {
+    let (x, y) = monitor::cursor_location();
+    let scale_factor =
+      monitor::monitor_from_point(x, y).map_or(1.0, |monitor| monitor.scale_factor());
+    Ok(LogicalPosition::new(x, y).to_physical(scale_factor))
+  }
+
+  pub fn modifiers_state(&self) -> ModifiersState {
+    let flags = CGEventFlags::from_bits_truncate(unsafe {
+      ffi::CGEventSourceFlagsState(CGEventSourceStateID::CombinedSessionState)
+    });
+    let mut mods = ModifiersState::empty();
+    mods.set(
+      ModifiersState::SHIFT,
+      flags.contains(CGEventFlags::CGEventFlagShift),
+    );
+    mods.set(
+      ModifiersState::CONTROL,
+      flags.contains(CGEventFlags::CGEventFlagControl),
+    );
+    mods.set(
+      ModifiersState::ALT,
+      flags.contains(CGEventFlags::CGEventFlagAlternate),
+    );
+    mods.set(
+      ModifiersState::SUPER,
+      flags.contains(CGEventFlags::CGEventFlagCommand),
+    );
+    mods
+  }
+
+  pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+    key.to_scancode().map_or(false, |keycode| unsafe {
+      ffi::CGEventSourceKeyState(
+        CGEventSourceStateID::CombinedSessionState,
+        keycode as CGKeyCode,
+      )
+    })
+  }
+
+  pub fn focused_window(&self) -> Option<RootWindowId> {
+    unsafe {
+      // `keyWindow` is `nil` while the app isn't active.
+      let key_window: id = msg_send![NSApp(), keyWindow];
+      if key_window == nil {
+        return None;
+      }
+      // The window classes are only registered when the first window of their kind is created.
+      let is_tao_window = ["TaoWindow", "TaoPanel", "TaoNonactivatingPanel"]
+        .iter()
+        .filter_map(|name| Class::get(name))
+        .any(|class| {
+          let is_kind: BOOL = msg_send![key_window, isKindOfClass: class];
+          is_kind == YES
+        });
+      if is_tao_window {
+        Some(RootWindowId(get_window_id(key_window)))
+      } else {
+        None
+      }
+    }
+  }
+
+  #[inline]
+  pub fn activate_app(&self, ignore_other_apps: bool) {
+    unsafe {
+      let ignore_other_apps = if ignore_other_apps { YES } else { NO };
+      let _: () = msg_send![NSApp(), activateIgnoringOtherApps: ignore_other_apps];
+    }
+  }
+
+  #[inline]
+  pub fn keyboard_layout(&self) -> Option<String> {
+    event::keyboard_layout()
+  }
+
+  #[inline]
+  pub fn accent_color(&self) -> Option<RGBA> {
+    util::accent_color()
+  }
+
+  #[inline]
+  pub fn listen_clipboard(&self, listen: bool) {
+    clipboard::listen_clipboard(listen)
+  }
+
+  #[inline]
+  pub fn enable_single_instance(&self, identifier: &str) -> bool {
+    single_instance::enable(identifier)
+  }
+
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    unsafe { device_events::set_filter(filter) }
+  }
+
+  #[inline]
+  pub fn is_compositor_active(&self) -> bool {
+    true
+  }
+
   #[inline]
   pub fn raw_display_handle(&self) -> RawDisplayHandle {
     RawDisplayHandle::AppKit(AppKitDisplayHandle::empty())
@@ -111,8 +230,23 @@ pub struct EventLoop<T: 'static> {
   _callback: Option<Rc<RefCell<dyn FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow)>>>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlatformSpecificEventLoopAttributes {
+  pub(crate) activation_policy: ActivationPolicy,
+  pub(crate) default_menu: bool,
+}
+
+impl Default for PlatformSpecificEventLoopAttributes {
+  fn default() -> Self {
+    Self {
+      activation_policy: Default::default(),
+      default_menu: true,
+    }
+  }
+}
+
 impl<T> EventLoop<T> {
-  pub fn new() -> Self {
+  pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> Self {
     let delegate = unsafe {
       let is_main_thread: BOOL = msg_send!(class!(NSThread), isMainThread);
       if is_main_thread == NO {
@@ -129,6 +263,13 @@ impl<T> EventLoop<T> {
       let pool = NSAutoreleasePool::new(nil);
       let _: () = msg_send![app, setDelegate:*delegate];
       let _: () = msg_send![pool, drain];
+
+      {
+        let mut aux = get_aux_state_mut(&**delegate);
+        aux.activation_policy = attributes.activation_policy;
+        aux.create_default_menu = attributes.default_menu;
+      }
+
       delegate
     };
     let panic_info: Rc<PanicInfo> = Default::default();
@@ -138,6 +279,7 @@ impl<T> EventLoop<T> {
       window_target: Rc::new(RootWindowTarget {
         p: Default::default(),
         _marker: PhantomData,
+        coalesce_motion_events: Default::default(),
       }),
       panic_info,
       _callback: None,
@@ -148,6 +290,13 @@ impl<T> EventLoop<T> {
     &self.window_target
   }
 
+  pub fn set_event_hook<F>(&mut self, hook: F)
+  where
+    F: FnMut(*mut c_void) -> bool + 'static,
+  {
+    app::set_event_hook(Box::new(hook));
+  }
+
   pub fn run<F>(mut self, callback: F) -> !
   where
     F: 'static + FnMut(Event<'_, T>, &RootWindowTarget<T>, &mut ControlFlow),
@@ -307,4 +456,19 @@ impl<T> Proxy<T> {
     }
     Ok(())
   }
+
+  pub fn send_task(&self, task: EventLoopTask<T>)
+  where
+    T: 'static,
+  {
+    Queue::main().exec_async(move || {
+      // Window targets don't hold anything windows need on macOS, so a new one will do.
+      let window_target = RootWindowTarget {
+        p: EventLoopWindowTarget::default(),
+        _marker: PhantomData,
+        coalesce_motion_events: Default::default(),
+      };
+      task(&window_target);
+    });
+  }
 }
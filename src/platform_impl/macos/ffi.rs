@@ -17,6 +17,7 @@ use cocoa::{
   base::id,
   foundation::{NSInteger, NSUInteger},
 };
+use core_foundation::mach_port::CFMachPortRef;
 use core_foundation::{
   array::CFArrayRef, data::CFDataRef, dictionary::CFDictionaryRef, string::CFStringRef,
   uuid::CFUUIDRef,
@@ -24,6 +25,9 @@ use core_foundation::{
 use core_graphics::{
   base::CGError,
   display::{CGDirectDisplayID, CGDisplayConfigRef},
+  event::{CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGKeyCode},
+  event_source::CGEventSourceStateID,
+  sys::CGEventRef,
 };
 pub const NSNotFound: NSInteger = NSInteger::max_value();
 
@@ -232,14 +236,34 @@ extern "C" {
     display: CGDirectDisplayID,
     options: CFDictionaryRef,
   ) -> CFArrayRef;
+  pub fn CGDisplayCopyDisplayMode(display: CGDirectDisplayID) -> CGDisplayModeRef;
   pub fn CGDisplayModeGetPixelWidth(mode: CGDisplayModeRef) -> usize;
   pub fn CGDisplayModeGetPixelHeight(mode: CGDisplayModeRef) -> usize;
   pub fn CGDisplayModeGetRefreshRate(mode: CGDisplayModeRef) -> f64;
   pub fn CGDisplayModeCopyPixelEncoding(mode: CGDisplayModeRef) -> CFStringRef;
   pub fn CGDisplayModeRetain(mode: CGDisplayModeRef);
   pub fn CGDisplayModeRelease(mode: CGDisplayModeRef);
+  pub fn CGEventTapCreate(
+    tap: CGEventTapLocation,
+    place: CGEventTapPlacement,
+    options: CGEventTapOptions,
+    events_of_interest: u64,
+    callback: CGEventTapCallBack,
+    user_info: *mut c_void,
+  ) -> CFMachPortRef;
+  pub fn CGEventTapEnable(tap: CFMachPortRef, enable: bool);
+  pub fn CGEventGetFlags(event: CGEventRef) -> u64;
+  pub fn CGEventSourceKeyState(state_id: CGEventSourceStateID, key: CGKeyCode) -> bool;
+  pub fn CGEventSourceFlagsState(state_id: CGEventSourceStateID) -> u64;
 }
 
+pub type CGEventTapCallBack = extern "C" fn(
+  proxy: *const c_void,
+  event_type: u32,
+  event: CGEventRef,
+  user_info: *mut c_void,
+) -> CGEventRef;
+
 #[repr(transparent)]
 pub struct TISInputSource(std::ffi::c_void);
 pub type TISInputSourceRef = *mut TISInputSource;
@@ -260,6 +284,7 @@ pub const kUCKeyTranslateNoDeadKeysMask: OptionBits = 1;
 #[link(name = "Carbon", kind = "framework")]
 extern "C" {
   pub static kTISPropertyUnicodeKeyLayoutData: CFStringRef;
+  pub static kTISPropertyInputSourceID: CFStringRef;
 
   #[allow(non_snake_case)]
   pub fn TISGetInputSourceProperty(
@@ -313,8 +338,20 @@ mod core_video {
   // CVDisplayLink.h
 
   pub type CVDisplayLinkRef = *mut c_void;
+  pub type CVOptionFlags = u64; // uint64_t
+  pub type CVDisplayLinkOutputCallback = extern "C" fn(
+    displayLink: CVDisplayLinkRef,
+    inNow: *const c_void,
+    inOutputTime: *const c_void,
+    flagsIn: CVOptionFlags,
+    flagsOut: *mut CVOptionFlags,
+    displayLinkContext: *mut c_void,
+  ) -> CVReturn;
 
   extern "C" {
+    pub fn CVDisplayLinkCreateWithActiveCGDisplays(
+      displayLinkOut: *mut CVDisplayLinkRef,
+    ) -> CVReturn;
     pub fn CVDisplayLinkCreateWithCGDisplay(
       displayID: CGDirectDisplayID,
       displayLinkOut: *mut CVDisplayLinkRef,
@@ -322,6 +359,13 @@ mod core_video {
     pub fn CVDisplayLinkGetNominalOutputVideoRefreshPeriod(displayLink: CVDisplayLinkRef)
       -> CVTime;
     pub fn CVDisplayLinkRelease(displayLink: CVDisplayLinkRef);
+    pub fn CVDisplayLinkSetOutputCallback(
+      displayLink: CVDisplayLinkRef,
+      callback: CVDisplayLinkOutputCallback,
+      userInfo: *mut c_void,
+    ) -> CVReturn;
+    pub fn CVDisplayLinkStart(displayLink: CVDisplayLinkRef) -> CVReturn;
+    pub fn CVDisplayLinkStop(displayLink: CVDisplayLinkRef) -> CVReturn;
   }
 }
 
@@ -11,7 +11,7 @@ use crate::{
   global_shortcut::{GlobalShortcut as RootGlobalShortcut, ShortcutManagerError},
 };
 
-use super::{app_state::AppState, event::EventWrapper};
+use super::{app_state::AppState, event::EventWrapper, media_keys, OsError};
 
 type KeyCallback = unsafe extern "C" fn(c_int, *mut c_void);
 #[derive(Debug, Clone)]
@@ -30,6 +30,10 @@ impl ShortcutManager {
     }
   }
 
+  pub(crate) fn is_supported(&self) -> bool {
+    true
+  }
+
   pub(crate) fn unregister_all(&self) -> Result<(), ShortcutManagerError> {
     for shortcut in &self.shortcuts {
       shortcut.unregister();
@@ -50,6 +54,20 @@ impl ShortcutManager {
     accelerator: Accelerator,
   ) -> Result<RootGlobalShortcut, ShortcutManagerError> {
     unsafe {
+      if media_keys::is_media_key(accelerator.key) {
+        if !media_keys::register(&accelerator) {
+          return Err(ShortcutManagerError::Os(os_error!(OsError::CreationError(
+            "Couldn't install the media key event tap"
+          ))));
+        }
+        let shortcut = GlobalShortcut {
+          accelerator,
+          carbon_ref: CarbonRef::new(std::ptr::null_mut()),
+        };
+        self.shortcuts.push(shortcut.clone());
+        return Ok(RootGlobalShortcut(shortcut));
+      }
+
       let mut converted_modifiers: i32 = 0;
       if accelerator.mods.shift_key() {
         converted_modifiers |= 512;
@@ -137,7 +155,14 @@ impl GlobalShortcut {
 
 impl GlobalShortcut {
   pub(crate) fn unregister(&self) {
-    unsafe { unregister_hotkey(self.carbon_ref.0) };
+    unsafe {
+      // Media keys are not Carbon hot keys and have no reference.
+      if self.carbon_ref.0.is_null() {
+        media_keys::unregister(self.id());
+      } else {
+        unregister_hotkey(self.carbon_ref.0);
+      }
+    }
   }
 }
 
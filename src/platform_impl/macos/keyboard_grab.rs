@@ -0,0 +1,138 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{cell::RefCell, ffi::c_void, ptr};
+
+use cocoa::{
+  appkit::NSApp,
+  base::{id, nil},
+};
+use core_foundation::{
+  base::TCFType,
+  mach_port::{CFMachPort, CFMachPortInvalidate},
+  runloop::{kCFRunLoopCommonModes, CFRunLoop, CFRunLoopSource},
+};
+use core_graphics::{
+  event::{CGEventFlags, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType},
+  sys::CGEventRef,
+};
+use objc::runtime::{BOOL, NO};
+
+use super::ffi;
+
+struct KeyboardGrab {
+  ns_window: id,
+  tap: CFMachPort,
+  source: CFRunLoopSource,
+}
+
+thread_local! {
+  // Only touched on the main thread, which is also where the tap callback runs.
+  static KEYBOARD_GRAB: RefCell<Option<KeyboardGrab>> = RefCell::new(None);
+}
+
+/// Installs or removes the event tap that redirects system shortcuts to `ns_window`.
+///
+/// Must be called on the main thread.
+pub unsafe fn set_keyboard_grab(ns_window: id, grab: bool) {
+  KEYBOARD_GRAB.with(|cell| {
+    let mut current = cell.borrow_mut();
+    match (grab, current.as_ref()) {
+      (true, Some(current)) if current.ns_window == ns_window => return,
+      // Another window releasing its grab must not remove ours.
+      (false, Some(current)) if current.ns_window != ns_window => return,
+      _ => (),
+    }
+
+    if let Some(previous) = current.take() {
+      ffi::CGEventTapEnable(previous.tap.as_concrete_TypeRef(), false);
+      CFRunLoop::get_main().remove_source(&previous.source, kCFRunLoopCommonModes);
+      CFMachPortInvalidate(previous.tap.as_concrete_TypeRef());
+    }
+
+    if grab {
+      *current = install(ns_window);
+    }
+  });
+}
+
+unsafe fn install(ns_window: id) -> Option<KeyboardGrab> {
+  let mask = (1 << CGEventType::KeyDown as u64) | (1 << CGEventType::KeyUp as u64);
+  let tap_ref = ffi::CGEventTapCreate(
+    CGEventTapLocation::Session,
+    CGEventTapPlacement::HeadInsertEventTap,
+    CGEventTapOptions::Default,
+    mask,
+    tap_callback,
+    ptr::null_mut(),
+  );
+  if tap_ref.is_null() {
+    warn!("Failed to create the keyboard event tap, is the app trusted for accessibility?");
+    return None;
+  }
+
+  let tap = CFMachPort::wrap_under_create_rule(tap_ref);
+  let source = match tap.create_runloop_source(0) {
+    Ok(source) => source,
+    Err(_) => {
+      warn!("Failed to create a run loop source for the keyboard event tap");
+      CFMachPortInvalidate(tap.as_concrete_TypeRef());
+      return None;
+    }
+  };
+  CFRunLoop::get_main().add_source(&source, kCFRunLoopCommonModes);
+  ffi::CGEventTapEnable(tap.as_concrete_TypeRef(), true);
+
+  Some(KeyboardGrab {
+    ns_window,
+    tap,
+    source,
+  })
+}
+
+extern "C" fn tap_callback(
+  _proxy: *const c_void,
+  event_type: u32,
+  event: CGEventRef,
+  _user_info: *mut c_void,
+) -> CGEventRef {
+  let ns_window = KEYBOARD_GRAB.with(|cell| {
+    let current = cell.borrow();
+    let current = current.as_ref()?;
+    // The system disables taps that take too long, turn ours back on.
+    if event_type == CGEventType::TapDisabledByTimeout as u32
+      || event_type == CGEventType::TapDisabledByUserInput as u32
+    {
+      unsafe { ffi::CGEventTapEnable(current.tap.as_concrete_TypeRef(), true) };
+      return None;
+    }
+    Some(current.ns_window)
+  });
+  let ns_window = match ns_window {
+    Some(ns_window) => ns_window,
+    None => return event,
+  };
+
+  unsafe {
+    let app_active: BOOL = msg_send![NSApp(), isActive];
+    let is_key_window: BOOL = msg_send![ns_window, isKeyWindow];
+    if app_active == NO || is_key_window == NO {
+      return event;
+    }
+
+    // Only shortcuts with these modifiers can be taken over by the system.
+    let flags = CGEventFlags::from_bits_truncate(ffi::CGEventGetFlags(event));
+    if !flags.intersects(CGEventFlags::CGEventFlagCommand | CGEventFlags::CGEventFlagControl) {
+      return event;
+    }
+
+    let ns_event: id = msg_send![class!(NSEvent), eventWithCGEvent: event];
+    if ns_event == nil {
+      return event;
+    }
+    // Deliver the event through our own queue instead, so it ends up as a regular key event.
+    let _: () = msg_send![NSApp(), postEvent: ns_event atStart: NO];
+    ptr::null_mut()
+  }
+}
@@ -0,0 +1,237 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Media keys are not regular key events on macOS and cannot be registered as Carbon hot keys, so
+//! global shortcuts using them are matched from an event tap on the system defined events.
+
+use std::{cell::RefCell, ffi::c_void, ptr};
+
+use cocoa::{
+  appkit::NSEventModifierFlags,
+  base::{id, nil},
+};
+use core_foundation::{
+  base::TCFType,
+  mach_port::{CFMachPort, CFMachPortInvalidate},
+  runloop::{kCFRunLoopCommonModes, CFRunLoop, CFRunLoopSource},
+};
+use core_graphics::{
+  event::{CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType},
+  sys::CGEventRef,
+};
+
+use super::{app_state::AppState, event::EventWrapper, ffi};
+use crate::{
+  accelerator::{Accelerator, AcceleratorId},
+  event::Event,
+  keyboard::{KeyCode, ModifiersState},
+};
+
+/// `NX_SYSDEFINED`, the event type media keys are delivered as.
+const SYSTEM_DEFINED_EVENT: u32 = 14;
+/// `NX_SUBTYPE_AUX_CONTROL_BUTTONS`
+const AUX_CONTROL_BUTTONS_SUBTYPE: i16 = 8;
+/// `NX_KEYDOWN`, as found in the key state of the event data.
+const KEY_DOWN_STATE: i64 = 0xa;
+
+// `NX_KEYTYPE_*` values from `IOKit/hidsystem/ev_keymap.h`.
+const NX_KEYTYPE_SOUND_UP: i64 = 0;
+const NX_KEYTYPE_SOUND_DOWN: i64 = 1;
+const NX_KEYTYPE_MUTE: i64 = 7;
+const NX_KEYTYPE_PLAY: i64 = 16;
+const NX_KEYTYPE_NEXT: i64 = 17;
+const NX_KEYTYPE_PREVIOUS: i64 = 18;
+const NX_KEYTYPE_FAST: i64 = 19;
+const NX_KEYTYPE_REWIND: i64 = 20;
+
+struct MediaKeyTap {
+  tap: CFMachPort,
+  source: CFRunLoopSource,
+}
+
+#[derive(Default)]
+struct MediaKeys {
+  shortcuts: Vec<(KeyCode, ModifiersState, AcceleratorId)>,
+  tap: Option<MediaKeyTap>,
+}
+
+thread_local! {
+  // Only touched on the main thread, which is also where the tap callback runs.
+  static MEDIA_KEYS: RefCell<MediaKeys> = RefCell::new(MediaKeys::default());
+}
+
+/// Whether `key` is only available as a media key.
+pub fn is_media_key(key: KeyCode) -> bool {
+  matches!(
+    key,
+    KeyCode::AudioVolumeUp
+      | KeyCode::AudioVolumeDown
+      | KeyCode::AudioVolumeMute
+      | KeyCode::MediaPlayPause
+      | KeyCode::MediaTrackNext
+      | KeyCode::MediaTrackPrevious
+  )
+}
+
+fn key_from_key_type(key_type: i64) -> Option<KeyCode> {
+  Some(match key_type {
+    NX_KEYTYPE_SOUND_UP => KeyCode::AudioVolumeUp,
+    NX_KEYTYPE_SOUND_DOWN => KeyCode::AudioVolumeDown,
+    NX_KEYTYPE_MUTE => KeyCode::AudioVolumeMute,
+    NX_KEYTYPE_PLAY => KeyCode::MediaPlayPause,
+    // Apple keyboards send the fast forward and rewind key types for their track keys.
+    NX_KEYTYPE_NEXT | NX_KEYTYPE_FAST => KeyCode::MediaTrackNext,
+    NX_KEYTYPE_PREVIOUS | NX_KEYTYPE_REWIND => KeyCode::MediaTrackPrevious,
+    _ => return None,
+  })
+}
+
+/// Registers a media key shortcut, installing the event tap if needed.
+///
+/// Must be called on the main thread.
+pub unsafe fn register(accelerator: &Accelerator) -> bool {
+  MEDIA_KEYS.with(|cell| {
+    let mut media_keys = cell.borrow_mut();
+    if media_keys.tap.is_none() {
+      media_keys.tap = install();
+    }
+    if media_keys.tap.is_none() {
+      return false;
+    }
+    media_keys
+      .shortcuts
+      .push((accelerator.key, accelerator.mods, accelerator.clone().id()));
+    true
+  })
+}
+
+/// Unregisters a media key shortcut, removing the event tap once none are left.
+///
+/// Must be called on the main thread.
+pub unsafe fn unregister(id: AcceleratorId) {
+  MEDIA_KEYS.with(|cell| {
+    let mut media_keys = cell.borrow_mut();
+    media_keys
+      .shortcuts
+      .retain(|(_, _, shortcut_id)| *shortcut_id != id);
+    if media_keys.shortcuts.is_empty() {
+      if let Some(tap) = media_keys.tap.take() {
+        ffi::CGEventTapEnable(tap.tap.as_concrete_TypeRef(), false);
+        CFRunLoop::get_main().remove_source(&tap.source, kCFRunLoopCommonModes);
+        CFMachPortInvalidate(tap.tap.as_concrete_TypeRef());
+      }
+    }
+  });
+}
+
+unsafe fn install() -> Option<MediaKeyTap> {
+  let tap_ref = ffi::CGEventTapCreate(
+    CGEventTapLocation::Session,
+    CGEventTapPlacement::HeadInsertEventTap,
+    CGEventTapOptions::Default,
+    1 << SYSTEM_DEFINED_EVENT as u64,
+    tap_callback,
+    ptr::null_mut(),
+  );
+  if tap_ref.is_null() {
+    warn!("Failed to create the media key event tap, is the app trusted for accessibility?");
+    return None;
+  }
+
+  let tap = CFMachPort::wrap_under_create_rule(tap_ref);
+  let source = match tap.create_runloop_source(0) {
+    Ok(source) => source,
+    Err(_) => {
+      warn!("Failed to create a run loop source for the media key event tap");
+      CFMachPortInvalidate(tap.as_concrete_TypeRef());
+      return None;
+    }
+  };
+  CFRunLoop::get_main().add_source(&source, kCFRunLoopCommonModes);
+  ffi::CGEventTapEnable(tap.as_concrete_TypeRef(), true);
+
+  Some(MediaKeyTap { tap, source })
+}
+
+extern "C" fn tap_callback(
+  _proxy: *const c_void,
+  event_type: u32,
+  event: CGEventRef,
+  _user_info: *mut c_void,
+) -> CGEventRef {
+  // The system disables taps that take too long, turn ours back on.
+  if event_type == CGEventType::TapDisabledByTimeout as u32
+    || event_type == CGEventType::TapDisabledByUserInput as u32
+  {
+    MEDIA_KEYS.with(|cell| {
+      if let Some(tap) = &cell.borrow().tap {
+        unsafe { ffi::CGEventTapEnable(tap.tap.as_concrete_TypeRef(), true) };
+      }
+    });
+    return event;
+  }
+  if event_type != SYSTEM_DEFINED_EVENT {
+    return event;
+  }
+
+  unsafe {
+    let ns_event: id = msg_send![class!(NSEvent), eventWithCGEvent: event];
+    if ns_event == nil {
+      return event;
+    }
+    let subtype: i16 = msg_send![ns_event, subtype];
+    if subtype != AUX_CONTROL_BUTTONS_SUBTYPE {
+      return event;
+    }
+
+    let data: i64 = msg_send![ns_event, data1];
+    let key_type = (data & 0xffff_0000) >> 16;
+    let key_flags = data & 0xffff;
+    let key_state = (key_flags & 0xff00) >> 8;
+    let key = match key_from_key_type(key_type) {
+      Some(key) => key,
+      None => return event,
+    };
+
+    let flags: NSEventModifierFlags = msg_send![ns_event, modifierFlags];
+    let mut mods = ModifiersState::empty();
+    mods.set(
+      ModifiersState::SHIFT,
+      flags.contains(NSEventModifierFlags::NSShiftKeyMask),
+    );
+    mods.set(
+      ModifiersState::CONTROL,
+      flags.contains(NSEventModifierFlags::NSControlKeyMask),
+    );
+    mods.set(
+      ModifiersState::ALT,
+      flags.contains(NSEventModifierFlags::NSAlternateKeyMask),
+    );
+    mods.set(
+      ModifiersState::SUPER,
+      flags.contains(NSEventModifierFlags::NSCommandKeyMask),
+    );
+
+    let id = MEDIA_KEYS.with(|cell| {
+      cell
+        .borrow()
+        .shortcuts
+        .iter()
+        .find(|(shortcut_key, shortcut_mods, _)| *shortcut_key == key && *shortcut_mods == mods)
+        .map(|(_, _, id)| *id)
+    });
+    let id = match id {
+      Some(id) => id,
+      None => return event,
+    };
+
+    // Swallow both the press and the release so the system does not also act on the key, but
+    // only report the first press and not the repeats.
+    let is_repeat = key_flags & 0x1 != 0;
+    if key_state == KEY_DOWN_STATE && !is_repeat {
+      AppState::queue_event(EventWrapper::StaticEvent(Event::GlobalShortcutEvent(id)));
+    }
+    ptr::null_mut()
+  }
+}
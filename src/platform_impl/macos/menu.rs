@@ -3,13 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use cocoa::{
-  appkit::{NSApp, NSApplication, NSButton, NSEventModifierFlags, NSImage, NSMenu, NSMenuItem},
+  appkit::{
+    NSApp, NSApplication, NSButton, NSEvent, NSEventModifierFlags, NSEventType, NSImage, NSMenu,
+    NSMenuItem,
+  },
   base::{id, nil, selector},
   foundation::{NSAutoreleasePool, NSData, NSString},
 };
 use objc::{
   declare::ClassDecl,
-  runtime::{Class, Object, Sel, NO, YES},
+  runtime::{Class, Object, Sel, BOOL, NO, YES},
 };
 use std::sync::Once;
 
@@ -18,16 +21,22 @@ use crate::{
   event::Event,
   icon::Icon,
   keyboard::{KeyCode, ModifiersState},
-  menu::{CustomMenuItem, MenuId, MenuItem, MenuType},
+  menu::{
+    CustomMenuItem, MenuId, MenuItem, MenuItemAttributes as RootMenuItemAttributes, MenuType,
+  },
   platform::macos::NativeImage,
   window::WindowId,
 };
 
 use super::{
-  app_state::AppState, event::EventWrapper, util::ns_string_to_rust, window::get_window_id,
+  app_state::AppState, event::EventWrapper, system_tray::tray_id_of_menu, util::ns_string_to_rust,
+  window::get_window_id,
 };
 
 static BLOCK_PTR: &str = "taoMenuItemBlockPtr";
+static KEY_EQUIVALENT_DISPLAY_ONLY: &str = "taoMenuItemKeyEquivalentDisplayOnly";
+/// The radio group of the item plus one, `0` if it isn't part of a group.
+static RADIO_GROUP: &str = "taoMenuItemRadioGroup";
 
 #[derive(Debug, Clone)]
 pub struct Menu {
@@ -73,6 +82,9 @@ impl MenuItemAttributes {
   }
   pub fn set_selected(&mut self, is_selected: bool) {
     unsafe {
+      if is_selected && select_radio_item(self.1) {
+        return;
+      }
       let state = match is_selected {
         true => 1_isize,
         false => 0_isize,
@@ -126,13 +138,27 @@ impl Menu {
 
   pub fn add_item(
     &mut self,
-    menu_id: MenuId,
-    title: &str,
-    accelerators: Option<Accelerator>,
-    enabled: bool,
-    selected: bool,
+    item: RootMenuItemAttributes<'_>,
+    menu_type: MenuType,
+  ) -> CustomMenuItem {
+    self.insert_item(usize::MAX, item, menu_type)
+  }
+
+  pub fn insert_item(
+    &mut self,
+    index: usize,
+    item: RootMenuItemAttributes<'_>,
     menu_type: MenuType,
   ) -> CustomMenuItem {
+    let RootMenuItemAttributes {
+      id: menu_id,
+      title,
+      keyboard_accelerator: accelerators,
+      accelerator_display_only,
+      enabled,
+      selected,
+      radio_group,
+    } = item;
     let menu_item = make_custom_menu_item(menu_id, title, None, accelerators, menu_type);
 
     unsafe {
@@ -142,13 +168,40 @@ impl Menu {
       if !enabled {
         let () = msg_send![menu_item, setEnabled: NO];
       }
+      if accelerator_display_only {
+        (*menu_item).set_ivar::<BOOL>(KEY_EQUIVALENT_DISPLAY_ONLY, YES);
+      }
+      if let Some(radio_group) = radio_group {
+        (*menu_item).set_ivar::<usize>(RADIO_GROUP, radio_group.0 as usize + 1);
+      }
 
-      self.menu.addItem_(menu_item);
+      let count: isize = msg_send![self.menu, numberOfItems];
+      let index = index.min(count as usize) as isize;
+      let () = msg_send![self.menu, insertItem: menu_item atIndex: index];
+
+      if selected {
+        select_radio_item(menu_item);
+      }
     }
 
     CustomMenuItem(MenuItemAttributes(Some(menu_id), menu_item))
   }
 
+  pub fn remove_item(&mut self, item: &MenuItemAttributes) {
+    unsafe {
+      let menu: id = msg_send![item.1, menu];
+      if menu == self.menu {
+        let () = msg_send![self.menu, removeItem: item.1];
+      }
+    }
+  }
+
+  pub fn clear(&mut self) {
+    unsafe {
+      let () = msg_send![self.menu, removeAllItems];
+    }
+  }
+
   pub fn add_submenu(&mut self, title: &str, enabled: bool, submenu: Menu) {
     unsafe {
       let menu_title = NSString::alloc(nil).init_str(title);
@@ -417,6 +470,8 @@ fn make_menu_item_class() -> *const Class {
     let superclass = class!(NSMenuItem);
     let mut decl = ClassDecl::new("TaoMenuItem", superclass).unwrap();
     decl.add_ivar::<usize>(BLOCK_PTR);
+    decl.add_ivar::<BOOL>(KEY_EQUIVALENT_DISPLAY_ONLY);
+    decl.add_ivar::<usize>(RADIO_GROUP);
 
     decl.add_method(
       sel!(dealloc),
@@ -448,6 +503,24 @@ extern "C" fn fire_menu_bar_click(this: &Object, _: Sel, _item: id) {
 }
 
 fn send_event(this: &Object, origin: MenuType) {
+  unsafe {
+    // NSMenu always handles key equivalents, so hand the key press over to the key window as if
+    // the menu didn't have it.
+    let display_only: BOOL = *this.get_ivar(KEY_EQUIVALENT_DISPLAY_ONLY);
+    let event: id = msg_send![NSApp(), currentEvent];
+    if display_only == YES && event != nil && event.eventType() == NSEventType::NSKeyDown {
+      let key_window: id = msg_send![NSApp(), keyWindow];
+      if key_window != nil {
+        let () = msg_send![key_window, sendEvent: event];
+      }
+      return;
+    }
+  }
+
+  unsafe {
+    select_radio_item(this as *const Object as id);
+  }
+
   let menu_id = unsafe {
     let ptr: usize = *this.get_ivar(BLOCK_PTR);
     let obj = ptr as *const Action;
@@ -455,24 +528,60 @@ fn send_event(this: &Object, origin: MenuType) {
   };
 
   // active window
-  let window_id = match origin {
+  let (window_id, tray_id) = match origin {
     MenuType::MenuBar => unsafe {
       let app: id = msg_send![class!(NSApplication), sharedApplication];
       let window_id: id = msg_send![app, mainWindow];
-      Some(WindowId(get_window_id(window_id)))
+      (Some(WindowId(get_window_id(window_id))), None)
     },
     // system tray do not send WindowId
-    MenuType::ContextMenu => None,
+    MenuType::ContextMenu => unsafe {
+      let menu: id = msg_send![this, menu];
+      (None, tray_id_of_menu(menu))
+    },
   };
 
   let event = Event::MenuEvent {
     window_id,
     menu_id: MenuId(*menu_id.0),
     origin,
+    tray_id,
   };
   AppState::queue_event(EventWrapper::StaticEvent(event));
 }
 
+/// Selects a radio item and deselects the other items of its group in the same menu.
+///
+/// Returns `false` if the item isn't part of a radio group.
+unsafe fn select_radio_item(item: id) -> bool {
+  let is_custom_item: BOOL = msg_send![item, isKindOfClass: make_menu_item_class()];
+  if is_custom_item == NO {
+    return false;
+  }
+  let group: usize = *(*item).get_ivar(RADIO_GROUP);
+  if group == 0 {
+    return false;
+  }
+
+  let menu: id = msg_send![item, menu];
+  if menu != nil {
+    let items: id = msg_send![menu, itemArray];
+    let count: usize = msg_send![items, count];
+    for i in 0..count {
+      let sibling: id = msg_send![items, objectAtIndex: i];
+      let is_custom_item: BOOL = msg_send![sibling, isKindOfClass: make_menu_item_class()];
+      if sibling != item
+        && is_custom_item == YES
+        && *(*sibling).get_ivar::<usize>(RADIO_GROUP) == group
+      {
+        let () = msg_send![sibling, setState: 0_isize];
+      }
+    }
+  }
+  let () = msg_send![item, setState: 1_isize];
+  true
+}
+
 extern "C" fn dealloc_custom_menuitem(this: &Object, _: Sel) {
   unsafe {
     let ptr: usize = *this.get_ivar(BLOCK_PTR);
@@ -4,19 +4,27 @@
 
 #![cfg(target_os = "macos")]
 
+#[cfg(feature = "accesskit")]
+mod accessibility;
 mod app;
 mod app_delegate;
 mod app_state;
 mod clipboard;
+mod device_events;
+mod display_link;
 mod event;
 mod event_loop;
 mod ffi;
 mod global_shortcut;
 mod icon;
+mod keyboard_grab;
 mod keycode;
+mod media_keys;
 mod menu;
 mod monitor;
 mod observer;
+mod progress_bar;
+mod single_instance;
 #[cfg(feature = "tray")]
 mod system_tray;
 mod util;
@@ -30,10 +38,12 @@ use std::{fmt, ops::Deref, sync::Arc};
 pub use self::system_tray::{SystemTray, SystemTrayBuilder};
 
 pub use self::{
-  app_delegate::{get_aux_state_mut, AuxDelegateState},
+  app_delegate::{get_aux_state_mut, set_dock_menu, AuxDelegateState},
   clipboard::Clipboard,
   event::KeyEventExtra,
-  event_loop::{EventLoop, EventLoopWindowTarget, Proxy as EventLoopProxy},
+  event_loop::{
+    EventLoop, EventLoopWindowTarget, PlatformSpecificEventLoopAttributes, Proxy as EventLoopProxy,
+  },
   global_shortcut::{GlobalShortcut, ShortcutManager},
   keycode::{keycode_from_scancode, keycode_to_scancode},
   menu::{Menu, MenuItemAttributes},
@@ -12,7 +12,7 @@ use crate::{
 use cocoa::{
   appkit::NSScreen,
   base::{id, nil},
-  foundation::NSUInteger,
+  foundation::{NSPoint, NSUInteger},
 };
 use core_foundation::{
   array::{CFArrayGetCount, CFArrayGetValueAtIndex},
@@ -158,6 +158,30 @@ pub fn primary_monitor() -> MonitorHandle {
   MonitorHandle(CGDisplay::main().id)
 }
 
+/// The location of the cursor in the logical, top left based coordinates of the displays.
+pub fn cursor_location() -> (f64, f64) {
+  unsafe {
+    // The origin of Cocoa coordinates is the bottom left corner of the main display.
+    let location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+    let main_bounds = CGDisplayBounds(CGDisplay::main().id);
+    (
+      location.x as f64,
+      main_bounds.size.height as f64 - location.y as f64,
+    )
+  }
+}
+
+/// The monitor containing `(x, y)`, in the logical, top left based coordinates of the displays.
+pub fn monitor_from_point(x: f64, y: f64) -> Option<MonitorHandle> {
+  available_monitors().into_iter().find(|monitor| {
+    let bounds = unsafe { CGDisplayBounds(monitor.0) };
+    x >= bounds.origin.x
+      && y >= bounds.origin.y
+      && x < bounds.origin.x + bounds.size.width
+      && y < bounds.origin.y + bounds.size.height
+  })
+}
+
 impl fmt::Debug for MonitorHandle {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     // TODO: Do this using the proper fmt API
@@ -224,22 +248,45 @@ impl MonitorHandle {
     unsafe { NSScreen::backingScaleFactor(screen) as f64 }
   }
 
-  pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
-    let cv_refresh_rate = unsafe {
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    unsafe {
+      let mode = ffi::CGDisplayCopyDisplayMode(self.0);
+      if !mode.is_null() {
+        let refresh_rate = ffi::CGDisplayModeGetRefreshRate(mode);
+        ffi::CGDisplayModeRelease(mode);
+        if refresh_rate > 0.0 {
+          return Some((refresh_rate * 1000.0).round() as u32);
+        }
+      }
+
+      // Built-in displays report 0, the period of their display link is right though.
       let mut display_link = std::ptr::null_mut();
-      assert_eq!(
-        ffi::CVDisplayLinkCreateWithCGDisplay(self.0, &mut display_link),
-        ffi::kCVReturnSuccess
-      );
+      if ffi::CVDisplayLinkCreateWithCGDisplay(self.0, &mut display_link) != ffi::kCVReturnSuccess {
+        return None;
+      }
       let time = ffi::CVDisplayLinkGetNominalOutputVideoRefreshPeriod(display_link);
       ffi::CVDisplayLinkRelease(display_link);
+      if time.flags & ffi::kCVTimeIsIndefinite != 0 || time.time_value == 0 {
+        return None;
+      }
+      Some((time.time_scale as f64 * 1000.0 / time.time_value as f64).round() as u32)
+    }
+  }
 
-      // This value is indefinite if an invalid display link was specified
-      assert!(time.flags & ffi::kCVTimeIsIndefinite == 0);
-
-      time.time_scale as i64 / time.time_value
-    };
+  pub fn mirror_group_id(&self) -> Option<u32> {
+    let display = CGDisplay::new(self.0);
+    if !display.is_in_mirror_set() {
+      return None;
+    }
+    // The mirror source doesn't mirror anything itself.
+    match display.mirrors_display() {
+      0 => Some(self.0),
+      source => Some(source),
+    }
+  }
 
+  pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
+    let cv_refresh_rate = self.cv_refresh_rate();
     let monitor = self.clone();
 
     unsafe {
@@ -258,45 +305,82 @@ impl MonitorHandle {
         modes
       };
 
-      modes.into_iter().map(move |mode| {
-        let cg_refresh_rate = ffi::CGDisplayModeGetRefreshRate(mode).round() as i64;
-
-        // CGDisplayModeGetRefreshRate returns 0.0 for any display that
-        // isn't a CRT
-        let refresh_rate = if cg_refresh_rate > 0 {
-          cg_refresh_rate
-        } else {
-          cv_refresh_rate
-        };
-
-        let pixel_encoding =
-          CFString::wrap_under_create_rule(ffi::CGDisplayModeCopyPixelEncoding(mode)).to_string();
-        let bit_depth = if pixel_encoding.eq_ignore_ascii_case(ffi::IO32BitDirectPixels) {
-          32
-        } else if pixel_encoding.eq_ignore_ascii_case(ffi::IO16BitDirectPixels) {
-          16
-        } else if pixel_encoding.eq_ignore_ascii_case(ffi::kIO30BitDirectPixels) {
-          30
-        } else {
-          unimplemented!()
-        };
-
-        let video_mode = VideoMode {
-          size: (
-            ffi::CGDisplayModeGetPixelWidth(mode) as u32,
-            ffi::CGDisplayModeGetPixelHeight(mode) as u32,
-          ),
-          refresh_rate: refresh_rate as u16,
-          bit_depth,
-          monitor: monitor.clone(),
-          native_mode: NativeDisplayMode(mode),
-        };
-
-        RootVideoMode { video_mode }
-      })
+      modes
+        .into_iter()
+        .map(move |mode| monitor.video_mode_from_native(mode, cv_refresh_rate))
     }
   }
 
+  /// Returns the video mode the display is currently using.
+  pub(crate) fn current_video_mode(&self) -> Option<RootVideoMode> {
+    unsafe {
+      let mode = ffi::CGDisplayCopyDisplayMode(self.0);
+      if mode.is_null() {
+        return None;
+      }
+      Some(self.video_mode_from_native(mode, self.cv_refresh_rate()))
+    }
+  }
+
+  fn cv_refresh_rate(&self) -> i64 {
+    unsafe {
+      let mut display_link = std::ptr::null_mut();
+      assert_eq!(
+        ffi::CVDisplayLinkCreateWithCGDisplay(self.0, &mut display_link),
+        ffi::kCVReturnSuccess
+      );
+      let time = ffi::CVDisplayLinkGetNominalOutputVideoRefreshPeriod(display_link);
+      ffi::CVDisplayLinkRelease(display_link);
+
+      // This value is indefinite if an invalid display link was specified
+      assert!(time.flags & ffi::kCVTimeIsIndefinite == 0);
+
+      time.time_scale as i64 / time.time_value
+    }
+  }
+
+  /// Takes ownership of a retained `mode`.
+  unsafe fn video_mode_from_native(
+    &self,
+    mode: ffi::CGDisplayModeRef,
+    cv_refresh_rate: i64,
+  ) -> RootVideoMode {
+    let cg_refresh_rate = ffi::CGDisplayModeGetRefreshRate(mode).round() as i64;
+
+    // CGDisplayModeGetRefreshRate returns 0.0 for any display that
+    // isn't a CRT
+    let refresh_rate = if cg_refresh_rate > 0 {
+      cg_refresh_rate
+    } else {
+      cv_refresh_rate
+    };
+
+    let pixel_encoding =
+      CFString::wrap_under_create_rule(ffi::CGDisplayModeCopyPixelEncoding(mode)).to_string();
+    let bit_depth = if pixel_encoding.eq_ignore_ascii_case(ffi::IO32BitDirectPixels) {
+      32
+    } else if pixel_encoding.eq_ignore_ascii_case(ffi::IO16BitDirectPixels) {
+      16
+    } else if pixel_encoding.eq_ignore_ascii_case(ffi::kIO30BitDirectPixels) {
+      30
+    } else {
+      unimplemented!()
+    };
+
+    let video_mode = VideoMode {
+      size: (
+        ffi::CGDisplayModeGetPixelWidth(mode) as u32,
+        ffi::CGDisplayModeGetPixelHeight(mode) as u32,
+      ),
+      refresh_rate: refresh_rate as u16,
+      bit_depth,
+      monitor: self.clone(),
+      native_mode: NativeDisplayMode(mode),
+    };
+
+    RootVideoMode { video_mode }
+  }
+
   pub(crate) fn ns_screen(&self) -> Option<id> {
     unsafe {
       let uuid = ffi::CGDisplayCreateUUIDFromDisplayID(self.0);
@@ -0,0 +1,83 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The dock tile has no progress bar of its own, so one is drawn by replacing its content view
+//! with the application icon and a progress indicator on top of it.
+
+use std::cell::Cell;
+
+use cocoa::{
+  appkit::NSApp,
+  base::{id, nil},
+  foundation::{NSPoint, NSRect, NSSize},
+};
+use objc::runtime::{NO, YES};
+
+use crate::window::{ProgressBarState, ProgressState};
+
+/// `NSProgressIndicatorStyleBar`
+const PROGRESS_INDICATOR_STYLE_BAR: u64 = 0;
+
+thread_local! {
+  // Only touched on the main thread, like the dock tile itself.
+  static PROGRESS_INDICATOR: Cell<id> = Cell::new(nil);
+}
+
+/// Must be called on the main thread.
+pub unsafe fn set_progress_bar(progress: ProgressBarState) {
+  PROGRESS_INDICATOR.with(|cell| {
+    let mut indicator = cell.get();
+    if indicator == nil {
+      indicator = create_progress_indicator();
+      cell.set(indicator);
+    }
+
+    if let Some(value) = progress.progress {
+      let value = value.min(100) as f64;
+      let _: () = msg_send![indicator, setDoubleValue: value];
+    }
+    if let Some(state) = progress.state {
+      let indeterminate = state == ProgressState::Indeterminate;
+      let _: () = msg_send![indicator, setIndeterminate: if indeterminate { YES } else { NO }];
+      let hidden = state == ProgressState::None;
+      let _: () = msg_send![indicator, setHidden: if hidden { YES } else { NO }];
+    }
+
+    let dock_tile: id = msg_send![NSApp(), dockTile];
+    let _: () = msg_send![dock_tile, display];
+  });
+}
+
+unsafe fn create_progress_indicator() -> id {
+  let dock_tile: id = msg_send![NSApp(), dockTile];
+  let size: NSSize = msg_send![dock_tile, size];
+
+  let image_view: id = msg_send![class!(NSImageView), alloc];
+  let image_view: id = msg_send![
+    image_view,
+    initWithFrame: NSRect::new(NSPoint::new(0., 0.), size)
+  ];
+  let icon: id = msg_send![NSApp(), applicationIconImage];
+  let _: () = msg_send![image_view, setImage: icon];
+
+  // A bar along the bottom of the icon, like the one Finder shows for copies.
+  let height = size.height / 8.;
+  let frame = NSRect::new(
+    NSPoint::new(size.width / 16., size.height / 16.),
+    NSSize::new(size.width * 7. / 8., height),
+  );
+  let indicator: id = msg_send![class!(NSProgressIndicator), alloc];
+  let indicator: id = msg_send![indicator, initWithFrame: frame];
+  let _: () = msg_send![indicator, setStyle: PROGRESS_INDICATOR_STYLE_BAR];
+  let _: () = msg_send![indicator, setMinValue: 0f64];
+  let _: () = msg_send![indicator, setMaxValue: 100f64];
+  let _: () = msg_send![indicator, setHidden: YES];
+  let _: () = msg_send![image_view, addSubview: indicator];
+
+  let _: () = msg_send![dock_tile, setContentView: image_view];
+  // The dock tile retains the content view, which retains the indicator.
+  let _: () = msg_send![image_view, release];
+  let _: () = msg_send![indicator, release];
+  indicator
+}
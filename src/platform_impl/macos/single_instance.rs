@@ -0,0 +1,87 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{env, fs::OpenOptions, mem, os::unix::io::AsRawFd, path::PathBuf};
+
+use cocoa::{
+  base::{id, nil, YES},
+  foundation::{NSArray, NSDictionary},
+};
+
+use super::{app_delegate, util, util::IdRef};
+
+const ARGS_KEY: &str = "args";
+const CWD_KEY: &str = "cwd";
+
+/// Locks a file named after `identifier` in the temporary directory to become the single
+/// instance, or posts the arguments of the process to the instance holding the lock.
+pub fn enable(identifier: &str) -> bool {
+  let notification_name = format!("{}.tao-new-instance", identifier);
+  let path = env::temp_dir().join(format!("{}.tao-single-instance.lock", identifier));
+  let file = match OpenOptions::new()
+    .create(true)
+    .write(true)
+    .truncate(true)
+    .open(&path)
+  {
+    Ok(file) => file,
+    Err(e) => {
+      warn!("Failed to open the single instance lock file: {}", e);
+      return true;
+    }
+  };
+
+  if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } == 0 {
+    // The lock is released when the process exits.
+    mem::forget(file);
+    app_delegate::observe_new_instances(&notification_name);
+    return true;
+  }
+
+  unsafe { post_new_instance(&notification_name) };
+  false
+}
+
+unsafe fn post_new_instance(notification_name: &str) {
+  let args: Vec<IdRef> = env::args_os()
+    .map(|arg| util::ns_string_id_ref(&arg.to_string_lossy()))
+    .collect();
+  let args: Vec<id> = args.iter().map(|arg| **arg).collect();
+  let args = NSArray::arrayWithObjects(nil, &args);
+  let cwd = util::ns_string_id_ref(&env::current_dir().unwrap_or_default().to_string_lossy());
+  let args_key = util::ns_string_id_ref(ARGS_KEY);
+  let cwd_key = util::ns_string_id_ref(CWD_KEY);
+  let user_info = NSDictionary::dictionaryWithObjects_forKeys_(
+    nil,
+    NSArray::arrayWithObjects(nil, &[args, *cwd]),
+    NSArray::arrayWithObjects(nil, &[*args_key, *cwd_key]),
+  );
+
+  let notification_center: id = msg_send![class!(NSDistributedNotificationCenter), defaultCenter];
+  let name = util::ns_string_id_ref(notification_name);
+  let _: () = msg_send![
+    notification_center,
+    postNotificationName: *name
+    object: nil
+    userInfo: user_info
+    deliverImmediately: YES
+  ];
+}
+
+/// Decodes the arguments and the current directory posted by another instance.
+pub unsafe fn decode_new_instance(notification: id) -> Option<(Vec<String>, PathBuf)> {
+  let user_info: id = msg_send![notification, userInfo];
+  if user_info == nil {
+    return None;
+  }
+  let args = user_info.objectForKey_(*util::ns_string_id_ref(ARGS_KEY));
+  let cwd = user_info.objectForKey_(*util::ns_string_id_ref(CWD_KEY));
+  if args == nil || cwd == nil {
+    return None;
+  }
+  let args = (0..NSArray::count(args))
+    .map(|i| util::ns_string_to_rust(NSArray::objectAtIndex(args, i)))
+    .collect();
+  Some((args, PathBuf::from(util::ns_string_to_rust(cwd))))
+}
@@ -13,22 +13,26 @@ use crate::{
   error::OsError,
   event::{Event, Rectangle, TrayEvent},
   event_loop::EventLoopWindowTarget,
-  system_tray::{Icon, SystemTray as RootSystemTray},
+  system_tray::{Icon, SystemTray as RootSystemTray, TrayStatus},
   TrayId,
 };
 use cocoa::{
   appkit::{
-    NSButton, NSEventMask, NSEventModifierFlags, NSEventType, NSImage, NSSquareStatusItemLength,
-    NSStatusBar, NSStatusItem, NSWindow,
+    NSApp, NSButton, NSEventMask, NSEventModifierFlags, NSEventType, NSImage,
+    NSSquareStatusItemLength, NSStatusBar, NSStatusItem, NSVariableStatusItemLength, NSView,
+    NSViewHeightSizable, NSViewWidthSizable, NSWindow,
   },
-  base::{id, nil, NO, YES},
-  foundation::{NSData, NSPoint, NSSize, NSString},
+  base::{id, nil, BOOL, NO, YES},
+  foundation::{NSData, NSPoint, NSSize, NSString, NSUInteger},
 };
 use objc::{
   declare::ClassDecl,
   runtime::{Class, Object, Protocol, Sel},
 };
-use std::sync::Once;
+use std::{ptr, sync::Once};
+
+/// `NSImageLeft` of `NSCellImagePosition`, which places the image left of the title.
+const NS_IMAGE_LEFT: NSUInteger = 2;
 
 pub struct SystemTrayBuilder {
   pub(crate) system_tray: SystemTray,
@@ -62,6 +66,7 @@ impl SystemTrayBuilder {
     _window_target: &EventLoopWindowTarget<T>,
     tray_id: TrayId,
     tooltip: Option<String>,
+    title: Option<String>,
   ) -> Result<RootSystemTray, OsError> {
     unsafe {
       // use our existing status bar
@@ -80,6 +85,15 @@ impl SystemTrayBuilder {
       (*tray_target).set_ivar("menu_on_left_click", self.system_tray.menu_on_left_click);
       let _: () = msg_send![button, setAction: sel!(click:)];
       let _: () = msg_send![button, setTarget: tray_target];
+      // Scrolling doesn't trigger the action, so it is caught by a view covering the button.
+      let scroll_view: id = msg_send![make_tray_scroll_view_class(), alloc];
+      let scroll_view: id = msg_send![scroll_view, initWithFrame: NSView::bounds(button)];
+      (*scroll_view).set_ivar("id", tray_id.0);
+      (*scroll_view).set_ivar("scroll_x", 0.0f64);
+      (*scroll_view).set_ivar("scroll_y", 0.0f64);
+      scroll_view.setAutoresizingMask_(NSViewWidthSizable | NSViewHeightSizable);
+      button.addSubview_(scroll_view);
+      let _: () = msg_send![scroll_view, release];
       let _: () = msg_send![
         button,
         sendActionOn: NSEventMask::NSLeftMouseDownMask
@@ -100,6 +114,10 @@ impl SystemTrayBuilder {
       if let Some(tooltip) = tooltip {
         self.system_tray.set_tooltip(&tooltip);
       }
+
+      if let Some(title) = title {
+        self.system_tray.set_title(&title);
+      }
     }
 
     Ok(RootSystemTray(self.system_tray))
@@ -138,7 +156,45 @@ impl SystemTray {
 
   pub fn set_menu(&mut self, tray_menu: &Menu) {
     unsafe {
-      self.ns_status_bar.setMenu_(tray_menu.menu);
+      // Like in `build`, the menu is given to the tray target, see `make_tray_class`.
+      let tray_target: id = msg_send![self.ns_status_bar.button(), target];
+      (*tray_target).set_ivar("menu", tray_menu.menu);
+      let () = msg_send![tray_menu.menu, setDelegate: tray_target];
+    }
+    self.tray_menu = Some(tray_menu.clone());
+  }
+
+  pub fn set_title(&self, title: &str) {
+    unsafe {
+      // The status item is only as wide as the icon, unless it has to fit a title too.
+      let length = if title.is_empty() {
+        NSSquareStatusItemLength
+      } else {
+        NSVariableStatusItemLength
+      };
+      let _: () = msg_send![self.ns_status_bar, setLength: length];
+
+      let button = self.ns_status_bar.button();
+      let title = NSString::alloc(nil).init_str(title);
+      let _: () = msg_send![button, setTitle: title];
+      let _: () = msg_send![button, setImagePosition: NS_IMAGE_LEFT];
+    }
+  }
+
+  pub fn set_status(&mut self, status: TrayStatus) {
+    let visible = if status == TrayStatus::Passive {
+      NO
+    } else {
+      YES
+    };
+    let highlighted = if status == TrayStatus::NeedsAttention {
+      YES
+    } else {
+      NO
+    };
+    unsafe {
+      let _: () = msg_send![self.ns_status_bar, setVisible: visible];
+      let _: () = msg_send![self.ns_status_bar.button(), highlight: highlighted];
     }
   }
 
@@ -215,6 +271,121 @@ fn make_tray_class() -> *const Class {
   unsafe { TRAY_CLASS }
 }
 
+/// Returns the id of the tray showing `menu`, which is the delegate of its top-level menu.
+pub(crate) unsafe fn tray_id_of_menu(mut menu: id) -> Option<TrayId> {
+  loop {
+    let supermenu: id = msg_send![menu, supermenu];
+    if supermenu == nil {
+      break;
+    }
+    menu = supermenu;
+  }
+  let delegate: id = msg_send![menu, delegate];
+  let is_tray_target: BOOL = msg_send![delegate, isKindOfClass: make_tray_class()];
+  if delegate != nil && is_tray_target == YES {
+    Some(TrayId(*(*delegate).get_ivar::<u16>("id")))
+  } else {
+    None
+  }
+}
+
+/// Create a `TaoTrayScrollView` class, which covers the status item button to report scrolling
+/// over it, and lets every other event through to the button.
+fn make_tray_scroll_view_class() -> *const Class {
+  static mut TRAY_SCROLL_VIEW_CLASS: *const Class = ptr::null();
+  static INIT: Once = Once::new();
+
+  INIT.call_once(|| unsafe {
+    let superclass = class!(NSView);
+    let mut decl = ClassDecl::new("TaoTrayScrollView", superclass).unwrap();
+    decl.add_ivar::<u16>("id");
+    // The fractions of a line scrolled since the last event.
+    decl.add_ivar::<f64>("scroll_x");
+    decl.add_ivar::<f64>("scroll_y");
+    decl.add_method(
+      sel!(hitTest:),
+      tray_scroll_view_hit_test as extern "C" fn(&Object, _, NSPoint) -> id,
+    );
+    decl.add_method(
+      sel!(scrollWheel:),
+      tray_scroll_wheel as extern "C" fn(&mut Object, _, id),
+    );
+
+    TRAY_SCROLL_VIEW_CLASS = decl.register();
+  });
+
+  unsafe { TRAY_SCROLL_VIEW_CLASS }
+}
+
+/// Queues a `TrayEvent` for the status item that `ns_event` happened on.
+unsafe fn queue_tray_event(id: u16, ns_event: id, tray_event: TrayEvent) {
+  // icon position & size
+  let window: id = msg_send![ns_event, window];
+  let frame = NSWindow::frame(window);
+  let scale_factor = NSWindow::backingScaleFactor(window) as f64;
+  let position: PhysicalPosition<f64> = LogicalPosition::new(
+    frame.origin.x as f64,
+    bottom_left_to_top_left_for_tray(frame),
+  )
+  .to_physical(scale_factor);
+
+  let logical: LogicalSize<f64> = (frame.size.width as f64, frame.size.height as f64).into();
+  let size: PhysicalSize<f64> = logical.to_physical(scale_factor);
+
+  // cursor position
+  let mouse_location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
+
+  let event = Event::TrayEvent {
+    id: TrayId(id),
+    bounds: Rectangle { position, size },
+    position: PhysicalPosition::new(
+      mouse_location.x,
+      bottom_left_to_top_left_for_cursor(mouse_location),
+    ),
+    event: tray_event,
+  };
+
+  AppState::queue_event(EventWrapper::StaticEvent(event));
+}
+
+extern "C" fn tray_scroll_view_hit_test(this: &Object, _: Sel, point: NSPoint) -> id {
+  unsafe {
+    let current_event: id = msg_send![NSApp(), currentEvent];
+    let event_type: NSEventType = msg_send![current_event, type];
+    if current_event != nil && event_type == NSEventType::NSScrollWheel {
+      msg_send![super(this, class!(NSView)), hitTest: point]
+    } else {
+      nil
+    }
+  }
+}
+
+extern "C" fn tray_scroll_wheel(this: &mut Object, _: Sel, event: id) {
+  unsafe {
+    // Trackpads report fractions of a line, which add up until a whole line is scrolled.
+    // `deltaX` is positive when scrolling left.
+    let delta_x: f64 = msg_send![event, deltaX];
+    let delta_y: f64 = msg_send![event, deltaY];
+    let scroll_x = *this.get_ivar::<f64>("scroll_x") - delta_x;
+    let scroll_y = *this.get_ivar::<f64>("scroll_y") + delta_y;
+    let (lines_x, lines_y) = (scroll_x.trunc(), scroll_y.trunc());
+    this.set_ivar("scroll_x", scroll_x - lines_x);
+    this.set_ivar("scroll_y", scroll_y - lines_y);
+
+    if lines_x != 0.0 || lines_y != 0.0 {
+      let id = *this.get_ivar::<u16>("id");
+      queue_tray_event(
+        id,
+        event,
+        TrayEvent::Scroll {
+          delta_x: lines_x as i32,
+          delta_y: lines_y as i32,
+        },
+      );
+    }
+  }
+}
+
 /// This will fire for an NSButton callback.
 extern "C" fn perform_tray_click(this: &mut Object, _: Sel, button: id) {
   unsafe {
@@ -222,21 +393,6 @@ extern "C" fn perform_tray_click(this: &mut Object, _: Sel, button: id) {
     let app: id = msg_send![class!(NSApplication), sharedApplication];
     let current_event: id = msg_send![app, currentEvent];
 
-    // icon position & size
-    let window: id = msg_send![current_event, window];
-    let frame = NSWindow::frame(window);
-    let scale_factor = NSWindow::backingScaleFactor(window) as f64;
-    let position: PhysicalPosition<f64> = LogicalPosition::new(
-      frame.origin.x as f64,
-      bottom_left_to_top_left_for_tray(frame),
-    )
-    .to_physical(scale_factor);
-
-    let logical: LogicalSize<f64> = (frame.size.width as f64, frame.size.height as f64).into();
-    let size: PhysicalSize<f64> = logical.to_physical(scale_factor);
-
-    // cursor position
-    let mouse_location: NSPoint = msg_send![class!(NSEvent), mouseLocation];
     // what type of click?
     let event_mask: NSEventType = msg_send![current_event, type];
     // grab the modifier flag, to make sure the ctrl + left click = right click
@@ -253,17 +409,7 @@ extern "C" fn perform_tray_click(this: &mut Object, _: Sel, button: id) {
     };
 
     if let Some(click_event) = click_type {
-      let event = Event::TrayEvent {
-        id: TrayId(*id as u16),
-        bounds: Rectangle { position, size },
-        position: PhysicalPosition::new(
-          mouse_location.x,
-          bottom_left_to_top_left_for_cursor(mouse_location),
-        ),
-        event: click_event,
-      };
-
-      AppState::queue_event(EventWrapper::StaticEvent(event));
+      queue_tray_event(*id, current_event, click_event);
 
       let menu = this.get_ivar::<id>("menu");
       if *menu != nil {
@@ -8,9 +8,9 @@ use std::{
 };
 
 use cocoa::{
-  appkit::{CGFloat, NSScreen, NSWindow, NSWindowStyleMask},
+  appkit::{CGFloat, NSApp, NSScreen, NSWindow, NSWindowStyleMask},
   base::{id, nil},
-  foundation::{NSPoint, NSSize, NSString},
+  foundation::{NSAutoreleasePool, NSPoint, NSSize, NSString},
 };
 use dispatch::Queue;
 use objc::{
@@ -24,11 +24,11 @@ use crate::{
   platform_impl::platform::{
     app_state::AppState,
     event::EventWrapper,
-    ffi,
+    ffi, keyboard_grab, progress_bar,
     util::IdRef,
-    window::{get_window_id, SharedState},
+    window::{self, get_window_id, SharedState, NS_NONACTIVATING_PANEL_MASK},
   },
-  window::WindowId,
+  window::{BlurEffect, ProgressBarState, Theme, WindowId, WindowLevel, RGBA},
 };
 
 // Unsafe wrapper type that allows us to dispatch things that aren't Send.
@@ -95,11 +95,11 @@ pub unsafe fn set_frame_top_left_point_async(ns_window: id, point: NSPoint) {
   });
 }
 
-// `setFrameTopLeftPoint:` isn't thread-safe, and fails silently.
-pub unsafe fn set_level_async(ns_window: id, level: ffi::NSWindowLevel) {
+// `setLevel:` and `setCollectionBehavior:` aren't thread-safe, and fail silently.
+pub unsafe fn set_window_level_async(ns_window: id, window_level: WindowLevel) {
   let ns_window = MainThreadSafe(ns_window);
   Queue::main().exec_async(move || {
-    ns_window.setLevel_(level as _);
+    window::set_window_level(*ns_window, window_level);
   });
 }
 
@@ -198,10 +198,42 @@ pub unsafe fn set_maximized_async(
 pub unsafe fn order_out_async(ns_window: id) {
   let ns_window = MainThreadSafe(ns_window);
   Queue::main().exec_async(move || {
-    ns_window.orderOut_(nil);
+    if !end_sheet(*ns_window) {
+      ns_window.orderOut_(nil);
+    }
+  });
+}
+
+/// Shows `ns_window` as a sheet on `owner`, unless it already is one.
+///
+/// Must be called on the main thread.
+pub unsafe fn begin_sheet(owner: id, ns_window: id) {
+  let sheet_parent: id = msg_send![ns_window, sheetParent];
+  if sheet_parent == nil {
+    let _: () = msg_send![owner, beginSheet: ns_window completionHandler: nil];
+  }
+}
+
+pub unsafe fn begin_sheet_async(owner: id, ns_window: id) {
+  let owner = MainThreadSafe(owner);
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    begin_sheet(*owner, *ns_window);
   });
 }
 
+/// Dismisses `ns_window` if it is shown as a sheet, returning whether it was.
+///
+/// Must be called on the main thread.
+unsafe fn end_sheet(ns_window: id) -> bool {
+  let sheet_parent: id = msg_send![ns_window, sheetParent];
+  if sheet_parent == nil {
+    return false;
+  }
+  let _: () = msg_send![sheet_parent, endSheet: ns_window];
+  true
+}
+
 // `makeKeyAndOrderFront:` isn't thread-safe. Calling it from another thread
 // actually works, but with an odd delay.
 pub unsafe fn make_key_and_order_front_async(ns_window: id) {
@@ -227,8 +259,89 @@ pub unsafe fn set_focus(ns_window: id) {
   let ns_window = MainThreadSafe(ns_window);
   Queue::main().exec_async(move || {
     ns_window.makeKeyAndOrderFront_(nil);
-    let app: id = msg_send![class!(NSApplication), sharedApplication];
-    let () = msg_send![app, activateIgnoringOtherApps: YES];
+    // Non-activating panels take the focus without activating the application.
+    if ns_window.styleMask().bits() & NS_NONACTIVATING_PANEL_MASK == 0 {
+      let app: id = msg_send![class!(NSApplication), sharedApplication];
+      let () = msg_send![app, activateIgnoringOtherApps: YES];
+    }
+  });
+}
+
+// `CGEventTap`s have to be managed on the main thread, where their callback runs.
+pub unsafe fn set_keyboard_grab_async(ns_window: id, grab: bool) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    keyboard_grab::set_keyboard_grab(*ns_window, grab);
+  });
+}
+
+// The dock tile can only be drawn on the main thread.
+pub unsafe fn set_progress_bar_async(progress: ProgressBarState) {
+  Queue::main().exec_async(move || {
+    progress_bar::set_progress_bar(progress);
+  });
+}
+
+pub unsafe fn set_badge_label_async(label: Option<String>) {
+  Queue::main().exec_async(move || {
+    let label = match label {
+      Some(label) => NSString::alloc(nil).init_str(&label).autorelease(),
+      None => nil,
+    };
+    let dock_tile: id = msg_send![NSApp(), dockTile];
+    let _: () = msg_send![dock_tile, setBadgeLabel: label];
+  });
+}
+
+pub unsafe fn set_maximizable_async(ns_window: id, maximizable: bool) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    window::set_maximizable(*ns_window, maximizable);
+  });
+}
+
+pub unsafe fn set_buttons_hidden_async(ns_window: id, hidden: bool) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    window::set_buttons_hidden(*ns_window, hidden);
+  });
+}
+
+pub unsafe fn set_blur_async(ns_window: id, effect: Option<BlurEffect>) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    window::set_blur(*ns_window, effect);
+  });
+}
+
+// `setBackgroundColor:` isn't thread-safe.
+pub unsafe fn set_background_color_async(ns_window: id, color: Option<RGBA>) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    window::set_background_color(*ns_window, color);
+  });
+}
+
+// `setAlphaValue:` isn't thread-safe.
+pub unsafe fn set_alpha_value_async(ns_window: id, alpha_value: CGFloat) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    let _: () = msg_send![*ns_window, setAlphaValue: alpha_value];
+  });
+}
+
+// `setAppearance:` isn't thread-safe.
+pub unsafe fn set_theme_async(ns_window: id, theme: Option<Theme>) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    window::set_appearance(*ns_window, theme);
+  });
+}
+
+pub unsafe fn set_drop_highlight_async(ns_window: id, highlight: bool) {
+  let ns_window = MainThreadSafe(ns_window);
+  Queue::main().exec_async(move || {
+    window::set_drop_highlight(*ns_window, highlight);
   });
 }
 
@@ -241,6 +354,7 @@ pub unsafe fn close_async(ns_window: IdRef) {
   let ns_window = MainThreadSafe(ns_window);
   Queue::main().exec_async(move || {
     autoreleasepool(move || {
+      end_sheet(**ns_window);
       ns_window.close();
       let event = Event::WindowEvent {
         window_id: WindowId(get_window_id(*ns_window.0)),
@@ -5,17 +5,22 @@
 use cocoa::{
   appkit::NSImage,
   base::{id, nil},
-  foundation::{NSDictionary, NSPoint, NSString},
+  foundation::{NSDictionary, NSInteger, NSPoint, NSSize, NSString},
 };
 use objc::runtime::{Sel, NO};
-use std::cell::RefCell;
+use std::{cell::RefCell, ptr};
 
-use crate::window::CursorIcon;
+use super::IdRef;
+use crate::{
+  platform_impl::platform::clipboard::{new_rgba_image_rep, ALPHA_NONPREMULTIPLIED_FORMAT},
+  window::{CursorIcon, CursorImage},
+};
 
 pub enum Cursor {
   Native(&'static str),
   Undocumented(&'static str),
   WebKit(&'static str),
+  Custom(IdRef),
 }
 
 impl From<CursorIcon> for Cursor {
@@ -68,6 +73,10 @@ impl From<CursorIcon> for Cursor {
       // https://stackoverflow.com/a/21786835/5435443
       CursorIcon::Move | CursorIcon::AllScroll => Cursor::WebKit("move"),
       CursorIcon::Cell => Cursor::WebKit("cell"),
+
+      CursorIcon::Custom(cursor) => {
+        Cursor::Custom(IdRef::new(unsafe { custom_cursor(&cursor.image) }))
+      }
     }
   }
 }
@@ -97,10 +106,36 @@ impl Cursor {
         msg_send![class, performSelector: sel]
       }
       Cursor::WebKit(cursor_name) => load_webkit_cursor(cursor_name),
+      Cursor::Custom(cursor) => **cursor,
     }
   }
 }
 
+/// Creates a cursor drawing `image` at its pixel size.
+unsafe fn custom_cursor(image: &CursorImage) -> id {
+  let (width, height) = (
+    image.rgba.width as NSInteger,
+    image.rgba.height as NSInteger,
+  );
+  let rep = new_rgba_image_rep(width, height, ALPHA_NONPREMULTIPLIED_FORMAT);
+  let bitmap_data: *mut u8 = msg_send![rep, bitmapData];
+  ptr::copy_nonoverlapping(image.rgba.rgba.as_ptr(), bitmap_data, image.rgba.rgba.len());
+
+  let ns_image = NSImage::initWithSize_(
+    NSImage::alloc(nil),
+    NSSize::new(width as f64, height as f64),
+  );
+  let _: () = msg_send![ns_image, addRepresentation: rep];
+  let _: () = msg_send![rep, release];
+  let cursor: id = msg_send![class!(NSCursor), alloc];
+  let cursor: id = msg_send![cursor,
+    initWithImage: ns_image
+    hotSpot: NSPoint::new(image.hotspot_x as f64, image.hotspot_y as f64)
+  ];
+  let _: () = msg_send![ns_image, release];
+  cursor
+}
+
 // Note that loading `busybutclickable` with this code won't animate the frames;
 // instead you'll just get them all in a column.
 pub unsafe fn load_webkit_cursor(cursor_name: &str) -> id {
@@ -13,14 +13,14 @@ use std::{
 };
 
 use cocoa::{
-  appkit::{NSApp, NSWindowStyleMask},
+  appkit::{CGFloat, NSApp, NSWindowStyleMask},
   base::{id, nil},
   foundation::{NSAutoreleasePool, NSPoint, NSRect, NSString, NSUInteger},
 };
 use core_graphics::display::CGDisplay;
-use objc::runtime::{Class, Object, Sel, BOOL, YES};
+use objc::runtime::{Class, Object, Sel, BOOL, NO, YES};
 
-use crate::{dpi::LogicalPosition, platform_impl::platform::ffi};
+use crate::{dpi::LogicalPosition, platform_impl::platform::ffi, window::RGBA};
 
 // Replace with `!` once stable
 #[derive(Debug)]
@@ -144,6 +144,34 @@ pub unsafe fn app_name() -> Option<id> {
   }
 }
 
+/// Returns the accent color chosen in the System Settings, on macOS 10.14 and later.
+pub fn accent_color() -> Option<RGBA> {
+  unsafe {
+    let has_accent_color: BOOL =
+      msg_send![class!(NSColor), respondsToSelector: sel!(controlAccentColor)];
+    if has_accent_color == NO {
+      return None;
+    }
+    let color: id = msg_send![class!(NSColor), controlAccentColor];
+    let color_space: id = msg_send![class!(NSColorSpace), sRGBColorSpace];
+    let color: id = msg_send![color, colorUsingColorSpace: color_space];
+    if color == nil {
+      return None;
+    }
+    let component = |value: CGFloat| (value.clamp(0., 1.) * 255.).round() as u8;
+    let red: CGFloat = msg_send![color, redComponent];
+    let green: CGFloat = msg_send![color, greenComponent];
+    let blue: CGFloat = msg_send![color, blueComponent];
+    let alpha: CGFloat = msg_send![color, alphaComponent];
+    Some((
+      component(red),
+      component(green),
+      component(blue),
+      component(alpha),
+    ))
+  }
+}
+
 pub unsafe fn superclass<'a>(this: &'a Object) -> &'a Class {
   let superclass: *const Class = msg_send![this, superclass];
   &*superclass
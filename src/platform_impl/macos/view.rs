@@ -4,10 +4,11 @@
 
 use std::{
   boxed::Box,
-  collections::{HashSet, VecDeque},
+  collections::{HashMap, HashSet, VecDeque},
   os::raw::*,
   ptr, slice, str,
   sync::{Arc, Mutex, Weak},
+  time::Instant,
 };
 
 use cocoa::{
@@ -21,20 +22,23 @@ use objc::{
 };
 
 use crate::{
-  dpi::LogicalPosition,
+  dpi::{LogicalPosition, LogicalSize},
   event::{
-    DeviceEvent, ElementState, Event, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+    DeviceEvent, ElementState, Event, Ime, KeyEvent, MouseButton, MouseScrollDelta, PenInput,
+    TouchPhase, WindowEvent,
   },
   keyboard::{KeyCode, ModifiersState},
   platform_impl::platform::{
     app_state::AppState,
-    event::{code_to_key, create_key_event, event_mods, get_scancode, EventWrapper},
+    event::{
+      code_to_key, create_key_event, event_mods, event_timestamp, get_scancode, EventWrapper,
+    },
     ffi::*,
     util::{self, IdRef},
     window::get_window_id,
     DEVICE_ID,
   },
-  window::WindowId,
+  window::{DragData, WindowId},
 };
 
 pub struct CursorState {
@@ -54,11 +58,14 @@ impl Default for CursorState {
 pub(super) struct ViewState {
   ns_window: id,
   pub cursor_state: Arc<Mutex<CursorState>>,
-  ime_spot: Option<(f64, f64)>,
+  /// The IME cursor area in screen coordinates.
+  ime_cursor_area: Option<NSRect>,
 
   /// This is true when we are currently modifying a marked text
   /// using ime. When the text gets commited, this is set to false.
   in_ime_preedit: bool,
+  /// Whether the key presses are interpreted by the input method.
+  ime_allowed: bool,
 
   /// This is used to detect if a key-press causes an ime event.
   /// If a key-press does not cause an ime event, that means
@@ -69,13 +76,33 @@ pub(super) struct ViewState {
   is_key_down: bool,
   pub(super) modifiers: ModifiersState,
   phys_modifiers: HashSet<KeyCode>,
+  /// The key down events of the keys held, to release them when the window loses focus.
+  pressed_keys: HashMap<c_ushort, IdRef>,
+  /// Whether the pen in proximity of the tablet is an eraser.
+  pen_eraser: bool,
   tracking_rect: Option<NSInteger>,
+  /// Whether the cursor is inside of the view, as last reported by `CursorEntered`/`CursorLeft`.
+  cursor_in_view: bool,
 }
 
 impl ViewState {
   fn get_scale_factor(&self) -> f64 {
     (unsafe { NSWindow::backingScaleFactor(self.ns_window) }) as f64
   }
+
+  /// Returns the release events of the keys held, which the window won't receive once it isn't
+  /// the key window anymore.
+  pub(super) fn release_pressed_keys(&mut self) -> Vec<KeyEvent> {
+    self
+      .pressed_keys
+      .drain()
+      .map(|(_, event)| {
+        let mut event = create_key_event(*event, false, false, false, None);
+        event.timestamp = Instant::now();
+        event
+      })
+      .collect()
+  }
 }
 
 pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
@@ -84,13 +111,17 @@ pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
   let state = ViewState {
     ns_window,
     cursor_state,
-    ime_spot: None,
+    ime_cursor_area: None,
     in_ime_preedit: false,
+    ime_allowed: true,
     key_triggered_ime: false,
     is_key_down: false,
     modifiers: Default::default(),
     phys_modifiers: Default::default(),
+    pressed_keys: Default::default(),
+    pen_eraser: false,
     tracking_rect: None,
+    cursor_in_view: false,
   };
   unsafe {
     // This is free'd in `dealloc`
@@ -103,17 +134,128 @@ pub fn new_view(ns_window: id) -> (IdRef, Weak<Mutex<CursorState>>) {
   }
 }
 
-pub unsafe fn set_ime_position(ns_view: id, input_context: id, x: f64, y: f64) {
+pub unsafe fn set_ime_cursor_area(
+  ns_view: id,
+  input_context: id,
+  position: LogicalPosition<f64>,
+  size: LogicalSize<f64>,
+) {
   let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("taoState");
   let state = &mut *(state_ptr as *mut ViewState);
   let content_rect =
     NSWindow::contentRectForFrameRect_(state.ns_window, NSWindow::frame(state.ns_window));
   let base_x = content_rect.origin.x as f64;
   let base_y = (content_rect.origin.y + content_rect.size.height) as f64;
-  state.ime_spot = Some((base_x + x, base_y - y));
+  // Screen coordinates have their origin at the bottom left.
+  state.ime_cursor_area = Some(NSRect::new(
+    NSPoint::new(
+      (base_x + position.x) as _,
+      (base_y - position.y - size.height) as _,
+    ),
+    NSSize::new(size.width as _, size.height as _),
+  ));
   let _: () = msg_send![input_context, invalidateCharacterCoordinates];
 }
 
+pub unsafe fn reset_dead_keys(ns_view: id) {
+  // `unmarkText` clears our marked text and discards the one held by the input context, ending
+  // the composition.
+  let _: () = msg_send![ns_view, unmarkText];
+}
+
+pub unsafe fn set_ime_allowed(ns_view: id, allowed: bool) {
+  let state_ptr: *mut c_void = *(*ns_view).get_mut_ivar("taoState");
+  let state = &mut *(state_ptr as *mut ViewState);
+  if state.ime_allowed == allowed {
+    return;
+  }
+  state.ime_allowed = allowed;
+  if !allowed {
+    let _: () = msg_send![ns_view, unmarkText];
+  }
+}
+
+/// Starts dragging `data` out of `ns_view`, which is the source of the dragging session.
+pub unsafe fn start_drag(ns_view: id, data: &DragData) {
+  const ICON_SIZE: f64 = 32.0;
+
+  let event: id = msg_send![NSApp(), currentEvent];
+  if event == nil {
+    return;
+  }
+  let location: NSPoint = msg_send![ns_view, convertPoint: event.locationInWindow() fromView: nil];
+  let frame = NSRect::new(
+    NSPoint::new(location.x - ICON_SIZE / 2.0, location.y - ICON_SIZE / 2.0),
+    NSSize::new(ICON_SIZE, ICON_SIZE),
+  );
+
+  let items: id = msg_send![class!(NSMutableArray), array];
+  let add_item = |writer: id, image: id| {
+    let item: id = msg_send![class!(NSDraggingItem), alloc];
+    let item: id = msg_send![item, initWithPasteboardWriter: writer];
+    let _: () = msg_send![item, setDraggingFrame: frame contents: image];
+    let _: () = msg_send![items, addObject: item];
+    let _: () = msg_send![item, release];
+  };
+  match data {
+    DragData::Files(paths) => {
+      let workspace: id = msg_send![class!(NSWorkspace), sharedWorkspace];
+      for path in paths {
+        let path = NSString::alloc(nil).init_str(&path.to_string_lossy());
+        let url: id = msg_send![class!(NSURL), fileURLWithPath: path];
+        let icon: id = msg_send![workspace, iconForFile: path];
+        add_item(url, icon);
+        let _: () = msg_send![path, release];
+      }
+    }
+    DragData::Text(text) => {
+      let text = NSString::alloc(nil).init_str(text);
+      add_item(text, nil);
+      let _: () = msg_send![text, release];
+    }
+  }
+
+  let _: id = msg_send![ns_view, beginDraggingSessionWithItems: items event: event source: ns_view];
+}
+
+extern "C" fn dragging_source_operation_mask(
+  _this: &Object,
+  _sel: Sel,
+  _session: id,
+  _context: NSInteger,
+) -> NSUInteger {
+  // NSDragOperationCopy
+  1
+}
+
+fn queue_ime_event(state: &ViewState, ime: Ime) {
+  AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
+    window_id: WindowId(get_window_id(state.ns_window)),
+    event: WindowEvent::Ime(ime),
+  }));
+}
+
+/// Ends the composition of `state`, if any.
+fn end_ime_preedit(state: &mut ViewState) {
+  if state.in_ime_preedit {
+    state.in_ime_preedit = false;
+    queue_ime_event(state, Ime::Preedit(String::new(), None));
+    queue_ime_event(state, Ime::Disabled);
+  }
+}
+
+/// Converts an offset in UTF-16 code units to one in bytes.
+fn utf16_to_byte_offset(text: &str, offset: usize) -> usize {
+  let mut units = 0;
+  for (index, c) in text.char_indices() {
+    if units >= offset {
+      return index;
+    }
+    units += c.len_utf16();
+  }
+  text.len()
+}
+
 fn is_arrow_key(keycode: KeyCode) -> bool {
   matches!(
     keycode,
@@ -275,6 +417,22 @@ lazy_static! {
       sel!(scrollWheel:),
       scroll_wheel as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(magnifyWithEvent:),
+      magnify_with_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(smartMagnifyWithEvent:),
+      smart_magnify_with_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(rotateWithEvent:),
+      rotate_with_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(tabletProximity:),
+      tablet_proximity as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_method(
       sel!(pressureChangeWithEvent:),
       pressure_change_with_event as extern "C" fn(&Object, Sel, id),
@@ -295,6 +453,10 @@ lazy_static! {
       sel!(acceptsFirstMouse:),
       accepts_first_mouse as extern "C" fn(&Object, Sel, id) -> BOOL,
     );
+    decl.add_method(
+      sel!(draggingSession:sourceOperationMaskForDraggingContext:),
+      dragging_source_operation_mask as extern "C" fn(&Object, Sel, id, NSInteger) -> NSUInteger,
+    );
     decl.add_ivar::<*mut c_void>("taoState");
     decl.add_ivar::<id>("markedText");
     let protocol = Protocol::get("NSTextInputClient").unwrap();
@@ -457,23 +619,46 @@ extern "C" fn set_marked_text(
   this: &mut Object,
   _sel: Sel,
   string: id,
-  _selected_range: NSRange,
+  selected_range: NSRange,
   _replacement_range: NSRange,
 ) {
   trace!("Triggered `setMarkedText`");
   unsafe {
     let marked_text_ref = clear_marked_text(this);
     let has_attr: BOOL = msg_send![string, isKindOfClass: class!(NSAttributedString)];
-    if has_attr != NO {
+    let characters = if has_attr != NO {
       marked_text_ref.initWithAttributedString(string);
+      msg_send![string, string]
     } else {
       marked_text_ref.initWithString(string);
+      string
     };
+    let text = util::ns_string_to_rust(characters);
 
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
-    state.in_ime_preedit = true;
     state.key_triggered_ime = true;
+    if text.is_empty() {
+      // The composition was cleared, e.g. with backspace.
+      end_ime_preedit(state);
+    } else {
+      if !state.in_ime_preedit {
+        state.in_ime_preedit = true;
+        queue_ime_event(state, Ime::Enabled);
+      }
+      // The selected range is in UTF-16 code units.
+      let cursor = if selected_range.location == NSNotFound as NSUInteger {
+        None
+      } else {
+        let start = selected_range.location as usize;
+        let end = start + selected_range.length as usize;
+        Some((
+          utf16_to_byte_offset(&text, start),
+          utf16_to_byte_offset(&text, end),
+        ))
+      };
+      queue_ime_event(state, Ime::Preedit(text, cursor));
+    }
   }
   trace!("Completed `setMarkedText`");
 }
@@ -484,6 +669,10 @@ extern "C" fn unmark_text(this: &mut Object, _sel: Sel) {
     clear_marked_text(this);
     let input_context: id = msg_send![this, inputContext];
     let _: () = msg_send![input_context, discardMarkedText];
+
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    end_ime_preedit(state);
   }
   trace!("Completed `unmarkText`");
 }
@@ -521,15 +710,15 @@ extern "C" fn first_rect_for_character_range(
     trace!("Triggered `firstRectForCharacterRange`");
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
-    let (x, y) = state.ime_spot.unwrap_or_else(|| {
+    let rect = state.ime_cursor_area.unwrap_or_else(|| {
       let content_rect =
         NSWindow::contentRectForFrameRect_(state.ns_window, NSWindow::frame(state.ns_window));
       let x = content_rect.origin.x;
       let y = util::bottom_left_to_top_left(content_rect);
-      (x, y)
+      NSRect::new(NSPoint::new(x as _, y as _), NSSize::new(0.0, 0.0))
     });
     trace!("Completed `firstRectForCharacterRange`");
-    NSRect::new(NSPoint::new(x as _, y as _), NSSize::new(0.0, 0.0))
+    rect
   }
 }
 
@@ -560,11 +749,14 @@ extern "C" fn insert_text(this: &Object, _sel: Sel, string: id, _replacement_ran
 
     AppState::queue_event(EventWrapper::StaticEvent(Event::WindowEvent {
       window_id: WindowId(get_window_id(state.ns_window)),
-      event: WindowEvent::ReceivedImeText(string),
+      event: WindowEvent::ReceivedImeText(string.clone()),
     }));
     if state.in_ime_preedit {
       state.in_ime_preedit = false;
       state.key_triggered_ime = true;
+      queue_ime_event(state, Ime::Preedit(String::new(), None));
+      queue_ime_event(state, Ime::Commit(string));
+      queue_ime_event(state, Ime::Disabled);
     }
   }
   trace!("Completed `insertText`");
@@ -696,11 +888,19 @@ extern "C" fn key_down(this: &mut Object, _sel: Sel, event: id) {
 
     update_potentially_stale_modifiers(state, event);
 
+    if !is_repeat {
+      state
+        .pressed_keys
+        .insert(get_scancode(event), IdRef::retain(event));
+    }
+
+    // Reset before `interpretKeyEvents`, so that a key event that doesn't go through the IME
+    // isn't reported as part of the previous composition.
+    state.key_triggered_ime = false;
     let pass_along = !is_repeat || !state.is_key_down;
-    if pass_along {
+    if pass_along && state.ime_allowed {
       // See below for why we do this.
       clear_marked_text(this);
-      state.key_triggered_ime = false;
 
       // Some keys (and only *some*, with no known reason) don't trigger `insertText`, while others do...
       // So, we don't give repeats the opportunity to trigger that, since otherwise our hack will cause some
@@ -724,7 +924,6 @@ extern "C" fn key_down(this: &mut Object, _sel: Sel, event: id) {
       if is_preediting && !state.key_triggered_ime && !is_arrow_key {
         // In this case we should cancel the IME session.
         let () = msg_send![this, unmarkText];
-        state.in_ime_preedit = false;
       }
     }
     let window_event = Event::WindowEvent {
@@ -747,6 +946,7 @@ extern "C" fn key_up(this: &Object, _sel: Sel, event: id) {
     let state = &mut *(state_ptr as *mut ViewState);
 
     state.is_key_down = false;
+    state.pressed_keys.remove(&get_scancode(event));
 
     update_potentially_stale_modifiers(state, event);
 
@@ -952,6 +1152,7 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
         device_id: DEVICE_ID,
         state: button_state,
         button,
+        timestamp: event_timestamp(event),
         modifiers: event_mods(event),
       },
     };
@@ -962,11 +1163,13 @@ fn mouse_click(this: &Object, event: id, button: MouseButton, button_state: Elem
 
 extern "C" fn mouse_down(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
+  pen_input(this, event, TouchPhase::Started);
   mouse_click(this, event, MouseButton::Left, ElementState::Pressed);
 }
 
 extern "C" fn mouse_up(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
+  pen_input(this, event, TouchPhase::Ended);
   mouse_click(this, event, MouseButton::Left, ElementState::Released);
 }
 
@@ -1002,11 +1205,16 @@ fn mouse_motion(this: &Object, event: id) {
     let view_point = view.convertPoint_fromView_(window_point, nil);
     let view_rect = NSView::frame(view);
 
-    if view_point.x.is_sign_negative()
+    let in_view = !(view_point.x.is_sign_negative()
       || view_point.y.is_sign_negative()
       || view_point.x > view_rect.size.width
-      || view_point.y > view_rect.size.height
-    {
+      || view_point.y > view_rect.size.height);
+
+    // The tracking rect doesn't report crossing its bounds while a button is held, so keep
+    // `CursorEntered` and `CursorLeft` in line with the position during drags.
+    set_cursor_in_view(state, in_view);
+
+    if !in_view {
       let mouse_buttons_down: NSUInteger = msg_send![class!(NSEvent), pressedMouseButtons];
       if mouse_buttons_down == 0 {
         // Point is outside of the client area (view) and no buttons are pressed
@@ -1025,6 +1233,7 @@ fn mouse_motion(this: &Object, event: id) {
       event: WindowEvent::CursorMoved {
         device_id: DEVICE_ID,
         position: logical_position.to_physical(state.get_scale_factor()),
+        timestamp: event_timestamp(event),
         modifiers: event_mods(event),
       },
     };
@@ -1035,10 +1244,78 @@ fn mouse_motion(this: &Object, event: id) {
 
 extern "C" fn mouse_moved(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
+  pen_input(this, event, TouchPhase::Moved);
 }
 
 extern "C" fn mouse_dragged(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
+  pen_input(this, event, TouchPhase::Moved);
+}
+
+/// `NSEventSubtypeTabletPoint`, the subtype of the mouse events sent by a pen.
+const TABLET_POINT_EVENT_SUBTYPE: i16 = 1;
+/// `NSEventSubtypeTabletProximity`
+const TABLET_PROXIMITY_EVENT_SUBTYPE: i16 = 2;
+/// `NSPointingDeviceTypeEraser`
+const ERASER_POINTING_DEVICE: NSUInteger = 3;
+/// `NSEventButtonMaskPenLowerSide | NSEventButtonMaskPenUpperSide`
+const PEN_BARREL_BUTTON_MASK: NSUInteger = 2 | 4;
+/// The tilt is normalized, assume it's the one of a Wacom pen, which goes up to 60°.
+const MAX_PEN_TILT: f64 = 60.0;
+
+extern "C" fn tablet_proximity(this: &Object, _sel: Sel, event: id) {
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+    let device_type: NSUInteger = msg_send![event, pointingDeviceType];
+    state.pen_eraser = device_type == ERASER_POINTING_DEVICE;
+  }
+}
+
+/// Emits `PenInput` if `event` was sent by a pen.
+fn pen_input(this: &Object, event: id, phase: TouchPhase) {
+  unsafe {
+    let subtype: i16 = msg_send![event, subtype];
+    if subtype == TABLET_PROXIMITY_EVENT_SUBTYPE {
+      tablet_proximity(this, sel!(tabletProximity:), event);
+      return;
+    }
+    if subtype != TABLET_POINT_EVENT_SUBTYPE {
+      return;
+    }
+
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let view: id = this as *const _ as *mut _;
+    let view_point = view.convertPoint_fromView_(event.locationInWindow(), nil);
+    let view_rect = NSView::frame(view);
+    let location = LogicalPosition::new(
+      view_point.x as f64,
+      view_rect.size.height as f64 - view_point.y as f64,
+    );
+
+    let tilt: NSPoint = msg_send![event, tilt];
+    let rotation: f32 = msg_send![event, rotation];
+    let button_mask: NSUInteger = msg_send![event, buttonMask];
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::PenInput(PenInput {
+        device_id: DEVICE_ID,
+        phase,
+        location: location.to_physical(state.get_scale_factor()),
+        pressure: event.pressure() as f64,
+        // The y axis points away from the user.
+        tilt: Some((tilt.x as f64 * MAX_PEN_TILT, -tilt.y as f64 * MAX_PEN_TILT)),
+        // Counterclockwise.
+        twist: Some((360.0 - rotation as f64) % 360.0),
+        eraser: state.pen_eraser,
+        barrel_button: button_mask & PEN_BARREL_BUTTON_MASK != 0,
+      }),
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
 }
 
 extern "C" fn right_mouse_dragged(this: &Object, _sel: Sel, event: id) {
@@ -1049,20 +1326,37 @@ extern "C" fn other_mouse_dragged(this: &Object, _sel: Sel, event: id) {
   mouse_motion(this, event);
 }
 
+/// Emits `CursorEntered` or `CursorLeft` if the cursor crossed the view bounds since the last call.
+fn set_cursor_in_view(state: &mut ViewState, in_view: bool) {
+  if state.cursor_in_view == in_view {
+    return;
+  }
+  state.cursor_in_view = in_view;
+
+  let event = if in_view {
+    WindowEvent::CursorEntered {
+      device_id: DEVICE_ID,
+    }
+  } else {
+    WindowEvent::CursorLeft {
+      device_id: DEVICE_ID,
+    }
+  };
+  let window_event = Event::WindowEvent {
+    window_id: WindowId(get_window_id(state.ns_window)),
+    event,
+  };
+
+  AppState::queue_event(EventWrapper::StaticEvent(window_event));
+}
+
 extern "C" fn mouse_entered(this: &Object, _sel: Sel, _event: id) {
   trace!("Triggered `mouseEntered`");
   unsafe {
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
 
-    let enter_event = Event::WindowEvent {
-      window_id: WindowId(get_window_id(state.ns_window)),
-      event: WindowEvent::CursorEntered {
-        device_id: DEVICE_ID,
-      },
-    };
-
-    AppState::queue_event(EventWrapper::StaticEvent(enter_event));
+    set_cursor_in_view(state, true);
   }
   trace!("Completed `mouseEntered`");
 }
@@ -1073,14 +1367,7 @@ extern "C" fn mouse_exited(this: &Object, _sel: Sel, _event: id) {
     let state_ptr: *mut c_void = *this.get_ivar("taoState");
     let state = &mut *(state_ptr as *mut ViewState);
 
-    let window_event = Event::WindowEvent {
-      window_id: WindowId(get_window_id(state.ns_window)),
-      event: WindowEvent::CursorLeft {
-        device_id: DEVICE_ID,
-      },
-    };
-
-    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+    set_cursor_in_view(state, false);
   }
   trace!("Completed `mouseExited`");
 }
@@ -1104,10 +1391,21 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
         MouseScrollDelta::LineDelta(x as f32, y as f32)
       }
     };
-    let phase = match event.phase() {
-      NSEventPhase::NSEventPhaseMayBegin | NSEventPhase::NSEventPhaseBegan => TouchPhase::Started,
-      NSEventPhase::NSEventPhaseEnded => TouchPhase::Ended,
-      _ => TouchPhase::Moved,
+    // Momentum events follow the end of the gesture, with no phase of their own.
+    let momentum_phase = event.momentumPhase();
+    let inertial = !momentum_phase.is_empty();
+    let phase = if inertial {
+      match momentum_phase {
+        NSEventPhase::NSEventPhaseEnded | NSEventPhase::NSEventPhaseCancelled => TouchPhase::Ended,
+        _ => TouchPhase::Moved,
+      }
+    } else {
+      match event.phase() {
+        NSEventPhase::NSEventPhaseMayBegin | NSEventPhase::NSEventPhaseBegan => TouchPhase::Started,
+        NSEventPhase::NSEventPhaseEnded => TouchPhase::Ended,
+        NSEventPhase::NSEventPhaseCancelled => TouchPhase::Cancelled,
+        _ => TouchPhase::Moved,
+      }
     };
 
     let device_event = Event::DeviceEvent {
@@ -1126,6 +1424,8 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
         device_id: DEVICE_ID,
         delta,
         phase,
+        inertial,
+        timestamp: event_timestamp(event),
         modifiers: event_mods(event),
       },
     };
@@ -1136,6 +1436,73 @@ extern "C" fn scroll_wheel(this: &Object, _sel: Sel, event: id) {
   trace!("Completed `scrollWheel`");
 }
 
+fn gesture_phase(event: id) -> TouchPhase {
+  match unsafe { event.phase() } {
+    NSEventPhase::NSEventPhaseBegan => TouchPhase::Started,
+    NSEventPhase::NSEventPhaseEnded => TouchPhase::Ended,
+    NSEventPhase::NSEventPhaseCancelled => TouchPhase::Cancelled,
+    _ => TouchPhase::Moved,
+  }
+}
+
+extern "C" fn magnify_with_event(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `magnifyWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::TouchpadMagnify {
+        device_id: DEVICE_ID,
+        delta: event.magnification(),
+        phase: gesture_phase(event),
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `magnifyWithEvent`");
+}
+
+extern "C" fn smart_magnify_with_event(this: &Object, _sel: Sel, _event: id) {
+  trace!("Triggered `smartMagnifyWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::SmartMagnify {
+        device_id: DEVICE_ID,
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `smartMagnifyWithEvent`");
+}
+
+extern "C" fn rotate_with_event(this: &Object, _sel: Sel, event: id) {
+  trace!("Triggered `rotateWithEvent`");
+  unsafe {
+    let state_ptr: *mut c_void = *this.get_ivar("taoState");
+    let state = &mut *(state_ptr as *mut ViewState);
+
+    let window_event = Event::WindowEvent {
+      window_id: WindowId(get_window_id(state.ns_window)),
+      event: WindowEvent::TouchpadRotate {
+        device_id: DEVICE_ID,
+        delta: event.rotation(),
+        phase: gesture_phase(event),
+      },
+    };
+
+    AppState::queue_event(EventWrapper::StaticEvent(window_event));
+  }
+  trace!("Completed `rotateWithEvent`");
+}
+
 extern "C" fn pressure_change_with_event(this: &Object, _sel: Sel, event: id) {
   trace!("Triggered `pressureChangeWithEvent`");
 
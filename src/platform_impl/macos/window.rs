@@ -5,7 +5,7 @@
 use std::{
   collections::VecDeque,
   convert::TryInto,
-  f64,
+  f64, mem,
   os::raw::c_void,
   sync::{
     atomic::{AtomicBool, Ordering},
@@ -22,31 +22,36 @@ use crate::{
     LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size, Size::Logical,
   },
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
+  event::Rectangle,
+  event_loop::DeviceEventFilter,
   icon::Icon,
   monitor::{MonitorHandle as RootMonitorHandle, VideoMode as RootVideoMode},
   platform::macos::WindowExtMacOS,
   platform_impl::platform::{
     app_state::AppState,
-    ffi, menu,
+    device_events, display_link, ffi, menu,
     monitor::{self, MonitorHandle, VideoMode},
     util::{self, IdRef},
     view::{self, new_view, CursorState},
-    window_delegate::new_delegate,
+    window_delegate::{dragged_types, new_delegate},
     OsError,
   },
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    BlurEffect, CursorGrabMode, CursorIcon, DragData, Fullscreen, ProgressBarState,
+    ResizeDirection, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    WindowLevel, RGBA,
   },
 };
 use cocoa::{
   appkit::{
     self, CGFloat, NSApp, NSApplication, NSApplicationPresentationOptions, NSColor, NSEvent,
-    NSRequestUserAttentionType, NSScreen, NSView, NSWindow, NSWindowButton, NSWindowOrderingMode,
-    NSWindowStyleMask,
+    NSRequestUserAttentionType, NSScreen, NSView, NSWindow, NSWindowButton,
+    NSWindowCollectionBehavior, NSWindowOrderingMode, NSWindowStyleMask,
   },
   base::{id, nil},
   foundation::{
-    NSArray, NSAutoreleasePool, NSDictionary, NSPoint, NSRect, NSSize, NSString, NSUInteger,
+    NSArray, NSAutoreleasePool, NSDictionary, NSInteger, NSPoint, NSRect, NSSize, NSString,
+    NSUInteger,
   },
 };
 use core_graphics::display::{CGDisplay, CGDisplayMode};
@@ -55,7 +60,19 @@ use objc::{
   runtime::{Class, Object, Sel, BOOL, NO, YES},
 };
 
+#[cfg(feature = "accesskit")]
+use super::accessibility;
 use super::{util::ns_string_to_rust, Menu};
+#[cfg(feature = "accesskit")]
+use dispatch::Queue;
+
+// `NSWindowSharingType` values, which are missing from `cocoa`.
+const NS_WINDOW_SHARING_NONE: NSUInteger = 0;
+const NS_WINDOW_SHARING_READ_ONLY: NSUInteger = 1;
+
+// `NSPanel` style mask values, which are missing from `cocoa`.
+const NS_UTILITY_WINDOW_MASK: NSUInteger = 1 << 4;
+pub(crate) const NS_NONACTIVATING_PANEL_MASK: NSUInteger = 1 << 7;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Id(pub usize);
@@ -91,8 +108,13 @@ pub struct PlatformSpecificWindowBuilderAttributes {
   pub resize_increments: Option<LogicalSize<f64>>,
   pub disallow_hidpi: bool,
   pub has_shadow: bool,
+  pub tabbing_identifier: Option<String>,
+  pub panel: bool,
 }
 
+// The parent window is only used on the main thread, when the window is created.
+unsafe impl Send for PlatformSpecificWindowBuilderAttributes {}
+
 impl Default for PlatformSpecificWindowBuilderAttributes {
   #[inline]
   fn default() -> Self {
@@ -107,6 +129,8 @@ impl Default for PlatformSpecificWindowBuilderAttributes {
       resize_increments: None,
       disallow_hidpi: false,
       has_shadow: true,
+      tabbing_identifier: None,
+      panel: false,
     }
   }
 }
@@ -151,10 +175,16 @@ fn create_window(
       Some(Fullscreen::Borderless(None)) => Some(appkit::NSScreen::mainScreen(nil)),
       None => None,
     };
+    let placement_screen = attrs
+      .position_on_monitor
+      .as_ref()
+      .and_then(|(monitor, placement)| Some((monitor.inner.ns_screen()?, *placement)));
     let frame = match screen {
       Some(screen) => NSScreen::frame(screen),
       None => {
-        let screen = NSScreen::mainScreen(nil);
+        let screen = placement_screen
+          .map(|(screen, _)| screen)
+          .unwrap_or_else(|| NSScreen::mainScreen(nil));
         let scale_factor = NSScreen::backingScaleFactor(screen) as f64;
         let (width, height) = match attrs.inner_size {
           Some(size) => {
@@ -194,12 +224,31 @@ fn create_window(
     if !attrs.resizable {
       masks &= !NSWindowStyleMask::NSResizableWindowMask;
     }
+    if !attrs.minimizable {
+      masks &= !NSWindowStyleMask::NSMiniaturizableWindowMask;
+    }
+    if !attrs.closable {
+      masks &= !NSWindowStyleMask::NSClosableWindowMask;
+    }
 
     if pl_attrs.fullsize_content_view {
       masks |= NSWindowStyleMask::NSFullSizeContentViewWindowMask;
     }
 
-    let ns_window: id = msg_send![WINDOW_CLASS.0, alloc];
+    let window_class = match attrs.tool_window {
+      Some(_) => {
+        masks = NSWindowStyleMask::from_bits_unchecked(
+          masks.bits() | NS_UTILITY_WINDOW_MASK | NS_NONACTIVATING_PANEL_MASK,
+        );
+        TOOL_WINDOW_CLASS.0
+      }
+      None if pl_attrs.panel => {
+        masks = NSWindowStyleMask::from_bits_unchecked(masks.bits() | NS_NONACTIVATING_PANEL_MASK);
+        PANEL_CLASS.0
+      }
+      None => WINDOW_CLASS.0,
+    };
+    let ns_window: id = msg_send![window_class, alloc];
     let ns_window = IdRef::new(ns_window.initWithContentRect_styleMask_backing_defer_(
       frame,
       masks,
@@ -228,23 +277,15 @@ fn create_window(
           let button = ns_window.standardWindowButton_(*titlebar_button);
           let _: () = msg_send![button, setHidden: YES];
         }
+      } else if !attrs.minimizable && !attrs.maximizable && !attrs.closable {
+        set_buttons_hidden(*ns_window, true);
       }
       if pl_attrs.movable_by_window_background {
         ns_window.setMovableByWindowBackground_(YES);
       }
 
-      if attrs.always_on_top {
-        let _: () = msg_send![
-          *ns_window,
-          setLevel: ffi::NSWindowLevel::NSFloatingWindowLevel
-        ];
-      }
-
-      if attrs.always_on_bottom {
-        let _: () = msg_send![
-          *ns_window,
-          setLevel: ffi::NSWindowLevel::BelowNormalWindowLevel
-        ];
+      if attrs.window_level != WindowLevel::Normal {
+        set_window_level(*ns_window, attrs.window_level);
       }
 
       if let Some(increments) = pl_attrs.resize_increments {
@@ -259,10 +300,59 @@ fn create_window(
         let _: () = msg_send![parent as id, addChildWindow: *ns_window ordered: NSWindowOrderingMode::NSWindowAbove];
       }
 
-      if !pl_attrs.has_shadow {
+      // Modal windows are attached to their owner as a sheet once shown instead.
+      if let (None, Some(owner), false) = (
+        attrs.tool_window,
+        attrs.parent.or(attrs.owner),
+        attrs.modal,
+      ) {
+        let owner = owner.0 .0 as id;
+        let _: () = msg_send![owner, addChildWindow: *ns_window ordered: NSWindowOrderingMode::NSWindowAbove];
+      }
+
+      if let Some(parent) = attrs.tool_window {
+        let _: () = msg_send![*ns_window, setFloatingPanel: YES];
+        let _: () = msg_send![*ns_window, setBecomesKeyOnlyIfNeeded: YES];
+        let parent = parent.0 .0 as id;
+        let _: () = msg_send![parent, addChildWindow: *ns_window ordered: NSWindowOrderingMode::NSWindowAbove];
+      } else if pl_attrs.panel {
+        // Panels hide when the app deactivates by default, which they must stay visible through.
+        let _: () = msg_send![*ns_window, setHidesOnDeactivate: NO];
+      }
+
+      if !pl_attrs.has_shadow || !attrs.shadow {
         ns_window.setHasShadow_(NO);
       }
-      if attrs.position.is_none() {
+      if let Some(identifier) = &pl_attrs.tabbing_identifier {
+        let identifier = NSString::alloc(nil).init_str(identifier);
+        let _: () = msg_send![*ns_window, setTabbingIdentifier: identifier];
+      }
+      if attrs.content_protection {
+        let _: () = msg_send![*ns_window, setSharingType: NS_WINDOW_SHARING_NONE];
+      }
+      if attrs.opacity < 1.0 {
+        let alpha_value = attrs.opacity.clamp(0.0, 1.0) as CGFloat;
+        let _: () = msg_send![*ns_window, setAlphaValue: alpha_value];
+      }
+      if let Some(effect) = attrs.blur {
+        set_blur(*ns_window, Some(effect));
+      }
+      if !attrs.maximizable {
+        set_maximizable(*ns_window, false);
+      }
+      if let (Some((screen, placement)), None) = (placement_screen, screen) {
+        let work_area = NSScreen::visibleFrame(screen);
+        let frame = NSWindow::frame(*ns_window);
+        let (x, y) = placement.offset(
+          (work_area.size.width, work_area.size.height),
+          (frame.size.width, frame.size.height),
+        );
+        // The offset is from the top left corner, but the origin is at the bottom left.
+        ns_window.setFrameOrigin_(NSPoint::new(
+          work_area.origin.x + x as CGFloat,
+          work_area.origin.y + work_area.size.height - frame.size.height - y as CGFloat,
+        ));
+      } else if attrs.position.is_none() {
         ns_window.center();
       }
       if let Some(window_menu) = attrs.window_menu.clone() {
@@ -300,23 +390,6 @@ pub(super) fn get_ns_theme() -> Theme {
   }
 }
 
-pub(super) fn set_ns_theme(theme: Theme) {
-  let name = match theme {
-    Theme::Dark => "NSAppearanceNameDarkAqua",
-    Theme::Light => "NSAppearanceNameAqua",
-  };
-  unsafe {
-    let app_class = class!(NSApplication);
-    let app: id = msg_send![app_class, sharedApplication];
-    let has_theme: BOOL = msg_send![app, respondsToSelector: sel!(effectiveAppearance)];
-    if has_theme == YES {
-      let name = NSString::alloc(nil).init_str(name);
-      let appearance: id = msg_send![class!(NSAppearance), appearanceNamed: name];
-      let _: () = msg_send![app, setAppearance: appearance];
-    }
-  }
-}
-
 struct WindowClass(*const Class);
 unsafe impl Send for WindowClass {}
 unsafe impl Sync for WindowClass {}
@@ -337,15 +410,253 @@ lazy_static! {
       sel!(sendEvent:),
       send_event as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(performMiniaturize:),
+      perform_miniaturize as extern "C" fn(&Object, Sel, id),
+    );
+    WindowClass(decl.register())
+  };
+  // Tool windows are panels, which never become the main window.
+  static ref TOOL_WINDOW_CLASS: WindowClass = unsafe {
+    let mut decl = ClassDecl::new("TaoPanel", class!(NSPanel)).unwrap();
+    decl.add_method(
+      sel!(sendEvent:),
+      send_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(performMiniaturize:),
+      perform_miniaturize as extern "C" fn(&Object, Sel, id),
+    );
+    WindowClass(decl.register())
+  };
+  // Non-activating panels, which can become the key window even when borderless.
+  static ref PANEL_CLASS: WindowClass = unsafe {
+    let mut decl = ClassDecl::new("TaoNonactivatingPanel", class!(NSPanel)).unwrap();
+    decl.add_method(
+      sel!(canBecomeKeyWindow),
+      util::yes as extern "C" fn(&Object, Sel) -> BOOL,
+    );
+    decl.add_method(
+      sel!(sendEvent:),
+      send_event as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(performMiniaturize:),
+      perform_miniaturize as extern "C" fn(&Object, Sel, id),
+    );
+    WindowClass(decl.register())
+  };
+  // A click-through overlay drawing a focus ring around the content view.
+  static ref DROP_HIGHLIGHT_CLASS: WindowClass = unsafe {
+    let mut decl = ClassDecl::new("TaoDropHighlightView", class!(NSView)).unwrap();
+    decl.add_method(
+      sel!(hitTest:),
+      click_through_hit_test as extern "C" fn(&Object, Sel, NSPoint) -> id,
+    );
+    decl.add_method(
+      sel!(drawRect:),
+      draw_drop_highlight as extern "C" fn(&Object, Sel, NSRect),
+    );
+    WindowClass(decl.register())
+  };
+  // A click-through visual effect view behind the content view's subviews.
+  static ref BLUR_VIEW_CLASS: WindowClass = unsafe {
+    let mut decl = ClassDecl::new("TaoBlurView", class!(NSVisualEffectView)).unwrap();
+    decl.add_method(
+      sel!(hitTest:),
+      click_through_hit_test as extern "C" fn(&Object, Sel, NSPoint) -> id,
+    );
     WindowClass(decl.register())
   };
 }
 
+extern "C" fn click_through_hit_test(_this: &Object, _sel: Sel, _point: NSPoint) -> id {
+  nil
+}
+
+extern "C" fn draw_drop_highlight(this: &Object, _sel: Sel, _dirty_rect: NSRect) {
+  const RING_WIDTH: CGFloat = 3.;
+  unsafe {
+    let bounds: NSRect = msg_send![this, bounds];
+    let rect = NSRect::new(
+      NSPoint::new(
+        bounds.origin.x + RING_WIDTH / 2.,
+        bounds.origin.y + RING_WIDTH / 2.,
+      ),
+      NSSize::new(
+        bounds.size.width - RING_WIDTH,
+        bounds.size.height - RING_WIDTH,
+      ),
+    );
+    let color: id = msg_send![class!(NSColor), keyboardFocusIndicatorColor];
+    let _: () = msg_send![color, setStroke];
+    let path: id = msg_send![class!(NSBezierPath), bezierPathWithRect: rect];
+    let _: () = msg_send![path, setLineWidth: RING_WIDTH];
+    let _: () = msg_send![path, stroke];
+  }
+}
+
+/// Enables or disables the zoom button. AppKit resets it whenever the style mask changes, so
+/// this needs to be called again afterwards.
+///
+/// Must be called on the main thread.
+pub(crate) unsafe fn set_maximizable(ns_window: id, maximizable: bool) {
+  let button = ns_window.standardWindowButton_(NSWindowButton::NSWindowZoomButton);
+  let _: () = msg_send![button, setEnabled: if maximizable { YES } else { NO }];
+}
+
+/// Shows or hides the close, minimize and zoom buttons.
+///
+/// Must be called on the main thread.
+pub(crate) unsafe fn set_buttons_hidden(ns_window: id, hidden: bool) {
+  for titlebar_button in &[
+    NSWindowButton::NSWindowCloseButton,
+    NSWindowButton::NSWindowMiniaturizeButton,
+    NSWindowButton::NSWindowZoomButton,
+  ] {
+    let button = ns_window.standardWindowButton_(*titlebar_button);
+    let _: () = msg_send![button, setHidden: if hidden { YES } else { NO }];
+  }
+}
+
+/// Adds or removes the drop highlight overlay on top of the window's content.
+///
+/// Must be called on the main thread.
+pub(crate) unsafe fn set_drop_highlight(ns_window: id, highlight: bool) {
+  let content_view: id = msg_send![ns_window, contentView];
+  let subviews: id = msg_send![content_view, subviews];
+  let count: NSUInteger = msg_send![subviews, count];
+  let overlay = (0..count)
+    .map(|i| -> id { msg_send![subviews, objectAtIndex: i] })
+    .find(|&view| {
+      let is_overlay: BOOL = msg_send![view, isKindOfClass: DROP_HIGHLIGHT_CLASS.0];
+      is_overlay == YES
+    });
+
+  match (highlight, overlay) {
+    (true, None) => {
+      let bounds: NSRect = msg_send![content_view, bounds];
+      let overlay: id = msg_send![DROP_HIGHLIGHT_CLASS.0, alloc];
+      let overlay: id = msg_send![overlay, initWithFrame: bounds];
+      let _: () = msg_send![
+        overlay,
+        setAutoresizingMask: appkit::NSViewWidthSizable | appkit::NSViewHeightSizable
+      ];
+      let _: () = msg_send![
+        content_view,
+        addSubview: overlay
+        positioned: NSWindowOrderingMode::NSWindowAbove
+        relativeTo: nil
+      ];
+      let _: () = msg_send![overlay, release];
+    }
+    (false, Some(overlay)) => {
+      let _: () = msg_send![overlay, removeFromSuperview];
+    }
+    _ => (),
+  }
+}
+
+/// Adds, changes or removes the visual effect view behind the window's content.
+///
+/// Must be called on the main thread.
+pub(crate) unsafe fn set_blur(ns_window: id, effect: Option<BlurEffect>) {
+  // `NSVisualEffectMaterial`, `NSVisualEffectBlendingModeBehindWindow` and
+  // `NSVisualEffectStateFollowsWindowActiveState`.
+  const MATERIAL_WINDOW_BACKGROUND: NSInteger = 12;
+  const MATERIAL_HUD_WINDOW: NSInteger = 13;
+  const MATERIAL_UNDER_WINDOW_BACKGROUND: NSInteger = 21;
+  const BLENDING_MODE_BEHIND_WINDOW: NSInteger = 0;
+  const STATE_FOLLOWS_WINDOW_ACTIVE_STATE: NSInteger = 0;
+
+  let content_view: id = msg_send![ns_window, contentView];
+  let subviews: id = msg_send![content_view, subviews];
+  let count: NSUInteger = msg_send![subviews, count];
+  let blur_view = (0..count)
+    .map(|i| -> id { msg_send![subviews, objectAtIndex: i] })
+    .find(|&view| {
+      let is_blur_view: BOOL = msg_send![view, isKindOfClass: BLUR_VIEW_CLASS.0];
+      is_blur_view == YES
+    });
+
+  let effect = match effect {
+    Some(effect) => effect,
+    None => {
+      if let Some(blur_view) = blur_view {
+        let _: () = msg_send![blur_view, removeFromSuperview];
+      }
+      return;
+    }
+  };
+  let blur_view = match blur_view {
+    Some(blur_view) => blur_view,
+    None => {
+      let bounds: NSRect = msg_send![content_view, bounds];
+      let blur_view: id = msg_send![BLUR_VIEW_CLASS.0, alloc];
+      let blur_view: id = msg_send![blur_view, initWithFrame: bounds];
+      let _: () = msg_send![
+        blur_view,
+        setAutoresizingMask: appkit::NSViewWidthSizable | appkit::NSViewHeightSizable
+      ];
+      let _: () = msg_send![blur_view, setBlendingMode: BLENDING_MODE_BEHIND_WINDOW];
+      let _: () = msg_send![blur_view, setState: STATE_FOLLOWS_WINDOW_ACTIVE_STATE];
+      let _: () = msg_send![
+        content_view,
+        addSubview: blur_view
+        positioned: NSWindowOrderingMode::NSWindowBelow
+        relativeTo: nil
+      ];
+      let _: () = msg_send![blur_view, release];
+      blur_view
+    }
+  };
+
+  let material = match effect {
+    BlurEffect::Blur => MATERIAL_UNDER_WINDOW_BACKGROUND,
+    BlurEffect::Acrylic => MATERIAL_HUD_WINDOW,
+    BlurEffect::Mica => MATERIAL_WINDOW_BACKGROUND,
+  };
+  let _: () = msg_send![blur_view, setMaterial: material];
+}
+
+/// Forces the appearance of the window, or lets it inherit the one of the application.
+///
+/// Must be called on the main thread.
+pub(crate) unsafe fn set_appearance(ns_window: id, theme: Option<Theme>) {
+  let appearance: id = match theme {
+    Some(theme) => {
+      let name = NSString::alloc(nil).init_str(match theme {
+        Theme::Dark => "NSAppearanceNameDarkAqua",
+        Theme::Light => "NSAppearanceNameAqua",
+      });
+      msg_send![class!(NSAppearance), appearanceNamed: name]
+    }
+    // Inherits the appearance of the application.
+    None => nil,
+  };
+  let _: () = msg_send![ns_window, setAppearance: appearance];
+}
+
 extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
   unsafe {
     let event_type = event.eventType();
     match event_type {
       appkit::NSLeftMouseDown => {
+        let delegate: id = msg_send![this, delegate];
+        let responds: BOOL = msg_send![delegate, respondsToSelector: sel!(isInDragRegion:)];
+        if responds == YES {
+          let in_drag_region: BOOL = msg_send![delegate, isInDragRegion: event];
+          if in_drag_region == YES {
+            let click_count: NSInteger = msg_send![event, clickCount];
+            if click_count == 2 {
+              perform_titlebar_double_click(this);
+            } else {
+              let _: () = msg_send![this, performWindowDragWithEvent: event];
+            }
+            return;
+          }
+        }
+
         // When wkwebview is set on NSWindow, `WindowBuilder::with_movable_by_window_background` is not working.
         // Because of this, we need to invoke `[NSWindow performWindowDragWithEvent]` in NSLeftMouseDown event.
         let is_movable_window: BOOL = msg_send![this, isMovableByWindowBackground];
@@ -360,9 +671,53 @@ extern "C" fn send_event(this: &Object, _sel: Sel, event: id) {
   }
 }
 
+// Does what double-clicking the titlebar does, according to the user's Dock preferences.
+unsafe fn perform_titlebar_double_click(this: &Object) {
+  let defaults: id = msg_send![class!(NSUserDefaults), standardUserDefaults];
+  let key = util::ns_string_id_ref("AppleActionOnDoubleClick");
+  let action: id = msg_send![defaults, stringForKey: *key];
+  let action = if action == nil {
+    String::new()
+  } else {
+    ns_string_to_rust(action)
+  };
+  match action.as_str() {
+    "Minimize" => {
+      let _: () = msg_send![this, performMiniaturize: nil];
+    }
+    "None" | "Do Nothing" => (),
+    _ => {
+      let _: () = msg_send![this, performZoom: nil];
+    }
+  }
+}
+
+// Invoked by the minimize button, the Window menu and Cmd+M, but not by `Window::set_minimized`.
+extern "C" fn perform_miniaturize(this: &Object, _sel: Sel, sender: id) {
+  unsafe {
+    let delegate: id = msg_send![this, delegate];
+    let responds: BOOL = msg_send![delegate, respondsToSelector: sel!(miniaturizeToTray)];
+    if responds == YES {
+      let handled: BOOL = msg_send![delegate, miniaturizeToTray];
+      if handled == YES {
+        return;
+      }
+    }
+    let superclass = util::superclass(this);
+    let _: () = msg_send![super(this, superclass), performMiniaturize: sender];
+  }
+}
+
 #[derive(Default)]
 pub struct SharedState {
   pub resizable: bool,
+  pub minimizable: bool,
+  pub maximizable: bool,
+  pub closable: bool,
+  /// Whether the title bar buttons are hidden because all of them are disabled.
+  pub buttons_hidden: bool,
+  /// Set by `with_titlebar_buttons_hidden`, the title bar buttons staying hidden.
+  pub titlebar_buttons_hidden: bool,
   pub fullscreen: Option<Fullscreen>,
   // This is true between windowWillEnterFullScreen and windowDidEnterFullScreen
   // or windowWillExitFullScreen and windowDidExitFullScreen.
@@ -383,6 +738,15 @@ pub struct SharedState {
   save_presentation_opts: Option<NSApplicationPresentationOptions>,
   pub saved_desktop_display_mode: Option<(CGDisplay, CGDisplayMode)>,
   pub current_theme: Theme,
+  /// Set by `set_theme`, the window following the system theme otherwise.
+  pub preferred_theme: Option<Theme>,
+  pub minimize_to_tray: bool,
+  pub drag_regions: Vec<Rectangle>,
+  pub drag_exclusions: Vec<Rectangle>,
+  /// The window this window is shown as a sheet on.
+  pub modal_owner: Option<RootWindowId>,
+  /// The id of the pending request for user attention, to cancel it.
+  pub user_attention_request: Option<NSInteger>,
 }
 
 impl SharedState {
@@ -397,6 +761,10 @@ impl From<WindowAttributes> for SharedState {
   fn from(attribs: WindowAttributes) -> Self {
     SharedState {
       resizable: attribs.resizable,
+      minimizable: attribs.minimizable,
+      maximizable: attribs.maximizable,
+      closable: attribs.closable,
+      buttons_hidden: !attribs.minimizable && !attribs.maximizable && !attribs.closable,
       // This fullscreen field tracks the current state of the window
       // (as seen by `WindowDelegate`), and since the window hasn't
       // actually been fullscreened yet, we can't set it yet. This is
@@ -405,6 +773,10 @@ impl From<WindowAttributes> for SharedState {
       // identical, resulting in a no-op.
       fullscreen: None,
       maximized: attribs.maximized,
+      modal_owner: match (attribs.tool_window, attribs.modal) {
+        (None, true) => attribs.owner,
+        _ => None,
+      },
       ..Default::default()
     }
   }
@@ -416,8 +788,14 @@ pub struct UnownedWindow {
   input_context: IdRef, // never changes
   pub shared_state: Arc<Mutex<SharedState>>,
   decorations: AtomicBool,
+  vsync_redraws: AtomicBool,
   cursor_state: Weak<Mutex<CursorState>>,
   pub inner_rect: Option<PhysicalSize<u32>>,
+  /// The frame of the window and the size of its content view the last time it was neither
+  /// maximized nor fullscreen, kept up to date by the window delegate.
+  pub(crate) restored_frame: Mutex<Option<(NSRect, NSSize)>>,
+  #[cfg(feature = "accesskit")]
+  pub(crate) accessibility: accessibility::Accessibility,
 }
 
 unsafe impl Send for UnownedWindow {}
@@ -463,6 +841,9 @@ impl UnownedWindow {
         ns_window.setOpaque_(NO);
         ns_window.setBackgroundColor_(NSColor::clearColor(nil));
       }
+      if let Some(color) = win_attribs.background_color {
+        ns_window.setBackgroundColor_(ns_color(color));
+      }
 
       win_attribs.min_inner_size.map(|dim| {
         let logical_dim = dim.to_logical(scale_factor);
@@ -472,12 +853,12 @@ impl UnownedWindow {
         let logical_dim = dim.to_logical(scale_factor);
         set_max_inner_size(*ns_window, logical_dim)
       });
+      if win_attribs.resize_increments.is_some() {
+        set_resize_increments(*ns_window, win_attribs.resize_increments, scale_factor);
+      }
 
       // register for drag and drop operations.
-      let () = msg_send![
-        *ns_window,
-        registerForDraggedTypes: NSArray::arrayWithObject(nil, appkit::NSFilenamesPboardType)
-      ];
+      let () = msg_send![*ns_window, registerForDraggedTypes: dragged_types()];
     }
 
     // Since `win_attribs` is put into a mutex below, we'll just copy these
@@ -494,6 +875,7 @@ impl UnownedWindow {
       .map(|size| size.to_physical(scale_factor));
 
     let cloned_preferred_theme = win_attribs.preferred_theme.clone();
+    let vsync_redraws = win_attribs.vsync_redraws;
 
     let window = Arc::new(UnownedWindow {
       ns_view,
@@ -501,21 +883,17 @@ impl UnownedWindow {
       input_context,
       shared_state: Arc::new(Mutex::new(win_attribs.into())),
       decorations: AtomicBool::new(decorations),
+      vsync_redraws: AtomicBool::new(vsync_redraws),
       cursor_state,
       inner_rect,
+      restored_frame: Mutex::new(None),
+      #[cfg(feature = "accesskit")]
+      accessibility: Default::default(),
     });
 
-    match cloned_preferred_theme {
-      Some(theme) => {
-        set_ns_theme(theme);
-        let mut state = window.shared_state.lock().unwrap();
-        state.current_theme = theme.clone();
-      }
-      None => {
-        let mut state = window.shared_state.lock().unwrap();
-        state.current_theme = get_ns_theme();
-      }
-    }
+    window.shared_state.lock().unwrap().titlebar_buttons_hidden =
+      pl_attribs.titlebar_buttons_hidden;
+    window.set_theme(cloned_preferred_theme);
 
     let delegate = new_delegate(&window, fullscreen.is_some());
 
@@ -526,8 +904,12 @@ impl UnownedWindow {
     // state, since otherwise we'll briefly see the window at normal size
     // before it transitions.
     if visible {
-      // Tightly linked with `app_state::window_activation_hack`
-      unsafe { window.ns_window.makeKeyAndOrderFront_(nil) };
+      let modal_owner = window.shared_state.lock().unwrap().modal_owner;
+      match modal_owner {
+        Some(owner) => unsafe { util::begin_sheet(owner.0 .0 as id, *window.ns_window) },
+        // Tightly linked with `app_state::window_activation_hack`
+        None => unsafe { window.ns_window.makeKeyAndOrderFront_(nil) },
+      }
     }
 
     if maximized {
@@ -566,9 +948,11 @@ impl UnownedWindow {
   }
 
   pub fn set_visible(&self, visible: bool) {
-    match visible {
-      true => unsafe { util::make_key_and_order_front_async(*self.ns_window) },
-      false => unsafe { util::order_out_async(*self.ns_window) },
+    let modal_owner = self.shared_state.lock().unwrap().modal_owner;
+    match (visible, modal_owner) {
+      (true, Some(owner)) => unsafe { util::begin_sheet_async(owner.0 .0 as id, *self.ns_window) },
+      (true, None) => unsafe { util::make_key_and_order_front_async(*self.ns_window) },
+      (false, _) => unsafe { util::order_out_async(*self.ns_window) },
     }
   }
 
@@ -592,7 +976,15 @@ impl UnownedWindow {
   }
 
   pub fn request_redraw(&self) {
-    AppState::queue_redraw(RootWindowId(self.id()));
+    if self.vsync_redraws.load(Ordering::Acquire) {
+      display_link::request_redraw(RootWindowId(self.id()));
+    } else {
+      AppState::queue_redraw(RootWindowId(self.id()));
+    }
+  }
+
+  pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+    self.vsync_redraws.store(vsync_redraws, Ordering::Release);
   }
 
   pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
@@ -651,6 +1043,27 @@ impl UnownedWindow {
     }
   }
 
+  pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    unsafe {
+      let is_main_thread: BOOL = msg_send!(class!(NSThread), isMainThread);
+      if is_main_thread == NO {
+        self.set_inner_size(size);
+        return None;
+      }
+
+      let size: LogicalSize<f64> = size.to_logical(self.scale_factor());
+      self
+        .ns_window
+        .setContentSize_(NSSize::new(size.width as CGFloat, size.height as CGFloat));
+    }
+    Some(self.inner_size())
+  }
+
+  pub fn set_resize_increments(&self, increments: Option<Size>) {
+    let scale_factor = self.scale_factor();
+    unsafe { set_resize_increments(*self.ns_window, increments, scale_factor) };
+  }
+
   pub fn set_min_inner_size(&self, dimensions: Option<Size>) {
     unsafe {
       let dimensions = dimensions.unwrap_or(Logical(LogicalSize {
@@ -675,12 +1088,15 @@ impl UnownedWindow {
 
   #[inline]
   pub fn set_resizable(&self, resizable: bool) {
-    let fullscreen = {
+    let (fullscreen, maximizable) = {
       trace!("Locked shared state in `set_resizable`");
       let mut shared_state_lock = self.shared_state.lock().unwrap();
       shared_state_lock.resizable = resizable;
       trace!("Unlocked shared state in `set_resizable`");
-      shared_state_lock.fullscreen.is_some()
+      (
+        shared_state_lock.fullscreen.is_some(),
+        shared_state_lock.maximizable,
+      )
     };
     if !fullscreen {
       let mut mask = unsafe { self.ns_window.styleMask() };
@@ -690,9 +1106,77 @@ impl UnownedWindow {
         mask &= !NSWindowStyleMask::NSResizableWindowMask;
       }
       self.set_style_mask_async(mask);
+      unsafe { util::set_maximizable_async(*self.ns_window, maximizable) };
     } // Otherwise, we don't change the mask until we exit fullscreen.
   }
 
+  #[inline]
+  pub fn set_minimizable(&self, minimizable: bool) {
+    self.set_style_mask_flag(
+      NSWindowStyleMask::NSMiniaturizableWindowMask,
+      minimizable,
+      |s| &mut s.minimizable,
+    );
+  }
+
+  #[inline]
+  pub fn set_maximizable(&self, maximizable: bool) {
+    trace!("Locked shared state in `set_maximizable`");
+    self.shared_state.lock().unwrap().maximizable = maximizable;
+    trace!("Unlocked shared state in `set_maximizable`");
+    unsafe { util::set_maximizable_async(*self.ns_window, maximizable) };
+    self.update_buttons_hidden();
+  }
+
+  #[inline]
+  pub fn set_closable(&self, closable: bool) {
+    self.set_style_mask_flag(NSWindowStyleMask::NSClosableWindowMask, closable, |s| {
+      &mut s.closable
+    });
+  }
+
+  /// Hides the title bar buttons while all of them are disabled, and shows them again otherwise.
+  fn update_buttons_hidden(&self) {
+    let hidden = {
+      let mut shared_state_lock = self.shared_state.lock().unwrap();
+      let hidden = !shared_state_lock.minimizable
+        && !shared_state_lock.maximizable
+        && !shared_state_lock.closable;
+      if shared_state_lock.titlebar_buttons_hidden
+        || mem::replace(&mut shared_state_lock.buttons_hidden, hidden) == hidden
+      {
+        return;
+      }
+      hidden
+    };
+    unsafe { util::set_buttons_hidden_async(*self.ns_window, hidden) };
+  }
+
+  /// Stores a style mask flag in the shared state and applies it, unless in fullscreen where it
+  /// is applied when exiting.
+  fn set_style_mask_flag(
+    &self,
+    flag: NSWindowStyleMask,
+    value: bool,
+    field: impl FnOnce(&mut SharedState) -> &mut bool,
+  ) {
+    let (fullscreen, maximizable) = {
+      let mut shared_state_lock = self.shared_state.lock().unwrap();
+      *field(&mut shared_state_lock) = value;
+      (
+        shared_state_lock.fullscreen.is_some() || shared_state_lock.is_simple_fullscreen,
+        shared_state_lock.maximizable,
+      )
+    };
+    if !fullscreen {
+      let mut mask = unsafe { self.ns_window.styleMask() };
+      mask.set(flag, value);
+      self.set_style_mask_async(mask);
+      unsafe { util::set_maximizable_async(*self.ns_window, maximizable) };
+    }
+    self.update_buttons_hidden();
+  }
+
   pub fn set_cursor_icon(&self, cursor: CursorIcon) {
     let cursor = util::Cursor::from(cursor);
     if let Some(cursor_access) = self.cursor_state.upgrade() {
@@ -706,9 +1190,16 @@ impl UnownedWindow {
   }
 
   #[inline]
-  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-    // TODO: Do this for real https://stackoverflow.com/a/40922095/5435443
-    CGDisplay::associate_mouse_and_mouse_cursor_position(!grab)
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    let associate = match mode {
+      CursorGrabMode::None => true,
+      CursorGrabMode::Locked => false,
+      // AppKit has no way to keep the cursor within a window.
+      CursorGrabMode::Confined => {
+        return Err(ExternalError::NotSupported(NotSupportedError::new()))
+      }
+    };
+    CGDisplay::associate_mouse_and_mouse_cursor_position(associate)
       .map_err(|status| ExternalError::Os(os_error!(OsError::CGError(status))))
   }
 
@@ -761,6 +1252,26 @@ impl UnownedWindow {
     Ok(())
   }
 
+  #[inline]
+  pub fn drag_resize_window(&self, _direction: ResizeDirection) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
+  #[inline]
+  pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+    unsafe { view::start_drag(*self.ns_view, &data) };
+
+    Ok(())
+  }
+
+  #[inline]
+  pub fn set_cursor_hittest_region(
+    &self,
+    _regions: Option<&[Rectangle]>,
+  ) -> Result<(), ExternalError> {
+    Err(ExternalError::NotSupported(NotSupportedError::new()))
+  }
+
   #[inline]
   pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<(), ExternalError> {
     unsafe {
@@ -792,15 +1303,23 @@ impl UnownedWindow {
   }
 
   fn saved_style(&self, shared_state: &mut SharedState) -> NSWindowStyleMask {
-    let base_mask = shared_state
+    let mut mask = shared_state
       .saved_style
       .take()
       .unwrap_or_else(|| unsafe { self.ns_window.styleMask() });
-    if shared_state.resizable {
-      base_mask | NSWindowStyleMask::NSResizableWindowMask
-    } else {
-      base_mask & !NSWindowStyleMask::NSResizableWindowMask
-    }
+    mask.set(
+      NSWindowStyleMask::NSResizableWindowMask,
+      shared_state.resizable,
+    );
+    mask.set(
+      NSWindowStyleMask::NSMiniaturizableWindowMask,
+      shared_state.minimizable,
+    );
+    mask.set(
+      NSWindowStyleMask::NSClosableWindowMask,
+      shared_state.closable,
+    );
+    mask
   }
 
   /// This is called when the window is exiting fullscreen, whether by the
@@ -813,12 +1332,14 @@ impl UnownedWindow {
     shared_state_lock.fullscreen = None;
 
     let maximized = shared_state_lock.maximized;
+    let maximizable = shared_state_lock.maximizable;
     let mask = self.saved_style(&mut *shared_state_lock);
 
     drop(shared_state_lock);
     trace!("Unocked shared state in `restore_state_from_fullscreen`");
 
     self.set_style_mask_async(mask);
+    unsafe { util::set_maximizable_async(*self.ns_window, maximizable) };
     self.set_maximized(maximized);
   }
 
@@ -841,6 +1362,11 @@ impl UnownedWindow {
     }
   }
 
+  #[inline]
+  pub fn set_minimize_to_tray(&self, minimize_to_tray: bool) {
+    self.shared_state.lock().unwrap().minimize_to_tray = minimize_to_tray;
+  }
+
   #[inline]
   pub fn set_maximized(&self, maximized: bool) {
     let is_zoomed = self.is_zoomed();
@@ -863,6 +1389,17 @@ impl UnownedWindow {
     shared_state_lock.fullscreen.clone()
   }
 
+  #[inline]
+  pub fn current_fullscreen(&self) -> Option<Fullscreen> {
+    match self.fullscreen() {
+      Some(Fullscreen::Exclusive(video_mode)) => {
+        let current = video_mode.video_mode.monitor.current_video_mode();
+        Some(Fullscreen::Exclusive(current.unwrap_or(video_mode)))
+      }
+      fullscreen => fullscreen,
+    }
+  }
+
   #[inline]
   pub fn is_visible(&self) -> bool {
     let is_visible: BOOL = unsafe { msg_send![*self.ns_window, isVisible] };
@@ -874,6 +1411,28 @@ impl UnownedWindow {
     self.is_zoomed()
   }
 
+  /// Returns the outer position and inner size the window has when it's neither maximized nor
+  /// fullscreen.
+  pub fn restored_geometry(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let (frame, content_size) = self
+      .restored_frame
+      .lock()
+      .unwrap()
+      .unwrap_or_else(|| unsafe {
+        (
+          NSWindow::frame(*self.ns_window),
+          NSView::frame(*self.ns_view).size,
+        )
+      });
+    let scale_factor = self.scale_factor();
+    let position = LogicalPosition::new(frame.origin.x, util::bottom_left_to_top_left(frame));
+    let size = LogicalSize::new(content_size.width, content_size.height);
+    (
+      position.to_physical(scale_factor),
+      size.to_physical(scale_factor),
+    )
+  }
+
   #[inline]
   pub fn is_minimized(&self) -> bool {
     let is_minimized: BOOL = unsafe { msg_send![*self.ns_window, isMiniaturized] };
@@ -886,6 +1445,21 @@ impl UnownedWindow {
     is_resizable == YES
   }
 
+  #[inline]
+  pub fn is_minimizable(&self) -> bool {
+    self.shared_state.lock().unwrap().minimizable
+  }
+
+  #[inline]
+  pub fn is_maximizable(&self) -> bool {
+    self.shared_state.lock().unwrap().maximizable
+  }
+
+  #[inline]
+  pub fn is_closable(&self) -> bool {
+    self.shared_state.lock().unwrap().closable
+  }
+
   #[inline]
   pub fn is_decorated(&self) -> bool {
     self.decorations.load(Ordering::Acquire)
@@ -1099,13 +1673,16 @@ impl UnownedWindow {
     if decorations != self.decorations.load(Ordering::Acquire) {
       self.decorations.store(decorations, Ordering::Release);
 
-      let (fullscreen, resizable) = {
+      let (fullscreen, resizable, minimizable, maximizable, closable) = {
         trace!("Locked shared state in `set_decorations`");
         let shared_state_lock = self.shared_state.lock().unwrap();
         trace!("Unlocked shared state in `set_decorations`");
         (
           shared_state_lock.fullscreen.is_some(),
           shared_state_lock.resizable,
+          shared_state_lock.minimizable,
+          shared_state_lock.maximizable,
+          shared_state_lock.closable,
         )
       };
 
@@ -1127,30 +1704,92 @@ impl UnownedWindow {
         if !resizable {
           new_mask &= !NSWindowStyleMask::NSResizableWindowMask;
         }
+        if !minimizable {
+          new_mask &= !NSWindowStyleMask::NSMiniaturizableWindowMask;
+        }
+        if !closable {
+          new_mask &= !NSWindowStyleMask::NSClosableWindowMask;
+        }
         new_mask
       };
       self.set_style_mask_async(new_mask);
+      unsafe { util::set_maximizable_async(*self.ns_window, maximizable) };
     }
   }
 
   #[inline]
-  pub fn set_always_on_bottom(&self, always_on_bottom: bool) {
-    let level = if always_on_bottom {
-      ffi::NSWindowLevel::BelowNormalWindowLevel
-    } else {
-      ffi::NSWindowLevel::NSNormalWindowLevel
-    };
-    unsafe { util::set_level_async(*self.ns_window, level) };
+  pub fn set_corner_radius(&self, _radius: Option<f64>) {}
+
+  #[inline]
+  pub fn set_window_level(&self, window_level: WindowLevel) {
+    unsafe { util::set_window_level_async(*self.ns_window, window_level) };
+  }
+
+  #[inline]
+  pub fn set_drag_regions(&self, regions: Vec<Rectangle>, exclusions: Vec<Rectangle>) {
+    let mut shared_state_lock = self.shared_state.lock().unwrap();
+    shared_state_lock.drag_regions = regions;
+    shared_state_lock.drag_exclusions = exclusions;
+  }
+
+  #[inline]
+  pub fn set_drop_highlight(&self, highlight: bool) {
+    unsafe { util::set_drop_highlight_async(*self.ns_window, highlight) };
   }
 
   #[inline]
-  pub fn set_always_on_top(&self, always_on_top: bool) {
-    let level = if always_on_top {
-      ffi::NSWindowLevel::NSFloatingWindowLevel
+  pub fn set_background_color(&self, color: Option<RGBA>) {
+    unsafe { util::set_background_color_async(*self.ns_window, color) };
+  }
+
+  #[inline]
+  pub fn set_keyboard_grab(&self, grab: bool) {
+    unsafe { util::set_keyboard_grab_async(*self.ns_window, grab) };
+  }
+
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    device_events::set_window_filter(self.id(), Some(filter));
+  }
+
+  #[inline]
+  pub fn set_shadow(&self, shadow: bool) {
+    self.set_has_shadow(shadow);
+  }
+
+  #[inline]
+  pub fn set_blur(&self, effect: Option<BlurEffect>) {
+    unsafe { util::set_blur_async(*self.ns_window, effect) };
+  }
+
+  #[inline]
+  pub fn set_opacity(&self, opacity: f64) {
+    let alpha_value = opacity.clamp(0.0, 1.0) as CGFloat;
+    unsafe { util::set_alpha_value_async(*self.ns_window, alpha_value) };
+  }
+
+  #[inline]
+  pub fn set_content_protection(&self, protected: bool) {
+    let sharing_type = if protected {
+      NS_WINDOW_SHARING_NONE
     } else {
-      ffi::NSWindowLevel::NSNormalWindowLevel
+      NS_WINDOW_SHARING_READ_ONLY
     };
-    unsafe { util::set_level_async(*self.ns_window, level) };
+    unsafe {
+      let _: () = msg_send![*self.ns_window, setSharingType: sharing_type];
+    }
+  }
+
+  #[inline]
+  pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+    unsafe {
+      let mut collection_behavior = self.ns_window.collectionBehavior();
+      collection_behavior.set(
+        NSWindowCollectionBehavior::NSWindowCollectionBehaviorCanJoinAllSpaces,
+        visible,
+      );
+      self.ns_window.setCollectionBehavior_(collection_behavior);
+    }
   }
 
   #[inline]
@@ -1166,32 +1805,54 @@ impl UnownedWindow {
   }
 
   #[inline]
-  pub fn set_ime_position(&self, spot: Position) {
+  pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
     let scale_factor = self.scale_factor();
-    let logical_spot = spot.to_logical(scale_factor);
+    let position = position.to_logical(scale_factor);
+    let size = size.to_logical(scale_factor);
     unsafe {
-      view::set_ime_position(
-        *self.ns_view,
-        *self.input_context,
-        logical_spot.x,
-        logical_spot.y,
-      );
+      view::set_ime_cursor_area(*self.ns_view, *self.input_context, position, size);
     }
   }
 
+  #[inline]
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    unsafe { view::set_ime_allowed(*self.ns_view, allowed) };
+  }
+
+  #[inline]
+  pub fn reset_dead_keys(&self) {
+    unsafe { view::reset_dead_keys(*self.ns_view) };
+  }
+
   #[inline]
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
     let ns_request_type = request_type.map(|ty| match ty {
       UserAttentionType::Critical => NSRequestUserAttentionType::NSCriticalRequest,
       UserAttentionType::Informational => NSRequestUserAttentionType::NSInformationalRequest,
     });
+    let mut shared_state_lock = self.shared_state.lock().unwrap();
     unsafe {
+      // A new request replaces the previous one, so that it can be cancelled.
+      if let Some(request) = shared_state_lock.user_attention_request.take() {
+        let _: () = msg_send![NSApp(), cancelUserAttentionRequest: request];
+      }
       if let Some(ty) = ns_request_type {
-        NSApp().requestUserAttention_(ty);
+        let request: NSInteger = msg_send![NSApp(), requestUserAttention: ty];
+        shared_state_lock.user_attention_request = Some(request);
       }
     }
   }
 
+  #[inline]
+  pub fn set_progress_bar(&self, progress: ProgressBarState) {
+    unsafe { util::set_progress_bar_async(progress) };
+  }
+
+  #[inline]
+  pub fn set_badge_count(&self, count: Option<u64>) {
+    unsafe { util::set_badge_label_async(count.map(|count| count.to_string())) };
+  }
+
   #[inline]
   pub fn hide_menu(&self) {}
 
@@ -1253,6 +1914,47 @@ impl UnownedWindow {
     let state = self.shared_state.lock().unwrap();
     state.current_theme
   }
+
+  #[inline]
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    unsafe { util::set_theme_async(*self.ns_window, theme) };
+    let mut state = self.shared_state.lock().unwrap();
+    state.preferred_theme = theme;
+    state.current_theme = theme.unwrap_or_else(get_ns_theme);
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn set_accessibility_source(&self, source: accessibility::AccessibilitySource) {
+    let set_source = move || unsafe {
+      accessibility::set_source(
+        &self.accessibility,
+        *self.ns_view,
+        self.id(),
+        self.is_focused(),
+        source,
+      )
+    };
+    let is_main_thread: BOOL = unsafe { msg_send!(class!(NSThread), isMainThread) };
+    if is_main_thread != NO {
+      set_source();
+    } else {
+      Queue::main().exec_sync(set_source);
+    }
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn update_accessibility<F>(&self, update: F)
+  where
+    F: FnOnce() -> crate::accesskit::TreeUpdate,
+  {
+    let is_main_thread: BOOL = unsafe { msg_send!(class!(NSThread), isMainThread) };
+    if is_main_thread != NO {
+      accessibility::update(&self.accessibility, update);
+    } else {
+      let update = update();
+      Queue::main().exec_sync(move || accessibility::update(&self.accessibility, || update));
+    }
+  }
 }
 
 impl WindowExtMacOS for UnownedWindow {
@@ -1340,6 +2042,7 @@ impl WindowExtMacOS for UnownedWindow {
       } else {
         let new_mask = self.saved_style(&mut *shared_state_lock);
         self.set_style_mask_async(new_mask);
+        util::set_maximizable_async(*self.ns_window, shared_state_lock.maximizable);
         shared_state_lock.is_simple_fullscreen = false;
 
         if let Some(presentation_opts) = shared_state_lock.save_presentation_opts {
@@ -1368,18 +2071,147 @@ impl WindowExtMacOS for UnownedWindow {
         .setHasShadow_(if has_shadow { YES } else { NO })
     }
   }
+
+  #[inline]
+  fn tabbing_identifier(&self) -> String {
+    unsafe {
+      let identifier: id = msg_send![*self.ns_window, tabbingIdentifier];
+      if identifier.is_null() {
+        return String::new();
+      }
+      let slice =
+        std::slice::from_raw_parts(identifier.UTF8String() as *const u8, identifier.len());
+      String::from_utf8_lossy(slice).into_owned()
+    }
+  }
+
+  #[inline]
+  fn set_tabbing_identifier(&self, identifier: &str) {
+    unsafe {
+      let identifier = NSString::alloc(nil).init_str(identifier);
+      let _: () = msg_send![*self.ns_window, setTabbingIdentifier: identifier];
+    }
+  }
+
+  #[inline]
+  fn select_next_tab(&self) {
+    unsafe {
+      let _: () = msg_send![*self.ns_window, selectNextTab: nil];
+    }
+  }
+
+  #[inline]
+  fn select_previous_tab(&self) {
+    unsafe {
+      let _: () = msg_send![*self.ns_window, selectPreviousTab: nil];
+    }
+  }
+
+  #[inline]
+  fn select_tab_at_index(&self, index: usize) {
+    unsafe {
+      let windows: id = msg_send![*self.ns_window, tabbedWindows];
+      if !windows.is_null() && index < NSArray::count(windows) as usize {
+        let window: id = msg_send![windows, objectAtIndex: index as NSUInteger];
+        let _: () = msg_send![window, makeKeyAndOrderFront: nil];
+      }
+    }
+  }
+
+  #[inline]
+  fn tabbed_windows(&self) -> Option<Vec<RootWindowId>> {
+    unsafe {
+      let windows: id = msg_send![*self.ns_window, tabbedWindows];
+      if windows.is_null() {
+        return None;
+      }
+      Some(
+        (0..NSArray::count(windows))
+          .map(|i| RootWindowId(get_window_id(NSArray::objectAtIndex(windows, i))))
+          .collect(),
+      )
+    }
+  }
 }
 
 impl Drop for UnownedWindow {
   fn drop(&mut self) {
     trace!("Dropping `UnownedWindow` ({:?})", self as *mut _);
+    device_events::set_window_filter(self.id(), None);
+    // The adapter restores the class of the view, which may only be done on the main thread.
+    #[cfg(feature = "accesskit")]
+    if self.accessibility.lock().unwrap().is_some() {
+      let is_main_thread: BOOL = unsafe { msg_send!(class!(NSThread), isMainThread) };
+      if is_main_thread == NO {
+        let this: &Self = self;
+        Queue::main().exec_sync(move || drop(this.accessibility.lock().unwrap().take()));
+      }
+    }
     // Close the window if it has not yet been closed.
     if *self.ns_window != nil {
-      unsafe { util::close_async(self.ns_window.clone()) };
+      unsafe {
+        util::set_keyboard_grab_async(*self.ns_window, false);
+        util::close_async(self.ns_window.clone());
+      }
     }
   }
 }
 
+unsafe fn ns_color((r, g, b, a): RGBA) -> id {
+  NSColor::colorWithSRGBRed_green_blue_alpha_(
+    nil,
+    r as CGFloat / 255.,
+    g as CGFloat / 255.,
+    b as CGFloat / 255.,
+    a as CGFloat / 255.,
+  )
+}
+
+/// Fills the window with `color`, or its default background otherwise. Must be called on the
+/// main thread.
+pub(crate) unsafe fn set_background_color(ns_window: id, color: Option<RGBA>) {
+  let is_opaque: BOOL = msg_send![ns_window, isOpaque];
+  let ns_color = match color {
+    Some(color) => ns_color(color),
+    None if is_opaque == NO => NSColor::clearColor(nil),
+    None => msg_send![class!(NSColor), windowBackgroundColor],
+  };
+  ns_window.setBackgroundColor_(ns_color);
+}
+
+fn ns_window_level(window_level: WindowLevel) -> ffi::NSWindowLevel {
+  match window_level {
+    WindowLevel::Desktop => ffi::NSWindowLevel::BelowNormalWindowLevel,
+    WindowLevel::Normal => ffi::NSWindowLevel::NSNormalWindowLevel,
+    WindowLevel::Floating => ffi::NSWindowLevel::NSFloatingWindowLevel,
+    WindowLevel::PanelOverlay => ffi::NSWindowLevel::NSStatusWindowLevel,
+    WindowLevel::ScreenSaver => ffi::NSWindowLevel::NSScreenSaverWindowLevel,
+  }
+}
+
+/// Sets the level of the window, letting a screen saver level window show in the space of a
+/// fullscreen app. Must be called on the main thread.
+pub(crate) unsafe fn set_window_level(window: id, window_level: WindowLevel) {
+  let _: () = msg_send![window, setLevel: ns_window_level(window_level)];
+  let mut collection_behavior = window.collectionBehavior();
+  collection_behavior.set(
+    NSWindowCollectionBehavior::NSWindowCollectionBehaviorFullScreenAuxiliary,
+    window_level == WindowLevel::ScreenSaver,
+  );
+  window.setCollectionBehavior_(collection_behavior);
+}
+
+unsafe fn set_resize_increments(window: id, increments: Option<Size>, scale_factor: f64) {
+  let increments = increments
+    .map(|increments| increments.to_logical::<f64>(scale_factor))
+    .unwrap_or_else(|| LogicalSize::new(1., 1.));
+  let size = NSSize::new(
+    increments.width.max(1.) as CGFloat,
+    increments.height.max(1.) as CGFloat,
+  );
+  let _: () = msg_send![window, setContentResizeIncrements: size];
+}
+
 unsafe fn set_min_inner_size<V: NSWindow + Copy>(window: V, mut min_size: LogicalSize<f64>) {
   let mut current_rect = NSWindow::frame(window);
   let content_rect = NSWindow::contentRectForFrameRect_(window, NSWindow::frame(window));
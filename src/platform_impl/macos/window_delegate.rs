@@ -9,25 +9,30 @@ use std::{
 };
 
 use cocoa::{
-  appkit::{self, NSApplicationPresentationOptions, NSView, NSWindow},
+  appkit::{
+    self, NSApplicationPresentationOptions, NSEvent, NSScreen, NSView, NSWindow, NSWindowStyleMask,
+  },
   base::{id, nil},
-  foundation::{NSAutoreleasePool, NSString, NSUInteger},
+  foundation::{NSAutoreleasePool, NSPoint, NSString, NSUInteger},
 };
 use objc::{
   declare::ClassDecl,
   runtime::{Class, Object, Sel, BOOL, NO, YES},
 };
 
+#[cfg(feature = "accesskit")]
+use crate::platform_impl::platform::accessibility;
 use crate::{
-  dpi::{LogicalPosition, LogicalSize},
-  event::{Event, WindowEvent},
+  dpi::{LogicalPosition, LogicalSize, PhysicalPosition},
+  event::{DragDropEvent, DropData, Event, WindowEvent},
   keyboard::ModifiersState,
   platform_impl::platform::{
     app_state::AppState,
     event::{EventProxy, EventWrapper},
     util::{self, IdRef},
     view::ViewState,
-    window::{get_ns_theme, get_window_id, UnownedWindow},
+    window::{get_ns_theme, get_window_id, set_drop_highlight, UnownedWindow},
+    DEVICE_ID,
   },
   window::{Fullscreen, WindowId},
 };
@@ -54,6 +59,9 @@ pub struct WindowDelegateState {
   // Used to prevent resized events from being fired
   // when we are using our workaround in the `is_zoomed` function.
   is_checking_zoomed_in: bool,
+
+  // During `windowDidResize`, we use this to only send Maximized if the zoom state changed.
+  previous_zoomed: bool,
 }
 
 impl WindowDelegateState {
@@ -67,6 +75,7 @@ impl WindowDelegateState {
       previous_position: None,
       previous_scale_factor: scale_factor,
       is_checking_zoomed_in: false,
+      previous_zoomed: false,
     };
     if (scale_factor - 1.0).abs() > f64::EPSILON {
       delegate_state.emit_static_scale_factor_changed_event();
@@ -126,6 +135,60 @@ impl WindowDelegateState {
     }
   }
 
+  fn emit_maximized_event(&mut self) {
+    let zoomed = unsafe {
+      let mask = self.ns_window.styleMask();
+      if mask
+        .contains(NSWindowStyleMask::NSTitledWindowMask | NSWindowStyleMask::NSResizableWindowMask)
+      {
+        let is_zoomed: BOOL = msg_send![*self.ns_window, isZoomed];
+        is_zoomed != NO
+      } else {
+        // `isZoomed` doesn't work without a resizable title bar, and `set_maximized` makes
+        // such windows cover the visible frame of the screen instead.
+        let screen: id = msg_send![*self.ns_window, screen];
+        if screen == nil {
+          false
+        } else {
+          let frame = NSWindow::frame(*self.ns_window);
+          let visible_frame = NSScreen::visibleFrame(screen);
+          frame.origin.x == visible_frame.origin.x
+            && frame.origin.y == visible_frame.origin.y
+            && frame.size.width == visible_frame.size.width
+            && frame.size.height == visible_frame.size.height
+        }
+      }
+    };
+    if zoomed != self.previous_zoomed {
+      self.previous_zoomed = zoomed;
+      self.emit_event(WindowEvent::Maximized(zoomed));
+    }
+  }
+
+  fn save_restored_frame(&mut self) {
+    let (frame, content_size) = unsafe {
+      let mask = self.ns_window.styleMask();
+      if self.previous_zoomed || mask.contains(NSWindowStyleMask::NSFullScreenWindowMask) {
+        return;
+      }
+      let frame = NSWindow::frame(*self.ns_window);
+      // Simple fullscreen covers the whole screen without being a native fullscreen window.
+      let screen: id = msg_send![*self.ns_window, screen];
+      if screen != nil {
+        let screen_frame = NSScreen::frame(screen);
+        if frame.origin.x == screen_frame.origin.x
+          && frame.origin.y == screen_frame.origin.y
+          && frame.size.width == screen_frame.size.width
+          && frame.size.height == screen_frame.size.height
+        {
+          return;
+        }
+      }
+      (frame, NSView::frame(*self.ns_view).size)
+    };
+    self.with_window(|window| *window.restored_frame.lock().unwrap() = Some((frame, content_size)));
+  }
+
   fn get_scale_factor(&self) -> f64 {
     (unsafe { NSWindow::backingScaleFactor(*self.ns_window) }) as f64
   }
@@ -169,6 +232,15 @@ lazy_static! {
       clear_is_checking_zoomed_in as extern "C" fn(&Object, Sel),
     );
 
+    decl.add_method(
+      sel!(miniaturizeToTray),
+      miniaturize_to_tray as extern "C" fn(&Object, Sel) -> BOOL,
+    );
+    decl.add_method(
+      sel!(isInDragRegion:),
+      is_in_drag_region as extern "C" fn(&Object, Sel, id) -> BOOL,
+    );
+
     decl.add_method(
       sel!(windowShouldClose:),
       window_should_close as extern "C" fn(&Object, Sel, id) -> BOOL,
@@ -181,6 +253,14 @@ lazy_static! {
       sel!(windowDidResize:),
       window_did_resize as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(windowDidMiniaturize:),
+      window_did_miniaturize as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(windowDidDeminiaturize:),
+      window_did_deminiaturize as extern "C" fn(&Object, Sel, id),
+    );
     decl.add_method(
       sel!(windowDidMove:),
       window_did_move as extern "C" fn(&Object, Sel, id),
@@ -197,11 +277,31 @@ lazy_static! {
       sel!(windowDidResignKey:),
       window_did_resign_key as extern "C" fn(&Object, Sel, id),
     );
+    decl.add_method(
+      sel!(windowDidBecomeMain:),
+      window_did_become_main as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(windowDidResignMain:),
+      window_did_resign_main as extern "C" fn(&Object, Sel, id),
+    );
+    decl.add_method(
+      sel!(observeValueForKeyPath:ofObject:change:context:),
+      observe_value_for_key_path as extern "C" fn(&Object, Sel, id, id, id, *mut c_void),
+    );
+    decl.add_method(
+      sel!(windowDidChangeOcclusionState:),
+      window_did_change_occlusion_state as extern "C" fn(&Object, Sel, id),
+    );
 
     decl.add_method(
       sel!(draggingEntered:),
       dragging_entered as extern "C" fn(&Object, Sel, id) -> BOOL,
     );
+    decl.add_method(
+      sel!(draggingUpdated:),
+      dragging_updated as extern "C" fn(&Object, Sel, id) -> NSUInteger,
+    );
     decl.add_method(
       sel!(prepareForDragOperation:),
       prepare_for_drag_operation as extern "C" fn(&Object, Sel, id) -> BOOL,
@@ -270,10 +370,23 @@ fn with_state<F: FnOnce(&mut WindowDelegateState) -> T, T>(this: &Object, callba
 
 extern "C" fn dealloc(this: &Object, _sel: Sel) {
   with_state(this, |state| unsafe {
+    if has_tab_groups(*state.ns_window) {
+      let key_path = NSString::alloc(nil).init_str(TAB_GROUP_WINDOWS_KEY_PATH);
+      let _: () = msg_send![*state.ns_window, removeObserver: this forKeyPath: key_path];
+    }
     Box::from_raw(state as *mut WindowDelegateState);
   });
 }
 
+/// The key path observed for `WindowEvent::TabGroupChanged`.
+const TAB_GROUP_WINDOWS_KEY_PATH: &str = "tabGroup.windows";
+
+/// Whether the window has a tab group, since macOS 10.13.
+unsafe fn has_tab_groups(ns_window: id) -> bool {
+  let responds: BOOL = msg_send![ns_window, respondsToSelector: sel!(tabGroup)];
+  responds == YES
+}
+
 extern "C" fn init_with_tao(this: &Object, _sel: Sel, state: *mut c_void) -> id {
   unsafe {
     let this: id = msg_send![this, init];
@@ -281,6 +394,16 @@ extern "C" fn init_with_tao(this: &Object, _sel: Sel, state: *mut c_void) -> id
       (*this).set_ivar("taoState", state);
       with_state(&*this, |state| {
         let () = msg_send![*state.ns_window, setDelegate: this];
+        if has_tab_groups(*state.ns_window) {
+          let key_path = NSString::alloc(nil).init_str(TAB_GROUP_WINDOWS_KEY_PATH);
+          let () = msg_send![
+            *state.ns_window,
+            addObserver: this
+            forKeyPath: key_path
+            options: 0 as NSUInteger
+            context: std::ptr::null_mut::<c_void>()
+          ];
+        }
       });
     }
 
@@ -311,6 +434,57 @@ extern "C" fn clear_is_checking_zoomed_in(this: &Object, _sel: Sel) {
   });
 }
 
+extern "C" fn miniaturize_to_tray(this: &Object, _: Sel) -> BOOL {
+  trace!("Triggered `miniaturizeToTray`");
+  let mut handled = NO;
+  with_state(this, |state| {
+    let minimize_to_tray = state
+      .with_window(|window| window.shared_state.lock().unwrap().minimize_to_tray)
+      .unwrap_or(false);
+    if minimize_to_tray {
+      unsafe {
+        let () = msg_send![*state.ns_window, orderOut: nil];
+      }
+      state.emit_event(WindowEvent::MinimizedToTray);
+      handled = YES;
+    }
+  });
+  trace!("Completed `miniaturizeToTray`");
+  handled
+}
+
+extern "C" fn is_in_drag_region(this: &Object, _: Sel, event: id) -> BOOL {
+  let mut in_drag_region = NO;
+  with_state(this, |state| {
+    let position = unsafe {
+      let view_point = state
+        .ns_view
+        .convertPoint_fromView_(event.locationInWindow(), nil);
+      let view_rect = NSView::frame(*state.ns_view);
+      let scale_factor = NSWindow::backingScaleFactor(*state.ns_window);
+      LogicalPosition::new(view_point.x, view_rect.size.height - view_point.y)
+        .to_physical(scale_factor)
+    };
+    let hit = state
+      .with_window(|window| {
+        let shared_state = window.shared_state.lock().unwrap();
+        shared_state
+          .drag_regions
+          .iter()
+          .any(|r| r.contains(position))
+          && !shared_state
+            .drag_exclusions
+            .iter()
+            .any(|r| r.contains(position))
+      })
+      .unwrap_or(false);
+    if hit {
+      in_drag_region = YES;
+    }
+  });
+  in_drag_region
+}
+
 extern "C" fn window_should_close(this: &Object, _: Sel, _: id) -> BOOL {
   trace!("Triggered `windowShouldClose:`");
   with_state(this, |state| state.emit_event(WindowEvent::CloseRequested));
@@ -338,16 +512,37 @@ extern "C" fn window_did_resize(this: &Object, _: Sel, _: id) {
     if !state.is_checking_zoomed_in {
       state.emit_resize_event();
       state.emit_move_event();
+      state.emit_maximized_event();
+      state.save_restored_frame();
     }
   });
   trace!("Completed `windowDidResize:`");
 }
 
+extern "C" fn window_did_miniaturize(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidMiniaturize:`");
+  with_state(this, |state| {
+    state.emit_event(WindowEvent::Minimized(true));
+  });
+  trace!("Completed `windowDidMiniaturize:`");
+}
+
+extern "C" fn window_did_deminiaturize(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidDeminiaturize:`");
+  with_state(this, |state| {
+    state.emit_event(WindowEvent::Minimized(false));
+  });
+  trace!("Completed `windowDidDeminiaturize:`");
+}
+
 // This won't be triggered if the move was part of a resize.
 extern "C" fn window_did_move(this: &Object, _: Sel, _: id) {
   trace!("Triggered `windowDidMove:`");
   with_state(this, |state| {
     state.emit_move_event();
+    if !state.is_checking_zoomed_in {
+      state.save_restored_frame();
+    }
   });
   trace!("Completed `windowDidMove:`");
 }
@@ -366,6 +561,8 @@ extern "C" fn window_did_become_key(this: &Object, _: Sel, _: id) {
     // TODO: center the cursor if the window had mouse grab when it
     // lost focus
     state.emit_event(WindowEvent::Focused(true));
+    #[cfg(feature = "accesskit")]
+    state.with_window(|window| accessibility::update_focus(&window.accessibility, true));
   });
   trace!("Completed `windowDidBecomeKey:`");
 }
@@ -390,6 +587,14 @@ extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) {
       &mut *(state_ptr as *mut ViewState)
     };
 
+    for event in view_state.release_pressed_keys() {
+      state.emit_event(WindowEvent::KeyboardInput {
+        device_id: DEVICE_ID,
+        event,
+        is_synthetic: true,
+      });
+    }
+
     // Both update the state and emit a ModifiersChanged event.
     if !view_state.modifiers.is_empty() {
       view_state.modifiers = ModifiersState::empty();
@@ -397,37 +602,153 @@ extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) {
     }
 
     state.emit_event(WindowEvent::Focused(false));
+    #[cfg(feature = "accesskit")]
+    state.with_window(|window| accessibility::update_focus(&window.accessibility, false));
   });
   trace!("Completed `windowDidResignKey:`");
 }
 
-/// Invoked when the dragged image enters destination bounds or frame
-extern "C" fn dragging_entered(this: &Object, _: Sel, sender: id) -> BOOL {
-  trace!("Triggered `draggingEntered:`");
+extern "C" fn window_did_become_main(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidBecomeMain:`");
+  with_state(this, |state| {
+    state.emit_event(WindowEvent::MainWindowChanged(true));
+  });
+  trace!("Completed `windowDidBecomeMain:`");
+}
 
-  use cocoa::{appkit::NSPasteboard, foundation::NSFastEnumeration};
-  use std::path::PathBuf;
+extern "C" fn window_did_resign_main(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidResignMain:`");
+  with_state(this, |state| {
+    state.emit_event(WindowEvent::MainWindowChanged(false));
+  });
+  trace!("Completed `windowDidResignMain:`");
+}
 
-  let pb: id = unsafe { msg_send![sender, draggingPasteboard] };
-  let filenames = unsafe { NSPasteboard::propertyListForType(pb, appkit::NSFilenamesPboardType) };
+extern "C" fn window_did_change_occlusion_state(this: &Object, _: Sel, _: id) {
+  trace!("Triggered `windowDidChangeOcclusionState:`");
+  with_state(this, |state| {
+    let occlusion_state: NSUInteger = unsafe { msg_send![*state.ns_window, occlusionState] };
+    // NSWindowOcclusionStateVisible
+    let occluded = occlusion_state & (1 << 1) == 0;
+    state.emit_event(WindowEvent::Occluded(occluded));
+  });
+  trace!("Completed `windowDidChangeOcclusionState:`");
+}
+
+/// Called when the windows of the tab group of the window change, which is the only key path
+/// observed.
+extern "C" fn observe_value_for_key_path(
+  this: &Object,
+  _: Sel,
+  _key_path: id,
+  _object: id,
+  _change: id,
+  _context: *mut c_void,
+) {
+  trace!("Triggered `observeValueForKeyPath:ofObject:change:context:`");
+  with_state(this, |state| {
+    state.emit_event(WindowEvent::TabGroupChanged);
+  });
+  trace!("Completed `observeValueForKeyPath:ofObject:change:context:`");
+}
 
-  for file in unsafe { filenames.iter() } {
-    use std::ffi::CStr;
+/// The pasteboard types of the items accepted by the window.
+pub unsafe fn dragged_types() -> id {
+  use cocoa::foundation::NSArray;
+
+  NSArray::arrayWithObjects(
+    nil,
+    &[
+      appkit::NSFilenamesPboardType,
+      NSString::alloc(nil).init_str("public.url").autorelease(),
+      NSString::alloc(nil)
+        .init_str("public.utf8-plain-text")
+        .autorelease(),
+      NSString::alloc(nil).init_str("public.html").autorelease(),
+    ],
+  )
+}
 
-    unsafe {
-      let f = NSString::UTF8String(file);
-      let path = CStr::from_ptr(f).to_string_lossy().into_owned();
+/// Reads the content of the item dragged by `sender`.
+unsafe fn drop_data(sender: id) -> DropData {
+  use cocoa::{appkit::NSPasteboard, foundation::NSFastEnumeration};
+  use std::{ffi::CStr, path::PathBuf};
+
+  let pb: id = msg_send![sender, draggingPasteboard];
+  let filenames = NSPasteboard::propertyListForType(pb, appkit::NSFilenamesPboardType);
+  let paths = filenames
+    .iter()
+    .map(|file| {
+      PathBuf::from(
+        CStr::from_ptr(NSString::UTF8String(file))
+          .to_string_lossy()
+          .into_owned(),
+      )
+    })
+    .collect();
 
-      with_state(this, |state| {
-        state.emit_event(WindowEvent::HoveredFile(PathBuf::from(path)));
-      });
+  let string_for_type = |pb_type: &str| {
+    let pb_type = NSString::alloc(nil).init_str(pb_type).autorelease();
+    let string: id = msg_send![pb, stringForType: pb_type];
+    if string == nil {
+      None
+    } else {
+      Some(util::ns_string_to_rust(string))
     }
+  };
+  DropData {
+    paths,
+    uris: string_for_type("public.url")
+      .filter(|url| !url.starts_with("file:"))
+      .into_iter()
+      .collect(),
+    text: string_for_type("public.utf8-plain-text"),
+    html: string_for_type("public.html"),
   }
+}
+
+/// Returns the position of the item dragged by `sender` in the content view.
+unsafe fn drag_position(state: &WindowDelegateState, sender: id) -> PhysicalPosition<f64> {
+  let location: NSPoint = msg_send![sender, draggingLocation];
+  let view_point = state.ns_view.convertPoint_fromView_(location, nil);
+  let view_rect = NSView::frame(*state.ns_view);
+  LogicalPosition::new(view_point.x, view_rect.size.height - view_point.y)
+    .to_physical(state.get_scale_factor())
+}
+
+/// Invoked when the dragged image enters destination bounds or frame
+extern "C" fn dragging_entered(this: &Object, _: Sel, sender: id) -> BOOL {
+  trace!("Triggered `draggingEntered:`");
+
+  let data = unsafe { drop_data(sender) };
+  with_state(this, |state| {
+    for path in &data.paths {
+      state.emit_event(WindowEvent::HoveredFile(path.clone()));
+    }
+    if !data.is_empty() {
+      let position = unsafe { drag_position(state, sender) };
+      state.emit_event(WindowEvent::DragDrop(DragDropEvent::Entered {
+        data,
+        position,
+      }));
+    }
+  });
 
   trace!("Completed `draggingEntered:`");
   YES
 }
 
+/// Invoked periodically as the image is held within the destination area
+extern "C" fn dragging_updated(this: &Object, _: Sel, sender: id) -> NSUInteger {
+  with_state(this, |state| {
+    let position = unsafe { drag_position(state, sender) };
+    state.emit_event(WindowEvent::DragDrop(DragDropEvent::Moved { position }));
+  });
+
+  // NSDragOperationCopy
+  1
+}
+
 /// Invoked when the image is released
 extern "C" fn prepare_for_drag_operation(_: &Object, _: Sel, _: id) -> BOOL {
   trace!("Triggered `prepareForDragOperation:`");
@@ -439,24 +760,20 @@ extern "C" fn prepare_for_drag_operation(_: &Object, _: Sel, _: id) -> BOOL {
 extern "C" fn perform_drag_operation(this: &Object, _: Sel, sender: id) -> BOOL {
   trace!("Triggered `performDragOperation:`");
 
-  use cocoa::{appkit::NSPasteboard, foundation::NSFastEnumeration};
-  use std::path::PathBuf;
-
-  let pb: id = unsafe { msg_send![sender, draggingPasteboard] };
-  let filenames = unsafe { NSPasteboard::propertyListForType(pb, appkit::NSFilenamesPboardType) };
-
-  for file in unsafe { filenames.iter() } {
-    use std::ffi::CStr;
-
-    unsafe {
-      let f = NSString::UTF8String(file);
-      let path = CStr::from_ptr(f).to_string_lossy().into_owned();
-
-      with_state(this, |state| {
-        state.emit_event(WindowEvent::DroppedFile(PathBuf::from(path)));
-      });
+  let data = unsafe { drop_data(sender) };
+  with_state(this, |state| {
+    unsafe { set_drop_highlight(*state.ns_window, false) };
+    for path in &data.paths {
+      state.emit_event(WindowEvent::DroppedFile(path.clone()));
     }
-  }
+    if !data.is_empty() {
+      let position = unsafe { drag_position(state, sender) };
+      state.emit_event(WindowEvent::DragDrop(DragDropEvent::Dropped {
+        data,
+        position,
+      }));
+    }
+  });
 
   trace!("Completed `performDragOperation:`");
   YES
@@ -472,7 +789,9 @@ extern "C" fn conclude_drag_operation(_: &Object, _: Sel, _: id) {
 extern "C" fn dragging_exited(this: &Object, _: Sel, _: id) {
   trace!("Triggered `draggingExited:`");
   with_state(this, |state| {
-    state.emit_event(WindowEvent::HoveredFileCancelled)
+    unsafe { set_drop_highlight(*state.ns_window, false) };
+    state.emit_event(WindowEvent::HoveredFileCancelled);
+    state.emit_event(WindowEvent::DragDrop(DragDropEvent::Left));
   });
   trace!("Completed `draggingExited:`");
 }
@@ -647,13 +966,17 @@ extern "C" fn effective_appearance_did_change(this: &Object, _: Sel, _: id) {
 extern "C" fn effective_appearance_did_changed_on_main_thread(this: &Object, _: Sel, _: id) {
   with_state(this, |state| {
     let theme = get_ns_theme();
-    let current_theme = state.window.upgrade().map(|w| {
+    let current_theme = state.window.upgrade().and_then(|w| {
       let mut state = w.shared_state.lock().unwrap();
+      // A theme forced with `set_theme` doesn't follow the system.
+      if state.preferred_theme.is_some() {
+        return None;
+      }
       let current_theme = state.current_theme;
       state.current_theme = theme;
-      current_theme
+      Some(current_theme)
     });
-    if current_theme != Some(theme) {
+    if current_theme.map_or(false, |current_theme| current_theme != theme) {
       state.emit_event(WindowEvent::ThemeChanged(theme));
     }
   });
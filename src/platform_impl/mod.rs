@@ -26,6 +26,16 @@ mod platform;
 #[path = "ios/mod.rs"]
 mod platform;
 
+#[cfg(any(
+  target_os = "windows",
+  target_os = "linux",
+  target_os = "dragonfly",
+  target_os = "freebsd",
+  target_os = "netbsd",
+  target_os = "openbsd"
+))]
+mod opened_urls;
+
 #[cfg(all(
   not(target_os = "ios"),
   not(target_os = "windows"),
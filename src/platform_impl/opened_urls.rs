@@ -0,0 +1,28 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use url::Url;
+
+/// The command-line arguments of the process that are URLs with a scheme or paths of existing
+/// files, reported as `Event::Opened` on the platforms where the shell opens them this way.
+pub fn from_args() -> Vec<Url> {
+  let cwd = std::env::current_dir().unwrap_or_default();
+  std::env::args_os()
+    .skip(1)
+    .filter_map(|arg| {
+      let arg = arg.to_str()?;
+      // Drive letters of Windows paths parse as one-letter schemes.
+      match Url::parse(arg) {
+        Ok(url) if url.scheme().len() > 1 => return Some(url),
+        _ => (),
+      }
+      let path = cwd.join(arg);
+      if path.is_file() {
+        Url::from_file_path(path).ok()
+      } else {
+        None
+      }
+    })
+    .collect()
+}
@@ -0,0 +1,124 @@
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! The accessibility tree of a window, exposed through UI Automation by AccessKit, which answers
+//! the `WM_GETOBJECT` messages of the window.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+  UI::WindowsAndMessaging::PostMessageW,
+};
+
+use crate::{
+  accesskit::{ActionHandler, ActionRequest, ActivationHandler, TreeUpdate},
+  platform_impl::platform::event_loop::ACCESSIBILITY_ACTION_MSG_ID,
+};
+
+pub(crate) type AccessibilitySource = Box<dyn FnMut() -> TreeUpdate + Send>;
+
+/// The adapter of a window, if it was given a source, shared by the `Window` and its window
+/// procedure.
+pub(crate) type Accessibility = Arc<Mutex<Option<Adapter>>>;
+
+pub(crate) struct Adapter {
+  adapter: accesskit_windows::Adapter,
+  source: Source,
+}
+
+struct Source(AccessibilitySource);
+
+impl ActivationHandler for Source {
+  fn request_initial_tree(&mut self) -> Option<TreeUpdate> {
+    Some((self.0)())
+  }
+}
+
+/// Posts the requested actions to the window, which reports them from its window procedure.
+struct ActionForwarder {
+  window: HWND,
+}
+
+impl ActionHandler for ActionForwarder {
+  fn do_action(&mut self, request: ActionRequest) {
+    let request = Box::into_raw(Box::new(request));
+    unsafe {
+      if !PostMessageW(
+        self.window,
+        *ACCESSIBILITY_ACTION_MSG_ID,
+        WPARAM(request as _),
+        LPARAM(0),
+      )
+      .as_bool()
+      {
+        drop(Box::from_raw(request));
+      }
+    }
+  }
+}
+
+pub(crate) fn set_source(
+  accessibility: &Accessibility,
+  window: HWND,
+  is_focused: bool,
+  source: AccessibilitySource,
+) {
+  let adapter = accesskit_windows::Adapter::new(
+    accesskit_windows::HWND(window.0 as _),
+    is_focused,
+    ActionForwarder { window },
+  );
+  *accessibility.lock() = Some(Adapter {
+    adapter,
+    source: Source(source),
+  });
+}
+
+pub(crate) fn update(accessibility: &Accessibility, update: impl FnOnce() -> TreeUpdate) {
+  let events = match &mut *accessibility.lock() {
+    Some(adapter) => adapter.adapter.update_if_active(update),
+    None => None,
+  };
+  // The events are raised without the lock, as they may trigger `WM_GETOBJECT`.
+  if let Some(events) = events {
+    events.raise();
+  }
+}
+
+pub(crate) fn update_focus(accessibility: &Accessibility, is_focused: bool) {
+  let events = match &mut *accessibility.lock() {
+    Some(adapter) => adapter.adapter.update_window_focus_state(is_focused),
+    None => None,
+  };
+  if let Some(events) = events {
+    events.raise();
+  }
+}
+
+/// Answers `WM_GETOBJECT`, unless the window has no source or the message is left to
+/// `DefWindowProc`.
+pub(crate) fn handle_wm_getobject(
+  accessibility: &Accessibility,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> Option<LRESULT> {
+  let result = {
+    let mut accessibility = accessibility.lock();
+    let Adapter { adapter, source } = accessibility.as_mut()?;
+    adapter.handle_wm_getobject(
+      accesskit_windows::WPARAM(wparam.0),
+      accesskit_windows::LPARAM(lparam.0),
+      source,
+    )?
+  };
+  // Returning the provider may send a nested `WM_GETOBJECT`, so it's done without the lock.
+  let result: accesskit_windows::LRESULT = result.into();
+  Some(LRESULT(result.0))
+}
+
+/// Takes back the request posted by `ActionForwarder`.
+pub(crate) unsafe fn take_action_request(wparam: WPARAM) -> ActionRequest {
+  *Box::from_raw(wparam.0 as *mut ActionRequest)
+}
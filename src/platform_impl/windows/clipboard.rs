@@ -3,20 +3,28 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::util;
-use crate::clipboard::{ClipboardFormat, FormatId};
-use std::{ffi::OsStr, os::windows::ffi::OsStrExt, ptr};
+use crate::clipboard::{ClipboardFormat, FormatId, RgbaImage};
+use std::{
+  ffi::{OsStr, OsString},
+  mem,
+  os::windows::ffi::{OsStrExt, OsStringExt},
+  path::PathBuf,
+  ptr, slice,
+};
 use windows::{
   core::{PCWSTR, PWSTR},
   Win32::{
-    Foundation::{HANDLE, HWND},
+    Foundation::{BOOL, HANDLE, HWND},
+    Graphics::Gdi::{BITMAPINFOHEADER, BI_BITFIELDS, BI_RGB},
     System::{
       DataExchange::{
         CloseClipboard, EmptyClipboard, GetClipboardData, OpenClipboard, RegisterClipboardFormatW,
         SetClipboardData,
       },
-      Memory::{GlobalAlloc, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
-      SystemServices::CF_UNICODETEXT,
+      Memory::{GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
+      SystemServices::{CF_DIB, CF_HDROP, CF_UNICODETEXT},
     },
+    UI::Shell::{DragQueryFileW, DROPFILES, HDROP},
   },
 };
 
@@ -54,6 +62,101 @@ impl Clipboard {
     .flatten()
   }
 
+  pub(crate) fn write_html(&mut self, html: impl AsRef<str>, alt_text: impl AsRef<str>) {
+    let html = ClipboardFormat::new(ClipboardFormat::HTML, to_cf_html(html.as_ref()));
+    self.put_formats(&[html, alt_text.as_ref().into()]);
+  }
+
+  pub(crate) fn read_html(&self) -> Option<String> {
+    let format_id = register_identifier(ClipboardFormat::HTML)?;
+    with_clipboard(|| unsafe {
+      let handle = GetClipboardData(format_id).unwrap_or_default();
+      if handle.is_invalid() {
+        return None;
+      }
+      let locked = GlobalLock(handle.0) as *const u8;
+      if locked.is_null() {
+        return None;
+      }
+      let html = from_cf_html(slice::from_raw_parts(locked, GlobalSize(handle.0)));
+      GlobalUnlock(handle.0);
+      html
+    })
+    .flatten()
+  }
+
+  pub(crate) fn write_files(&mut self, paths: Vec<PathBuf>) {
+    let header = DROPFILES {
+      pFiles: mem::size_of::<DROPFILES>() as u32,
+      fWide: BOOL::from(true),
+      ..Default::default()
+    };
+
+    // The header is followed by the nul terminated paths, and an empty one to end the list.
+    let mut data = unsafe {
+      slice::from_raw_parts(
+        &header as *const _ as *const u8,
+        mem::size_of::<DROPFILES>(),
+      )
+    }
+    .to_vec();
+    for path in &paths {
+      for c in path.as_os_str().encode_wide().chain(Some(0)) {
+        data.extend_from_slice(&c.to_le_bytes());
+      }
+    }
+    data.extend_from_slice(&[0, 0]);
+    self.put_formats(&[ClipboardFormat::new("CF_HDROP", data)]);
+  }
+
+  pub(crate) fn read_files(&self) -> Option<Vec<PathBuf>> {
+    with_clipboard(|| unsafe {
+      let handle = GetClipboardData(CF_HDROP.0).unwrap_or_default();
+      if handle.is_invalid() {
+        return None;
+      }
+      let hdrop = HDROP(handle.0);
+      let count = DragQueryFileW(hdrop, u32::MAX, &mut []);
+      if count == 0 {
+        return None;
+      }
+      let paths = (0..count)
+        .map(|i| {
+          let len = DragQueryFileW(hdrop, i, &mut []) as usize;
+          let mut path = vec![0u16; len + 1];
+          DragQueryFileW(hdrop, i, &mut path);
+          path.truncate(len);
+          PathBuf::from(OsString::from_wide(&path))
+        })
+        .collect();
+      Some(paths)
+    })
+    .flatten()
+  }
+
+  pub(crate) fn write_image(&mut self, image: &RgbaImage) {
+    if let Some(dib) = rgba_to_dib(image) {
+      self.put_formats(&[ClipboardFormat::new("CF_DIB", dib)]);
+    }
+  }
+
+  pub(crate) fn read_image(&self) -> Option<RgbaImage> {
+    with_clipboard(|| unsafe {
+      let handle = GetClipboardData(CF_DIB.0).unwrap_or_default();
+      if handle.is_invalid() {
+        return None;
+      }
+      let locked = GlobalLock(handle.0) as *const u8;
+      if locked.is_null() {
+        return None;
+      }
+      let image = dib_to_rgba(slice::from_raw_parts(locked, GlobalSize(handle.0)));
+      GlobalUnlock(handle.0);
+      image
+    })
+    .flatten()
+  }
+
   pub(crate) fn put_formats(&mut self, formats: &[ClipboardFormat]) {
     with_clipboard(|| unsafe {
       EmptyClipboard();
@@ -80,6 +183,158 @@ impl Clipboard {
   }
 }
 
+/// Wraps an HTML fragment in the `CF_HTML` description header.
+///
+/// See <https://docs.microsoft.com/en-us/windows/win32/dataxchg/html-clipboard-format>.
+fn to_cf_html(fragment: &str) -> Vec<u8> {
+  const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+  const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+
+  // The offsets are zero padded, so the header length does not depend on them.
+  let header_len = format_cf_html_header(0, 0, 0, 0).len();
+  let start_fragment = header_len + PREFIX.len();
+  let end_fragment = start_fragment + fragment.len();
+  let end_html = end_fragment + SUFFIX.len();
+
+  let mut data = format_cf_html_header(header_len, end_html, start_fragment, end_fragment);
+  data.push_str(PREFIX);
+  data.push_str(fragment);
+  data.push_str(SUFFIX);
+  let mut data = data.into_bytes();
+  data.push(0);
+  data
+}
+
+fn format_cf_html_header(
+  start_html: usize,
+  end_html: usize,
+  start_fragment: usize,
+  end_fragment: usize,
+) -> String {
+  format!(
+    "Version:0.9\r\nStartHTML:{:010}\r\nEndHTML:{:010}\r\nStartFragment:{:010}\r\nEndFragment:{:010}\r\n",
+    start_html, end_html, start_fragment, end_fragment
+  )
+}
+
+/// Extracts the fragment from `CF_HTML` data.
+pub(crate) fn from_cf_html(data: &[u8]) -> Option<String> {
+  let data = match data.iter().position(|b| *b == 0) {
+    Some(end) => &data[..end],
+    None => data,
+  };
+
+  let mut start_fragment = None;
+  let mut end_fragment = None;
+  // The description is ASCII, stop at the first line which is not part of it.
+  for line in data.split(|b| *b == b'\n') {
+    let line = match std::str::from_utf8(line) {
+      Ok(line) => line.trim_end_matches('\r'),
+      Err(_) => break,
+    };
+    match line.split_once(':') {
+      Some(("StartFragment", offset)) => start_fragment = offset.trim().parse::<usize>().ok(),
+      Some(("EndFragment", offset)) => end_fragment = offset.trim().parse::<usize>().ok(),
+      Some(_) => (),
+      None => break,
+    }
+    if start_fragment.is_some() && end_fragment.is_some() {
+      break;
+    }
+  }
+
+  let fragment = data.get(start_fragment?..end_fragment?)?;
+  Some(String::from_utf8_lossy(fragment).into_owned())
+}
+
+/// Converts an image to a packed 32bpp DIB.
+fn rgba_to_dib(image: &RgbaImage) -> Option<Vec<u8>> {
+  let (width, height) = (image.width() as usize, image.height() as usize);
+  if width == 0 || height == 0 {
+    return None;
+  }
+  let header = BITMAPINFOHEADER {
+    biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+    biWidth: width as i32,
+    // Positive for a bottom-up bitmap, which is what most applications expect.
+    biHeight: height as i32,
+    biPlanes: 1,
+    biBitCount: 32,
+    biCompression: BI_RGB as u32,
+    biSizeImage: (width * height * 4) as u32,
+    ..Default::default()
+  };
+
+  let mut data = Vec::with_capacity(header.biSize as usize + width * height * 4);
+  data.extend_from_slice(unsafe {
+    slice::from_raw_parts(
+      &header as *const _ as *const u8,
+      mem::size_of::<BITMAPINFOHEADER>(),
+    )
+  });
+  for row in image.rgba().chunks_exact(width * 4).rev() {
+    for pixel in row.chunks_exact(4) {
+      data.extend_from_slice(&[pixel[2], pixel[1], pixel[0], pixel[3]]);
+    }
+  }
+  Some(data)
+}
+
+/// Converts a packed 24 or 32bpp DIB to RGBA.
+unsafe fn dib_to_rgba(dib: &[u8]) -> Option<RgbaImage> {
+  if dib.len() < mem::size_of::<BITMAPINFOHEADER>() {
+    return None;
+  }
+  let header = ptr::read_unaligned(dib.as_ptr() as *const BITMAPINFOHEADER);
+  let compression = header.biCompression as i32;
+  if !matches!(header.biBitCount, 24 | 32)
+    || !(compression == BI_RGB || compression == BI_BITFIELDS)
+    || header.biWidth <= 0
+  {
+    return None;
+  }
+
+  let width = header.biWidth as usize;
+  let height = header.biHeight.unsigned_abs() as usize;
+  let bytes_per_pixel = header.biBitCount as usize / 8;
+  // Rows are padded to 4 bytes.
+  let stride = width.checked_mul(bytes_per_pixel)?.checked_add(3)? & !3;
+
+  // The pixels follow the header, the color masks of `BI_BITFIELDS` bitmaps and the color table.
+  let mut offset =
+    (header.biSize as usize).checked_add((header.biClrUsed as usize).checked_mul(4)?)?;
+  if compression == BI_BITFIELDS && header.biSize as usize == mem::size_of::<BITMAPINFOHEADER>() {
+    offset = offset.checked_add(3 * 4)?;
+  }
+  let pixels = dib.get(offset..offset.checked_add(stride.checked_mul(height)?)?)?;
+
+  let mut rgba = Vec::with_capacity(width * height * 4);
+  let mut has_alpha = false;
+  for y in 0..height {
+    let row = if header.biHeight > 0 {
+      height - 1 - y
+    } else {
+      y
+    };
+    let row = &pixels[row * stride..row * stride + width * bytes_per_pixel];
+    for pixel in row.chunks_exact(bytes_per_pixel) {
+      let alpha = if bytes_per_pixel == 4 { pixel[3] } else { 0 };
+      has_alpha |= alpha != 0;
+      rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], alpha]);
+    }
+  }
+  // Most applications leave the fourth byte unused, in which case the image is opaque.
+  if !has_alpha {
+    rgba
+      .iter_mut()
+      .skip(3)
+      .step_by(4)
+      .for_each(|alpha| *alpha = u8::MAX);
+  }
+
+  RgbaImage::from_rgba(rgba, width as u32, height as u32).ok()
+}
+
 fn get_format_id(format: FormatId) -> Option<u32> {
   if let Some((id, _)) = STANDARD_FORMATS.iter().find(|(_, s)| s == &format) {
     return Some(*id);
@@ -169,3 +424,72 @@ static STANDARD_FORMATS: &[(u32, &str)] = &[
   (0x0300, "CF_GDIOBJFIRST"),
   (0x03FF, "CF_GDIOBJLAST"),
 ];
+
+#[test]
+fn test_dib_to_rgba() {
+  let rgba = vec![
+    255, 0, 0, 255, 0, 255, 0, 128, //
+    0, 0, 255, 0, 10, 20, 30, 40,
+  ];
+  let image = RgbaImage::from_rgba(rgba.clone(), 2, 2).unwrap();
+  let dib = rgba_to_dib(&image).unwrap();
+  assert_eq!(dib.len(), mem::size_of::<BITMAPINFOHEADER>() + rgba.len());
+  let image = unsafe { dib_to_rgba(&dib) }.unwrap();
+  assert_eq!((image.width(), image.height()), (2, 2));
+  assert_eq!(image.rgba(), &rgba[..]);
+
+  // A top-down 24bpp bitmap, whose 3 bytes wide rows are padded to 4 bytes.
+  let header = BITMAPINFOHEADER {
+    biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+    biWidth: 1,
+    biHeight: -2,
+    biPlanes: 1,
+    biBitCount: 24,
+    biCompression: BI_RGB as u32,
+    ..Default::default()
+  };
+  let mut dib = unsafe {
+    slice::from_raw_parts(
+      &header as *const _ as *const u8,
+      mem::size_of::<BITMAPINFOHEADER>(),
+    )
+  }
+  .to_vec();
+  dib.extend_from_slice(&[3, 2, 1, 0, 6, 5, 4, 0]);
+  let image = unsafe { dib_to_rgba(&dib) }.unwrap();
+  assert_eq!(image.rgba(), &[1, 2, 3, 255, 4, 5, 6, 255]);
+
+  dib.truncate(dib.len() - 1);
+  assert!(unsafe { dib_to_rgba(&dib) }.is_none());
+  assert!(unsafe { dib_to_rgba(&dib[..8]) }.is_none());
+}
+
+#[test]
+fn test_cf_html() {
+  let fragment = "<b>Bold</b> and <i>italic</i>";
+  let data = to_cf_html(fragment);
+  assert_eq!(data.last(), Some(&0));
+  assert_eq!(from_cf_html(&data).as_deref(), Some(fragment));
+
+  let html = String::from_utf8(data[..data.len() - 1].to_vec()).unwrap();
+  let offset = |key: &str| -> usize {
+    let start = html.find(key).unwrap() + key.len();
+    html[start..start + 10].parse().unwrap()
+  };
+  assert_eq!(offset("StartHTML:"), html.find("<html>").unwrap());
+  assert_eq!(offset("EndHTML:"), html.len());
+  assert_eq!(
+    &html[offset("StartFragment:")..offset("EndFragment:")],
+    fragment
+  );
+
+  // Offsets that aren't zero padded, and a description ending with LF only.
+  let data =
+    b"Version:1.0\nStartHTML:71\nEndHTML:87\nStartFragment:71\nEndFragment:83\n<p>Hello, world</p>";
+  assert_eq!(from_cf_html(data).as_deref(), Some("Hello, world"));
+  assert_eq!(from_cf_html(b"Version:0.9\r\nStartFragment:10\r\n"), None);
+  assert_eq!(
+    from_cf_html(b"Version:0.9\r\nStartFragment:0\r\nEndFragment:1000\r\n"),
+    None
+  );
+}
@@ -4,17 +4,18 @@
 
 /// This is a simple implementation of support for Windows Dark Mode,
 /// which is inspired by the solution in https://github.com/ysc3839/win32-darkmode
+use std::mem;
+
 use windows::{
   core::{s, PCSTR, PCWSTR, PSTR},
   Win32::{
     Foundation::{BOOL, HWND},
+    Graphics::Dwm::{DwmSetWindowAttribute, DWMWA_USE_IMMERSIVE_DARK_MODE},
     System::LibraryLoader::*,
     UI::{Accessibility::*, Controls::*, WindowsAndMessaging::*},
   },
 };
 
-use std::ffi::c_void;
-
 use crate::{platform_impl::platform::util, window::Theme};
 
 lazy_static! {
@@ -108,44 +109,19 @@ pub fn try_theme(hwnd: HWND, preferred_theme: Option<Theme>) -> Theme {
 fn set_dark_mode_for_window(hwnd: HWND, is_dark_mode: bool) -> bool {
   // Uses Windows undocumented API SetWindowCompositionAttribute,
   // as seen in win32-darkmode example linked at top of file.
-
-  type SetWindowCompositionAttribute =
-    unsafe extern "system" fn(HWND, *mut WINDOWCOMPOSITIONATTRIBDATA) -> BOOL;
-
-  #[allow(non_snake_case)]
-  type WINDOWCOMPOSITIONATTRIB = u32;
-  const WCA_USEDARKMODECOLORS: WINDOWCOMPOSITIONATTRIB = 26;
-
-  #[allow(non_snake_case)]
-  #[repr(C)]
-  struct WINDOWCOMPOSITIONATTRIBDATA {
-    Attrib: WINDOWCOMPOSITIONATTRIB,
-    pvData: *mut c_void,
-    cbData: usize,
-  }
-
-  lazy_static! {
-    static ref SET_WINDOW_COMPOSITION_ATTRIBUTE: Option<SetWindowCompositionAttribute> =
-      get_function!("user32.dll", SetWindowCompositionAttribute);
-  }
-
-  if let Some(set_window_composition_attribute) = *SET_WINDOW_COMPOSITION_ATTRIBUTE {
-    unsafe {
-      // SetWindowCompositionAttribute needs a bigbool (i32), not bool.
-      let mut is_dark_mode_bigbool: BOOL = is_dark_mode.into();
-
-      let mut data = WINDOWCOMPOSITIONATTRIBDATA {
-        Attrib: WCA_USEDARKMODECOLORS,
-        pvData: &mut is_dark_mode_bigbool as *mut _ as _,
-        cbData: std::mem::size_of_val(&is_dark_mode_bigbool) as _,
-      };
-
-      let status = set_window_composition_attribute(hwnd, &mut data as *mut _);
-
-      status.as_bool()
-    }
-  } else {
-    false
+  const WCA_USEDARKMODECOLORS: u32 = 26;
+
+  // SetWindowCompositionAttribute needs a bigbool (i32), not bool.
+  let mut is_dark_mode_bigbool: BOOL = is_dark_mode.into();
+  unsafe {
+    // Applies to the title bar right away, but fails before Windows 10 20H1.
+    let _ = DwmSetWindowAttribute(
+      hwnd,
+      DWMWA_USE_IMMERSIVE_DARK_MODE,
+      &is_dark_mode_bigbool as *const _ as _,
+      mem::size_of::<BOOL>() as u32,
+    );
+    util::set_window_composition_attribute(hwnd, WCA_USEDARKMODECOLORS, &mut is_dark_mode_bigbool)
   }
 }
 
@@ -0,0 +1,275 @@
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Touchpad gestures through Direct Manipulation, which reports the precise movement of the
+//! fingers on precision touchpads, where the mouse wheel messages only carry line deltas and
+//! pinches are sent as `Ctrl` + mouse wheel.
+
+use std::cell::Cell;
+
+use windows::{
+  core::Result,
+  Win32::{
+    Foundation::{HWND, RECT, WPARAM},
+    Graphics::DirectManipulation::*,
+    System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+    UI::{
+      Input::Pointer::GetPointerType,
+      WindowsAndMessaging::{KillTimer, SetTimer, POINTER_INPUT_TYPE, PT_TOUCHPAD},
+    },
+  },
+};
+
+use windows_implement::implement;
+
+use crate::{
+  dpi::PhysicalPosition,
+  event::{Event, MouseScrollDelta, TouchPhase, WindowEvent},
+  platform_impl::platform::{
+    keyboard_layout::LAYOUT_CACHE, util, window::com_initialized, WindowId, DEVICE_ID,
+  },
+  window::WindowId as SuperWindowId,
+};
+
+/// The timer of a window that updates its viewport while a gesture or its inertia runs, since the
+/// viewport is updated manually.
+pub(crate) const DIRECT_MANIPULATION_TIMER_ID: usize = 3;
+
+/// The size of the viewport, which doesn't need to match the window, as its content is moved back
+/// to the origin after each gesture and only the changes of its transform are reported.
+const VIEWPORT_SIZE: i32 = 1000;
+
+/// The direct manipulation viewport of a window, fed with the touchpad contacts it is hit-tested
+/// with in `DM_POINTERHITTEST`.
+pub(crate) struct DirectManipulation {
+  window: HWND,
+  manager: IDirectManipulationManager,
+  update_manager: IDirectManipulationUpdateManager,
+  viewport: IDirectManipulationViewport,
+  handler_cookie: u32,
+}
+
+impl DirectManipulation {
+  pub unsafe fn new(
+    window: HWND,
+    send_event: Box<dyn Fn(Event<'static, ()>)>,
+  ) -> Result<DirectManipulation> {
+    com_initialized();
+    let manager: IDirectManipulationManager =
+      CoCreateInstance(&DirectManipulationManager, None, CLSCTX_INPROC_SERVER)?;
+    let update_manager: IDirectManipulationUpdateManager = manager.GetUpdateManager()?;
+    let viewport: IDirectManipulationViewport = manager.CreateViewport(None, window)?;
+
+    let configuration = DIRECTMANIPULATION_CONFIGURATION_INTERACTION.0
+      | DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_X.0
+      | DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_Y.0
+      | DIRECTMANIPULATION_CONFIGURATION_TRANSLATION_INERTIA.0
+      | DIRECTMANIPULATION_CONFIGURATION_SCALING.0;
+    viewport.ActivateConfiguration(DIRECTMANIPULATION_CONFIGURATION(configuration))?;
+    viewport.SetViewportOptions(DIRECTMANIPULATION_VIEWPORT_OPTIONS_MANUALUPDATE)?;
+
+    let handler: IDirectManipulationViewportEventHandler = ViewportEventHandler {
+      window,
+      send_event,
+      status: Cell::new(DIRECTMANIPULATION_BUILDING),
+      scrolling: Cell::new(false),
+      magnifying: Cell::new(false),
+      translation: Cell::new((0.0, 0.0)),
+      scale: Cell::new(1.0),
+    }
+    .into();
+    let handler_cookie = viewport.AddEventHandler(window, &handler)?;
+
+    let rect = RECT {
+      left: 0,
+      top: 0,
+      right: VIEWPORT_SIZE,
+      bottom: VIEWPORT_SIZE,
+    };
+    viewport.SetViewportRect(&rect)?;
+    manager.Activate(window)?;
+    viewport.Enable()?;
+
+    Ok(DirectManipulation {
+      window,
+      manager,
+      update_manager,
+      viewport,
+      handler_cookie,
+    })
+  }
+
+  /// Hands the pointer of a `DM_POINTERHITTEST` message to the viewport, if it is a touchpad's.
+  pub unsafe fn set_contact(&self, wparam: WPARAM) {
+    let pointer_id = u32::from(util::LOWORD(wparam.0 as u32));
+    let mut pointer_type = POINTER_INPUT_TYPE::default();
+    if GetPointerType(pointer_id, &mut pointer_type).as_bool() && pointer_type == PT_TOUCHPAD {
+      let _ = self.viewport.SetContact(pointer_id);
+    }
+  }
+
+  /// Updates the viewport, on each tick of `DIRECT_MANIPULATION_TIMER_ID`.
+  pub unsafe fn update(&self) {
+    let _ = self.update_manager.Update(None);
+  }
+}
+
+impl Drop for DirectManipulation {
+  fn drop(&mut self) {
+    unsafe {
+      let _ = self.viewport.RemoveEventHandler(self.handler_cookie);
+      let _ = self.viewport.Abandon();
+      let _ = self.manager.Deactivate(self.window);
+    }
+  }
+}
+
+#[implement(IDirectManipulationViewportEventHandler)]
+struct ViewportEventHandler {
+  window: HWND,
+  send_event: Box<dyn Fn(Event<'static, ()>)>,
+  status: Cell<DIRECTMANIPULATION_STATUS>,
+  /// Whether the `Started` phase of a gesture, or the first event of its inertia, was sent.
+  scrolling: Cell<bool>,
+  /// Whether the `Started` phase of a pinch was sent.
+  magnifying: Cell<bool>,
+  /// The translation of the content at the last update.
+  translation: Cell<(f32, f32)>,
+  /// The scale of the content at the last update.
+  scale: Cell<f32>,
+}
+
+impl ViewportEventHandler {
+  fn send_scroll(&self, (x, y): (f32, f32), phase: TouchPhase, inertial: bool) {
+    (self.send_event)(Event::WindowEvent {
+      window_id: SuperWindowId(WindowId(self.window.0)),
+      event: WindowEvent::MouseWheel {
+        device_id: DEVICE_ID,
+        delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(x.into(), y.into())),
+        phase,
+        inertial,
+        timestamp: util::message_timestamp(),
+        modifiers: LAYOUT_CACHE.lock().get_agnostic_mods(),
+      },
+    });
+  }
+
+  /// Sends the `Ended` phase of the gesture or inertia that is scrolling, if any.
+  fn end_scroll(&self, inertial: bool) {
+    if self.scrolling.replace(false) {
+      self.send_scroll((0.0, 0.0), TouchPhase::Ended, inertial);
+    }
+  }
+
+  fn send_magnify(&self, delta: f32, phase: TouchPhase) {
+    (self.send_event)(Event::WindowEvent {
+      window_id: SuperWindowId(WindowId(self.window.0)),
+      event: WindowEvent::TouchpadMagnify {
+        device_id: DEVICE_ID,
+        delta: delta.into(),
+        phase,
+      },
+    });
+  }
+
+  /// Sends the `Ended` phase of the pinch, if any.
+  fn end_magnify(&self) {
+    if self.magnifying.replace(false) {
+      self.send_magnify(0.0, TouchPhase::Ended);
+    }
+  }
+}
+
+#[allow(non_snake_case)]
+impl IDirectManipulationViewportEventHandler_Impl for ViewportEventHandler {
+  fn OnViewportStatusChanged(
+    &self,
+    viewport: &Option<IDirectManipulationViewport>,
+    current: DIRECTMANIPULATION_STATUS,
+    previous: DIRECTMANIPULATION_STATUS,
+  ) -> Result<()> {
+    self.status.set(current);
+    if current == previous {
+      return Ok(());
+    }
+
+    match current {
+      // A gesture starts, possibly interrupting the inertia of the previous one.
+      DIRECTMANIPULATION_RUNNING => unsafe {
+        self.end_scroll(true);
+        SetTimer(self.window, DIRECT_MANIPULATION_TIMER_ID, 16, None);
+      },
+      // The fingers were lifted, the gesture ends and its inertia follows.
+      DIRECTMANIPULATION_INERTIA => {
+        self.end_scroll(false);
+        self.end_magnify();
+      }
+      DIRECTMANIPULATION_READY => unsafe {
+        KillTimer(self.window, DIRECT_MANIPULATION_TIMER_ID);
+        self.end_scroll(previous == DIRECTMANIPULATION_INERTIA);
+        self.end_magnify();
+        // Moves the content back, for the next gesture to start from the origin and scale.
+        self.translation.set((0.0, 0.0));
+        self.scale.set(1.0);
+        if let Some(viewport) = viewport {
+          let size = VIEWPORT_SIZE as f32;
+          viewport.ZoomToRect(0.0, 0.0, size, size, false)?;
+        }
+      },
+      _ => (),
+    }
+    Ok(())
+  }
+
+  fn OnViewportUpdated(&self, _viewport: &Option<IDirectManipulationViewport>) -> Result<()> {
+    Ok(())
+  }
+
+  fn OnContentUpdated(
+    &self,
+    _viewport: &Option<IDirectManipulationViewport>,
+    content: &Option<IDirectManipulationContent>,
+  ) -> Result<()> {
+    let content = match content {
+      Some(content) => content,
+      None => return Ok(()),
+    };
+    // A 3x2 matrix, with the scale on its diagonal and the translation in its last row.
+    let mut transform = [0.0f32; 6];
+    unsafe { content.GetContentTransform(&mut transform)? };
+    let scale = transform[0];
+    let translation = (transform[4], transform[5]);
+    let last_scale = self.scale.replace(scale);
+    let (last_x, last_y) = self.translation.replace(translation);
+    let delta = (translation.0 - last_x, translation.1 - last_y);
+
+    let status = self.status.get();
+    if status != DIRECTMANIPULATION_RUNNING && status != DIRECTMANIPULATION_INERTIA {
+      return Ok(());
+    }
+
+    // A pinch also moves the content, to keep the point between the fingers in place, which
+    // isn't a scroll.
+    if scale != last_scale {
+      if !self.magnifying.replace(true) {
+        self.send_magnify(0.0, TouchPhase::Started);
+      }
+      self.send_magnify(scale - last_scale, TouchPhase::Moved);
+      return Ok(());
+    }
+    if delta == (0.0, 0.0) {
+      return Ok(());
+    }
+
+    // The content follows the fingers, so moving them down scrolls up, with a positive delta.
+    let inertial = status == DIRECTMANIPULATION_INERTIA;
+    let phase = if !inertial && !self.scrolling.get() {
+      TouchPhase::Started
+    } else {
+      TouchPhase::Moved
+    };
+    self.scrolling.set(true);
+    self.send_scroll(delta, phase, inertial);
+    Ok(())
+  }
+}
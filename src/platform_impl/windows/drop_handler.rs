@@ -2,23 +2,49 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{cell::UnsafeCell, ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf, ptr};
-
-use windows::Win32::{
-  Foundation::{self as win32f, HWND, POINTL},
-  System::{
-    Com::{IDataObject, DVASPECT_CONTENT, FORMATETC, TYMED_HGLOBAL},
-    Ole::{IDropTarget, IDropTarget_Impl, DROPEFFECT_COPY, DROPEFFECT_NONE},
-    SystemServices::CF_HDROP,
+use std::{
+  cell::UnsafeCell, ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf, ptr, slice,
+};
+
+use windows::{
+  core::{Interface, PCWSTR},
+  Win32::{
+    Foundation::{
+      self as win32f, BOOL, DRAGDROP_S_CANCEL, DRAGDROP_S_DROP, DRAGDROP_S_USEDEFAULTCURSORS,
+      E_POINTER, HWND, POINT, POINTL,
+    },
+    Graphics::Gdi::ScreenToClient,
+    System::{
+      Com::{IDataObject, DVASPECT_CONTENT, FORMATETC, STGMEDIUM, STGMEDIUM_0, TYMED_HGLOBAL},
+      DataExchange::RegisterClipboardFormatW,
+      Memory::{GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
+      Ole::{
+        DoDragDrop, IDropSource, IDropSource_Impl, IDropTarget, IDropTarget_Impl, ReleaseStgMedium,
+        DROPEFFECT, DROPEFFECT_COPY, DROPEFFECT_NONE,
+      },
+      SystemServices::{CF_HDROP, CF_UNICODETEXT},
+    },
+    UI::{
+      Shell::{
+        BHID_DataObject, DragFinish, DragQueryFileW, ILCreateFromPathW, ILFree, SHCreateDataObject,
+        SHCreateShellItemArrayFromIDLists, HDROP,
+      },
+      WindowsAndMessaging::MK_LBUTTON,
+    },
   },
-  UI::Shell::{DragFinish, DragQueryFileW, HDROP},
 };
 
 use windows_implement::implement;
 
-use crate::platform_impl::platform::WindowId;
+use crate::platform_impl::platform::{
+  clipboard::from_cf_html, util, window::set_drop_highlight, WindowId,
+};
 
-use crate::{event::Event, window::WindowId as SuperWindowId};
+use crate::{
+  dpi::PhysicalPosition,
+  event::{DragDropEvent, DropData, Event, WindowEvent},
+  window::{DragData, WindowId as SuperWindowId},
+};
 
 #[implement(IDropTarget)]
 pub struct FileDropHandler {
@@ -26,6 +52,7 @@ pub struct FileDropHandler {
   send_event: Box<dyn Fn(Event<'static, ()>)>,
   cursor_effect: UnsafeCell<u32>,
   hovered_is_valid: UnsafeCell<bool>, /* If the currently hovered item is not valid there must not be any `HoveredFileCancelled` emitted */
+  drop_is_valid: UnsafeCell<bool>, /* Same for `DragDropEvent::Left`, for items in none of the supported formats */
 }
 
 impl FileDropHandler {
@@ -35,12 +62,76 @@ impl FileDropHandler {
       send_event,
       cursor_effect: DROPEFFECT_NONE.0.into(),
       hovered_is_valid: false.into(),
+      drop_is_valid: false.into(),
+    }
+  }
+
+  fn send_drag_drop(&self, event: DragDropEvent) {
+    (self.send_event)(Event::WindowEvent {
+      window_id: SuperWindowId(WindowId(self.window.0)),
+      event: WindowEvent::DragDrop(event),
+    });
+  }
+
+  /// Converts the screen position of a drag to the client area of the window.
+  unsafe fn client_position(&self, pt: &POINTL) -> PhysicalPosition<f64> {
+    let mut point = POINT { x: pt.x, y: pt.y };
+    ScreenToClient(self.window, &mut point);
+    PhysicalPosition::new(point.x as f64, point.y as f64)
+  }
+
+  /// Reads the text, URL and HTML of a dragged item, along with its already read `paths`.
+  unsafe fn drop_data(data_obj: &Option<IDataObject>, paths: Vec<PathBuf>) -> DropData {
+    let mut data = DropData {
+      paths,
+      ..Default::default()
+    };
+    let data_obj = match data_obj {
+      Some(data_obj) => data_obj,
+      None => return data,
+    };
+
+    data.text = Self::global_data(data_obj, CF_UNICODETEXT.0).map(|text| decode_wide(&text));
+    let url_format = register_format("UniformResourceLocatorW");
+    if let Some(url) = Self::global_data(data_obj, url_format).map(|url| decode_wide(&url)) {
+      if !url.is_empty() && !url.starts_with("file:") {
+        data.uris.push(url);
+      }
+    }
+    let html_format = register_format("HTML Format");
+    data.html = Self::global_data(data_obj, html_format).and_then(|html| from_cf_html(&html));
+    data
+  }
+
+  /// Returns the content of the item in `format`, if it's offered in global memory.
+  unsafe fn global_data(data_obj: &IDataObject, format: u32) -> Option<Vec<u8>> {
+    if format == 0 {
+      return None;
     }
+    let format = FORMATETC {
+      cfFormat: format as u16,
+      ptd: ptr::null_mut(),
+      dwAspect: DVASPECT_CONTENT.0 as u32,
+      lindex: -1,
+      tymed: TYMED_HGLOBAL.0 as u32,
+    };
+    let mut medium = data_obj.GetData(&format).ok()?;
+    let hglobal = medium.Anonymous.hGlobal;
+    let locked = GlobalLock(hglobal) as *const u8;
+    let bytes = if locked.is_null() {
+      None
+    } else {
+      let bytes = slice::from_raw_parts(locked, GlobalSize(hglobal)).to_vec();
+      GlobalUnlock(hglobal);
+      Some(bytes)
+    };
+    ReleaseStgMedium(&mut medium);
+    bytes
   }
 
-  unsafe fn iterate_filenames<F>(data_obj: &Option<IDataObject>, callback: F) -> Option<HDROP>
+  unsafe fn iterate_filenames<F>(data_obj: &Option<IDataObject>, mut callback: F) -> Option<HDROP>
   where
-    F: Fn(PathBuf),
+    F: FnMut(PathBuf),
   {
     let drop_format = FORMATETC {
       cfFormat: CF_HDROP.0 as u16,
@@ -104,24 +195,35 @@ impl IDropTarget_Impl for FileDropHandler {
     &self,
     pDataObj: &Option<IDataObject>,
     _grfKeyState: u32,
-    _pt: &POINTL,
+    pt: &POINTL,
     pdwEffect: *mut u32,
   ) -> windows::core::Result<()> {
     use crate::event::WindowEvent::HoveredFile;
     unsafe {
+      let mut paths = Vec::new();
       let hdrop = Self::iterate_filenames(pDataObj, |filename| {
+        paths.push(filename.clone());
         (self.send_event)(Event::WindowEvent {
           window_id: SuperWindowId(WindowId(self.window.0)),
           event: HoveredFile(filename),
         });
       });
       let hovered_is_valid = hdrop.is_some();
-      let cursor_effect = if hovered_is_valid {
+      let data = Self::drop_data(pDataObj, paths);
+      let drop_is_valid = !data.is_empty();
+      if drop_is_valid {
+        self.send_drag_drop(DragDropEvent::Entered {
+          data,
+          position: self.client_position(pt),
+        });
+      }
+      let cursor_effect = if drop_is_valid {
         DROPEFFECT_COPY
       } else {
         DROPEFFECT_NONE
       };
       *self.hovered_is_valid.get() = hovered_is_valid;
+      *self.drop_is_valid.get() = drop_is_valid;
       *self.cursor_effect.get() = cursor_effect.0;
       *pdwEffect = cursor_effect.0;
     }
@@ -131,10 +233,15 @@ impl IDropTarget_Impl for FileDropHandler {
   fn DragOver(
     &self,
     _grfKeyState: u32,
-    _pt: &POINTL,
+    pt: &POINTL,
     pdwEffect: *mut u32,
   ) -> windows::core::Result<()> {
     unsafe {
+      if *self.drop_is_valid.get() {
+        self.send_drag_drop(DragDropEvent::Moved {
+          position: self.client_position(pt),
+        });
+      }
       *pdwEffect = *self.cursor_effect.get();
     }
     Ok(())
@@ -142,12 +249,16 @@ impl IDropTarget_Impl for FileDropHandler {
 
   fn DragLeave(&self) -> windows::core::Result<()> {
     use crate::event::WindowEvent::HoveredFileCancelled;
+    unsafe { set_drop_highlight(self.window, false) };
     if unsafe { *self.hovered_is_valid.get() } {
       (self.send_event)(Event::WindowEvent {
         window_id: SuperWindowId(WindowId(self.window.0)),
         event: HoveredFileCancelled,
       });
     }
+    if unsafe { *self.drop_is_valid.get() } {
+      self.send_drag_drop(DragDropEvent::Left);
+    }
     Ok(())
   }
 
@@ -155,12 +266,15 @@ impl IDropTarget_Impl for FileDropHandler {
     &self,
     pDataObj: &Option<IDataObject>,
     _grfKeyState: u32,
-    _pt: &POINTL,
+    pt: &POINTL,
     _pdwEffect: *mut u32,
   ) -> windows::core::Result<()> {
     use crate::event::WindowEvent::DroppedFile;
     unsafe {
+      set_drop_highlight(self.window, false);
+      let mut paths = Vec::new();
       let hdrop = Self::iterate_filenames(pDataObj, |filename| {
+        paths.push(filename.clone());
         (self.send_event)(Event::WindowEvent {
           window_id: SuperWindowId(WindowId(self.window.0)),
           event: DroppedFile(filename),
@@ -169,7 +283,112 @@ impl IDropTarget_Impl for FileDropHandler {
       if let Some(hdrop) = hdrop {
         DragFinish(hdrop);
       }
+      let data = Self::drop_data(pDataObj, paths);
+      if !data.is_empty() {
+        self.send_drag_drop(DragDropEvent::Dropped {
+          data,
+          position: self.client_position(pt),
+        });
+      }
     }
     Ok(())
   }
 }
+
+unsafe fn register_format(name: &str) -> u32 {
+  let name = util::encode_wide(name);
+  RegisterClipboardFormatW(PCWSTR::from_raw(name.as_ptr()))
+}
+
+/// Decodes a null-terminated UTF-16 string.
+fn decode_wide(bytes: &[u8]) -> String {
+  let wide = bytes
+    .chunks_exact(2)
+    .map(|c| u16::from_ne_bytes([c[0], c[1]]))
+    .take_while(|&c| c != 0)
+    .collect::<Vec<_>>();
+  String::from_utf16_lossy(&wide)
+}
+
+/// Ends the drag when the left mouse button is released, and cancels it on escape.
+#[implement(IDropSource)]
+struct DragSource;
+
+#[allow(non_snake_case)]
+impl IDropSource_Impl for DragSource {
+  fn QueryContinueDrag(&self, fEscapePressed: BOOL, grfKeyState: u32) -> windows::core::Result<()> {
+    if fEscapePressed.as_bool() {
+      Err(DRAGDROP_S_CANCEL.into())
+    } else if grfKeyState & MK_LBUTTON == 0 {
+      Err(DRAGDROP_S_DROP.into())
+    } else {
+      Ok(())
+    }
+  }
+
+  fn GiveFeedback(&self, _dwEffect: u32) -> windows::core::Result<()> {
+    Err(DRAGDROP_S_USEDEFAULTCURSORS.into())
+  }
+}
+
+/// Drags `data` out of the window until it's dropped or the drag is cancelled.
+pub unsafe fn start_drag(data: &DragData) -> windows::core::Result<()> {
+  let data_obj = match data {
+    DragData::Files(paths) => {
+      let pidls = paths
+        .iter()
+        .map(|path| ILCreateFromPathW(PCWSTR::from_raw(util::encode_wide(path).as_ptr())))
+        .filter(|pidl| !pidl.is_null())
+        .map(|pidl| pidl as *const _)
+        .collect::<Vec<_>>();
+      let items = SHCreateShellItemArrayFromIDLists(&pidls);
+      for pidl in pidls {
+        ILFree(pidl);
+      }
+      items?.BindToHandler::<_, IDataObject>(None, &BHID_DataObject)?
+    }
+    DragData::Text(text) => {
+      let mut data_obj: Option<IDataObject> = None;
+      SHCreateDataObject(
+        ptr::null(),
+        &[],
+        None,
+        &IDataObject::IID,
+        &mut data_obj as *mut _ as *mut _,
+      )?;
+      let data_obj = data_obj.ok_or_else(|| windows::core::Error::from(E_POINTER))?;
+
+      let text = util::encode_wide(text);
+      let size = text.len() * std::mem::size_of::<u16>();
+      let hglobal = GlobalAlloc(GMEM_MOVEABLE, size);
+      if hglobal == 0 {
+        return Err(windows::core::Error::from_win32());
+      }
+      ptr::copy_nonoverlapping(text.as_ptr(), GlobalLock(hglobal) as *mut u16, text.len());
+      GlobalUnlock(hglobal);
+
+      let format = FORMATETC {
+        cfFormat: CF_UNICODETEXT.0 as u16,
+        ptd: ptr::null_mut(),
+        dwAspect: DVASPECT_CONTENT.0 as u32,
+        lindex: -1,
+        tymed: TYMED_HGLOBAL.0 as u32,
+      };
+      let medium = STGMEDIUM {
+        tymed: TYMED_HGLOBAL.0 as u32,
+        Anonymous: STGMEDIUM_0 { hGlobal: hglobal },
+        pUnkForRelease: None,
+      };
+      // The data object takes ownership of the memory on success.
+      if let Err(error) = data_obj.SetData(&format, &medium, true) {
+        GlobalFree(hglobal);
+        return Err(error);
+      }
+      data_obj
+    }
+  };
+
+  let drop_source: IDropSource = DragSource.into();
+  let mut effect = DROPEFFECT::default();
+  DoDragDrop(&data_obj, &drop_source, DROPEFFECT_COPY, &mut effect).ok()
+}
@@ -10,8 +10,9 @@ use crossbeam_channel::{self as channel, Receiver, Sender};
 use parking_lot::Mutex;
 use raw_window_handle::{RawDisplayHandle, WindowsDisplayHandle};
 use std::{
-  cell::Cell,
-  collections::VecDeque,
+  cell::{Cell, RefCell},
+  collections::{HashSet, VecDeque},
+  io,
   marker::PhantomData,
   mem, panic, ptr,
   rc::Rc,
@@ -24,19 +25,30 @@ use windows::{
   Win32::{
     Devices::HumanInterfaceDevice::*,
     Foundation::{
-      BOOL, HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, WAIT_TIMEOUT, WPARAM,
+      CloseHandle, BOOL, HANDLE, HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, WAIT_OBJECT_0,
+      WPARAM,
     },
-    Graphics::Gdi::*,
+    Graphics::{Dwm::DwmIsCompositionEnabled, Gdi::*},
     System::{
+      DataExchange::{AddClipboardFormatListener, RemoveClipboardFormatListener, COPYDATASTRUCT},
       LibraryLoader::GetModuleHandleW,
       Ole::{IDropTarget, RevokeDragDrop},
-      Threading::GetCurrentThreadId,
+      RemoteDesktop::{
+        WTSRegisterSessionNotification, WTSUnRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION,
+      },
+      SystemServices::TIMER_MODIFY_STATE,
+      Threading::{
+        AttachThreadInput, CreateWaitableTimerExW, GetCurrentProcessId, GetCurrentThreadId,
+        SetWaitableTimer, CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+      },
       WindowsProgramming::INFINITE,
     },
     UI::{
+      Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK},
       Controls::{self as win32c, HOVER_DEFAULT},
       Input::{KeyboardAndMouse::*, Pointer::*, Touch::*, *},
-      Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+      Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass, THBN_CLICKED},
+      TextServices::HKL,
       WindowsAndMessaging::{self as win32wm, *},
     },
   },
@@ -45,27 +57,36 @@ use windows::{
 use crate::{
   accelerator::AcceleratorId,
   dpi::{PhysicalPosition, PhysicalSize},
-  event::{DeviceEvent, Event, Force, RawKeyEvent, Touch, TouchPhase, WindowEvent},
-  event_loop::{ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopWindowTarget as RootELW},
+  error::ExternalError,
+  event::{DeviceEvent, Event, Force, PenInput, RawKeyEvent, Touch, TouchPhase, WindowEvent},
+  event_loop::{
+    ControlFlow, DeviceEventFilter, EventLoopClosed, EventLoopTask,
+    EventLoopWindowTarget as RootELW,
+  },
   keyboard::{KeyCode, ModifiersState},
-  monitor::MonitorHandle as RootMonitorHandle,
+  monitor::{MonitorHandle as RootMonitorHandle, MonitorTracker},
   platform_impl::platform::{
     accelerator,
     dark_mode::try_theme,
+    direct_manipulation::{DirectManipulation, DIRECT_MANIPULATION_TIMER_ID},
     dpi::{become_dpi_aware, dpi_to_scale_factor, enable_non_client_dpi_scaling},
     keyboard::is_msg_keyboard_related,
-    keyboard_layout::LAYOUT_CACHE,
+    keyboard_grab,
+    keyboard_layout::{self, LAYOUT_CACHE},
     minimal_ime::is_msg_ime_related,
     monitor::{self, MonitorHandle},
-    raw_input, util,
-    window::set_skip_taskbar,
+    raw_input, single_instance, util,
+    window::{set_skip_taskbar, set_thumbbar_buttons},
     window_state::{CursorFlags, WindowFlags, WindowState},
-    wrap_device_id, WindowId, DEVICE_ID,
+    wrap_device_id, OsError, WindowId, DEVICE_ID,
   },
-  window::{Fullscreen, WindowId as RootWindowId},
+  window::{Fullscreen, WindowId as RootWindowId, RGBA},
 };
 use runner::{EventLoopRunner, EventLoopRunnerShared};
 
+#[cfg(feature = "accesskit")]
+use crate::platform_impl::platform::accessibility::{self, Accessibility};
+
 type GetPointerFrameInfoHistory = unsafe extern "system" fn(
   pointerId: u32,
   entriesCount: *mut u32,
@@ -88,6 +109,8 @@ type GetPointerPenInfo =
   unsafe extern "system" fn(pointId: u32, penInfo: *mut POINTER_PEN_INFO) -> BOOL;
 
 lazy_static! {
+  /// The accent color last reported, since every top-level window is told about its changes.
+  static ref ACCENT_COLOR: Mutex<Option<RGBA>> = Mutex::new(util::accent_color());
   static ref GET_POINTER_FRAME_INFO_HISTORY: Option<GetPointerFrameInfoHistory> =
     get_function!("user32.dll", GetPointerFrameInfoHistory);
   static ref SKIP_POINTER_FRAME_MESSAGES: Option<SkipPointerFrameMessages> =
@@ -104,8 +127,34 @@ pub(crate) struct SubclassInput<T: 'static> {
   pub window_state: Arc<Mutex<WindowState>>,
   pub event_loop_runner: EventLoopRunnerShared<T>,
   pub _file_drop_handler: Option<IDropTarget>,
+  pub direct_manipulation: Option<DirectManipulation>,
   pub subclass_removed: Cell<bool>,
   pub recurse_depth: Cell<u32>,
+  pub subclass_hook: SubclassHook,
+  #[cfg(feature = "accesskit")]
+  pub accessibility: Accessibility,
+}
+
+pub(crate) type SubclassHookFn =
+  Box<dyn FnMut(HWND, u32, WPARAM, LPARAM) -> Option<LRESULT> + Send>;
+
+/// The hook set with `WindowExtWindows::set_subclass_hook`, called before tao handles a message.
+pub(crate) type SubclassHook = Arc<Mutex<SubclassHookSlot>>;
+
+#[derive(Default)]
+pub(crate) struct SubclassHookSlot {
+  hook: Option<SubclassHookFn>,
+  /// Whether the hook was set while the previous one was running.
+  replaced: bool,
+  /// How many calls of the hooks are running, more than one for re-entrant messages.
+  depth: usize,
+}
+
+impl SubclassHookSlot {
+  pub fn set(&mut self, hook: Option<SubclassHookFn>) {
+    self.hook = hook;
+    self.replaced = true;
+  }
 }
 
 impl<T> SubclassInput<T> {
@@ -117,6 +166,8 @@ impl<T> SubclassInput<T> {
 struct ThreadMsgTargetSubclassInput<T: 'static> {
   event_loop_runner: EventLoopRunnerShared<T>,
   user_event_receiver: Receiver<T>,
+  /// The monitors last reported, to tell what changed on `WM_DISPLAYCHANGE`.
+  monitors: RefCell<MonitorTracker>,
 }
 
 impl<T> ThreadMsgTargetSubclassInput<T> {
@@ -135,6 +186,9 @@ pub(crate) enum ProcResult {
 pub struct EventLoop<T: 'static> {
   thread_msg_sender: Sender<T>,
   window_target: RootELW<T>,
+  msg_hook: Option<Box<dyn FnMut(*const MSG) -> bool>>,
+  /// Reports the cloaking of the windows, removed when the event loop is dropped.
+  cloak_event_hook: HWINEVENTHOOK,
 }
 
 #[derive(Clone)]
@@ -151,42 +205,67 @@ macro_rules! main_thread_check {
       panic!(concat!(
         "Initializing the event loop outside of the main thread is a significant \
                  cross-platform compatibility hazard. If you really, absolutely need to create an \
-                 EventLoop on a different thread, please use the `EventLoopExtWindows::",
+                 EventLoop on a different thread, please use the `EventLoopBuilderExtWindows::",
         $fn_name,
-        "` function."
+        "` method."
       ));
     }
   }};
 }
 
-impl<T: 'static> EventLoop<T> {
-  pub fn new() -> EventLoop<T> {
-    main_thread_check!("new_any_thread");
-
-    Self::new_any_thread()
-  }
+pub struct PlatformSpecificEventLoopAttributes {
+  pub(crate) any_thread: bool,
+  pub(crate) dpi_aware: bool,
+  pub(crate) msg_hook: Option<Box<dyn FnMut(*const MSG) -> bool + 'static>>,
+  pub(crate) opened_urls_from_args: bool,
+}
 
-  pub fn new_any_thread() -> EventLoop<T> {
-    become_dpi_aware();
-    Self::new_dpi_unaware_any_thread()
+impl Default for PlatformSpecificEventLoopAttributes {
+  fn default() -> Self {
+    Self {
+      any_thread: false,
+      dpi_aware: true,
+      msg_hook: None,
+      opened_urls_from_args: false,
+    }
   }
+}
 
-  pub fn new_dpi_unaware() -> EventLoop<T> {
-    main_thread_check!("new_dpi_unaware_any_thread");
+impl<T: 'static> EventLoop<T> {
+  pub(crate) fn new(attributes: &mut PlatformSpecificEventLoopAttributes) -> EventLoop<T> {
+    if !attributes.any_thread {
+      main_thread_check!("with_any_thread");
+    }
 
-    Self::new_dpi_unaware_any_thread()
-  }
+    if attributes.dpi_aware {
+      become_dpi_aware();
+    }
 
-  pub fn new_dpi_unaware_any_thread() -> EventLoop<T> {
     let thread_id = unsafe { GetCurrentThreadId() };
 
     let thread_msg_target = create_event_target_window();
+    lazy_static::initialize(&ACCENT_COLOR);
+    let cloak_event_hook = unsafe {
+      SetWinEventHook(
+        EVENT_OBJECT_CLOAKED,
+        EVENT_OBJECT_UNCLOAKED,
+        HINSTANCE::default(),
+        Some(cloak_event_hook),
+        GetCurrentProcessId(),
+        0,
+        WINEVENT_OUTOFCONTEXT,
+      )
+    };
 
     let send_thread_msg_target = thread_msg_target;
     thread::spawn(move || wait_thread(thread_id, send_thread_msg_target));
     let wait_thread_id = get_wait_thread_id();
 
-    let runner_shared = Rc::new(EventLoopRunner::new(thread_msg_target, wait_thread_id));
+    let runner_shared = Rc::new(EventLoopRunner::new(
+      thread_msg_target,
+      wait_thread_id,
+      attributes.opened_urls_from_args,
+    ));
 
     let thread_msg_sender = subclass_event_target_window(thread_msg_target, runner_shared.clone());
     raw_input::register_all_mice_and_keyboards_for_raw_input(thread_msg_target, Default::default());
@@ -200,7 +279,10 @@ impl<T: 'static> EventLoop<T> {
           runner_shared,
         },
         _marker: PhantomData,
+        coalesce_motion_events: Default::default(),
       },
+      msg_hook: attributes.msg_hook.take(),
+      cloak_event_hook,
     }
   }
 
@@ -208,6 +290,21 @@ impl<T: 'static> EventLoop<T> {
     &self.window_target
   }
 
+  pub fn set_msg_hook<F>(&mut self, hook: F)
+  where
+    F: FnMut(*const MSG) -> bool + 'static,
+  {
+    self.msg_hook = Some(Box::new(hook));
+  }
+
+  pub fn set_size_move_user_event_interval(&mut self, interval: Option<Duration>) {
+    self
+      .window_target
+      .p
+      .runner_shared
+      .set_size_move_user_event_interval(interval);
+  }
+
   pub fn run<F>(mut self, event_handler: F) -> !
   where
     F: 'static + FnMut(Event<'_, T>, &RootELW<T>, &mut ControlFlow),
@@ -243,22 +340,27 @@ impl<T: 'static> EventLoop<T> {
           break 'main 0;
         }
 
-        // global accelerator
-        if msg.message == WM_HOTKEY {
-          let event_loop_runner = self.window_target.p.runner_shared.clone();
-          event_loop_runner.send_event(Event::GlobalShortcutEvent(AcceleratorId(
-            msg.wParam.0 as u16,
-          )));
-        }
+        // user hook, which may consume the message before we get to see it
+        let handled = self.msg_hook.as_mut().map_or(false, |hook| hook(&msg));
 
-        // window accelerator
-        let accels = accelerator::find_accels(GetAncestor(msg.hwnd, GA_ROOT));
-        let translated = accels.map_or(false, |it| {
-          TranslateAcceleratorW(msg.hwnd, it.handle(), &msg) != 0
-        });
-        if !translated {
-          TranslateMessage(&msg);
-          DispatchMessageW(&msg);
+        if !handled {
+          // global accelerator
+          if msg.message == WM_HOTKEY {
+            let event_loop_runner = self.window_target.p.runner_shared.clone();
+            event_loop_runner.send_event(Event::GlobalShortcutEvent(AcceleratorId(
+              msg.wParam.0 as u16,
+            )));
+          }
+
+          // window accelerator
+          let accels = accelerator::find_accels(GetAncestor(msg.hwnd, GA_ROOT));
+          let translated = accels.map_or(false, |it| {
+            TranslateAcceleratorW(msg.hwnd, it.handle(), &msg) != 0
+          });
+          if !translated {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+          }
         }
 
         if let Err(payload) = runner.take_panic_error() {
@@ -308,12 +410,153 @@ impl<T> EventLoopWindowTarget<T> {
     Some(RootMonitorHandle { inner: monitor })
   }
 
+  pub fn cursor_position(&self) -> Result<PhysicalPosition<f64>, ExternalError> {
+    let mut point = POINT::default();
+    if !unsafe { GetCursorPos(&mut point) }.as_bool() {
+      return Err(ExternalError::Os(os_error!(OsError::IoError(
+        io::Error::last_os_error()
+      ))));
+    }
+    Ok(PhysicalPosition::new(point.x as f64, point.y as f64))
+  }
+
+  pub fn modifiers_state(&self) -> ModifiersState {
+    // `GetKeyState` would only reflect the keyboard messages this thread already processed.
+    let pressed = |vk: VIRTUAL_KEY| unsafe { GetAsyncKeyState(i32::from(vk.0)) } < 0;
+    let filter_out_altgr =
+      LAYOUT_CACHE.lock().get_current_layout().1.has_alt_graph && pressed(VK_RMENU);
+    let mut mods = ModifiersState::empty();
+    mods.set(ModifiersState::SHIFT, pressed(VK_SHIFT));
+    mods.set(
+      ModifiersState::CONTROL,
+      pressed(VK_CONTROL) && !filter_out_altgr,
+    );
+    mods.set(ModifiersState::ALT, pressed(VK_MENU) && !filter_out_altgr);
+    mods.set(ModifiersState::SUPER, pressed(VK_LWIN) || pressed(VK_RWIN));
+    mods
+  }
+
+  pub fn is_key_pressed(&self, key: KeyCode) -> bool {
+    let vk = match key {
+      // `MAPVK_VSC_TO_VK_EX` maps the scancodes of these two the other way around.
+      KeyCode::Pause => u32::from(VK_PAUSE.0),
+      KeyCode::NumLock => u32::from(VK_NUMLOCK.0),
+      // Extended scancodes are written as `0xE0xx` by `MAPVK_VSC_TO_VK_EX`, like ours.
+      key => match key.to_scancode() {
+        Some(scancode) => unsafe { MapVirtualKeyW(scancode, MAPVK_VSC_TO_VK_EX) },
+        None => return false,
+      },
+    };
+    vk != 0 && unsafe { GetAsyncKeyState(vk as i32) } < 0
+  }
+
+  pub fn focused_window(&self) -> Option<RootWindowId> {
+    let window = unsafe { GetForegroundWindow() };
+    // Only report the foreground window if it was created on this event loop's thread.
+    let thread_id = unsafe { GetWindowThreadProcessId(window, ptr::null_mut()) };
+    if window.0 != 0 && window != self.thread_msg_target && thread_id == self.thread_id {
+      Some(RootWindowId(WindowId(window.0)))
+    } else {
+      None
+    }
+  }
+
   pub fn raw_display_handle(&self) -> RawDisplayHandle {
     RawDisplayHandle::Windows(WindowsDisplayHandle::empty())
   }
 
+  pub fn activate_app(&self, ignore_other_apps: bool) {
+    // Collect the visible windows of this event loop, topmost first.
+    let mut windows = Vec::new();
+    let mut owned_windows = HashSet::new();
+    self.runner_shared.owned_windows(|window| {
+      owned_windows.insert(window.0);
+    });
+    unsafe {
+      let mut window = GetTopWindow(HWND::default());
+      while window.0 != 0 {
+        if owned_windows.contains(&window.0) && IsWindowVisible(window).as_bool() {
+          windows.push(window);
+        }
+        window = GetWindow(window, GW_HWNDNEXT);
+      }
+    }
+
+    // The topmost one is the window the user interacted with last.
+    let main_window = match windows.first() {
+      Some(window) => *window,
+      None => return,
+    };
+
+    unsafe {
+      // Raise the others first, keeping their stacking order.
+      for window in windows.iter().rev() {
+        if *window != main_window && IsIconic(*window).as_bool() {
+          ShowWindow(*window, SW_SHOWNOACTIVATE);
+        }
+        SetWindowPos(
+          *window,
+          HWND_TOP,
+          0,
+          0,
+          0,
+          0,
+          SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+        );
+      }
+
+      if IsIconic(main_window).as_bool() {
+        ShowWindow(main_window, SW_RESTORE);
+      }
+
+      // Windows only lets the foreground thread change the foreground window, otherwise it
+      // flashes the taskbar button instead. Attaching to its input queue lifts that restriction.
+      let foreground_thread_id = GetWindowThreadProcessId(GetForegroundWindow(), ptr::null_mut());
+      let attach =
+        ignore_other_apps && foreground_thread_id != 0 && foreground_thread_id != self.thread_id;
+      if attach {
+        AttachThreadInput(self.thread_id, foreground_thread_id, true);
+      }
+      SetForegroundWindow(main_window);
+      BringWindowToTop(main_window);
+      if attach {
+        AttachThreadInput(self.thread_id, foreground_thread_id, false);
+      }
+    }
+  }
+
+  pub fn is_compositor_active(&self) -> bool {
+    // Desktop composition can't be turned off since Windows 8, where this always returns `TRUE`.
+    unsafe { DwmIsCompositionEnabled() }
+      .map(|enabled| enabled.as_bool())
+      .unwrap_or(false)
+  }
+
+  pub fn keyboard_layout(&self) -> Option<String> {
+    keyboard_layout::active_layout_name()
+  }
+
+  pub fn accent_color(&self) -> Option<RGBA> {
+    util::accent_color()
+  }
+
+  pub fn listen_clipboard(&self, listen: bool) {
+    // Both fail harmlessly when the listener is already in the requested state.
+    unsafe {
+      if listen {
+        AddClipboardFormatListener(self.thread_msg_target);
+      } else {
+        RemoveClipboardFormatListener(self.thread_msg_target);
+      }
+    }
+  }
+
   pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
-    raw_input::register_all_mice_and_keyboards_for_raw_input(self.thread_msg_target, filter);
+    self.runner_shared.set_device_event_filter(filter);
+  }
+
+  pub fn enable_single_instance(&self, identifier: &str) -> bool {
+    single_instance::enable(self.thread_msg_target, identifier)
   }
 }
 
@@ -362,6 +605,7 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
       LPARAM(cur_thread_id as _),
     );
 
+    let timer = create_wait_timer();
     let mut wait_until_opt = None;
     'main: loop {
       // Zeroing out the message ensures that the `WaitUntilInstantBox` doesn't get
@@ -390,16 +634,13 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
       if let Some(wait_until) = wait_until_opt {
         let now = Instant::now();
         if now < wait_until {
-          // MsgWaitForMultipleObjects tends to overshoot just a little bit. We subtract
-          // 1 millisecond from the requested time and spinlock for the remainder to
-          // compensate for that.
-          let resume_reason = MsgWaitForMultipleObjectsEx(
-            &[],
-            dur2timeout(wait_until - now).saturating_sub(1),
-            QS_ALLEVENTS,
-            MWMO_INPUTAVAILABLE,
-          );
-          if resume_reason == WAIT_TIMEOUT.0 {
+          // A negative due time is relative, in 100 nanosecond intervals. Setting the timer
+          // again also resets it if it went off while we were handling messages.
+          let due_time = -(((wait_until - now).as_nanos() / 100).min(i64::MAX as u128) as i64);
+          SetWaitableTimer(timer, &due_time, 0, None, ptr::null(), false);
+          let resume_reason =
+            MsgWaitForMultipleObjectsEx(&[timer], INFINITE, QS_ALLEVENTS, MWMO_INPUTAVAILABLE);
+          if resume_reason == WAIT_OBJECT_0.0 {
             PostMessageW(
               msg_window_id,
               *PROCESS_NEW_EVENTS_MSG_ID,
@@ -419,43 +660,52 @@ fn wait_thread(parent_thread_id: u32, msg_window_id: HWND) {
         }
       }
     }
+
+    CloseHandle(timer);
   }
 }
 
-// Implementation taken from https://github.com/rust-lang/rust/blob/db5476571d9b27c862b95c1e64764b0ac8980e23/src/libstd/sys/windows/mod.rs
-fn dur2timeout(dur: Duration) -> u32 {
-  // Note that a duration is a (u64, u32) (seconds, nanoseconds) pair, and the
-  // timeouts in windows APIs are typically u32 milliseconds. To translate, we
-  // have two pieces to take care of:
-  //
-  // * Nanosecond precision is rounded up
-  // * Greater than u32::MAX milliseconds (50 days) is rounded up to INFINITE
-  //   (never time out).
-  dur
-    .as_secs()
-    .checked_mul(1000)
-    .and_then(|ms| ms.checked_add((dur.subsec_nanos() as u64) / 1_000_000))
-    .and_then(|ms| {
-      ms.checked_add(if dur.subsec_nanos() % 1_000_000 > 0 {
-        1
-      } else {
-        0
-      })
-    })
-    .map(|ms| {
-      if ms > u32::max_value() as u64 {
-        INFINITE
-      } else {
-        ms as u32
-      }
-    })
-    .unwrap_or(INFINITE)
+/// Creates the timer the wait thread waits on for `ControlFlow::WaitUntil`.
+///
+/// High resolution timers are accurate to about half a millisecond without raising the system
+/// timer resolution with `timeBeginPeriod`, but need Windows 10 version 1803. Older versions fall
+/// back to a regular timer, which goes off on the next system clock tick, usually within 16
+/// milliseconds.
+unsafe fn create_wait_timer() -> HANDLE {
+  // `SYNCHRONIZE`, so the timer can be waited on.
+  const TIMER_ACCESS: u32 = 0x0010_0000 | TIMER_MODIFY_STATE;
+  CreateWaitableTimerExW(
+    ptr::null(),
+    PCWSTR::null(),
+    CREATE_WAITABLE_TIMER_HIGH_RESOLUTION,
+    TIMER_ACCESS,
+  )
+  .or_else(|_| CreateWaitableTimerExW(ptr::null(), PCWSTR::null(), 0, TIMER_ACCESS))
+  .expect("Failed to create the event loop wait timer")
 }
 
 impl<T> Drop for EventLoop<T> {
   fn drop(&mut self) {
     unsafe {
+      if !self.cloak_event_hook.is_invalid() {
+        UnhookWinEvent(self.cloak_event_hook);
+      }
       DestroyWindow(self.window_target.p.thread_msg_target);
+
+      // Posting tasks fails once the window is destroyed, so only the tasks that were already
+      // posted are left to be freed.
+      let mut msg = MSG::default();
+      while PeekMessageW(
+        &mut msg,
+        HWND::default(),
+        *TASK_MSG_ID,
+        *TASK_MSG_ID,
+        PM_REMOVE,
+      )
+      .as_bool()
+      {
+        drop(Box::from_raw(msg.wParam.0 as *mut EventLoopTask<T>));
+      }
     }
   }
 }
@@ -546,10 +796,35 @@ impl<T: 'static> EventLoopProxy<T> {
       }
     }
   }
+
+  pub fn send_task(&self, task: EventLoopTask<T>) {
+    // We double-box because the first box is a fat pointer.
+    let raw = Box::into_raw(Box::new(task));
+    unsafe {
+      if !PostMessageW(
+        self.target_window,
+        *TASK_MSG_ID,
+        WPARAM(raw as _),
+        LPARAM(0),
+      )
+      .as_bool()
+      {
+        drop(Box::from_raw(raw));
+      }
+    }
+  }
 }
 
 type WaitUntilInstantBox = Box<Instant>;
 
+/// The timer of the thread event target window that pumps user events while a window is being
+/// moved or resized.
+const SIZE_MOVE_USER_EVENT_TIMER_ID: usize = 1;
+
+/// The timer of a window that watches for the cursor to come back over one of its cursor hit-test
+/// regions, while the window lets the cursor through.
+pub(crate) const CURSOR_HITTEST_TIMER_ID: usize = 2;
+
 lazy_static! {
     /// Message sent by the `EventLoopProxy` when we want to wake up the thread.
     /// WPARAM and LPARAM are unused.
@@ -566,6 +841,14 @@ lazy_static! {
             RegisterWindowMessageA(s!("Tao::ExecMsg"))
         }
     };
+    /// Message sent by the `EventLoopProxy` to run a task with the window target.
+    /// WPARAM contains a Box<EventLoopTask<T>> that must be retrieved with `Box::from_raw`,
+    /// and LPARAM is unused.
+    static ref TASK_MSG_ID: u32 = {
+        unsafe {
+            RegisterWindowMessageA(s!("Tao::TaskMsg"))
+        }
+    };
     static ref PROCESS_NEW_EVENTS_MSG_ID: u32 = {
         unsafe {
             RegisterWindowMessageA(s!("Tao::ProcessNewEvents"))
@@ -601,11 +884,26 @@ lazy_static! {
     pub static ref SET_RETAIN_STATE_ON_SIZE_MSG_ID: u32 = unsafe {
         RegisterWindowMessageA(s!("Tao::SetRetainMaximized"))
     };
+    /// Sent after `WindowState::device_event_filter` changed, so that it gets applied if the
+    /// window is focused.
+    pub static ref DEVICE_EVENT_FILTER_CHANGED_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::DeviceEventFilterChanged"))
+    };
+    /// Posted to a window when it is cloaked or uncloaked by the DWM.
+    /// WPARAM is a bool specifying whether the window is cloaked.
+    pub static ref OCCLUSION_CHANGED_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::OcclusionChanged"))
+    };
     /// When the taskbar is created, it registers a message with the "TaskbarCreated" string and then broadcasts this message to all top-level windows
     /// When the application receives this message, it should assume that any taskbar icons it added have been removed and add them again.
     pub static ref S_U_TASKBAR_RESTART: u32 = unsafe {
       RegisterWindowMessageA(s!("TaskbarCreated"))
     };
+    /// Sent to a window when its taskbar button is created, after which the buttons of its
+    /// thumbnail toolbar can be added.
+    pub static ref S_U_TASKBAR_BUTTON_CREATED: u32 = unsafe {
+      RegisterWindowMessageA(s!("TaskbarButtonCreated"))
+    };
     static ref THREAD_EVENT_TARGET_WINDOW_CLASS: Vec<u16> = unsafe {
         let class_name= util::encode_wide("Tao Thread Event Target");
 
@@ -630,6 +928,15 @@ lazy_static! {
     };
 }
 
+#[cfg(feature = "accesskit")]
+lazy_static! {
+    /// Posted to a window when an assistive technology requests an action.
+    /// WPARAM is a `Box<ActionRequest>`, see `accessibility::take_action_request`.
+    pub static ref ACCESSIBILITY_ACTION_MSG_ID: u32 = unsafe {
+        RegisterWindowMessageA(s!("Tao::AccessibilityAction"))
+    };
+}
+
 fn create_event_target_window() -> HWND {
   let window = unsafe {
     CreateWindowExW(
@@ -663,6 +970,8 @@ fn create_event_target_window() -> HWND {
     // the LAYERED style.
     (WS_VISIBLE | WS_POPUP).0 as isize,
   );
+  // Deliver `WM_WTSSESSION_CHANGE` so session lock and unlock can be reported.
+  unsafe { WTSRegisterSessionNotification(window, NOTIFY_FOR_THIS_SESSION) };
   window
 }
 
@@ -676,6 +985,7 @@ fn subclass_event_target_window<T>(
     let subclass_input = ThreadMsgTargetSubclassInput {
       event_loop_runner,
       user_event_receiver: rx,
+      monitors: RefCell::new(MonitorTracker::new(monitor::available_monitors())),
     };
     let input_ptr = Box::into_raw(Box::new(subclass_input));
     let subclass_result = SetWindowSubclass(
@@ -701,6 +1011,17 @@ fn remove_event_target_window_subclass<T: 'static>(window: HWND) {
   assert!(removal_result.as_bool());
 }
 
+/// Enables the owner of a shown modal window again. This must be done before the modal window is
+/// destroyed, otherwise the owner isn't activated in its place.
+unsafe fn enable_modal_owner(window: HWND, window_state: &WindowState) {
+  if window_state
+    .window_flags
+    .contains(WindowFlags::MODAL | WindowFlags::VISIBLE)
+  {
+    EnableWindow(GetWindow(window, GW_OWNER), true);
+  }
+}
+
 /// Capture mouse input, allowing `window` to receive mouse events when the cursor is outside of
 /// the window.
 unsafe fn capture_mouse(window: HWND, window_state: &mut WindowState) {
@@ -753,6 +1074,30 @@ fn normalize_pointer_pressure(pressure: u32) -> Option<Force> {
   }
 }
 
+fn pen_input(
+  pen_info: &POINTER_PEN_INFO,
+  phase: TouchPhase,
+  location: PhysicalPosition<f64>,
+) -> PenInput {
+  let has = |mask| pen_info.penMask & mask != 0;
+  PenInput {
+    device_id: DEVICE_ID,
+    phase,
+    location,
+    // Ranges from 0 to 1024.
+    pressure: if has(PEN_MASK_PRESSURE) {
+      pen_info.pressure.min(1024) as f64 / 1024.0
+    } else {
+      0.0
+    },
+    tilt: (has(PEN_MASK_TILT_X) || has(PEN_MASK_TILT_Y))
+      .then(|| (pen_info.tiltX as f64, pen_info.tiltY as f64)),
+    twist: has(PEN_MASK_ROTATION).then(|| pen_info.rotation as f64),
+    eraser: pen_info.penFlags & (PEN_FLAG_ERASER | PEN_FLAG_INVERTED) != 0,
+    barrel_button: pen_info.penFlags & PEN_FLAG_BARREL != 0,
+  }
+}
+
 /// Flush redraw events for Tao's windows.
 ///
 /// Tao's API guarantees that all redraw events will be clustered together and dispatched all at
@@ -845,10 +1190,59 @@ fn update_modifiers<T>(window: HWND, subclass_input: &SubclassInput<T>) -> Modif
   modifiers
 }
 
+/// Forwards the cloaking of the windows of this thread to them, the hook of every event loop
+/// getting the notifications of the whole process.
+unsafe extern "system" fn cloak_event_hook(
+  _: HWINEVENTHOOK,
+  event: u32,
+  window: HWND,
+  id_object: i32,
+  _: i32,
+  _: u32,
+  _: u32,
+) {
+  if id_object == OBJID_WINDOW.0
+    && GetWindowThreadProcessId(window, ptr::null_mut()) == GetCurrentThreadId()
+  {
+    let cloaked = event == EVENT_OBJECT_CLOAKED;
+    PostMessageW(
+      window,
+      *OCCLUSION_CHANGED_MSG_ID,
+      WPARAM(cloaked as _),
+      LPARAM(0),
+    );
+  }
+}
+
+/// Sends `WindowEvent::Occluded` if `update` changed whether the window is occluded.
+unsafe fn update_occlusion<T>(
+  window: HWND,
+  subclass_input: &SubclassInput<T>,
+  update: impl FnOnce(&mut WindowState),
+) {
+  let (was_occluded, occluded) = {
+    let mut window_state = subclass_input.window_state.lock();
+    let was_occluded = window_state.is_occluded();
+    update(&mut window_state);
+    (was_occluded, window_state.is_occluded())
+  };
+  if occluded != was_occluded {
+    subclass_input.send_event(Event::WindowEvent {
+      window_id: RootWindowId(WindowId(window.0)),
+      event: WindowEvent::Occluded(occluded),
+    });
+  }
+}
+
 unsafe fn gain_active_focus<T>(window: HWND, subclass_input: &SubclassInput<T>) {
   use crate::event::WindowEvent::Focused;
   update_modifiers(window, subclass_input);
 
+  let device_event_filter = subclass_input.window_state.lock().device_event_filter;
+  subclass_input
+    .event_loop_runner
+    .set_focused_device_event_filter(device_event_filter);
+
   subclass_input.send_event(Event::WindowEvent {
     window_id: RootWindowId(WindowId(window.0)),
     event: Focused(true),
@@ -859,6 +1253,10 @@ unsafe fn lose_active_focus<T>(window: HWND, subclass_input: &SubclassInput<T>)
   use crate::event::WindowEvent::{Focused, ModifiersChanged};
 
   subclass_input.window_state.lock().modifiers_state = ModifiersState::empty();
+  subclass_input
+    .event_loop_runner
+    .set_focused_device_event_filter(None);
+
   subclass_input.send_event(Event::WindowEvent {
     window_id: RootWindowId(WindowId(window.0)),
     event: ModifiersChanged(ModifiersState::empty()),
@@ -895,8 +1293,12 @@ unsafe extern "system" fn public_window_callback<T: 'static>(
     // Clear userdata
     util::SetWindowLongPtrW(window, GWL_USERDATA, 0);
 
-    let result =
-      public_window_callback_inner(window, msg, wparam, lparam, uidsubclass, subclass_input);
+    let result = match call_subclass_hook(window, msg, wparam, lparam, subclass_input) {
+      Some(result) => result,
+      None => {
+        public_window_callback_inner(window, msg, wparam, lparam, uidsubclass, subclass_input)
+      }
+    };
 
     let subclass_removed = subclass_input.subclass_removed.get();
     let recurse_depth = subclass_input.recurse_depth.get() - 1;
@@ -912,6 +1314,44 @@ unsafe extern "system" fn public_window_callback<T: 'static>(
   result
 }
 
+/// Calls the hook of the window, if any, which handles the message instead of tao when it returns
+/// a result. `WM_NCDESTROY` is always left to tao, which cleans up the window with it.
+fn call_subclass_hook<T: 'static>(
+  window: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+  subclass_input: &SubclassInput<T>,
+) -> Option<LRESULT> {
+  // The hook is taken out while it runs, so it can replace itself and re-entrant messages
+  // don't deadlock. A re-entrant message only reaches a hook set by an outer call, which must
+  // still see that replacement once the inner call returns.
+  let (mut hook, outer_replaced) = {
+    let mut slot = subclass_input.subclass_hook.lock();
+    let hook = slot.hook.take()?;
+    let replaced = mem::replace(&mut slot.replaced, false);
+    slot.depth += 1;
+    (hook, slot.depth > 1 && replaced)
+  };
+  let result = subclass_input
+    .event_loop_runner
+    .catch_unwind(|| hook(window, msg, wparam, lparam))
+    .unwrap_or(Some(LRESULT(-1)));
+  let mut slot = subclass_input.subclass_hook.lock();
+  if !slot.replaced {
+    slot.hook = Some(hook);
+  }
+  slot.replaced |= outer_replaced;
+  slot.depth -= 1;
+  drop(slot);
+
+  if msg == win32wm::WM_NCDESTROY {
+    None
+  } else {
+    result
+  }
+}
+
 unsafe fn public_window_callback_inner<T: 'static>(
   window: HWND,
   msg: u32,
@@ -976,17 +1416,27 @@ unsafe fn public_window_callback_inner<T: 'static>(
     .unwrap_or_else(|| result = ProcResult::Value(LRESULT(-1)));
 
   let ime_callback = || {
-    use crate::event::WindowEvent::ReceivedImeText;
+    use crate::event::WindowEvent::{Ime, ReceivedImeText};
     let is_ime_related = is_msg_ime_related(msg);
     if !is_ime_related {
       return;
     }
-    let text = {
+    let (events, text) = {
       let mut window_state = subclass_input.window_state.lock();
-      window_state
+      let events = window_state
+        .ime_handler
+        .composition_events(window, msg, lparam);
+      let text = window_state
         .ime_handler
-        .process_message(window, msg, wparam, lparam, &mut result)
+        .process_message(window, msg, wparam, lparam, &mut result);
+      (events, text)
     };
+    for event in events {
+      subclass_input.send_event(Event::WindowEvent {
+        window_id: RootWindowId(WindowId(window.0)),
+        event: Ime(event),
+      });
+    }
     if let Some(str) = text {
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0)),
@@ -1008,6 +1458,19 @@ unsafe fn public_window_callback_inner<T: 'static>(
         .window_state
         .lock()
         .set_window_flags_in_place(|f| f.insert(WindowFlags::MARKER_IN_SIZE_MOVE));
+
+      // User events can stall while the modal move/resize loop runs, so also pump them from a
+      // timer until it ends.
+      let runner = &subclass_input.event_loop_runner;
+      if let Some(interval) = runner.size_move_user_event_interval() {
+        let interval = interval.as_millis().min(u32::MAX as u128) as u32;
+        SetTimer(
+          runner.thread_msg_target(),
+          SIZE_MOVE_USER_EVENT_TIMER_ID,
+          interval,
+          None,
+        );
+      }
       result = ProcResult::Value(LRESULT(0));
     }
 
@@ -1016,6 +1479,10 @@ unsafe fn public_window_callback_inner<T: 'static>(
         .window_state
         .lock()
         .set_window_flags_in_place(|f| f.remove(WindowFlags::MARKER_IN_SIZE_MOVE));
+      KillTimer(
+        subclass_input.event_loop_runner.thread_msg_target(),
+        SIZE_MOVE_USER_EVENT_TIMER_ID,
+      );
       result = ProcResult::Value(LRESULT(0));
     }
 
@@ -1034,6 +1501,23 @@ unsafe fn public_window_callback_inner<T: 'static>(
       });
     }
 
+    win32wm::WM_COMMAND if util::HIWORD(wparam.0 as u32) as u32 == THBN_CLICKED => {
+      let slot = util::LOWORD(wparam.0 as u32) as usize;
+      let button_id = subclass_input
+        .window_state
+        .lock()
+        .thumbbar_buttons
+        .get(slot)
+        .map(|button| button.id);
+      if let Some(button_id) = button_id {
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: WindowEvent::ThumbBarButtonClicked(button_id),
+        });
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
     win32wm::WM_CLOSE => {
       use crate::event::WindowEvent::CloseRequested;
       subclass_input.send_event(Event::WindowEvent {
@@ -1046,6 +1530,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
     win32wm::WM_DESTROY => {
       use crate::event::WindowEvent::Destroyed;
       let _ = RevokeDragDrop(window);
+      keyboard_grab::set_keyboard_grab(window, false);
+      enable_modal_owner(window, &subclass_input.window_state.lock());
       subclass_input.send_event(Event::WindowEvent {
         window_id: RootWindowId(WindowId(window.0)),
         event: Destroyed,
@@ -1060,6 +1546,20 @@ unsafe fn public_window_callback_inner<T: 'static>(
       result = ProcResult::Value(LRESULT(0));
     }
 
+    win32wm::WM_ERASEBKGND => {
+      let background_color = subclass_input.window_state.lock().background_color;
+      if let Some((r, g, b, _)) = background_color {
+        let hdc = HDC(wparam.0 as isize);
+        let mut rect = RECT::default();
+        GetClientRect(window, &mut rect);
+        // COLORREF is laid out as 0x00BBGGRR.
+        let brush = CreateSolidBrush(r as u32 | (g as u32) << 8 | (b as u32) << 16);
+        FillRect(hdc, &rect, brush);
+        DeleteObject(brush);
+        result = ProcResult::Value(LRESULT(1));
+      }
+    }
+
     win32wm::WM_PAINT => {
       if subclass_input.event_loop_runner.should_buffer() {
         // this branch can happen in response to `UpdateWindow`, if win32 decides to
@@ -1203,6 +1703,33 @@ unsafe fn public_window_callback_inner<T: 'static>(
       }
 
       subclass_input.send_event(event);
+
+      let minimized = wparam.0 == win32wm::SIZE_MINIMIZED as _;
+      let (was_minimized, maximized_changed) = {
+        let mut w = subclass_input.window_state.lock();
+        // A maximized window that gets minimized stays maximized until it's restored.
+        let maximized = if minimized {
+          w.is_maximized
+        } else {
+          wparam.0 == win32wm::SIZE_MAXIMIZED as _
+        };
+        let maximized_changed = (maximized != w.is_maximized).then(|| maximized);
+        w.is_maximized = maximized;
+        (w.is_minimized, maximized_changed)
+      };
+      update_occlusion(window, subclass_input, |w| w.is_minimized = minimized);
+      if minimized != was_minimized {
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: WindowEvent::Minimized(minimized),
+        });
+      }
+      if let Some(maximized) = maximized_changed {
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: WindowEvent::Maximized(maximized),
+        });
+      }
       result = ProcResult::Value(LRESULT(0));
     }
 
@@ -1214,9 +1741,18 @@ unsafe fn public_window_callback_inner<T: 'static>(
       }
       if wparam.0 == SC_MINIMIZE as _ {
         let mut w = subclass_input.window_state.lock();
+        if w.minimize_to_tray {
+          // Hiding the window also removes its taskbar button.
+          WindowState::set_window_flags(w, window, |f| f.set(WindowFlags::VISIBLE, false));
+          subclass_input.send_event(Event::WindowEvent {
+            window_id: RootWindowId(WindowId(window.0)),
+            event: WindowEvent::MinimizedToTray,
+          });
+          result = ProcResult::Value(LRESULT(0));
+          return;
+        }
         w.set_window_flags_in_place(|f| f.set(WindowFlags::MINIMIZED, true));
       }
-      // Send `WindowEvent::Minimized` here if we decide to implement one
 
       if wparam.0 == SC_SCREENSAVE as _ {
         let window_state = subclass_input.window_state.lock();
@@ -1230,18 +1766,33 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_MOUSEMOVE => {
-      use crate::event::WindowEvent::{CursorEntered, CursorMoved};
-      let mouse_was_outside_window = {
+      use crate::event::WindowEvent::{CursorEntered, CursorLeft, CursorMoved};
+
+      // While the mouse is captured (e.g. a button is held), we keep receiving WM_MOUSEMOVE
+      // outside of the window but no WM_MOUSELEAVE, so check the position against the client
+      // area to report enter and leave as they happen.
+      let (cx, cy) = (
+        util::GET_X_LPARAM(lparam) as i32,
+        util::GET_Y_LPARAM(lparam) as i32,
+      );
+      let mut client_rect = RECT::default();
+      GetClientRect(window, &mut client_rect);
+      let mouse_in_window = cx >= client_rect.left
+        && cy >= client_rect.top
+        && cx < client_rect.right
+        && cy < client_rect.bottom;
+
+      let mouse_was_in_window = {
         let mut w = subclass_input.window_state.lock();
 
-        let was_outside_window = !w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
+        let was_in_window = w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
         w.mouse
-          .set_cursor_flags(window, |f| f.set(CursorFlags::IN_WINDOW, true))
+          .set_cursor_flags(window, |f| f.set(CursorFlags::IN_WINDOW, mouse_in_window))
           .ok();
-        was_outside_window
+        was_in_window
       };
 
-      if mouse_was_outside_window {
+      if mouse_in_window && !mouse_was_in_window {
         subclass_input.send_event(Event::WindowEvent {
           window_id: RootWindowId(WindowId(window.0)),
           event: CursorEntered {
@@ -1256,10 +1807,17 @@ unsafe fn public_window_callback_inner<T: 'static>(
           hwndTrack: window,
           dwHoverTime: HOVER_DEFAULT,
         });
+      } else if !mouse_in_window && mouse_was_in_window {
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: CursorLeft {
+            device_id: DEVICE_ID,
+          },
+        });
       }
 
-      let x = f64::from(util::GET_X_LPARAM(lparam));
-      let y = f64::from(util::GET_Y_LPARAM(lparam));
+      let x = f64::from(cx);
+      let y = f64::from(cy);
       let position = PhysicalPosition::new(x, y);
       let cursor_moved;
       {
@@ -1277,6 +1835,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           event: CursorMoved {
             device_id: DEVICE_ID,
             position,
+            timestamp: util::message_timestamp(),
             modifiers,
           },
         });
@@ -1287,19 +1846,24 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
     win32c::WM_MOUSELEAVE => {
       use crate::event::WindowEvent::CursorLeft;
-      {
+      let mouse_was_in_window = {
         let mut w = subclass_input.window_state.lock();
+        let was_in_window = w.mouse.cursor_flags().contains(CursorFlags::IN_WINDOW);
         w.mouse
           .set_cursor_flags(window, |f| f.set(CursorFlags::IN_WINDOW, false))
           .ok();
-      }
+        was_in_window
+      };
 
-      subclass_input.send_event(Event::WindowEvent {
-        window_id: RootWindowId(WindowId(window.0)),
-        event: CursorLeft {
-          device_id: DEVICE_ID,
-        },
-      });
+      // `CursorLeft` was already sent from WM_MOUSEMOVE if the mouse left while captured.
+      if mouse_was_in_window {
+        subclass_input.send_event(Event::WindowEvent {
+          window_id: RootWindowId(WindowId(window.0)),
+          event: CursorLeft {
+            device_id: DEVICE_ID,
+          },
+        });
+      }
 
       result = ProcResult::Value(LRESULT(0));
     }
@@ -1318,6 +1882,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           delta: LineDelta(0.0, value),
           phase: TouchPhase::Moved,
+          inertial: false,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1339,6 +1905,8 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           delta: LineDelta(value, 0.0),
           phase: TouchPhase::Moved,
+          inertial: false,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1346,6 +1914,20 @@ unsafe fn public_window_callback_inner<T: 'static>(
       result = ProcResult::Value(LRESULT(0));
     }
 
+    win32wm::DM_POINTERHITTEST => {
+      if let Some(direct_manipulation) = &subclass_input.direct_manipulation {
+        direct_manipulation.set_contact(wparam);
+      }
+      result = ProcResult::DefSubclassProc;
+    }
+
+    win32wm::WM_TIMER if wparam.0 == DIRECT_MANIPULATION_TIMER_ID => {
+      if let Some(direct_manipulation) = &subclass_input.direct_manipulation {
+        direct_manipulation.update();
+      }
+      result = ProcResult::Value(LRESULT(0));
+    }
+
     win32wm::WM_KEYDOWN | win32wm::WM_SYSKEYDOWN => {
       if msg == WM_SYSKEYDOWN && wparam.0 == usize::from(VK_F4.0) {
         result = ProcResult::DefSubclassProc;
@@ -1365,6 +1947,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Left,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1384,6 +1967,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Left,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1403,6 +1987,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Right,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1422,6 +2007,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Right,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1441,6 +2027,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Middle,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1460,6 +2047,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Middle,
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1480,6 +2068,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Pressed,
           button: Other(xbutton),
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1500,6 +2089,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
           device_id: DEVICE_ID,
           state: Released,
           button: Other(xbutton),
+          timestamp: util::message_timestamp(),
           modifiers,
         },
       });
@@ -1559,6 +2149,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
               force: None, // WM_TOUCH doesn't support pressure information
               id: input.dwID as u64,
               device_id: DEVICE_ID,
+              timestamp: util::message_timestamp(),
             }),
           });
         }
@@ -1652,6 +2243,37 @@ unsafe fn public_window_callback_inner<T: 'static>(
             continue;
           }
 
+          let x = location.x as f64 + x.fract();
+          let y = location.y as f64 + y.fract();
+          let location = PhysicalPosition::new(x, y);
+          let phase = if (pointer_info.pointerFlags & POINTER_FLAG_DOWN) != Default::default() {
+            TouchPhase::Started
+          } else if (pointer_info.pointerFlags & POINTER_FLAG_UP) != Default::default() {
+            TouchPhase::Ended
+          } else if (pointer_info.pointerFlags & POINTER_FLAG_UPDATE) != Default::default() {
+            TouchPhase::Moved
+          } else {
+            continue;
+          };
+
+          if pointer_info.pointerType == win32wm::PT_PEN {
+            let mut pen_info = mem::MaybeUninit::uninit();
+            let pen_info = GET_POINTER_PEN_INFO.and_then(|GetPointerPenInfo| {
+              if GetPointerPenInfo(pointer_info.pointerId, pen_info.as_mut_ptr()).as_bool() {
+                Some(pen_info.assume_init())
+              } else {
+                None
+              }
+            });
+            if let Some(pen_info) = pen_info {
+              subclass_input.send_event(Event::WindowEvent {
+                window_id: RootWindowId(WindowId(window.0)),
+                event: WindowEvent::PenInput(pen_input(&pen_info, phase, location)),
+              });
+            }
+            continue;
+          }
+
           let force = match pointer_info.pointerType {
             win32wm::PT_TOUCH => {
               let mut touch_info = mem::MaybeUninit::uninit();
@@ -1663,38 +2285,18 @@ unsafe fn public_window_callback_inner<T: 'static>(
                 }
               })
             }
-            win32wm::PT_PEN => {
-              let mut pen_info = mem::MaybeUninit::uninit();
-              GET_POINTER_PEN_INFO.and_then(|GetPointerPenInfo| {
-                if GetPointerPenInfo(pointer_info.pointerId, pen_info.as_mut_ptr()).as_bool() {
-                  normalize_pointer_pressure(pen_info.assume_init().pressure)
-                } else {
-                  None
-                }
-              })
-            }
             _ => None,
           };
 
-          let x = location.x as f64 + x.fract();
-          let y = location.y as f64 + y.fract();
-          let location = PhysicalPosition::new(x, y);
           subclass_input.send_event(Event::WindowEvent {
             window_id: RootWindowId(WindowId(window.0)),
             event: WindowEvent::Touch(Touch {
-              phase: if (pointer_info.pointerFlags & POINTER_FLAG_DOWN) != Default::default() {
-                TouchPhase::Started
-              } else if (pointer_info.pointerFlags & POINTER_FLAG_UP) != Default::default() {
-                TouchPhase::Ended
-              } else if (pointer_info.pointerFlags & POINTER_FLAG_UPDATE) != Default::default() {
-                TouchPhase::Moved
-              } else {
-                continue;
-              },
+              phase,
               location,
               force,
               id: pointer_info.pointerId as u64,
               device_id: DEVICE_ID,
+              timestamp: util::message_timestamp(),
             }),
           });
         }
@@ -1718,11 +2320,31 @@ unsafe fn public_window_callback_inner<T: 'static>(
       result = ProcResult::DefWindowProc;
     }
 
+    win32wm::WM_DWMCOLORIZATIONCOLORCHANGED => {
+      let accent_color = util::accent_color();
+      let changed = mem::replace(&mut *ACCENT_COLOR.lock(), accent_color) != accent_color;
+      if let (true, Some(accent_color)) = (changed, accent_color) {
+        subclass_input.send_event(Event::AccentColorChanged(accent_color));
+      }
+      result = ProcResult::DefWindowProc;
+    }
+
+    win32wm::WM_INPUTLANGCHANGE => {
+      // `lparam` is the new layout, which the keys are translated with from now on.
+      let changed = LAYOUT_CACHE.lock().set_active_layout(HKL(lparam.0));
+      if changed {
+        subclass_input.send_event(Event::KeyboardLayoutChanged);
+      }
+      result = ProcResult::DefWindowProc;
+    }
+
     win32wm::WM_SETFOCUS => {
       let active_focus_changed = subclass_input.window_state.lock().set_focused(true);
       if active_focus_changed {
         gain_active_focus(window, subclass_input);
       }
+      #[cfg(feature = "accesskit")]
+      accessibility::update_focus(&subclass_input.accessibility, true);
       result = ProcResult::Value(LRESULT(0));
     }
 
@@ -1731,6 +2353,27 @@ unsafe fn public_window_callback_inner<T: 'static>(
       if active_focus_changed {
         lose_active_focus(window, subclass_input);
       }
+      #[cfg(feature = "accesskit")]
+      accessibility::update_focus(&subclass_input.accessibility, false);
+      result = ProcResult::Value(LRESULT(0));
+    }
+
+    #[cfg(feature = "accesskit")]
+    win32wm::WM_GETOBJECT => {
+      if let Some(value) =
+        accessibility::handle_wm_getobject(&subclass_input.accessibility, wparam, lparam)
+      {
+        result = ProcResult::Value(value);
+      }
+    }
+
+    #[cfg(feature = "accesskit")]
+    _ if msg == *ACCESSIBILITY_ACTION_MSG_ID => {
+      let request = accessibility::take_action_request(wparam);
+      subclass_input.send_event(Event::WindowEvent {
+        window_id: RootWindowId(WindowId(window.0)),
+        event: WindowEvent::AccessibilityActionRequested(request),
+      });
       result = ProcResult::Value(LRESULT(0));
     }
 
@@ -1742,7 +2385,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
         // `WM_MOUSEMOVE` seems to come after `WM_SETCURSOR` for a given cursor movement.
         let in_client_area = u32::from(util::LOWORD(lparam.0 as u32)) == HTCLIENT;
         if in_client_area {
-          Some(window_state.mouse.cursor)
+          Some(window_state.mouse.cursor_handle())
         } else {
           None
         }
@@ -1750,7 +2393,7 @@ unsafe fn public_window_callback_inner<T: 'static>(
 
       match set_cursor_to {
         Some(cursor) => {
-          if let Ok(cursor) = LoadCursorW(HINSTANCE::default(), cursor.to_windows_cursor()) {
+          if let Some(cursor) = cursor {
             SetCursor(cursor);
           }
           result = ProcResult::Value(LRESULT(0));
@@ -1759,6 +2402,53 @@ unsafe fn public_window_callback_inner<T: 'static>(
       }
     }
 
+    win32wm::WM_SIZING => {
+      let window_state = subclass_input.window_state.lock();
+
+      if let Some(increments) = window_state.resize_increments {
+        let (increment_width, increment_height): (i32, i32) = increments
+          .to_physical::<u32>(window_state.scale_factor)
+          .cast::<i32>()
+          .into();
+        let is_decorated = window_state
+          .window_flags()
+          .contains(WindowFlags::DECORATIONS);
+        drop(window_state);
+
+        let rect = &mut *(lparam.0 as *mut RECT);
+        // The size of the frame around the client area.
+        let (frame_width, frame_height): (u32, u32) =
+          util::adjust_size(window, PhysicalSize::new(0, 0), is_decorated).into();
+        let (frame_width, frame_height) = (frame_width as i32, frame_height as i32);
+
+        let width = rect.right - rect.left - frame_width;
+        let height = rect.bottom - rect.top - frame_height;
+        let width = match increment_width {
+          0 => width,
+          _ => width - width % increment_width,
+        };
+        let height = match increment_height {
+          0 => height,
+          _ => height - height % increment_height,
+        };
+
+        // Only move the edges that are being dragged.
+        match wparam.0 as u32 {
+          WMSZ_LEFT | WMSZ_TOPLEFT | WMSZ_BOTTOMLEFT => {
+            rect.left = rect.right - width - frame_width
+          }
+          _ => rect.right = rect.left + width + frame_width,
+        }
+        match wparam.0 as u32 {
+          WMSZ_TOP | WMSZ_TOPLEFT | WMSZ_TOPRIGHT => rect.top = rect.bottom - height - frame_height,
+          _ => rect.bottom = rect.top + height + frame_height,
+        }
+        result = ProcResult::Value(LRESULT(1));
+      } else {
+        result = ProcResult::DefWindowProc;
+      }
+    }
+
     win32wm::WM_GETMINMAXINFO => {
       let mmi = lparam.0 as *mut MINMAXINFO;
 
@@ -2053,28 +2743,69 @@ unsafe fn public_window_callback_inner<T: 'static>(
     }
 
     win32wm::WM_NCHITTEST => {
+      // cursor location
+      let (cx, cy) = (
+        i32::from(util::GET_X_LPARAM(lparam)),
+        i32::from(util::GET_Y_LPARAM(lparam)),
+      );
+
       // Allow resizing unmaximized borderless window
-      if !util::is_maximized(window)
-        && !subclass_input
-          .window_state
-          .lock()
-          .window_flags()
-          .contains(WindowFlags::DECORATIONS)
+      let window_flags = subclass_input.window_state.lock().window_flags();
+      let hit = if !util::is_maximized(window)
+        && !window_flags.contains(WindowFlags::DECORATIONS)
+        && window_flags.contains(WindowFlags::RESIZABLE)
+        && !window_flags.contains(WindowFlags::NO_RESIZE_BORDERS)
       {
-        // cursor location
-        let (cx, cy) = (
-          i32::from(util::GET_X_LPARAM(lparam)),
-          i32::from(util::GET_Y_LPARAM(lparam)),
-        );
+        crate::platform_impl::hit_test(window.0 as _, cx, cy)
+      } else {
+        DefSubclassProc(window, msg, wparam, lparam)
+      };
 
-        result = ProcResult::Value(crate::platform_impl::hit_test(window.0 as _, cx, cy));
+      // Let the system handle dragging, double-click to maximize and the system menu.
+      let mut point = POINT { x: cx, y: cy };
+      let in_client = hit.0 == HTCLIENT as isize && ScreenToClient(window, &mut point).as_bool();
+      let position = PhysicalPosition::new(point.x as f64, point.y as f64);
+      let window_state = subclass_input.window_state.lock();
+      let in_drag_region = in_client && window_state.is_in_drag_region(position);
+      let in_hittest_region = !in_client || window_state.is_in_cursor_hittest_region(position);
+      result = ProcResult::Value(if !in_hittest_region {
+        // Returning `HTTRANSPARENT` only reaches windows of this thread, so the window itself is
+        // made transparent to the cursor until the timer finds it back over a region.
+        WindowState::set_window_flags(window_state, window, |f| {
+          f.insert(WindowFlags::MARKER_CURSOR_PASSTHROUGH)
+        });
+        SetTimer(window, CURSOR_HITTEST_TIMER_ID, 50, None);
+        LRESULT(HTTRANSPARENT as isize)
+      } else if in_drag_region {
+        LRESULT(HTCAPTION as isize)
       } else {
-        result = ProcResult::DefSubclassProc;
+        hit
+      });
+    }
+
+    win32wm::WM_TIMER if wparam.0 == CURSOR_HITTEST_TIMER_ID => {
+      let mut point = POINT::default();
+      let mut client_rect = RECT::default();
+      GetCursorPos(&mut point);
+      ScreenToClient(window, &mut point);
+      GetClientRect(window, &mut client_rect);
+      let position = PhysicalPosition::new(point.x as f64, point.y as f64);
+      let window_state = subclass_input.window_state.lock();
+      // Once the cursor leaves the client area, the next `WM_NCHITTEST` takes over again.
+      if !PtInRect(&client_rect, point).as_bool()
+        || window_state.is_in_cursor_hittest_region(position)
+      {
+        KillTimer(window, CURSOR_HITTEST_TIMER_ID);
+        WindowState::set_window_flags(window_state, window, |f| {
+          f.remove(WindowFlags::MARKER_CURSOR_PASSTHROUGH)
+        });
       }
+      result = ProcResult::Value(LRESULT(0));
     }
 
     _ => {
       if msg == *DESTROY_MSG_ID {
+        enable_modal_owner(window, &subclass_input.window_state.lock());
         DestroyWindow(window);
         result = ProcResult::Value(LRESULT(0));
       } else if msg == *SET_RETAIN_STATE_ON_SIZE_MSG_ID {
@@ -2083,9 +2814,24 @@ unsafe fn public_window_callback_inner<T: 'static>(
           f.set(WindowFlags::MARKER_RETAIN_STATE_ON_SIZE, wparam.0 != 0)
         });
         result = ProcResult::Value(LRESULT(0));
+      } else if msg == *DEVICE_EVENT_FILTER_CHANGED_MSG_ID {
+        let window_state = subclass_input.window_state.lock();
+        if window_state.has_active_focus() {
+          subclass_input
+            .event_loop_runner
+            .set_focused_device_event_filter(window_state.device_event_filter);
+        }
+        result = ProcResult::Value(LRESULT(0));
+      } else if msg == *OCCLUSION_CHANGED_MSG_ID {
+        update_occlusion(window, subclass_input, |w| w.is_cloaked = wparam.0 != 0);
+        result = ProcResult::Value(LRESULT(0));
       } else if msg == *S_U_TASKBAR_RESTART {
         let window_state = subclass_input.window_state.lock();
         set_skip_taskbar(window, window_state.skip_taskbar);
+      } else if msg == *S_U_TASKBAR_BUTTON_CREATED {
+        // The buttons of the previous taskbar button are gone.
+        subclass_input.window_state.lock().thumbbar_added = false;
+        set_thumbbar_buttons(window, &subclass_input.window_state);
       }
     }
   };
@@ -2119,6 +2865,7 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
   // the git blame and history would be preserved.
   let callback = || match msg {
     win32wm::WM_NCDESTROY => {
+      WTSUnRegisterSessionNotification(window);
       remove_event_target_window_subclass::<T>(window);
       subclass_removed = true;
       RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
@@ -2168,6 +2915,63 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
       LRESULT(0)
     }
 
+    win32wm::WM_COPYDATA => {
+      let copy_data = &*(lparam.0 as *const COPYDATASTRUCT);
+      match single_instance::decode_new_instance(copy_data) {
+        Some((args, cwd)) => {
+          subclass_input.send_event(Event::NewInstance { args, cwd });
+          RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+          LRESULT(1)
+        }
+        None => DefSubclassProc(window, msg, wparam, lparam),
+      }
+    }
+
+    win32wm::WM_WTSSESSION_CHANGE => {
+      match wparam.0 as u32 {
+        win32wm::WTS_SESSION_LOCK => subclass_input.send_event(Event::SessionLock),
+        win32wm::WTS_SESSION_UNLOCK => subclass_input.send_event(Event::SessionUnlock),
+        _ => (),
+      }
+      RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+
+      LRESULT(0)
+    }
+
+    // Broadcast to the top-level windows, which the event target window is.
+    win32wm::WM_POWERBROADCAST => {
+      match wparam.0 as u32 {
+        win32wm::PBT_APMSUSPEND => subclass_input.send_event(Event::SystemSuspend),
+        // Sent on every resume, unlike `PBT_APMRESUMESUSPEND` which needs user input.
+        win32wm::PBT_APMRESUMEAUTOMATIC => subclass_input.send_event(Event::SystemResume),
+        _ => (),
+      }
+      RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+
+      LRESULT(1)
+    }
+
+    // Scale factor changes are only reported through `WM_SETTINGCHANGE` on some versions.
+    win32wm::WM_DISPLAYCHANGE | win32wm::WM_SETTINGCHANGE => {
+      let events = subclass_input
+        .monitors
+        .borrow_mut()
+        .update(monitor::available_monitors());
+      for event in events {
+        subclass_input.send_event(event);
+      }
+      RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+
+      DefSubclassProc(window, msg, wparam, lparam)
+    }
+
+    win32wm::WM_CLIPBOARDUPDATE => {
+      subclass_input.send_event(Event::ClipboardUpdated);
+      RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+
+      LRESULT(0)
+    }
+
     win32wm::WM_INPUT => {
       if let Some(data) = raw_input::get_raw_input_data(HRAWINPUT(lparam.0)) {
         handle_raw_input(&subclass_input, data);
@@ -2177,6 +2981,26 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
       DefSubclassProc(window, msg, wparam, lparam)
     }
 
+    win32wm::WM_TIMER if wparam.0 == SIZE_MOVE_USER_EVENT_TIMER_ID => {
+      // Each user event is paired with a wakeup message, so dispatch those still queued.
+      let mut msg = MSG::default();
+      while PeekMessageW(
+        &mut msg,
+        window,
+        *USER_EVENT_MSG_ID,
+        *USER_EVENT_MSG_ID,
+        PM_REMOVE,
+      )
+      .as_bool()
+      {
+        if let Ok(event) = subclass_input.user_event_receiver.recv() {
+          subclass_input.send_event(Event::UserEvent(event));
+        }
+      }
+      RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+      LRESULT(0)
+    }
+
     _ if msg == *USER_EVENT_MSG_ID => {
       if let Ok(event) = subclass_input.user_event_receiver.recv() {
         subclass_input.send_event(Event::UserEvent(event));
@@ -2190,6 +3014,21 @@ unsafe extern "system" fn thread_event_target_callback<T: 'static>(
       RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
       LRESULT(0)
     }
+    _ if msg == *TASK_MSG_ID => {
+      let task: Box<EventLoopTask<T>> = Box::from_raw(wparam.0 as *mut _);
+      let window_target = RootELW {
+        p: EventLoopWindowTarget {
+          thread_id: GetCurrentThreadId(),
+          thread_msg_target: window,
+          runner_shared: subclass_input.event_loop_runner.clone(),
+        },
+        _marker: PhantomData,
+        coalesce_motion_events: Default::default(),
+      };
+      task(&window_target);
+      RedrawWindow(window, ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+      LRESULT(0)
+    }
     _ if msg == *PROCESS_NEW_EVENTS_MSG_ID => {
       PostThreadMessageW(
         subclass_input.event_loop_runner.wait_thread_id(),
@@ -8,7 +8,7 @@ use std::{
   collections::{HashSet, VecDeque},
   mem, panic, ptr,
   rc::Rc,
-  time::Instant,
+  time::{Duration, Instant},
 };
 
 use windows::Win32::{
@@ -19,8 +19,11 @@ use windows::Win32::{
 use crate::{
   dpi::PhysicalSize,
   event::{Event, StartCause, WindowEvent},
-  event_loop::ControlFlow,
-  platform_impl::platform::util,
+  event_loop::{ControlFlow, DeviceEventFilter},
+  platform_impl::{
+    opened_urls,
+    platform::{raw_input, util},
+  },
   window::WindowId,
 };
 
@@ -39,6 +42,16 @@ pub(crate) struct EventLoopRunner<T: 'static> {
 
   owned_windows: Cell<HashSet<isize>>,
 
+  // The filter set on the event loop, and the override of the focused window if it has one.
+  device_event_filter: Cell<DeviceEventFilter>,
+  focused_device_event_filter: Cell<Option<DeviceEventFilter>>,
+
+  // How often user events get pumped while a window is being moved or resized.
+  size_move_user_event_interval: Cell<Option<Duration>>,
+
+  // Whether the command-line arguments are still to be reported as `Event::Opened`.
+  report_opened_urls: Cell<bool>,
+
   panic_error: Cell<Option<PanicError>>,
 }
 
@@ -67,7 +80,11 @@ enum BufferedEvent<T: 'static> {
 }
 
 impl<T> EventLoopRunner<T> {
-  pub(crate) fn new(thread_msg_target: HWND, wait_thread_id: u32) -> EventLoopRunner<T> {
+  pub(crate) fn new(
+    thread_msg_target: HWND,
+    wait_thread_id: u32,
+    report_opened_urls: bool,
+  ) -> EventLoopRunner<T> {
     EventLoopRunner {
       thread_msg_target,
       wait_thread_id,
@@ -78,6 +95,10 @@ impl<T> EventLoopRunner<T> {
       event_handler: Cell::new(None),
       event_buffer: RefCell::new(VecDeque::new()),
       owned_windows: Cell::new(HashSet::new()),
+      device_event_filter: Cell::new(Default::default()),
+      focused_device_event_filter: Cell::new(None),
+      size_move_user_event_interval: Cell::new(None),
+      report_opened_urls: Cell::new(report_opened_urls),
     }
   }
 
@@ -103,6 +124,10 @@ impl<T> EventLoopRunner<T> {
       event_handler,
       event_buffer: _,
       owned_windows: _,
+      device_event_filter: _,
+      focused_device_event_filter: _,
+      size_move_user_event_interval: _,
+      report_opened_urls: _,
     } = self;
     runner_state.set(RunnerState::Uninitialized);
     panic_error.set(None);
@@ -121,6 +146,17 @@ impl<T> EventLoopRunner<T> {
     self.wait_thread_id
   }
 
+  pub fn device_event_filter(&self) -> DeviceEventFilter {
+    self
+      .focused_device_event_filter
+      .get()
+      .unwrap_or_else(|| self.device_event_filter.get())
+  }
+
+  pub fn size_move_user_event_interval(&self) -> Option<Duration> {
+    self.size_move_user_event_interval.get()
+  }
+
   pub fn redrawing(&self) -> bool {
     self.runner_state.get() == RunnerState::HandlingRedrawEvents
   }
@@ -197,6 +233,29 @@ impl<T> EventLoopRunner<T> {
     owned_windows.extend(&new_owned_windows);
     self.owned_windows.set(owned_windows);
   }
+
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    self.device_event_filter.set(filter);
+    self.register_device_events();
+  }
+
+  pub fn set_size_move_user_event_interval(&self, interval: Option<Duration>) {
+    self.size_move_user_event_interval.set(interval);
+  }
+
+  /// Sets the device event filter override of the focused window, `None` meaning it has none.
+  pub fn set_focused_device_event_filter(&self, filter: Option<DeviceEventFilter>) {
+    if self.focused_device_event_filter.replace(filter) != filter {
+      self.register_device_events();
+    }
+  }
+
+  fn register_device_events(&self) {
+    raw_input::register_all_mice_and_keyboards_for_raw_input(
+      self.thread_msg_target,
+      self.device_event_filter(),
+    );
+  }
 }
 
 /// Event dispatch functions.
@@ -394,6 +453,12 @@ impl<T> EventLoopRunner<T> {
       }
     };
     self.call_event_handler(Event::NewEvents(start_cause));
+    if init && self.report_opened_urls.replace(false) {
+      let urls = opened_urls::from_args();
+      if !urls.is_empty() {
+        self.call_event_handler(Event::Opened { urls });
+      }
+    }
     self.dispatch_buffered_events();
     RedrawWindow(
       self.thread_msg_target,
@@ -19,6 +19,10 @@ impl ShortcutManager {
     }
   }
 
+  pub(crate) fn is_supported(&self) -> bool {
+    true
+  }
+
   pub(crate) fn register(
     &mut self,
     accelerator: Accelerator,
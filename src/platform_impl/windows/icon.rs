@@ -2,18 +2,31 @@
 // Copyright 2021-2022 Tauri Programme within The Commons Conservancy
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt, io, iter::once, mem, os::windows::ffi::OsStrExt, path::Path, sync::Arc};
+use std::{
+  fmt, io,
+  iter::once,
+  mem,
+  os::windows::ffi::OsStrExt,
+  path::Path,
+  ptr,
+  sync::{Arc, Weak},
+};
+
+use lazy_static::lazy_static;
+use parking_lot::Mutex;
 
 use windows::{
   core::PCWSTR,
   Win32::{
-    Foundation::{HINSTANCE, HWND, LPARAM, WPARAM},
+    Foundation::{HANDLE, HINSTANCE, HWND, LPARAM, RECT, WPARAM},
+    Graphics::Gdi::*,
     System::LibraryLoader::*,
     UI::WindowsAndMessaging::*,
   },
 };
 
-use crate::{dpi::PhysicalSize, icon::*};
+use super::util;
+use crate::{dpi::PhysicalSize, icon::*, window::CursorImage};
 
 impl Pixel {
   fn to_bgra(&mut self) {
@@ -139,6 +152,157 @@ impl WinIcon {
     }
   }
 
+  /// Draws the icon into a new 32bpp bitmap of the small icon size, which is the format menus
+  /// need for item images. The caller owns the returned bitmap.
+  pub(crate) fn to_menu_bitmap(&self) -> HBITMAP {
+    unsafe {
+      let width = GetSystemMetrics(SM_CXSMICON);
+      let height = GetSystemMetrics(SM_CYSMICON);
+      let bitmap_info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+          biSize: mem::size_of::<BITMAPINFOHEADER>() as _,
+          biWidth: width,
+          // A negative height makes a top-down bitmap.
+          biHeight: -height,
+          biPlanes: 1,
+          biBitCount: 32,
+          biCompression: BI_RGB as _,
+          ..Default::default()
+        },
+        ..Default::default()
+      };
+
+      let hdc = CreateCompatibleDC(HDC::default());
+      let mut bits = ptr::null_mut();
+      let bitmap = CreateDIBSection(
+        hdc,
+        &bitmap_info,
+        DIB_RGB_COLORS,
+        &mut bits,
+        HANDLE::default(),
+        0,
+      )
+      .unwrap_or_default();
+      // The bitmap starts out transparent, and drawing keeps the alpha channel of the icon.
+      let old_bitmap = SelectObject(hdc, bitmap);
+      DrawIconEx(
+        hdc,
+        0,
+        0,
+        self.as_raw_handle(),
+        width,
+        height,
+        0,
+        HBRUSH::default(),
+        DI_NORMAL,
+      );
+      SelectObject(hdc, old_bitmap);
+      DeleteDC(hdc);
+      bitmap
+    }
+  }
+
+  /// Draws `text` in white on a red circle of the small icon size, for taskbar overlay icons.
+  pub(crate) fn badge(text: &str) -> Result<Self, BadIcon> {
+    unsafe {
+      let size = GetSystemMetrics(SM_CXSMICON);
+      let bitmap_info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+          biSize: mem::size_of::<BITMAPINFOHEADER>() as _,
+          biWidth: size,
+          // A negative height makes a top-down bitmap.
+          biHeight: -size,
+          biPlanes: 1,
+          biBitCount: 32,
+          biCompression: BI_RGB as _,
+          ..Default::default()
+        },
+        ..Default::default()
+      };
+
+      let hdc = CreateCompatibleDC(HDC::default());
+      let mut bits = ptr::null_mut();
+      let bitmap = match CreateDIBSection(
+        hdc,
+        &bitmap_info,
+        DIB_RGB_COLORS,
+        &mut bits,
+        HANDLE::default(),
+        0,
+      ) {
+        Ok(bitmap) => bitmap,
+        Err(_) => {
+          let error = io::Error::last_os_error();
+          DeleteDC(hdc);
+          return Err(BadIcon::OsError(error));
+        }
+      };
+      let old_bitmap = SelectObject(hdc, bitmap);
+
+      // The text is drawn on a red background, the circle is cut out with the alpha channel below.
+      let pixel_count = (size * size) as usize;
+      std::slice::from_raw_parts_mut(bits as *mut u8, pixel_count * PIXEL_SIZE)
+        .chunks_exact_mut(PIXEL_SIZE)
+        .for_each(|pixel| pixel.copy_from_slice(&[0x30, 0x30, 0xe0, 0]));
+
+      let face_name = util::encode_wide("Segoe UI");
+      let font = CreateFontW(
+        -(size * 3 / 4),
+        0,
+        0,
+        0,
+        FW_BOLD as _,
+        0,
+        0,
+        0,
+        DEFAULT_CHARSET,
+        OUT_DEFAULT_PRECIS,
+        CLIP_DEFAULT_PRECIS,
+        ANTIALIASED_QUALITY,
+        FF_SWISS,
+        PCWSTR::from_raw(face_name.as_ptr()),
+      );
+      let old_font = SelectObject(hdc, font);
+      SetBkMode(hdc, TRANSPARENT);
+      SetTextColor(hdc, 0x00ffffff);
+      let text: Vec<u16> = text.encode_utf16().collect();
+      let mut rect = RECT {
+        left: 0,
+        top: 0,
+        right: size,
+        bottom: size,
+      };
+      DrawTextW(
+        hdc,
+        &text,
+        &mut rect,
+        DT_CENTER | DT_VCENTER | DT_SINGLELINE,
+      );
+      GdiFlush();
+
+      SelectObject(hdc, old_font);
+      SelectObject(hdc, old_bitmap);
+      DeleteObject(font);
+      DeleteDC(hdc);
+
+      // GDI leaves the alpha channel alone, so the circle is made opaque here, with its edge
+      // antialiased by the distance of each pixel center to it.
+      let radius = size as f64 / 2.0;
+      let bgra = std::slice::from_raw_parts(bits as *const u8, pixel_count * PIXEL_SIZE);
+      let mut rgba = Vec::with_capacity(bgra.len());
+      for (index, pixel) in bgra.chunks_exact(PIXEL_SIZE).enumerate() {
+        let x = (index % size as usize) as f64 + 0.5 - radius;
+        let y = (index / size as usize) as f64 + 0.5 - radius;
+        let coverage = (radius - x.hypot(y) + 0.5).clamp(0.0, 1.0);
+        let a = (coverage * u8::MAX as f64).round() as u8;
+        rgba.extend_from_slice(&[pixel[2], pixel[1], pixel[0], a]);
+      }
+      DeleteObject(bitmap);
+
+      WinIcon::from_rgba(rgba, size as u32, size as u32)
+    }
+  }
+
   fn from_handle(handle: HICON) -> Self {
     Self {
       inner: Arc::new(RaiiIcon { handle }),
@@ -158,6 +322,101 @@ impl fmt::Debug for WinIcon {
   }
 }
 
+#[derive(Debug)]
+struct RaiiCursor {
+  handle: HCURSOR,
+}
+
+impl Drop for RaiiCursor {
+  fn drop(&mut self) {
+    unsafe { DestroyCursor(self.handle) };
+  }
+}
+
+lazy_static! {
+  /// The cursors created for the images of the `CustomCursor`s still alive, for them to be created
+  /// once instead of on each `set_cursor_icon`.
+  static ref CURSOR_CACHE: Mutex<Vec<(Weak<CursorImage>, WinCursor)>> = Mutex::new(Vec::new());
+}
+
+/// A cursor created from an image, see [`crate::window::CustomCursor`].
+#[derive(Clone, Debug)]
+pub struct WinCursor {
+  inner: Arc<RaiiCursor>,
+}
+
+impl WinCursor {
+  pub fn as_raw_handle(&self) -> HCURSOR {
+    self.inner.handle
+  }
+
+  /// Returns the cursor of an image, creating it the first time it is used.
+  pub fn for_image(image: &Arc<CursorImage>) -> Result<Self, BadIcon> {
+    let mut cache = CURSOR_CACHE.lock();
+    cache.retain(|(cached, _)| cached.strong_count() > 0);
+    if let Some((_, cursor)) = cache
+      .iter()
+      .find(|(cached, _)| ptr::eq(cached.as_ptr(), Arc::as_ptr(image)))
+    {
+      return Ok(cursor.clone());
+    }
+    let cursor = Self::from_image(image)?;
+    cache.push((Arc::downgrade(image), cursor.clone()));
+    Ok(cursor)
+  }
+
+  fn from_image(image: &CursorImage) -> Result<Self, BadIcon> {
+    let RgbaIcon {
+      rgba,
+      width,
+      height,
+    } = &image.rgba;
+    let mut bgra = rgba.clone();
+    let pixel_count = bgra.len() / PIXEL_SIZE;
+    let pixels =
+      unsafe { std::slice::from_raw_parts_mut(bgra.as_mut_ptr() as *mut Pixel, pixel_count) };
+    for pixel in pixels {
+      pixel.to_bgra();
+    }
+    // The mask is ignored for images with an alpha channel, but must still be provided.
+    // Its rows are aligned on 16 bits.
+    let mask = vec![0u8; (*width as usize + 15) / 16 * 2 * *height as usize];
+
+    unsafe {
+      let color_bitmap = CreateBitmap(
+        *width as i32,
+        *height as i32,
+        1,
+        (PIXEL_SIZE * 8) as u32,
+        bgra.as_ptr() as *const _,
+      );
+      let mask_bitmap = CreateBitmap(
+        *width as i32,
+        *height as i32,
+        1,
+        1,
+        mask.as_ptr() as *const _,
+      );
+      let icon_info = ICONINFO {
+        fIcon: false.into(),
+        xHotspot: image.hotspot_x,
+        yHotspot: image.hotspot_y,
+        hbmMask: mask_bitmap,
+        hbmColor: color_bitmap,
+      };
+      let handle = CreateIconIndirect(&icon_info);
+      DeleteObject(color_bitmap);
+      DeleteObject(mask_bitmap);
+      let handle = handle.map_err(|_| BadIcon::OsError(io::Error::last_os_error()))?;
+      Ok(WinCursor {
+        inner: Arc::new(RaiiCursor {
+          handle: HCURSOR(handle.0),
+        }),
+      })
+    }
+  }
+}
+
 pub fn unset_for_window(hwnd: HWND, icon_type: IconType) {
   unsafe {
     SendMessageW(hwnd, WM_SETICON, WPARAM(icon_type as _), LPARAM(0));
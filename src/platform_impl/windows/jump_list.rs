@@ -0,0 +1,126 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use windows::{
+  core::{Interface, PCWSTR, PWSTR},
+  Win32::{
+    System::{
+      Com::{CoCreateInstance, StructuredStorage::PROPVARIANT, CLSCTX_INPROC_SERVER},
+      Ole::VT_LPWSTR,
+    },
+    UI::Shell::{
+      Common::{IObjectArray, IObjectCollection},
+      DestinationList, EnumerableObjectCollection, ICustomDestinationList, IShellLinkW,
+      PropertiesSystem::{IPropertyStore, PROPERTYKEY},
+      ShellLink, KDC_FREQUENT, KDC_RECENT,
+    },
+  },
+};
+
+use crate::{
+  error::ExternalError,
+  platform::windows::{JumpList, JumpListCategory, JumpListTask},
+};
+
+use super::{util, window::com_initialized, OsError};
+
+/// `PKEY_Title`, the title of a shell link shown in the jump list.
+const PKEY_TITLE: PROPERTYKEY = PROPERTYKEY {
+  fmtid: windows::core::GUID::from_u128(0xf29f85e0_4ff9_1068_ab91_08002b27b3d9),
+  pid: 2,
+};
+
+pub fn apply_jump_list(list: &JumpList) -> Result<(), ExternalError> {
+  com_initialized();
+  unsafe { apply(list) }.map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e.into()))))
+}
+
+pub fn clear_jump_list(app_id: Option<&str>) -> Result<(), ExternalError> {
+  com_initialized();
+  unsafe { clear(app_id) }.map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e.into()))))
+}
+
+unsafe fn clear(app_id: Option<&str>) -> windows::core::Result<()> {
+  let destination_list: ICustomDestinationList =
+    CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+  let app_id = app_id.map(util::encode_wide);
+  destination_list.DeleteList(
+    app_id
+      .as_ref()
+      .map_or(PCWSTR::null(), |app_id| PCWSTR::from_raw(app_id.as_ptr())),
+  )
+}
+
+unsafe fn apply(list: &JumpList) -> windows::core::Result<()> {
+  let destination_list: ICustomDestinationList =
+    CoCreateInstance(&DestinationList, None, CLSCTX_INPROC_SERVER)?;
+  if let Some(app_id) = &list.app_id {
+    let app_id = util::encode_wide(app_id);
+    destination_list.SetAppID(PCWSTR::from_raw(app_id.as_ptr()))?;
+  }
+
+  let mut min_slots = 0;
+  let _removed: IObjectArray = destination_list.BeginList(&mut min_slots)?;
+
+  let result = (|| {
+    for category in &list.categories {
+      match category {
+        JumpListCategory::Recent => destination_list.AppendKnownCategory(KDC_RECENT)?,
+        JumpListCategory::Frequent => destination_list.AppendKnownCategory(KDC_FREQUENT)?,
+        JumpListCategory::Custom(title, tasks) => {
+          let title = util::encode_wide(title);
+          let tasks = create_links(tasks)?;
+          destination_list.AppendCategory(PCWSTR::from_raw(title.as_ptr()), &tasks)?;
+        }
+      }
+    }
+
+    if !list.tasks.is_empty() {
+      destination_list.AddUserTasks(&create_links(&list.tasks)?)?;
+    }
+
+    destination_list.CommitList()
+  })();
+
+  if result.is_err() {
+    let _ = destination_list.AbortList();
+  }
+  result
+}
+
+/// Creates the shell links starting the executable of the application for `tasks`.
+unsafe fn create_links(tasks: &[JumpListTask]) -> windows::core::Result<IObjectArray> {
+  let exe = std::env::current_exe().map_err(|_| windows::core::Error::from_win32())?;
+  let exe = util::encode_wide(exe);
+
+  let collection: IObjectCollection =
+    CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_INPROC_SERVER)?;
+  for task in tasks {
+    let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+    link.SetPath(PCWSTR::from_raw(exe.as_ptr()))?;
+
+    let arguments = util::encode_wide(&task.arguments);
+    link.SetArguments(PCWSTR::from_raw(arguments.as_ptr()))?;
+    if !task.description.is_empty() {
+      let description = util::encode_wide(&task.description);
+      link.SetDescription(PCWSTR::from_raw(description.as_ptr()))?;
+    }
+    if let Some((path, index)) = &task.icon {
+      let path = util::encode_wide(path);
+      link.SetIconLocation(PCWSTR::from_raw(path.as_ptr()), *index)?;
+    }
+
+    // The property store copies the title, so it doesn't need to outlive `SetValue`.
+    let mut title = util::encode_wide(&task.title);
+    let mut value = PROPVARIANT::default();
+    (*value.Anonymous.Anonymous).vt = VT_LPWSTR.0 as u16;
+    (*value.Anonymous.Anonymous).Anonymous.pwszVal = PWSTR(title.as_mut_ptr());
+    let store: IPropertyStore = link.cast()?;
+    store.SetValue(&PKEY_TITLE, &value)?;
+    store.Commit()?;
+
+    collection.AddObject(&link)?;
+  }
+  collection.cast()
+}
@@ -5,6 +5,7 @@ use std::{
   ffi::OsString,
   mem::MaybeUninit,
   os::windows::ffi::OsStringExt,
+  time::Instant,
 };
 
 use windows::Win32::{
@@ -25,7 +26,7 @@ use crate::{
     platform::{
       event_loop::ProcResult,
       keyboard_layout::{get_or_insert_str, Layout, LayoutCache, WindowsModifiers, LAYOUT_CACHE},
-      KeyEventExtra,
+      util, KeyEventExtra,
     },
     WindowId,
   },
@@ -466,6 +467,7 @@ impl KeyEventBuilder {
       location: get_location(scancode, locale_id),
       utf16parts: Vec::with_capacity(8),
       text: PartialText::Text(text),
+      timestamp: Instant::now(),
     };
 
     let mut event = event_info.finalize(&mut layouts.strings);
@@ -511,6 +513,9 @@ struct PartialKeyEventInfo {
   utf16parts: Vec<u16>,
 
   text: PartialText,
+
+  /// The time of the key down or up message.
+  timestamp: Instant,
 }
 
 impl PartialKeyEventInfo {
@@ -605,6 +610,7 @@ impl PartialKeyEventInfo {
       location,
       utf16parts: Vec::with_capacity(8),
       text: PartialText::System(Vec::new()),
+      timestamp: util::message_timestamp(),
     }
   }
 
@@ -656,6 +662,7 @@ impl PartialKeyEventInfo {
       location: self.location,
       state: self.key_state,
       repeat: self.is_repeat,
+      timestamp: self.timestamp,
       platform_specific: KeyEventExtra {
         text_with_all_modifiers: char_with_all_modifiers,
         key_without_modifiers: self.key_without_modifiers,
@@ -918,6 +925,9 @@ pub(crate) fn key_to_vk(key: &KeyCode) -> Option<VIRTUAL_KEY> {
     KeyCode::MediaStop => VK_MEDIA_STOP,
     KeyCode::MediaPlayPause => VK_MEDIA_PLAY_PAUSE,
     KeyCode::LaunchMail => VK_LAUNCH_MAIL,
+    KeyCode::LaunchApp1 => VK_LAUNCH_APP1,
+    KeyCode::LaunchApp2 => VK_LAUNCH_APP2,
+    KeyCode::MediaSelect => VK_LAUNCH_MEDIA_SELECT,
     KeyCode::Convert => VK_CONVERT,
     _ => return None,
   })
@@ -0,0 +1,95 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::cell::Cell;
+
+use windows::{
+  core::PCWSTR,
+  Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    System::LibraryLoader::GetModuleHandleW,
+    UI::{
+      Input::KeyboardAndMouse::{
+        GetAsyncKeyState, VK_CONTROL, VK_ESCAPE, VK_LWIN, VK_RWIN, VK_TAB,
+      },
+      WindowsAndMessaging::*,
+    },
+  },
+};
+
+thread_local! {
+  // Low-level hooks are called on the thread that installed them, which is the window's thread.
+  static KEYBOARD_GRAB: Cell<Option<(HWND, HHOOK)>> = Cell::new(None);
+}
+
+/// Installs or removes the low-level keyboard hook that redirects system shortcuts to `hwnd`.
+///
+/// Must be called on the thread that owns `hwnd`.
+pub unsafe fn set_keyboard_grab(hwnd: HWND, grab: bool) {
+  KEYBOARD_GRAB.with(|current| {
+    match (grab, current.get()) {
+      (true, Some((grab_hwnd, _))) if grab_hwnd == hwnd => return,
+      // Another window releasing its grab must not remove ours.
+      (false, Some((grab_hwnd, _))) if grab_hwnd != hwnd => return,
+      _ => (),
+    }
+
+    if let Some((_, hook)) = current.take() {
+      UnhookWindowsHookEx(hook);
+    }
+
+    if grab {
+      let hinstance = GetModuleHandleW(PCWSTR::null()).unwrap_or_default();
+      match SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook_proc), hinstance, 0) {
+        Ok(hook) => current.set(Some((hwnd, hook))),
+        Err(e) => warn!("Failed to install the low-level keyboard hook: {}", e),
+      }
+    }
+  });
+}
+
+unsafe extern "system" fn keyboard_hook_proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+  let (hwnd, hook) = match KEYBOARD_GRAB.with(|current| current.get()) {
+    Some(grab) => grab,
+    None => return CallNextHookEx(HHOOK::default(), code, wparam, lparam),
+  };
+
+  if code == HC_ACTION as i32 && GetForegroundWindow() == hwnd {
+    let info = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+    if is_system_shortcut(info) {
+      // Swallow the key and hand it to the window instead, as if it was never intercepted.
+      let up = info.flags.0 & LLKHF_UP.0 != 0;
+      let mut key_lparam = 1 | (info.scanCode & 0xff) << 16;
+      if info.flags.0 & LLKHF_EXTENDED.0 != 0 {
+        key_lparam |= 1 << 24;
+      }
+      if info.flags.0 & LLKHF_ALTDOWN.0 != 0 {
+        key_lparam |= 1 << 29;
+      }
+      if up {
+        key_lparam |= 0b11 << 30;
+      }
+      PostMessageW(
+        hwnd,
+        wparam.0 as u32,
+        WPARAM(info.vkCode as usize),
+        LPARAM(key_lparam as isize),
+      );
+      return LRESULT(1);
+    }
+  }
+
+  CallNextHookEx(hook, code, wparam, lparam)
+}
+
+unsafe fn is_system_shortcut(info: &KBDLLHOOKSTRUCT) -> bool {
+  let alt = info.flags.0 & LLKHF_ALTDOWN.0 != 0;
+  let ctrl = GetAsyncKeyState(VK_CONTROL.0 as i32) < 0;
+  match info.vkCode as u16 {
+    vk if vk == VK_LWIN.0 || vk == VK_RWIN.0 => true,
+    vk if vk == VK_TAB.0 => alt,
+    vk if vk == VK_ESCAPE.0 => alt || ctrl,
+    _ => false,
+  }
+}
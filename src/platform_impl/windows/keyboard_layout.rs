@@ -234,6 +234,8 @@ pub(crate) struct LayoutCache {
   /// Maps locale identifiers (HKL) to layouts
   pub layouts: HashMap<isize, Layout>,
   pub strings: HashSet<&'static str>,
+  /// The layout last reported with `Event::KeyboardLayoutChanged`.
+  reported_layout: Option<isize>,
 }
 
 impl LayoutCache {
@@ -251,6 +253,14 @@ impl LayoutCache {
     }
   }
 
+  /// Records `locale_id` as the active layout, returning whether it differs from the previous one.
+  ///
+  /// `WM_INPUTLANGCHANGE` is sent to every window affected by the switch, but should only be
+  /// reported once.
+  pub fn set_active_layout(&mut self, locale_id: HKL) -> bool {
+    self.reported_layout.replace(locale_id.0) != Some(locale_id.0)
+  }
+
   pub fn get_agnostic_mods(&mut self) -> ModifiersState {
     let (_, layout) = self.get_current_layout();
     let filter_out_altgr = layout.has_alt_graph && key_pressed(VK_RMENU);
@@ -474,6 +484,17 @@ impl LayoutCache {
   }
 }
 
+/// Returns the identifier of the active layout of the current thread, e.g. `00000409`.
+pub fn active_layout_name() -> Option<String> {
+  let mut name = [0u16; KL_NAMELENGTH as usize];
+  if unsafe { GetKeyboardLayoutNameW(&mut name) }.as_bool() {
+    let len = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+    Some(String::from_utf16_lossy(&name[..len]))
+  } else {
+    None
+  }
+}
+
 pub fn get_or_insert_str<T>(strings: &mut HashSet<&'static str>, string: T) -> &'static str
 where
   T: AsRef<str>,
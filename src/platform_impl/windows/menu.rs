@@ -8,6 +8,7 @@ use windows::{
   core::{PCWSTR, PWSTR},
   Win32::{
     Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    Graphics::Gdi::DeleteObject,
     UI::{
       Input::KeyboardAndMouse::*,
       Shell::*,
@@ -21,8 +22,11 @@ use crate::{
   event::{Event, WindowEvent},
   icon::Icon,
   keyboard::{KeyCode, ModifiersState},
-  menu::{CustomMenuItem, MenuId, MenuItem, MenuType},
+  menu::{
+    CustomMenuItem, MenuId, MenuItem, MenuItemAttributes as RootMenuItemAttributes, MenuType,
+  },
   window::WindowId as RootWindowId,
+  TrayId,
 };
 
 use super::{accelerator::register_accel, keyboard::key_to_vk, util, WindowId};
@@ -46,12 +50,18 @@ const MINIMIZE_ID: usize = 5008;
 
 lazy_static! {
   static ref MENU_IDS: Mutex<Vec<u16>> = Mutex::new(vec![]);
+  // Windows each menu bar is attached to.
+  static ref MENU_WINDOWS: Mutex<HashMap<isize, Vec<isize>>> = Mutex::new(HashMap::new());
+  // Radio group of each radio item, keyed by the menu it belongs to and its id.
+  static ref RADIO_GROUPS: Mutex<HashMap<(isize, u16), u16>> = Mutex::new(HashMap::new());
 }
 
 pub struct MenuHandler {
   window_id: Option<RootWindowId>,
+  tray_id: Option<TrayId>,
   menu_type: MenuType,
   event_sender: Box<dyn Fn(Event<'static, ()>)>,
+  hmenu: HMENU,
 }
 
 impl MenuHandler {
@@ -59,11 +69,14 @@ impl MenuHandler {
     event_sender: Box<dyn Fn(Event<'static, ()>)>,
     menu_type: MenuType,
     window_id: Option<RootWindowId>,
+    tray_id: Option<TrayId>,
   ) -> MenuHandler {
     MenuHandler {
       window_id,
+      tray_id,
       menu_type,
       event_sender,
+      hmenu: HMENU::default(),
     }
   }
   pub fn send_menu_event(&self, menu_id: u16) {
@@ -71,6 +84,7 @@ impl MenuHandler {
       menu_id: MenuId(menu_id),
       origin: self.menu_type,
       window_id: self.window_id,
+      tray_id: self.tray_id,
     });
   }
 
@@ -121,7 +135,7 @@ impl MenuItemAttributes {
     let mut title = title.to_string();
     if let Some(accelerator) = &self.2 {
       title.push('\t');
-      title.push_str(accelerator.to_string().as_str());
+      title.push_str(&AcceleratorLabel(accelerator).to_string());
     }
     unsafe {
       let info = MENUITEMINFOW {
@@ -136,6 +150,9 @@ impl MenuItemAttributes {
   }
   pub fn set_selected(&mut self, selected: bool) {
     unsafe {
+      if selected && select_radio_item(self.1, self.0) {
+        return;
+      }
       CheckMenuItem(
         self.1,
         self.0 as u32,
@@ -147,8 +164,24 @@ impl MenuItemAttributes {
     }
   }
 
-  // todo: set custom icon to the menu item
-  pub fn set_icon(&mut self, _icon: Icon) {}
+  pub fn set_icon(&mut self, icon: Icon) {
+    unsafe {
+      let mut info = MENUITEMINFOW {
+        cbSize: std::mem::size_of::<MENUITEMINFOW>() as _,
+        fMask: MIIM_BITMAP,
+        ..Default::default()
+      };
+      GetMenuItemInfoW(self.1, self.0 as u32, false, &mut info);
+      // The menu doesn't take ownership of its item bitmaps, so we free the one we replace.
+      let old_bitmap = info.hbmpItem;
+
+      info.hbmpItem = icon.inner.to_menu_bitmap();
+      SetMenuItemInfoW(self.1, self.0 as u32, false, &info);
+      if !old_bitmap.is_invalid() {
+        DeleteObject(old_bitmap);
+      }
+    }
+  }
 }
 
 #[derive(Debug, Clone)]
@@ -201,13 +234,27 @@ impl Menu {
 
   pub fn add_item(
     &mut self,
-    menu_id: MenuId,
-    title: &str,
-    accelerator: Option<Accelerator>,
-    enabled: bool,
-    selected: bool,
+    item: RootMenuItemAttributes<'_>,
+    menu_type: MenuType,
+  ) -> CustomMenuItem {
+    self.insert_item(usize::MAX, item, menu_type)
+  }
+
+  pub fn insert_item(
+    &mut self,
+    index: usize,
+    item: RootMenuItemAttributes<'_>,
     _menu_type: MenuType,
   ) -> CustomMenuItem {
+    let RootMenuItemAttributes {
+      id: menu_id,
+      title,
+      keyboard_accelerator: accelerator,
+      accelerator_display_only,
+      enabled,
+      selected,
+      radio_group,
+    } = item;
     unsafe {
       let mut flags = MF_STRING;
       if !enabled {
@@ -220,27 +267,80 @@ impl Menu {
       let mut title = title.to_string();
       if let Some(accelerator) = &accelerator {
         title.push('\t');
-        title.push_str(accelerator.to_string().as_str());
+        title.push_str(&AcceleratorLabel(accelerator).to_string());
       }
 
-      AppendMenuW(
+      // `u32::MAX` appends the item.
+      let position = u32::try_from(index).unwrap_or(u32::MAX);
+      InsertMenuW(
         self.hmenu,
-        flags,
+        position,
+        flags | MF_BYPOSITION,
         menu_id.0 as _,
         PCWSTR::from_raw(util::encode_wide(title).as_ptr()),
       );
 
-      // add our accels
-      if let Some(accelerators) = &accelerator {
+      if let Some(radio_group) = radio_group {
+        let info = MENUITEMINFOW {
+          cbSize: std::mem::size_of::<MENUITEMINFOW>() as _,
+          fMask: MIIM_FTYPE,
+          fType: MFT_STRING | MFT_RADIOCHECK,
+          ..Default::default()
+        };
+        SetMenuItemInfoW(self.hmenu, menu_id.0 as u32, false, &info);
+        RADIO_GROUPS
+          .lock()
+          .unwrap()
+          .insert((self.hmenu.0, menu_id.0), radio_group.0);
+        if selected {
+          select_radio_item(self.hmenu, menu_id.0);
+        }
+      }
+
+      // add our accels, unless the app handles the shortcut itself
+      if let (Some(accelerators), false) = (&accelerator, accelerator_display_only) {
         if let Some(accelerators) = accelerators.to_accel(menu_id.0) {
           self.accels.insert(menu_id.0, AccelWrapper(accelerators));
         }
       }
       MENU_IDS.lock().unwrap().push(menu_id.0 as _);
+      redraw_menu_bar(self.hmenu);
       CustomMenuItem(MenuItemAttributes(menu_id.0, self.hmenu, accelerator))
     }
   }
 
+  pub fn remove_item(&mut self, item: &MenuItemAttributes) {
+    if item.1 != self.hmenu {
+      return;
+    }
+    unsafe {
+      release_menu_item(self.hmenu, item.0);
+      self.accels.remove(&item.0);
+      DeleteMenu(self.hmenu, item.0 as u32, MF_BYCOMMAND);
+      redraw_menu_bar(self.hmenu);
+    }
+  }
+
+  pub fn clear(&mut self) {
+    unsafe {
+      while GetMenuItemCount(self.hmenu) > 0 {
+        let mut info = MENUITEMINFOW {
+          cbSize: std::mem::size_of::<MENUITEMINFOW>() as _,
+          fMask: MIIM_ID | MIIM_SUBMENU,
+          ..Default::default()
+        };
+        GetMenuItemInfoW(self.hmenu, 0, true, &mut info);
+        if info.hSubMenu.is_invalid() {
+          release_menu_item(self.hmenu, info.wID as u16);
+        }
+        // Unlike `DeleteMenu`, this doesn't destroy submenus, which may still be in use.
+        RemoveMenu(self.hmenu, 0, MF_BYPOSITION);
+      }
+      self.accels.clear();
+      redraw_menu_bar(self.hmenu);
+    }
+  }
+
   pub fn add_submenu(&mut self, title: &str, enabled: bool, mut submenu: Menu) {
     unsafe {
       let child_accels = std::mem::take(&mut submenu.accels);
@@ -347,9 +447,16 @@ impl Menu {
 
 const MENU_SUBCLASS_ID: usize = 4568;
 
-pub fn initialize(menu_builder: Menu, window: HWND, menu_handler: MenuHandler) -> HMENU {
-  let sender: *mut MenuHandler = Box::into_raw(Box::new(menu_handler));
+pub fn initialize(menu_builder: Menu, window: HWND, mut menu_handler: MenuHandler) -> HMENU {
   let menu = menu_builder.hmenu();
+  menu_handler.hmenu = menu;
+  MENU_WINDOWS
+    .lock()
+    .unwrap()
+    .entry(menu.0)
+    .or_default()
+    .push(window.0);
+  let sender: *mut MenuHandler = Box::into_raw(Box::new(menu_handler));
 
   unsafe {
     SetWindowSubclass(window, Some(subclass_proc), MENU_SUBCLASS_ID, sender as _);
@@ -375,6 +482,7 @@ pub(crate) unsafe extern "system" fn subclass_proc(
   let subclass_input = &*(subclass_input_ptr);
 
   if msg == WM_DESTROY {
+    release_menu(hwnd, subclass_input.hmenu);
     Box::from_raw(subclass_input_ptr);
   }
 
@@ -412,6 +520,16 @@ pub(crate) unsafe extern "system" fn subclass_proc(
         _ => {
           let menu_id = util::LOWORD(wparam.0 as u32);
           if MENU_IDS.lock().unwrap().contains(&menu_id) {
+            let radio_menus: Vec<isize> = RADIO_GROUPS
+              .lock()
+              .unwrap()
+              .keys()
+              .filter(|(_, id)| *id == menu_id)
+              .map(|(hmenu, _)| *hmenu)
+              .collect();
+            for hmenu in radio_menus {
+              select_radio_item(HMENU(hmenu), menu_id);
+            }
             subclass_input.send_menu_event(menu_id);
           }
         }
@@ -422,6 +540,88 @@ pub(crate) unsafe extern "system" fn subclass_proc(
   }
 }
 
+/// Checks a radio item and unchecks the other items of its group.
+///
+/// Returns `false` if the item isn't part of a radio group.
+unsafe fn select_radio_item(hmenu: HMENU, menu_id: u16) -> bool {
+  let radio_groups = RADIO_GROUPS.lock().unwrap();
+  let group = match radio_groups.get(&(hmenu.0, menu_id)) {
+    Some(group) => *group,
+    None => return false,
+  };
+  for ((item_hmenu, item_id), item_group) in radio_groups.iter() {
+    if *item_hmenu == hmenu.0 && *item_group == group {
+      let state = if *item_id == menu_id {
+        MF_CHECKED
+      } else {
+        MF_UNCHECKED
+      };
+      CheckMenuItem(hmenu, *item_id as u32, state.0);
+    }
+  }
+  true
+}
+
+// `DestroyWindow` destroys the menu attached to the window, so we detach it first
+// if other windows are still using it.
+unsafe fn release_menu(hwnd: HWND, hmenu: HMENU) {
+  let mut menu_windows = MENU_WINDOWS.lock().unwrap();
+  if let Some(windows) = menu_windows.get_mut(&hmenu.0) {
+    if let Some(position) = windows.iter().position(|window| *window == hwnd.0) {
+      windows.remove(position);
+    }
+    if windows.is_empty() {
+      menu_windows.remove(&hmenu.0);
+      forget_radio_groups(hmenu);
+    } else if GetMenu(hwnd) == hmenu {
+      SetMenu(hwnd, HMENU::default());
+    }
+  }
+}
+
+/// Forgets the radio items of `hmenu` and its submenus once they are no longer shown, before they
+/// are destroyed, since their handles can be reused by new menus.
+pub(crate) unsafe fn forget_radio_groups(hmenu: HMENU) {
+  for position in 0..GetMenuItemCount(hmenu).max(0) {
+    let submenu = GetSubMenu(hmenu, position);
+    if !submenu.is_invalid() {
+      forget_radio_groups(submenu);
+    }
+  }
+  RADIO_GROUPS
+    .lock()
+    .unwrap()
+    .retain(|(item_hmenu, _), _| *item_hmenu != hmenu.0);
+}
+
+/// Redraws the windows showing `hmenu` as their menu bar, after it was modified.
+unsafe fn redraw_menu_bar(hmenu: HMENU) {
+  if let Some(windows) = MENU_WINDOWS.lock().unwrap().get(&hmenu.0) {
+    for window in windows {
+      DrawMenuBar(HWND(*window));
+    }
+  }
+}
+
+/// Frees what we keep around for a custom item before it is removed from `hmenu`.
+unsafe fn release_menu_item(hmenu: HMENU, menu_id: u16) {
+  let mut info = MENUITEMINFOW {
+    cbSize: std::mem::size_of::<MENUITEMINFOW>() as _,
+    fMask: MIIM_BITMAP,
+    ..Default::default()
+  };
+  GetMenuItemInfoW(hmenu, menu_id as u32, false, &mut info);
+  if !info.hbmpItem.is_invalid() {
+    DeleteObject(info.hbmpItem);
+  }
+
+  let mut menu_ids = MENU_IDS.lock().unwrap();
+  if let Some(position) = menu_ids.iter().position(|id| *id == menu_id) {
+    menu_ids.remove(position);
+  }
+  RADIO_GROUPS.lock().unwrap().remove(&(hmenu.0, menu_id));
+}
+
 enum EditCommand {
   Copy,
   Cut,
@@ -498,9 +698,12 @@ impl Accelerator {
   }
 }
 
-impl fmt::Display for Accelerator {
+/// Formats an accelerator the way Windows menus show it next to the item label.
+struct AcceleratorLabel<'a>(&'a Accelerator);
+
+impl fmt::Display for AcceleratorLabel<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    let key_mods: ModifiersState = self.mods;
+    let key_mods: ModifiersState = self.0.mods;
     if key_mods.control_key() {
       write!(f, "Ctrl+")?;
     }
@@ -513,7 +716,7 @@ impl fmt::Display for Accelerator {
     if key_mods.super_key() {
       write!(f, "Windows+")?;
     }
-    match &self.key {
+    match &self.0.key {
       KeyCode::KeyA => write!(f, "A"),
       KeyCode::KeyB => write!(f, "B"),
       KeyCode::KeyC => write!(f, "C"),
@@ -573,7 +776,7 @@ impl fmt::Display for Accelerator {
       KeyCode::ArrowRight => write!(f, "Right"),
       KeyCode::ArrowUp => write!(f, "Up"),
       KeyCode::ArrowDown => write!(f, "Down"),
-      _ => write!(f, "{:?}", self.key),
+      _ => write!(f, "{:?}", self.0.key),
     }
   }
 }
@@ -1,11 +1,18 @@
-use std::mem::MaybeUninit;
+use std::{mem::MaybeUninit, ptr};
 
 use windows::Win32::{
   Foundation::{HWND, LPARAM, LRESULT, WPARAM},
-  UI::WindowsAndMessaging::{self as win32wm, *},
+  Globalization::HIMC,
+  UI::{
+    Input::Ime::{
+      ImmGetCompositionStringW, ImmGetContext, ImmReleaseContext, GCS_COMPSTR, GCS_CURSORPOS,
+      GCS_RESULTSTR,
+    },
+    WindowsAndMessaging::{self as win32wm, *},
+  },
 };
 
-use crate::platform_impl::platform::event_loop::ProcResult;
+use crate::{event::Ime, platform_impl::platform::event_loop::ProcResult};
 
 pub fn is_msg_ime_related(msg_kind: u32) -> bool {
   matches!(
@@ -35,6 +42,59 @@ impl Default for MinimalIme {
   }
 }
 impl MinimalIme {
+  /// Returns the composition events of an IME message. The committed text is still reported
+  /// by `process_message`, from the `WM_CHAR` messages that follow.
+  pub(crate) fn composition_events(
+    &mut self,
+    hwnd: HWND,
+    msg_kind: u32,
+    lparam: LPARAM,
+  ) -> Vec<Ime> {
+    match msg_kind {
+      win32wm::WM_IME_STARTCOMPOSITION => vec![Ime::Enabled],
+      win32wm::WM_IME_COMPOSITION => unsafe {
+        let himc = ImmGetContext(hwnd);
+        if himc.0 == 0 {
+          return Vec::new();
+        }
+        let flags = lparam.0 as u32;
+        let mut events = Vec::new();
+        if flags & GCS_RESULTSTR != 0 {
+          if let Some(text) = composition_string(himc, GCS_RESULTSTR) {
+            events.push(Ime::Preedit(String::new(), None));
+            events.push(Ime::Commit(text));
+          }
+        }
+        if flags & GCS_COMPSTR != 0 {
+          if let Some(text) = composition_string(himc, GCS_COMPSTR) {
+            let cursor = if flags & GCS_CURSORPOS != 0 {
+              // The cursor is an index in UTF-16 code units.
+              let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, ptr::null_mut(), 0);
+              let cursor = text
+                .chars()
+                .scan(0, |units, c| {
+                  let index = *units;
+                  *units += c.len_utf16();
+                  Some((index, c.len_utf8()))
+                })
+                .take_while(|(units, _)| *units < cursor.max(0) as usize)
+                .map(|(_, bytes)| bytes)
+                .sum();
+              Some((cursor, cursor))
+            } else {
+              None
+            };
+            events.push(Ime::Preedit(text, cursor));
+          }
+        }
+        ImmReleaseContext(hwnd, himc);
+        events
+      },
+      win32wm::WM_IME_ENDCOMPOSITION => vec![Ime::Preedit(String::new(), None), Ime::Disabled],
+      _ => Vec::new(),
+    }
+  }
+
   pub(crate) fn process_message(
     &mut self,
     hwnd: HWND,
@@ -90,3 +150,14 @@ impl MinimalIme {
     None
   }
 }
+
+unsafe fn composition_string(himc: HIMC, kind: u32) -> Option<String> {
+  // The length is in bytes.
+  let len = ImmGetCompositionStringW(himc, kind, ptr::null_mut(), 0);
+  if len < 0 {
+    return None;
+  }
+  let mut buffer = vec![0u16; len as usize / 2];
+  ImmGetCompositionStringW(himc, kind, buffer.as_mut_ptr() as *mut _, len as u32);
+  String::from_utf16(&buffer).ok()
+}
@@ -11,9 +11,12 @@ use windows::Win32::{
 
 pub use self::{
   clipboard::Clipboard,
-  event_loop::{EventLoop, EventLoopProxy, EventLoopWindowTarget},
+  event_loop::{
+    EventLoop, EventLoopProxy, EventLoopWindowTarget, PlatformSpecificEventLoopAttributes,
+  },
   global_shortcut::{GlobalShortcut, ShortcutManager},
   icon::WinIcon,
+  jump_list::{apply_jump_list, clear_jump_list},
   keycode::{keycode_from_scancode, keycode_to_scancode},
   menu::{Menu, MenuItemAttributes},
   monitor::{MonitorHandle, VideoMode},
@@ -137,16 +140,23 @@ impl WindowId {
 
 #[macro_use]
 mod util;
+#[cfg(feature = "accesskit")]
+mod accessibility;
 mod clipboard;
 mod dark_mode;
+mod direct_manipulation;
 mod dpi;
 mod drop_handler;
 mod event_loop;
 mod icon;
+mod jump_list;
 mod keyboard;
+mod keyboard_grab;
 mod keyboard_layout;
 mod minimal_ime;
 mod monitor;
 mod raw_input;
+mod single_instance;
+mod vsync;
 mod window;
 mod window_state;
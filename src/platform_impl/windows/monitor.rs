@@ -173,6 +173,13 @@ impl MonitorHandle {
     HMONITOR(self.0)
   }
 
+  /// Returns the part of the monitor not covered by the taskbar and app bars.
+  pub(crate) fn work_area(&self) -> RECT {
+    get_monitor_info(self.hmonitor())
+      .map(|info| info.monitorInfo.rcWork)
+      .unwrap_or_default()
+  }
+
   #[inline]
   pub fn size(&self) -> PhysicalSize<u32> {
     let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
@@ -198,6 +205,19 @@ impl MonitorHandle {
     dpi_to_scale_factor(get_monitor_dpi(self.hmonitor()).unwrap_or(96))
   }
 
+  #[inline]
+  pub fn refresh_rate_millihertz(&self) -> Option<u32> {
+    let refresh_rate = self.current_video_mode()?.refresh_rate();
+    // 0 and 1 stand for the default refresh rate of the hardware.
+    (refresh_rate > 1).then(|| refresh_rate as u32 * 1000)
+  }
+
+  #[inline]
+  pub fn mirror_group_id(&self) -> Option<u32> {
+    // Windows merges cloned displays into a single `HMONITOR`.
+    None
+  }
+
   #[inline]
   pub fn video_modes(&self) -> impl Iterator<Item = RootVideoMode> {
     // EnumDisplaySettingsExW can return duplicate values (or some of the
@@ -206,35 +226,42 @@ impl MonitorHandle {
     let mut modes = BTreeSet::new();
     let mut i = 0;
 
-    loop {
-      unsafe {
-        let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
-        let device_name = PCWSTR::from_raw(monitor_info.szDevice.as_ptr());
-        let mut mode: DEVMODEW = mem::zeroed();
-        mode.dmSize = mem::size_of_val(&mode) as u16;
-        if !EnumDisplaySettingsExW(device_name, ENUM_DISPLAY_SETTINGS_MODE(i), &mut mode, 0)
-          .as_bool()
-        {
-          break;
-        }
-        i += 1;
-
-        const REQUIRED_FIELDS: u32 =
-          (DM_BITSPERPEL | DM_PELSWIDTH | DM_PELSHEIGHT | DM_DISPLAYFREQUENCY) as u32;
-        assert!(mode.dmFields & REQUIRED_FIELDS == REQUIRED_FIELDS);
-
-        modes.insert(RootVideoMode {
-          video_mode: VideoMode {
-            size: (mode.dmPelsWidth, mode.dmPelsHeight),
-            bit_depth: mode.dmBitsPerPel as u16,
-            refresh_rate: mode.dmDisplayFrequency as u16,
-            monitor: self.clone(),
-            native_video_mode: mode,
-          },
-        });
-      }
+    while let Some(mode) = self.enum_display_settings(ENUM_DISPLAY_SETTINGS_MODE(i)) {
+      modes.insert(mode);
+      i += 1;
     }
 
     modes.into_iter()
   }
+
+  /// Returns the video mode the display is currently using.
+  pub(crate) fn current_video_mode(&self) -> Option<RootVideoMode> {
+    self.enum_display_settings(ENUM_CURRENT_SETTINGS)
+  }
+
+  fn enum_display_settings(&self, mode_num: ENUM_DISPLAY_SETTINGS_MODE) -> Option<RootVideoMode> {
+    unsafe {
+      let monitor_info = get_monitor_info(self.hmonitor()).unwrap();
+      let device_name = PCWSTR::from_raw(monitor_info.szDevice.as_ptr());
+      let mut mode: DEVMODEW = mem::zeroed();
+      mode.dmSize = mem::size_of_val(&mode) as u16;
+      if !EnumDisplaySettingsExW(device_name, mode_num, &mut mode, 0).as_bool() {
+        return None;
+      }
+
+      const REQUIRED_FIELDS: u32 =
+        (DM_BITSPERPEL | DM_PELSWIDTH | DM_PELSHEIGHT | DM_DISPLAYFREQUENCY) as u32;
+      assert!(mode.dmFields & REQUIRED_FIELDS == REQUIRED_FIELDS);
+
+      Some(RootVideoMode {
+        video_mode: VideoMode {
+          size: (mode.dmPelsWidth, mode.dmPelsHeight),
+          bit_depth: mode.dmBitsPerPel as u16,
+          refresh_rate: mode.dmDisplayFrequency as u16,
+          monitor: self.clone(),
+          native_video_mode: mode,
+        },
+      })
+    }
+  }
 }
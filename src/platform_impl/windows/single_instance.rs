@@ -0,0 +1,118 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{path::PathBuf, slice, thread, time::Duration};
+
+use windows::{
+  core::PCWSTR,
+  Win32::{
+    Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, HWND, LPARAM, WPARAM},
+    System::{DataExchange::COPYDATASTRUCT, Threading::CreateMutexW},
+    UI::WindowsAndMessaging::{
+      AllowSetForegroundWindow, FindWindowW, GetClassNameW, GetWindowThreadProcessId,
+      SendMessageTimeoutW, SetWindowTextW, SMTO_ABORTIFHUNG, SMTO_BLOCK, WM_COPYDATA,
+    },
+  },
+};
+
+use super::util;
+
+/// Identifies the `WM_COPYDATA` messages carrying the arguments of another instance.
+pub const NEW_INSTANCE_COPYDATA_ID: usize = 0x5441_4f31;
+
+/// How long another instance waits for the single instance to title its window, or to handle
+/// the arguments.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Makes the process the single instance named by `identifier`, or forwards its arguments to
+/// the single instance when there already is one. The event target window of the single
+/// instance is titled after the mutex, so other instances can find it.
+pub fn enable(event_target_window: HWND, identifier: &str) -> bool {
+  let name = util::encode_wide(format!("tao-single-instance-{}", identifier));
+  unsafe {
+    let mutex = match CreateMutexW(std::ptr::null(), true, PCWSTR::from_raw(name.as_ptr())) {
+      Ok(mutex) => mutex,
+      Err(e) => {
+        warn!("Failed to create the single instance mutex: {}", e);
+        return true;
+      }
+    };
+    if GetLastError() != ERROR_ALREADY_EXISTS {
+      // The mutex is owned until the process exits.
+      SetWindowTextW(event_target_window, PCWSTR::from_raw(name.as_ptr()));
+      return true;
+    }
+    CloseHandle(mutex);
+
+    let mut class_name = [0u16; 256];
+    let len = GetClassNameW(event_target_window, &mut class_name) as usize;
+    let class_name = [&class_name[..len], &[0]].concat();
+    // The single instance titles its window right after creating the mutex, which may not be
+    // done yet.
+    const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+    let mut retries = TIMEOUT.as_millis() / RETRY_INTERVAL.as_millis();
+    let window = loop {
+      let window = FindWindowW(
+        PCWSTR::from_raw(class_name.as_ptr()),
+        PCWSTR::from_raw(name.as_ptr()),
+      );
+      if window.0 != 0 {
+        break window;
+      }
+      if retries == 0 {
+        warn!("Failed to find the single instance window");
+        return false;
+      }
+      retries -= 1;
+      thread::sleep(RETRY_INTERVAL);
+    };
+
+    let data = encode_new_instance();
+    let copy_data = COPYDATASTRUCT {
+      dwData: NEW_INSTANCE_COPYDATA_ID,
+      cbData: data.len() as u32,
+      lpData: data.as_ptr() as *mut _,
+    };
+    // Lets the single instance bring its window to the foreground.
+    let mut process_id = 0;
+    GetWindowThreadProcessId(window, &mut process_id);
+    AllowSetForegroundWindow(process_id);
+    // Don't hang when the single instance doesn't respond.
+    let mut result = 0;
+    if SendMessageTimeoutW(
+      window,
+      WM_COPYDATA,
+      WPARAM(event_target_window.0 as _),
+      LPARAM(&copy_data as *const _ as _),
+      SMTO_ABORTIFHUNG | SMTO_BLOCK,
+      TIMEOUT.as_millis() as u32,
+      &mut result,
+    )
+    .0 == 0
+    {
+      warn!("The single instance didn't receive the arguments in time");
+    }
+    false
+  }
+}
+
+/// Encodes the current directory and the arguments of the process as nul-separated UTF-8.
+fn encode_new_instance() -> Vec<u8> {
+  let cwd = std::env::current_dir().unwrap_or_default();
+  let mut parts = vec![cwd.to_string_lossy().into_owned()];
+  parts.extend(std::env::args_os().map(|arg| arg.to_string_lossy().into_owned()));
+  parts.join("\0").into_bytes()
+}
+
+/// Decodes the arguments and the current directory of another instance from `WM_COPYDATA`.
+pub unsafe fn decode_new_instance(copy_data: &COPYDATASTRUCT) -> Option<(Vec<String>, PathBuf)> {
+  if copy_data.dwData != NEW_INSTANCE_COPYDATA_ID || copy_data.lpData.is_null() {
+    return None;
+  }
+  let data = slice::from_raw_parts(copy_data.lpData as *const u8, copy_data.cbData as usize);
+  let data = String::from_utf8_lossy(data);
+  let mut parts = data.split('\0').map(String::from);
+  let cwd = PathBuf::from(parts.next()?);
+  Some((parts.collect(), cwd))
+}
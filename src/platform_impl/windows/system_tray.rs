@@ -4,7 +4,7 @@
 
 use super::{
   event_loop::S_U_TASKBAR_RESTART,
-  menu::{subclass_proc as menu_subclass_proc, Menu, MenuHandler},
+  menu::{forget_radio_groups, subclass_proc as menu_subclass_proc, Menu, MenuHandler},
   util, OsError,
 };
 use crate::{
@@ -13,7 +13,7 @@ use crate::{
   event::{Event, Rectangle, TrayEvent},
   event_loop::EventLoopWindowTarget,
   menu::MenuType,
-  system_tray::{Icon, SystemTray as RootSystemTray},
+  system_tray::{Icon, SystemTray as RootSystemTray, TrayStatus},
   TrayId,
 };
 use windows::{
@@ -35,6 +35,7 @@ const WM_USER_TRAYICON: u32 = 6004;
 const WM_USER_UPDATE_TRAYMENU: u32 = 6005;
 const WM_USER_UPDATE_TRAYICON: u32 = 6006;
 const WM_USER_UPDATE_TOOLTIP: u32 = 6007;
+const WM_USER_UPDATE_HIDDEN: u32 = 6008;
 
 struct TrayLoopData {
   id: TrayId,
@@ -42,6 +43,7 @@ struct TrayLoopData {
   hmenu: Option<HMENU>,
   icon: Icon,
   tooltip: Option<String>,
+  hidden: bool,
   sender: Box<dyn Fn(Event<'static, ()>)>,
 }
 
@@ -62,6 +64,7 @@ impl SystemTrayBuilder {
     window_target: &EventLoopWindowTarget<T>,
     tray_id: TrayId,
     tooltip: Option<String>,
+    _title: Option<String>,
   ) -> Result<RootSystemTray, RootOsError> {
     let hmenu: Option<HMENU> = self.tray_menu.map(|m| m.hmenu());
 
@@ -124,6 +127,7 @@ impl SystemTrayBuilder {
         hmenu,
         icon: self.icon,
         tooltip,
+        hidden: false,
         sender: Box::new(move |event| {
           if let Ok(e) = event.map_nonuser_event() {
             event_loop_runner.send_event(e)
@@ -147,6 +151,7 @@ impl SystemTrayBuilder {
         }),
         MenuType::ContextMenu,
         None,
+        Some(tray_id),
       );
       SetWindowSubclass(
         hwnd,
@@ -214,6 +219,25 @@ impl SystemTray {
     }
   }
 
+  pub fn set_title(&mut self, _title: &str) {}
+
+  pub fn set_status(&mut self, status: TrayStatus) {
+    let hidden = status == TrayStatus::Passive;
+    unsafe {
+      if !set_tray_icon_hidden(self.hwnd, hidden) {
+        debug!("Error setting tray icon status");
+      }
+
+      // send the new state to the subclass proc so it survives a taskbar restart
+      SendMessageW(
+        self.hwnd,
+        WM_USER_UPDATE_HIDDEN,
+        WPARAM(hidden as _),
+        LPARAM(0),
+      );
+    }
+  }
+
   pub fn set_menu(&mut self, tray_menu: &Menu) {
     unsafe {
       // send the new menu to the subclass proc where we will update there
@@ -259,11 +283,19 @@ unsafe extern "system" fn tray_subclass_proc(
   let mut subclass_input = &mut *(subclass_input_ptr);
 
   if msg == WM_DESTROY {
+    if let Some(hmenu) = subclass_input.hmenu {
+      forget_radio_groups(hmenu);
+    }
     Box::from_raw(subclass_input_ptr);
   }
 
   if msg == WM_USER_UPDATE_TRAYMENU {
-    subclass_input.hmenu = Some(HMENU(wparam.0 as _));
+    let hmenu = HMENU(wparam.0 as _);
+    if let Some(previous) = subclass_input.hmenu.replace(hmenu) {
+      if previous != hmenu {
+        forget_radio_groups(previous);
+      }
+    }
   }
 
   if msg == WM_USER_UPDATE_TRAYICON {
@@ -276,12 +308,19 @@ unsafe extern "system" fn tray_subclass_proc(
     subclass_input.tooltip = Some((*tooltip).clone());
   }
 
+  if msg == WM_USER_UPDATE_HIDDEN {
+    subclass_input.hidden = wparam.0 != 0;
+  }
+
   if msg == *S_U_TASKBAR_RESTART {
     register_tray_icon(
       subclass_input.hwnd,
       subclass_input.icon.inner.as_raw_handle(),
       subclass_input.tooltip.clone(),
     );
+    if subclass_input.hidden {
+      set_tray_icon_hidden(subclass_input.hwnd, true);
+    }
   }
 
   if msg == WM_USER_TRAYICON
@@ -385,3 +424,16 @@ unsafe fn register_tray_icon(hwnd: HWND, hicon: HICON, tooltip: Option<String>)
 
   Shell_NotifyIconW(NIM_ADD, &mut nid as _).as_bool()
 }
+
+unsafe fn set_tray_icon_hidden(hwnd: HWND, hidden: bool) -> bool {
+  let mut nid = NOTIFYICONDATAW {
+    uFlags: NIF_STATE,
+    hWnd: hwnd,
+    uID: TRAYICON_UID,
+    dwState: if hidden { NIS_HIDDEN } else { 0 },
+    dwStateMask: NIS_HIDDEN,
+    ..std::mem::zeroed()
+  };
+
+  Shell_NotifyIconW(NIM_MODIFY, &mut nid as _).as_bool()
+}
@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::{
+  ffi::c_void,
   io,
   iter::once,
   mem,
@@ -10,17 +11,24 @@ use std::{
   os::windows::prelude::OsStrExt,
   ptr, slice,
   sync::atomic::{AtomicBool, Ordering},
+  time::{Duration, Instant},
 };
 
-use crate::{dpi::PhysicalSize, window::CursorIcon};
+use crate::{
+  dpi::PhysicalSize,
+  window::{CursorIcon, RGBA},
+};
 
 use windows::{
   core::{HRESULT, PCSTR, PCWSTR},
   Win32::{
     Foundation::{BOOL, FARPROC, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
     Globalization::lstrlenW,
-    Graphics::Gdi::{ClientToScreen, InvalidateRgn, HMONITOR, HRGN},
-    System::LibraryLoader::*,
+    Graphics::{
+      Dwm::DwmGetColorizationColor,
+      Gdi::{ClientToScreen, InvalidateRgn, HMONITOR, HRGN},
+    },
+    System::{LibraryLoader::*, SystemInformation::GetTickCount},
     UI::{
       HiDpi::*,
       Input::KeyboardAndMouse::*,
@@ -30,6 +38,20 @@ use windows::{
   },
 };
 
+/// Converts the time of the message being processed, in milliseconds since the system started,
+/// to an `Instant`.
+pub fn message_timestamp() -> Instant {
+  let now = Instant::now();
+  let age = unsafe { GetTickCount().wrapping_sub(GetMessageTime() as u32) };
+  // Sent messages don't have a time of their own, but that of the last posted message.
+  if age > 60_000 {
+    return now;
+  }
+  now
+    .checked_sub(Duration::from_millis(u64::from(age)))
+    .unwrap_or(now)
+}
+
 pub fn has_flag<T>(bitset: T, flag: T) -> bool
 where
   T: Copy + PartialEq + BitAnd<T, Output = T>,
@@ -210,6 +232,16 @@ pub fn get_desktop_rect() -> RECT {
   }
 }
 
+/// Returns the DWM colorization color, which follows the accent color of the Windows settings.
+pub fn accent_color() -> Option<RGBA> {
+  let mut color = 0;
+  let mut opaque_blend = BOOL::default();
+  unsafe { DwmGetColorizationColor(&mut color, &mut opaque_blend) }.ok()?;
+  // 0xAARRGGBB
+  let [b, g, r, a] = color.to_le_bytes();
+  Some((r, g, b, a))
+}
+
 pub fn is_focused(window: HWND) -> bool {
   window == unsafe { GetActiveWindow() }
 }
@@ -230,7 +262,7 @@ pub fn is_maximized(window: HWND) -> bool {
 }
 
 impl CursorIcon {
-  pub(crate) fn to_windows_cursor(self) -> PCWSTR {
+  pub(crate) fn to_windows_cursor(&self) -> PCWSTR {
     match self {
       CursorIcon::Arrow | CursorIcon::Default => IDC_ARROW,
       CursorIcon::Hand => IDC_HAND,
@@ -407,3 +439,35 @@ pub unsafe extern "system" fn call_default_window_proc(
 ) -> LRESULT {
   DefWindowProcW(hwnd, msg, wparam, lparam)
 }
+
+/// Calls the undocumented `SetWindowCompositionAttribute` with `data` as the value of `attrib`,
+/// returning whether it succeeded.
+pub unsafe fn set_window_composition_attribute<T>(hwnd: HWND, attrib: u32, data: &mut T) -> bool {
+  type SetWindowCompositionAttribute =
+    unsafe extern "system" fn(HWND, *mut WINDOWCOMPOSITIONATTRIBDATA) -> BOOL;
+
+  #[allow(non_snake_case)]
+  #[repr(C)]
+  struct WINDOWCOMPOSITIONATTRIBDATA {
+    Attrib: u32,
+    pvData: *mut c_void,
+    cbData: usize,
+  }
+
+  lazy_static! {
+    static ref SET_WINDOW_COMPOSITION_ATTRIBUTE: Option<SetWindowCompositionAttribute> =
+      get_function!("user32.dll", SetWindowCompositionAttribute);
+  }
+
+  match *SET_WINDOW_COMPOSITION_ATTRIBUTE {
+    Some(set_window_composition_attribute) => {
+      let mut data = WINDOWCOMPOSITIONATTRIBDATA {
+        Attrib: attrib,
+        pvData: data as *mut T as _,
+        cbData: mem::size_of::<T>(),
+      };
+      set_window_composition_attribute(hwnd, &mut data).as_bool()
+    }
+    None => false,
+  }
+}
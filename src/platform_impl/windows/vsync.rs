@@ -0,0 +1,65 @@
+// Copyright 2014-2021 The winit contributors
+// Copyright 2021-2022 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+
+//! Redraws paced by the DWM, which composes one frame per vertical blank. A single thread waits
+//! for the frames and redraws every window that asked for it in the meantime.
+
+use std::{ptr, sync::Once, thread, time::Duration};
+
+use parking_lot::{Condvar, Mutex};
+use windows::Win32::{
+  Foundation::HWND,
+  Graphics::{
+    Dwm::DwmFlush,
+    Gdi::{RedrawWindow, HRGN, RDW_INTERNALPAINT},
+  },
+};
+
+#[derive(Default)]
+struct PendingRedraws {
+  windows: Mutex<Vec<isize>>,
+  condvar: Condvar,
+}
+
+lazy_static! {
+  static ref PENDING_REDRAWS: PendingRedraws = Default::default();
+}
+
+static START_THREAD: Once = Once::new();
+
+/// Redraws `hwnd` once the DWM composed its next frame.
+pub fn request_redraw(hwnd: HWND) {
+  START_THREAD.call_once(|| {
+    thread::spawn(vsync_thread);
+  });
+
+  let mut windows = PENDING_REDRAWS.windows.lock();
+  if !windows.contains(&hwnd.0) {
+    windows.push(hwnd.0);
+  }
+  PENDING_REDRAWS.condvar.notify_one();
+}
+
+fn vsync_thread() {
+  loop {
+    {
+      let mut windows = PENDING_REDRAWS.windows.lock();
+      while windows.is_empty() {
+        PENDING_REDRAWS.condvar.wait(&mut windows);
+      }
+    }
+
+    // Fails while composition is disabled, which only happens on Windows 7.
+    if unsafe { DwmFlush() }.is_err() {
+      thread::sleep(Duration::from_millis(16));
+    }
+
+    let windows = std::mem::take(&mut *PENDING_REDRAWS.windows.lock());
+    for hwnd in windows {
+      unsafe {
+        RedrawWindow(HWND(hwnd), ptr::null(), HRGN::default(), RDW_INTERNALPAINT);
+      }
+    }
+  }
+}
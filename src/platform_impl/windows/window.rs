@@ -23,12 +23,19 @@ use windows::{
   core::PCWSTR,
   Win32::{
     Foundation::{self as win32f, HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
+    Globalization::HIMC,
     Graphics::{
-      Dwm::{DwmEnableBlurBehindWindow, DWM_BB_BLURREGION, DWM_BB_ENABLE, DWM_BLURBEHIND},
+      Dwm::{
+        DwmEnableBlurBehindWindow, DwmExtendFrameIntoClientArea, DwmSetWindowAttribute,
+        DWMWA_BORDER_COLOR, DWMWA_COLOR_DEFAULT, DWMWA_WINDOW_CORNER_PREFERENCE, DWMWCP_DONOTROUND,
+        DWMWCP_ROUND, DWMWINDOWATTRIBUTE, DWM_BB_BLURREGION, DWM_BB_ENABLE, DWM_BLURBEHIND,
+        DWM_WINDOW_CORNER_PREFERENCE,
+      },
       Gdi::*,
     },
     System::{Com::*, LibraryLoader::*, Ole::*},
     UI::{
+      Controls::MARGINS,
       Input::{Ime::*, KeyboardAndMouse::*, Touch::*},
       Shell::*,
       WindowsAndMessaging::{self as win32wm, *},
@@ -39,26 +46,37 @@ use windows::{
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize, Position, Size},
   error::{ExternalError, NotSupportedError, OsError as RootOsError},
+  event::Rectangle,
+  event_loop::DeviceEventFilter,
   icon::Icon,
   menu::MenuType,
   monitor::MonitorHandle as RootMonitorHandle,
+  platform::windows::ThumbButton,
   platform_impl::platform::{
     dark_mode::try_theme,
+    direct_manipulation::DirectManipulation,
     dpi::{dpi_to_scale_factor, hwnd_dpi},
-    drop_handler::FileDropHandler,
+    drop_handler::{self, FileDropHandler},
     event_loop::{self, EventLoopWindowTarget, DESTROY_MSG_ID},
-    icon::{self, IconType},
+    icon::{self, IconType, WinCursor},
     menu, monitor, util,
     window_state::{CursorFlags, SavedWindow, WindowFlags, WindowState},
     OsError, Parent, PlatformSpecificWindowBuilderAttributes, WindowId,
   },
   window::{
-    CursorIcon, Fullscreen, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
-    BORDERLESS_RESIZE_INSET,
+    BlurEffect, CursorGrabMode, CursorIcon, DragData, Fullscreen, ProgressBarState, ProgressState,
+    ResizeDirection, Theme, UserAttentionType, WindowAttributes, WindowId as RootWindowId,
+    WindowLevel, BORDERLESS_RESIZE_INSET, RGBA,
   },
 };
 
-use super::keyboard::{KeyEventBuilder, KEY_EVENT_BUILDERS};
+use super::{
+  keyboard::{KeyEventBuilder, KEY_EVENT_BUILDERS},
+  keyboard_grab, vsync,
+};
+
+#[cfg(feature = "accesskit")]
+use super::accessibility;
 
 struct HMenuWrapper(HMENU);
 unsafe impl Send for HMenuWrapper {}
@@ -77,6 +95,12 @@ pub struct Window {
 
   // The menu associated with the window
   menu: Option<HMenuWrapper>,
+
+  // The hook called with the messages of the window before tao handles them.
+  subclass_hook: event_loop::SubclassHook,
+
+  #[cfg(feature = "accesskit")]
+  accessibility: accessibility::Accessibility,
 }
 
 impl Window {
@@ -126,12 +150,28 @@ impl Window {
           None
         };
 
+        // Unavailable before Windows 8, where touchpads only send mouse wheel messages.
+        let direct_manipulation_runner = event_loop.runner_shared.clone();
+        let direct_manipulation = DirectManipulation::new(
+          win.window.0,
+          Box::new(move |event| {
+            if let Ok(e) = event.map_nonuser_event() {
+              direct_manipulation_runner.send_event(e)
+            }
+          }),
+        )
+        .ok();
+
         let subclass_input = event_loop::SubclassInput {
           window_state: win.window_state.clone(),
           event_loop_runner: event_loop.runner_shared.clone(),
           _file_drop_handler: file_drop_handler,
+          direct_manipulation,
           subclass_removed: Cell::new(false),
           recurse_depth: Cell::new(0),
+          subclass_hook: win.subclass_hook.clone(),
+          #[cfg(feature = "accesskit")]
+          accessibility: win.accessibility.clone(),
         };
 
         event_loop::subclass_window(win.window.0, subclass_input);
@@ -183,6 +223,10 @@ impl Window {
 
   #[inline]
   pub fn request_redraw(&self) {
+    if self.window_state.lock().vsync_redraws {
+      vsync::request_redraw(self.window.0);
+      return;
+    }
     unsafe {
       RedrawWindow(
         self.window.0,
@@ -193,6 +237,11 @@ impl Window {
     }
   }
 
+  #[inline]
+  pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+    self.window_state.lock().vsync_redraws = vsync_redraws;
+  }
+
   #[inline]
   pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
     util::get_window_rect(self.window.0)
@@ -281,6 +330,13 @@ impl Window {
     util::set_inner_size_physical(self.window.0, width, height, is_decorated);
   }
 
+  #[inline]
+  pub fn request_inner_size(&self, size: Size) -> Option<PhysicalSize<u32>> {
+    // `WM_SIZE` is what reports the new size, so there is always a `Resized` event.
+    self.set_inner_size(size);
+    None
+  }
+
   #[inline]
   pub fn set_min_inner_size(&self, size: Option<Size>) {
     self.window_state.lock().min_size = size;
@@ -289,6 +345,11 @@ impl Window {
     self.set_inner_size(size.into());
   }
 
+  #[inline]
+  pub fn set_resize_increments(&self, increments: Option<Size>) {
+    self.window_state.lock().resize_increments = increments;
+  }
+
   #[inline]
   pub fn set_max_inner_size(&self, size: Option<Size>) {
     self.window_state.lock().max_size = size;
@@ -309,6 +370,42 @@ impl Window {
     });
   }
 
+  #[inline]
+  pub fn set_minimizable(&self, minimizable: bool) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+
+    self.thread_executor.execute_in_thread(move || {
+      WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+        f.set(WindowFlags::MINIMIZABLE, minimizable)
+      });
+    });
+  }
+
+  #[inline]
+  pub fn set_maximizable(&self, maximizable: bool) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+
+    self.thread_executor.execute_in_thread(move || {
+      WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+        f.set(WindowFlags::MAXIMIZABLE, maximizable)
+      });
+    });
+  }
+
+  #[inline]
+  pub fn set_closable(&self, closable: bool) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+
+    self.thread_executor.execute_in_thread(move || {
+      WindowState::set_window_flags(window_state.lock(), window.0, |f| {
+        f.set(WindowFlags::CLOSABLE, closable)
+      });
+    });
+  }
+
   /// Returns the `hwnd` of this window.
   #[inline]
   pub fn hwnd(&self) -> HWND {
@@ -335,16 +432,30 @@ impl Window {
 
   #[inline]
   pub fn set_cursor_icon(&self, cursor: CursorIcon) {
-    self.window_state.lock().mouse.cursor = cursor;
+    let custom_cursor = match &cursor {
+      CursorIcon::Custom(cursor) => WinCursor::for_image(&cursor.image)
+        .map_err(|e| warn!("Failed to create the custom cursor: {}", e))
+        .ok(),
+      _ => None,
+    };
+    let (handle, mut old_custom_cursor) = {
+      let mut window_state = self.window_state.lock();
+      window_state.mouse.cursor = cursor;
+      let old_custom_cursor = mem::replace(&mut window_state.mouse.custom_cursor, custom_cursor);
+      (window_state.mouse.cursor_handle(), old_custom_cursor)
+    };
     self.thread_executor.execute_in_thread(move || unsafe {
-      let cursor =
-        LoadCursorW(HINSTANCE::default(), cursor.to_windows_cursor()).unwrap_or_default();
-      SetCursor(cursor);
+      if let Some(handle) = handle {
+        SetCursor(handle);
+      }
+      // The previous cursor may still be shown until now, so it's only destroyed after being
+      // replaced.
+      old_custom_cursor.take();
     });
   }
 
   #[inline]
-  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
     let window = self.window.clone();
     let window_state = Arc::clone(&self.window_state);
     let (tx, rx) = channel::unbounded();
@@ -353,7 +464,10 @@ impl Window {
       let result = window_state
         .lock()
         .mouse
-        .set_cursor_flags(window.0, |f| f.set(CursorFlags::GRABBED, grab))
+        .set_cursor_flags(window.0, |f| {
+          f.set(CursorFlags::GRABBED, mode == CursorGrabMode::Confined);
+          f.set(CursorFlags::LOCKED, mode == CursorGrabMode::Locked);
+        })
         .map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e))));
       let _ = tx.send(result);
     });
@@ -403,8 +517,45 @@ impl Window {
     Ok(())
   }
 
+  #[inline]
+  pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+    unsafe {
+      // `DoDragDrop` requires OLE, which is only initialized for windows accepting drops.
+      let initialized = OleInitialize(ptr::null_mut()).is_ok();
+      let result = drop_handler::start_drag(&data);
+      if initialized {
+        OleUninitialize();
+      }
+      result.map_err(|e| ExternalError::Os(os_error!(OsError::IoError(e.into()))))
+    }
+  }
+
   #[inline]
   pub fn drag_window(&self) -> Result<(), ExternalError> {
+    self.handle_os_dragging(HTCAPTION);
+
+    Ok(())
+  }
+
+  #[inline]
+  pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+    self.handle_os_dragging(match direction {
+      ResizeDirection::East => HTRIGHT,
+      ResizeDirection::North => HTTOP,
+      ResizeDirection::NorthEast => HTTOPRIGHT,
+      ResizeDirection::NorthWest => HTTOPLEFT,
+      ResizeDirection::South => HTBOTTOM,
+      ResizeDirection::SouthEast => HTBOTTOMRIGHT,
+      ResizeDirection::SouthWest => HTBOTTOMLEFT,
+      ResizeDirection::West => HTLEFT,
+    });
+
+    Ok(())
+  }
+
+  /// Makes the window act as if the left mouse button was pressed on the non-client `hit_test`
+  /// area at the cursor position.
+  fn handle_os_dragging(&self, hit_test: u32) {
     let mut pos = POINT::default();
     unsafe {
       GetCursorPos(&mut pos);
@@ -412,12 +563,10 @@ impl Window {
       PostMessageW(
         self.window.0,
         WM_NCLBUTTONDOWN,
-        WPARAM(HTCAPTION as _),
+        WPARAM(hit_test as _),
         util::MAKELPARAM(pos.x as i16, pos.y as i16),
       );
     }
-
-    Ok(())
   }
 
   #[inline]
@@ -433,6 +582,30 @@ impl Window {
     Ok(())
   }
 
+  #[inline]
+  pub fn set_cursor_hittest_region(
+    &self,
+    regions: Option<&[Rectangle]>,
+  ) -> Result<(), ExternalError> {
+    let regions = regions.map(<[Rectangle]>::to_vec);
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || unsafe {
+      let mut state = window_state.lock();
+      let passthrough = regions.is_some();
+      state.cursor_hittest_regions = regions.clone();
+      // Otherwise the cursor is let through until it's back over one of the new regions.
+      if !passthrough {
+        KillTimer(window.0, event_loop::CURSOR_HITTEST_TIMER_ID);
+        WindowState::set_window_flags(state, window.0, |f| {
+          f.remove(WindowFlags::MARKER_CURSOR_PASSTHROUGH)
+        });
+      }
+    });
+
+    Ok(())
+  }
+
   #[inline]
   pub fn id(&self) -> WindowId {
     WindowId(self.window.0 .0)
@@ -450,6 +623,11 @@ impl Window {
     });
   }
 
+  #[inline]
+  pub fn set_minimize_to_tray(&self, minimize_to_tray: bool) {
+    self.window_state.lock().minimize_to_tray = minimize_to_tray;
+  }
+
   #[inline]
   pub fn set_maximized(&self, maximized: bool) {
     let window = self.window.clone();
@@ -468,6 +646,55 @@ impl Window {
     window_state.window_flags.contains(WindowFlags::MAXIMIZED)
   }
 
+  /// Returns the outer position and inner size the window has when it's neither maximized nor
+  /// fullscreen.
+  pub fn restored_geometry(&self) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+    let (saved_placement, is_decorated) = {
+      let window_state = self.window_state.lock();
+      (
+        window_state
+          .saved_window
+          .as_ref()
+          .map(|saved| saved.placement),
+        window_state.window_flags.contains(WindowFlags::DECORATIONS),
+      )
+    };
+    // While fullscreen, the placement saved on entering it still has the restored geometry.
+    let placement = saved_placement.unwrap_or_else(|| {
+      let mut placement = WINDOWPLACEMENT {
+        length: mem::size_of::<WINDOWPLACEMENT>() as u32,
+        ..Default::default()
+      };
+      unsafe { GetWindowPlacement(self.window.0, &mut placement) };
+      placement
+    });
+    let mut rect = placement.rcNormalPosition;
+
+    // `rcNormalPosition` is in workspace coordinates unless the window is a tool window, so
+    // offset it by the space the taskbar takes on the left or top of its monitor.
+    let style_ex = unsafe { GetWindowLongW(self.window.0, GWL_EXSTYLE) } as u32;
+    if style_ex & WS_EX_TOOLWINDOW.0 == 0 {
+      let hmonitor = unsafe { MonitorFromRect(&rect, MONITOR_DEFAULTTONEAREST) };
+      if let Ok(info) = monitor::get_monitor_info(hmonitor) {
+        let offset_x = info.monitorInfo.rcWork.left - info.monitorInfo.rcMonitor.left;
+        let offset_y = info.monitorInfo.rcWork.top - info.monitorInfo.rcMonitor.top;
+        rect.left += offset_x;
+        rect.right += offset_x;
+        rect.top += offset_y;
+        rect.bottom += offset_y;
+      }
+    }
+
+    let frame =
+      util::adjust_window_rect(self.window.0, RECT::default(), is_decorated).unwrap_or_default();
+    let width = (rect.right - rect.left) - (frame.right - frame.left);
+    let height = (rect.bottom - rect.top) - (frame.bottom - frame.top);
+    (
+      PhysicalPosition::new(rect.left, rect.top),
+      PhysicalSize::new(width.max(0) as u32, height.max(0) as u32),
+    )
+  }
+
   #[inline]
   pub fn is_minimized(&self) -> bool {
     let window_state = self.window_state.lock();
@@ -480,6 +707,24 @@ impl Window {
     window_state.window_flags.contains(WindowFlags::RESIZABLE)
   }
 
+  #[inline]
+  pub fn is_minimizable(&self) -> bool {
+    let window_state = self.window_state.lock();
+    window_state.window_flags.contains(WindowFlags::MINIMIZABLE)
+  }
+
+  #[inline]
+  pub fn is_maximizable(&self) -> bool {
+    let window_state = self.window_state.lock();
+    window_state.window_flags.contains(WindowFlags::MAXIMIZABLE)
+  }
+
+  #[inline]
+  pub fn is_closable(&self) -> bool {
+    let window_state = self.window_state.lock();
+    window_state.window_flags.contains(WindowFlags::CLOSABLE)
+  }
+
   #[inline]
   pub fn is_decorated(&self) -> bool {
     let window_state = self.window_state.lock();
@@ -497,6 +742,17 @@ impl Window {
     window_state.fullscreen.clone()
   }
 
+  #[inline]
+  pub fn current_fullscreen(&self) -> Option<Fullscreen> {
+    match self.fullscreen() {
+      Some(Fullscreen::Exclusive(video_mode)) => {
+        let current = video_mode.video_mode.monitor.current_video_mode();
+        Some(Fullscreen::Exclusive(current.unwrap_or(video_mode)))
+      }
+      fullscreen => fullscreen,
+    }
+  }
+
   #[inline]
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
     let window = self.window.clone();
@@ -658,29 +914,122 @@ impl Window {
   }
 
   #[inline]
-  pub fn set_always_on_bottom(&self, always_on_bottom: bool) {
+  pub fn set_shadow(&self, shadow: bool) {
     let window = self.window.clone();
     let window_state = Arc::clone(&self.window_state);
 
     self.thread_executor.execute_in_thread(move || {
       WindowState::set_window_flags(window_state.lock(), window.0, |f| {
-        f.set(WindowFlags::ALWAYS_ON_BOTTOM, always_on_bottom)
+        f.set(WindowFlags::NO_SHADOW, !shadow)
       });
     });
   }
 
   #[inline]
-  pub fn set_always_on_top(&self, always_on_top: bool) {
+  pub fn set_corner_radius(&self, radius: Option<f64>) {
+    let window = self.window.clone();
+    let preference = match radius {
+      Some(radius) if radius > 0.0 => DWMWCP_ROUND,
+      _ => DWMWCP_DONOTROUND,
+    };
+
+    self.thread_executor.execute_in_thread(move || unsafe {
+      let _ = DwmSetWindowAttribute(
+        window.0,
+        DWMWA_WINDOW_CORNER_PREFERENCE,
+        &preference as *const _ as _,
+        mem::size_of::<DWM_WINDOW_CORNER_PREFERENCE>() as _,
+      );
+    });
+  }
+
+  #[inline]
+  pub fn set_drag_regions(&self, regions: Vec<Rectangle>, exclusions: Vec<Rectangle>) {
+    let mut window_state = self.window_state.lock();
+    window_state.drag_regions = regions;
+    window_state.drag_exclusions = exclusions;
+  }
+
+  #[inline]
+  pub fn set_device_event_filter(&self, filter: DeviceEventFilter) {
+    self.window_state.lock().device_event_filter = Some(filter);
+    unsafe {
+      PostMessageW(
+        self.window.0,
+        *event_loop::DEVICE_EVENT_FILTER_CHANGED_MSG_ID,
+        WPARAM(0),
+        LPARAM(0),
+      );
+    }
+  }
+
+  #[inline]
+  pub fn set_drop_highlight(&self, highlight: bool) {
+    let window = self.window.clone();
+    self.thread_executor.execute_in_thread(move || unsafe {
+      set_drop_highlight(window.0, highlight);
+    });
+  }
+
+  #[inline]
+  pub fn set_background_color(&self, color: Option<RGBA>) {
+    self.window_state.lock().background_color = color;
+    unsafe {
+      InvalidateRect(self.window.0, ptr::null(), true);
+    }
+  }
+
+  #[inline]
+  pub fn set_keyboard_grab(&self, grab: bool) {
+    let window = self.window.clone();
+    self.thread_executor.execute_in_thread(move || unsafe {
+      keyboard_grab::set_keyboard_grab(window.0, grab);
+    });
+  }
+
+  #[inline]
+  pub fn set_blur(&self, effect: Option<BlurEffect>) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || unsafe {
+      set_blur(window.0, &window_state, effect);
+    });
+  }
+
+  #[inline]
+  pub fn set_opacity(&self, opacity: f64) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || unsafe {
+      set_opacity(window.0, &window_state, opacity);
+    });
+  }
+
+  #[inline]
+  pub fn set_content_protection(&self, protected: bool) {
+    let window = self.window.clone();
+    self.thread_executor.execute_in_thread(move || unsafe {
+      set_content_protection(window.0, protected);
+    });
+  }
+
+  #[inline]
+  pub fn set_window_level(&self, window_level: WindowLevel) {
     let window = self.window.clone();
     let window_state = Arc::clone(&self.window_state);
 
     self.thread_executor.execute_in_thread(move || {
       WindowState::set_window_flags(window_state.lock(), window.0, |f| {
-        f.set(WindowFlags::ALWAYS_ON_TOP, always_on_top)
+        set_window_level_flags(f, window_level)
       });
     });
   }
 
+  #[inline]
+  pub fn set_visible_on_all_workspaces(&self, _visible: bool) {
+    warn!("`Window::set_visible_on_all_workspaces` is ignored on Windows")
+  }
+
   #[inline]
   pub fn current_monitor(&self) -> Option<RootMonitorHandle> {
     Some(RootMonitorHandle {
@@ -712,32 +1061,83 @@ impl Window {
     self.window_state.lock().taskbar_icon = taskbar_icon;
   }
 
-  pub(crate) fn set_ime_position_physical(&self, x: i32, y: i32) {
-    if unsafe { GetSystemMetrics(SM_IMMENABLED) } != 0 {
-      let composition_form = COMPOSITIONFORM {
-        dwStyle: CFS_POINT,
-        ptCurrentPos: POINT { x, y },
-        rcArea: RECT::default(),
-      };
-      unsafe {
-        let himc = ImmGetContext(self.window.0);
-        ImmSetCompositionWindow(himc, &composition_form);
-        ImmReleaseContext(self.window.0, himc);
-      }
+  #[inline]
+  pub fn set_ime_cursor_area(&self, position: Position, size: Size) {
+    if unsafe { GetSystemMetrics(SM_IMMENABLED) } == 0 {
+      return;
+    }
+
+    let scale_factor = self.scale_factor();
+    let (x, y) = position.to_physical::<i32>(scale_factor).into();
+    let (width, height): (i32, i32) = size.to_physical::<i32>(scale_factor).into();
+    let area = RECT {
+      left: x,
+      top: y,
+      right: x + width,
+      bottom: y + height,
+    };
+
+    let composition_form = COMPOSITIONFORM {
+      dwStyle: CFS_POINT,
+      ptCurrentPos: POINT { x, y },
+      rcArea: RECT::default(),
+    };
+    // Keeps the candidate list out of the cursor area.
+    let candidate_form = CANDIDATEFORM {
+      dwIndex: 0,
+      dwStyle: CFS_EXCLUDE,
+      ptCurrentPos: POINT { x, y },
+      rcArea: area,
+    };
+    unsafe {
+      let himc = ImmGetContext(self.window.0);
+      ImmSetCompositionWindow(himc, &composition_form);
+      ImmSetCandidateWindow(himc, &candidate_form);
+      ImmReleaseContext(self.window.0, himc);
+    }
+  }
+
+  #[inline]
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    // Without an input context, the keys go straight to the window.
+    let flags = if allowed { IACE_DEFAULT } else { 0 };
+    unsafe {
+      ImmAssociateContextEx(self.window.0, HIMC::default(), flags);
     }
   }
 
   #[inline]
-  pub fn set_ime_position(&self, spot: Position) {
-    let (x, y) = spot.to_physical::<i32>(self.scale_factor()).into();
-    self.set_ime_position_physical(x, y);
+  pub fn set_progress_bar(&self, progress: ProgressBarState) {
+    let window = self.window.clone();
+    self.thread_executor.execute_in_thread(move || unsafe {
+      set_taskbar_progress(window.0, &progress);
+    });
+  }
+
+  #[inline]
+  pub fn set_thumbbar_buttons(&self, buttons: Vec<ThumbButton>) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || unsafe {
+      window_state.lock().thumbbar_buttons = buttons.clone();
+      set_thumbbar_buttons(window.0, &window_state);
+    });
+  }
+
+  #[inline]
+  pub fn set_badge_count(&self, count: Option<u64>) {
+    let window = self.window.clone();
+    self.thread_executor.execute_in_thread(move || unsafe {
+      set_taskbar_badge(window.0, count);
+    });
   }
 
   #[inline]
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
     let window = self.window.clone();
+    // The request has no effect on the active window, but it can still be cancelled.
     let active_window_handle = unsafe { GetActiveWindow() };
-    if window.0 == active_window_handle {
+    if request_type.is_some() && window.0 == active_window_handle {
       return;
     }
 
@@ -765,6 +1165,18 @@ impl Window {
     self.window_state.lock().current_theme
   }
 
+  #[inline]
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    let window = self.window.clone();
+    let window_state = Arc::clone(&self.window_state);
+    self.thread_executor.execute_in_thread(move || {
+      let current_theme = try_theme(window.0, theme);
+      let mut window_state = window_state.lock();
+      window_state.preferred_theme = theme;
+      window_state.current_theme = current_theme;
+    });
+  }
+
   #[inline]
   pub fn hide_menu(&self) {
     unsafe {
@@ -821,6 +1233,25 @@ impl Window {
     self.window_state.lock().skip_taskbar = skip;
     unsafe { set_skip_taskbar(self.hwnd(), skip) };
   }
+
+  #[cfg(feature = "accesskit")]
+  pub fn set_accessibility_source(&self, source: accessibility::AccessibilitySource) {
+    let is_focused = self.window_state.lock().is_focused;
+    accessibility::set_source(&self.accessibility, self.window.0, is_focused, source);
+  }
+
+  #[cfg(feature = "accesskit")]
+  pub fn update_accessibility<F>(&self, update: F)
+  where
+    F: FnOnce() -> crate::accesskit::TreeUpdate,
+  {
+    accessibility::update(&self.accessibility, update);
+  }
+
+  #[inline]
+  pub(crate) fn set_subclass_hook(&self, hook: Option<event_loop::SubclassHookFn>) {
+    self.subclass_hook.lock().set(hook);
+  }
 }
 
 impl Drop for Window {
@@ -847,6 +1278,18 @@ pub struct WindowWrapper(HWND);
 unsafe impl Sync for WindowWrapper {}
 unsafe impl Send for WindowWrapper {}
 
+/// Every level above the normal one is a topmost window, as Windows has no other band.
+fn set_window_level_flags(flags: &mut WindowFlags, window_level: WindowLevel) {
+  flags.set(
+    WindowFlags::ALWAYS_ON_BOTTOM,
+    window_level == WindowLevel::Desktop,
+  );
+  flags.set(
+    WindowFlags::ALWAYS_ON_TOP,
+    !matches!(window_level, WindowLevel::Desktop | WindowLevel::Normal),
+  );
+}
+
 unsafe fn init<T: 'static>(
   attributes: WindowAttributes,
   pl_attribs: PlatformSpecificWindowBuilderAttributes,
@@ -857,8 +1300,7 @@ unsafe fn init<T: 'static>(
 
   let mut window_flags = WindowFlags::empty();
   window_flags.set(WindowFlags::DECORATIONS, attributes.decorations);
-  window_flags.set(WindowFlags::ALWAYS_ON_BOTTOM, attributes.always_on_bottom);
-  window_flags.set(WindowFlags::ALWAYS_ON_TOP, attributes.always_on_top);
+  set_window_level_flags(&mut window_flags, attributes.window_level);
   window_flags.set(
     WindowFlags::NO_BACK_BUFFER,
     pl_attribs.no_redirection_bitmap,
@@ -866,8 +1308,29 @@ unsafe fn init<T: 'static>(
   window_flags.set(WindowFlags::TRANSPARENT, attributes.transparent);
   // WindowFlags::VISIBLE and MAXIMIZED are set down below after the window has been configured.
   window_flags.set(WindowFlags::RESIZABLE, attributes.resizable);
+  window_flags.set(WindowFlags::MINIMIZABLE, attributes.minimizable);
+  window_flags.set(WindowFlags::MAXIMIZABLE, attributes.maximizable);
+  window_flags.set(WindowFlags::CLOSABLE, attributes.closable);
+  window_flags.set(WindowFlags::NO_SHADOW, !attributes.shadow);
+  window_flags.set(
+    WindowFlags::NO_RESIZE_BORDERS,
+    !attributes.undecorated_resize_borders,
+  );
 
-  let parent = match pl_attribs.parent {
+  // Tool windows are owned by their parent.
+  let parent = match (attributes.tool_window, attributes.parent, attributes.owner) {
+    (Some(parent), ..) => {
+      window_flags.set(WindowFlags::TOOL_WINDOW, true);
+      Parent::OwnedBy(HWND(parent.0 .0))
+    }
+    (None, Some(parent), _) => Parent::ChildOf(HWND(parent.0 .0)),
+    (None, None, Some(owner)) => {
+      window_flags.set(WindowFlags::MODAL, attributes.modal);
+      Parent::OwnedBy(HWND(owner.0 .0))
+    }
+    (None, None, None) => pl_attribs.parent,
+  };
+  let parent = match parent {
     Parent::ChildOf(parent) => {
       window_flags.set(WindowFlags::CHILD, true);
       if pl_attribs.menu.is_some() {
@@ -938,6 +1401,11 @@ unsafe fn init<T: 'static>(
     DeleteObject(region);
   }
 
+  // Exclude the window from capture before it gets a chance to be shown.
+  if attributes.content_protection {
+    set_content_protection(real_window.0, true);
+  }
+
   // If the system theme is dark, we need to set the window theme now
   // before we update the window flags (and possibly show the
   // window for the first time).
@@ -961,14 +1429,42 @@ unsafe fn init<T: 'static>(
     window_state,
     thread_executor: event_loop.create_thread_executor(),
     menu: None,
+    subclass_hook: Default::default(),
+    #[cfg(feature = "accesskit")]
+    accessibility: Default::default(),
   };
 
   KEY_EVENT_BUILDERS
     .lock()
     .insert(win.id(), KeyEventBuilder::default());
 
+  if attributes.opacity < 1.0 {
+    set_opacity(win.window.0, &win.window_state, attributes.opacity);
+  }
+
+  if let Some(effect) = attributes.blur {
+    set_blur(win.window.0, &win.window_state, Some(effect));
+  }
+
   win.set_skip_taskbar(pl_attribs.skip_taskbar);
 
+  let monitor_placement = attributes
+    .position_on_monitor
+    .as_ref()
+    .map(|(monitor, placement)| (monitor.inner.work_area(), *placement));
+  if let Some((work_area, _)) = monitor_placement {
+    // Move to the monitor first, so the size below is scaled with its DPI.
+    SetWindowPos(
+      win.window.0,
+      HWND::default(),
+      work_area.left,
+      work_area.top,
+      0,
+      0,
+      SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+    );
+  }
+
   if attributes.fullscreen.is_some() {
     win.set_fullscreen(attributes.fullscreen);
     force_window_active(win.window.0);
@@ -989,12 +1485,34 @@ unsafe fn init<T: 'static>(
       // Need to set MAXIMIZED after setting `inner_size` as
       // `Window::set_inner_size` changes MAXIMIZED to false.
       win.set_maximized(true);
+    } else if let Some((work_area, placement)) = monitor_placement {
+      let mut rect = RECT::default();
+      GetWindowRect(win.window.0, &mut rect);
+      let (x, y) = placement.offset(
+        (
+          (work_area.right - work_area.left) as f64,
+          (work_area.bottom - work_area.top) as f64,
+        ),
+        (
+          (rect.right - rect.left) as f64,
+          (rect.bottom - rect.top) as f64,
+        ),
+      );
+      SetWindowPos(
+        win.window.0,
+        HWND::default(),
+        work_area.left + x as i32,
+        work_area.top + y as i32,
+        0,
+        0,
+        SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE,
+      );
     }
   }
 
   win.set_visible(attributes.visible);
 
-  if let Some(position) = attributes.position {
+  if let (Some(position), None) = (attributes.position, &attributes.position_on_monitor) {
     win.set_outer_position(position);
   }
 
@@ -1009,6 +1527,7 @@ unsafe fn init<T: 'static>(
       }),
       MenuType::MenuBar,
       Some(window_id),
+      None,
     );
 
     win.menu = Some(HMenuWrapper(menu::initialize(
@@ -1121,7 +1640,7 @@ thread_local! {
         }
     };
 
-    static TASKBAR_LIST: RefCell<Option<ITaskbarList2>> = RefCell::new(None);
+    static TASKBAR_LIST: RefCell<Option<ITaskbarList3>> = RefCell::new(None);
 }
 
 pub fn com_initialized() {
@@ -1137,13 +1656,113 @@ pub fn com_initialized() {
 // heuristics to determine how the window should be treated, which means
 // that it could still consider the window as fullscreen. :(
 unsafe fn taskbar_mark_fullscreen(handle: HWND, fullscreen: bool) {
+  with_taskbar_list(|task_bar_list| {
+    let _ = task_bar_list.MarkFullscreenWindow(handle, fullscreen);
+  })
+}
+
+unsafe fn set_taskbar_progress(handle: HWND, progress: &ProgressBarState) {
+  with_taskbar_list(|task_bar_list| {
+    // Setting a value also switches from no progress to the normal state, so the state has to be
+    // applied last.
+    if let Some(value) = progress.progress {
+      let _ = task_bar_list.SetProgressValue(handle, value.min(100), 100);
+    }
+    if let Some(state) = progress.state {
+      let flag = match state {
+        ProgressState::None => TBPF_NOPROGRESS,
+        ProgressState::Normal => TBPF_NORMAL,
+        ProgressState::Indeterminate => TBPF_INDETERMINATE,
+        ProgressState::Paused => TBPF_PAUSED,
+        ProgressState::Error => TBPF_ERROR,
+      };
+      let _ = task_bar_list.SetProgressState(handle, flag);
+    }
+  })
+}
+
+unsafe fn set_taskbar_badge(handle: HWND, count: Option<u64>) {
+  let badge = count.map(|count| {
+    // Overlay icons are only 16 pixels wide at 100% scale, which fits a single digit.
+    let text = if count > 9 {
+      "9+".to_string()
+    } else {
+      count.to_string()
+    };
+    (
+      icon::WinIcon::badge(&text),
+      util::encode_wide(count.to_string()),
+    )
+  });
+  with_taskbar_list(|task_bar_list| {
+    // The taskbar keeps its own copy of the overlay icon.
+    let _ = match &badge {
+      Some((Ok(icon), description)) => task_bar_list.SetOverlayIcon(
+        handle,
+        icon.as_raw_handle(),
+        PCWSTR::from_raw(description.as_ptr()),
+      ),
+      Some((Err(e), _)) => {
+        warn!("Failed to draw the badge icon: {}", e);
+        return;
+      }
+      None => task_bar_list.SetOverlayIcon(handle, HICON::default(), PCWSTR::null()),
+    };
+  })
+}
+
+/// The number of buttons of a thumbnail toolbar, which can't be changed once they are added.
+const THUMBBAR_BUTTON_COUNT: usize = 7;
+
+/// Applies `WindowState::thumbbar_buttons` to the thumbnail toolbar of the window, adding all
+/// its buttons the first time and hiding the unused ones.
+pub(crate) unsafe fn set_thumbbar_buttons(hwnd: HWND, window_state: &Mutex<WindowState>) {
+  let (buttons, added) = {
+    let window_state = window_state.lock();
+    if window_state.thumbbar_buttons.is_empty() && !window_state.thumbbar_added {
+      return;
+    }
+    let mut buttons = [THUMBBUTTON::default(); THUMBBAR_BUTTON_COUNT];
+    for (slot, button) in buttons.iter_mut().enumerate() {
+      button.iId = slot as u32;
+      button.dwMask = THUMBBUTTONMASK(THB_FLAGS.0 | THB_ICON.0 | THB_TOOLTIP.0);
+      button.dwFlags = THBF_HIDDEN;
+      if let Some(thumb_button) = window_state.thumbbar_buttons.get(slot) {
+        button.hIcon = thumb_button.icon.inner.as_raw_handle();
+        button.dwFlags = if thumb_button.enabled {
+          THBF_ENABLED
+        } else {
+          THBF_DISABLED
+        };
+        if thumb_button.dismiss_on_click {
+          button.dwFlags = THUMBBUTTONFLAGS(button.dwFlags.0 | THBF_DISMISSONCLICK.0);
+        }
+        let tooltip = util::encode_wide(&thumb_button.tooltip);
+        let len = tooltip.len().min(button.szTip.len() - 1);
+        button.szTip[..len].copy_from_slice(&tooltip[..len]);
+      }
+    }
+    (buttons, window_state.thumbbar_added)
+  };
+
+  // The taskbar may send messages to the window, so the state isn't locked here.
+  with_taskbar_list(|task_bar_list| {
+    if added {
+      let _ = task_bar_list.ThumbBarUpdateButtons(hwnd, &buttons);
+    } else if task_bar_list.ThumbBarAddButtons(hwnd, &buttons).is_ok() {
+      window_state.lock().thumbbar_added = true;
+    }
+  })
+}
+
+unsafe fn with_taskbar_list(f: impl FnOnce(&ITaskbarList3)) {
   com_initialized();
 
   TASKBAR_LIST.with(|task_bar_list_ptr| {
     let mut task_bar_list = task_bar_list_ptr.borrow().clone();
 
     if task_bar_list.is_none() {
-      let result: windows::core::Result<ITaskbarList2> =
+      let result: windows::core::Result<ITaskbarList3> =
         CoCreateInstance(&TaskbarList, None, CLSCTX_ALL);
       if let Ok(created) = result {
         if let Ok(()) = created.HrInit() {
@@ -1158,9 +1777,7 @@ unsafe fn taskbar_mark_fullscreen(handle: HWND, fullscreen: bool) {
       *task_bar_list_ptr.borrow_mut() = task_bar_list.clone();
     }
 
-    let _ = task_bar_list
-      .unwrap()
-      .MarkFullscreenWindow(handle, fullscreen);
+    f(task_bar_list.as_ref().unwrap());
   })
 }
 
@@ -1188,6 +1805,108 @@ unsafe fn force_window_active(handle: HWND) {
   SetForegroundWindow(handle);
 }
 
+pub(crate) unsafe fn set_drop_highlight(hwnd: HWND, highlight: bool) {
+  // Paint the window border with the selection color, the same as Explorer's drop targets.
+  let color = if highlight {
+    GetSysColor(COLOR_HIGHLIGHT)
+  } else {
+    DWMWA_COLOR_DEFAULT
+  };
+  let _ = DwmSetWindowAttribute(
+    hwnd,
+    DWMWA_BORDER_COLOR,
+    &color as *const _ as _,
+    mem::size_of::<u32>() as _,
+  );
+}
+
+unsafe fn set_opacity(hwnd: HWND, window_state: &Mutex<WindowState>, opacity: f64) {
+  let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+  WindowState::set_window_flags(window_state.lock(), hwnd, |f| {
+    f.set(WindowFlags::TRANSLUCENT, alpha < u8::MAX)
+  });
+  // Fails harmlessly when the window isn't layered anymore.
+  SetLayeredWindowAttributes(hwnd, 0, alpha, LWA_ALPHA);
+}
+
+pub(crate) unsafe fn set_content_protection(hwnd: HWND, protected: bool) {
+  if !protected {
+    SetWindowDisplayAffinity(hwnd, WDA_NONE);
+  } else if !SetWindowDisplayAffinity(hwnd, WDA_EXCLUDEFROMCAPTURE).as_bool() {
+    // `WDA_EXCLUDEFROMCAPTURE` is only available since Windows 10 version 2004.
+    SetWindowDisplayAffinity(hwnd, WDA_MONITOR);
+  }
+}
+
+pub(crate) unsafe fn set_blur(
+  hwnd: HWND,
+  window_state: &Mutex<WindowState>,
+  effect: Option<BlurEffect>,
+) {
+  // `DWMWA_SYSTEMBACKDROP_TYPE` and its `DWM_SYSTEMBACKDROP_TYPE` values, which the bindings
+  // don't have yet.
+  const DWMWA_SYSTEMBACKDROP_TYPE: DWMWINDOWATTRIBUTE = DWMWINDOWATTRIBUTE(38);
+  const DWMSBT_NONE: i32 = 1;
+  const DWMSBT_MAINWINDOW: i32 = 2;
+  const DWMSBT_TRANSIENTWINDOW: i32 = 3;
+
+  const WCA_ACCENT_POLICY: u32 = 19;
+  const ACCENT_DISABLED: u32 = 0;
+  const ACCENT_ENABLE_BLURBEHIND: u32 = 3;
+
+  #[allow(non_snake_case)]
+  #[repr(C)]
+  struct ACCENT_POLICY {
+    AccentState: u32,
+    AccentFlags: u32,
+    GradientColor: u32,
+    AnimationId: u32,
+  }
+
+  let backdrop = match effect {
+    Some(BlurEffect::Acrylic) => DWMSBT_TRANSIENTWINDOW,
+    Some(BlurEffect::Mica) => DWMSBT_MAINWINDOW,
+    _ => DWMSBT_NONE,
+  };
+  // Fails before Windows 11 22H2.
+  let has_backdrop = DwmSetWindowAttribute(
+    hwnd,
+    DWMWA_SYSTEMBACKDROP_TYPE,
+    &backdrop as *const _ as _,
+    mem::size_of::<i32>() as _,
+  )
+  .is_ok();
+
+  let accent_state = match effect {
+    Some(BlurEffect::Blur) => ACCENT_ENABLE_BLURBEHIND,
+    Some(_) if !has_backdrop => ACCENT_ENABLE_BLURBEHIND,
+    _ => ACCENT_DISABLED,
+  };
+  let mut accent = ACCENT_POLICY {
+    AccentState: accent_state,
+    AccentFlags: 0,
+    GradientColor: 0,
+    AnimationId: 0,
+  };
+  util::set_window_composition_attribute(hwnd, WCA_ACCENT_POLICY, &mut accent);
+
+  // The backdrops are only drawn behind the frame, so it's extended over the whole window. DWM
+  // can't tell which margins the window had before, so they're only reset if we extended them,
+  // which leaves the margins set by the application alone otherwise.
+  let extend_frame = effect.is_some();
+  if mem::replace(&mut window_state.lock().blur_extended_frame, extend_frame) == extend_frame {
+    return;
+  }
+  let inset = if extend_frame { -1 } else { 0 };
+  let margins = MARGINS {
+    cxLeftWidth: inset,
+    cxRightWidth: inset,
+    cyTopHeight: inset,
+    cyBottomHeight: inset,
+  };
+  let _ = DwmExtendFrameIntoClientArea(hwnd, &margins);
+}
+
 pub(crate) unsafe fn set_skip_taskbar(hwnd: HWND, skip: bool) {
   com_initialized();
   let taskbar_list: ITaskbarList =
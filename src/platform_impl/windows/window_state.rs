@@ -4,17 +4,26 @@
 
 use crate::{
   dpi::{PhysicalPosition, Size},
+  event::Rectangle,
+  event_loop::DeviceEventFilter,
   icon::Icon,
   keyboard::ModifiersState,
-  platform_impl::platform::{event_loop, minimal_ime::MinimalIme, util},
-  window::{CursorIcon, Fullscreen, Theme, WindowAttributes},
+  platform::windows::ThumbButton,
+  platform_impl::platform::{event_loop, icon::WinCursor, minimal_ime::MinimalIme, util},
+  window::{CursorIcon, Fullscreen, Theme, WindowAttributes, RGBA},
 };
 use parking_lot::MutexGuard;
-use std::io;
+use std::{io, mem};
 use windows::Win32::{
-  Foundation::{HWND, LPARAM, RECT, WPARAM},
-  Graphics::Gdi::{InvalidateRgn, HRGN},
-  UI::WindowsAndMessaging::*,
+  Foundation::{HINSTANCE, HWND, LPARAM, POINT, RECT, WPARAM},
+  Graphics::{
+    Dwm::{
+      DwmSetWindowAttribute, DWMNCRENDERINGPOLICY, DWMNCRP_DISABLED, DWMNCRP_USEWINDOWSTYLE,
+      DWMWA_NCRENDERING_POLICY,
+    },
+    Gdi::{InvalidateRgn, HRGN},
+  },
+  UI::{Input::KeyboardAndMouse::EnableWindow, WindowsAndMessaging::*},
 };
 
 /// Contains information about states and the window that the callback is going to use.
@@ -24,6 +33,8 @@ pub struct WindowState {
   /// Used by `WM_GETMINMAXINFO`.
   pub min_size: Option<Size>,
   pub max_size: Option<Size>,
+  /// Used by `WM_SIZING`.
+  pub resize_increments: Option<Size>,
 
   pub window_icon: Option<Icon>,
   pub taskbar_icon: Option<Icon>,
@@ -33,6 +44,28 @@ pub struct WindowState {
 
   pub skip_taskbar: bool,
 
+  /// The buttons of the thumbnail toolbar, in order, and whether they were added to the
+  /// taskbar button of the window.
+  pub thumbbar_buttons: Vec<ThumbButton>,
+  pub thumbbar_added: bool,
+  pub minimize_to_tray: bool,
+
+  /// Used by `WM_ERASEBKGND`.
+  pub background_color: Option<RGBA>,
+
+  /// Used by `WM_NCHITTEST`, in client coordinates.
+  pub drag_regions: Vec<Rectangle>,
+  pub drag_exclusions: Vec<Rectangle>,
+  pub cursor_hittest_regions: Option<Vec<Rectangle>>,
+
+  /// Overrides the event loop's device event filter while the window is focused.
+  pub device_event_filter: Option<DeviceEventFilter>,
+
+  /// Whether `request_redraw` waits for the next frame of the DWM.
+  pub vsync_redraws: bool,
+  /// Whether `set_blur` extended the frame over the whole window.
+  pub blur_extended_frame: bool,
+
   pub modifiers_state: ModifiersState,
   pub fullscreen: Option<Fullscreen>,
   pub current_theme: Theme,
@@ -46,6 +79,13 @@ pub struct WindowState {
   // Used by WM_NCACTIVATE, WM_SETFOCUS and WM_KILLFOCUS
   pub is_active: bool,
   pub is_focused: bool,
+
+  // Used by WM_SIZE and OCCLUSION_CHANGED_MSG_ID
+  pub is_minimized: bool,
+  pub is_cloaked: bool,
+
+  // The maximized state last reported with `WindowEvent::Maximized`, used by WM_SIZE
+  pub is_maximized: bool,
 }
 
 #[derive(Clone)]
@@ -56,6 +96,8 @@ pub struct SavedWindow {
 #[derive(Clone)]
 pub struct MouseProperties {
   pub cursor: CursorIcon,
+  /// The cursor created for `cursor`, if it's a [`CursorIcon::Custom`].
+  pub custom_cursor: Option<WinCursor>,
   pub capture_count: u32,
   cursor_flags: CursorFlags,
   pub last_position: Option<PhysicalPosition<f64>>,
@@ -66,6 +108,7 @@ bitflags! {
         const GRABBED   = 1 << 0;
         const HIDDEN    = 1 << 1;
         const IN_WINDOW = 1 << 2;
+        const LOCKED    = 1 << 3;
     }
 }
 bitflags! {
@@ -81,6 +124,17 @@ bitflags! {
         const MAXIMIZED        = 1 << 8;
         const POPUP            = 1 << 14;
         const ALWAYS_ON_BOTTOM = 1 << 16;
+        const MINIMIZABLE      = 1 << 17;
+        const MAXIMIZABLE      = 1 << 18;
+        const CLOSABLE         = 1 << 19;
+        const TOOL_WINDOW      = 1 << 20;
+        const NO_SHADOW        = 1 << 22;
+        /// The owner of the window is disabled until the window is destroyed.
+        const MODAL            = 1 << 23;
+        /// Set while the opacity of the window is below 1, which needs a layered window.
+        const TRANSLUCENT      = 1 << 24;
+        /// A window without decorations isn't resized from its edges.
+        const NO_RESIZE_BORDERS = 1 << 25;
 
         /// Marker flag for fullscreen. Should always match `WindowState::fullscreen`, but is
         /// included here to make masking easier.
@@ -100,6 +154,10 @@ bitflags! {
 
         const IGNORE_CURSOR_EVENT = 1 << 15;
 
+        /// Set while the cursor is outside of the cursor hit-test regions, so that the window
+        /// lets it through to the windows behind it.
+        const MARKER_CURSOR_PASSTHROUGH = 1 << 21;
+
         const EXCLUSIVE_FULLSCREEN_OR_MASK = WindowFlags::ALWAYS_ON_TOP.bits;
     }
 }
@@ -115,6 +173,7 @@ impl WindowState {
     WindowState {
       mouse: MouseProperties {
         cursor: CursorIcon::default(),
+        custom_cursor: None,
         capture_count: 0,
         cursor_flags: CursorFlags::empty(),
         last_position: None,
@@ -122,6 +181,7 @@ impl WindowState {
 
       min_size: attributes.min_inner_size,
       max_size: attributes.max_inner_size,
+      resize_increments: attributes.resize_increments,
 
       window_icon: attributes.window_icon.clone(),
       taskbar_icon,
@@ -136,13 +196,40 @@ impl WindowState {
       current_theme,
       preferred_theme,
       high_surrogate: None,
+      minimize_to_tray: false,
+      thumbbar_buttons: Vec::new(),
+      thumbbar_added: false,
+      background_color: attributes.background_color,
+      drag_regions: Vec::new(),
+      drag_exclusions: Vec::new(),
+      cursor_hittest_regions: None,
+      device_event_filter: None,
+      vsync_redraws: attributes.vsync_redraws,
+      blur_extended_frame: false,
       ime_handler: MinimalIme::default(),
       window_flags: WindowFlags::empty(),
       is_active: false,
       is_focused: false,
+      is_minimized: false,
+      is_cloaked: false,
+      is_maximized: false,
     }
   }
 
+  pub fn is_in_drag_region(&self, position: PhysicalPosition<f64>) -> bool {
+    let contains = |rect: &Rectangle| rect.contains(position);
+    self.drag_regions.iter().any(contains) && !self.drag_exclusions.iter().any(contains)
+  }
+
+  pub fn is_in_cursor_hittest_region(&self, position: PhysicalPosition<f64>) -> bool {
+    self
+      .cursor_hittest_regions
+      .as_ref()
+      .map_or(true, |regions| {
+        regions.iter().any(|rect| rect.contains(position))
+      })
+  }
+
   pub fn window_flags(&self) -> WindowFlags {
     self.window_flags
   }
@@ -170,6 +257,10 @@ impl WindowState {
     self.is_active && self.is_focused
   }
 
+  pub fn is_occluded(&self) -> bool {
+    self.is_minimized || self.is_cloaked
+  }
+
   // Updates is_active and returns whether active-focus state has changed
   pub fn set_active(&mut self, is_active: bool) -> bool {
     let old = self.has_active_focus();
@@ -186,6 +277,14 @@ impl WindowState {
 }
 
 impl MouseProperties {
+  /// Returns the handle of the cursor to show over the client area.
+  pub fn cursor_handle(&self) -> Option<HCURSOR> {
+    match &self.custom_cursor {
+      Some(cursor) => Some(cursor.as_raw_handle()),
+      None => unsafe { LoadCursorW(HINSTANCE::default(), self.cursor.to_windows_cursor()).ok() },
+    }
+  }
+
   pub fn cursor_flags(&self) -> CursorFlags {
     self.cursor_flags
   }
@@ -219,11 +318,17 @@ impl WindowFlags {
 
   pub fn to_window_styles(self) -> (WINDOW_STYLE, WINDOW_EX_STYLE) {
     let (mut style, mut style_ex) = (Default::default(), Default::default());
-    style |= WS_CLIPSIBLINGS | WS_CLIPCHILDREN | WS_SYSMENU | WS_CAPTION | WS_MINIMIZEBOX;
+    style |= WS_CLIPSIBLINGS | WS_CLIPCHILDREN | WS_SYSMENU | WS_CAPTION;
     style_ex |= WS_EX_ACCEPTFILES;
 
     if self.contains(WindowFlags::RESIZABLE) {
-      style |= WS_SIZEBOX | WS_MAXIMIZEBOX;
+      style |= WS_SIZEBOX;
+    }
+    if self.contains(WindowFlags::MINIMIZABLE) {
+      style |= WS_MINIMIZEBOX;
+    }
+    if self.contains(WindowFlags::RESIZABLE | WindowFlags::MAXIMIZABLE) {
+      style |= WS_MAXIMIZEBOX;
     }
     if self.contains(WindowFlags::DECORATIONS) {
       style_ex |= WS_EX_WINDOWEDGE;
@@ -237,6 +342,9 @@ impl WindowFlags {
     if self.contains(WindowFlags::ALWAYS_ON_TOP) {
       style_ex |= WS_EX_TOPMOST;
     }
+    if self.contains(WindowFlags::TOOL_WINDOW) {
+      style_ex |= WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE;
+    }
     if self.contains(WindowFlags::NO_BACK_BUFFER) {
       style_ex |= WS_EX_NOREDIRECTIONBITMAP;
     }
@@ -252,9 +360,12 @@ impl WindowFlags {
     if self.contains(WindowFlags::MAXIMIZED) {
       style |= WS_MAXIMIZE;
     }
-    if self.contains(WindowFlags::IGNORE_CURSOR_EVENT) {
+    if self.intersects(WindowFlags::IGNORE_CURSOR_EVENT | WindowFlags::MARKER_CURSOR_PASSTHROUGH) {
       style_ex |= WS_EX_TRANSPARENT | WS_EX_LAYERED;
     }
+    if self.contains(WindowFlags::TRANSLUCENT) {
+      style_ex |= WS_EX_LAYERED;
+    }
     if self.intersects(
       WindowFlags::MARKER_EXCLUSIVE_FULLSCREEN | WindowFlags::MARKER_BORDERLESS_FULLSCREEN,
     ) {
@@ -275,9 +386,25 @@ impl WindowFlags {
       return;
     }
 
+    // The owner of a modal window is only disabled while the modal window is shown, and enabled
+    // before it is hidden so that the owner gets activated in its place.
+    if new.contains(WindowFlags::MODAL) && diff.contains(WindowFlags::VISIBLE) {
+      unsafe {
+        EnableWindow(
+          GetWindow(window, GW_OWNER),
+          !new.contains(WindowFlags::VISIBLE),
+        );
+      }
+    }
+
     if new.contains(WindowFlags::VISIBLE) {
+      let show = if new.contains(WindowFlags::TOOL_WINDOW) {
+        SW_SHOWNA
+      } else {
+        SW_SHOW
+      };
       unsafe {
-        ShowWindow(window, SW_SHOW);
+        ShowWindow(window, show);
       }
     }
 
@@ -299,7 +426,8 @@ impl WindowFlags {
       }
     }
 
-    if diff.contains(WindowFlags::ALWAYS_ON_BOTTOM) {
+    // Don't take a window that just became topmost out of the topmost band again.
+    if diff.contains(WindowFlags::ALWAYS_ON_BOTTOM) && !new.contains(WindowFlags::ALWAYS_ON_TOP) {
       unsafe {
         SetWindowPos(
           window,
@@ -348,6 +476,38 @@ impl WindowFlags {
       }
     }
 
+    // The close button follows the state of the window menu's close item. Also checking `new`
+    // covers windows created without one, as `diff` is computed against empty flags then.
+    if diff.contains(WindowFlags::CLOSABLE) || !new.contains(WindowFlags::CLOSABLE) {
+      let flags = if new.contains(WindowFlags::CLOSABLE) {
+        MF_BYCOMMAND | MF_ENABLED
+      } else {
+        MF_BYCOMMAND | MF_DISABLED | MF_GRAYED
+      };
+      unsafe {
+        EnableMenuItem(GetSystemMenu(window, false), SC_CLOSE, flags);
+      }
+    }
+
+    // Undecorated windows keep their frame styles to stay resizable, so DWM keeps drawing the
+    // shadow of the frame unless its rendering is disabled.
+    if diff.intersects(WindowFlags::DECORATIONS | WindowFlags::NO_SHADOW) {
+      let policy =
+        if !new.contains(WindowFlags::DECORATIONS) && new.contains(WindowFlags::NO_SHADOW) {
+          DWMNCRP_DISABLED
+        } else {
+          DWMNCRP_USEWINDOWSTYLE
+        };
+      unsafe {
+        let _ = DwmSetWindowAttribute(
+          window,
+          DWMWA_NCRENDERING_POLICY,
+          &policy as *const _ as _,
+          mem::size_of::<DWMNCRENDERINGPOLICY>() as _,
+        );
+      }
+    }
+
     if diff != WindowFlags::empty() {
       let (style, style_ex) = new.to_window_styles();
 
@@ -365,6 +525,13 @@ impl WindowFlags {
           SetWindowLongW(window, GWL_EXSTYLE, style_ex.0 as i32);
         }
 
+        // A layered window isn't drawn until its attributes are set, so keep it fully opaque,
+        // unless it already was layered and may have been made translucent.
+        let was_layered = self.to_window_styles().1 .0 & WS_EX_LAYERED.0 != 0;
+        if style_ex.0 & WS_EX_LAYERED.0 != 0 && !was_layered {
+          SetLayeredWindowAttributes(window, 0, u8::MAX, LWA_ALPHA);
+        }
+
         let mut flags = SWP_NOZORDER | SWP_NOMOVE | SWP_NOSIZE | SWP_FRAMECHANGED;
 
         // We generally don't want style changes here to affect window
@@ -394,13 +561,33 @@ impl CursorFlags {
     let client_rect = util::get_client_rect(window)?;
 
     if util::is_focused(window) {
-      let cursor_clip = match self.contains(CursorFlags::GRABBED) {
-        true => Some(client_rect),
-        false => None,
+      let rect_to_tuple = |rect: RECT| (rect.left, rect.top, rect.right, rect.bottom);
+      let active_cursor_clip = util::get_cursor_clip()?;
+
+      let cursor_clip = if self.contains(CursorFlags::LOCKED) {
+        // Keeps the cursor in the single pixel it's clipped to, or the one it's over when it
+        // wasn't locked yet. Its motion is still reported by raw input.
+        let mut position = POINT::default();
+        unsafe { GetCursorPos(&mut position) };
+        let locked = active_cursor_clip.right - active_cursor_clip.left == 1
+          && active_cursor_clip.bottom - active_cursor_clip.top == 1;
+        if locked {
+          Some(active_cursor_clip)
+        } else {
+          Some(RECT {
+            left: position.x,
+            top: position.y,
+            right: position.x + 1,
+            bottom: position.y + 1,
+          })
+        }
+      } else if self.contains(CursorFlags::GRABBED) {
+        Some(client_rect)
+      } else {
+        None
       };
 
-      let rect_to_tuple = |rect: RECT| (rect.left, rect.top, rect.right, rect.bottom);
-      let active_cursor_clip = rect_to_tuple(util::get_cursor_clip()?);
+      let active_cursor_clip = rect_to_tuple(active_cursor_clip);
       let desktop_rect = rect_to_tuple(util::get_desktop_rect());
 
       let active_cursor_clip = match desktop_rect == active_cursor_clip {
@@ -45,6 +45,7 @@ pub use crate::icon::{BadIcon, Icon};
 pub struct SystemTrayBuilder {
   pub(crate) platform_tray_builder: SystemTrayBuilderPlatform,
   tooltip: Option<String>,
+  title: Option<String>,
   id: TrayId,
 }
 
@@ -57,11 +58,18 @@ impl SystemTrayBuilder {
         tray_menu.map(|m| m.0.menu_platform),
       ),
       tooltip: None,
+      title: None,
       id: TrayId::EMPTY,
     }
   }
 
   /// Sets the tray identifier.
+  ///
+  /// An application can build several trays. Give each one its own id to tell their
+  /// [`Event::TrayEvent`]s and [`Event::MenuEvent`]s apart.
+  ///
+  /// [`Event::TrayEvent`]: crate::event::Event::TrayEvent
+  /// [`Event::MenuEvent`]: crate::event::Event::MenuEvent
   pub fn with_id(mut self, id: TrayId) -> Self {
     self.id = id;
     self
@@ -71,12 +79,23 @@ impl SystemTrayBuilder {
   ///
   /// ## Platform-specific:
   ///
-  /// - **Linux:** Unsupported
+  /// - **Linux:** Sets the indicator title, which most desktops show when hovering the icon.
   pub fn with_tooltip(mut self, tooltip: &str) -> Self {
     self.tooltip = Some(tooltip.to_string());
     self
   }
 
+  /// Adds a text shown next to the tray icon, e.g. to display a live value.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux:** Sets the indicator label, which not every desktop shows.
+  /// - **Windows:** Unsupported
+  pub fn with_title(mut self, title: &str) -> Self {
+    self.title = Some(title.to_string());
+    self
+  }
+
   /// Builds the SystemTray.
   ///
   /// Possible causes of error include denied permission, incompatible system, and lack of memory.
@@ -86,7 +105,7 @@ impl SystemTrayBuilder {
   ) -> Result<SystemTray, OsError> {
     self
       .platform_tray_builder
-      .build(window_target, self.id, self.tooltip)
+      .build(window_target, self.id, self.tooltip, self.title)
   }
 }
 
@@ -96,7 +115,7 @@ impl SystemTrayBuilder {
 ///
 /// **Linux:**
 ///   - Dropping the tray too early could lead to a default icon.
-///   - Dropping the tray hides it.
+///   - Dropping the tray removes it and unregisters its indicator from the desktop.
 /// **Windows / macOS:** Dropping the tray will effectively remove the icon from the system tray.
 pub struct SystemTray(pub SystemTrayPlatform);
 
@@ -115,8 +134,51 @@ impl SystemTray {
   ///
   /// ## Platform-specific:
   ///
-  /// - **Linux:** Unsupported
+  /// - **Linux:** Sets the indicator title, which most desktops show when hovering the icon.
   pub fn set_tooltip(&mut self, tooltip: &str) {
     self.0.set_tooltip(tooltip);
   }
+
+  /// Sets the text shown next to the tray icon. An empty title removes it.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Linux:** Sets the indicator label, which not every desktop shows.
+  /// - **Windows:** Unsupported
+  pub fn set_title(&mut self, title: &str) {
+    self.0.set_title(title);
+  }
+
+  /// Removes the tray icon, which is the same as dropping it. See the drop behavior above.
+  pub fn destroy(self) {
+    drop(self)
+  }
+
+  /// Sets the status of this tray icon, e.g. to hide it while the application is idle.
+  ///
+  /// ## Platform-specific:
+  ///
+  /// - **Windows:** `NeedsAttention` shows the icon like `Active`, there is no way to flag it.
+  /// - **macOS:** `NeedsAttention` highlights the icon.
+  pub fn set_status(&mut self, status: TrayStatus) {
+    self.0.set_status(status);
+  }
+}
+
+/// The status of a tray icon.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TrayStatus {
+  /// The icon is shown. This is the default.
+  Active,
+  /// The icon is hidden.
+  Passive,
+  /// The icon is shown, and flagged as needing the user's attention.
+  NeedsAttention,
+}
+
+impl Default for TrayStatus {
+  fn default() -> Self {
+    TrayStatus::Active
+  }
 }
@@ -3,14 +3,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! The `Window` struct and associated types.
-use std::fmt;
+use std::{fmt, path::PathBuf, sync::Arc};
 
 use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle};
 
 use crate::{
   dpi::{PhysicalPosition, PhysicalSize, Position, Size},
   error::{ExternalError, NotSupportedError, OsError},
-  event_loop::EventLoopWindowTarget,
+  event::Rectangle,
+  event_loop::{DeviceEventFilter, EventLoopWindowTarget},
+  icon::RgbaIcon,
   menu::MenuBar,
   monitor::{MonitorHandle, VideoMode},
   platform_impl,
@@ -125,6 +127,11 @@ pub struct WindowAttributes {
   /// The default is `None`.
   pub max_inner_size: Option<Size>,
 
+  /// The steps the window is resized by, if any.
+  ///
+  /// The default is `None`.
+  pub resize_increments: Option<Size>,
+
   /// The desired position of the window. If this is `None`, some platform-specific position
   /// will be chosen.
   ///
@@ -150,11 +157,42 @@ pub struct WindowAttributes {
   /// [`Window::set_outer_position`]: crate::window::Window::set_outer_position
   pub position: Option<Position>,
 
+  /// The monitor to place the window on, and where within its work area.
+  ///
+  /// Takes precedence over [`WindowAttributes::position`].
+  ///
+  /// The default is `None`.
+  pub position_on_monitor: Option<(MonitorHandle, MonitorPlacement)>,
+
+  /// A saved window state to restore, see [`WindowBuilder::with_state`].
+  ///
+  /// Takes precedence over [`WindowAttributes::inner_size`], [`WindowAttributes::position`],
+  /// [`WindowAttributes::position_on_monitor`], [`WindowAttributes::maximized`] and
+  /// [`WindowAttributes::fullscreen`].
+  ///
+  /// The default is `None`.
+  pub state: Option<WindowState>,
+
   /// Whether the window is resizable or not.
   ///
   /// The default is `true`.
   pub resizable: bool,
 
+  /// Whether the window has an enabled minimize button.
+  ///
+  /// The default is `true`.
+  pub minimizable: bool,
+
+  /// Whether the window has an enabled maximize button.
+  ///
+  /// The default is `true`.
+  pub maximizable: bool,
+
+  /// Whether the window has an enabled close button.
+  ///
+  /// The default is `true`.
+  pub closable: bool,
+
   /// Whether the window should be set as fullscreen upon creation.
   ///
   /// The default is `None`.
@@ -186,15 +224,10 @@ pub struct WindowAttributes {
   /// The default is `true`.
   pub decorations: bool,
 
-  /// Whether the window should always be on top of other windows.
+  /// The stacking level of the window.
   ///
-  /// The default is `false`.
-  pub always_on_top: bool,
-
-  /// Whether the window should always be on bottom of other windows.
-  ///
-  /// The default is `false`.
-  pub always_on_bottom: bool,
+  /// The default is [`WindowLevel::Normal`].
+  pub window_level: WindowLevel,
 
   /// The window icon.
   ///
@@ -207,6 +240,62 @@ pub struct WindowAttributes {
   pub window_menu: Option<platform_impl::Menu>,
 
   pub preferred_theme: Option<Theme>,
+
+  /// The color used to fill the window before the app paints it.
+  ///
+  /// The default is `None`, which keeps the OS default background.
+  pub background_color: Option<RGBA>,
+
+  /// Whether the window content is protected from being captured by other apps.
+  ///
+  /// The default is `false`.
+  pub content_protection: bool,
+
+  /// The opacity of the whole window, from `0.0` (invisible) to `1.0` (opaque).
+  ///
+  /// The default is `1.0`.
+  pub opacity: f64,
+
+  /// The window this window is a tool window of, if any.
+  ///
+  /// The default is `None`.
+  pub tool_window: Option<WindowId>,
+
+  /// The window this window is a child of, if any.
+  ///
+  /// The default is `None`.
+  pub parent: Option<WindowId>,
+
+  /// The window this window is owned by, if any.
+  ///
+  /// The default is `None`.
+  pub owner: Option<WindowId>,
+
+  /// Whether the window is modal for its [`owner`](WindowAttributes::owner), which can't be
+  /// interacted with until this window is closed.
+  ///
+  /// The default is `false`.
+  pub modal: bool,
+
+  /// The effect drawn behind the transparent parts of the window.
+  ///
+  /// The default is `None`.
+  pub blur: Option<BlurEffect>,
+
+  /// Whether the window has a drop shadow.
+  ///
+  /// The default is `true`.
+  pub shadow: bool,
+
+  /// Whether a resizable window without decorations can be resized from its edges.
+  ///
+  /// The default is `true`.
+  pub undecorated_resize_borders: bool,
+
+  /// Whether redraws are paced by the frame clock of the compositor.
+  ///
+  /// The default is `false`.
+  pub vsync_redraws: bool,
 }
 
 impl Default for WindowAttributes {
@@ -215,20 +304,36 @@ impl Default for WindowAttributes {
     WindowAttributes {
       inner_size: None,
       min_inner_size: None,
+      resize_increments: None,
       max_inner_size: None,
       position: None,
+      position_on_monitor: None,
+      state: None,
       resizable: true,
+      minimizable: true,
+      maximizable: true,
+      closable: true,
       title: "tao window".to_owned(),
       maximized: false,
       fullscreen: None,
       visible: true,
       transparent: false,
       decorations: true,
-      always_on_top: false,
-      always_on_bottom: false,
+      window_level: Default::default(),
       window_icon: None,
       window_menu: None,
       preferred_theme: None,
+      background_color: None,
+      content_protection: false,
+      opacity: 1.0,
+      tool_window: None,
+      parent: None,
+      owner: None,
+      modal: false,
+      blur: None,
+      shadow: true,
+      undecorated_resize_borders: true,
+      vsync_redraws: false,
     }
   }
 }
@@ -273,6 +378,17 @@ impl WindowBuilder {
     self
   }
 
+  /// Sets the steps the window is resized by.
+  ///
+  /// See [`Window::set_resize_increments`] for details.
+  ///
+  /// [`Window::set_resize_increments`]: crate::window::Window::set_resize_increments
+  #[inline]
+  pub fn with_resize_increments<S: Into<Size>>(mut self, increments: S) -> Self {
+    self.window.resize_increments = Some(increments.into());
+    self
+  }
+
   /// Sets a desired initial position for the window.
   ///
   /// See [`WindowAttributes::position`] for details.
@@ -284,6 +400,28 @@ impl WindowBuilder {
     self
   }
 
+  /// Places the window on `monitor` at `placement` within its work area, i.e. the area not
+  /// covered by the taskbar, dock or panels. The position is resolved when the window is
+  /// created, so the window doesn't jump when it is shown. The window size is scaled with
+  /// the scale factor of `monitor`.
+  ///
+  /// Overrides [`WindowBuilder::with_position`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The window decorations are not known before the window is shown, so the
+  ///   window may be off by their size. Wayland doesn't allow positioning windows.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_position_on_monitor(
+    mut self,
+    monitor: MonitorHandle,
+    placement: MonitorPlacement,
+  ) -> Self {
+    self.window.position_on_monitor = Some((monitor, placement));
+    self
+  }
+
   /// Sets whether the window is resizable or not.
   ///
   /// See [`Window::set_resizable`] for details.
@@ -295,6 +433,52 @@ impl WindowBuilder {
     self
   }
 
+  /// Sets whether the window's minimize button is enabled.
+  ///
+  /// See [`Window::set_minimizable`] for details.
+  ///
+  /// [`Window::set_minimizable`]: crate::window::Window::set_minimizable
+  #[inline]
+  pub fn with_minimizable(mut self, minimizable: bool) -> Self {
+    self.window.minimizable = minimizable;
+    self
+  }
+
+  /// Sets whether the window's maximize button is enabled.
+  ///
+  /// See [`Window::set_maximizable`] for details.
+  ///
+  /// [`Window::set_maximizable`]: crate::window::Window::set_maximizable
+  #[inline]
+  pub fn with_maximizable(mut self, maximizable: bool) -> Self {
+    self.window.maximizable = maximizable;
+    self
+  }
+
+  /// Sets whether the window's close button is enabled.
+  ///
+  /// See [`Window::set_closable`] for details.
+  ///
+  /// [`Window::set_closable`]: crate::window::Window::set_closable
+  #[inline]
+  pub fn with_closable(mut self, closable: bool) -> Self {
+    self.window.closable = closable;
+    self
+  }
+
+  /// Sets which of the window's buttons are enabled.
+  ///
+  /// See [`Window::set_enabled_buttons`] for details.
+  ///
+  /// [`Window::set_enabled_buttons`]: crate::window::Window::set_enabled_buttons
+  #[inline]
+  pub fn with_enabled_buttons(mut self, buttons: WindowButtons) -> Self {
+    self.window.minimizable = buttons.contains(WindowButtons::MINIMIZE);
+    self.window.maximizable = buttons.contains(WindowButtons::MAXIMIZE);
+    self.window.closable = buttons.contains(WindowButtons::CLOSE);
+    self
+  }
+
   /// Requests a specific title for the window.
   ///
   /// See [`Window::set_title`] for details.
@@ -339,6 +523,28 @@ impl WindowBuilder {
     self
   }
 
+  /// Restores a state previously returned by [`Window::outer_state`].
+  ///
+  /// The state is resolved against the monitors available when the window is built: the
+  /// window goes back to the monitor it was saved on, or to the monitor under its saved
+  /// position, or else to the primary monitor. The size is scaled from the saved scale factor
+  /// to the one of that monitor and shrunk to fit it, and the window is centered on the monitor
+  /// when its saved position is no longer on it.
+  ///
+  /// Overrides [`WindowBuilder::with_inner_size`], [`WindowBuilder::with_position`],
+  /// [`WindowBuilder::with_position_on_monitor`], [`WindowBuilder::with_maximized`] and
+  /// [`WindowBuilder::with_fullscreen`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux(Wayland):** The position can't be restored.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_state(mut self, state: WindowState) -> Self {
+    self.window.state = Some(state);
+    self
+  }
+
   /// Sets whether the window will be initially hidden or visible.
   ///
   /// See [`Window::set_visible`] for details.
@@ -357,6 +563,29 @@ impl WindowBuilder {
     self
   }
 
+  /// Sets the color the window is filled with before the app paints it, to avoid a flash
+  /// of the OS default background.
+  ///
+  /// See [`Window::set_background_color`] for details.
+  ///
+  /// [`Window::set_background_color`]: crate::window::Window::set_background_color
+  #[inline]
+  pub fn with_background_color(mut self, color: RGBA) -> Self {
+    self.window.background_color = Some(color);
+    self
+  }
+
+  /// Sets whether redraws are paced by the frame clock of the compositor.
+  ///
+  /// See [`Window::set_vsync_redraws`] for details.
+  ///
+  /// [`Window::set_vsync_redraws`]: crate::window::Window::set_vsync_redraws
+  #[inline]
+  pub fn with_vsync_redraws(mut self, vsync_redraws: bool) -> Self {
+    self.window.vsync_redraws = vsync_redraws;
+    self
+  }
+
   /// Sets whether the window should have a border, a title bar, etc.
   ///
   /// See [`Window::set_decorations`] for details.
@@ -375,8 +604,11 @@ impl WindowBuilder {
   /// [`Window::set_always_on_bottom`]: crate::window::Window::set_always_on_bottom
   #[inline]
   pub fn with_always_on_bottom(mut self, always_on_bottom: bool) -> Self {
-    self.window.always_on_top = false;
-    self.window.always_on_bottom = always_on_bottom;
+    self.window.window_level = if always_on_bottom {
+      WindowLevel::Desktop
+    } else {
+      WindowLevel::Normal
+    };
     self
   }
 
@@ -387,8 +619,22 @@ impl WindowBuilder {
   /// [`Window::set_always_on_top`]: crate::window::Window::set_always_on_top
   #[inline]
   pub fn with_always_on_top(mut self, always_on_top: bool) -> Self {
-    self.window.always_on_bottom = false;
-    self.window.always_on_top = always_on_top;
+    self.window.window_level = if always_on_top {
+      WindowLevel::Floating
+    } else {
+      WindowLevel::Normal
+    };
+    self
+  }
+
+  /// Sets the stacking level of the window.
+  ///
+  /// See [`Window::set_window_level`] for details.
+  ///
+  /// [`Window::set_window_level`]: crate::window::Window::set_window_level
+  #[inline]
+  pub fn with_window_level(mut self, window_level: WindowLevel) -> Self {
+    self.window.window_level = window_level;
     self
   }
 
@@ -404,20 +650,172 @@ impl WindowBuilder {
   }
 
   /// Forces a theme or uses the system settings if `None` was provided.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Applies the dark or light variant of the GTK theme, which is shared by every
+  ///   window of the application.
   #[inline]
   pub fn with_theme(mut self, theme: Option<Theme>) -> WindowBuilder {
     self.window.preferred_theme = theme;
     self
   }
 
+  /// Sets whether the window content is protected from being captured by other apps, before
+  /// it is shown for the first time.
+  ///
+  /// See [`Window::set_content_protection`] for details.
+  ///
+  /// [`Window::set_content_protection`]: crate::window::Window::set_content_protection
+  #[inline]
+  pub fn with_content_protection(mut self, protected: bool) -> WindowBuilder {
+    self.window.content_protection = protected;
+    self
+  }
+
+  /// Sets the opacity of the whole window, before it is shown for the first time.
+  ///
+  /// See [`Window::set_opacity`] for details.
+  ///
+  /// [`Window::set_opacity`]: crate::window::Window::set_opacity
+  #[inline]
+  pub fn with_opacity(mut self, opacity: f64) -> WindowBuilder {
+    self.window.opacity = opacity;
+    self
+  }
+
+  /// Sets whether the window has a drop shadow.
+  ///
+  /// See [`Window::set_shadow`] for details.
+  ///
+  /// [`Window::set_shadow`]: crate::window::Window::set_shadow
+  #[inline]
+  pub fn with_shadow(mut self, shadow: bool) -> WindowBuilder {
+    self.window.shadow = shadow;
+    self
+  }
+
+  /// Sets whether a resizable window without decorations can be resized from its edges, within
+  /// [`BORDERLESS_RESIZE_INSET`] of them, as if it had native borders. Disable it for apps that
+  /// hit-test their own resize handles, for example with [`Window::drag_resize_window`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Unsupported, a resizable window always keeps its native resize borders.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`Window::drag_resize_window`]: crate::window::Window::drag_resize_window
+  #[inline]
+  pub fn with_undecorated_resize_borders(mut self, resize_borders: bool) -> WindowBuilder {
+    self.window.undecorated_resize_borders = resize_borders;
+    self
+  }
+
+  /// Sets the effect drawn behind the transparent parts of the window.
+  ///
+  /// See [`Window::set_blur`] for details.
+  ///
+  /// [`Window::set_blur`]: crate::window::Window::set_blur
+  #[inline]
+  pub fn with_blur(mut self, effect: BlurEffect) -> WindowBuilder {
+    self.window.blur = Some(effect);
+    self
+  }
+
+  /// Makes the window a tool window of `parent`, like a palette: it floats above `parent`,
+  /// has a smaller title bar, isn't shown in the taskbar, and clicking it doesn't take the
+  /// focus away from `parent`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Creates a `WS_EX_TOOLWINDOW` window owned by `parent`. It never gets the
+  ///   keyboard focus.
+  /// - **macOS:** Creates a non-activating utility `NSPanel`, which only gets the keyboard
+  ///   focus when clicking a control that needs it. It moves with `parent`.
+  /// - **Linux:** Creates a utility window transient for `parent`. It never gets the keyboard
+  ///   focus.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_tool_window(mut self, parent: &Window) -> WindowBuilder {
+    self.window.tool_window = Some(parent.id());
+    self
+  }
+
+  /// Makes the window a child of `parent`.
+  ///
+  /// Replaces the owner set by [`WindowBuilder::with_owner`] or [`WindowBuilder::with_modal`],
+  /// and is ignored for tool windows.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Creates a `WS_CHILD` window, which is drawn within and clipped to the client
+  ///   area of `parent`, and positioned relative to it.
+  /// - **macOS / Linux:** Child windows are top-level windows, so this is the same as
+  ///   [`WindowBuilder::with_owner`].
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_parent(mut self, parent: &Window) -> WindowBuilder {
+    self.window.owner = None;
+    self.window.modal = false;
+    self.window.parent = Some(parent.id());
+    self
+  }
+
+  /// Makes the window owned by `owner`: it stays above `owner`, and is minimized and restored
+  /// with it.
+  ///
+  /// Replaces the parent set by [`WindowBuilder::with_parent`] or the owner set by
+  /// [`WindowBuilder::with_modal`], and is ignored for tool windows.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Creates a window owned by `owner`, which isn't shown in the taskbar and is
+  ///   destroyed with `owner`.
+  /// - **macOS:** Adds the window as a child window of `owner`, so it also moves with `owner`.
+  /// - **Linux:** Makes the window transient for `owner`, and destroyed with it. Whether it is
+  ///   minimized with `owner` depends on the window manager.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_owner(mut self, owner: &Window) -> WindowBuilder {
+    self.window.parent = None;
+    self.window.modal = false;
+    self.window.owner = Some(owner.id());
+    self
+  }
+
+  /// Makes the window a modal window owned by `owner`: `owner` can't be interacted with until
+  /// the modal window is closed, which is useful for settings or confirmation windows.
+  ///
+  /// Replaces the parent or owner set by [`WindowBuilder::with_parent`] and
+  /// [`WindowBuilder::with_owner`], and is ignored for tool windows.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** `owner` is disabled while the window is shown, and enabled again when it is
+  ///   hidden or destroyed.
+  /// - **macOS:** The window is shown as a sheet attached to the title bar of `owner`.
+  /// - **Linux:** The window is made modal and transient for `owner`. GTK blocks input to the
+  ///   other windows of the application while it is shown.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn with_modal(mut self, owner: &Window) -> WindowBuilder {
+    self.window.parent = None;
+    self.window.modal = true;
+    self.window.owner = Some(owner.id());
+    self
+  }
+
   /// Builds the window.
   ///
   /// Possible causes of error include denied permission, incompatible system, and lack of memory.
   #[inline]
   pub fn build<T: 'static>(
-    self,
+    mut self,
     window_target: &EventLoopWindowTarget<T>,
   ) -> Result<Window, OsError> {
+    if let Some(state) = self.window.state.take() {
+      state.apply(&mut self.window, window_target);
+    }
     platform_impl::Window::new(&window_target.p, self.window, self.platform_specific).map(
       |window| {
         window.request_redraw();
@@ -489,6 +887,26 @@ impl Window {
   pub fn request_redraw(&self) {
     self.window.request_redraw()
   }
+
+  /// Sets whether [`Window::request_redraw`] is paced by the frame clock of the compositor.
+  ///
+  /// When enabled, `RedrawRequested` is emitted at most once per frame, after the next vertical
+  /// blank of the display, instead of as soon as possible. This lets render loops that don't
+  /// present through a GPU swapchain, e.g. software renderers, draw at the refresh rate of the
+  /// display without busy looping.
+  ///
+  /// Redraws requested by the OS, e.g. when the window is resized, aren't delayed.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Waits for the next frame of the DWM with `DwmFlush`.
+  /// - **macOS:** Uses a `CVDisplayLink` on the active displays.
+  /// - **Linux:** Uses the GTK frame clock of the window.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_vsync_redraws(&self, vsync_redraws: bool) {
+    self.window.set_vsync_redraws(vsync_redraws)
+  }
 }
 
 /// Position and size functions.
@@ -502,7 +920,7 @@ impl Window {
   ///
   /// - **iOS:** Can only be called on the main thread. Returns the top left coordinates of the
   ///   window's [safe area] in the screen space coordinate system.
-  /// - **Android:** Always returns [`NotSupportedError`].
+  /// - **Android / Linux(Wayland):** Always returns [`NotSupportedError`].
   ///
   /// [safe area]: https://developer.apple.com/documentation/uikit/uiview/2891103-safeareainsets?language=objc
   #[inline]
@@ -524,8 +942,8 @@ impl Window {
   ///
   /// - **iOS:** Can only be called on the main thread. Returns the top left coordinates of the
   ///   window in the screen space coordinate system.
-  /// - **Android:** Always returns [`NotSupportedError`].
-  /// - **Linux(Wayland)**: Has no effect, since Wayland doesn't support a global cordinate system
+  /// - **Android / Linux(Wayland):** Always returns [`NotSupportedError`], since Wayland doesn't
+  ///   support a global coordinate system.
   #[inline]
   pub fn outer_position(&self) -> Result<PhysicalPosition<i32>, NotSupportedError> {
     self.window.outer_position()
@@ -540,7 +958,7 @@ impl Window {
   ///
   /// - **iOS:** Can only be called on the main thread. Sets the top left coordinates of the
   ///   window in the screen space coordinate system.
-  /// - **Android:** Unsupported.
+  /// - **Android / Linux(Wayland):** Unsupported.
   #[inline]
   pub fn set_outer_position<P: Into<Position>>(&self, position: P) {
     self.window.set_outer_position(position.into())
@@ -574,6 +992,29 @@ impl Window {
     self.window.set_inner_size(size.into())
   }
 
+  /// Requests a new inner size for the window, like [`Window::set_inner_size`], and tells
+  /// whether it was applied right away.
+  ///
+  /// Returns `Some(new_size)` when the window was resized before returning, in which case the
+  /// returned size is the one to render at. Returns `None` when the resize happens later, in
+  /// which case a [`WindowEvent::Resized`] event is emitted once it takes effect.
+  ///
+  /// Note that the window manager may still adjust the size, e.g. to honor the minimum or
+  /// maximum size, and report it in a later `Resized` event.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows / Linux:** Always returns `None`.
+  /// - **macOS:** Returns `None` when called from another thread than the main thread.
+  /// - **iOS / Android:** Unsupported, always returns `Some` with the current inner size.
+  ///
+  /// [`WindowEvent::Resized`]: crate::event::WindowEvent::Resized
+  #[inline]
+  #[must_use]
+  pub fn request_inner_size<S: Into<Size>>(&self, size: S) -> Option<PhysicalSize<u32>> {
+    self.window.request_inner_size(size.into())
+  }
+
   /// Returns the physical size of the entire window.
   ///
   /// These dimensions include the title bar and borders. If you don't want that (and you usually don't),
@@ -607,6 +1048,23 @@ impl Window {
   pub fn set_max_inner_size<S: Into<Size>>(&self, max_size: Option<S>) {
     self.window.set_max_inner_size(max_size.map(|s| s.into()))
   }
+
+  /// Makes the user resize the window by steps, for example the size of a character cell in a
+  /// terminal emulator, or lets it be resized freely again if `None` was provided.
+  ///
+  /// This only applies to the resizing done by the user, the window can still be given any
+  /// size with [`Window::set_inner_size`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The steps start from the minimum size on X11.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_resize_increments<S: Into<Size>>(&self, increments: Option<S>) {
+    self
+      .window
+      .set_resize_increments(increments.map(|s| s.into()))
+  }
 }
 
 /// Misc. attribute functions.
@@ -687,66 +1145,218 @@ impl Window {
     self.window.set_resizable(resizable)
   }
 
-  /// Sets the window to minimized or back
+  /// Sets whether the window's minimize button is enabled.
+  ///
+  /// The setting is kept across fullscreen and decoration changes.
   ///
   /// ## Platform-specific
   ///
+  /// - **Linux:** Only supported on X11, with the window manager hints that most window managers
+  ///   follow.
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn set_minimized(&self, minimized: bool) {
-    self.window.set_minimized(minimized);
+  pub fn set_minimizable(&self, minimizable: bool) {
+    self.window.set_minimizable(minimizable)
   }
 
-  /// Sets the window to maximized or back.
+  /// Sets whether the window's maximize button is enabled.
+  ///
+  /// The setting is kept across fullscreen and decoration changes.
   ///
   /// ## Platform-specific
   ///
+  /// - **Windows:** A window that isn't resizable can't be maximized either.
+  /// - **macOS:** Disables the zoom button, the fullscreen button is unaffected.
+  /// - **Linux:** Only supported on X11, with the window manager hints that most window managers
+  ///   follow.
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn set_maximized(&self, maximized: bool) {
-    self.window.set_maximized(maximized)
+  pub fn set_maximizable(&self, maximizable: bool) {
+    self.window.set_maximizable(maximizable)
   }
 
-  /// Gets the window's current maximized state.
+  /// Sets whether the window's close button is enabled.
+  ///
+  /// The setting is kept across fullscreen and decoration changes.
   ///
   /// ## Platform-specific
   ///
+  /// - **Windows:** Also disables the *Close* item of the window menu and <kbd>Alt</kbd>+<kbd>F4</kbd>.
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn is_maximized(&self) -> bool {
-    self.window.is_maximized()
+  pub fn set_closable(&self, closable: bool) {
+    self.window.set_closable(closable)
   }
 
-  /// Gets the window's current minimized state.
+  /// Sets which of the window's buttons are enabled, the others being disabled, see
+  /// [`Window::set_minimizable`], [`Window::set_maximizable`] and [`Window::set_closable`].
   ///
   /// ## Platform-specific
   ///
+  /// - **macOS:** The buttons are hidden while all of them are disabled.
+  /// - **Linux:** The minimize and maximize buttons are only disabled on X11, with the window
+  ///   manager hints that most window managers follow.
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn is_minimized(&self) -> bool {
-    self.window.is_minimized()
+  pub fn set_enabled_buttons(&self, buttons: WindowButtons) {
+    self
+      .window
+      .set_minimizable(buttons.contains(WindowButtons::MINIMIZE));
+    self
+      .window
+      .set_maximizable(buttons.contains(WindowButtons::MAXIMIZE));
+    self
+      .window
+      .set_closable(buttons.contains(WindowButtons::CLOSE));
   }
 
-  /// Gets the window's current visibility state.
+  /// Sets the window to minimized or back
   ///
   /// ## Platform-specific
   ///
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn is_visible(&self) -> bool {
-    self.window.is_visible()
+  pub fn set_minimized(&self, minimized: bool) {
+    self.window.set_minimized(minimized);
   }
 
-  /// Gets the window's current resizable state.
+  /// Sets whether minimizing the window hides it instead, removing it from the taskbar.
+  ///
+  /// When the user minimizes the window, it is hidden and [`WindowEvent::MinimizedToTray`] is
+  /// emitted, so that the app can update its tray. Restore the window with
+  /// [`Window::set_visible`], e.g. from a tray menu item.
   ///
   /// ## Platform-specific
   ///
+  /// - **Windows / macOS:** [`Window::set_minimized`] still minimizes the window.
+  /// - **Linux:** [`Window::set_minimized`] hides the window as well.
   /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::MinimizedToTray`]: crate::event::WindowEvent::MinimizedToTray
   #[inline]
-  pub fn is_resizable(&self) -> bool {
+  pub fn set_minimize_to_tray(&self, minimize_to_tray: bool) {
+    self.window.set_minimize_to_tray(minimize_to_tray);
+  }
+
+  /// Sets the window to maximized or back.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_maximized(&self, maximized: bool) {
+    self.window.set_maximized(maximized)
+  }
+
+  /// Gets the window's current maximized state.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_maximized(&self) -> bool {
+    self.window.is_maximized()
+  }
+
+  /// Returns the current geometry and state of the window, to be saved and later restored
+  /// with [`WindowBuilder::with_state`].
+  ///
+  /// The position and size are those the window has when it's neither maximized nor
+  /// fullscreen, so restoring the state puts the window back at that geometry before
+  /// maximizing it or making it fullscreen again.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux(Wayland) / Android:** The position is always `(0, 0)`.
+  /// - **iOS / Android:** The position and size are those of the window as it currently is.
+  /// - **iOS:** Can only be called on the main thread.
+  #[inline]
+  pub fn outer_state(&self) -> WindowState {
+    let (position, size) = self.window.restored_geometry();
+    WindowState {
+      position,
+      size,
+      scale_factor: self.scale_factor(),
+      maximized: self.is_maximized(),
+      fullscreen: self.fullscreen().is_some(),
+      monitor: self.current_monitor().and_then(|monitor| monitor.name()),
+    }
+  }
+
+  /// Gets the window's current minimized state.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_minimized(&self) -> bool {
+    self.window.is_minimized()
+  }
+
+  /// Gets the window's current visibility state.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_visible(&self) -> bool {
+    self.window.is_visible()
+  }
+
+  /// Gets the window's current resizable state.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_resizable(&self) -> bool {
     self.window.is_resizable()
   }
 
+  /// Gets whether the window's minimize button is enabled.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_minimizable(&self) -> bool {
+    self.window.is_minimizable()
+  }
+
+  /// Gets whether the window's maximize button is enabled.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_maximizable(&self) -> bool {
+    self.window.is_maximizable()
+  }
+
+  /// Gets whether the window's close button is enabled.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn is_closable(&self) -> bool {
+    self.window.is_closable()
+  }
+
+  /// Gets which of the window's buttons are enabled.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn enabled_buttons(&self) -> WindowButtons {
+    let mut buttons = WindowButtons::empty();
+    buttons.set(WindowButtons::MINIMIZE, self.window.is_minimizable());
+    buttons.set(WindowButtons::MAXIMIZE, self.window.is_maximizable());
+    buttons.set(WindowButtons::CLOSE, self.window.is_closable());
+    buttons
+  }
+
   /// Gets the window's current decoration state.
   ///
   /// ## Platform-specific
@@ -774,7 +1384,11 @@ impl Window {
   ///   The dock and the menu bar are always disabled in fullscreen mode.
   /// - **iOS:** Can only be called on the main thread.
   /// - **Windows:** Screen saver is disabled in fullscreen mode.
-  /// - **Linux:** The window will only fullscreen to current monitor no matter which enum variant.
+  /// - **Linux:** `Fullscreen::Exclusive` switches the monitor to the video mode through XRandR,
+  ///   and restores the previous mode when leaving fullscreen or destroying the window. Modes
+  ///   bigger than the X screen are refused, since the screen isn't resized. On Wayland, clients
+  ///   can't change the video mode, so it falls back to borderless fullscreen on the monitor of
+  ///   the video mode.
   /// - **Android:** Unsupported.
   #[inline]
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
@@ -792,6 +1406,25 @@ impl Window {
     self.window.fullscreen()
   }
 
+  /// Gets the window's actual fullscreen state.
+  ///
+  /// Unlike [`Window::fullscreen`], which returns the state last passed to
+  /// [`Window::set_fullscreen`], the [`VideoMode`] in `Fullscreen::Exclusive` is read back
+  /// from the display. Comparing the two tells whether the OS applied a different mode
+  /// than the one requested, e.g. a lower refresh rate.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The video mode isn't read back from XRandR, so this is the same as
+  ///   [`Window::fullscreen`].
+  /// - **iOS:** Exclusive fullscreen is unsupported, so this is the same as
+  ///   [`Window::fullscreen`].
+  /// - **Android:** Will always return `None`.
+  #[inline]
+  pub fn current_fullscreen(&self) -> Option<Fullscreen> {
+    self.window.current_fullscreen()
+  }
+
   /// Turn window decorations on or off.
   ///
   /// ## Platform-specific
@@ -804,32 +1437,212 @@ impl Window {
     self.window.set_decorations(decorations)
   }
 
-  /// Change whether or not the window will always be below other windows.
+  /// Sets the radius of the window corners, in logical pixels. `None` gives square corners.
   ///
   /// ## Platform-specific
   ///
-  /// - **Windows**: There is no guarantee that the window will be the bottom most but it will try to be.
-  /// - **iOS / Android:** Unsupported.
+  /// - **Linux:** Applies a rounded shape and input region to the window. Wayland compositors
+  ///   only honor the input region, so corners are best painted transparent as well.
+  /// - **Windows:** Only the corner preference is applied, the radius itself is chosen by the system.
+  ///   Requires Windows 11.
+  /// - **macOS / iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_corner_radius(&self, radius: Option<f64>) {
+    self.window.set_corner_radius(radius)
+  }
+
+  /// Change whether or not the window will always be below other windows.
+  ///
+  /// Shorthand for [`Window::set_window_level`] with [`WindowLevel::Desktop`], or
+  /// [`WindowLevel::Normal`] when `false`.
   #[inline]
   pub fn set_always_on_bottom(&self, always_on_bottom: bool) {
-    self.window.set_always_on_bottom(always_on_bottom)
+    self.set_window_level(if always_on_bottom {
+      WindowLevel::Desktop
+    } else {
+      WindowLevel::Normal
+    })
   }
 
   /// Change whether or not the window will always be on top of other windows.
   ///
+  /// Shorthand for [`Window::set_window_level`] with [`WindowLevel::Floating`], or
+  /// [`WindowLevel::Normal`] when `false`.
+  #[inline]
+  pub fn set_always_on_top(&self, always_on_top: bool) {
+    self.set_window_level(if always_on_top {
+      WindowLevel::Floating
+    } else {
+      WindowLevel::Normal
+    })
+  }
+
+  /// Change the stacking level of the window, relative to the other windows of the desktop.
+  ///
   /// ## Platform-specific
   ///
+  /// - **Windows:** There is no guarantee that a [`WindowLevel::Desktop`] window will be the
+  ///   bottom most but it will try to be. [`WindowLevel::PanelOverlay`] and
+  ///   [`WindowLevel::ScreenSaver`] are the same as [`WindowLevel::Floating`], as every topmost
+  ///   window shares one band, and can't cover exclusive fullscreen apps.
+  /// - **Linux:** [`WindowLevel::PanelOverlay`] and [`WindowLevel::ScreenSaver`] are the same as
+  ///   [`WindowLevel::Floating`], keeping the window above the others, as the window manager
+  ///   decides which windows cover the panels of the desktop.
   /// - **iOS / Android:** Unsupported.
   #[inline]
-  pub fn set_always_on_top(&self, always_on_top: bool) {
-    self.window.set_always_on_top(always_on_top)
+  pub fn set_window_level(&self, window_level: WindowLevel) {
+    self.window.set_window_level(window_level)
+  }
+
+  /// Change whether or not the window is shown on every workspace, or virtual desktop, instead of
+  /// only the one it was opened on.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** The window manager may not support it.
+  /// - **Windows:** Unsupported, `IVirtualDesktopManager` can only move a window to another
+  ///   virtual desktop, pinning it to all of them isn't part of the documented API.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_visible_on_all_workspaces(&self, visible: bool) {
+    self.window.set_visible_on_all_workspaces(visible)
+  }
+
+  /// Declares the client areas that move the window when dragged with the left mouse button,
+  /// like a titlebar. Double-clicking them maximizes or restores the window. Clicks within
+  /// `exclusions`, e.g. on buttons in a custom titlebar, are delivered to the app as usual.
+  ///
+  /// Replaces the previously declared regions. Rectangles are relative to the top left corner
+  /// of the client area. Mouse presses in drag regions are not reported as
+  /// [`WindowEvent::MouseInput`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Drag regions are reported as `HTCAPTION` by `WM_NCHITTEST`, so they also
+  ///   show the system menu on right click.
+  /// - **macOS:** Double-clicking follows the user's titlebar double-click preference.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::MouseInput`]: crate::event::WindowEvent::MouseInput
+  #[inline]
+  pub fn set_drag_regions(&self, regions: Vec<Rectangle>, exclusions: Vec<Rectangle>) {
+    self.window.set_drag_regions(regions, exclusions)
+  }
+
+  /// Shows or hides the system drop target highlight around the window, to indicate that
+  /// the item being dragged over it can be dropped. The highlight is cleared automatically
+  /// when the drag leaves the window or the item is dropped.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Colors the window border with the system selection color. Requires
+  ///   Windows 11.
+  /// - **macOS:** Draws a focus ring around the content view.
+  /// - **Linux:** Uses the GTK drag highlight.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_drop_highlight(&self, highlight: bool) {
+    self.window.set_drop_highlight(highlight)
+  }
+
+  /// Sets the color the window is filled with before the app paints it. `None` restores the
+  /// OS default background.
+  ///
+  /// The alpha channel is only honored for windows created with
+  /// [`WindowBuilder::with_transparent`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The alpha channel is ignored.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_background_color(&self, color: Option<RGBA>) {
+    self.window.set_background_color(color)
+  }
+
+  /// Captures all keyboard input for this window while it is focused, including system
+  /// shortcuts such as <kbd>Alt</kbd>+<kbd>Tab</kbd> or the Windows key, which are then
+  /// delivered as regular [`WindowEvent::KeyboardInput`] events. Useful for kiosk and
+  /// remote desktop apps.
+  ///
+  /// Only one window can grab the keyboard at a time; grabbing with another window
+  /// releases the previous grab.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Installs a low-level keyboard hook. <kbd>Ctrl</kbd>+<kbd>Alt</kbd>+<kbd>Del</kbd>
+  ///   can never be captured.
+  /// - **macOS:** Installs a `CGEventTap`, which requires the app to be granted the
+  ///   Accessibility permission in *System Settings > Privacy & Security*. Until the user
+  ///   grants it, this has no effect.
+  /// - **Linux:** Grabs the keyboard through the GDK seat. The window must be visible. On
+  ///   Wayland, the compositor may ask the user to allow the shortcuts to be inhibited.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::KeyboardInput`]: crate::event::WindowEvent::KeyboardInput
+  #[inline]
+  pub fn set_keyboard_grab(&self, grab: bool) {
+    self.window.set_keyboard_grab(grab)
+  }
+
+  /// Overrides the [`DeviceEvent`] filter mode set with
+  /// [`EventLoopWindowTarget::set_device_event_filter`] while this window is focused.
+  ///
+  /// Lets e.g. a 3D view receive raw mouse motion while other windows of the app don't.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux / iOS / Android:** Unsupported.
+  ///
+  /// [`DeviceEvent`]: crate::event::DeviceEvent
+  #[inline]
+  pub fn set_device_event_filter(&self, _filter: DeviceEventFilter) {
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    self.window.set_device_event_filter(_filter);
+  }
+
+  /// Prevents the window contents from being captured by other apps, e.g. screenshots and
+  /// screen recordings.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Uses `WDA_EXCLUDEFROMCAPTURE`, which requires Windows 10 version 2004.
+  ///   On older versions the window is captured as a black rectangle instead.
+  /// - **macOS:** Sets the sharing type of the window to `NSWindowSharingNone`.
+  /// - **Linux:** Unsupported, neither X11 nor Wayland let a window opt out of being captured.
+  ///   A warning is logged and the window can still be captured.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_content_protection(&self, protected: bool) {
+    self.window.set_content_protection(protected)
+  }
+
+  /// Sets the opacity of the whole window, decorations included, from `0.0` (invisible) to
+  /// `1.0` (opaque). Values out of this range are clamped.
+  ///
+  /// Unlike [`WindowBuilder::with_transparent`], the content doesn't need to be drawn with an
+  /// alpha channel, which makes it suited to fading windows in and out.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Makes the window a layered window while it's translucent.
+  /// - **Linux:** Needs a compositing window manager on X11.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_opacity(&self, opacity: f64) {
+    self.window.set_opacity(opacity)
   }
 
   /// Sets the window icon. On Windows and Linux, this is typically the small icon in the top-left
   /// corner of the title bar.
   ///
+  /// The icon can be changed at any time, e.g. to render a count of unread items into it, and
+  /// `None` removes it.
+  ///
   /// ## Platform-specific
   ///
+  /// - **Linux:** The icon is also the one of the window in the taskbar and the window switcher.
+  ///   Without an icon, the window falls back to the default icon of GTK, if any.
   /// - **iOS / Android / macOS:** Unsupported.
   ///
   /// On Windows, this sets `ICON_SMALL`. The base size for a window icon is 16x16, but it's
@@ -841,31 +1654,159 @@ impl Window {
 
   /// Sets location of IME candidate box in client area coordinates relative to the top left.
   ///
+  /// Same as [`Window::set_ime_cursor_area`] with an empty size, which lets the candidate box
+  /// overlap the text being typed.
+  ///
   /// ## Platform-specific
   ///
   /// - **iOS / Android:** Unsupported.
+  #[deprecated = "Use `Window::set_ime_cursor_area` instead"]
   #[inline]
   pub fn set_ime_position<P: Into<Position>>(&self, position: P) {
-    self.window.set_ime_position(position.into())
+    self.set_ime_cursor_area(position, PhysicalSize::new(0, 0))
+  }
+
+  /// Sets the area of the text cursor, or of the text being composed, in client area
+  /// coordinates relative to the top left. The IME places its candidate box next to the
+  /// area without covering it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Only used by input methods that show a candidate box.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_ime_cursor_area<P: Into<Position>, S: Into<Size>>(&self, position: P, size: S) {
+    self
+      .window
+      .set_ime_cursor_area(position.into(), size.into())
+  }
+
+  /// Sets whether key presses are sent to the input method, which is the default.
+  ///
+  /// Turn it off while the window doesn't accept text, e.g. in a game, so that the keys don't
+  /// start a composition. Disallowing it cancels the current composition.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** Also stops dead keys from being composed.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_ime_allowed(&self, allowed: bool) {
+    self.window.set_ime_allowed(allowed)
+  }
+
+  /// Reset the dead key state of the keyboard.
+  ///
+  /// This is useful when a dead key is bound to trigger an action, or when focus changes in the
+  /// middle of a composition. Calling it clears any pending dead key or composition so that
+  /// follow-up text input won't be affected by it.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn reset_dead_keys(&self) {
+    self.window.reset_dead_keys()
   }
 
   /// Requests user attention to the window, this has no effect if the application
   /// is already focused. How requesting for user attention manifests is platform dependent,
   /// see `UserAttentionType` for details.
   ///
-  /// Providing `None` will unset the request for user attention. Unsetting the request for
-  /// user attention might not be done automatically by the WM when the window receives input.
+  /// Providing `None` will unset the request for user attention, like
+  /// [`Window::clear_user_attention`]. Unsetting the request for user attention might not be
+  /// done automatically by the WM when the window receives input.
   ///
   /// ## Platform-specific
   ///
   /// - **iOS / Android:** Unsupported.
-  /// - **macOS:** `None` has no effect.
-  /// - **Linux:** Urgency levels have the same effect.
   #[inline]
   pub fn request_user_attention(&self, request_type: Option<UserAttentionType>) {
     self.window.request_user_attention(request_type)
   }
 
+  /// Cancels a request for user attention made with [`Window::request_user_attention`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn clear_user_attention(&self) {
+    self.window.request_user_attention(None)
+  }
+
+  /// Sets whether the window has the drop shadow drawn by the system, which pixel-precise
+  /// overlays usually don't want. Windows have one by default.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only undecorated windows can have their shadow removed, which also removes
+  ///   their rounded corners on Windows 11.
+  /// - **Linux:** Only removes the shadow that GTK draws around client-side decorations, e.g. on
+  ///   Wayland, not the one some X11 window managers draw themselves.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_shadow(&self, shadow: bool) {
+    self.window.set_shadow(shadow)
+  }
+
+  /// Draws a frosted glass effect behind the transparent parts of the window, or removes it with
+  /// `None`. The window has to be created with [`WindowBuilder::with_transparent`] for the
+  /// effect to show through.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** [`BlurEffect::Acrylic`] and [`BlurEffect::Mica`] use the system backdrops
+  ///   of Windows 11 22H2 and newer, and fall back to [`BlurEffect::Blur`] on older versions,
+  ///   which uses the undocumented accent blur of Windows 10. The DWM frame is extended over the
+  ///   whole window while an effect is shown, and only reset when the effect is removed.
+  /// - **macOS:** Adds an `NSVisualEffectView` behind the content of the window, using the under
+  ///   window background, HUD window and window background materials. Requires macOS 10.14.
+  /// - **Linux:** Only supported on X11 by KWin, through the `_KDE_NET_WM_BLUR_BEHIND_REGION`
+  ///   hint, and all effects are the same blur. Unsupported on Wayland and by other window
+  ///   managers, such as GNOME's Mutter, which have no protocol for it.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_blur(&self, effect: Option<BlurEffect>) {
+    self.window.set_blur(effect)
+  }
+
+  /// Shows the progress of a long running task, e.g. a download, on the taskbar button or dock
+  /// icon of the application.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Uses `ITaskbarList3`, and shows the paused and error states in yellow and red.
+  ///   The progress can only be shown once the taskbar button exists, which is shortly after the
+  ///   window is first shown.
+  /// - **macOS:** The progress is shown on the dock icon, which all windows share. The paused and
+  ///   error states look the same as the normal one.
+  /// - **Linux:** Uses the Unity `LauncherEntry` D-Bus API, which only some docks and desktops
+  ///   implement, e.g. KDE Plasma and Ubuntu. The progress is shared by all windows, indeterminate
+  ///   progress is shown with its last value and the error state marks the launcher urgent.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_progress_bar(&self, progress: ProgressBarState) {
+    self.window.set_progress_bar(progress)
+  }
+
+  /// Shows a count, e.g. of unread messages, on the taskbar button or dock icon of the
+  /// application, or hides it with `None`.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Shown as an overlay icon on the taskbar button of this window, with counts
+  ///   above 9 shown as `9+`. Like the progress bar, it can only be shown once the taskbar button
+  ///   exists.
+  /// - **macOS:** Shown as the badge of the dock icon, which all windows share.
+  /// - **Linux:** Uses the Unity `LauncherEntry` D-Bus API like [`Window::set_progress_bar`], and
+  ///   shares its `.desktop` file name.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_badge_count(&self, count: Option<u64>) {
+    self.window.set_badge_count(count)
+  }
+
   /// Hides the menu associated with the window
   ///
   /// ## Platform-specific
@@ -905,6 +1846,22 @@ impl Window {
   pub fn theme(&self) -> Theme {
     self.window.theme()
   }
+
+  /// Forces the theme of the window, or makes it follow the system theme again if `None` was
+  /// provided, see [`WindowBuilder::with_theme`].
+  ///
+  /// [`WindowEvent::ThemeChanged`](crate::event::WindowEvent::ThemeChanged) isn't emitted while
+  /// the theme is forced.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Applies the dark or light variant of the GTK theme, which is shared by every
+  ///   window of the application.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn set_theme(&self, theme: Option<Theme>) {
+    self.window.set_theme(theme)
+  }
 }
 
 /// Cursor functions.
@@ -919,28 +1876,40 @@ impl Window {
     self.window.set_cursor_icon(cursor);
   }
 
-  /// Changes the position of the cursor in window coordinates.
+  /// Changes the position of the cursor in window coordinates, i.e. relative to the top left
+  /// corner of the client area, e.g. to recenter it for first-person controls.
+  ///
+  /// See [`EventLoopWindowTarget::cursor_position`] to read the position back.
   ///
   /// ## Platform-specific
   ///
+  /// - **macOS:** The cursor doesn't move for a short moment after being warped, unless the
+  ///   mouse keeps being associated with it, which tao does.
+  /// - **Linux(Wayland):** Always returns an [`ExternalError::NotSupported`]. Wayland only lets
+  ///   clients hint the cursor position while the pointer is locked by the pointer constraints
+  ///   protocol, which GTK doesn't expose.
   /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
   #[inline]
   pub fn set_cursor_position<P: Into<Position>>(&self, position: P) -> Result<(), ExternalError> {
     self.window.set_cursor_position(position.into())
   }
 
-  /// Grabs the cursor, preventing it from leaving the window.
+  /// Grabs the cursor, confining it to the window or locking it in place, see
+  /// [`CursorGrabMode`].
   ///
   /// There's no guarantee that the cursor will be hidden. You should
   /// hide it by yourself if you want so.
   ///
   /// ## Platform-specific
   ///
-  /// - **macOS:** This locks the cursor in a fixed location, which looks visually awkward.
+  /// - **Windows:** The cursor is released while the window isn't focused.
+  /// - **macOS:** [`CursorGrabMode::Confined`] returns an [`ExternalError::NotSupported`].
+  /// - **Linux:** [`CursorGrabMode::Confined`] is only supported on X11, and
+  ///   [`CursorGrabMode::Locked`] returns an [`ExternalError::NotSupported`].
   /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
   #[inline]
-  pub fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
-    self.window.set_cursor_grab(grab)
+  pub fn set_cursor_grab(&self, mode: CursorGrabMode) -> Result<(), ExternalError> {
+    self.window.set_cursor_grab(mode)
   }
 
   /// Modifies the cursor's visibility.
@@ -972,6 +1941,35 @@ impl Window {
     self.window.drag_window()
   }
 
+  /// Resizes the window from the given edge or corner with the left mouse button until the
+  /// button is released, for the resize handles of a window without decorations.
+  ///
+  /// There's no guarantee that this will work unless the left mouse button was pressed
+  /// immediately before this function is called.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS / iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+  #[inline]
+  pub fn drag_resize_window(&self, direction: ResizeDirection) -> Result<(), ExternalError> {
+    self.window.drag_resize_window(direction)
+  }
+
+  /// Starts dragging `data` out of the window with the left mouse button, so it can be dropped
+  /// into the file manager or other applications.
+  ///
+  /// There's no guarantee that this will work unless the left mouse button was pressed
+  /// immediately before this function is called.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Blocks until the item is dropped or the drag is cancelled.
+  /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+  #[inline]
+  pub fn start_drag(&self, data: DragData) -> Result<(), ExternalError> {
+    self.window.start_drag(data)
+  }
+
   /// Modifies whether the window catches cursor events.
   ///
   /// If `true`, the events are passed through the window such that any other window behind it receives them.
@@ -979,11 +1977,36 @@ impl Window {
   ///
   /// ## Platform-specific
   ///
+  /// - **Linux:** Empties the input region of the window, which takes precedence over the one set
+  ///   by [`Window::set_corner_radius`].
   /// - **iOS / Android:** Always returns an [`ExternalError::NotSupported`]
   #[inline]
   pub fn set_ignore_cursor_events(&self, ignore: bool) -> Result<(), ExternalError> {
     self.window.set_ignore_cursor_events(ignore)
   }
+
+  /// Limits the parts of the window that catch cursor events to `regions`, passing the events
+  /// elsewhere through to the windows behind it, e.g. for overlays that are only partly
+  /// interactive. `None` makes the whole window catch cursor events again.
+  ///
+  /// Replaces the previously set regions. Rectangles are relative to the top left corner of the
+  /// client area. [`Window::set_ignore_cursor_events`] takes precedence over the regions.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Only applies to the client area, the window frame always catches cursor
+  ///   events. The window stops letting the cursor through shortly after it moves back over a
+  ///   region, as the system can't hit-test other applications' windows for us.
+  /// - **Linux:** The regions are intersected with the input region of
+  ///   [`Window::set_corner_radius`].
+  /// - **macOS / iOS / Android:** Always returns an [`ExternalError::NotSupported`].
+  #[inline]
+  pub fn set_cursor_hittest_region(
+    &self,
+    regions: Option<&[Rectangle]>,
+  ) -> Result<(), ExternalError> {
+    self.window.set_cursor_hittest_region(regions)
+  }
 }
 
 /// Monitor info functions.
@@ -1031,6 +2054,54 @@ impl Window {
   }
 }
 
+/// Accessibility functions.
+#[cfg(feature = "accesskit")]
+impl Window {
+  /// Exposes the window to assistive technologies, such as screen readers, as the tree of
+  /// accessibility nodes returned by `source`, for custom-rendered content to be readable.
+  ///
+  /// `source` is called the first time an assistive technology requests the tree, which may never
+  /// happen, and must return the full tree. The tree is then kept up to date with
+  /// [`Window::update_accessibility`], and the actions requested on its nodes are reported as
+  /// [`WindowEvent::AccessibilityActionRequested`]. Replaces the previous source, if any.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** Exposed through UI Automation, `source` being called while handling
+  ///   `WM_GETOBJECT` on the thread of the event loop.
+  /// - **macOS:** Exposed by subclassing the content view of the window, `source` being called
+  ///   on the main thread. Must be set before the window is first shown or focused, e.g. with
+  ///   [`WindowBuilder::with_visible`]`(false)`.
+  /// - **Linux:** Exposed through AT-SPI, `source` being called on a thread of AccessKit.
+  /// - **iOS / Android:** Unsupported.
+  ///
+  /// [`WindowEvent::AccessibilityActionRequested`]: crate::event::WindowEvent::AccessibilityActionRequested
+  /// [`WindowBuilder::with_visible`]: crate::window::WindowBuilder::with_visible
+  #[inline]
+  pub fn set_accessibility_source<F>(&self, source: F)
+  where
+    F: FnMut() -> crate::accesskit::TreeUpdate + Send + 'static,
+  {
+    self.window.set_accessibility_source(Box::new(source))
+  }
+
+  /// Applies the changes of the accessibility tree returned by `update`, which is only called
+  /// once an assistive technology requested the tree of [`Window::set_accessibility_source`].
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **macOS:** `update` is called right away when not on the main thread, before the changes
+  ///   are applied on it.
+  /// - **iOS / Android:** Unsupported.
+  #[inline]
+  pub fn update_accessibility<F>(&self, update: F)
+  where
+    F: FnOnce() -> crate::accesskit::TreeUpdate,
+  {
+    self.window.update_accessibility(update)
+  }
+}
+
 // Safety: objc runtime calls are unsafe
 unsafe impl HasRawWindowHandle for Window {
   /// Returns a `raw_window_handle::RawWindowHandle` for the Window
@@ -1053,9 +2124,101 @@ unsafe impl HasRawDisplayHandle for Window {
     self.window.raw_display_handle()
   }
 }
+
+#[cfg(feature = "rwh_06")]
+impl rwh_06::HasWindowHandle for Window {
+  /// Returns a [`rwh_06::WindowHandle`] for the Window, see [`HasRawWindowHandle`].
+  fn window_handle(&self) -> Result<rwh_06::WindowHandle<'_>, rwh_06::HandleError> {
+    let raw = raw_window_handle_06(self.window.raw_window_handle())?;
+    // Safety: the window outlives the borrow of the handle.
+    Ok(unsafe { rwh_06::WindowHandle::borrow_raw(raw) })
+  }
+}
+
+#[cfg(feature = "rwh_06")]
+impl rwh_06::HasDisplayHandle for Window {
+  /// Returns a [`rwh_06::DisplayHandle`] used by the [`EventLoop`] that created a window.
+  ///
+  /// [`EventLoop`]: crate::event_loop::EventLoop
+  fn display_handle(&self) -> Result<rwh_06::DisplayHandle<'_>, rwh_06::HandleError> {
+    let raw = raw_display_handle_06(self.window.raw_display_handle())?;
+    // Safety: the display outlives the windows created on it.
+    Ok(unsafe { rwh_06::DisplayHandle::borrow_raw(raw) })
+  }
+}
+
+/// Converts a raw window handle of raw-window-handle 0.5 to 0.6, which doesn't allow null handles.
+#[cfg(feature = "rwh_06")]
+fn raw_window_handle_06(
+  handle: raw_window_handle::RawWindowHandle,
+) -> Result<rwh_06::RawWindowHandle, rwh_06::HandleError> {
+  use raw_window_handle::RawWindowHandle;
+  use std::{num::NonZeroIsize, ptr::NonNull};
+
+  let unavailable = rwh_06::HandleError::Unavailable;
+  Ok(match handle {
+    RawWindowHandle::Win32(handle) => {
+      let hwnd = NonZeroIsize::new(handle.hwnd as isize).ok_or(unavailable)?;
+      let mut win32 = rwh_06::Win32WindowHandle::new(hwnd);
+      win32.hinstance = NonZeroIsize::new(handle.hinstance as isize);
+      win32.into()
+    }
+    RawWindowHandle::AppKit(handle) => {
+      let ns_view = NonNull::new(handle.ns_view).ok_or(unavailable)?;
+      rwh_06::AppKitWindowHandle::new(ns_view).into()
+    }
+    RawWindowHandle::UiKit(handle) => {
+      let ui_view = NonNull::new(handle.ui_view).ok_or(unavailable)?;
+      let mut ui_kit = rwh_06::UiKitWindowHandle::new(ui_view);
+      ui_kit.ui_view_controller = NonNull::new(handle.ui_view_controller);
+      ui_kit.into()
+    }
+    RawWindowHandle::Xlib(handle) => {
+      if handle.window == 0 {
+        return Err(unavailable);
+      }
+      let mut xlib = rwh_06::XlibWindowHandle::new(handle.window);
+      xlib.visual_id = handle.visual_id;
+      xlib.into()
+    }
+    RawWindowHandle::Wayland(handle) => {
+      let surface = NonNull::new(handle.surface).ok_or(unavailable)?;
+      rwh_06::WaylandWindowHandle::new(surface).into()
+    }
+    RawWindowHandle::AndroidNdk(handle) => {
+      let a_native_window = NonNull::new(handle.a_native_window).ok_or(unavailable)?;
+      rwh_06::AndroidNdkWindowHandle::new(a_native_window).into()
+    }
+    _ => return Err(rwh_06::HandleError::NotSupported),
+  })
+}
+
+/// Converts a raw display handle of raw-window-handle 0.5 to 0.6.
+#[cfg(feature = "rwh_06")]
+pub(crate) fn raw_display_handle_06(
+  handle: RawDisplayHandle,
+) -> Result<rwh_06::RawDisplayHandle, rwh_06::HandleError> {
+  use std::ptr::NonNull;
+
+  Ok(match handle {
+    RawDisplayHandle::Windows(_) => rwh_06::WindowsDisplayHandle::new().into(),
+    RawDisplayHandle::AppKit(_) => rwh_06::AppKitDisplayHandle::new().into(),
+    RawDisplayHandle::UiKit(_) => rwh_06::UiKitDisplayHandle::new().into(),
+    RawDisplayHandle::Xlib(handle) => {
+      rwh_06::XlibDisplayHandle::new(NonNull::new(handle.display), handle.screen).into()
+    }
+    RawDisplayHandle::Wayland(handle) => {
+      let display = NonNull::new(handle.display).ok_or(rwh_06::HandleError::Unavailable)?;
+      rwh_06::WaylandDisplayHandle::new(display).into()
+    }
+    RawDisplayHandle::Android(_) => rwh_06::AndroidDisplayHandle::new().into(),
+    _ => return Err(rwh_06::HandleError::NotSupported),
+  })
+}
+
 /// Describes the appearance of the mouse cursor.
 #[non_exhaustive]
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum CursorIcon {
   /// The platform-dependent default cursor.
@@ -1111,6 +2274,10 @@ pub enum CursorIcon {
   NwseResize,
   ColResize,
   RowResize,
+
+  /// A cursor created from an image.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  Custom(CustomCursor),
 }
 
 impl Default for CursorIcon {
@@ -1119,6 +2286,142 @@ impl Default for CursorIcon {
   }
 }
 
+/// A cursor created from an image, see [`CursorIcon::Custom`].
+///
+/// Cloning it is cheap, the image being shared.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CustomCursor {
+  pub(crate) image: Arc<CursorImage>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) struct CursorImage {
+  pub(crate) rgba: RgbaIcon,
+  pub(crate) hotspot_x: u32,
+  pub(crate) hotspot_y: u32,
+}
+
+impl CustomCursor {
+  /// Creates a cursor from 32bpp RGBA data, `(hotspot_x, hotspot_y)` being the pixel pointing at
+  /// the position of the cursor.
+  ///
+  /// The length of `rgba` must be divisible by 4, `width * height` must equal `rgba.len() / 4`,
+  /// and the hotspot must be within the image. Otherwise, this will return a `BadIcon` error.
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Windows:** The image is drawn at its size in physical pixels.
+  /// - **macOS / Linux:** The image is scaled by the scale factor of the window.
+  pub fn from_rgba(
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    hotspot_x: u32,
+    hotspot_y: u32,
+  ) -> Result<Self, BadIcon> {
+    let rgba = RgbaIcon::from_rgba(rgba, width, height)?;
+    if hotspot_x >= width || hotspot_y >= height {
+      return Err(BadIcon::HotspotOutOfBounds {
+        width,
+        height,
+        hotspot_x,
+        hotspot_y,
+      });
+    }
+    Ok(Self {
+      image: Arc::new(CursorImage {
+        rgba,
+        hotspot_x,
+        hotspot_y,
+      }),
+    })
+  }
+}
+
+bitflags! {
+  /// The buttons of the title bar of a window, see [`Window::set_enabled_buttons`].
+  pub struct WindowButtons: u32 {
+    /// The close button.
+    const CLOSE = 1 << 0;
+    /// The minimize button.
+    const MINIMIZE = 1 << 1;
+    /// The maximize, or zoom, button.
+    const MAXIMIZE = 1 << 2;
+  }
+}
+
+/// The edge or corner of a window to resize it from, see [`Window::drag_resize_window`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ResizeDirection {
+  /// The right edge.
+  East,
+  /// The top edge.
+  North,
+  /// The top right corner.
+  NorthEast,
+  /// The top left corner.
+  NorthWest,
+  /// The bottom edge.
+  South,
+  /// The bottom right corner.
+  SouthEast,
+  /// The bottom left corner.
+  SouthWest,
+  /// The left edge.
+  West,
+}
+
+/// The stacking level of a window, see [`Window::set_window_level`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WindowLevel {
+  /// Below the normal windows.
+  Desktop,
+  /// The level of most windows.
+  Normal,
+  /// Above the normal windows, for example for a utility palette.
+  Floating,
+  /// Above the floating windows and the panels of the desktop, like the dock or the taskbar.
+  PanelOverlay,
+  /// Above every other window, including fullscreen apps, for example for the overlay of a
+  /// screen recorder.
+  ScreenSaver,
+}
+
+impl Default for WindowLevel {
+  fn default() -> Self {
+    WindowLevel::Normal
+  }
+}
+
+/// How the cursor is grabbed by a window, see [`Window::set_cursor_grab`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CursorGrabMode {
+  /// The cursor moves freely.
+  None,
+  /// The cursor can't leave the window.
+  Confined,
+  /// The cursor is locked in place, for example for the camera of a first person game, the
+  /// motion of the mouse still being reported by
+  /// [`DeviceEvent::MouseMotion`](crate::event::DeviceEvent::MouseMotion).
+  ///
+  /// ## Platform-specific
+  ///
+  /// - **Linux:** Unsupported.
+  Locked,
+}
+
+impl Default for CursorGrabMode {
+  fn default() -> Self {
+    CursorGrabMode::None
+  }
+}
+
 /// Fullscreen modes.
 #[non_exhaustive]
 #[allow(clippy::large_enum_variant)]
@@ -1130,6 +2433,146 @@ pub enum Fullscreen {
   Borderless(Option<MonitorHandle>),
 }
 
+/// Where to place a window within a monitor's work area.
+///
+/// See [`WindowBuilder::with_position_on_monitor`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MonitorPlacement {
+  TopLeft,
+  Top,
+  TopRight,
+  Left,
+  Center,
+  Right,
+  BottomLeft,
+  Bottom,
+  BottomRight,
+}
+
+impl Default for MonitorPlacement {
+  fn default() -> Self {
+    MonitorPlacement::Center
+  }
+}
+
+impl MonitorPlacement {
+  /// Returns the offset of a window of `window_size` from the top left corner of a work area
+  /// of `area_size`. Windows larger than the work area stick to its top left corner.
+  #[cfg_attr(any(target_os = "ios", target_os = "android"), allow(dead_code))]
+  pub(crate) fn offset(self, area_size: (f64, f64), window_size: (f64, f64)) -> (f64, f64) {
+    use MonitorPlacement::*;
+    let (horizontal, vertical) = match self {
+      TopLeft => (0., 0.),
+      Top => (0.5, 0.),
+      TopRight => (1., 0.),
+      Left => (0., 0.5),
+      Center => (0.5, 0.5),
+      Right => (1., 0.5),
+      BottomLeft => (0., 1.),
+      Bottom => (0.5, 1.),
+      BottomRight => (1., 1.),
+    };
+    (
+      ((area_size.0 - window_size.0) * horizontal).max(0.),
+      ((area_size.1 - window_size.1) * vertical).max(0.),
+    )
+  }
+}
+
+/// The geometry and state of a window, as returned by [`Window::outer_state`] and restored by
+/// [`WindowBuilder::with_state`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WindowState {
+  /// The outer position of the window, see [`Window::outer_position`].
+  pub position: PhysicalPosition<i32>,
+  /// The inner size of the window, see [`Window::inner_size`].
+  pub size: PhysicalSize<u32>,
+  /// The scale factor the position and size were saved with.
+  pub scale_factor: f64,
+  /// Whether the window is maximized.
+  pub maximized: bool,
+  /// Whether the window is fullscreen. It is restored as borderless fullscreen.
+  pub fullscreen: bool,
+  /// The name of the monitor the window is on, see [`MonitorHandle::name`].
+  pub monitor: Option<String>,
+}
+
+impl WindowState {
+  /// Resolves the state against the current monitors into `attributes`.
+  fn apply<T: 'static>(self, attributes: &mut WindowAttributes, target: &EventLoopWindowTarget<T>) {
+    let contains_position = |monitor: &MonitorHandle| {
+      let origin = monitor.position();
+      let size = monitor.size();
+      self.position.x >= origin.x
+        && self.position.y >= origin.y
+        && (self.position.x - origin.x) < size.width as i32
+        && (self.position.y - origin.y) < size.height as i32
+    };
+
+    let monitors: Vec<_> = target.available_monitors().collect();
+    let named = self.monitor.as_ref().and_then(|name| {
+      let mut named = monitors
+        .iter()
+        .filter(|monitor| monitor.name().as_ref() == Some(name));
+      let first = named.next().cloned();
+      named
+        .find(|monitor| contains_position(monitor))
+        .cloned()
+        .or(first)
+    });
+    let monitor = named
+      .or_else(|| {
+        monitors
+          .iter()
+          .find(|monitor| contains_position(monitor))
+          .cloned()
+      })
+      .or_else(|| target.primary_monitor());
+
+    let mut size = self.size;
+    if let Some(monitor) = &monitor {
+      let scale_factor = monitor.scale_factor();
+      if self.scale_factor > 0. && (self.scale_factor - scale_factor).abs() > f64::EPSILON {
+        size = self
+          .size
+          .to_logical::<f64>(self.scale_factor)
+          .to_physical(scale_factor);
+      }
+      let bounds = monitor.size();
+      size.width = size.width.min(bounds.width);
+      size.height = size.height.min(bounds.height);
+    }
+    attributes.inner_size = Some(size.into());
+
+    match &monitor {
+      Some(monitor) if contains_position(monitor) => {
+        attributes.position = Some(self.position.into());
+        attributes.position_on_monitor = None;
+      }
+      Some(monitor) => {
+        attributes.position = None;
+        attributes.position_on_monitor = Some((monitor.clone(), MonitorPlacement::Center));
+      }
+      None => {
+        attributes.position = Some(self.position.into());
+        attributes.position_on_monitor = None;
+      }
+    }
+
+    attributes.maximized = self.maximized;
+    attributes.fullscreen = if self.fullscreen {
+      Some(Fullscreen::Borderless(monitor))
+    } else {
+      None
+    };
+  }
+}
+
+/// A color as `(red, green, blue, alpha)`.
+pub type RGBA = (u8, u8, u8, u8);
+
 #[non_exhaustive]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Theme {
@@ -1149,10 +2592,13 @@ pub enum UserAttentionType {
   /// ## Platform-specific
   /// - **macOS:** Bounces the dock icon until the application is in focus.
   /// - **Windows:** Flashes both the window and the taskbar button until the application is in focus.
+  /// - **Linux:** Sets the urgency hint of the window and rings the bell, which desktops can
+  ///   show by flashing the window.
   Critical,
   /// ## Platform-specific
   /// - **macOS:** Bounces the dock icon once.
   /// - **Windows:** Flashes the taskbar button until the application is in focus.
+  /// - **Linux:** Sets the urgency hint of the window, usually shown on its taskbar button.
   Informational,
 }
 
@@ -1162,6 +2608,54 @@ impl Default for UserAttentionType {
   }
 }
 
+/// A frosted glass effect drawn behind the transparent parts of a window, see
+/// [`Window::set_blur`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlurEffect {
+  /// Blurs what is behind the window.
+  Blur,
+  /// A blur with a tint and a subtle noise, like the one of menus and flyouts.
+  Acrylic,
+  /// A material tinted by the desktop wallpaper, like the one of main app windows.
+  Mica,
+}
+
+/// The state of a progress bar, see [`Window::set_progress_bar`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressState {
+  /// Hides the progress bar.
+  None,
+  Normal,
+  /// Shows a progress bar without a value, for when the progress can't be measured.
+  Indeterminate,
+  Paused,
+  Error,
+}
+
+/// Changes to the progress bar of a window, see [`Window::set_progress_bar`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgressBarState {
+  /// The state of the progress bar, or `None` to keep the current one.
+  pub state: Option<ProgressState>,
+  /// The progress from 0 to 100, or `None` to keep the current one.
+  pub progress: Option<u64>,
+  /// The name of the `.desktop` file of the application, which the Linux launcher API identifies
+  /// applications by, e.g. `my-app.desktop`. Defaults to the executable name followed by
+  /// `.desktop`.
+  pub desktop_filename: Option<String>,
+}
+
+/// The item dragged out of a window, see [`Window::start_drag`].
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DragData {
+  /// Absolute paths of files or directories.
+  Files(Vec<PathBuf>),
+  Text(String),
+}
+
 /// A constant used to determine how much inside the window, the resize handler should appear (only used in Linux(gtk) and Windows).
 /// You probably need to scale it by the scale_factor of the window.
 pub const BORDERLESS_RESIZE_INSET: i32 = 5;